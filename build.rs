@@ -443,6 +443,11 @@ fn parse_param(param_str: &str) -> Option<ParamInfo> {
         return None;
     }
 
+    // Skip the plugin token, which the plugin's editor object passes itself
+    if param_str.contains("token: String") {
+        return None;
+    }
+
     // Check for #[string] or #[serde] attribute
     let is_string = param_str.contains("#[string]");
     let is_serde = param_str.contains("#[serde]");
@@ -739,6 +744,7 @@ interface EditorAPI {
    *
    * Returns a ProcessHandle that can be awaited for the result or killed early.
    * The handle is also a PromiseLike, so `await spawnProcess(...)` works directly.
+   * The command must be declared in the plugin's manifest "process" list.
    * @param command - Program name (searched in PATH) or absolute path
    * @param args - Command arguments (each array element is one argument)
    * @param cwd - Working directory; null uses editor's cwd
@@ -1105,7 +1111,7 @@ fn categorize_op(js_name: &str, is_async: bool) -> &'static str {
     }
 
     // Status ops
-    if js_name == "setStatus" || js_name == "debug" {
+    if js_name == "setStatus" || js_name == "setStatusSegment" || js_name == "debug" {
         return "status";
     }
 
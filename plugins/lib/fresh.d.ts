@@ -395,6 +395,16 @@ interface EditorAPI {
    * @param message - Text to display; keep short (status bar has limited width)
    */
   setStatus(message: string): void;
+  /**
+   * Set the text of a named segment of the status bar
+   *
+   * Unlike the status message, a segment stays until the plugin changes it.
+   * Segments are shown where the `status_bar` config places them by name,
+   * or else by its "plugins" segment. Empty text removes the segment.
+   * @param name - Name of the segment, as used in the `status_bar` config
+   * @param text - Text to display; keep short (status bar has limited width)
+   */
+  setStatusSegment(name: string, text: string): void;
   /**
    * Log a debug message from a plugin
    *
@@ -717,9 +727,10 @@ interface EditorAPI {
    */
   unregisterCommand(name: string): boolean;
   /**
-   * Set or unset a custom context for command visibility
+   * Set or unset a custom context for command visibility and keybindings
    * Custom contexts allow plugins to control when their commands are available.
    * For example, setting "config-editor" context makes config editor commands visible.
+   * Keybinding `when` clauses can check the context by name (e.g. "config-editor && !readOnly").
    * @param name - Context name (e.g., "config-editor")
    * @param active - Whether the context is active (true = set, false = unset)
    * @returns true if the context was updated
@@ -727,7 +738,10 @@ interface EditorAPI {
   setContext(name: string, active: boolean): boolean;
   /**
    * Open a file in the editor, optionally at a specific location
-   * @param path - File path to open
+   *
+   * The path must be inside a directory of the plugin's manifest "fs_read"
+   * list. The first call asks the user and throws; once allowed, calls succeed.
+   * @param path - File path to open (absolute or relative to cwd)
    * @param line - Line number to jump to (0 for no jump)
    * @param column - Column number to jump to (0 for no jump)
    * @returns true if file was opened
@@ -735,8 +749,10 @@ interface EditorAPI {
   openFile(path: string, line: number, column: number): boolean;
   /**
    * Open a file in a specific split pane
+   *
+   * Needs "fs_read" for the path, like openFile.
    * @param split_id - The split ID to open the file in
-   * @param path - File path to open
+   * @param path - File path to open (absolute or relative to cwd)
    * @param line - Line number to jump to (0 for no jump)
    * @param column - Column number to jump to (0 for no jump)
    * @returns true if file was opened
@@ -749,6 +765,7 @@ interface EditorAPI {
    * in the background and returns immediately with a process ID.
    * Use killProcess(id) to terminate the process later.
    * Use isProcessRunning(id) to check if it's still running.
+   * The command must be declared in the plugin's manifest "process" list.
    *
    * @param command - Program name (searched in PATH) or absolute path
    * @param args - Command arguments (each array element is one argument)
@@ -862,6 +879,10 @@ interface EditorAPI {
    * 3. Gets new cursor position
    * 4. Deletes from old to new position
    *
+   * Saving ("save", "save_all", "save_as_root") a file the plugin opened or
+   * edited needs "fs_write" for it, and files in the config directory can't
+   * be saved at all.
+   *
    * @param action_name - Action name (e.g., "move_word_right", "move_line_end")
    * @returns true if action was sent successfully
    */
@@ -871,6 +892,7 @@ interface EditorAPI {
    *
    * Used by vi mode for count prefix (e.g., "3dw" = delete 3 words).
    * All actions execute atomically with no plugin roundtrips between them.
+   * Save-type actions are limited like in executeAction.
    *
    * @param actions - Array of {action: string, count?: number} objects
    * @returns true if actions were sent successfully
@@ -923,6 +945,7 @@ interface EditorAPI {
    *
    * Returns a ProcessHandle that can be awaited for the result or killed early.
    * The handle is also a PromiseLike, so `await spawnProcess(...)` works directly.
+   * The command must be declared in the plugin's manifest "process" list.
    * @param command - Program name (searched in PATH) or absolute path
    * @param args - Command arguments (each array element is one argument)
    * @param cwd - Working directory; null uses editor's cwd
@@ -1033,6 +1056,7 @@ interface EditorAPI {
    *
    * Throws if file doesn't exist, isn't readable, or isn't valid UTF-8.
    * For binary files, this will fail. For large files, consider memory usage.
+   * The path must be inside a directory of the plugin's manifest "fs_read" list.
    * @param path - File path (absolute or relative to cwd)
    */
   readFile(path: string): Promise<string>;
@@ -1041,6 +1065,7 @@ interface EditorAPI {
    *
    * Creates parent directories if they don't exist (behavior may vary).
    * Replaces file contents entirely; use readFile + modify + writeFile for edits.
+   * The path must be inside a directory of the plugin's manifest "fs_write" list.
    * @param path - Destination path (absolute or relative to cwd)
    * @param content - UTF-8 string to write
    */
@@ -1049,7 +1074,7 @@ interface EditorAPI {
    * Check if a path exists (file, directory, or symlink)
    *
    * Does not follow symlinks; returns true for broken symlinks.
-   * Use fileStat for more detailed information.
+   * Use fileStat for more detailed information. Needs no capability.
    * @param path - Path to check (absolute or relative to cwd)
    */
   fileExists(path: string): boolean;
@@ -1058,6 +1083,7 @@ interface EditorAPI {
    *
    * Follows symlinks. Returns exists=false for non-existent paths
    * rather than throwing. Size is in bytes; directories may report 0.
+   * Needs no capability.
    * @param path - Path to stat (absolute or relative to cwd)
    */
   fileStat(path: string): FileStat;
@@ -1067,6 +1093,8 @@ interface EditorAPI {
    * Returns unsorted entries with type info. Entry names are relative
    * to the directory (use pathJoin to construct full paths).
    * Throws on permission errors or if path is not a directory.
+   * The path must be inside a directory of the plugin's manifest "fs_read"
+   * list. The first call asks the user and throws; once allowed, calls succeed.
    * @param path - Directory path (absolute or relative to cwd)
    * @example
    * const entries = editor.readDir("/home/user");
@@ -1079,8 +1107,11 @@ interface EditorAPI {
   // === Environment Operations ===
   /**
    * Get an environment variable
+   *
+   * The name must be declared in the plugin's manifest "env" list. The first
+   * call asks the user and returns null; once allowed, calls return the value.
    * @param name - Name of environment variable
-   * @returns Value if set, null if not set
+   * @returns Value if set and allowed, null otherwise
    */
   getEnv(name: string): string;
   /**
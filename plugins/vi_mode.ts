@@ -1936,8 +1936,13 @@ globalThis.vi_command_handler = async function (args: { prompt_type: string; inp
     return true; // Empty command, just dismiss
   }
 
-  // Parse the command
-  const result = await executeViCommand(input);
+  // Parse the command (opening or saving a file throws if it isn't allowed)
+  let result: CommandResult;
+  try {
+    result = await executeViCommand(input);
+  } catch (e) {
+    result = { error: String(e) };
+  }
 
   if (result.error) {
     editor.setStatus(`E: ${result.error}`);
@@ -0,0 +1,31 @@
+; Symbol definitions for the outline (see src/primitives/outline.rs)
+
+(function_definition
+  declarator: (function_declarator
+    declarator: (identifier) @name)) @definition.function
+
+(function_definition
+  declarator: (pointer_declarator
+    declarator: (function_declarator
+      declarator: (identifier) @name))) @definition.function
+
+(struct_specifier
+  name: (type_identifier) @name
+  body: (_)) @definition.struct
+
+(union_specifier
+  name: (type_identifier) @name
+  body: (_)) @definition.struct
+
+(enum_specifier
+  name: (type_identifier) @name
+  body: (_)) @definition.enum
+
+(type_definition
+  declarator: (type_identifier) @name) @definition.type
+
+(preproc_function_def
+  name: (identifier) @name) @definition.macro
+
+(preproc_def
+  name: (identifier) @name) @definition.constant
@@ -0,0 +1,43 @@
+; Symbol definitions for the outline (see src/primitives/outline.rs)
+
+(namespace_definition
+  name: (_) @name) @definition.module
+
+(class_specifier
+  name: (_) @name
+  body: (_)) @definition.class
+
+(struct_specifier
+  name: (_) @name
+  body: (_)) @definition.struct
+
+(union_specifier
+  name: (_) @name
+  body: (_)) @definition.struct
+
+(enum_specifier
+  name: (_) @name
+  body: (_)) @definition.enum
+
+(function_definition
+  declarator: (function_declarator
+    declarator: (_) @name)) @definition.function
+
+(function_definition
+  declarator: (pointer_declarator
+    declarator: (function_declarator
+      declarator: (_) @name))) @definition.function
+
+(function_definition
+  declarator: (reference_declarator
+    (function_declarator
+      declarator: (_) @name))) @definition.function
+
+(type_definition
+  declarator: (type_identifier) @name) @definition.type
+
+(alias_declaration
+  name: (type_identifier) @name) @definition.type
+
+(preproc_function_def
+  name: (identifier) @name) @definition.macro
@@ -0,0 +1,28 @@
+; Symbol definitions for the outline (see src/primitives/outline.rs)
+
+(namespace_declaration
+  name: (_) @name) @definition.module
+
+(class_declaration
+  name: (identifier) @name) @definition.class
+
+(record_declaration
+  name: (identifier) @name) @definition.class
+
+(struct_declaration
+  name: (identifier) @name) @definition.struct
+
+(interface_declaration
+  name: (identifier) @name) @definition.interface
+
+(enum_declaration
+  name: (identifier) @name) @definition.enum
+
+(constructor_declaration
+  name: (identifier) @name) @definition.constructor
+
+(method_declaration
+  name: (identifier) @name) @definition.method
+
+(property_declaration
+  name: (identifier) @name) @definition.property
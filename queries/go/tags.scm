@@ -0,0 +1,25 @@
+; Symbol definitions for the outline (see src/primitives/outline.rs)
+; When several patterns match the same node, the first one wins.
+
+(type_spec
+  name: (type_identifier) @name
+  type: (struct_type)) @definition.struct
+
+(type_spec
+  name: (type_identifier) @name
+  type: (interface_type)) @definition.interface
+
+(type_spec
+  name: (type_identifier) @name) @definition.type
+
+(field_declaration
+  name: (field_identifier) @name) @definition.field
+
+(function_declaration
+  name: (identifier) @name) @definition.function
+
+(method_declaration
+  name: (field_identifier) @name) @definition.method
+
+(const_spec
+  name: (identifier) @name) @definition.constant
@@ -0,0 +1,26 @@
+; Symbol definitions for the outline (see src/primitives/outline.rs)
+
+(class_declaration
+  name: (identifier) @name) @definition.class
+
+(record_declaration
+  name: (identifier) @name) @definition.class
+
+(interface_declaration
+  name: (identifier) @name) @definition.interface
+
+(enum_declaration
+  name: (identifier) @name) @definition.enum
+
+(enum_constant
+  name: (identifier) @name) @definition.variant
+
+(constructor_declaration
+  name: (identifier) @name) @definition.constructor
+
+(method_declaration
+  name: (identifier) @name) @definition.method
+
+(field_declaration
+  declarator: (variable_declarator
+    name: (identifier) @name)) @definition.field
@@ -0,0 +1,24 @@
+; Symbol definitions for the outline (see src/primitives/outline.rs)
+
+(class_declaration
+  name: (identifier) @name) @definition.class
+
+(method_definition
+  name: (_) @name) @definition.method
+
+(function_declaration
+  name: (identifier) @name) @definition.function
+
+(generator_function_declaration
+  name: (identifier) @name) @definition.function
+
+; const f = () => {} / const f = function () {}
+(lexical_declaration
+  (variable_declarator
+    name: (identifier) @name
+    value: [(arrow_function) (function_expression)]) @definition.function)
+
+(variable_declaration
+  (variable_declarator
+    name: (identifier) @name
+    value: [(arrow_function) (function_expression)]) @definition.function)
@@ -0,0 +1,4 @@
+; Symbol definitions for the outline (see src/primitives/outline.rs)
+
+(function_declaration
+  name: (_) @name) @definition.function
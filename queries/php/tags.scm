@@ -0,0 +1,22 @@
+; Symbol definitions for the outline (see src/primitives/outline.rs)
+
+(namespace_definition
+  name: (namespace_name) @name) @definition.module
+
+(class_declaration
+  name: (name) @name) @definition.class
+
+(interface_declaration
+  name: (name) @name) @definition.interface
+
+(trait_declaration
+  name: (name) @name) @definition.interface
+
+(enum_declaration
+  name: (name) @name) @definition.enum
+
+(function_definition
+  name: (name) @name) @definition.function
+
+(method_declaration
+  name: (name) @name) @definition.method
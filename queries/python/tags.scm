@@ -0,0 +1,13 @@
+; Symbol definitions for the outline (see src/primitives/outline.rs)
+
+(class_definition
+  name: (identifier) @name) @definition.class
+
+(function_definition
+  name: (identifier) @name) @definition.function
+
+; Module-level assignments
+(module
+  (expression_statement
+    (assignment
+      left: (identifier) @name) @definition.constant))
@@ -0,0 +1,13 @@
+; Symbol definitions for the outline (see src/primitives/outline.rs)
+
+(module
+  name: (_) @name) @definition.module
+
+(class
+  name: (_) @name) @definition.class
+
+(method
+  name: (_) @name) @definition.method
+
+(singleton_method
+  name: (_) @name) @definition.method
@@ -0,0 +1,44 @@
+; Symbol definitions for the outline (see src/primitives/outline.rs)
+
+(mod_item
+  name: (identifier) @name) @definition.module
+
+(struct_item
+  name: (type_identifier) @name) @definition.struct
+
+(union_item
+  name: (type_identifier) @name) @definition.struct
+
+(enum_item
+  name: (type_identifier) @name) @definition.enum
+
+(enum_variant
+  name: (identifier) @name) @definition.variant
+
+(field_declaration
+  name: (field_identifier) @name) @definition.field
+
+(trait_item
+  name: (type_identifier) @name) @definition.interface
+
+; Impl blocks are named after their type ("impl Foo")
+(impl_item
+  type: (_) @name) @definition.impl
+
+(type_item
+  name: (type_identifier) @name) @definition.type
+
+(function_item
+  name: (identifier) @name) @definition.function
+
+(function_signature_item
+  name: (identifier) @name) @definition.function
+
+(const_item
+  name: (identifier) @name) @definition.constant
+
+(static_item
+  name: (identifier) @name) @definition.constant
+
+(macro_definition
+  name: (identifier) @name) @definition.macro
@@ -333,6 +333,11 @@ impl Editor {
         // Get old status for event
         let old_status = self.lsp_server_statuses.get(&language).cloned();
 
+        // A (re)starting server registers its file watchers again
+        if status == LspServerStatus::Starting {
+            self.clear_lsp_file_watchers(&language);
+        }
+
         // Update server status
         self.lsp_server_statuses
             .insert(language.clone(), status.clone());
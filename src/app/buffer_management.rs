@@ -13,6 +13,7 @@ use std::io;
 use std::path::Path;
 
 use crate::app::warning_domains::WarningDomain;
+use crate::model::encoding::Encoding;
 use crate::model::event::{BufferId, Event, SplitId};
 use crate::services::lsp::manager::detect_language;
 use crate::state::EditorState;
@@ -88,16 +89,22 @@ impl Editor {
             path.to_path_buf()
         };
 
+        // Files are read and saved through the filesystem backend
+        let fs = self.buffer_fs();
+        let is_remote = fs.as_ref().is_some_and(|fs| !fs.is_local());
+        let canonicalize = |path: &Path| match &fs {
+            Some(fs) => fs.canonicalize(path),
+            None => path.canonicalize(),
+        };
+
         // Determine if we're opening a non-existent file (for creating new files)
-        let file_exists = resolved_path.exists();
+        let file_exists = self.path_exists(&resolved_path);
 
         // Canonicalize the path to resolve symlinks and normalize path components
         // This ensures consistent path representation throughout the editor
         // For non-existent files, we need to canonicalize the parent directory and append the filename
         let canonical_path = if file_exists {
-            resolved_path
-                .canonicalize()
-                .unwrap_or_else(|_| resolved_path.clone())
+            canonicalize(&resolved_path).unwrap_or_else(|_| resolved_path.clone())
         } else {
             // For non-existent files, canonicalize parent dir and append filename
             if let Some(parent) = resolved_path.parent() {
@@ -105,9 +112,7 @@ impl Editor {
                     // No parent means just a filename, use working dir
                     self.working_dir.clone()
                 } else {
-                    parent
-                        .canonicalize()
-                        .unwrap_or_else(|_| parent.to_path_buf())
+                    canonicalize(parent).unwrap_or_else(|_| parent.to_path_buf())
                 };
                 if let Some(filename) = resolved_path.file_name() {
                     canonical_parent.join(filename)
@@ -152,15 +157,33 @@ impl Editor {
         };
 
         // Create the editor state - either load from file or create empty buffer
+        let mut chosen_encoding = None;
         let mut state = if file_exists {
-            EditorState::from_file_with_languages(
-                path,
-                self.terminal_width,
-                self.terminal_height,
-                self.config.editor.large_file_threshold_bytes as usize,
-                &self.grammar_registry,
-                &self.config.languages,
-            )?
+            let load = |encoding| {
+                EditorState::from_file_with_encoding(
+                    path,
+                    self.terminal_width,
+                    self.terminal_height,
+                    self.config.editor.large_file_threshold_bytes as usize,
+                    &self.grammar_registry,
+                    &self.config.languages,
+                    encoding,
+                    fs.clone(),
+                )
+            };
+            // An encoding picked for this file before is used again, if the file is valid in it
+            // (per-file sessions are kept for local files only)
+            let remembered = crate::session::PersistedFileSession::load(path)
+                .filter(|_| !is_remote)
+                .and_then(|file_state| file_state.encoding)
+                .and_then(|name| Encoding::from_name(&name));
+            match remembered.and_then(|encoding| load(Some(encoding)).ok()) {
+                Some(state) => {
+                    chosen_encoding = remembered;
+                    state
+                }
+                None => load(None)?,
+            }
         } else {
             // File doesn't exist - create empty buffer with the file path set
             let mut new_state = EditorState::new(
@@ -170,6 +193,9 @@ impl Editor {
             );
             // Set the file path so saving will create the file
             new_state.buffer.set_file_path(path.to_path_buf());
+            if let Some(fs) = fs.clone() {
+                new_state.buffer.set_fs(fs);
+            }
             new_state
         };
         // Note: line_wrap_enabled is set on SplitViewState.viewport when the split is created
@@ -180,6 +206,9 @@ impl Editor {
             // Make binary buffers read-only
             state.editing_disabled = true;
             tracing::info!("Detected binary file: {}", path.display());
+            if self.config.editor.hex_view_binary_files {
+                state.show_hex_view();
+            }
         }
 
         // Set show_whitespace_tabs, use_tabs, and tab_size based on language config
@@ -202,13 +231,20 @@ impl Editor {
             .margins
             .set_line_numbers(self.config.editor.line_numbers);
 
+        // Edits from before the file was last closed can still be undone
+        let event_log = if file_exists && !is_remote && !state.buffer.is_large_file() {
+            self.load_undo_history(path)
+        } else {
+            None
+        };
         self.buffers.insert(buffer_id, state);
         self.event_logs
-            .insert(buffer_id, crate::model::event::EventLog::new());
+            .insert(buffer_id, event_log.unwrap_or_default());
 
         // Create metadata for this buffer
         let mut metadata =
             super::types::BufferMetadata::with_file(path.to_path_buf(), &self.working_dir);
+        metadata.chosen_encoding = chosen_encoding;
 
         // Mark binary files in metadata and disable LSP
         if is_binary {
@@ -217,8 +253,13 @@ impl Editor {
             metadata.disable_lsp(t!("buffer.binary_file").to_string());
         }
 
-        // Notify LSP about the newly opened file (skip for binary files)
-        if !is_binary {
+        // Language servers run locally and can't read remote files
+        if is_remote {
+            metadata.disable_lsp(t!("buffer.remote_file").to_string());
+        }
+
+        // Notify LSP about the newly opened file (skip for binary and remote files)
+        if !is_binary && !is_remote {
             self.notify_lsp_file_opened(path, buffer_id, &mut metadata);
         }
 
@@ -235,7 +276,9 @@ impl Editor {
 
         // Restore global file state (scroll/cursor position) if available
         // This persists file positions across projects and editor instances
-        self.restore_global_file_state(buffer_id, path, active_split);
+        if !is_remote {
+            self.restore_global_file_state(buffer_id, path, active_split);
+        }
 
         // Emit control event
         self.emit_event(
@@ -247,7 +290,9 @@ impl Editor {
         );
 
         // Track file for auto-revert and conflict detection
-        self.watch_file(path);
+        if !is_remote {
+            self.watch_file(path);
+        }
 
         // Fire AfterFileOpen hook for plugins
         self.plugin_manager.run_hook(
@@ -286,6 +331,7 @@ impl Editor {
             editor_state.cursors.primary_mut().position = cursor_pos;
             editor_state.cursors.primary_mut().anchor =
                 file_state.cursor.anchor.map(|a| a.min(max_pos));
+            super::session::restore_folds(editor_state, &file_state.folds);
         }
 
         // Apply viewport (scroll) state to SplitViewState
@@ -309,6 +355,14 @@ impl Editor {
             },
             None => return,
         };
+        // Per-file sessions are kept for local files only
+        if self
+            .buffers
+            .get(&buffer_id)
+            .is_some_and(|state| state.buffer.is_remote())
+        {
+            return;
+        }
 
         // Find a split that has this buffer open to get the view state
         let view_state = self
@@ -344,6 +398,16 @@ impl Editor {
                 top_view_line_offset: view_state.viewport.top_view_line_offset,
                 left_column: view_state.viewport.left_column,
             },
+            folds: self
+                .buffers
+                .get(&buffer_id)
+                .map(super::session::serialize_folds)
+                .unwrap_or_default(),
+            encoding: self
+                .buffer_metadata
+                .get(&buffer_id)
+                .and_then(|metadata| metadata.chosen_encoding)
+                .map(|encoding| encoding.display_name().to_string()),
         };
 
         // Save to disk
@@ -411,6 +475,7 @@ impl Editor {
             };
 
             if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.folds.unfold_at(&mut state.marker_list, position);
                 state.apply(&event);
             }
         }
@@ -982,6 +1047,7 @@ impl Editor {
     fn close_buffer_internal(&mut self, id: BufferId) -> io::Result<()> {
         // Save file state before closing (for per-file session persistence)
         self.save_file_state_on_close(id);
+        self.save_undo_history(id);
 
         // If closing a terminal buffer, clean up terminal-related data structures
         if let Some(terminal_id) = self.terminal_buffers.remove(&id) {
@@ -1048,11 +1114,16 @@ impl Editor {
         self.event_logs.remove(&id);
         self.seen_byte_ranges.remove(&id);
         self.buffer_metadata.remove(&id);
+        self.semantic_tokens.remove(&id);
+        self.code_lenses.remove(id);
 
         // Remove buffer from panel_ids mapping if it was a panel buffer
         // This prevents stale entries when the same panel_id is reused later
         self.panel_ids.retain(|_, &mut buf_id| buf_id != id);
 
+        // Forget the macro the buffer was editing
+        self.macro_edits.remove(&id);
+
         // Remove buffer from all splits' open_buffers lists
         for view_state in self.split_view_states.values_mut() {
             view_state.remove_buffer(id);
@@ -1063,6 +1134,16 @@ impl Editor {
             self.focus_file_explorer();
         }
 
+        // Close the diff view with its buffers, or the diff views showing it
+        self.close_diff_views_of(id);
+
+        // Likewise for merge views
+        self.close_merge_views_of(id);
+
+        // Answer `fresh --remote --wait` clients this buffer was the last one of
+        #[cfg(unix)]
+        self.release_waiting_clients(id);
+
         Ok(())
     }
 
@@ -408,6 +408,12 @@ impl Editor {
             crate::model::buffer::LineEnding::CR => normalized.replace('\n', "\r"),
         };
 
+        self.replace_selections(&paste_text, "Paste");
+        self.status_message = Some(t!("clipboard.pasted").to_string());
+    }
+
+    /// Insert `text` at every cursor, replacing the selections, as one undo step
+    pub(super) fn replace_selections(&mut self, text: &str, description: &str) {
         let mut events = Vec::new();
 
         // Collect cursor info sorted in reverse order by position
@@ -442,32 +448,31 @@ impl Editor {
 
         // Build events for each cursor
         for (cursor_id, selection, insert_position, deleted_text) in cursor_data_with_text {
-            if let (Some(range), Some(text)) = (selection, deleted_text) {
+            if let (Some(range), Some(deleted_text)) = (selection, deleted_text) {
                 events.push(Event::Delete {
                     range,
-                    deleted_text: text,
+                    deleted_text,
                     cursor_id,
                 });
             }
             events.push(Event::Insert {
                 position: insert_position,
-                text: paste_text.clone(),
+                text: text.to_string(),
                 cursor_id,
             });
         }
 
         // Apply events with atomic undo using bulk edit for O(n) performance
         if events.len() > 1 {
-            // Use optimized bulk edit for multi-cursor paste
-            if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, "Paste".to_string()) {
+            // Use optimized bulk edit for multiple cursors
+            if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, description.to_string())
+            {
                 self.active_event_log_mut().append(bulk_edit);
             }
         } else if let Some(event) = events.into_iter().next() {
             self.active_event_log_mut().append(event.clone());
             self.apply_event_to_active_buffer(&event);
         }
-
-        self.status_message = Some(t!("clipboard.pasted").to_string());
     }
 
     /// Set clipboard content for testing purposes
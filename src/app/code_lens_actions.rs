@@ -0,0 +1,578 @@
+//! Code lenses for the Editor.
+//!
+//! The active buffer's code lenses are requested from its language server
+//! when it is first shown and again once edits pause for a moment. Servers
+//! that resolve lenses lazily send them without commands; those are resolved
+//! (`codeLens/resolve`) once they scroll into view. The titles of each line's
+//! lenses are shown on a virtual line above it. Clicking a title, or "Run Code
+//! Lens" on the line, runs its command on the server
+//! (`workspace/executeCommand`).
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use lsp_types::CodeLens;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use rust_i18n::t;
+
+use crate::app::types::ViewLineMapping;
+use crate::input::commands::Suggestion;
+use crate::model::event::BufferId;
+use crate::model::marker::MarkerId;
+use crate::model::piece_tree::PieceTreeNode;
+use crate::services::lsp::manager::detect_language;
+use crate::view::prompt::{Prompt, PromptType};
+use crate::view::virtual_text::{VirtualTextNamespace, VirtualTextPosition};
+
+use super::Editor;
+
+/// How long edits must pause before lenses are requested again
+const CODE_LENS_REFRESH_DELAY: Duration = Duration::from_millis(500);
+
+/// Between the titles of the lenses of one line
+const CODE_LENS_SEPARATOR: &str = " | ";
+
+/// Namespace of the code lens virtual lines
+fn code_lens_namespace() -> VirtualTextNamespace {
+    VirtualTextNamespace::from_string("lsp-code-lens".to_string())
+}
+
+/// Code lenses of every buffer and the command they are running
+#[derive(Debug, Default)]
+pub(super) struct CodeLenses {
+    buffers: HashMap<BufferId, CodeLensState>,
+    /// In-flight workspace/executeCommand request and the lens title
+    running: Option<(u64, String)>,
+}
+
+/// Code lens requests and shown lenses of one buffer
+#[derive(Debug, Default)]
+struct CodeLensState {
+    /// Content the shown lenses (or the pending request) were requested for
+    requested: Option<Arc<PieceTreeNode>>,
+    /// Content last seen by the poller and when it was first seen
+    seen: Option<(Arc<PieceTreeNode>, Instant)>,
+    /// In-flight request ID
+    pending: Option<u64>,
+    /// In-flight resolve request and the indices of the lenses it resolves
+    resolving: Option<(u64, Vec<usize>)>,
+    lenses: Vec<ShownLens>,
+    /// Virtual lines showing the lenses
+    rows: Vec<LensRow>,
+}
+
+/// A lens and the marker following the start of its range
+#[derive(Debug)]
+struct ShownLens {
+    lens: CodeLens,
+    marker: MarkerId,
+    resolve_requested: bool,
+}
+
+/// A virtual line and the columns of each lens title on it
+#[derive(Debug)]
+struct LensRow {
+    /// Marker of the virtual text
+    marker: MarkerId,
+    /// Columns of a title and the index of its lens
+    titles: Vec<(Range<usize>, usize)>,
+}
+
+impl CodeLenses {
+    /// Forget the lenses of a closed buffer
+    pub(super) fn remove(&mut self, buffer_id: BufferId) {
+        self.buffers.remove(&buffer_id);
+    }
+}
+
+impl Editor {
+    /// Request or resolve the code lenses of the active buffer when needed
+    ///
+    /// Called from the main loop; lenses are shown when the response arrives.
+    pub(super) fn poll_code_lenses(&mut self) {
+        if !self.config.editor.enable_code_lens {
+            return;
+        }
+
+        let buffer_id = self.active_buffer();
+        let viewport = self.active_viewport();
+        let (top_byte, height) = (viewport.top_byte, viewport.visible_line_count());
+        let Some(state) = self.buffers.get(&buffer_id) else {
+            return;
+        };
+        let content = state.buffer.content_root();
+        let top_line = state.buffer.get_line_number(top_byte);
+        let now = self.time_source.now();
+
+        let lenses = self.code_lenses.buffers.entry(buffer_id).or_default();
+        if lenses.pending.is_some() {
+            return;
+        }
+        if !lenses
+            .seen
+            .as_ref()
+            .is_some_and(|(seen, _)| Arc::ptr_eq(seen, &content))
+        {
+            lenses.seen = Some((content.clone(), now));
+        }
+
+        let changed = !lenses
+            .requested
+            .as_ref()
+            .is_some_and(|requested| Arc::ptr_eq(requested, &content));
+        if !changed {
+            if lenses.resolving.is_none() {
+                self.resolve_code_lenses(buffer_id, top_line..top_line + height);
+            }
+            return;
+        }
+        // Newly shown buffers get their lenses right away, edits are debounced
+        if lenses.requested.is_some() {
+            let seen_at = lenses.seen.as_ref().map_or(now, |(_, at)| *at);
+            if self.time_source.elapsed_since(seen_at) < CODE_LENS_REFRESH_DELAY {
+                return;
+            }
+        }
+        self.request_code_lenses(buffer_id, content);
+    }
+
+    /// Request the lenses of every buffer again
+    ///
+    /// Requests sent before a server finished initializing get no lenses, and
+    /// those sent to a server that went away get no response at all.
+    pub(super) fn reset_code_lenses(&mut self) {
+        for lenses in self.code_lenses.buffers.values_mut() {
+            lenses.requested = None;
+            lenses.pending = None;
+            lenses.resolving = None;
+        }
+    }
+
+    /// Handle workspace/codeLens/refresh: request the lenses of the server's buffers again
+    pub(super) fn handle_lsp_code_lens_refresh(&mut self, language: String) {
+        for (buffer_id, lenses) in self.code_lenses.buffers.iter_mut() {
+            let buffer_language = self
+                .buffer_metadata
+                .get(buffer_id)
+                .and_then(|metadata| metadata.file_path())
+                .and_then(|path| detect_language(path, &self.config.languages));
+            if buffer_language.as_deref() == Some(language.as_str()) {
+                lenses.requested = None;
+                lenses.pending = None;
+                lenses.resolving = None;
+            }
+        }
+    }
+
+    fn request_code_lenses(&mut self, buffer_id: BufferId, content: Arc<PieceTreeNode>) {
+        let request_id = self.next_lsp_request_id;
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+                let result = handle.code_lens(request_id, uri.clone());
+                if let Err(e) = &result {
+                    tracing::debug!("Failed to request code lenses: {}", e);
+                }
+                result.is_ok()
+            })
+            .unwrap_or(false);
+
+        let lenses = self.code_lenses.buffers.entry(buffer_id).or_default();
+        lenses.requested = Some(content);
+        if sent {
+            self.next_lsp_request_id += 1;
+            lenses.pending = Some(request_id);
+        }
+    }
+
+    /// Resolve the lenses without commands on `lines`
+    fn resolve_code_lenses(&mut self, buffer_id: BufferId, lines: Range<usize>) {
+        let (Some(state), Some(lenses)) = (
+            self.buffers.get(&buffer_id),
+            self.code_lenses.buffers.get(&buffer_id),
+        ) else {
+            return;
+        };
+        let indices: Vec<usize> = lenses
+            .lenses
+            .iter()
+            .enumerate()
+            .filter(|(_, shown)| shown.lens.command.is_none() && !shown.resolve_requested)
+            .filter(|(_, shown)| {
+                state
+                    .marker_list
+                    .get_position(shown.marker)
+                    .is_some_and(|position| lines.contains(&state.buffer.get_line_number(position)))
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if indices.is_empty() {
+            return;
+        }
+        let unresolved: Vec<CodeLens> = indices
+            .iter()
+            .map(|&index| lenses.lenses[index].lens.clone())
+            .collect();
+
+        let request_id = self.next_lsp_request_id;
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+                let result = handle.resolve_code_lenses(request_id, uri.clone(), unresolved);
+                if let Err(e) = &result {
+                    tracing::debug!("Failed to resolve code lenses: {}", e);
+                }
+                result.is_ok()
+            })
+            .unwrap_or(false);
+
+        let Some(lenses) = self.code_lenses.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        for &index in &indices {
+            lenses.lenses[index].resolve_requested = true;
+        }
+        if sent {
+            self.next_lsp_request_id += 1;
+            lenses.resolving = Some((request_id, indices));
+        }
+    }
+
+    /// Handle LSP code lens response
+    pub(super) fn handle_lsp_code_lenses(
+        &mut self,
+        request_id: u64,
+        uri: String,
+        mut received: Vec<CodeLens>,
+    ) {
+        let Some((&buffer_id, lenses)) = self
+            .code_lenses
+            .buffers
+            .iter_mut()
+            .find(|(_, lenses)| lenses.pending == Some(request_id))
+        else {
+            tracing::debug!(
+                "Ignoring stale code lens response (request_id={})",
+                request_id
+            );
+            return;
+        };
+        lenses.pending = None;
+        let Some(requested) = lenses.requested.clone() else {
+            return;
+        };
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        // Ranges refer to the requested content; newer edits are requested again
+        if !Arc::ptr_eq(&state.buffer.content_root(), &requested) {
+            return;
+        }
+
+        tracing::debug!(
+            "Received {} code lenses for {} (request_id={})",
+            received.len(),
+            uri,
+            request_id
+        );
+        for shown in lenses.lenses.drain(..) {
+            state.marker_list.delete(shown.marker);
+        }
+        lenses.resolving = None;
+        received.sort_by_key(|lens| (lens.range.start.line, lens.range.start.character));
+        lenses.lenses = received
+            .into_iter()
+            .map(|lens| {
+                let position = state.buffer.lsp_position_to_byte(
+                    lens.range.start.line as usize,
+                    lens.range.start.character as usize,
+                );
+                ShownLens {
+                    marker: state.marker_list.create(position, false),
+                    lens,
+                    resolve_requested: false,
+                }
+            })
+            .collect();
+        self.show_code_lenses(buffer_id);
+    }
+
+    /// Handle LSP code lens resolve response
+    pub(super) fn handle_lsp_code_lenses_resolved(
+        &mut self,
+        request_id: u64,
+        resolved: Vec<CodeLens>,
+    ) {
+        let Some((&buffer_id, lenses)) =
+            self.code_lenses.buffers.iter_mut().find(
+                |(_, lenses)| matches!(&lenses.resolving, Some((id, _)) if *id == request_id),
+            )
+        else {
+            return;
+        };
+        let Some((_, indices)) = lenses.resolving.take() else {
+            return;
+        };
+        for (index, lens) in indices.into_iter().zip(resolved) {
+            if let Some(shown) = lenses.lenses.get_mut(index) {
+                shown.lens.command = lens.command;
+                shown.lens.data = lens.data;
+            }
+        }
+        self.show_code_lenses(buffer_id);
+    }
+
+    /// Replace the virtual lines of a buffer's lenses
+    fn show_code_lenses(&mut self, buffer_id: BufferId) {
+        let (Some(state), Some(lenses)) = (
+            self.buffers.get_mut(&buffer_id),
+            self.code_lenses.buffers.get_mut(&buffer_id),
+        ) else {
+            return;
+        };
+        let ns = code_lens_namespace();
+        state
+            .virtual_texts
+            .clear_namespace(&mut state.marker_list, &ns);
+        lenses.rows.clear();
+
+        let mut by_line: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (index, shown) in lenses.lenses.iter().enumerate() {
+            if shown.lens.command.is_none() {
+                continue;
+            }
+            if let Some(position) = state.marker_list.get_position(shown.marker) {
+                by_line
+                    .entry(state.buffer.get_line_number(position))
+                    .or_default()
+                    .push(index);
+            }
+        }
+
+        let style = Style::default().fg(self.theme.line_number_fg);
+        for (line, indices) in by_line {
+            let Some(line_start) = state.buffer.line_start_offset(line) else {
+                continue;
+            };
+            // Align the titles with the code of the line
+            let indent = state.buffer.get_line(line).map_or(0, |text| {
+                text.iter().take_while(|&&b| b == b' ' || b == b'\t').fold(
+                    0,
+                    |column, &b| match b {
+                        b'\t' => (column / state.tab_size + 1) * state.tab_size,
+                        _ => column + 1,
+                    },
+                )
+            });
+
+            let mut text = " ".repeat(indent);
+            let mut titles = Vec::new();
+            for index in indices {
+                let Some(command) = &lenses.lenses[index].lens.command else {
+                    continue;
+                };
+                if !titles.is_empty() {
+                    text.push_str(CODE_LENS_SEPARATOR);
+                }
+                let start = text.chars().count();
+                text.push_str(&command.title);
+                titles.push((start..text.chars().count(), index));
+            }
+
+            let id = state.virtual_texts.add_line(
+                &mut state.marker_list,
+                line_start,
+                text,
+                style,
+                VirtualTextPosition::LineAbove,
+                ns.clone(),
+                0,
+            );
+            if let Some(marker) = state.virtual_texts.get(id).map(|vtext| vtext.marker_id) {
+                lenses.rows.push(LensRow { marker, titles });
+            }
+        }
+    }
+
+    /// Run the code lens under a mouse click, if the click is on a code lens line
+    ///
+    /// Returns true if the click was on a code lens line.
+    pub(super) fn click_code_lens(
+        &mut self,
+        buffer_id: BufferId,
+        col: u16,
+        row: u16,
+        content_rect: Rect,
+        mappings: &Option<Vec<ViewLineMapping>>,
+    ) -> bool {
+        let (Some(state), Some(lenses), Some(mappings)) = (
+            self.buffers.get(&buffer_id),
+            self.code_lenses.buffers.get(&buffer_id),
+            mappings.as_ref(),
+        ) else {
+            return false;
+        };
+        if lenses.rows.is_empty() {
+            return false;
+        }
+
+        // Virtual lines above a line come right before its first row
+        let clicked = row.saturating_sub(content_rect.y) as usize;
+        let is_virtual =
+            |mapping: &ViewLineMapping| mapping.char_source_bytes.iter().all(Option::is_none);
+        if !mappings.get(clicked).is_some_and(is_virtual) {
+            return false;
+        }
+        let Some((source_row, source)) = mappings
+            .iter()
+            .enumerate()
+            .skip(clicked)
+            .find(|(_, mapping)| !is_virtual(mapping))
+        else {
+            return false;
+        };
+        let bytes = source.char_source_bytes.iter().flatten();
+        let (Some(&start), Some(&end)) = (bytes.clone().next(), bytes.last()) else {
+            return false;
+        };
+        let above: Vec<_> = state
+            .virtual_texts
+            .query_lines_in_range(&state.marker_list, start, end + 1)
+            .into_iter()
+            .filter(|(_, vtext)| vtext.position == VirtualTextPosition::LineAbove)
+            .collect();
+        let Some((_, vtext)) = above
+            .len()
+            .checked_sub(source_row - clicked)
+            .and_then(|index| above.get(index))
+        else {
+            return false;
+        };
+        let Some(lens_row) = lenses
+            .rows
+            .iter()
+            .find(|lens_row| lens_row.marker == vtext.marker_id)
+        else {
+            return false;
+        };
+
+        let gutter_width = state.margins.left_total_width() as u16;
+        let column = col
+            .saturating_sub(content_rect.x)
+            .saturating_sub(gutter_width) as usize;
+        let index = lens_row
+            .titles
+            .iter()
+            .find(|(columns, _)| columns.contains(&column))
+            .map(|(_, index)| *index);
+        if let Some(index) = index {
+            self.run_code_lens(buffer_id, index);
+        }
+        true
+    }
+
+    /// Run the code lens of the cursor line, asking which one if there are several
+    pub(super) fn run_code_lens_at_cursor(&mut self) {
+        let buffer_id = self.active_buffer();
+        let state = self.active_state();
+        let line = state
+            .buffer
+            .get_line_number(state.cursors.primary().position);
+        let candidates: Vec<(usize, String)> = self
+            .code_lenses
+            .buffers
+            .get(&buffer_id)
+            .map(|lenses| {
+                lenses
+                    .lenses
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, shown)| {
+                        state
+                            .marker_list
+                            .get_position(shown.marker)
+                            .is_some_and(|position| state.buffer.get_line_number(position) == line)
+                    })
+                    .filter_map(|(index, shown)| {
+                        Some((index, shown.lens.command.as_ref()?.title.clone()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        match candidates.as_slice() {
+            [] => self.set_status_message(t!("code_lens.none").to_string()),
+            [(index, _)] => self.run_code_lens(buffer_id, *index),
+            _ => {
+                let suggestions = candidates
+                    .into_iter()
+                    .map(|(index, title)| Suggestion {
+                        text: title,
+                        description: None,
+                        value: Some(index.to_string()),
+                        disabled: false,
+                        keybinding: None,
+                        source: None,
+                    })
+                    .collect();
+                self.prompt = Some(Prompt::with_suggestions(
+                    t!("code_lens.prompt").to_string(),
+                    PromptType::CodeLens,
+                    suggestions,
+                ));
+            }
+        }
+    }
+
+    /// Run the command of a lens of a buffer (`index` into its lenses)
+    pub(super) fn run_code_lens(&mut self, buffer_id: BufferId, index: usize) {
+        let Some(command) = self
+            .code_lenses
+            .buffers
+            .get(&buffer_id)
+            .and_then(|lenses| lenses.lenses.get(index))
+            .and_then(|shown| shown.lens.command.clone())
+        else {
+            return;
+        };
+
+        let request_id = self.next_lsp_request_id;
+        let title = command.title.clone();
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, _uri, _language| {
+                let result = handle.execute_command(request_id, command);
+                if let Err(e) = &result {
+                    tracing::debug!("Failed to execute code lens command: {}", e);
+                }
+                result.is_ok()
+            })
+            .unwrap_or(false);
+
+        if sent {
+            self.next_lsp_request_id += 1;
+            self.set_status_message(t!("code_lens.running", title = &title).to_string());
+            self.code_lenses.running = Some((request_id, title));
+        }
+    }
+
+    /// Handle LSP workspace/executeCommand response
+    pub(super) fn handle_lsp_execute_command(
+        &mut self,
+        request_id: u64,
+        result: Result<(), String>,
+    ) {
+        let Some((_, title)) = self
+            .code_lenses
+            .running
+            .take_if(|(id, _)| *id == request_id)
+        else {
+            return;
+        };
+        match result {
+            Ok(()) => self.set_status_message(t!("code_lens.done", title = &title).to_string()),
+            Err(e) => self
+                .set_status_message(t!("code_lens.failed", title = &title, error = &e).to_string()),
+        }
+    }
+}
@@ -20,7 +20,7 @@
 use crate::app::types::BufferMetadata;
 use crate::app::Editor;
 use crate::model::composite_buffer::{CompositeBuffer, CompositeLayout, LineAlignment, SourcePane};
-use crate::model::event::{BufferId, SplitId};
+use crate::model::event::{BufferId, CursorId, Event, SplitId};
 use crate::view::composite_view::CompositeViewState;
 use unicode_segmentation::UnicodeSegmentation;
 
@@ -31,6 +31,18 @@ struct CursorLineInfo {
     pane_width: usize,
 }
 
+/// Position of a composite view's cursor in the source of its focused pane
+struct SourceCursor {
+    buffer_id: BufferId,
+    /// Byte offset of the cursor
+    position: usize,
+    /// Start and end (before the line break) of the cursor's line
+    line_start: usize,
+    line_end: usize,
+    /// The cursor is on a padding row, which has no line in the pane
+    on_padding: bool,
+}
+
 /// Direction for cursor movement
 #[derive(Clone, Copy)]
 enum CursorMovement {
@@ -846,6 +858,348 @@ impl Editor {
         // Don't clear selection after copy - user may want to continue working with it
     }
 
+    // =========================================================================
+    // Editing Editable Panes
+    // =========================================================================
+
+    /// Handle an editing action in the active composite buffer
+    ///
+    /// Typing, deleting, pasting, undo and redo go to the source buffer of the
+    /// focused pane if it is editable, and saving saves the modified editable
+    /// sources. Returns false for other actions and other buffers.
+    pub(crate) fn handle_composite_edit(
+        &mut self,
+        action: &crate::input::keybindings::Action,
+    ) -> std::io::Result<bool> {
+        use crate::input::keybindings::Action;
+
+        let buffer_id = self.active_buffer();
+        let split_id = self.split_manager.active_split();
+        if !self.composite_buffers.contains_key(&buffer_id) || self.is_prompting() {
+            return Ok(false);
+        }
+        if let Action::Save = action {
+            return self.save_composite_sources(buffer_id);
+        }
+        let is_edit = matches!(
+            action,
+            Action::InsertChar(_)
+                | Action::InsertNewline
+                | Action::DeleteBackward
+                | Action::DeleteForward
+                | Action::Paste
+                | Action::Undo
+                | Action::Redo
+        );
+        if !is_edit {
+            return Ok(false);
+        }
+        let Some(cursor) = self.composite_source_cursor(split_id, buffer_id) else {
+            self.set_status_message(rust_i18n::t!("buffer.editing_disabled").to_string());
+            return Ok(true);
+        };
+
+        let newline = self
+            .buffers
+            .get(&cursor.buffer_id)
+            .map_or("\n", |state| state.buffer.line_ending().as_str());
+        let new_position = match action {
+            Action::InsertChar(c) => self.insert_at_composite_cursor(&cursor, &c.to_string()),
+            Action::InsertNewline => self.insert_at_composite_cursor(&cursor, newline),
+            Action::Paste => match self.clipboard.paste() {
+                Some(text) => {
+                    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+                    let text = if newline == "\n" {
+                        text
+                    } else {
+                        text.replace('\n', newline)
+                    };
+                    self.insert_at_composite_cursor(&cursor, &text)
+                }
+                None => None,
+            },
+            Action::DeleteBackward => self.delete_at_composite_cursor(&cursor, true),
+            Action::DeleteForward => self.delete_at_composite_cursor(&cursor, false),
+            _ => self.composite_source_history(cursor.buffer_id, matches!(action, Action::Undo)),
+        };
+
+        self.composite_sources_edited(buffer_id);
+        if let Some(position) = new_position {
+            self.move_composite_cursor_to(split_id, buffer_id, position);
+        }
+        self.sync_editor_cursor_from_composite(split_id, buffer_id);
+        Ok(true)
+    }
+
+    /// Apply an edit to a source buffer of a composite buffer, as one undo
+    /// step of that buffer
+    pub(crate) fn apply_composite_source_event(&mut self, source_id: BufferId, event: Event) {
+        let Some(state) = self.buffers.get_mut(&source_id) else {
+            return;
+        };
+        state.apply(&event);
+        if let Some(log) = self.event_logs.get_mut(&source_id) {
+            log.append(event);
+        }
+        self.invalidate_layouts_for_buffer(source_id);
+    }
+
+    /// Update what is shown from the sources of a composite buffer after they
+    /// were edited
+    pub(crate) fn composite_sources_edited(&mut self, buffer_id: BufferId) {
+        self.refresh_diff_view(buffer_id);
+        self.refresh_merge_view(buffer_id);
+    }
+
+    /// Where the composite cursor is in the focused pane's source, if that
+    /// pane is editable
+    fn composite_source_cursor(
+        &self,
+        split_id: SplitId,
+        buffer_id: BufferId,
+    ) -> Option<SourceCursor> {
+        let composite = self.composite_buffers.get(&buffer_id)?;
+        let view_state = self.composite_view_states.get(&(split_id, buffer_id))?;
+        let pane = view_state.focused_pane;
+        let source = composite
+            .sources
+            .get(pane)
+            .filter(|source| source.editable)?;
+        let buffer = &self.buffers.get(&source.buffer_id)?.buffer;
+
+        let line = composite
+            .alignment
+            .get_row(view_state.cursor_row)
+            .and_then(|row| row.get_pane_line(pane))
+            .map(|line_ref| line_ref.line);
+        let Some(line) = line else {
+            // On a padding row: text goes in before the pane's next line
+            let next_line = composite
+                .alignment
+                .rows
+                .iter()
+                .skip(view_state.cursor_row)
+                .find_map(|row| row.get_pane_line(pane))
+                .map(|line_ref| line_ref.line);
+            let position = next_line
+                .and_then(|line| buffer.line_start_offset(line))
+                .unwrap_or(buffer.len());
+            return Some(SourceCursor {
+                buffer_id: source.buffer_id,
+                position,
+                line_start: position,
+                line_end: position,
+                on_padding: true,
+            });
+        };
+
+        let line_start = buffer.line_start_offset(line)?;
+        let content = buffer
+            .get_line(line)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default();
+        let content = content.trim_end_matches('\n').trim_end_matches('\r');
+        let column_bytes: usize = content
+            .graphemes(true)
+            .take(view_state.cursor_column)
+            .map(str::len)
+            .sum();
+        Some(SourceCursor {
+            buffer_id: source.buffer_id,
+            position: line_start + column_bytes,
+            line_start,
+            line_end: line_start + content.len(),
+            on_padding: false,
+        })
+    }
+
+    /// Insert text at the composite cursor; returns the position after it
+    fn insert_at_composite_cursor(&mut self, cursor: &SourceCursor, text: &str) -> Option<usize> {
+        if text.is_empty() {
+            return None;
+        }
+        let state = self.buffers.get_mut(&cursor.buffer_id)?;
+        let newline = state.buffer.line_ending().as_str();
+        let len = state.buffer.len();
+        let ends_with_line_break =
+            len == 0 || matches!(state.get_text_range(len - 1, len).as_str(), "\n" | "\r");
+        // A padding row stands for a line of its own
+        let inserted = if !cursor.on_padding || text == newline {
+            text.to_string()
+        } else if cursor.position == len && !ends_with_line_break {
+            format!("{}{}", newline, text)
+        } else {
+            format!("{}{}", text, newline)
+        };
+        let after = if inserted.starts_with(newline) && text != newline {
+            cursor.position + inserted.len()
+        } else {
+            cursor.position + text.len()
+        };
+        self.apply_composite_source_event(
+            cursor.buffer_id,
+            Event::Insert {
+                position: cursor.position,
+                text: inserted,
+                cursor_id: CursorId(0),
+            },
+        );
+        Some(after)
+    }
+
+    /// Delete the grapheme (or line break) before or after the composite
+    /// cursor; returns the position of the cursor after it
+    fn delete_at_composite_cursor(
+        &mut self,
+        cursor: &SourceCursor,
+        backward: bool,
+    ) -> Option<usize> {
+        if cursor.on_padding {
+            return None;
+        }
+        let state = self.buffers.get_mut(&cursor.buffer_id)?;
+        let line_start = cursor.line_start;
+        let range = if backward {
+            if cursor.position > line_start {
+                let before = state.get_text_range(line_start, cursor.position);
+                let last = before.graphemes(true).next_back().map_or(1, str::len);
+                (cursor.position - last)..cursor.position
+            } else if line_start > 0 {
+                let break_start = if line_start >= 2
+                    && state.get_text_range(line_start - 2, line_start) == "\r\n"
+                {
+                    line_start - 2
+                } else {
+                    line_start - 1
+                };
+                break_start..line_start
+            } else {
+                return None;
+            }
+        } else if cursor.position < cursor.line_end {
+            let after = state.get_text_range(cursor.position, cursor.line_end);
+            let next = after.graphemes(true).next().map_or(1, str::len);
+            cursor.position..(cursor.position + next)
+        } else {
+            let len = state.buffer.len();
+            let break_len = state
+                .get_text_range(cursor.line_end, (cursor.line_end + 2).min(len))
+                .find('\n')
+                .map(|index| index + 1)?;
+            cursor.line_end..(cursor.line_end + break_len)
+        };
+        let deleted_text = state.get_text_range(range.start, range.end);
+        let position = range.start;
+        self.apply_composite_source_event(
+            cursor.buffer_id,
+            Event::Delete {
+                range,
+                deleted_text,
+                cursor_id: CursorId(0),
+            },
+        );
+        Some(position)
+    }
+
+    /// Undo or redo in a source buffer; returns where its last change was
+    fn composite_source_history(&mut self, source_id: BufferId, undo: bool) -> Option<usize> {
+        let log = self.event_logs.get_mut(&source_id)?;
+        let events = if undo { log.undo() } else { log.redo() };
+        let is_at_saved = log.is_at_saved_position();
+        let state = self.buffers.get_mut(&source_id)?;
+        let mut position = None;
+        for event in &events {
+            state.apply(event);
+            position = match event {
+                Event::Insert { position, text, .. } => Some(position + text.len()),
+                Event::Delete { range, .. } => Some(range.start),
+                _ => position,
+            };
+        }
+        state.buffer.set_modified(!is_at_saved);
+        self.invalidate_layouts_for_buffer(source_id);
+        position
+    }
+
+    /// Move the composite cursor to a byte position of the focused pane's
+    /// source
+    fn move_composite_cursor_to(
+        &mut self,
+        split_id: SplitId,
+        buffer_id: BufferId,
+        position: usize,
+    ) {
+        let viewport_height = self.get_composite_viewport_height(split_id);
+        let (Some(composite), Some(view_state)) = (
+            self.composite_buffers.get(&buffer_id),
+            self.composite_view_states.get_mut(&(split_id, buffer_id)),
+        ) else {
+            return;
+        };
+        let pane = view_state.focused_pane;
+        let Some(buffer) = composite
+            .sources
+            .get(pane)
+            .and_then(|source| self.buffers.get(&source.buffer_id))
+            .map(|state| &state.buffer)
+        else {
+            return;
+        };
+        let Some(target) = buffer.offset_to_position(position) else {
+            return;
+        };
+        let Some(row) = composite.alignment.rows.iter().position(|row| {
+            row.get_pane_line(pane)
+                .is_some_and(|line_ref| line_ref.line == target.line)
+        }) else {
+            return;
+        };
+        let column = buffer
+            .get_line(target.line)
+            .map(|bytes| {
+                let prefix = &bytes[..target.column.min(bytes.len())];
+                String::from_utf8_lossy(prefix).graphemes(true).count()
+            })
+            .unwrap_or(0);
+
+        view_state.cursor_row = row;
+        view_state.cursor_column = column;
+        view_state.sticky_column = column;
+        if row < view_state.scroll_row {
+            view_state.scroll_row = row;
+        } else if row >= view_state.scroll_row + viewport_height {
+            view_state.scroll_row = row + 1 - viewport_height.max(1);
+        }
+    }
+
+    /// Save the modified editable sources of a composite buffer (the
+    /// composite buffer itself has no file)
+    fn save_composite_sources(&mut self, buffer_id: BufferId) -> std::io::Result<bool> {
+        let Some(composite) = self.composite_buffers.get(&buffer_id) else {
+            return Ok(false);
+        };
+        let sources: Vec<BufferId> = composite
+            .sources
+            .iter()
+            .filter(|source| source.editable)
+            .map(|source| source.buffer_id)
+            .filter(|id| {
+                self.buffers.get(id).is_some_and(|state| {
+                    state.buffer.is_modified() && state.buffer.file_path().is_some()
+                })
+            })
+            .collect();
+        // Saving works on the active buffer, so each source stands in for
+        // the composite buffer while it is saved
+        for source_id in sources {
+            self.split_manager.set_active_buffer_id(source_id);
+            let result = self.save();
+            self.split_manager.set_active_buffer_id(buffer_id);
+            result?;
+        }
+        Ok(true)
+    }
+
     // =========================================================================
     // Plugin Command Handlers
     // =========================================================================
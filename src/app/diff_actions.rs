@@ -0,0 +1,598 @@
+//! Two-file diff view
+//!
+//! `fresh --diff LEFT RIGHT`, "Compare with..." and "Compare with Saved"
+//! show two texts side by side in a composite buffer, aligned by the line
+//! diff of `model::line_diff`. Both panes of a file diff can be edited, and
+//! the view is diffed again after each edit; the saved side of "Compare
+//! with Saved" is read-only. Files opened only for the diff are hidden from
+//! the tabs and closed along with it.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+use rust_i18n::t;
+
+use crate::model::composite_buffer::{
+    CompositeLayout, DiffHunk, LineAlignment, PaneStyle, RowType, SourcePane,
+};
+use crate::model::event::{BufferId, CursorId, Event};
+use crate::model::line_diff::diff_hunks;
+use crate::primitives::text_property::TextPropertyEntry;
+
+use super::Editor;
+
+/// Mode of diff view buffers (see `ModeRegistry::new`)
+pub(super) const DIFF_MODE: &str = "diff";
+
+/// State of a diff view, keyed by its composite buffer
+#[derive(Debug)]
+pub(super) struct DiffView {
+    /// Buffers in the left and right panes
+    left: BufferId,
+    right: BufferId,
+    /// Buffers opened for the diff only
+    owned: Vec<BufferId>,
+    /// Changes from left to right, one per hunk header row
+    hunks: Vec<DiffHunk>,
+    /// Event log positions and sizes of the sides when last diffed
+    versions: [(usize, usize, usize); 2],
+}
+
+/// Which side of a diff view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DiffSide {
+    Left,
+    Right,
+}
+
+impl Editor {
+    /// Show the differences between two files
+    pub fn open_diff(&mut self, left: &Path, right: &Path) -> io::Result<BufferId> {
+        let (ids, owned) = self.open_view_sources(&[left, right])?;
+        let (left_id, right_id) = (ids[0], ids[1]);
+        let left_name = self.get_buffer_display_name(left_id);
+        let right_name = self.get_buffer_display_name(right_id);
+        Ok(self.create_diff_view(
+            (left_id, left_name, true),
+            (right_id, right_name, true),
+            owned,
+        ))
+    }
+
+    /// Compare the active buffer with a file
+    pub fn compare_with_file(&mut self, path: &Path) {
+        let current = self.active_buffer();
+        if self.is_composite_buffer(current) {
+            self.set_status_message(t!("diff.not_a_file").to_string());
+            return;
+        }
+        let Some(current_path) = self
+            .buffers
+            .get(&current)
+            .and_then(|state| state.buffer.file_path())
+            .map(Path::to_path_buf)
+        else {
+            self.set_status_message(t!("diff.not_a_file").to_string());
+            return;
+        };
+        if let Err(e) = self.open_diff(&current_path, path) {
+            self.set_status_message(t!("file.error_opening", error = e.to_string()).to_string());
+        }
+    }
+
+    /// Compare the active buffer with its file on disk
+    pub fn compare_with_saved(&mut self) {
+        let current = self.active_buffer();
+        let Some(path) = self
+            .buffers
+            .get(&current)
+            .filter(|state| !state.is_composite_buffer)
+            .and_then(|state| state.buffer.file_path())
+            .map(Path::to_path_buf)
+        else {
+            self.set_status_message(t!("diff.not_a_file").to_string());
+            return;
+        };
+        let saved = match self.buffer_fs() {
+            Some(fs) => fs.read(&path),
+            None => std::fs::read(&path),
+        };
+        let saved = match saved {
+            Ok(saved) => String::from_utf8_lossy(&saved).into_owned(),
+            Err(e) => {
+                self.set_status_message(
+                    t!("file.error_opening", error = e.to_string()).to_string(),
+                );
+                return;
+            }
+        };
+
+        let name = self.get_buffer_display_name(current);
+        let saved_name = t!("diff.saved_label", name = &name).to_string();
+        let saved_id =
+            self.create_virtual_buffer(format!("*{}*", saved_name), "normal".into(), true);
+        if let Some(state) = self.buffers.get_mut(&saved_id) {
+            state.editing_disabled = true;
+        }
+        if let Some(metadata) = self.buffer_metadata.get_mut(&saved_id) {
+            metadata.hidden_from_tabs = true;
+        }
+        if let Err(e) =
+            self.set_virtual_buffer_content(saved_id, vec![TextPropertyEntry::text(saved)])
+        {
+            tracing::error!("Failed to set saved text for the diff: {}", e);
+        }
+        self.create_diff_view(
+            (saved_id, saved_name, false),
+            (current, name, true),
+            vec![saved_id],
+        );
+    }
+
+    /// Create the composite buffer of a diff view and make it active
+    fn create_diff_view(
+        &mut self,
+        (left, left_label, left_editable): (BufferId, String, bool),
+        (right, right_label, right_editable): (BufferId, String, bool),
+        owned: Vec<BufferId>,
+    ) -> BufferId {
+        let file_name = |label: &str| {
+            Path::new(label)
+                .file_name()
+                .map_or(label.to_string(), |name| {
+                    name.to_string_lossy().into_owned()
+                })
+        };
+        let name = t!(
+            "diff.name",
+            left = file_name(&left_label),
+            right = file_name(&right_label)
+        )
+        .to_string();
+        let buffer_id = self.create_composite_buffer(
+            name,
+            DIFF_MODE.to_string(),
+            CompositeLayout::default(),
+            vec![
+                SourcePane::new(left, left_label, left_editable).with_style(PaneStyle::old_diff()),
+                SourcePane::new(right, right_label, right_editable)
+                    .with_style(PaneStyle::new_diff()),
+            ],
+        );
+        self.diff_views.insert(
+            buffer_id,
+            DiffView {
+                left,
+                right,
+                owned,
+                hunks: Vec::new(),
+                versions: Default::default(),
+            },
+        );
+        self.set_active_buffer(buffer_id);
+        self.refresh_diff_view(buffer_id);
+
+        // Start on the first change, in the pane that can be edited
+        let split_id = self.split_manager.active_split();
+        let first_change = self.diff_hunk_rows(buffer_id).first().copied();
+        if let Some(view_state) = self.get_composite_view_state(split_id, buffer_id) {
+            if !left_editable {
+                view_state.focused_pane = 1;
+            }
+            if let Some(row) = first_change {
+                view_state.scroll_row = row.saturating_sub(2);
+                view_state.cursor_row = row + 1;
+            }
+        }
+        if let Some(composite) = self.composite_buffers.get_mut(&buffer_id) {
+            composite.active_pane = if left_editable { 0 } else { 1 };
+        }
+        if first_change.is_none() {
+            self.set_status_message(t!("diff.identical").to_string());
+        }
+        buffer_id
+    }
+
+    /// Diff the sides of a diff view again
+    pub(super) fn refresh_diff_view(&mut self, buffer_id: BufferId) {
+        let Some(view) = self.diff_views.get(&buffer_id) else {
+            return;
+        };
+        let (left, right) = (view.left, view.right);
+        let versions = [self.diff_side_version(left), self.diff_side_version(right)];
+        let text = |id| {
+            self.buffers
+                .get(&id)
+                .and_then(|state| state.buffer.to_string())
+        };
+        // Large files aren't fully loaded; there is nothing to compare
+        let (Some(left_text), Some(right_text)) = (text(left), text(right)) else {
+            self.set_status_message(t!("diff.too_large").to_string());
+            return;
+        };
+        let hunks = diff_hunks(left_text.as_bytes(), right_text.as_bytes());
+        let line_count = |id| {
+            self.buffers
+                .get(&id)
+                .and_then(|state| state.buffer.line_count())
+                .unwrap_or(0)
+        };
+        let alignment = LineAlignment::from_hunks(&hunks, line_count(left), line_count(right));
+        let max_row = alignment.row_count().saturating_sub(1);
+        self.set_composite_alignment(buffer_id, alignment);
+        for ((_, id), view_state) in self.composite_view_states.iter_mut() {
+            if *id == buffer_id {
+                view_state.cursor_row = view_state.cursor_row.min(max_row);
+                view_state.scroll_row = view_state.scroll_row.min(max_row);
+            }
+        }
+        if let Some(view) = self.diff_views.get_mut(&buffer_id) {
+            view.hunks = hunks;
+            view.versions = versions;
+        }
+    }
+
+    /// Diff the diff views again whose sides were changed elsewhere
+    ///
+    /// Called from the main loop; returns true if a redraw is needed.
+    pub(super) fn update_diff_views(&mut self) -> bool {
+        let changed: Vec<BufferId> = self
+            .diff_views
+            .iter()
+            .filter(|(_, view)| {
+                view.versions
+                    != [
+                        self.diff_side_version(view.left),
+                        self.diff_side_version(view.right),
+                    ]
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        for buffer_id in &changed {
+            self.refresh_diff_view(*buffer_id);
+        }
+        !changed.is_empty()
+    }
+
+    /// What changes when a side of a diff is edited, undone or reloaded
+    pub(super) fn diff_side_version(&self, buffer_id: BufferId) -> (usize, usize, usize) {
+        let log = self.event_logs.get(&buffer_id);
+        (
+            log.map_or(0, |log| log.len()),
+            log.map_or(0, |log| log.current_index()),
+            self.buffers
+                .get(&buffer_id)
+                .map_or(0, |state| state.buffer.len()),
+        )
+    }
+
+    /// Rows of the hunk headers of a diff view, one per hunk
+    fn diff_hunk_rows(&self, buffer_id: BufferId) -> Vec<usize> {
+        self.composite_buffers
+            .get(&buffer_id)
+            .map(|composite| {
+                composite
+                    .alignment
+                    .rows
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, row)| row.row_type == RowType::HunkHeader)
+                    .map(|(index, _)| index)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Move the cursor of the active diff view to the next or previous change
+    pub(super) fn diff_goto_hunk(&mut self, next: bool) {
+        let buffer_id = self.active_buffer();
+        if !self.diff_views.contains_key(&buffer_id) {
+            self.set_status_message(t!("diff.not_in_diff").to_string());
+            return;
+        }
+        let split_id = self.split_manager.active_split();
+        let rows = self.diff_hunk_rows(buffer_id);
+        let viewport_height = self
+            .split_view_states
+            .get(&split_id)
+            .map_or(24, |view_state| {
+                view_state.viewport.height.saturating_sub(1) as usize
+            });
+        let Some(view_state) = self.get_composite_view_state(split_id, buffer_id) else {
+            return;
+        };
+        let cursor_row = view_state.cursor_row;
+        // The cursor lands on the first row after a header
+        let target = if next {
+            rows.iter().position(|&row| row >= cursor_row)
+        } else {
+            rows.iter().rposition(|&row| row + 1 < cursor_row)
+        };
+        let Some(index) = target else {
+            self.set_status_message(t!("diff.no_more_changes").to_string());
+            return;
+        };
+        let header = rows[index];
+        view_state.cursor_row = header + 1;
+        view_state.cursor_column = 0;
+        view_state.sticky_column = 0;
+        if header < view_state.scroll_row
+            || header + 1 >= view_state.scroll_row + viewport_height.max(1)
+        {
+            view_state.scroll_row = header.saturating_sub(2);
+        }
+        self.set_status_message(
+            t!(
+                "diff.change_position",
+                index = index + 1,
+                count = rows.len()
+            )
+            .to_string(),
+        );
+    }
+
+    /// Replace the change under the cursor on one side with the other side's
+    /// version of it
+    pub(super) fn diff_take(&mut self, from: DiffSide) {
+        let buffer_id = self.active_buffer();
+        let Some(view) = self.diff_views.get(&buffer_id) else {
+            self.set_status_message(t!("diff.not_in_diff").to_string());
+            return;
+        };
+        let split_id = self.split_manager.active_split();
+        let cursor_row = self
+            .composite_view_states
+            .get(&(split_id, buffer_id))
+            .map_or(0, |view_state| view_state.cursor_row);
+        let rows = self.diff_hunk_rows(buffer_id);
+        let hunk = rows
+            .iter()
+            .rposition(|&row| row <= cursor_row)
+            .and_then(|index| Some((rows[index], view.hunks.get(index)?.clone())))
+            .filter(|(row, hunk)| cursor_row <= row + hunk.old_count.max(hunk.new_count));
+        let Some((_, hunk)) = hunk else {
+            self.set_status_message(t!("diff.not_on_change").to_string());
+            return;
+        };
+
+        let (source, target, target_pane) = match from {
+            DiffSide::Left => (view.left, view.right, 1),
+            DiffSide::Right => (view.right, view.left, 0),
+        };
+        let (source_lines, target_lines) = match from {
+            DiffSide::Left => (
+                hunk.old_start..hunk.old_start + hunk.old_count,
+                hunk.new_start..hunk.new_start + hunk.new_count,
+            ),
+            DiffSide::Right => (
+                hunk.new_start..hunk.new_start + hunk.new_count,
+                hunk.old_start..hunk.old_start + hunk.old_count,
+            ),
+        };
+        let editable = self
+            .composite_buffers
+            .get(&buffer_id)
+            .and_then(|composite| composite.sources.get(target_pane))
+            .is_some_and(|pane| pane.editable);
+        if !editable {
+            self.set_status_message(t!("buffer.editing_disabled").to_string());
+            return;
+        }
+
+        let text = |id| {
+            self.buffers
+                .get(&id)
+                .and_then(|state| state.buffer.to_string())
+        };
+        let (Some(source_text), Some(target_text)) = (text(source), text(target)) else {
+            self.set_status_message(t!("diff.too_large").to_string());
+            return;
+        };
+        let new_text = replace_lines(&target_text, target_lines, &source_text, source_lines);
+        for event in minimal_edit(&target_text, &new_text) {
+            self.apply_composite_source_event(target, event);
+        }
+        self.composite_sources_edited(buffer_id);
+    }
+
+    /// Open the files shown by a diff or merge view, returning their buffers
+    /// and those of them that were not open before, which are hidden from
+    /// the tabs
+    pub(super) fn open_view_sources(
+        &mut self,
+        paths: &[&Path],
+    ) -> io::Result<(Vec<BufferId>, Vec<BufferId>)> {
+        let open_before: HashSet<BufferId> = self
+            .buffers
+            .iter()
+            .filter(|(_, state)| state.buffer.file_path().is_some())
+            .map(|(id, _)| *id)
+            .collect();
+        let ids = paths
+            .iter()
+            .map(|path| self.open_file_no_focus(path))
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut owned: Vec<BufferId> = Vec::new();
+        for id in &ids {
+            if !open_before.contains(id) && !owned.contains(id) {
+                owned.push(*id);
+            }
+        }
+        for id in &owned {
+            if let Some(metadata) = self.buffer_metadata.get_mut(id) {
+                metadata.hidden_from_tabs = true;
+            }
+        }
+        Ok((ids, owned))
+    }
+
+    /// Close the buffers opened for a diff or merge view
+    pub(super) fn close_view_sources(&mut self, owned: Vec<BufferId>) {
+        for id in owned {
+            // Edits made in the view stay open in a tab of their own
+            if self.close_buffer(id).is_err() {
+                if let Some(metadata) = self.buffer_metadata.get_mut(&id) {
+                    metadata.hidden_from_tabs = false;
+                }
+                let split_id = self.split_manager.active_split();
+                if let Some(view_state) = self.split_view_states.get_mut(&split_id) {
+                    view_state.add_buffer(id);
+                }
+            }
+        }
+    }
+
+    /// Close the diff view of a closed buffer, or the diff views showing it
+    pub(super) fn close_diff_views_of(&mut self, closed: BufferId) {
+        if let Some(view) = self.diff_views.remove(&closed) {
+            self.close_composite_buffer(closed);
+            self.close_view_sources(view.owned);
+            return;
+        }
+        let showing: Vec<BufferId> = self
+            .diff_views
+            .iter()
+            .filter(|(_, view)| view.left == closed || view.right == closed)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in showing {
+            let _ = self.close_buffer(id);
+        }
+    }
+}
+
+/// `text` with its lines `lines` replaced by the lines `source_lines` of
+/// `source` (lines as split by `diff_hunks`, so the last one has no line
+/// break)
+fn replace_lines(
+    text: &str,
+    lines: std::ops::Range<usize>,
+    source: &str,
+    source_lines: std::ops::Range<usize>,
+) -> String {
+    let text_lines: Vec<&str> = text.split('\n').collect();
+    let source_lines = source
+        .split('\n')
+        .skip(source_lines.start)
+        .take(source_lines.len());
+    text_lines[..lines.start.min(text_lines.len())]
+        .iter()
+        .copied()
+        .chain(source_lines)
+        .chain(
+            text_lines[lines.end.min(text_lines.len())..]
+                .iter()
+                .copied(),
+        )
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Events turning `old` into `new`, leaving their common start and end alone
+pub(super) fn minimal_edit(old: &str, new: &str) -> Vec<Event> {
+    let prefix = old
+        .char_indices()
+        .zip(new.chars())
+        .take_while(|((_, a), b)| a == b)
+        .last()
+        .map_or(0, |((index, c), _)| index + c.len_utf8());
+    let suffix = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum::<usize>();
+    let removed = &old[prefix..old.len() - suffix];
+    let added = &new[prefix..new.len() - suffix];
+
+    let mut events = Vec::new();
+    if !removed.is_empty() {
+        events.push(Event::Delete {
+            range: prefix..prefix + removed.len(),
+            deleted_text: removed.to_string(),
+            cursor_id: CursorId(0),
+        });
+    }
+    if !added.is_empty() {
+        events.push(Event::Insert {
+            position: prefix,
+            text: added.to_string(),
+            cursor_id: CursorId(0),
+        });
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(text: &str, events: &[Event]) -> String {
+        let mut text = text.to_string();
+        for event in events {
+            match event {
+                Event::Delete { range, .. } => text.replace_range(range.clone(), ""),
+                Event::Insert {
+                    position, text: t, ..
+                } => text.insert_str(*position, t),
+                _ => unreachable!(),
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn test_replace_lines() {
+        assert_eq!(
+            replace_lines("a\nb\nc\n", 1..2, "x\ny\n", 0..2),
+            "a\nx\ny\nc\n"
+        );
+        // Lines only on one side
+        assert_eq!(
+            replace_lines("a\nc\n", 1..1, "a\nb\nc\n", 1..2),
+            "a\nb\nc\n"
+        );
+        assert_eq!(replace_lines("a\nb\nc\n", 1..2, "a\nc\n", 1..1), "a\nc\n");
+        // Without a line break at the end
+        assert_eq!(replace_lines("a\nb", 1..2, "a\nc\n", 1..3), "a\nc\n");
+    }
+
+    #[test]
+    fn test_take_a_hunk() {
+        let left = "one\ntwo\nthree\nfour\n";
+        let right = "one\n2\nthree\nfour\nfive\n";
+        let hunks = diff_hunks(left.as_bytes(), right.as_bytes());
+        assert_eq!(hunks.len(), 2);
+
+        let hunk = &hunks[0];
+        let taken = replace_lines(
+            right,
+            hunk.new_start..hunk.new_start + hunk.new_count,
+            left,
+            hunk.old_start..hunk.old_start + hunk.old_count,
+        );
+        assert_eq!(taken, "one\ntwo\nthree\nfour\nfive\n");
+
+        let hunk = &hunks[1];
+        let taken = replace_lines(
+            left,
+            hunk.old_start..hunk.old_start + hunk.old_count,
+            right,
+            hunk.new_start..hunk.new_start + hunk.new_count,
+        );
+        assert_eq!(taken, "one\ntwo\nthree\nfour\nfive\n");
+    }
+
+    #[test]
+    fn test_minimal_edit() {
+        let events = minimal_edit("one\ntwo\nthree\n", "one\n2\nthree\n");
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], Event::Delete { range, .. } if *range == (4..7)));
+        assert_eq!(apply("one\ntwo\nthree\n", &events), "one\n2\nthree\n");
+
+        assert!(minimal_edit("same", "same").is_empty());
+        assert_eq!(apply("aé", &minimal_edit("aé", "aéb")), "aéb");
+    }
+}
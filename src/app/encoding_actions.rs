@@ -0,0 +1,181 @@
+//! Text encodings for the Editor.
+//!
+//! Files are decoded when they are opened (see `model::encoding`), and the
+//! detected encoding is shown in the status bar. "Reopen with Encoding" reads
+//! the file again in another encoding, for when detection guessed wrong, and
+//! "Save with Encoding" converts it. An encoding picked this way sticks to the
+//! file: it is used when the file is reverted, and remembered with the
+//! per-file session for the next time it is opened.
+
+use rust_i18n::t;
+
+use crate::input::commands::Suggestion;
+use crate::model::encoding::Encoding;
+use crate::model::event::BufferId;
+use crate::view::prompt::{Prompt, PromptType};
+
+use super::Editor;
+
+/// What the text of an encoding is used for, shown next to its name
+fn encoding_description(encoding: Encoding) -> &'static str {
+    match encoding {
+        Encoding::Utf8 => "Unicode",
+        Encoding::Utf8Bom => "Unicode, with byte order mark",
+        Encoding::Utf16Le => "Unicode, little endian",
+        Encoding::Utf16Be => "Unicode, big endian",
+        Encoding::Latin1 => "Western European, ISO-8859-1",
+        Encoding::Windows1252 => "Western European",
+        Encoding::Windows1250 => "Central European",
+        Encoding::Windows1251 => "Cyrillic",
+        Encoding::ShiftJis | Encoding::EucJp => "Japanese",
+        Encoding::Gb18030 => "Simplified Chinese",
+        Encoding::Big5 => "Traditional Chinese",
+        Encoding::EucKr => "Korean",
+    }
+}
+
+impl Editor {
+    /// Start the prompt picking an encoding to reopen or save the active buffer with
+    pub(super) fn start_encoding_prompt(&mut self, prompt_type: PromptType) {
+        let current = self.active_state().buffer.encoding();
+        let suggestions = Encoding::ALL
+            .into_iter()
+            .map(|encoding| {
+                let description = encoding_description(encoding);
+                Suggestion {
+                    text: encoding.display_name().to_string(),
+                    description: Some(if encoding == current {
+                        format!("{} (current)", description)
+                    } else {
+                        description.to_string()
+                    }),
+                    value: Some(encoding.display_name().to_string()),
+                    disabled: false,
+                    keybinding: None,
+                    source: None,
+                }
+            })
+            .collect();
+
+        let message = if matches!(prompt_type, PromptType::ReopenWithEncoding) {
+            t!("encoding.reopen_prompt")
+        } else {
+            t!("encoding.save_prompt")
+        };
+        let mut prompt = Prompt::with_suggestions(message.to_string(), prompt_type, suggestions);
+        prompt.selected_suggestion = Encoding::ALL.iter().position(|e| *e == current);
+        self.prompt = Some(prompt);
+    }
+
+    /// Parse the encoding picked in an encoding prompt
+    pub(super) fn parse_encoding_input(&mut self, input: &str) -> Option<Encoding> {
+        let encoding = Encoding::from_name(input.trim());
+        if encoding.is_none() {
+            self.set_status_message(t!("error.unknown_encoding", input = input).to_string());
+        }
+        encoding
+    }
+
+    /// Read the active buffer's file again, decoding it as `encoding`
+    pub(super) fn reopen_with_encoding(&mut self, encoding: Encoding) {
+        let buffer_id = self.active_buffer();
+        let Some(path) = self
+            .active_state()
+            .buffer
+            .file_path()
+            .map(|p| p.to_path_buf())
+        else {
+            self.set_status_message(t!("status.no_file_to_revert").to_string());
+            return;
+        };
+        if self.active_state().buffer.is_modified() {
+            self.set_status_message(t!("encoding.unsaved_changes").to_string());
+            return;
+        }
+        // Check first, so the buffer stays as it is if the file is not valid in the encoding
+        match std::fs::read(&path) {
+            Ok(bytes) if encoding.decode(&bytes).is_none() => {
+                self.set_status_message(
+                    t!("encoding.invalid", encoding = encoding.display_name()).to_string(),
+                );
+                return;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                self.set_status_message(
+                    t!("file.revert_failed", error = e.to_string()).to_string(),
+                );
+                return;
+            }
+        }
+
+        let previous = self
+            .buffer_metadata
+            .get_mut(&buffer_id)
+            .and_then(|metadata| metadata.chosen_encoding.replace(encoding));
+        let reopened = self.revert_file();
+        if !matches!(reopened, Ok(true)) {
+            if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
+                metadata.chosen_encoding = previous;
+            }
+        }
+        match reopened {
+            Ok(true) => self.set_status_message(
+                t!("encoding.reopened", encoding = encoding.display_name()).to_string(),
+            ),
+            Ok(false) => {}
+            Err(e) => {
+                self.set_status_message(t!("file.revert_failed", error = e.to_string()).to_string())
+            }
+        }
+    }
+
+    /// Save the active buffer, converting it to `encoding`
+    pub(super) fn save_with_encoding(&mut self, encoding: Encoding) {
+        let buffer_id = self.active_buffer();
+        let buffer = &mut self.active_state_mut().buffer;
+        let previous = buffer.encoding();
+        let was_modified = buffer.is_modified();
+        buffer.set_encoding(encoding);
+        if buffer.file_path().is_none() {
+            // Written in the new encoding once it has a name
+            self.start_prompt_with_initial_text(
+                t!("file.save_as_prompt").to_string(),
+                PromptType::SaveFileAs,
+                String::new(),
+            );
+            return;
+        }
+        if self.check_save_conflict().is_some() {
+            // Saved in the new encoding if the user overwrites
+            self.remember_encoding(buffer_id, encoding);
+            self.start_prompt(
+                t!("file.file_changed_prompt").to_string(),
+                PromptType::ConfirmSaveConflict,
+            );
+            return;
+        }
+
+        match self.save() {
+            Ok(()) => {
+                self.remember_encoding(buffer_id, encoding);
+                self.set_status_message(
+                    t!("encoding.saved", encoding = encoding.display_name()).to_string(),
+                );
+            }
+            Err(e) => {
+                // Characters the encoding can't represent; keep the buffer as it was
+                let buffer = &mut self.active_state_mut().buffer;
+                buffer.set_encoding(previous);
+                buffer.set_modified(was_modified);
+                self.set_status_message(t!("file.save_failed", error = e.to_string()).to_string());
+            }
+        }
+    }
+
+    fn remember_encoding(&mut self, buffer_id: BufferId, encoding: Encoding) {
+        if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
+            metadata.chosen_encoding = Some(encoding);
+        }
+    }
+}
@@ -378,8 +378,7 @@ impl Editor {
                     if let Some(runtime) = &self.tokio_runtime {
                         let path_clone = file_path.clone();
                         let selected_id = selected_id;
-                        let result =
-                            runtime.block_on(async { tokio::fs::File::create(&path_clone).await });
+                        let result = runtime.block_on(self.fs_manager.write(&path_clone, &[]));
 
                         match result {
                             Ok(_) => {
@@ -417,8 +416,7 @@ impl Editor {
                         let path_clone = dir_path.clone();
                         let dirname_clone = dirname.clone();
                         let selected_id = selected_id;
-                        let result =
-                            runtime.block_on(async { tokio::fs::create_dir(&path_clone).await });
+                        let result = runtime.block_on(self.fs_manager.mkdir(&path_clone));
 
                         match result {
                             Ok(_) => {
@@ -480,16 +478,29 @@ impl Editor {
     }
 
     /// Perform the actual file explorer delete operation (called after prompt confirmation)
-    /// Moves the file/directory to the system trash/recycle bin
+    /// Moves the file/directory to the system trash/recycle bin, or deletes it
+    /// on a remote filesystem, which has no trash
     pub fn perform_file_explorer_delete(&mut self, path: std::path::PathBuf, _is_dir: bool) {
         let name = path
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_default();
 
-        // Move to trash instead of permanent deletion
-        match trash::delete(&path) {
-            Ok(_) => {
+        let result = if self.fs_manager.backend().is_local() {
+            // Move to trash instead of permanent deletion
+            trash::delete(&path)
+                .map(|()| t!("explorer.moved_to_trash", name = &name).to_string())
+                .map_err(|e| t!("explorer.error_trash", error = e.to_string()).to_string())
+        } else if let Some(runtime) = &self.tokio_runtime {
+            runtime
+                .block_on(self.fs_manager.remove(&path))
+                .map(|()| t!("explorer.deleted", name = &name).to_string())
+                .map_err(|e| t!("explorer.error_deleting", error = e.to_string()).to_string())
+        } else {
+            return;
+        };
+        match result {
+            Ok(message) => {
                 // Refresh the parent directory in the file explorer
                 if let Some(explorer) = &mut self.file_explorer {
                     if let Some(runtime) = &self.tokio_runtime {
@@ -501,13 +512,9 @@ impl Editor {
                         }
                     }
                 }
-                self.set_status_message(t!("explorer.moved_to_trash", name = &name).to_string());
-            }
-            Err(e) => {
-                self.set_status_message(
-                    t!("explorer.error_trash", error = e.to_string()).to_string(),
-                );
+                self.set_status_message(message);
             }
+            Err(message) => self.set_status_message(message),
         }
     }
 
@@ -558,8 +565,7 @@ impl Editor {
             .unwrap_or_else(|| original_path.clone());
 
         if let Some(runtime) = &self.tokio_runtime {
-            let result =
-                runtime.block_on(async { tokio::fs::rename(&original_path, &new_path).await });
+            let result = runtime.block_on(self.fs_manager.rename(&original_path, &new_path));
 
             match result {
                 Ok(_) => {
@@ -1,7 +1,8 @@
 //! Input handling for the file open dialog
 //!
 //! This module handles keyboard and mouse input specifically for the file
-//! browser popup when the Open File or Switch Project prompt is active.
+//! browser popup when the Open File, Switch Project or Compare With prompt
+//! is active.
 
 use super::file_open::{FileOpenSection, SortMode};
 use super::Editor;
@@ -10,14 +11,15 @@ use crate::view::prompt::PromptType;
 use rust_i18n::t;
 
 impl Editor {
-    /// Check if the file open dialog is active (for both OpenFile and SwitchProject)
+    /// Check if the file open dialog is active (for OpenFile, SwitchProject and
+    /// CompareWithFile)
     pub fn is_file_open_active(&self) -> bool {
         self.prompt
             .as_ref()
             .map(|p| {
                 matches!(
                     p.prompt_type,
-                    PromptType::OpenFile | PromptType::SwitchProject
+                    PromptType::OpenFile | PromptType::SwitchProject | PromptType::CompareWithFile
                 )
             })
             .unwrap_or(false)
@@ -179,7 +181,7 @@ impl Editor {
                 current_dir.join(&prompt_input)
             };
 
-            if expanded_path.is_dir() {
+            if self.path_is_dir(&expanded_path) {
                 if is_folder_mode {
                     // In folder mode, selecting a directory switches to it as the project root
                     self.file_open_select_folder(expanded_path);
@@ -187,7 +189,7 @@ impl Editor {
                     self.file_open_navigate_to(expanded_path);
                 }
                 return;
-            } else if expanded_path.is_file() && !is_folder_mode {
+            } else if self.path_is_file(&expanded_path) && !is_folder_mode {
                 // File exists - open it directly (handles pasted paths before async load completes)
                 // Only allowed in file mode, not folder mode
                 self.file_open_open_file(expanded_path);
@@ -261,12 +263,22 @@ impl Editor {
         self.load_file_open_directory(path);
     }
 
-    /// Open a file from the file browser
+    /// Open a file from the file browser (or compare with it)
     fn file_open_open_file(&mut self, path: std::path::PathBuf) {
+        let compare = self
+            .prompt
+            .as_ref()
+            .is_some_and(|p| p.prompt_type == PromptType::CompareWithFile);
+
         // Close the file browser
         self.file_open_state = None;
         self.prompt = None;
 
+        if compare {
+            self.compare_with_file(&path);
+            return;
+        }
+
         // Open the file
         if let Err(e) = self.open_file(&path) {
             self.set_status_message(t!("file.error_opening", error = e.to_string()).to_string());
@@ -342,7 +354,7 @@ impl Editor {
             };
 
             // Navigate to target directory if it exists and is different from current
-            if target_dir.is_dir() && target_dir != current_dir {
+            if self.path_is_dir(&target_dir) && target_dir != current_dir {
                 // Update prompt to only show the filename (directory is shown separately)
                 if let Some(prompt) = &mut self.prompt {
                     prompt.input = filename.clone();
@@ -10,11 +10,13 @@
 
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use lsp_types::TextDocumentContentChangeEvent;
 use rust_i18n::t;
 
 use crate::model::event::{BufferId, EventLog};
+use crate::services::fs::BlockingFs;
 use crate::services::lsp::manager::{detect_language, LspSpawnResult};
 use crate::state::EditorState;
 
@@ -29,6 +31,14 @@ impl Editor {
             .file_path()
             .map(|p| p.to_path_buf());
         self.active_state_mut().buffer.save()?;
+        self.finish_save(path);
+        Ok(())
+    }
+
+    /// Update the editor after the active buffer was written to `path`:
+    /// modification times, language servers, recovery, plugins and on-save
+    /// actions
+    pub(super) fn finish_save(&mut self, path: Option<PathBuf>) {
         self.status_message = Some(t!("status.file_saved").to_string());
 
         // Mark the event log position as saved (for undo modified tracking)
@@ -89,8 +99,6 @@ impl Editor {
                 self.status_message = Some(e);
             }
         }
-
-        Ok(())
     }
 
     /// Revert the active buffer to the last saved version on disk
@@ -104,7 +112,7 @@ impl Editor {
             }
         };
 
-        if !path.exists() {
+        if !self.path_exists(&path) {
             self.status_message =
                 Some(t!("status.file_not_exists", path = path.display().to_string()).to_string());
             return Ok(false);
@@ -120,14 +128,7 @@ impl Editor {
         let old_cursors = self.active_state().cursors.clone();
 
         // Load the file content fresh from disk
-        let mut new_state = EditorState::from_file_with_languages(
-            &path,
-            self.terminal_width,
-            self.terminal_height,
-            self.config.editor.large_file_threshold_bytes as usize,
-            &self.grammar_registry,
-            &self.config.languages,
-        )?;
+        let mut new_state = self.load_buffer_file(self.active_buffer(), &path)?;
 
         // Restore cursor positions (clamped to valid range for new file size)
         let new_file_size = new_state.buffer.len();
@@ -175,6 +176,65 @@ impl Editor {
         Ok(true)
     }
 
+    /// Load a buffer's file from disk, in the encoding picked for it if any
+    fn load_buffer_file(&self, buffer_id: BufferId, path: &Path) -> io::Result<EditorState> {
+        let load = |encoding| {
+            EditorState::from_file_with_encoding(
+                path,
+                self.terminal_width,
+                self.terminal_height,
+                self.config.editor.large_file_threshold_bytes as usize,
+                &self.grammar_registry,
+                &self.config.languages,
+                encoding,
+                self.buffer_fs(),
+            )
+        };
+        match self
+            .buffer_metadata
+            .get(&buffer_id)
+            .and_then(|metadata| metadata.chosen_encoding)
+        {
+            // The file may have been rewritten in another encoding since
+            Some(encoding) => load(Some(encoding)).or_else(|_| load(None)),
+            None => load(None),
+        }
+    }
+
+    /// Blocking access to the filesystem backend, which buffers read and
+    /// save their files through (`None` without an async runtime)
+    pub(super) fn buffer_fs(&self) -> Option<BlockingFs> {
+        let runtime = self.tokio_runtime.as_ref()?;
+        Some(BlockingFs::new(
+            Arc::clone(self.fs_manager.backend()),
+            runtime.handle().clone(),
+        ))
+    }
+
+    /// Whether a file or directory exists, on the filesystem backend
+    pub(super) fn path_exists(&self, path: &Path) -> bool {
+        match self.buffer_fs() {
+            Some(fs) => fs.exists(path),
+            None => path.exists(),
+        }
+    }
+
+    /// Whether a path is a directory, on the filesystem backend
+    pub(super) fn path_is_dir(&self, path: &Path) -> bool {
+        match self.buffer_fs() {
+            Some(fs) => fs.is_dir(path),
+            None => path.is_dir(),
+        }
+    }
+
+    /// Whether a path is a file, on the filesystem backend
+    pub(super) fn path_is_file(&self, path: &Path) -> bool {
+        match self.buffer_fs() {
+            Some(fs) => fs.exists(path) && !fs.is_dir(path),
+            None => path.is_file(),
+        }
+    }
+
     /// Toggle auto-revert mode
     pub fn toggle_auto_revert(&mut self) {
         self.auto_revert_enabled = !self.auto_revert_enabled;
@@ -189,6 +249,7 @@ impl Editor {
     /// Poll for file changes (called from main loop)
     ///
     /// Checks modification times of open files to detect external changes.
+    /// Files in directories the file watcher reports on are skipped.
     /// Returns true if any file was changed (requires re-render).
     pub fn poll_file_changes(&mut self) -> bool {
         // Skip if auto-revert is disabled
@@ -214,12 +275,19 @@ impl Editor {
         let files_to_check: Vec<PathBuf> = self
             .buffers
             .values()
+            .filter(|state| !state.buffer.is_remote())
             .filter_map(|state| state.buffer.file_path().map(PathBuf::from))
+            .filter(|path| !path.parent().is_some_and(|dir| self.is_dir_watched(dir)))
             .collect();
 
         let mut any_changed = false;
 
         for path in files_to_check {
+            // Later changes are reported by the file watcher if it can watch the directory
+            if let Some(dir) = path.parent() {
+                self.watch_dir(dir);
+            }
+
             // Get current mtime
             let current_mtime = match std::fs::metadata(&path) {
                 Ok(meta) => match meta.modified() {
@@ -252,6 +320,7 @@ impl Editor {
     /// Poll for file tree changes (called from main loop)
     ///
     /// Checks modification times of expanded directories to detect new/deleted files.
+    /// Directories the file watcher reports on are skipped.
     /// Returns true if any directory was refreshed (requires re-render).
     pub fn poll_file_tree_changes(&mut self) -> bool {
         // Check poll interval
@@ -273,6 +342,7 @@ impl Editor {
             .tree()
             .all_nodes()
             .filter(|node| node.is_dir() && node.is_expanded())
+            .filter(|node| !self.is_dir_watched(&node.entry.path))
             .map(|node| (node.id, node.entry.path.clone()))
             .collect();
 
@@ -280,6 +350,8 @@ impl Editor {
         let mut dirs_to_refresh: Vec<NodeId> = Vec::new();
 
         for (node_id, path) in expanded_dirs {
+            self.watch_dir(&path);
+
             // Get current mtime
             let current_mtime = match std::fs::metadata(&path) {
                 Ok(meta) => match meta.modified() {
@@ -578,14 +650,7 @@ impl Editor {
     /// cursors (clamped to valid positions), but does NOT touch any viewport state.
     fn revert_buffer_by_id(&mut self, buffer_id: BufferId, path: &Path) -> io::Result<()> {
         // Load the file content fresh from disk
-        let new_state = EditorState::from_file_with_languages(
-            path,
-            self.terminal_width,
-            self.terminal_height,
-            self.config.editor.large_file_threshold_bytes as usize,
-            &self.grammar_registry,
-            &self.config.languages,
-        )?;
+        let new_state = self.load_buffer_file(buffer_id, path)?;
 
         // Get the new file size for clamping
         let new_file_size = new_state.buffer.len();
@@ -0,0 +1,303 @@
+//! Reacting to changes on disk
+//!
+//! The file watcher (see `services::file_watcher`) reports changes as they
+//! happen. They revert open buffers, refresh the file explorer and are sent to
+//! the language servers that asked for them with `client/registerCapability`.
+//! Directories the watcher cannot watch are still polled by
+//! `poll_file_changes` and `poll_file_tree_changes`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobMatcher};
+use lsp_types::{FileChangeType, FileEvent, FileSystemWatcher, GlobPattern, OneOf, WatchKind};
+
+use crate::services::file_watcher::{WatchEvent, WatchEventKind};
+use crate::view::file_tree::NodeId;
+
+use super::{uri_to_path, Editor};
+
+/// File watchers the language servers registered, by language and registration ID
+#[derive(Default)]
+pub(super) struct LspFileWatchers(HashMap<String, HashMap<String, Vec<LspFileWatcher>>>);
+
+/// A glob pattern a language server wants the changes of
+struct LspFileWatcher {
+    /// Directory the pattern is relative to (None for plain patterns)
+    base: Option<PathBuf>,
+    glob: GlobMatcher,
+    kind: WatchKind,
+}
+
+impl LspFileWatcher {
+    fn new(watcher: FileSystemWatcher) -> Option<Self> {
+        let (base, pattern) = match watcher.glob_pattern {
+            GlobPattern::String(pattern) => (None, pattern),
+            GlobPattern::Relative(relative) => {
+                let base_uri = match relative.base_uri {
+                    OneOf::Left(folder) => folder.uri,
+                    OneOf::Right(uri) => uri,
+                };
+                (Some(uri_to_path(&base_uri).ok()?), relative.pattern)
+            }
+        };
+        let glob = match GlobBuilder::new(&pattern).literal_separator(true).build() {
+            Ok(glob) => glob.compile_matcher(),
+            Err(e) => {
+                tracing::debug!("Ignoring file watcher with invalid glob {}: {}", pattern, e);
+                return None;
+            }
+        };
+        Some(Self {
+            base,
+            glob,
+            kind: watcher.kind.unwrap_or(WatchKind::all()),
+        })
+    }
+
+    fn matches(&self, path: &Path, kind: WatchKind, workspace: &Path) -> bool {
+        if !self.kind.contains(kind) {
+            return false;
+        }
+        match &self.base {
+            Some(base) => path
+                .strip_prefix(base)
+                .is_ok_and(|relative| self.glob.is_match(relative)),
+            // Plain patterns are usually `**/...`, but may be relative to the workspace
+            None => {
+                self.glob.is_match(path)
+                    || path
+                        .strip_prefix(workspace)
+                        .is_ok_and(|relative| self.glob.is_match(relative))
+            }
+        }
+    }
+}
+
+impl Editor {
+    /// Handle changes reported by the file watcher
+    pub(super) fn handle_files_changed(&mut self, events: Vec<WatchEvent>) {
+        tracing::trace!("Files changed on disk: {:?}", events);
+        self.revert_changed_files(&events);
+        self.refresh_changed_directories(&events);
+        self.notify_lsp_watched_files(&events);
+    }
+
+    /// Whether changes to the entries of `dir` are reported by the file watcher
+    pub(super) fn is_dir_watched(&self, dir: &Path) -> bool {
+        self.file_watcher
+            .as_ref()
+            .is_some_and(|watcher| watcher.is_watched(dir))
+    }
+
+    /// Let the file watcher report the changes of `dir` from now on, if it can
+    pub(super) fn watch_dir(&self, dir: &Path) {
+        if let Some(watcher) = &self.file_watcher {
+            watcher.watch_dir(dir);
+        }
+    }
+
+    fn revert_changed_files(&mut self, events: &[WatchEvent]) {
+        if !self.auto_revert_enabled {
+            return;
+        }
+        for event in events {
+            if event.kind == WatchEventKind::Deleted {
+                continue;
+            }
+            let is_open = self
+                .buffers
+                .values()
+                .any(|state| state.buffer.file_path() == Some(event.path.as_path()));
+            if !is_open {
+                continue;
+            }
+            // The editor's own saves are reported too, and are already known
+            let Ok(mtime) = std::fs::metadata(&event.path).and_then(|m| m.modified()) else {
+                continue;
+            };
+            match self.file_mod_times.get(&event.path) {
+                Some(stored) if *stored != mtime => {
+                    self.handle_async_file_changed(event.path.display().to_string());
+                }
+                Some(_) => {}
+                None => {
+                    self.file_mod_times.insert(event.path.clone(), mtime);
+                }
+            }
+        }
+    }
+
+    fn refresh_changed_directories(&mut self, events: &[WatchEvent]) {
+        let Some(explorer) = &self.file_explorer else {
+            return;
+        };
+        let mut dirs_to_refresh: Vec<NodeId> = Vec::new();
+        for event in events {
+            // Writes do not change the listing
+            if event.kind == WatchEventKind::Changed {
+                continue;
+            }
+            let Some(node) = event
+                .path
+                .parent()
+                .and_then(|dir| explorer.tree().get_node_by_path(dir))
+            else {
+                continue;
+            };
+            if node.is_dir() && node.is_expanded() && !dirs_to_refresh.contains(&node.id) {
+                dirs_to_refresh.push(node.id);
+            }
+        }
+
+        if dirs_to_refresh.is_empty() {
+            return;
+        }
+        if let (Some(runtime), Some(explorer)) = (&self.tokio_runtime, &mut self.file_explorer) {
+            // Refreshed directories get new nodes, so keep the selection by path
+            let selected = explorer
+                .get_selected_entry()
+                .map(|entry| entry.path.clone());
+            for node_id in dirs_to_refresh {
+                if let Err(e) = runtime.block_on(explorer.tree_mut().refresh_node(node_id)) {
+                    tracing::warn!("Failed to refresh directory: {}", e);
+                }
+            }
+            // A deleted entry leaves the selection on its directory
+            let selected = selected.as_deref().and_then(|selected| {
+                selected
+                    .ancestors()
+                    .find(|path| explorer.tree().get_node_by_path(path).is_some())
+            });
+            if let Some(selected) = selected {
+                explorer.navigate_to_path(selected);
+            }
+        }
+    }
+
+    fn notify_lsp_watched_files(&self, events: &[WatchEvent]) {
+        let Some(lsp) = &self.lsp else {
+            return;
+        };
+        for (language, registrations) in &self.lsp_file_watchers.0 {
+            let changes: Vec<FileEvent> = events
+                .iter()
+                .filter(|event| {
+                    let kind = match event.kind {
+                        WatchEventKind::Created => WatchKind::Create,
+                        WatchEventKind::Changed => WatchKind::Change,
+                        WatchEventKind::Deleted => WatchKind::Delete,
+                    };
+                    registrations
+                        .values()
+                        .flatten()
+                        .any(|watcher| watcher.matches(&event.path, kind, &self.working_dir))
+                })
+                .filter_map(|event| {
+                    let uri = url::Url::from_file_path(&event.path).ok()?;
+                    let typ = match event.kind {
+                        WatchEventKind::Created => FileChangeType::CREATED,
+                        WatchEventKind::Changed => FileChangeType::CHANGED,
+                        WatchEventKind::Deleted => FileChangeType::DELETED,
+                    };
+                    Some(FileEvent::new(uri.as_str().parse().ok()?, typ))
+                })
+                .collect();
+            if changes.is_empty() {
+                continue;
+            }
+            let Some(handle) = lsp.get_handle(language) else {
+                continue;
+            };
+            tracing::debug!(
+                "Sending {} watched file changes to {} server",
+                changes.len(),
+                language
+            );
+            if let Err(e) = handle.did_change_watched_files(changes) {
+                tracing::warn!("Failed to send watched file changes: {}", e);
+            }
+        }
+    }
+
+    /// Handle a language server registering file watchers
+    pub(super) fn handle_lsp_file_watchers_registered(
+        &mut self,
+        language: String,
+        id: String,
+        watchers: Vec<FileSystemWatcher>,
+    ) {
+        let watchers: Vec<LspFileWatcher> = watchers
+            .into_iter()
+            .filter_map(LspFileWatcher::new)
+            .collect();
+        tracing::debug!(
+            "{} server registered {} file watchers ({})",
+            language,
+            watchers.len(),
+            id
+        );
+        // The workspace is watched from the start; other directories on demand
+        if let Some(file_watcher) = &self.file_watcher {
+            for base in watchers.iter().filter_map(|w| w.base.as_ref()) {
+                if !base.starts_with(&self.working_dir) && !file_watcher.is_watched(base) {
+                    file_watcher.watch_tree(base);
+                }
+            }
+        }
+        self.lsp_file_watchers
+            .0
+            .entry(language)
+            .or_default()
+            .insert(id, watchers);
+    }
+
+    /// Handle a language server unregistering file watchers
+    pub(super) fn handle_lsp_file_watchers_unregistered(&mut self, language: String, id: String) {
+        if let Some(registrations) = self.lsp_file_watchers.0.get_mut(&language) {
+            registrations.remove(&id);
+        }
+    }
+
+    /// Forget the file watchers of a language server that is (re)starting
+    pub(super) fn clear_lsp_file_watchers(&mut self, language: &str) {
+        self.lsp_file_watchers.0.remove(language);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watcher(glob_pattern: GlobPattern, kind: Option<WatchKind>) -> LspFileWatcher {
+        LspFileWatcher::new(FileSystemWatcher { glob_pattern, kind }).unwrap()
+    }
+
+    #[test]
+    fn test_lsp_file_watcher_patterns() {
+        let workspace = Path::new("/work");
+        let any_rust = watcher(GlobPattern::String("**/*.rs".to_string()), None);
+        assert!(any_rust.matches(Path::new("/work/src/main.rs"), WatchKind::Change, workspace));
+        assert!(!any_rust.matches(Path::new("/work/Cargo.toml"), WatchKind::Change, workspace));
+
+        // Plain patterns may be relative to the workspace, `*` stays within a directory
+        let manifest = watcher(GlobPattern::String("*.toml".to_string()), None);
+        assert!(manifest.matches(Path::new("/work/Cargo.toml"), WatchKind::Create, workspace));
+        assert!(!manifest.matches(
+            Path::new("/work/a/Cargo.toml"),
+            WatchKind::Create,
+            workspace
+        ));
+
+        let relative = watcher(
+            GlobPattern::Relative(lsp_types::RelativePattern {
+                base_uri: OneOf::Right("file:///other".parse().unwrap()),
+                pattern: "{a,b}/*.json".to_string(),
+            }),
+            Some(WatchKind::Delete),
+        );
+        assert!(relative.matches(Path::new("/other/b/x.json"), WatchKind::Delete, workspace));
+        assert!(!relative.matches(Path::new("/other/b/x.json"), WatchKind::Create, workspace));
+        assert!(!relative.matches(Path::new("/work/b/x.json"), WatchKind::Delete, workspace));
+    }
+}
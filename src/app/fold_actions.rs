@@ -0,0 +1,219 @@
+//! Code folding actions for the Editor.
+//!
+//! Foldable ranges come from the language server (`textDocument/foldingRange`)
+//! when one is running for the buffer, and from the tree-sitter syntax tree
+//! otherwise. Folding through the language server is asynchronous: the action
+//! is remembered in `pending_folding_range_request` and completed when the
+//! response arrives.
+
+use rust_i18n::t;
+
+use crate::model::event::BufferId;
+use crate::primitives::folding::{innermost_range_at_line, tree_sitter_fold_ranges, FoldRange};
+use crate::state::EditorState;
+
+use super::Editor;
+
+/// Fold action waiting for folding ranges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum PendingFold {
+    /// Fold the innermost range at each cursor
+    AtCursors,
+    /// Fold every range in the buffer
+    All,
+}
+
+impl Editor {
+    /// Fold the innermost foldable range at each cursor
+    pub(super) fn fold_at_cursors(&mut self) {
+        self.request_fold(PendingFold::AtCursors);
+    }
+
+    /// Fold every foldable range in the active buffer
+    pub(super) fn fold_all(&mut self) {
+        self.request_fold(PendingFold::All);
+    }
+
+    /// Unfold the folds whose header is on a cursor line
+    pub(super) fn unfold_at_cursors(&mut self) {
+        let state = self.active_state_mut();
+        let header_lines: Vec<usize> = state
+            .cursors
+            .iter()
+            .map(|(_, cursor)| state.buffer.get_line_number(cursor.position))
+            .collect();
+
+        let mut unfolded = 0;
+        for line in header_lines {
+            if let Some(hidden_start) = state.buffer.line_start_offset(line + 1) {
+                unfolded += state
+                    .folds
+                    .unfold_starting_at(&mut state.marker_list, hidden_start);
+            }
+        }
+
+        if unfolded == 0 {
+            self.set_status_message(t!("fold.no_fold_at_cursor").to_string());
+        }
+    }
+
+    /// Unfold everything in the active buffer
+    pub(super) fn unfold_all(&mut self) {
+        let state = self.active_state_mut();
+        let count = state.folds.len();
+        state.folds.clear(&mut state.marker_list);
+        self.set_status_message(t!("fold.unfolded_all", count = count).to_string());
+    }
+
+    /// Get folding ranges for the active buffer and run `action` with them
+    fn request_fold(&mut self, action: PendingFold) {
+        let buffer_id = self.active_buffer();
+        let request_id = self.next_lsp_request_id;
+
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+                let result = handle.folding_range(request_id, uri.clone());
+                if let Err(e) = &result {
+                    tracing::debug!("Failed to request folding ranges: {}", e);
+                }
+                result.is_ok()
+            })
+            .unwrap_or(false);
+
+        if sent {
+            self.next_lsp_request_id += 1;
+            self.pending_folding_range_request = Some((request_id, buffer_id, action));
+        } else {
+            self.apply_fold(buffer_id, Vec::new(), action);
+        }
+    }
+
+    /// Handle LSP folding range response
+    pub(super) fn handle_lsp_folding_ranges(
+        &mut self,
+        request_id: u64,
+        uri: String,
+        ranges: Vec<lsp_types::FoldingRange>,
+    ) {
+        let Some((pending_id, buffer_id, action)) = self.pending_folding_range_request else {
+            return;
+        };
+        if pending_id != request_id {
+            tracing::debug!(
+                "Ignoring stale folding range response (request_id={})",
+                request_id
+            );
+            return;
+        }
+        self.pending_folding_range_request = None;
+
+        tracing::debug!(
+            "Received {} folding ranges for {} (request_id={})",
+            ranges.len(),
+            uri,
+            request_id
+        );
+
+        let ranges = ranges.iter().filter_map(FoldRange::from_lsp).collect();
+        self.apply_fold(buffer_id, ranges, action);
+    }
+
+    /// Fold `lsp_ranges` (or tree-sitter ranges when empty) in a buffer
+    fn apply_fold(&mut self, buffer_id: BufferId, lsp_ranges: Vec<FoldRange>, action: PendingFold) {
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+
+        let ranges = if lsp_ranges.is_empty() {
+            match state.highlighter.language() {
+                Some(language) => tree_sitter_fold_ranges(&state.buffer, language),
+                None => Vec::new(),
+            }
+        } else {
+            lsp_ranges
+        };
+        state.folds.set_ranges(ranges);
+
+        let folded = match action {
+            PendingFold::AtCursors => Self::fold_ranges_at_cursors(state),
+            PendingFold::All => Self::fold_all_ranges(state),
+        };
+        Self::move_cursors_out_of_folds(state);
+
+        if folded == 0 {
+            self.set_status_message(t!("fold.nothing_to_fold").to_string());
+        }
+    }
+
+    /// Fold the innermost not yet folded range at each cursor line
+    fn fold_ranges_at_cursors(state: &mut EditorState) -> usize {
+        let folded_headers: Vec<usize> = state
+            .folds
+            .spans(&state.marker_list)
+            .iter()
+            .map(|span| state.buffer.get_line_number(span.hidden.start - 1))
+            .collect();
+        let cursor_lines: Vec<usize> = state
+            .cursors
+            .iter()
+            .map(|(_, cursor)| state.buffer.get_line_number(cursor.position))
+            .collect();
+
+        let mut folded = 0;
+        for line in cursor_lines {
+            let Some(range) =
+                innermost_range_at_line(state.folds.ranges(), line, &folded_headers).cloned()
+            else {
+                continue;
+            };
+            folded += state
+                .folds
+                .fold_range(&mut state.marker_list, &state.buffer, &range)
+                as usize;
+        }
+        folded
+    }
+
+    /// Fold every range of the buffer
+    fn fold_all_ranges(state: &mut EditorState) -> usize {
+        let ranges = state.folds.ranges().to_vec();
+        ranges
+            .iter()
+            .filter(|range| {
+                state
+                    .folds
+                    .fold_range(&mut state.marker_list, &state.buffer, range)
+            })
+            .count()
+    }
+
+    /// Move cursors (and selection anchors) inside folds to the end of the fold header
+    fn move_cursors_out_of_folds(state: &mut EditorState) {
+        let hidden = state.folds.hidden_ranges(&state.marker_list);
+        if hidden.is_empty() {
+            return;
+        }
+        let buffer = &state.buffer;
+        let header_end = |pos: usize| {
+            hidden.iter().find(|r| r.contains(&pos)).map(|r| {
+                // Line ending of the header line (before "\r\n" or "\n")
+                let newline = r.start - 1;
+                if newline > 0 && buffer.slice_bytes(newline - 1..newline) == b"\r" {
+                    newline - 1
+                } else {
+                    newline
+                }
+            })
+        };
+        state.cursors.map(|cursor| {
+            if let Some(pos) = header_end(cursor.position) {
+                cursor.position = pos;
+                cursor.sticky_column = 0;
+            }
+            if let Some(pos) = cursor.anchor.and_then(header_end) {
+                cursor.anchor = Some(pos);
+            }
+        });
+        state.cursors.normalize();
+    }
+}
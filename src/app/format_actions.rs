@@ -0,0 +1,250 @@
+//! Formatting through the language server.
+//!
+//! Languages whose formatter `source` is "lsp" are formatted with
+//! `textDocument/formatting`, both by "Format Buffer" and on save. "Format
+//! Selection" always uses `textDocument/rangeFormatting` since command
+//! formatters can't format part of a file, and formatters with `on_type` set
+//! send `textDocument/onTypeFormatting` after each typed character.
+//!
+//! Responses arrive asynchronously. Edits are only applied if the buffer still
+//! has the content they were computed for, as one undo step.
+
+use std::sync::Arc;
+
+use lsp_types::{FormattingOptions, Position, Range};
+use rust_i18n::t;
+
+use crate::config::BufferConfig;
+use crate::model::event::BufferId;
+use crate::model::piece_tree::PieceTreeNode;
+use crate::services::lsp::manager::detect_language;
+
+use super::Editor;
+
+/// What a formatting request was made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FormatKind {
+    /// "Format Buffer"
+    Buffer,
+    /// "Format Selection"
+    Selection,
+    /// A trigger character was typed
+    OnType,
+    /// Format-on-save; the buffer is saved again once formatted
+    Save,
+}
+
+/// A formatting request waiting for the server
+#[derive(Debug)]
+pub(super) struct PendingFormat {
+    buffer_id: BufferId,
+    kind: FormatKind,
+    /// Content the request was made against
+    content: Arc<PieceTreeNode>,
+}
+
+impl Editor {
+    /// Ask the language server to format the active buffer or its selection
+    ///
+    /// Returns an error message if the request couldn't be sent.
+    pub(super) fn request_lsp_format(&mut self, kind: FormatKind) -> Result<(), String> {
+        let buffer_id = self.active_buffer();
+        let request_id = self.next_lsp_request_id;
+        let options = self.lsp_formatting_options(buffer_id);
+
+        let state = self.active_state();
+        let range = if kind == FormatKind::Selection {
+            let Some(selection) = state.cursors.primary().selection_range() else {
+                return Err(t!("format.no_selection").to_string());
+            };
+            let (start_line, start_char) = state.buffer.position_to_lsp_position(selection.start);
+            let (end_line, end_char) = state.buffer.position_to_lsp_position(selection.end);
+            Some(Range::new(
+                Position::new(start_line as u32, start_char as u32),
+                Position::new(end_line as u32, end_char as u32),
+            ))
+        } else {
+            None
+        };
+        let content = state.buffer.content_root();
+
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| match range {
+                Some(range) => handle.range_formatting(request_id, uri.clone(), range, options),
+                None => handle.formatting(request_id, uri.clone(), options),
+            })
+            .unwrap_or_else(|| Err(t!("format.lsp_unavailable").to_string()));
+        sent?;
+
+        self.next_lsp_request_id += 1;
+        self.pending_format_requests.insert(
+            request_id,
+            PendingFormat {
+                buffer_id,
+                kind,
+                content,
+            },
+        );
+        self.lsp_status = "LSP: formatting...".to_string();
+        Ok(())
+    }
+
+    /// Format the selection through the language server
+    pub(super) fn format_selection(&mut self) -> Result<(), String> {
+        self.request_lsp_format(FormatKind::Selection)
+    }
+
+    /// Let the language server reformat around a character just typed
+    ///
+    /// Only done for languages whose formatter has `on_type` set; the LSP task
+    /// drops characters that are not one of the server's triggers.
+    pub(super) fn request_on_type_formatting(&mut self, ch: char) {
+        let buffer_id = self.active_buffer();
+        let enabled = self
+            .buffer_language(buffer_id)
+            .and_then(|language| self.config.languages.get(&language))
+            .and_then(|lang_config| lang_config.formatter.as_ref())
+            .is_some_and(|formatter| formatter.on_type);
+        if !enabled {
+            return;
+        }
+
+        let request_id = self.next_lsp_request_id;
+        let options = self.lsp_formatting_options(buffer_id);
+        let state = self.active_state();
+        let (line, character) = state
+            .buffer
+            .position_to_lsp_position(state.cursors.primary().position);
+        let content = state.buffer.content_root();
+
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+                handle
+                    .on_type_formatting(
+                        request_id,
+                        uri.clone(),
+                        line as u32,
+                        character as u32,
+                        ch.to_string(),
+                        options,
+                    )
+                    .is_ok()
+            })
+            .unwrap_or(false);
+        if sent {
+            self.next_lsp_request_id += 1;
+            self.pending_format_requests.insert(
+                request_id,
+                PendingFormat {
+                    buffer_id,
+                    kind: FormatKind::OnType,
+                    content,
+                },
+            );
+        }
+    }
+
+    /// Handle the edits returned by a formatting request
+    pub(super) fn handle_lsp_formatting(
+        &mut self,
+        request_id: u64,
+        result: Result<Vec<lsp_types::TextEdit>, String>,
+    ) {
+        let Some(pending) = self.pending_format_requests.remove(&request_id) else {
+            tracing::debug!("Ignoring unknown formatting response: {}", request_id);
+            return;
+        };
+        if pending.kind != FormatKind::OnType {
+            self.lsp_status.clear();
+        }
+
+        let edits = match result {
+            Ok(edits) => edits,
+            Err(e) => {
+                tracing::debug!("Formatting request {} failed: {}", request_id, e);
+                if pending.kind != FormatKind::OnType {
+                    self.set_status_message(t!("error.format_failed", error = e).to_string());
+                }
+                return;
+            }
+        };
+
+        let unchanged = self
+            .buffers
+            .get(&pending.buffer_id)
+            .is_some_and(|state| Arc::ptr_eq(&state.buffer.content_root(), &pending.content));
+        if !unchanged {
+            tracing::debug!(
+                "Dropping formatting edits for changed buffer {:?}",
+                pending.buffer_id
+            );
+            if matches!(pending.kind, FormatKind::Buffer | FormatKind::Selection) {
+                self.set_status_message(t!("format.buffer_changed").to_string());
+            }
+            return;
+        }
+
+        let changes = match self.apply_lsp_text_edits(pending.buffer_id, edits, "LSP Format") {
+            Ok(changes) => changes,
+            Err(e) => {
+                self.set_status_message(
+                    t!("error.format_failed", error = e.to_string()).to_string(),
+                );
+                return;
+            }
+        };
+
+        match pending.kind {
+            FormatKind::Buffer | FormatKind::Selection => {
+                self.set_status_message(t!("format.formatted_by_lsp").to_string());
+            }
+            FormatKind::OnType => {}
+            FormatKind::Save => {
+                if changes > 0 {
+                    if let Err(e) = self.resave_formatted_buffer(pending.buffer_id) {
+                        self.set_status_message(e);
+                        return;
+                    }
+                    self.set_status_message(t!("status.file_saved_with_actions").to_string());
+                }
+            }
+        }
+    }
+
+    /// Save a buffer again after format-on-save edits arrived
+    fn resave_formatted_buffer(&mut self, buffer_id: BufferId) -> Result<(), String> {
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return Ok(());
+        };
+        if let Err(e) = state.buffer.save() {
+            return Err(format!("Failed to re-save after format: {}", e));
+        }
+        let path = state.buffer.file_path().map(|p| p.to_path_buf());
+        if let Some(event_log) = self.event_logs.get_mut(&buffer_id) {
+            event_log.mark_saved();
+        }
+        if let Some(path) = path {
+            if let Ok(mtime) = std::fs::metadata(&path).and_then(|m| m.modified()) {
+                self.file_mod_times.insert(path, mtime);
+            }
+        }
+        Ok(())
+    }
+
+    /// Language of a buffer, detected from its file path
+    fn buffer_language(&self, buffer_id: BufferId) -> Option<String> {
+        let path = self.buffer_metadata.get(&buffer_id)?.file_path()?;
+        detect_language(path, &self.config.languages)
+    }
+
+    /// Indentation settings sent along with formatting requests
+    fn lsp_formatting_options(&self, buffer_id: BufferId) -> FormattingOptions {
+        let language = self.buffer_language(buffer_id);
+        let buffer_config = BufferConfig::resolve(&self.config, language.as_deref());
+        FormattingOptions {
+            tab_size: buffer_config.tab_size as u32,
+            insert_spaces: !buffer_config.use_tabs,
+            ..Default::default()
+        }
+    }
+}
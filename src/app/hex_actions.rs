@@ -0,0 +1,372 @@
+//! Hex view actions for the Editor.
+//!
+//! "Toggle Hex View" shows the active buffer's bytes (see `view::hex_view`).
+//! While it is shown, movement goes by bytes and rows, typing overwrites the
+//! nibble under the cursor (or the byte, in the ASCII column), and Go to Line
+//! and Search take offsets and byte patterns instead. Each overwritten byte
+//! is one undo step; the rest of the file never moves, so saving streams the
+//! unchanged parts of large files from disk as usual.
+
+use rust_i18n::t;
+
+use crate::input::keybindings::{Action, KeyContext};
+use crate::model::event::Event;
+use crate::view::hex_view::{self, BYTES_PER_ROW, HEX_COLUMN_WIDTH};
+use crate::view::prompt::PromptType;
+use crate::view::ui::HexViewRenderer;
+
+use super::Editor;
+
+/// Rows assumed when the active split has no viewport yet
+const DEFAULT_VIEWPORT_HEIGHT: usize = 24;
+
+impl Editor {
+    /// Switch the active buffer between its text and its hex view
+    pub fn toggle_hex_view(&mut self) {
+        let state = self.active_state_mut();
+        let message = if state.hex_view.is_some() {
+            state.hide_hex_view();
+            t!("hex.hidden")
+        } else {
+            state.show_hex_view();
+            t!("hex.shown")
+        };
+        self.sync_editor_state_to_split_view_state();
+        self.scroll_hex_view_to_cursor();
+        self.set_status_message(message.to_string());
+    }
+
+    /// Handle an action in the hex view of the active buffer
+    ///
+    /// Returns false for actions the hex view leaves to the normal handling.
+    pub(super) fn handle_hex_action(&mut self, action: &Action) -> bool {
+        if self.active_state().hex_view.is_none()
+            || self.is_prompting()
+            || self.key_context != KeyContext::Normal
+        {
+            return false;
+        }
+        let len = self.active_state().buffer.len();
+        let cursor = self.active_state().cursors.primary().position;
+        let page = self.hex_viewport_height().saturating_sub(1).max(1) * BYTES_PER_ROW;
+        let row_start = cursor - cursor % BYTES_PER_ROW;
+
+        match action {
+            Action::MoveLeft | Action::SelectLeft | Action::DeleteBackward => {
+                self.move_hex_cursor(cursor.saturating_sub(1))
+            }
+            Action::MoveRight | Action::SelectRight => self.move_hex_cursor(cursor + 1),
+            Action::MoveUp | Action::SelectUp => {
+                self.move_hex_cursor(cursor.saturating_sub(BYTES_PER_ROW))
+            }
+            Action::MoveDown | Action::SelectDown => {
+                // Stay put on the last row rather than jumping to the last byte
+                if cursor + BYTES_PER_ROW < len {
+                    self.move_hex_cursor(cursor + BYTES_PER_ROW);
+                }
+            }
+            Action::MoveWordLeft | Action::SelectWordLeft => {
+                self.move_hex_cursor(cursor.saturating_sub(BYTES_PER_ROW / 2))
+            }
+            Action::MoveWordRight | Action::SelectWordRight => {
+                self.move_hex_cursor(cursor + BYTES_PER_ROW / 2)
+            }
+            Action::MoveLineStart | Action::SelectLineStart | Action::SmartHome => {
+                self.move_hex_cursor(row_start)
+            }
+            Action::MoveLineEnd | Action::SelectLineEnd => {
+                self.move_hex_cursor(row_start + BYTES_PER_ROW - 1)
+            }
+            Action::MovePageUp | Action::SelectPageUp => {
+                self.move_hex_cursor(cursor.saturating_sub(page))
+            }
+            Action::MovePageDown | Action::SelectPageDown => self.move_hex_cursor(cursor + page),
+            Action::MoveDocumentStart | Action::SelectDocumentStart => self.move_hex_cursor(0),
+            Action::MoveDocumentEnd | Action::SelectDocumentEnd => self.move_hex_cursor(len),
+            Action::ScrollUp => self.scroll_hex_view(-1),
+            Action::ScrollDown => self.scroll_hex_view(1),
+            Action::InsertChar(c) => self.type_in_hex_view(*c),
+            Action::InsertTab => {
+                if let Some(hex) = self.active_state_mut().hex_view.as_mut() {
+                    hex.ascii_column = !hex.ascii_column;
+                    hex.low_nibble = false;
+                }
+            }
+            Action::GotoLine => self.start_prompt(
+                t!("hex.goto_offset_prompt").to_string(),
+                PromptType::HexGotoOffset,
+            ),
+            Action::Search => {
+                self.start_prompt(t!("hex.search_prompt").to_string(), PromptType::HexSearch)
+            }
+            Action::FindNext => self.find_in_hex_view(false),
+            Action::FindPrevious => self.find_in_hex_view(true),
+            // Text edits would shift the bytes after them
+            Action::InsertNewline
+            | Action::DeleteForward
+            | Action::DeleteWordBackward
+            | Action::DeleteWordForward
+            | Action::DeleteLine
+            | Action::DeleteToLineEnd
+            | Action::DeleteToLineStart
+            | Action::TransposeChars
+            | Action::OpenLine
+            | Action::Cut
+            | Action::Paste
+            | Action::DedentSelection
+            | Action::ToggleComment
+            | Action::InsertSnippet
+            | Action::FormatBuffer
+            | Action::FormatSelection
+            | Action::AddCursorAbove
+            | Action::AddCursorBelow
+            | Action::AddCursorNextMatch
+            | Action::SelectAll
+            | Action::SelectWord
+            | Action::SelectLine
+            | Action::ExpandSelection
+            | Action::Replace
+            | Action::QueryReplace
+            | Action::ShellCommandReplace
+            | Action::ToUpperCase
+            | Action::ToLowerCase
+            | Action::SetLineEnding
+            | Action::SaveWithEncoding
+            | Action::LspCompletion
+            | Action::LspRename
+            | Action::LspCodeActions => {
+                self.set_status_message(t!("hex.text_only").to_string());
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Scroll the active buffer's hex view by `delta` rows (mouse wheel)
+    pub(super) fn scroll_hex_view(&mut self, delta: isize) {
+        let state = self.active_state_mut();
+        let rows = hex_view::row_count(state.buffer.len());
+        if let Some(hex) = state.hex_view.as_mut() {
+            hex.top_row = hex
+                .top_row
+                .saturating_add_signed(delta)
+                .min(rows.saturating_sub(1));
+        }
+    }
+
+    /// Move the cursor to the byte clicked in the hex view
+    pub(super) fn handle_hex_click(
+        &mut self,
+        col: u16,
+        row: u16,
+        content_rect: ratatui::layout::Rect,
+    ) {
+        let state = self.active_state();
+        let Some(hex) = state.hex_view.as_ref() else {
+            return;
+        };
+        let len = state.buffer.len();
+        let row_offset =
+            (hex.top_row + row.saturating_sub(content_rect.y) as usize) * BYTES_PER_ROW;
+        let column = col.saturating_sub(content_rect.x) as usize;
+
+        let hex_start = HexViewRenderer::hex_start(len);
+        let ascii_start = HexViewRenderer::ascii_start(len);
+        let target = if column >= ascii_start {
+            let index = (column - ascii_start).min(BYTES_PER_ROW - 1);
+            Some((row_offset + index, true, false))
+        } else if column >= hex_start && column < hex_start + HEX_COLUMN_WIDTH {
+            let in_hex = column - hex_start;
+            (0..BYTES_PER_ROW)
+                .rev()
+                .find(|&i| hex_view::hex_column(i) <= in_hex)
+                .map(|i| (row_offset + i, false, in_hex - hex_view::hex_column(i) == 1))
+        } else {
+            None
+        };
+
+        if let Some((offset, ascii_column, low_nibble)) = target {
+            self.move_hex_cursor(offset);
+            if let Some(hex) = self.active_state_mut().hex_view.as_mut() {
+                hex.ascii_column = ascii_column;
+                hex.low_nibble = low_nibble;
+            }
+        }
+    }
+
+    /// Handle the input of the go to offset prompt
+    pub(super) fn handle_hex_goto_offset(&mut self, input: &str) {
+        let cursor = self.active_state().cursors.primary().position;
+        let len = self.active_state().buffer.len();
+        match hex_view::parse_offset(input, cursor) {
+            Some(offset) if offset < len.max(1) => {
+                self.move_hex_cursor(offset);
+                self.set_status_message(
+                    t!("hex.jumped", offset = format!("0x{:X}", offset)).to_string(),
+                );
+            }
+            Some(_) => self.set_status_message(t!("hex.offset_past_end").to_string()),
+            None => self.set_status_message(t!("hex.invalid_offset", input = input).to_string()),
+        }
+    }
+
+    /// Handle the input of the byte search prompt
+    pub(super) fn handle_hex_search(&mut self, input: &str) {
+        let Some(pattern) = hex_view::parse_search_pattern(input) else {
+            return;
+        };
+        if let Some(hex) = self.active_state_mut().hex_view.as_mut() {
+            hex.last_search = Some(pattern);
+        }
+        self.find_in_hex_view(false);
+    }
+
+    /// Move the cursor to the next (or previous) match of the last search,
+    /// wrapping around the ends of the buffer
+    fn find_in_hex_view(&mut self, backward: bool) {
+        let state = self.active_state();
+        let Some(pattern) = state.hex_view.as_ref().and_then(|h| h.last_search.clone()) else {
+            self.set_status_message(t!("hex.no_search").to_string());
+            return;
+        };
+        let buffer = &state.buffer;
+        let cursor = state.cursors.primary().position;
+        let len = buffer.len();
+
+        let first = if backward {
+            buffer.find_bytes(
+                &pattern,
+                0..(cursor + pattern.len()).saturating_sub(1),
+                true,
+            )
+        } else {
+            buffer.find_bytes(&pattern, cursor + 1..len, false)
+        };
+        let found = match first {
+            Ok(None) => buffer.find_bytes(&pattern, 0..len, backward),
+            other => other,
+        };
+
+        match found {
+            Ok(Some(offset)) => {
+                self.move_hex_cursor(offset);
+                self.set_status_message(
+                    t!("hex.found", offset = format!("0x{:X}", offset)).to_string(),
+                );
+            }
+            Ok(None) => self.set_status_message(t!("hex.not_found").to_string()),
+            Err(e) => {
+                self.set_status_message(t!("hex.read_failed", error = e.to_string()).to_string())
+            }
+        }
+    }
+
+    /// Overwrite the nibble (or, in the ASCII column, the byte) under the cursor
+    fn type_in_hex_view(&mut self, c: char) {
+        if self.is_editing_disabled() {
+            self.set_status_message(t!("buffer.editing_disabled").to_string());
+            return;
+        }
+        let state = self.active_state();
+        let Some(hex) = state.hex_view.as_ref() else {
+            return;
+        };
+        let (ascii_column, low_nibble) = (hex.ascii_column, hex.low_nibble);
+        let cursor = state.cursors.primary().position;
+        let len = state.buffer.len();
+        if cursor >= len {
+            return;
+        }
+
+        let byte = if ascii_column {
+            if !c.is_ascii() {
+                self.set_status_message(t!("hex.not_ascii").to_string());
+                return;
+            }
+            c as u8
+        } else {
+            let Some(digit) = c.to_digit(16) else {
+                self.set_status_message(t!("hex.not_hex_digit").to_string());
+                return;
+            };
+            let old = match state.buffer.read_range(cursor, 1) {
+                Ok(bytes) => bytes[0],
+                Err(e) => {
+                    self.set_status_message(
+                        t!("hex.read_failed", error = e.to_string()).to_string(),
+                    );
+                    return;
+                }
+            };
+            hex_view::set_nibble(old, digit as u8, low_nibble)
+        };
+        self.overwrite_byte(cursor, byte);
+
+        // The high nibble is followed by the low one, the low one by the next byte
+        if !ascii_column && !low_nibble {
+            if let Some(hex) = self.active_state_mut().hex_view.as_mut() {
+                hex.low_nibble = true;
+            }
+        } else if cursor + 1 < len {
+            self.move_hex_cursor(cursor + 1);
+        }
+    }
+
+    /// Replace the byte at `offset` as one undo step
+    fn overwrite_byte(&mut self, offset: usize, byte: u8) {
+        let state = self.active_state_mut();
+        let cursors: Vec<_> = state
+            .cursors
+            .iter()
+            .map(|(id, c)| (id, c.position, c.anchor))
+            .collect();
+        let old_tree = state.buffer.snapshot_piece_tree();
+        state.buffer.delete_bytes(offset, 1);
+        state.buffer.insert_bytes(offset, vec![byte]);
+        let new_tree = state.buffer.snapshot_piece_tree();
+
+        let bulk_edit = Event::BulkEdit {
+            old_tree: Some(old_tree),
+            new_tree: Some(new_tree),
+            old_cursors: cursors.clone(),
+            new_cursors: cursors,
+            description: "Hex edit".to_string(),
+        };
+        self.sync_editor_state_to_split_view_state();
+        self.invalidate_layouts_for_buffer(self.active_buffer());
+        self.active_event_log_mut().append(bulk_edit);
+    }
+
+    /// Put the cursor on the byte at `offset` (clamped to the last byte),
+    /// at its high nibble, and scroll it into view
+    fn move_hex_cursor(&mut self, offset: usize) {
+        let state = self.active_state_mut();
+        let offset = offset.min(state.buffer.len().saturating_sub(1));
+        let cursor = state.cursors.primary_mut();
+        cursor.position = offset;
+        cursor.anchor = None;
+        if let Some(hex) = state.hex_view.as_mut() {
+            hex.low_nibble = false;
+        }
+        self.sync_editor_state_to_split_view_state();
+        self.scroll_hex_view_to_cursor();
+    }
+
+    /// Scroll the active buffer's hex view so the cursor's row is visible
+    fn scroll_hex_view_to_cursor(&mut self) {
+        let height = self.hex_viewport_height();
+        let state = self.active_state_mut();
+        let cursor = state.cursors.primary().position;
+        if let Some(hex) = state.hex_view.as_mut() {
+            hex.scroll_to(cursor, height);
+        }
+    }
+
+    /// Rows of bytes the active split shows
+    fn hex_viewport_height(&self) -> usize {
+        self.split_view_states
+            .get(&self.split_manager.active_split())
+            .map(|vs| vs.viewport.height as usize)
+            .unwrap_or(DEFAULT_VIEWPORT_HEIGHT)
+    }
+}
@@ -0,0 +1,436 @@
+//! Call and type hierarchy actions for the Editor.
+//!
+//! "Show Call Hierarchy" and "Show Type Hierarchy" prepare a hierarchy item at
+//! the cursor and show it in the `*Hierarchy*` panel as an expandable tree
+//! (see `primitives::hierarchy`). Children are requested from the language
+//! server when a node is first expanded. Every node line carries a "node" text
+//! property so the panel can map its cursor back to the tree.
+
+use rust_i18n::t;
+
+use crate::model::event::{BufferId, SplitDirection, SplitId};
+use crate::primitives::hierarchy::{
+    HierarchyChild, HierarchyDirection, HierarchyItem, HierarchyToggle, HierarchyTree,
+};
+use crate::primitives::outline::kind_label;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::view::split::SplitViewState;
+
+use super::{uri_to_path, Editor};
+
+/// Name of the hierarchy panel buffer
+pub(super) const HIERARCHY_BUFFER_NAME: &str = "*Hierarchy*";
+
+/// What an outstanding hierarchy request is for
+#[derive(Debug, Clone)]
+pub(super) enum PendingHierarchy {
+    /// Items at the cursor, to be shown in `direction`
+    Prepare {
+        direction: HierarchyDirection,
+        language: String,
+    },
+    /// Children of a node of the panel's tree
+    Children(usize),
+}
+
+/// State of the hierarchy panel
+#[derive(Debug)]
+pub(super) struct HierarchyPanel {
+    /// The `*Hierarchy*` virtual buffer
+    buffer_id: BufferId,
+    /// Split showing the hierarchy buffer
+    split_id: SplitId,
+    /// Split the hierarchy was requested from, where jumps are shown
+    source_split: SplitId,
+    /// Language server the items come from
+    language: String,
+    tree: HierarchyTree,
+}
+
+impl Editor {
+    /// Show the hierarchy of the symbol under the cursor
+    pub(super) fn start_hierarchy(&mut self, direction: HierarchyDirection) {
+        let state = self.active_state();
+        let (line, character) = state
+            .buffer
+            .position_to_lsp_position(state.cursors.primary().position);
+        let buffer_id = self.active_buffer();
+        let request_id = self.next_lsp_request_id;
+
+        let language = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, language| {
+                match handle.prepare_hierarchy(
+                    request_id,
+                    direction,
+                    uri.clone(),
+                    line as u32,
+                    character as u32,
+                ) {
+                    Ok(()) => Some(language.to_string()),
+                    Err(e) => {
+                        tracing::debug!("Failed to prepare hierarchy: {}", e);
+                        None
+                    }
+                }
+            })
+            .flatten();
+
+        match language {
+            Some(language) => {
+                self.next_lsp_request_id += 1;
+                self.pending_hierarchy_request = Some((
+                    request_id,
+                    PendingHierarchy::Prepare {
+                        direction,
+                        language,
+                    },
+                ));
+            }
+            None => self.set_status_message(t!("lsp.no_server_active").to_string()),
+        }
+    }
+
+    /// Handle LSP hierarchy prepare response
+    pub(super) fn handle_lsp_hierarchy_items(
+        &mut self,
+        request_id: u64,
+        items: Vec<HierarchyItem>,
+    ) {
+        let Some((
+            pending_id,
+            PendingHierarchy::Prepare {
+                direction,
+                language,
+            },
+        )) = self.pending_hierarchy_request.clone()
+        else {
+            return;
+        };
+        if pending_id != request_id {
+            tracing::debug!(
+                "Ignoring stale hierarchy response (request_id={})",
+                request_id
+            );
+            return;
+        }
+        self.pending_hierarchy_request = None;
+
+        if items.is_empty() {
+            self.set_status_message(t!("hierarchy.no_item").to_string());
+            return;
+        }
+
+        let tree = HierarchyTree::new(direction, items);
+        let is_open = self.hierarchy_panel_is_open();
+        match &mut self.hierarchy_panel {
+            Some(panel) if is_open => {
+                panel.tree = tree;
+                panel.language = language;
+                let split_id = panel.split_id;
+                self.save_current_split_view_state();
+                self.split_manager.set_active_split(split_id);
+                self.restore_current_split_view_state();
+            }
+            _ => self.open_hierarchy_panel(tree, language),
+        }
+        self.fill_hierarchy_panel(0);
+        self.request_hierarchy_children(0);
+    }
+
+    /// Handle LSP response listing the children of a node
+    pub(super) fn handle_lsp_hierarchy_children(
+        &mut self,
+        request_id: u64,
+        children: Vec<HierarchyChild>,
+    ) {
+        let Some((pending_id, PendingHierarchy::Children(node))) =
+            self.pending_hierarchy_request.clone()
+        else {
+            return;
+        };
+        if pending_id != request_id {
+            tracing::debug!(
+                "Ignoring stale hierarchy children response (request_id={})",
+                request_id
+            );
+            return;
+        }
+        self.pending_hierarchy_request = None;
+
+        let Some(panel) = &mut self.hierarchy_panel else {
+            return;
+        };
+        panel.tree.set_children(node, children);
+        self.fill_hierarchy_panel(node);
+    }
+
+    /// Expand or collapse the node under the panel cursor
+    pub(super) fn hierarchy_toggle(&mut self) {
+        let Some(node) = self.hierarchy_node_at_cursor() else {
+            return;
+        };
+        let Some(panel) = &mut self.hierarchy_panel else {
+            return;
+        };
+        match panel.tree.toggle(node) {
+            HierarchyToggle::NeedsChildren => self.request_hierarchy_children(node),
+            HierarchyToggle::Toggled => self.fill_hierarchy_panel(node),
+            HierarchyToggle::Leaf => {}
+        }
+    }
+
+    /// Switch between callers and callees, or supertypes and subtypes
+    pub(super) fn hierarchy_flip_direction(&mut self) {
+        let active_buffer = self.active_buffer();
+        let Some(panel) = &mut self.hierarchy_panel else {
+            return;
+        };
+        if active_buffer != panel.buffer_id {
+            return;
+        }
+        panel.tree = HierarchyTree::new(panel.tree.direction.flipped(), panel.tree.root_items());
+        // Drop the children of the previous tree still being fetched
+        if matches!(
+            self.pending_hierarchy_request,
+            Some((_, PendingHierarchy::Children(_)))
+        ) {
+            self.pending_hierarchy_request = None;
+        }
+        self.fill_hierarchy_panel(0);
+        self.request_hierarchy_children(0);
+    }
+
+    /// Jump to the call site (or the definition) of the node under the panel cursor
+    pub(super) fn hierarchy_jump(&mut self) {
+        let Some(node) = self.hierarchy_node_at_cursor() else {
+            return;
+        };
+        let Some(panel) = &self.hierarchy_panel else {
+            return;
+        };
+        let Some(target) = panel.tree.node(node).map(|node| node.target()) else {
+            return;
+        };
+        let (panel_split, source_split) = (panel.split_id, panel.source_split);
+
+        let Ok(path) = uri_to_path(&target.uri) else {
+            self.set_status_message(t!("lsp.cannot_open_definition").to_string());
+            return;
+        };
+
+        // Show the target next to the panel rather than in it
+        let source_split = if source_split != panel_split
+            && self.split_manager.get_buffer_id(source_split).is_some()
+        {
+            Some(source_split)
+        } else {
+            None
+        };
+        if let Some(split) = source_split {
+            self.save_current_split_view_state();
+            self.split_manager.set_active_split(split);
+            self.restore_current_split_view_state();
+        }
+
+        if let Err(e) = self.open_file(&path) {
+            self.set_status_message(t!("file.error_opening", error = e.to_string()).to_string());
+            return;
+        }
+        let position = self.active_state().buffer.lsp_position_to_byte(
+            target.range.start.line as usize,
+            target.range.start.character as usize,
+        );
+        self.goto_symbol_position(position);
+    }
+
+    /// Close the hierarchy panel
+    pub(super) fn close_hierarchy_panel(&mut self) {
+        let Some(panel) = self.hierarchy_panel.take() else {
+            return;
+        };
+        self.pending_hierarchy_request = None;
+        let was_active = self.split_manager.active_split() == panel.split_id;
+        if self.split_manager.close_split(panel.split_id).is_ok() {
+            self.split_view_states.remove(&panel.split_id);
+            if was_active {
+                self.sync_split_view_state_to_editor_state();
+            }
+        }
+        let _ = self.close_buffer(panel.buffer_id);
+    }
+
+    /// Whether the panel's split still shows the hierarchy buffer
+    fn hierarchy_panel_is_open(&self) -> bool {
+        self.hierarchy_panel.as_ref().is_some_and(|panel| {
+            self.buffers.contains_key(&panel.buffer_id)
+                && self.split_manager.get_buffer_id(panel.split_id) == Some(panel.buffer_id)
+        })
+    }
+
+    /// Create the hierarchy buffer in a split below the active one and focus it
+    fn open_hierarchy_panel(&mut self, tree: HierarchyTree, language: String) {
+        // Forget a panel whose split was closed through other means
+        if let Some(panel) = self.hierarchy_panel.take() {
+            if self
+                .split_manager
+                .splits_for_buffer(panel.buffer_id)
+                .is_empty()
+            {
+                let _ = self.close_buffer(panel.buffer_id);
+            }
+        }
+
+        let source_split = self.split_manager.active_split();
+        let buffer_id = self.create_virtual_buffer(
+            HIERARCHY_BUFFER_NAME.to_string(),
+            "hierarchy".to_string(),
+            true,
+        );
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = true;
+            state.margins.set_line_numbers(false);
+        }
+
+        self.save_current_split_view_state();
+        let split_id =
+            match self
+                .split_manager
+                .split_active(SplitDirection::Horizontal, buffer_id, 0.6)
+            {
+                Ok(split_id) => split_id,
+                Err(e) => {
+                    let _ = self.close_buffer(buffer_id);
+                    self.set_status_message(t!("split.error", error = e.to_string()).to_string());
+                    return;
+                }
+            };
+        let mut view_state =
+            SplitViewState::with_buffer(self.terminal_width, self.terminal_height, buffer_id);
+        view_state.viewport.line_wrap_enabled = false;
+        self.split_view_states.insert(split_id, view_state);
+        self.restore_current_split_view_state();
+
+        self.hierarchy_panel = Some(HierarchyPanel {
+            buffer_id,
+            split_id,
+            source_split,
+            language,
+            tree,
+        });
+    }
+
+    /// Ask the language server for the children of a node
+    fn request_hierarchy_children(&mut self, node: usize) {
+        let Some(panel) = &self.hierarchy_panel else {
+            return;
+        };
+        let Some(item) = panel.tree.node(node).map(|node| node.item.clone()) else {
+            return;
+        };
+        let direction = panel.tree.direction;
+        let request_id = self.next_lsp_request_id;
+
+        let Some(handle) = self
+            .lsp
+            .as_ref()
+            .and_then(|lsp| lsp.get_handle(&panel.language))
+        else {
+            self.set_status_message(t!("lsp.no_server_active").to_string());
+            return;
+        };
+        match handle.hierarchy_children(request_id, direction, item) {
+            Ok(()) => {
+                self.next_lsp_request_id += 1;
+                self.pending_hierarchy_request =
+                    Some((request_id, PendingHierarchy::Children(node)));
+            }
+            Err(e) => tracing::debug!("Failed to request hierarchy children: {}", e),
+        }
+    }
+
+    /// Node on the line of the panel cursor
+    fn hierarchy_node_at_cursor(&self) -> Option<usize> {
+        let panel = self.hierarchy_panel.as_ref()?;
+        if self.active_buffer() != panel.buffer_id {
+            return None;
+        }
+        let state = self.active_state();
+        state
+            .text_properties
+            .get_at(state.cursors.primary().position)
+            .iter()
+            .find_map(|prop| prop.get_as::<usize>("node"))
+    }
+
+    /// Render the tree into the panel and put the cursor on `current`
+    fn fill_hierarchy_panel(&mut self, current: usize) {
+        let Some(panel) = &self.hierarchy_panel else {
+            return;
+        };
+        let (buffer_id, split_id) = (panel.buffer_id, panel.split_id);
+        let tree = &panel.tree;
+
+        let header = match tree.direction {
+            HierarchyDirection::IncomingCalls => t!("hierarchy.incoming_calls"),
+            HierarchyDirection::OutgoingCalls => t!("hierarchy.outgoing_calls"),
+            HierarchyDirection::Supertypes => t!("hierarchy.supertypes"),
+            HierarchyDirection::Subtypes => t!("hierarchy.subtypes"),
+        };
+        let mut entries = vec![TextPropertyEntry::text(format!("{}\n", header))];
+        let visible = tree.visible();
+        for &index in &visible {
+            let Some(node) = tree.node(index) else {
+                continue;
+            };
+            let marker = match &node.children {
+                None => "▸",
+                Some(children) if children.is_empty() => " ",
+                Some(_) if node.expanded => "▾",
+                Some(_) => "▸",
+            };
+            let target = node.target();
+            let path = match uri_to_path(&target.uri) {
+                Ok(path) => path
+                    .strip_prefix(&self.working_dir)
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|_| path.display().to_string()),
+                Err(_) => target.uri.as_str().to_string(),
+            };
+            let detail = node
+                .item
+                .detail
+                .as_deref()
+                .unwrap_or_else(|| kind_label(node.item.kind));
+            entries.push(
+                TextPropertyEntry::text(format!(
+                    "{}{} {}  {}  {}:{}\n",
+                    "  ".repeat(node.depth),
+                    marker,
+                    node.item.name,
+                    detail,
+                    path,
+                    target.range.start.line + 1
+                ))
+                .with_property("node", serde_json::json!(index)),
+            );
+        }
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to set hierarchy content: {}", e);
+            return;
+        }
+
+        // Line 0 is the header
+        let line = visible.iter().position(|&i| i == current).unwrap_or(0) + 1;
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        let position = state.buffer.line_start_offset(line).unwrap_or(0);
+        let cursor = state.cursors.primary_mut();
+        cursor.position = position;
+        cursor.anchor = None;
+        cursor.sticky_column = 0;
+        if let Some(view_state) = self.split_view_states.get_mut(&split_id) {
+            view_state.cursors = state.cursors.clone();
+        }
+    }
+}
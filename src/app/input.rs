@@ -1,4 +1,7 @@
+use super::diff_actions::DiffSide;
 use super::*;
+use crate::model::merge::Resolution;
+use crate::primitives::hierarchy::HierarchyDirection;
 use crate::services::plugins::hooks::HookArgs;
 use rust_i18n::t;
 impl Editor {
@@ -21,6 +24,50 @@ impl Editor {
         }
     }
 
+    /// Values of the context keys that keybinding `when` clauses check
+    pub fn get_when_context(&self) -> crate::input::when_clause::WhenContext {
+        use crate::input::when_clause::{keys, WhenContext};
+
+        let state = self.active_state();
+        let mut values = WhenContext::new();
+        values
+            .set_flag(
+                keys::EDITOR_HAS_SELECTION,
+                state
+                    .cursors
+                    .iter()
+                    .any(|(_, cursor)| cursor.selection_range().is_some()),
+            )
+            .set_flag(keys::MULTI_CURSOR, state.cursors.count() > 1)
+            .set_flag(
+                keys::READ_ONLY,
+                self.is_active_buffer_read_only() || self.is_editing_disabled(),
+            )
+            .set_flag(keys::LSP_AVAILABLE, self.is_lsp_available());
+        let language = self
+            .buffer_metadata
+            .get(&self.active_buffer())
+            .and_then(|metadata| metadata.file_path())
+            .and_then(|path| {
+                crate::services::lsp::manager::detect_language(path, &self.config.languages)
+            });
+        if let Some(language) = language {
+            values.set(keys::EDITOR_LANG_ID, language);
+        }
+        if let Some(mode) = self
+            .editor_mode
+            .as_deref()
+            .or_else(|| self.active_buffer_mode())
+        {
+            values.set(keys::MODE, mode);
+        }
+        // Contexts set by plugins with setContext()
+        for name in &self.active_custom_contexts {
+            values.set_flag(name.clone(), true);
+        }
+        values
+    }
+
     /// Handle a key event and return whether it was handled
     /// This is the central key handling logic used by both main.rs and tests
     pub fn handle_key(
@@ -156,9 +203,13 @@ impl Editor {
 
         // Check for chord sequence matches first
         let key_event = crossterm::event::KeyEvent::new(code, modifiers);
-        let chord_result = self
-            .keybindings
-            .resolve_chord(&self.chord_state, &key_event, context);
+        let when_context = self.get_when_context();
+        let chord_result = self.keybindings.resolve_chord_when(
+            &self.chord_state,
+            &key_event,
+            context,
+            &when_context,
+        );
 
         match chord_result {
             crate::input::keybindings::ChordResolution::Complete(action) => {
@@ -183,7 +234,9 @@ impl Editor {
         }
 
         // Regular single-key resolution
-        let action = self.keybindings.resolve(&key_event, context);
+        let action = self
+            .keybindings
+            .resolve_when(&key_event, context, &when_context);
 
         tracing::trace!("Context: {:?} -> Action: {:?}", context, action);
 
@@ -216,11 +269,21 @@ impl Editor {
         // Record action to macro if recording
         self.record_macro_action(&action);
 
+        if self.handle_hex_action(&action) {
+            return Ok(());
+        }
+        if self.handle_composite_edit(&action)? {
+            return Ok(());
+        }
+
         match action {
             Action::Quit => self.quit(),
             Action::Save => {
-                // Check if buffer has a file path - if not, redirect to SaveAs
-                if self.active_state().buffer.file_path().is_none() {
+                // Buffers editing a macro save into the macro
+                if self.is_macro_edit(self.active_buffer()) {
+                    self.save_macro_edit();
+                } else if self.active_state().buffer.file_path().is_none() {
+                    // No file path - redirect to SaveAs
                     self.start_prompt_with_initial_text(
                         t!("file.save_as_prompt").to_string(),
                         PromptType::SaveFileAs,
@@ -233,9 +296,10 @@ impl Editor {
                         PromptType::ConfirmSaveConflict,
                     );
                 } else {
-                    self.save()?;
+                    self.save_or_offer_save_as_root()?;
                 }
             }
+            Action::SaveAsRoot => self.start_save_as_root(),
             Action::SaveAs => {
                 // Get current filename as default suggestion
                 let current_path = self
@@ -338,6 +402,13 @@ impl Editor {
                     );
                 }
             }
+            Action::FormatSelection => {
+                if let Err(e) = self.format_selection() {
+                    self.set_status_message(
+                        t!("error.format_failed", error = e.to_string()).to_string(),
+                    );
+                }
+            }
             Action::Copy => {
                 // Check if active buffer is a composite buffer
                 let buffer_id = self.active_buffer();
@@ -373,6 +444,28 @@ impl Editor {
             Action::Redo => {
                 self.handle_redo();
             }
+            Action::UndoEarlier => self.start_time_travel_prompt(false),
+            Action::UndoLater => self.start_time_travel_prompt(true),
+            Action::UndoHistory => self.open_undo_history(),
+            Action::UndoHistoryJump => self.undo_history_jump(),
+            Action::CloseUndoHistory => self.close_undo_history(),
+            Action::CompareWithFile => {
+                self.start_prompt(
+                    t!("diff.compare_prompt").to_string(),
+                    PromptType::CompareWithFile,
+                );
+                self.init_file_open_state();
+            }
+            Action::CompareWithSaved => self.compare_with_saved(),
+            Action::DiffNextHunk => self.diff_goto_hunk(true),
+            Action::DiffPrevHunk => self.diff_goto_hunk(false),
+            Action::DiffTakeLeft => self.diff_take(DiffSide::Left),
+            Action::DiffTakeRight => self.diff_take(DiffSide::Right),
+            Action::MergeNextConflict => self.merge_goto_conflict(true),
+            Action::MergePrevConflict => self.merge_goto_conflict(false),
+            Action::MergeAcceptLocal => self.merge_accept(Resolution::Local),
+            Action::MergeAcceptRemote => self.merge_accept(Resolution::Remote),
+            Action::MergeAcceptBoth => self.merge_accept(Resolution::Both),
             Action::ShowHelp => {
                 self.open_help_manual();
             }
@@ -429,6 +522,7 @@ impl Editor {
             Action::ToggleComposeMode => {
                 self.handle_toggle_compose_mode();
             }
+            Action::ToggleHexView => self.toggle_hex_view(),
             Action::SetComposeWidth => {
                 let active_split = self.split_manager.active_split();
                 let current = self
@@ -488,6 +582,9 @@ impl Editor {
             Action::LspCodeActions => {
                 self.request_code_actions()?;
             }
+            Action::LspCodeLens => {
+                self.run_code_lens_at_cursor();
+            }
             Action::LspRestart => {
                 self.handle_lsp_restart();
             }
@@ -636,6 +733,13 @@ impl Editor {
             Action::SetLineEnding => {
                 self.start_set_line_ending_prompt();
             }
+            Action::ReopenWithEncoding => {
+                self.start_encoding_prompt(PromptType::ReopenWithEncoding);
+            }
+            Action::SaveWithEncoding => {
+                self.start_encoding_prompt(PromptType::SaveWithEncoding);
+            }
+            Action::InsertSnippet => self.start_insert_snippet_prompt(),
             Action::ToggleIndentationStyle => {
                 if let Some(state) = self.buffers.get_mut(&self.active_buffer()) {
                     state.use_tabs = !state.use_tabs;
@@ -676,6 +780,7 @@ impl Editor {
             Action::FileExplorerToggleHidden => self.file_explorer_toggle_hidden(),
             Action::FileExplorerToggleGitignored => self.file_explorer_toggle_gitignored(),
             Action::RemoveSecondaryCursors => {
+                self.active_state_mut().snippet_session = None;
                 // Convert action to events and apply them
                 if let Some(events) = self.action_to_events(Action::RemoveSecondaryCursors) {
                     // Wrap in batch for atomic undo
@@ -757,6 +862,48 @@ impl Editor {
             Action::ToggleComment => {
                 self.toggle_comment();
             }
+            Action::Fold => {
+                self.fold_at_cursors();
+            }
+            Action::Unfold => {
+                self.unfold_at_cursors();
+            }
+            Action::FoldAll => {
+                self.fold_all();
+            }
+            Action::UnfoldAll => {
+                self.unfold_all();
+            }
+            Action::GotoSymbol => {
+                self.start_goto_symbol_prompt();
+            }
+            Action::WorkspaceSymbol => {
+                self.start_workspace_symbol_prompt();
+            }
+            Action::ToggleOutline => {
+                self.toggle_outline_panel();
+            }
+            Action::OutlineJump => {
+                self.outline_jump();
+            }
+            Action::CallHierarchy => {
+                self.start_hierarchy(HierarchyDirection::IncomingCalls);
+            }
+            Action::TypeHierarchy => {
+                self.start_hierarchy(HierarchyDirection::Subtypes);
+            }
+            Action::HierarchyToggle => {
+                self.hierarchy_toggle();
+            }
+            Action::HierarchyJump => {
+                self.hierarchy_jump();
+            }
+            Action::HierarchyFlipDirection => {
+                self.hierarchy_flip_direction();
+            }
+            Action::CloseHierarchy => {
+                self.close_hierarchy_panel();
+            }
             Action::GoToMatchingBracket => {
                 self.goto_matching_bracket();
             }
@@ -878,26 +1025,38 @@ impl Editor {
                 self.stop_macro_recording();
             }
             Action::PlayMacro(key) => {
-                self.play_macro(key);
+                self.play_macro(&key.to_string());
+            }
+            Action::PlayNamedMacro(name) => {
+                self.play_macro(&name);
             }
             Action::ToggleMacroRecording(key) => {
-                self.toggle_macro_recording(key);
+                self.toggle_macro_recording(&key.to_string());
             }
             Action::ShowMacro(key) => {
-                self.show_macro_in_buffer(key);
+                self.edit_macro(&key.to_string());
             }
             Action::ListMacros => {
                 self.list_macros_in_buffer();
             }
             Action::PromptRecordMacro => {
-                self.start_prompt("Record macro (0-9): ".to_string(), PromptType::RecordMacro);
+                self.start_macro_prompt(PromptType::RecordMacro);
             }
             Action::PromptPlayMacro => {
-                self.start_prompt("Play macro (0-9): ".to_string(), PromptType::PlayMacro);
+                self.start_macro_prompt(PromptType::PlayMacro);
+            }
+            Action::PromptEditMacro => {
+                self.start_macro_prompt(PromptType::EditMacro);
+            }
+            Action::PromptPlayMacroOnLines => {
+                self.start_macro_prompt(PromptType::PlayMacroOnLines);
+            }
+            Action::PromptPlayMacroTimes => {
+                self.start_macro_prompt(PromptType::PlayMacroTimes);
             }
             Action::PlayLastMacro => {
-                if let Some(key) = self.last_macro_register {
-                    self.play_macro(key);
+                if let Some(key) = self.last_macro_register.clone() {
+                    self.play_macro(&key);
                 } else {
                     self.set_status_message(t!("status.no_macro_recorded").to_string());
                 }
@@ -1135,6 +1294,17 @@ impl Editor {
                     self.update_prompt_suggestions();
                 }
             }
+            // Tab moves through the tabstops of a snippet, or expands a snippet prefix
+            Action::InsertTab => {
+                if !self.move_to_snippet_tabstop(true) && !self.expand_snippet_prefix() {
+                    self.apply_action_as_events(action)?;
+                }
+            }
+            Action::DedentSelection => {
+                if !self.move_to_snippet_tabstop(false) {
+                    self.apply_action_as_events(action)?;
+                }
+            }
             _ => {
                 // TODO: Why do we have this catch-all? It seems like actions should either:
                 // 1. Be handled explicitly above (like InsertChar, PopupConfirm, etc.)
@@ -1203,6 +1373,11 @@ impl Editor {
         let active_split = self.split_manager.active_split();
         let buffer_id = self.active_buffer();
 
+        if self.active_state().hex_view.is_some() {
+            self.scroll_hex_view(delta as isize);
+            return Ok(());
+        }
+
         // Check if this is a composite buffer - if so, use composite scroll
         if self.is_composite_buffer(buffer_id) {
             let max_row = self
@@ -1726,6 +1901,11 @@ impl Editor {
             return self.handle_composite_click(col, row, split_id, buffer_id, content_rect);
         }
 
+        if self.active_state().hex_view.is_some() {
+            self.handle_hex_click(col, row, content_rect);
+            return Ok(());
+        }
+
         // Ensure key context is Normal for non-terminal buffers
         // This handles the edge case where split/buffer don't change but we clicked from FileExplorer
         if !self.is_terminal_buffer(buffer_id) {
@@ -1746,6 +1926,11 @@ impl Editor {
             .map(|vs| vs.viewport.top_byte)
             .unwrap_or(0);
 
+        // Clicking a code lens runs it instead of moving the cursor
+        if self.click_code_lens(buffer_id, col, row, content_rect, &cached_mappings) {
+            return Ok(());
+        }
+
         // Calculate clicked position in buffer
         if let Some(state) = self.buffers.get_mut(&buffer_id) {
             let gutter_width = state.margins.left_total_width() as u16;
@@ -2512,6 +2697,8 @@ impl Editor {
             let _ = self.request_signature_help();
         }
 
+        self.request_on_type_formatting(c);
+
         Ok(())
     }
 
@@ -2531,6 +2718,7 @@ impl Editor {
 
         // Get description before moving action
         let action_description = format!("{:?}", action);
+        let is_newline = matches!(action, Action::InsertNewline);
 
         // Check if this is an editing action and editing is disabled
         let is_editing_action = matches!(
@@ -2588,6 +2776,10 @@ impl Editor {
             }
         }
 
+        if is_newline {
+            self.request_on_type_formatting('\n');
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,98 @@
+//! Opening files sent by `fresh --remote`
+//!
+//! The editor's `InstanceServer` (see `services::instance_server`) passes
+//! requests on through the async bridge. Their files are opened like files
+//! given on the command line; a client that asked to wait is answered once
+//! all of its buffers are closed, and the editor then goes back to the
+//! buffer it was in, typically the terminal the client was run from.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+use rust_i18n::t;
+
+use crate::model::event::BufferId;
+use crate::services::instance_server::{InstanceServer, PendingRequest, SERVER_ENV};
+
+use super::Editor;
+
+/// A `fresh --remote --wait` client
+pub(super) struct WaitingClient {
+    request: PendingRequest,
+    /// Its buffers that are still open
+    buffers: HashSet<BufferId>,
+    /// The buffer that was active when the request came in
+    return_to: BufferId,
+}
+
+impl Editor {
+    /// Listen for files to open on the socket at `path`
+    ///
+    /// Terminals opened afterwards get the path in `FRESH_SERVER`.
+    pub fn start_instance_server(&mut self, path: &Path) -> io::Result<()> {
+        let Some(bridge) = &self.async_bridge else {
+            return Err(io::Error::other("no async bridge"));
+        };
+        let server = InstanceServer::start(path, bridge.sender())?;
+        self.terminal_manager
+            .set_env(SERVER_ENV, server.path().as_os_str());
+        self.instance_server = Some(server);
+        Ok(())
+    }
+
+    /// Open the files of a request and answer it, or remember it until its
+    /// buffers are closed
+    pub(super) fn handle_open_files_request(&mut self, request: PendingRequest) {
+        let return_to = self.active_buffer();
+        let mut buffers = HashSet::new();
+        for file in &request.request.files {
+            match self.open_file(&file.path) {
+                Ok(buffer_id) => {
+                    buffers.insert(buffer_id);
+                }
+                Err(e) => {
+                    let message = format!("{}: {}", file.path.display(), e);
+                    self.set_status_message(
+                        t!("file.error_opening", error = message.as_str()).to_string(),
+                    );
+                    request.reply(Some(message));
+                    return;
+                }
+            }
+            if let Some(line) = file.line {
+                self.goto_line_col(line, file.column);
+            }
+        }
+
+        if request.request.wait && !buffers.is_empty() {
+            self.set_status_message(t!("file.close_when_done").to_string());
+            self.waiting_clients.push(WaitingClient {
+                request,
+                buffers,
+                return_to,
+            });
+        } else {
+            request.reply(None);
+        }
+    }
+
+    /// Answer the clients that were waiting only for `closed`
+    pub(super) fn release_waiting_clients(&mut self, closed: BufferId) {
+        let (done, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.waiting_clients)
+            .into_iter()
+            .map(|mut client| {
+                client.buffers.remove(&closed);
+                client
+            })
+            .partition(|client| client.buffers.is_empty());
+        self.waiting_clients = waiting;
+
+        for client in done {
+            if self.buffers.contains_key(&client.return_to) {
+                self.set_active_buffer(client.return_to);
+            }
+            client.request.reply(None);
+        }
+    }
+}
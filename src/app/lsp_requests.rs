@@ -638,16 +638,24 @@ impl Editor {
         }
     }
 
+    /// Namespace of the inlay hint virtual texts
+    pub(crate) fn inlay_hints_namespace() -> crate::view::virtual_text::VirtualTextNamespace {
+        crate::view::virtual_text::VirtualTextNamespace::from_string("lsp-inlay-hints".to_string())
+    }
+
     /// Apply inlay hints to editor state as virtual text
     pub(crate) fn apply_inlay_hints_to_state(
         state: &mut crate::state::EditorState,
         hints: &[lsp_types::InlayHint],
     ) {
-        use crate::view::virtual_text::VirtualTextPosition;
+        use crate::view::virtual_text::{InlineTextOptions, VirtualTextPosition};
         use ratatui::style::{Color, Style};
 
         // Clear existing inlay hints
-        state.virtual_texts.clear(&mut state.marker_list);
+        let ns = Self::inlay_hints_namespace();
+        state
+            .virtual_texts
+            .clear_namespace(&mut state.marker_list, &ns);
 
         if hints.is_empty() {
             return;
@@ -682,13 +690,16 @@ impl Editor {
             // Use the hint text as-is - spacing is handled during rendering
             let display_text = text;
 
-            state.virtual_texts.add(
+            state.virtual_texts.add_inline(
                 &mut state.marker_list,
                 byte_offset,
                 display_text,
-                hint_style,
-                position,
-                0, // Default priority
+                InlineTextOptions {
+                    style: hint_style,
+                    placement: position,
+                    namespace: ns.clone(),
+                    priority: 0, // Default priority
+                },
             );
         }
 
@@ -1146,6 +1157,7 @@ impl Editor {
         &mut self,
         buffer_id: BufferId,
         mut edits: Vec<lsp_types::TextEdit>,
+        description: &str,
     ) -> io::Result<usize> {
         if edits.is_empty() {
             return Ok(0);
@@ -1226,12 +1238,12 @@ impl Editor {
             changes += 1;
         }
 
-        // Apply all rename changes using bulk edit for O(n) performance
+        // Apply all changes using bulk edit for O(n) performance
         if !batch_events.is_empty() {
             self.apply_events_to_buffer_as_bulk_edit(
                 buffer_id,
                 batch_events,
-                "LSP Rename".to_string(),
+                description.to_string(),
             )?;
         }
 
@@ -1259,80 +1271,16 @@ impl Editor {
                     })
                 );
 
-                // Apply the workspace edit
-                let mut total_changes = 0;
-
-                // Handle changes (map of URI -> Vec<TextEdit>)
-                if let Some(changes) = workspace_edit.changes {
-                    for (uri, edits) in changes {
-                        if let Ok(path) = uri_to_path(&uri) {
-                            let buffer_id = self.open_file(&path)?;
-                            total_changes += self.apply_lsp_text_edits(buffer_id, edits)?;
-                        }
+                match self.apply_workspace_edit(workspace_edit, "LSP Rename") {
+                    Ok(total_changes) => {
+                        self.status_message =
+                            Some(t!("lsp.renamed", count = total_changes).to_string());
                     }
-                }
-
-                // Handle document_changes (TextDocumentEdit[])
-                // This is what rust-analyzer sends instead of changes
-                if let Some(document_changes) = workspace_edit.document_changes {
-                    use lsp_types::DocumentChanges;
-
-                    let text_edits = match document_changes {
-                        DocumentChanges::Edits(edits) => edits,
-                        DocumentChanges::Operations(ops) => {
-                            // Extract TextDocumentEdit from operations
-                            ops.into_iter()
-                                .filter_map(|op| {
-                                    if let lsp_types::DocumentChangeOperation::Edit(edit) = op {
-                                        Some(edit)
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect()
-                        }
-                    };
-
-                    for text_doc_edit in text_edits {
-                        let uri = text_doc_edit.text_document.uri;
-
-                        if let Ok(path) = uri_to_path(&uri) {
-                            let buffer_id = self.open_file(&path)?;
-
-                            // Extract TextEdit from OneOf<TextEdit, AnnotatedTextEdit>
-                            let edits: Vec<lsp_types::TextEdit> = text_doc_edit
-                                .edits
-                                .into_iter()
-                                .map(|one_of| match one_of {
-                                    lsp_types::OneOf::Left(text_edit) => text_edit,
-                                    lsp_types::OneOf::Right(annotated) => annotated.text_edit,
-                                })
-                                .collect();
-
-                            // Log the edits for debugging
-                            tracing::info!(
-                                "Applying {} edits from rust-analyzer for {:?}:",
-                                edits.len(),
-                                path
-                            );
-                            for (i, edit) in edits.iter().enumerate() {
-                                tracing::info!(
-                                    "  Edit {}: line {}:{}-{}:{} -> {:?}",
-                                    i,
-                                    edit.range.start.line,
-                                    edit.range.start.character,
-                                    edit.range.end.line,
-                                    edit.range.end.character,
-                                    edit.new_text
-                                );
-                            }
-
-                            total_changes += self.apply_lsp_text_edits(buffer_id, edits)?;
-                        }
+                    Err(failure) => {
+                        self.status_message =
+                            Some(t!("lsp.rename_failed", error = &failure.reason).to_string());
                     }
                 }
-
-                self.status_message = Some(t!("lsp.renamed", count = total_changes).to_string());
             }
             Err(error) => {
                 // Per LSP spec: ContentModified errors (-32801) should NOT be shown to user
@@ -0,0 +1,250 @@
+//! Saved, named and edited keyboard macros.
+//!
+//! Recording and playing a single macro lives in render.rs; this module
+//! keeps the macros file up to date, opens macros for editing and plays
+//! them over several lines or several times.
+
+use rust_i18n::t;
+
+use crate::input::commands::Suggestion;
+use crate::input::macros::{format_actions, is_macro_control, parse_actions, save_macros};
+use crate::model::event::BufferId;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::view::prompt::{Prompt, PromptType};
+
+use super::Editor;
+
+/// Most times "Play Macro N Times" plays a macro, so a typo can't keep the
+/// editor busy for hours
+const MAX_MACRO_REPEAT: usize = 10_000;
+
+impl Editor {
+    /// Write the macros to the macros file
+    pub(super) fn save_macros(&mut self) {
+        let path = self.dir_context.macros_path();
+        if let Err(e) = save_macros(&path, &self.macros) {
+            tracing::warn!("Failed to save macros to {:?}: {}", path, e);
+            self.set_status_message(t!("macro.save_failed", error = e.to_string()).to_string());
+        }
+    }
+
+    /// Start a prompt for a macro, offering the existing macros
+    pub(super) fn start_macro_prompt(&mut self, prompt_type: PromptType) {
+        let message = match &prompt_type {
+            PromptType::RecordMacro => t!("macro.record_prompt"),
+            PromptType::EditMacro => t!("macro.edit_prompt"),
+            PromptType::PlayMacroOnLines => t!("macro.play_on_lines_prompt"),
+            PromptType::PlayMacroTimes => t!("macro.play_times_prompt"),
+            _ => t!("macro.play_prompt"),
+        };
+        let playing = !matches!(prompt_type, PromptType::RecordMacro | PromptType::EditMacro);
+        if playing && self.macros.is_empty() {
+            self.set_status_message(t!("macro.none_recorded").to_string());
+            return;
+        }
+
+        let mut names: Vec<&String> = self.macros.keys().collect();
+        names.sort();
+        let suggestions = names
+            .into_iter()
+            .map(|name| Suggestion {
+                text: name.clone(),
+                description: Some(
+                    t!("macro.action_count", count = self.macros[name].len()).to_string(),
+                ),
+                value: None,
+                disabled: false,
+                keybinding: None,
+                source: None,
+            })
+            .collect();
+        self.prompt = Some(Prompt::with_suggestions(
+            message.to_string(),
+            prompt_type,
+            suggestions,
+        ));
+    }
+
+    /// Handle the input of a macro prompt
+    pub(super) fn handle_macro_prompt(&mut self, prompt_type: PromptType, input: &str) {
+        let key = input.trim();
+        if key.is_empty() {
+            self.set_status_message(t!("register.not_specified").to_string());
+            return;
+        }
+        match prompt_type {
+            PromptType::RecordMacro => self.toggle_macro_recording(key),
+            PromptType::EditMacro => self.edit_macro(key),
+            PromptType::PlayMacroOnLines => self.play_macro_on_lines(key),
+            PromptType::PlayMacroTimes => {
+                if self.macros.contains_key(key) {
+                    self.start_prompt(
+                        t!("macro.count_prompt", key = key).to_string(),
+                        PromptType::MacroCount {
+                            name: key.to_string(),
+                        },
+                    );
+                } else {
+                    self.set_status_message(t!("macro.not_found", key = key).to_string());
+                }
+            }
+            PromptType::MacroCount { name } => match key.parse::<usize>() {
+                Ok(count) if count > MAX_MACRO_REPEAT => {
+                    self.set_status_message(
+                        t!("macro.count_too_large", max = MAX_MACRO_REPEAT).to_string(),
+                    );
+                }
+                Ok(count) if count > 0 => self.play_macro_times(&name, count),
+                _ => {
+                    self.set_status_message(t!("macro.invalid_count", input = key).to_string());
+                }
+            },
+            _ => self.play_macro(key),
+        }
+    }
+
+    /// Play the actions of a macro `times` times.
+    ///
+    /// Playback stops at the first action that fails. Returns the number of
+    /// actions of the macro, or None after reporting a macro that doesn't
+    /// exist, is empty or failed.
+    pub(super) fn replay_macro(&mut self, key: &str, times: usize) -> Option<usize> {
+        let Some(actions) = self.macros.get(key).cloned() else {
+            self.set_status_message(t!("macro.not_found", key = key).to_string());
+            return None;
+        };
+        if actions.is_empty() {
+            self.set_status_message(t!("macro.empty", key = key).to_string());
+            return None;
+        }
+
+        // Temporarily disable recording to avoid recording the playback
+        let was_recording = self.macro_recording.take();
+        let mut result = Ok(());
+        for _ in 0..times {
+            // Macros don't play other macros, which could play themselves
+            result = actions
+                .iter()
+                .filter(|action| !is_macro_control(action))
+                .try_for_each(|action| self.handle_action(action.clone()));
+            if result.is_err() {
+                break;
+            }
+        }
+        self.macro_recording = was_recording;
+        if let Err(e) = result {
+            self.set_status_message(
+                t!("macro.stopped", key = key, error = e.to_string()).to_string(),
+            );
+            return None;
+        }
+        Some(actions.len())
+    }
+
+    /// Play a macro `count` times in a row
+    pub(super) fn play_macro_times(&mut self, key: &str, count: usize) {
+        if self.replay_macro(key, count).is_some() {
+            self.set_status_message(t!("macro.played_times", key = key, count = count).to_string());
+        }
+    }
+
+    /// Play a macro from the start of each line of the selection (or of the
+    /// cursor line)
+    pub(super) fn play_macro_on_lines(&mut self, key: &str) {
+        if !self.macros.contains_key(key) {
+            self.set_status_message(t!("macro.not_found", key = key).to_string());
+            return;
+        }
+
+        let state = self.active_state();
+        let cursor = state.cursors.primary();
+        let range = cursor
+            .selection_range()
+            .unwrap_or(cursor.position..cursor.position);
+        let first = state.buffer.get_line_number(range.start);
+        let mut last = state.buffer.get_line_number(range.end);
+        // A selection ending at the start of a line doesn't cover that line
+        if last > first && state.buffer.line_start_offset(last) == Some(range.end) {
+            last -= 1;
+        }
+
+        // Bottom to top, so lines added or removed by the macro don't move
+        // the lines still to be done
+        for line in (first..=last).rev() {
+            self.goto_line_col(line + 1, None);
+            if self.replay_macro(key, 1).is_none() {
+                return;
+            }
+        }
+        self.set_status_message(
+            t!("macro.played_lines", key = key, count = last - first + 1).to_string(),
+        );
+    }
+
+    /// Open a macro in a buffer where it can be edited as JSON, one action
+    /// per line. Saving the buffer updates the macro, or creates it.
+    pub(super) fn edit_macro(&mut self, key: &str) {
+        if let Some((&buffer_id, _)) = self.macro_edits.iter().find(|(_, name)| *name == key) {
+            self.set_active_buffer(buffer_id);
+            return;
+        }
+
+        let actions = self.macros.get(key).cloned().unwrap_or_default();
+        let text = format!(
+            "// Macro '{}': one action per line. Save to update the macro, an empty list deletes it.\n{}\n",
+            key,
+            format_actions(&actions, "")
+        );
+        let buffer_id =
+            self.create_virtual_buffer(format!("*Macro {}*", key), "normal".into(), false);
+        if let Err(e) =
+            self.set_virtual_buffer_content(buffer_id, vec![TextPropertyEntry::text(text)])
+        {
+            tracing::error!("Failed to set the text of macro {}: {}", key, e);
+        }
+        if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
+            metadata.disable_lsp(t!("lsp.disabled.virtual_macro").to_string());
+        }
+        self.macro_edits.insert(buffer_id, key.to_string());
+        self.set_active_buffer(buffer_id);
+        self.set_status_message(t!("macro.editing", key = key).to_string());
+    }
+
+    /// Whether the buffer is editing a macro
+    pub(super) fn is_macro_edit(&self, buffer_id: BufferId) -> bool {
+        self.macro_edits.contains_key(&buffer_id)
+    }
+
+    /// Parse the active macro edit buffer back into its macro and save it
+    pub(super) fn save_macro_edit(&mut self) {
+        let buffer_id = self.active_buffer();
+        let Some(key) = self.macro_edits.get(&buffer_id).cloned() else {
+            return;
+        };
+        let text = self.active_state().buffer.to_string().unwrap_or_default();
+        let actions = match parse_actions(&text) {
+            Ok(actions) => actions,
+            Err(e) => {
+                self.set_status_message(
+                    t!("macro.parse_failed", error = e.to_string()).to_string(),
+                );
+                return;
+            }
+        };
+
+        let count = actions.len();
+        if actions.is_empty() {
+            self.macros.remove(&key);
+        } else {
+            self.macros.insert(key.clone(), actions);
+        }
+        self.active_state_mut().buffer.clear_modified();
+        self.active_event_log_mut().mark_saved();
+        self.set_status_message(if count == 0 {
+            t!("macro.deleted", key = &key).to_string()
+        } else {
+            t!("macro.updated", key = &key, count = count).to_string()
+        });
+        self.save_macros();
+    }
+}
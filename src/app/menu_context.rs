@@ -99,7 +99,7 @@ impl Editor {
     }
 
     /// Check if an LSP server is available and ready for the current buffer's language.
-    fn is_lsp_available(&self) -> bool {
+    pub(super) fn is_lsp_available(&self) -> bool {
         self.buffer_metadata
             .get(&self.active_buffer())
             .and_then(|metadata| {
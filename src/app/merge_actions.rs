@@ -0,0 +1,364 @@
+//! Three-way merge view
+//!
+//! `fresh --merge BASE LOCAL REMOTE OUTPUT` shows the common base, the two
+//! versions and the merge result side by side in a composite buffer, aligned
+//! on the base lines each of them changed. The result starts as the merge of
+//! `model::merge`, with markers around the conflicts, and is the only pane
+//! that can be edited; the accept actions replace the conflict under the
+//! cursor with one side or both. The number of conflicts left is shown in
+//! the status bar.
+
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use rust_i18n::t;
+
+use crate::model::composite_buffer::{CompositeLayout, LineAlignment, PaneStyle, SourcePane};
+use crate::model::event::BufferId;
+use crate::model::line_diff::diff_hunks;
+use crate::model::merge::{self, conflict_blocks, resolve_conflict, ConflictBlock, Resolution};
+
+use super::diff_actions::minimal_edit;
+use super::Editor;
+
+/// Mode of merge view buffers (see `ModeRegistry::new`)
+pub(super) const MERGE_MODE: &str = "merge";
+
+/// Panes of a merge view, from left to right
+const BASE_PANE: usize = 0;
+const LOCAL_PANE: usize = 1;
+const RESULT_PANE: usize = 2;
+const REMOTE_PANE: usize = 3;
+
+/// State of a merge view, keyed by its composite buffer
+#[derive(Debug)]
+pub(super) struct MergeView {
+    /// Buffers in the BASE, LOCAL, RESULT and REMOTE panes
+    panes: [BufferId; 4],
+    /// Buffers opened for the merge only
+    owned: Vec<BufferId>,
+    /// Rows of the changed regions, with the RESULT lines they show
+    regions: Vec<(Range<usize>, Range<usize>)>,
+    /// Event log positions and sizes of the panes when last aligned
+    versions: [(usize, usize, usize); 4],
+}
+
+impl Editor {
+    /// Merge the changes from `base` to `local` and to `remote` into `output`
+    pub fn open_merge(
+        &mut self,
+        base: &Path,
+        local: &Path,
+        remote: &Path,
+        output: &Path,
+    ) -> io::Result<BufferId> {
+        let (ids, owned) = self.open_view_sources(&[base, local, output, remote])?;
+        let panes = [ids[0], ids[1], ids[2], ids[3]];
+        let text = |editor: &Self, id| {
+            editor
+                .buffers
+                .get(&id)
+                .and_then(|state| state.buffer.to_string())
+                .ok_or_else(|| io::Error::other(t!("diff.too_large").to_string()))
+        };
+        let merged = merge::merge(
+            &text(self, panes[BASE_PANE])?,
+            &text(self, panes[LOCAL_PANE])?,
+            &text(self, panes[REMOTE_PANE])?,
+        );
+        let result = panes[RESULT_PANE];
+        for event in minimal_edit(&text(self, result)?, &merged.text) {
+            self.apply_composite_source_event(result, event);
+        }
+
+        let file_name = output.file_name().map_or_else(
+            || output.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        let pane =
+            |index: usize, label: &str, editable| SourcePane::new(panes[index], label, editable);
+        let buffer_id = self.create_composite_buffer(
+            t!("merge.name", name = file_name).to_string(),
+            MERGE_MODE.to_string(),
+            CompositeLayout::SideBySide {
+                ratios: vec![0.25; 4],
+                show_separator: true,
+            },
+            vec![
+                pane(BASE_PANE, "BASE", false),
+                pane(LOCAL_PANE, "LOCAL", false).with_style(PaneStyle::old_diff()),
+                pane(RESULT_PANE, "RESULT", true),
+                pane(REMOTE_PANE, "REMOTE", false).with_style(PaneStyle::new_diff()),
+            ],
+        );
+        self.merge_views.insert(
+            buffer_id,
+            MergeView {
+                panes,
+                owned,
+                regions: Vec::new(),
+                versions: Default::default(),
+            },
+        );
+        self.set_active_buffer(buffer_id);
+        self.refresh_merge_view(buffer_id);
+
+        // Start on the first conflict, in the result
+        let split_id = self.split_manager.active_split();
+        if let Some(view_state) = self.get_composite_view_state(split_id, buffer_id) {
+            view_state.focused_pane = RESULT_PANE;
+        }
+        if let Some(composite) = self.composite_buffers.get_mut(&buffer_id) {
+            composite.active_pane = RESULT_PANE;
+        }
+        if merged.conflicts > 0 {
+            self.merge_goto_conflict(true);
+        } else {
+            self.set_status_message(merge_status(0));
+        }
+        Ok(buffer_id)
+    }
+
+    /// Align the panes of a merge view again and count the conflicts left
+    pub(super) fn refresh_merge_view(&mut self, buffer_id: BufferId) {
+        let Some(view) = self.merge_views.get(&buffer_id) else {
+            return;
+        };
+        let panes = view.panes;
+        let versions = panes.map(|id| self.diff_side_version(id));
+        let texts: Vec<Option<String>> = panes
+            .iter()
+            .map(|id| {
+                self.buffers
+                    .get(id)
+                    .and_then(|state| state.buffer.to_string())
+            })
+            .collect();
+        let Some(texts) = texts.into_iter().collect::<Option<Vec<String>>>() else {
+            self.set_status_message(t!("diff.too_large").to_string());
+            return;
+        };
+
+        // Every pane is aligned on the lines of the base
+        let base = texts[BASE_PANE].as_bytes();
+        let side_hunks: Vec<_> = [LOCAL_PANE, RESULT_PANE, REMOTE_PANE]
+            .iter()
+            .map(|&pane| diff_hunks(base, texts[pane].as_bytes()))
+            .collect();
+        let regions = merge::regions(&side_hunks, texts[BASE_PANE].split('\n').count());
+        let mut changed = Vec::new();
+        let mut row = 0;
+        for region in &regions {
+            let height = std::iter::once(&region.base)
+                .chain(&region.sides)
+                .map(|lines| lines.len())
+                .max()
+                .unwrap_or(0);
+            if region.is_changed() {
+                // The result is the second side, after the local version
+                changed.push((row..row + 1 + height, region.sides[1].clone()));
+                row += 1;
+            }
+            row += height;
+        }
+        let alignment = LineAlignment::from_regions(&regions);
+        let max_row = alignment.row_count().saturating_sub(1);
+        self.set_composite_alignment(buffer_id, alignment);
+        for ((_, id), view_state) in self.composite_view_states.iter_mut() {
+            if *id == buffer_id {
+                view_state.cursor_row = view_state.cursor_row.min(max_row);
+                view_state.scroll_row = view_state.scroll_row.min(max_row);
+            }
+        }
+
+        let conflicts = conflict_blocks(&texts[RESULT_PANE]).len();
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.merge_conflicts = Some(conflicts);
+        }
+        if let Some(view) = self.merge_views.get_mut(&buffer_id) {
+            view.regions = changed;
+            view.versions = versions;
+        }
+    }
+
+    /// Align the merge views again whose panes were changed elsewhere
+    ///
+    /// Called from the main loop; returns true if a redraw is needed.
+    pub(super) fn update_merge_views(&mut self) -> bool {
+        let changed: Vec<BufferId> = self
+            .merge_views
+            .iter()
+            .filter(|(_, view)| view.versions != view.panes.map(|id| self.diff_side_version(id)))
+            .map(|(id, _)| *id)
+            .collect();
+        for buffer_id in &changed {
+            self.refresh_merge_view(*buffer_id);
+        }
+        !changed.is_empty()
+    }
+
+    /// The conflicts left in the result of a merge view, with the row each
+    /// one starts on
+    fn merge_conflicts(&self, buffer_id: BufferId) -> Vec<(usize, ConflictBlock)> {
+        let (Some(view), Some(composite)) = (
+            self.merge_views.get(&buffer_id),
+            self.composite_buffers.get(&buffer_id),
+        ) else {
+            return Vec::new();
+        };
+        let Some(text) = self
+            .buffers
+            .get(&view.panes[RESULT_PANE])
+            .and_then(|state| state.buffer.to_string())
+        else {
+            return Vec::new();
+        };
+        conflict_blocks(&text)
+            .into_iter()
+            .filter_map(|block| {
+                let row = composite.alignment.rows.iter().position(|row| {
+                    row.get_pane_line(RESULT_PANE)
+                        .is_some_and(|line_ref| line_ref.line == block.lines.start)
+                })?;
+                Some((row, block))
+            })
+            .collect()
+    }
+
+    /// Move the cursor of the active merge view to the next or previous
+    /// conflict
+    pub(super) fn merge_goto_conflict(&mut self, next: bool) {
+        let buffer_id = self.active_buffer();
+        if !self.merge_views.contains_key(&buffer_id) {
+            self.set_status_message(t!("merge.not_in_merge").to_string());
+            return;
+        }
+        let split_id = self.split_manager.active_split();
+        let rows: Vec<usize> = self
+            .merge_conflicts(buffer_id)
+            .into_iter()
+            .map(|(row, _)| row)
+            .collect();
+        let viewport_height = self
+            .split_view_states
+            .get(&split_id)
+            .map_or(24, |view_state| {
+                view_state.viewport.height.saturating_sub(1) as usize
+            });
+        let Some(view_state) = self.get_composite_view_state(split_id, buffer_id) else {
+            return;
+        };
+        let cursor_row = view_state.cursor_row;
+        let target = if next {
+            rows.iter().position(|&row| row > cursor_row)
+        } else {
+            rows.iter().rposition(|&row| row < cursor_row)
+        };
+        let Some(index) = target else {
+            self.set_status_message(t!("merge.no_more_conflicts").to_string());
+            return;
+        };
+        let row = rows[index];
+        view_state.cursor_row = row;
+        view_state.cursor_column = 0;
+        view_state.sticky_column = 0;
+        if row < view_state.scroll_row || row >= view_state.scroll_row + viewport_height.max(1) {
+            view_state.scroll_row = row.saturating_sub(3);
+        }
+        self.set_status_message(
+            t!(
+                "merge.conflict_position",
+                index = index + 1,
+                count = rows.len()
+            )
+            .to_string(),
+        );
+    }
+
+    /// Replace the conflict under the cursor in the result with the local
+    /// lines, the remote lines or both
+    pub(super) fn merge_accept(&mut self, resolution: Resolution) {
+        let buffer_id = self.active_buffer();
+        let Some(view) = self.merge_views.get(&buffer_id) else {
+            self.set_status_message(t!("merge.not_in_merge").to_string());
+            return;
+        };
+        let result = view.panes[RESULT_PANE];
+        let split_id = self.split_manager.active_split();
+        let cursor_row = self
+            .composite_view_states
+            .get(&(split_id, buffer_id))
+            .map_or(0, |view_state| view_state.cursor_row);
+
+        // The conflict in the changed region the cursor is in
+        let lines = view
+            .regions
+            .iter()
+            .find(|(rows, _)| rows.contains(&cursor_row))
+            .map(|(_, lines)| lines.clone());
+        let cursor_line = self
+            .composite_buffers
+            .get(&buffer_id)
+            .and_then(|composite| composite.alignment.get_row(cursor_row))
+            .and_then(|row| row.get_pane_line(RESULT_PANE))
+            .map(|line_ref| line_ref.line);
+        let Some(text) = self
+            .buffers
+            .get(&result)
+            .and_then(|state| state.buffer.to_string())
+        else {
+            self.set_status_message(t!("diff.too_large").to_string());
+            return;
+        };
+        let blocks = conflict_blocks(&text);
+        let block = blocks
+            .iter()
+            .find(|block| cursor_line.is_some_and(|line| block.lines.contains(&line)))
+            .or_else(|| {
+                let lines = lines.as_ref()?;
+                blocks
+                    .iter()
+                    .find(|block| block.lines.start < lines.end && lines.start < block.lines.end)
+            });
+        let Some(block) = block else {
+            self.set_status_message(t!("merge.not_on_conflict").to_string());
+            return;
+        };
+
+        let new_text = resolve_conflict(&text, block, resolution);
+        for event in minimal_edit(&text, &new_text) {
+            self.apply_composite_source_event(result, event);
+        }
+        self.composite_sources_edited(buffer_id);
+        self.set_status_message(merge_status(blocks.len() - 1));
+    }
+
+    /// Close the merge view of a closed buffer, or the merge views showing it
+    pub(super) fn close_merge_views_of(&mut self, closed: BufferId) {
+        if let Some(view) = self.merge_views.remove(&closed) {
+            self.close_composite_buffer(closed);
+            self.close_view_sources(view.owned);
+            return;
+        }
+        let showing: Vec<BufferId> = self
+            .merge_views
+            .iter()
+            .filter(|(_, view)| view.panes.contains(&closed))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in showing {
+            let _ = self.close_buffer(id);
+        }
+    }
+}
+
+/// Status message after resolving a conflict, given how many are left (the
+/// count itself is shown in the status bar)
+fn merge_status(conflicts: usize) -> String {
+    if conflicts == 0 {
+        t!("merge.resolved").to_string()
+    } else {
+        t!("merge.conflict_resolved").to_string()
+    }
+}
@@ -3,37 +3,59 @@ mod buffer_management;
 mod calibration_actions;
 pub mod calibration_wizard;
 mod clipboard;
+mod code_lens_actions;
 mod composite_buffer_actions;
+mod diff_actions;
+mod encoding_actions;
 mod file_explorer;
 pub mod file_open;
 mod file_open_input;
 mod file_operations;
+mod file_watching;
+mod fold_actions;
+mod format_actions;
 mod help;
+mod hex_actions;
+mod hierarchy_actions;
 mod input;
 mod input_dispatch;
+#[cfg(unix)]
+mod instance_server_actions;
 mod lsp_actions;
 mod lsp_requests;
+mod macro_actions;
 mod menu_actions;
 mod menu_context;
+mod merge_actions;
 mod mouse_input;
 mod on_save_actions;
+mod outline_actions;
 mod plugin_commands;
+mod plugin_permission_actions;
 mod popup_actions;
+mod privileged_save_actions;
 mod prompt_actions;
 mod recovery_actions;
 mod render;
+mod semantic_token_actions;
 pub mod session;
 mod settings_actions;
 mod shell_command;
+mod snippet_actions;
 mod split_actions;
+mod status_bar_actions;
 mod tab_drag;
 mod terminal;
 mod terminal_input;
 mod toggle_actions;
 pub mod types;
 mod undo_actions;
+mod undo_tree_actions;
 mod view_actions;
 pub mod warning_domains;
+mod which_key;
+mod workspace_edit_actions;
+mod workspace_symbol_actions;
 
 use rust_i18n::t;
 use std::path::Component;
@@ -85,6 +107,7 @@ use crate::input::keybindings::{Action, KeyContext, KeybindingResolver};
 use crate::input::position_history::PositionHistory;
 use crate::model::event::{Event, EventLog, SplitDirection, SplitId};
 use crate::services::async_bridge::{AsyncBridge, AsyncMessage};
+use crate::services::file_watcher::FileWatcher;
 use crate::services::fs::{FsBackend, FsManager, LocalFsBackend};
 use crate::services::lsp::manager::{detect_language, LspManager};
 #[cfg(feature = "plugins")]
@@ -185,6 +208,15 @@ pub struct Editor {
     /// Plugin-provided status message (displayed alongside the core status)
     plugin_status_message: Option<String>,
 
+    /// Status bar segments set by plugins (name, text)
+    plugin_status_segments: Vec<(String, String)>,
+
+    /// Git branch shown in the status bar
+    git_branch: status_bar_actions::GitBranchState,
+
+    /// Time last shown by the clock segments of the status bar
+    status_bar_clock: String,
+
     /// Active prompt (minibuffer)
     prompt: Option<Prompt>,
 
@@ -308,6 +340,45 @@ pub struct Editor {
     /// Pending LSP inlay hints request ID (if any)
     pending_inlay_hints_request: Option<u64>,
 
+    /// Pending LSP folding range request: (request ID, buffer, fold action to run)
+    pending_folding_range_request: Option<(u64, BufferId, fold_actions::PendingFold)>,
+
+    /// Pending LSP document symbol request: (request ID, buffer, where to show the symbols)
+    pending_document_symbol_request: Option<(u64, BufferId, outline_actions::OutlineTarget)>,
+
+    /// Outline side panel (None when hidden)
+    outline_panel: Option<outline_actions::OutlinePanel>,
+
+    /// Pending LSP call/type hierarchy request: (request ID, what it is for)
+    pending_hierarchy_request: Option<(u64, hierarchy_actions::PendingHierarchy)>,
+
+    /// Call/type hierarchy panel (None when hidden)
+    hierarchy_panel: Option<hierarchy_actions::HierarchyPanel>,
+
+    /// Undo history panel (None when hidden)
+    undo_history_panel: Option<undo_tree_actions::UndoHistoryPanel>,
+
+    /// Two-file diff views, by composite buffer
+    diff_views: HashMap<BufferId, diff_actions::DiffView>,
+
+    /// Three-way merge views, by composite buffer
+    merge_views: HashMap<BufferId, merge_actions::MergeView>,
+
+    /// Pending LSP formatting requests by request ID
+    pending_format_requests: HashMap<u64, format_actions::PendingFormat>,
+
+    /// Semantic token requests per buffer
+    semantic_tokens: HashMap<BufferId, semantic_token_actions::SemanticTokenState>,
+
+    /// Code lenses per buffer and the lens command being run
+    code_lenses: code_lens_actions::CodeLenses,
+
+    /// Open workspace symbol prompt and its in-flight requests (if any)
+    workspace_symbol_search: Option<workspace_symbol_actions::WorkspaceSymbolSearch>,
+
+    /// Workspace symbol resolve request to jump to once answered (if any)
+    workspace_symbol_resolve: Option<u64>,
+
     /// Hover symbol range (byte offsets) - for highlighting the symbol under hover
     /// Format: (start_byte_offset, end_byte_offset)
     hover_symbol_range: Option<(usize, usize)>,
@@ -401,14 +472,18 @@ pub struct Editor {
     /// Whether to confirm each replacement (interactive/query-replace mode)
     search_confirm_each: bool,
 
-    /// Macro storage (key -> list of recorded actions)
-    macros: HashMap<char, Vec<Action>>,
+    /// Macro storage (register or name -> list of recorded actions),
+    /// saved to the macros file
+    macros: crate::input::macros::Macros,
 
     /// Macro recording state (Some(key) if recording, None otherwise)
     macro_recording: Option<MacroRecordingState>,
 
     /// Last recorded macro register (for F12 to replay)
-    last_macro_register: Option<char>,
+    last_macro_register: Option<String>,
+
+    /// Buffers editing a macro (buffer -> macro name)
+    macro_edits: HashMap<BufferId, String>,
 
     /// Pending plugin action receivers (for async action execution)
     #[cfg(feature = "plugins")]
@@ -425,6 +500,13 @@ pub struct Editor {
     /// Stores the keys pressed so far in a chord sequence
     chord_state: Vec<(crossterm::event::KeyCode, crossterm::event::KeyModifiers)>,
 
+    /// Pending chord prefix and whether the keys that can follow it are shown
+    which_key: which_key::WhichKeyState,
+
+    /// Plugin permission requests waiting for an answer, and the answers
+    /// that only last for this session
+    plugin_permissions: plugin_permission_actions::PluginPermissionState,
+
     /// Pending LSP confirmation - language name awaiting user confirmation
     /// When Some, a confirmation popup is shown asking user to approve LSP spawn
     pending_lsp_confirmation: Option<String>,
@@ -436,6 +518,12 @@ pub struct Editor {
     /// Whether auto-revert mode is enabled (automatically reload files when changed on disk)
     auto_revert_enabled: bool,
 
+    /// Reports changes on disk as they happen (None when everything is polled)
+    file_watcher: Option<FileWatcher>,
+
+    /// File watchers registered by language servers
+    lsp_file_watchers: file_watching::LspFileWatchers,
+
     /// Last time we polled for file changes (for auto-revert)
     last_auto_revert_poll: std::time::Instant,
 
@@ -490,6 +578,14 @@ pub struct Editor {
     /// Terminal manager for built-in terminal support
     terminal_manager: crate::services::terminal::TerminalManager,
 
+    /// Socket `fresh --remote` sends files to open on
+    #[cfg(unix)]
+    instance_server: Option<crate::services::instance_server::InstanceServer>,
+
+    /// Clients of `fresh --remote --wait` waiting for their buffers to close
+    #[cfg(unix)]
+    waiting_clients: Vec<instance_server_actions::WaitingClient>,
+
     /// Maps buffer ID to terminal ID (for terminal buffers)
     terminal_buffers: HashMap<BufferId, crate::services::terminal::TerminalId>,
 
@@ -583,11 +679,16 @@ impl Editor {
             dir_context,
             true,
             color_capability,
+            None,
         )
     }
 
     /// Create a new editor with an explicit working directory
     /// This is useful for testing with isolated temporary directories
+    ///
+    /// Files are accessed through `fs_backend` if given, e.g. an SFTP
+    /// backend for a project on another host, and the local filesystem
+    /// otherwise.
     pub fn with_working_dir(
         config: Config,
         width: u16,
@@ -596,13 +697,14 @@ impl Editor {
         dir_context: DirectoryContext,
         plugins_enabled: bool,
         color_capability: crate::view::color_support::ColorCapability,
+        fs_backend: Option<Arc<dyn FsBackend>>,
     ) -> io::Result<Self> {
         Self::with_options(
             config,
             width,
             height,
             working_dir,
-            None,
+            fs_backend,
             plugins_enabled,
             dir_context,
             None,
@@ -660,9 +762,18 @@ impl Editor {
         let working_dir = working_dir
             .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
 
+        // Use provided backend or create default LocalFsBackend
+        let fs_backend = fs_backend.unwrap_or_else(|| Arc::new(LocalFsBackend::new()));
+        let local_fs = fs_backend.is_local();
+
         // Canonicalize working_dir to resolve symlinks and normalize path components
         // This ensures consistent path comparisons throughout the editor
-        let working_dir = working_dir.canonicalize().unwrap_or_else(|_| working_dir);
+        // (a remote working_dir is already resolved on its host)
+        let working_dir = if local_fs {
+            working_dir.canonicalize().unwrap_or(working_dir)
+        } else {
+            working_dir
+        };
 
         // Load theme from config
         let theme = crate::view::theme::Theme::from_name(&config.theme);
@@ -675,6 +786,9 @@ impl Editor {
             grammar_registry.available_syntaxes().len()
         );
 
+        // Tree-sitter grammars installed as shared libraries
+        crate::primitives::dynamic_grammar::load_grammars(&dir_context.grammars_dir());
+
         let keybindings = KeybindingResolver::new(&config);
 
         // Create an empty initial buffer
@@ -716,6 +830,16 @@ impl Editor {
             tracing::warn!("Failed to create Tokio runtime - async features disabled");
         }
 
+        // Watch the workspace for changes made outside the editor
+        let file_watcher = if config.editor.native_file_watching {
+            FileWatcher::new(async_bridge.sender())
+        } else {
+            None
+        };
+        if let Some(file_watcher) = file_watcher.as_ref().filter(|_| local_fs) {
+            file_watcher.watch_tree(&working_dir);
+        }
+
         // Create LSP manager with async support
         let mut lsp = LspManager::new(root_uri);
 
@@ -740,8 +864,6 @@ impl Editor {
         split_view_states.insert(initial_split_id, initial_view_state);
 
         // Initialize filesystem manager for file explorer
-        // Use provided backend or create default LocalFsBackend
-        let fs_backend = fs_backend.unwrap_or_else(|| Arc::new(LocalFsBackend::new()));
         let fs_manager = Arc::new(FsManager::new(fs_backend));
 
         // Initialize command registry (always available, used by both plugins and core)
@@ -846,6 +968,9 @@ impl Editor {
             restart_with_dir: None,
             status_message: None,
             plugin_status_message: None,
+            plugin_status_segments: Vec::new(),
+            git_branch: Default::default(),
+            status_bar_clock: String::new(),
             prompt: None,
             terminal_width: width,
             terminal_height: height,
@@ -884,6 +1009,19 @@ impl Editor {
             pending_signature_help_request: None,
             pending_code_actions_request: None,
             pending_inlay_hints_request: None,
+            pending_folding_range_request: None,
+            pending_document_symbol_request: None,
+            outline_panel: None,
+            workspace_symbol_search: None,
+            workspace_symbol_resolve: None,
+            pending_hierarchy_request: None,
+            hierarchy_panel: None,
+            undo_history_panel: None,
+            diff_views: HashMap::new(),
+            merge_views: HashMap::new(),
+            pending_format_requests: HashMap::new(),
+            semantic_tokens: HashMap::new(),
+            code_lenses: Default::default(),
             hover_symbol_range: None,
             hover_symbol_overlay: None,
             mouse_hover_screen_position: None,
@@ -936,17 +1074,22 @@ impl Editor {
             search_whole_word: false,
             search_use_regex: false,
             search_confirm_each: false,
-            macros: HashMap::new(),
+            macros: crate::input::macros::load_macros(&dir_context.macros_path()),
             macro_recording: None,
             last_macro_register: None,
+            macro_edits: HashMap::new(),
             #[cfg(feature = "plugins")]
             pending_plugin_actions: Vec::new(),
             #[cfg(feature = "plugins")]
             plugin_render_requested: false,
             chord_state: Vec::new(),
+            which_key: Default::default(),
+            plugin_permissions: Default::default(),
             pending_lsp_confirmation: None,
             pending_close_buffer: None,
             auto_revert_enabled: true,
+            file_watcher,
+            lsp_file_watchers: Default::default(),
             last_auto_revert_poll: time_source.now(),
             last_file_tree_poll: time_source.now(),
             file_mod_times: HashMap::new(),
@@ -970,6 +1113,10 @@ impl Editor {
             warning_domains: WarningDomainRegistry::new(),
             update_checker,
             terminal_manager: crate::services::terminal::TerminalManager::new(),
+            #[cfg(unix)]
+            instance_server: None,
+            #[cfg(unix)]
+            waiting_clients: Vec::new(),
             terminal_buffers: HashMap::new(),
             terminal_backing_files: HashMap::new(),
             terminal_log_files: HashMap::new(),
@@ -1651,6 +1798,7 @@ impl Editor {
         match event {
             Event::Insert { .. } | Event::Delete { .. } | Event::BulkEdit { .. } => {
                 self.invalidate_layouts_for_buffer(self.active_buffer());
+                self.mark_outline_edited(self.active_buffer());
             }
             Event::Batch { events, .. } => {
                 let has_edits = events
@@ -1658,6 +1806,7 @@ impl Editor {
                     .any(|e| matches!(e, Event::Insert { .. } | Event::Delete { .. }));
                 if has_edits {
                     self.invalidate_layouts_for_buffer(self.active_buffer());
+                    self.mark_outline_edited(self.active_buffer());
                 }
             }
             _ => {}
@@ -1758,6 +1907,13 @@ impl Editor {
         // Sort edits by position descending (required by apply_bulk_edits)
         edits.sort_by(|a, b| b.0.cmp(&a.0));
 
+        // Keep the snippet tabstops on their text (e.g. typing into mirrored tabstops)
+        if let Some(session) = &mut state.snippet_session {
+            for (pos, del, text) in &edits {
+                session.adjust_for_edit(*pos, *del, text.len());
+            }
+        }
+
         // Convert to references for apply_bulk_edits
         let edit_refs: Vec<(usize, usize, &str)> = edits
             .iter()
@@ -2261,6 +2417,11 @@ impl Editor {
         }
     }
 
+    /// The project's working directory
+    pub fn working_dir(&self) -> &Path {
+        &self.working_dir
+    }
+
     /// Check if the editor should quit
     pub fn should_quit(&self) -> bool {
         self.should_quit
@@ -2439,6 +2600,7 @@ impl Editor {
             prompt_type,
             PromptType::OpenFile
                 | PromptType::SwitchProject
+                | PromptType::CompareWithFile
                 | PromptType::SaveFileAs
                 | PromptType::Command
         );
@@ -2630,6 +2792,11 @@ impl Editor {
 
     /// Cancel the current prompt and return to normal mode
     pub fn cancel_prompt(&mut self) {
+        let plugin_permission = self
+            .prompt
+            .as_ref()
+            .is_some_and(|p| p.prompt_type == PromptType::ConfirmPluginPermission);
+
         // Determine prompt type and reset appropriate history navigation
         if let Some(ref prompt) = self.prompt {
             match &prompt.prompt_type {
@@ -2658,7 +2825,7 @@ impl Editor {
                     };
                     self.apply_event_to_active_buffer(&remove_overlay_event);
                 }
-                PromptType::OpenFile | PromptType::SwitchProject => {
+                PromptType::OpenFile | PromptType::SwitchProject | PromptType::CompareWithFile => {
                     // Clear file browser state
                     self.file_open_state = None;
                     self.file_browser_layout = None;
@@ -2670,6 +2837,11 @@ impl Editor {
         self.prompt = None;
         self.pending_search_range = None;
         self.status_message = Some(t!("search.cancelled").to_string());
+
+        // The plugin is waiting for an answer, so cancelling denies it
+        if plugin_permission {
+            self.handle_confirm_plugin_permission("");
+        }
     }
 
     /// Get the confirmed input and prompt type, consuming the prompt
@@ -2685,11 +2857,21 @@ impl Editor {
                 PromptType::Command
                     | PromptType::OpenFile
                     | PromptType::SwitchProject
+                    | PromptType::CompareWithFile
                     | PromptType::SaveFileAs
                     | PromptType::StopLspServer
                     | PromptType::SelectTheme
                     | PromptType::SelectLocale
                     | PromptType::SwitchToTab
+                    | PromptType::GotoSymbol
+                    | PromptType::WorkspaceSymbol
+                    | PromptType::CodeLens
+                    | PromptType::ReopenWithEncoding
+                    | PromptType::SaveWithEncoding
+                    | PromptType::InsertSnippet
+                    | PromptType::PlayMacro
+                    | PromptType::PlayMacroOnLines
+                    | PromptType::PlayMacroTimes
             ) {
                 // Use the selected suggestion if any
                 if let Some(selected_idx) = prompt.selected_suggestion {
@@ -2850,7 +3032,7 @@ impl Editor {
                 // Reset history navigation when user types - allows Up to navigate history
                 self.replace_history.reset_navigation();
             }
-            PromptType::OpenFile | PromptType::SwitchProject => {
+            PromptType::OpenFile | PromptType::SwitchProject | PromptType::CompareWithFile => {
                 // For OpenFile/SwitchProject, update the file browser filter (native implementation)
                 self.update_file_open_filter();
             }
@@ -2879,15 +3061,35 @@ impl Editor {
                     },
                 );
             }
+            PromptType::WorkspaceSymbol => {
+                // Results arrive asynchronously, see workspace_symbol_actions
+                self.workspace_symbol_query_changed(input);
+            }
             PromptType::SwitchToTab
             | PromptType::SelectTheme
             | PromptType::SelectLocale
-            | PromptType::StopLspServer => {
+            | PromptType::StopLspServer
+            | PromptType::GotoSymbol
+            | PromptType::CodeLens
+            | PromptType::ReopenWithEncoding
+            | PromptType::SaveWithEncoding
+            | PromptType::InsertSnippet
+            | PromptType::RecordMacro
+            | PromptType::PlayMacro
+            | PromptType::EditMacro
+            | PromptType::PlayMacroOnLines
+            | PromptType::PlayMacroTimes => {
                 // Filter suggestions using fuzzy matching
                 use crate::input::fuzzy::{fuzzy_match, FuzzyMatch};
 
                 if let Some(prompt) = &mut self.prompt {
-                    let match_description = matches!(prompt.prompt_type, PromptType::SelectLocale);
+                    let match_description = matches!(
+                        prompt.prompt_type,
+                        PromptType::SelectLocale
+                            | PromptType::ReopenWithEncoding
+                            | PromptType::SaveWithEncoding
+                            | PromptType::InsertSnippet
+                    );
 
                     if let Some(original) = &prompt.original_suggestions {
                         // Apply fuzzy filtering with scoring
@@ -2955,6 +3157,13 @@ impl Editor {
 
                     // Send didOpen for all open buffers of this language
                     self.resend_did_open_for_language(&language);
+
+                    // Let an open workspace symbol search include the new server
+                    self.resend_workspace_symbol_query();
+
+                    // Earlier requests were answered without tokens or lenses
+                    self.reset_semantic_tokens();
+                    self.reset_code_lenses();
                 }
                 AsyncMessage::LspError {
                     language,
@@ -3085,11 +3294,86 @@ impl Editor {
                 } => {
                     self.handle_lsp_inlay_hints(request_id, uri, hints);
                 }
+                AsyncMessage::LspFoldingRanges {
+                    request_id,
+                    uri,
+                    ranges,
+                } => {
+                    self.handle_lsp_folding_ranges(request_id, uri, ranges);
+                }
+                AsyncMessage::LspDocumentSymbols {
+                    request_id,
+                    uri,
+                    symbols,
+                } => {
+                    self.handle_lsp_document_symbols(request_id, uri, symbols);
+                }
+                AsyncMessage::LspHierarchyItems { request_id, items } => {
+                    self.handle_lsp_hierarchy_items(request_id, items);
+                }
+                AsyncMessage::LspHierarchyChildren {
+                    request_id,
+                    children,
+                } => {
+                    self.handle_lsp_hierarchy_children(request_id, children);
+                }
+                AsyncMessage::LspFormatting { request_id, result } => {
+                    self.handle_lsp_formatting(request_id, result);
+                }
+                AsyncMessage::LspSemanticTokens {
+                    request_id,
+                    uri,
+                    legend,
+                    range,
+                    tokens,
+                } => {
+                    self.handle_lsp_semantic_tokens(request_id, uri, legend, range, tokens);
+                }
+                AsyncMessage::LspCodeLenses {
+                    request_id,
+                    uri,
+                    lenses,
+                } => {
+                    self.handle_lsp_code_lenses(request_id, uri, lenses);
+                }
+                AsyncMessage::LspCodeLensesResolved {
+                    request_id,
+                    uri: _,
+                    lenses,
+                } => {
+                    self.handle_lsp_code_lenses_resolved(request_id, lenses);
+                }
+                AsyncMessage::LspExecuteCommand { request_id, result } => {
+                    self.handle_lsp_execute_command(request_id, result);
+                }
+                AsyncMessage::LspCodeLensRefresh { language } => {
+                    self.handle_lsp_code_lens_refresh(language);
+                }
+                AsyncMessage::LspWorkspaceSymbols {
+                    request_id,
+                    language,
+                    symbols,
+                } => {
+                    self.handle_lsp_workspace_symbols(request_id, language, symbols);
+                }
+                AsyncMessage::LspWorkspaceSymbolResolved { request_id, symbol } => {
+                    self.handle_lsp_workspace_symbol_resolved(request_id, symbol);
+                }
+                AsyncMessage::LspFileWatchersRegistered {
+                    language,
+                    id,
+                    watchers,
+                } => {
+                    self.handle_lsp_file_watchers_registered(language, id, watchers);
+                }
+                AsyncMessage::LspFileWatchersUnregistered { language, id } => {
+                    self.handle_lsp_file_watchers_unregistered(language, id);
+                }
                 AsyncMessage::LspServerQuiescent { language } => {
                     self.handle_lsp_server_quiescent(language);
                 }
-                AsyncMessage::FileChanged { path } => {
-                    self.handle_async_file_changed(path);
+                AsyncMessage::FilesChanged(events) => {
+                    self.handle_files_changed(events);
                 }
                 AsyncMessage::GitStatusChanged { status } => {
                     tracing::info!("Git status changed: {}", status);
@@ -3122,6 +3406,14 @@ impl Editor {
                 } => {
                     self.handle_custom_notification(language, method, params);
                 }
+                AsyncMessage::LspApplyEdit {
+                    language,
+                    server_request_id,
+                    label,
+                    edit,
+                } => {
+                    self.handle_lsp_apply_edit(language, server_request_id, label, edit);
+                }
                 AsyncMessage::LspServerRequest {
                     language,
                     server_command,
@@ -3167,6 +3459,19 @@ impl Editor {
                 AsyncMessage::FileOpenDirectoryLoaded(result) => {
                     self.handle_file_open_directory_loaded(result);
                 }
+                AsyncMessage::PrivilegedSaveChecked {
+                    buffer_id,
+                    needs_password,
+                } => {
+                    self.handle_privileged_save_checked(buffer_id, needs_password);
+                }
+                AsyncMessage::PrivilegedSaveFinished {
+                    buffer_id,
+                    copy,
+                    result,
+                } => {
+                    self.handle_privileged_save_finished(buffer_id, copy, result);
+                }
                 AsyncMessage::TerminalOutput { terminal_id } => {
                     // Terminal output received - check if we should auto-jump back to terminal mode
                     tracing::trace!("Terminal output received for {:?}", terminal_id);
@@ -3208,6 +3513,10 @@ impl Editor {
                     }
                     self.terminal_manager.close(terminal_id);
                 }
+                #[cfg(unix)]
+                AsyncMessage::OpenFiles(pending) => {
+                    self.handle_open_files_request(pending);
+                }
             }
         }
 
@@ -3246,8 +3555,50 @@ impl Editor {
         let file_changes = self.poll_file_changes();
         let tree_changes = self.poll_file_tree_changes();
 
+        // Keep the outline panel in sync with the active buffer
+        let outline_changes = self.update_outline_panel();
+
+        // Keep the undo history panel in sync with its buffer's history
+        let undo_history_changes = self.update_undo_history_panel();
+
+        // Diff the diff views again whose files were edited elsewhere
+        let diff_changes = self.update_diff_views();
+
+        // Align the merge views again whose files were edited elsewhere
+        let merge_changes = self.update_merge_views();
+
+        // Send the workspace symbol query once the user pauses typing
+        let symbol_search_changes = self.poll_workspace_symbol_search();
+
+        // Keep the semantic tokens of the active buffer up to date
+        self.poll_semantic_tokens();
+
+        // Keep the code lenses of the active buffer up to date
+        self.poll_code_lenses();
+
+        // Keep the git branch and clock of the status bar up to date
+        let status_bar_changes = self.poll_status_bar();
+
+        // List the keys that can follow a pending chord prefix
+        let which_key_changes = self.poll_which_key();
+
+        // Ask the user about capabilities plugins are waiting for
+        let permission_prompt = self.poll_plugin_permission_prompt();
+
         // Trigger render if any async messages, plugin commands were processed, or plugin requested render
-        needs_render || processed_any_commands || plugin_render || file_changes || tree_changes
+        needs_render
+            || processed_any_commands
+            || plugin_render
+            || file_changes
+            || tree_changes
+            || outline_changes
+            || undo_history_changes
+            || diff_changes
+            || merge_changes
+            || symbol_search_changes
+            || status_bar_changes
+            || which_key_changes
+            || permission_prompt
     }
 
     /// Update LSP status bar string from active progress operations
@@ -3436,6 +3787,10 @@ impl Editor {
 
             // Update editor mode (for vi mode and other modal editing)
             snapshot.editor_mode = self.editor_mode.clone();
+
+            // Update what plugins were allowed to do (checked by the plugin ops)
+            snapshot.plugin_permissions = self.effective_plugin_permissions();
+            snapshot.config_dir = self.dir_context.config_dir.clone();
         }
     }
 
@@ -3663,6 +4018,9 @@ impl Editor {
             PluginCommand::SetStatus { message } => {
                 self.handle_set_status(message);
             }
+            PluginCommand::SetStatusSegment { name, text } => {
+                self.handle_set_status_segment(name, text);
+            }
             PluginCommand::ApplyTheme { theme_name } => {
                 self.apply_theme(&theme_name);
             }
@@ -4248,6 +4606,21 @@ impl Editor {
                     tracing::warn!("Scroll sync group {} not found", group_id);
                 }
             }
+            PluginCommand::RequestPluginPermission {
+                plugin_name,
+                capability,
+                entry,
+                description,
+                request_id,
+            } => {
+                self.handle_request_plugin_permission(
+                    plugin_name,
+                    capability,
+                    entry,
+                    description,
+                    request_id,
+                );
+            }
 
             // ==================== Composite Buffer Commands ====================
             PluginCommand::CreateCompositeBuffer {
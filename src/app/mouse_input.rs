@@ -704,6 +704,15 @@ impl Editor {
         // Check status bar indicators
         if let Some((status_row, _status_x, _status_width)) = self.cached_layout.status_bar_area {
             if row == status_row {
+                // Check encoding indicator area
+                if let Some((enc_row, enc_start, enc_end)) =
+                    self.cached_layout.status_bar_encoding_area
+                {
+                    if row == enc_row && col >= enc_start && col < enc_end {
+                        return Some(HoverTarget::StatusBarEncodingIndicator);
+                    }
+                }
+
                 // Check line ending indicator area
                 if let Some((le_row, le_start, le_end)) =
                     self.cached_layout.status_bar_line_ending_area
@@ -1025,6 +1034,15 @@ impl Editor {
         // Check if click is on status bar indicators
         if let Some((status_row, _status_x, _status_width)) = self.cached_layout.status_bar_area {
             if row == status_row {
+                // Check encoding indicator - click opens encoding selector
+                if let Some((enc_row, enc_start, enc_end)) =
+                    self.cached_layout.status_bar_encoding_area
+                {
+                    if row == enc_row && col >= enc_start && col < enc_end {
+                        return self.handle_action(Action::ReopenWithEncoding);
+                    }
+                }
+
                 // Check line ending indicator - click opens line ending selector
                 if let Some((le_row, le_start, le_end)) =
                     self.cached_layout.status_bar_line_ending_area
@@ -8,8 +8,9 @@ use std::path::Path;
 use std::process::{Command, Stdio};
 use std::time::Duration;
 
+use super::format_actions::FormatKind;
 use super::Editor;
-use crate::config::{FormatterConfig, OnSaveAction};
+use crate::config::{FormatterConfig, FormatterSource, OnSaveAction};
 use crate::model::event::Event;
 use crate::services::lsp::manager::detect_language;
 use rust_i18n::t;
@@ -51,24 +52,36 @@ impl Editor {
         // Run formatter if format_on_save is enabled
         if lang_config.format_on_save {
             if let Some(ref formatter) = lang_config.formatter {
-                match self.run_formatter(formatter, &path) {
-                    ActionResult::Success(output) => {
-                        self.replace_buffer_with_output(&output)?;
-                        // Re-save after formatting
-                        if let Err(e) = self.active_state_mut().buffer.save() {
-                            return Err(format!("Failed to re-save after format: {}", e));
-                        }
-                        self.active_event_log_mut().mark_saved();
-                        ran_any_action = true;
-                    }
-                    ActionResult::CommandNotFound(cmd) => {
-                        self.status_message = Some(format!(
-                            "Formatter '{}' not found (install it for auto-formatting)",
-                            cmd
-                        ));
+                if formatter.source == FormatterSource::Lsp {
+                    // The server answers later; the buffer is re-saved once
+                    // the edits arrive (see format_actions)
+                    if let Err(e) = self.request_lsp_format(FormatKind::Save) {
+                        self.status_message = Some(e);
                     }
-                    ActionResult::Error(e) => {
-                        return Err(e);
+                } else {
+                    match self.run_formatter(formatter, &path) {
+                        ActionResult::Success(output) => {
+                            self.replace_buffer_with_output(&output)?;
+                            // Re-save after formatting
+                            if let Err(e) = self.active_state_mut().buffer.save() {
+                                return Err(format!("Failed to re-save after format: {}", e));
+                            }
+                            self.active_event_log_mut().mark_saved();
+                            // Our own write, not an external change to revert
+                            if let Ok(mtime) = std::fs::metadata(&path).and_then(|m| m.modified()) {
+                                self.file_mod_times.insert(path.clone(), mtime);
+                            }
+                            ran_any_action = true;
+                        }
+                        ActionResult::CommandNotFound(cmd) => {
+                            self.status_message = Some(format!(
+                                "Formatter '{}' not found (install it for auto-formatting)",
+                                cmd
+                            ));
+                        }
+                        ActionResult::Error(e) => {
+                            return Err(e);
+                        }
                     }
                 }
             }
@@ -129,6 +142,10 @@ impl Editor {
             None => return Err(format!("No formatter configured for {}", language)),
         };
 
+        if formatter.source == FormatterSource::Lsp {
+            return self.request_lsp_format(FormatKind::Buffer);
+        }
+
         match self.run_formatter(&formatter, &path) {
             ActionResult::Success(output) => {
                 self.replace_buffer_with_output(&output)?;
@@ -0,0 +1,460 @@
+//! Document outline actions for the Editor.
+//!
+//! "Go to Symbol" lists the symbols of the active buffer in a fuzzy picker,
+//! and the outline panel shows them in a side split that follows the cursor.
+//! Symbols come from the language server (`textDocument/documentSymbol`) when
+//! one is running for the buffer, and from the tree-sitter tags queries
+//! otherwise (see `primitives::outline`). Requests to the language server are
+//! asynchronous: the target is remembered in `pending_document_symbol_request`
+//! and filled in when the response arrives.
+
+use std::time::{Duration, Instant};
+
+use rust_i18n::t;
+
+use crate::input::commands::Suggestion;
+use crate::model::event::{BufferId, Event, SplitDirection, SplitId};
+use crate::primitives::outline::{
+    kind_label, symbol_at, symbols_from_lsp, tree_sitter_symbols, OutlineSymbol,
+};
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::view::prompt::{Prompt, PromptType};
+use crate::view::split::SplitViewState;
+
+use super::Editor;
+
+/// Name of the outline panel buffer
+pub(super) const OUTLINE_BUFFER_NAME: &str = "*Outline*";
+
+/// How long the source buffer must be left alone before the panel is refreshed
+const OUTLINE_REFRESH_DELAY: Duration = Duration::from_millis(500);
+
+/// Where requested symbols are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum OutlineTarget {
+    /// The "Go to Symbol" picker
+    Picker,
+    /// The outline panel
+    Panel,
+}
+
+/// State of the outline side panel
+#[derive(Debug)]
+pub(super) struct OutlinePanel {
+    /// The `*Outline*` virtual buffer
+    buffer_id: BufferId,
+    /// Split showing the outline buffer
+    split_id: SplitId,
+    /// Buffer whose symbols are listed
+    source: BufferId,
+    /// Listed symbols, one per line of the outline buffer
+    symbols: Vec<OutlineSymbol>,
+    /// Time of the last edit of the source since the symbols were fetched
+    edited_at: Option<Instant>,
+    /// Symbol the panel cursor was last moved to
+    current: Option<usize>,
+}
+
+impl Editor {
+    /// Open the "Go to Symbol" picker for the active buffer
+    pub(super) fn start_goto_symbol_prompt(&mut self) {
+        self.request_document_symbols(self.active_buffer(), OutlineTarget::Picker);
+    }
+
+    /// Show or hide the outline panel
+    pub(super) fn toggle_outline_panel(&mut self) {
+        if self.outline_panel.is_some() && self.outline_panel_is_open() {
+            self.close_outline_panel();
+        } else {
+            self.open_outline_panel();
+        }
+    }
+
+    /// Jump to the symbol under the cursor of the outline panel
+    pub(super) fn outline_jump(&mut self) {
+        let Some(panel) = &self.outline_panel else {
+            return;
+        };
+        if self.active_buffer() != panel.buffer_id {
+            return;
+        }
+        let (source, panel_split) = (panel.source, panel.split_id);
+
+        let state = self.active_state();
+        let index = state
+            .text_properties
+            .get_at(state.cursors.primary().position)
+            .iter()
+            .find_map(|prop| prop.get_as::<usize>("symbol"));
+        let Some(position) = index.and_then(|i| panel.symbols.get(i).map(|symbol| symbol.position))
+        else {
+            return;
+        };
+
+        let Some(source_split) = self
+            .split_manager
+            .splits_for_buffer(source)
+            .into_iter()
+            .find(|&split| split != panel_split)
+        else {
+            self.set_status_message(t!("outline.source_not_visible").to_string());
+            return;
+        };
+
+        self.save_current_split_view_state();
+        self.split_manager.set_active_split(source_split);
+        self.restore_current_split_view_state();
+        self.goto_symbol_position(position);
+    }
+
+    /// Move the primary cursor of the active buffer to a symbol
+    pub(super) fn goto_symbol_position(&mut self, position: usize) {
+        let state = self.active_state_mut();
+        let position = state
+            .buffer
+            .snap_to_char_boundary(position.min(state.buffer.len()));
+        let cursor = *state.cursors.primary();
+        let event = Event::MoveCursor {
+            cursor_id: state.cursors.primary_id(),
+            old_position: cursor.position,
+            new_position: position,
+            old_anchor: cursor.anchor,
+            new_anchor: None,
+            old_sticky_column: cursor.sticky_column,
+            new_sticky_column: 0,
+        };
+        state.folds.unfold_at(&mut state.marker_list, position);
+        state.apply(&event);
+    }
+
+    /// Note an edit of `buffer_id` so the outline panel gets refreshed
+    pub(super) fn mark_outline_edited(&mut self, buffer_id: BufferId) {
+        let now = self.time_source.now();
+        if let Some(panel) = &mut self.outline_panel {
+            if panel.source == buffer_id {
+                panel.edited_at = Some(now);
+            }
+        }
+    }
+
+    /// Keep the outline panel in sync with the active buffer and its cursor
+    ///
+    /// Called from the main loop; returns true if a redraw is needed.
+    pub(super) fn update_outline_panel(&mut self) -> bool {
+        if self.outline_panel.is_none() {
+            return false;
+        }
+        if !self.outline_panel_is_open() {
+            // The panel was closed through other means (e.g. closing its split)
+            if let Some(panel) = self.outline_panel.take() {
+                if self
+                    .split_manager
+                    .splits_for_buffer(panel.buffer_id)
+                    .is_empty()
+                {
+                    let _ = self.close_buffer(panel.buffer_id);
+                }
+            }
+            return true;
+        }
+
+        // Don't take over the request slot of an opening picker
+        if matches!(
+            self.pending_document_symbol_request,
+            Some((_, _, OutlineTarget::Picker))
+        ) {
+            return false;
+        }
+
+        let active_buffer = self.active_buffer();
+        let Some(panel) = &mut self.outline_panel else {
+            return false;
+        };
+        if self.split_manager.active_split() == panel.split_id {
+            // The user is browsing the outline, leave its cursor alone
+            return false;
+        }
+
+        // Follow the active buffer
+        if active_buffer != panel.source {
+            panel.source = active_buffer;
+            panel.edited_at = None;
+            self.request_document_symbols(active_buffer, OutlineTarget::Panel);
+            return true;
+        }
+
+        // Refresh once the source has been left alone for a moment
+        if panel
+            .edited_at
+            .is_some_and(|at| self.time_source.elapsed_since(at) >= OUTLINE_REFRESH_DELAY)
+        {
+            panel.edited_at = None;
+            self.request_document_symbols(active_buffer, OutlineTarget::Panel);
+            return true;
+        }
+
+        // Follow the cursor
+        let Some(state) = self.buffers.get(&active_buffer) else {
+            return false;
+        };
+        let current = symbol_at(&panel.symbols, state.cursors.primary().position);
+        if current == panel.current {
+            return false;
+        }
+        panel.current = current;
+        let (buffer_id, split_id) = (panel.buffer_id, panel.split_id);
+        self.move_outline_cursor(buffer_id, split_id, current.unwrap_or(0));
+        true
+    }
+
+    /// Whether the outline panel's split still shows the outline buffer
+    fn outline_panel_is_open(&self) -> bool {
+        self.outline_panel.as_ref().is_some_and(|panel| {
+            self.buffers.contains_key(&panel.buffer_id)
+                && self.split_manager.get_buffer_id(panel.split_id) == Some(panel.buffer_id)
+        })
+    }
+
+    /// Create the outline buffer in a split to the right of the active one
+    fn open_outline_panel(&mut self) {
+        let source = self.active_buffer();
+        let source_split = self.split_manager.active_split();
+
+        let buffer_id = self.create_virtual_buffer(
+            OUTLINE_BUFFER_NAME.to_string(),
+            "outline".to_string(),
+            true,
+        );
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = true;
+            state.margins.set_line_numbers(false);
+        }
+
+        self.save_current_split_view_state();
+        let split_id =
+            match self
+                .split_manager
+                .split_active(SplitDirection::Vertical, buffer_id, 0.75)
+            {
+                Ok(split_id) => split_id,
+                Err(e) => {
+                    let _ = self.close_buffer(buffer_id);
+                    self.set_status_message(t!("split.error", error = e.to_string()).to_string());
+                    return;
+                }
+            };
+        let mut view_state =
+            SplitViewState::with_buffer(self.terminal_width, self.terminal_height, buffer_id);
+        view_state.viewport.line_wrap_enabled = false;
+        self.split_view_states.insert(split_id, view_state);
+
+        // Keep editing the source buffer
+        self.split_manager.set_active_split(source_split);
+        self.restore_current_split_view_state();
+
+        self.outline_panel = Some(OutlinePanel {
+            buffer_id,
+            split_id,
+            source,
+            symbols: Vec::new(),
+            edited_at: None,
+            current: None,
+        });
+        self.request_document_symbols(source, OutlineTarget::Panel);
+    }
+
+    /// Remove the outline split and buffer
+    fn close_outline_panel(&mut self) {
+        let Some(panel) = self.outline_panel.take() else {
+            return;
+        };
+        let was_active = self.split_manager.active_split() == panel.split_id;
+        if self.split_manager.close_split(panel.split_id).is_ok() {
+            self.split_view_states.remove(&panel.split_id);
+            if was_active {
+                self.sync_split_view_state_to_editor_state();
+            }
+        }
+        let _ = self.close_buffer(panel.buffer_id);
+    }
+
+    /// Get the symbols of a buffer and show them in `target`
+    fn request_document_symbols(&mut self, buffer_id: BufferId, target: OutlineTarget) {
+        let request_id = self.next_lsp_request_id;
+
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+                let result = handle.document_symbol(request_id, uri.clone());
+                if let Err(e) = &result {
+                    tracing::debug!("Failed to request document symbols: {}", e);
+                }
+                result.is_ok()
+            })
+            .unwrap_or(false);
+
+        if sent {
+            self.next_lsp_request_id += 1;
+            self.pending_document_symbol_request = Some((request_id, buffer_id, target));
+        } else {
+            self.show_outline(buffer_id, Vec::new(), target);
+        }
+    }
+
+    /// Handle LSP document symbol response
+    pub(super) fn handle_lsp_document_symbols(
+        &mut self,
+        request_id: u64,
+        uri: String,
+        symbols: Option<lsp_types::DocumentSymbolResponse>,
+    ) {
+        let Some((pending_id, buffer_id, target)) = self.pending_document_symbol_request else {
+            return;
+        };
+        if pending_id != request_id {
+            tracing::debug!(
+                "Ignoring stale document symbol response (request_id={})",
+                request_id
+            );
+            return;
+        }
+        self.pending_document_symbol_request = None;
+
+        tracing::debug!(
+            "Received document symbols for {} (request_id={})",
+            uri,
+            request_id
+        );
+
+        let symbols = match (symbols, self.buffers.get(&buffer_id)) {
+            (Some(symbols), Some(state)) => symbols_from_lsp(&state.buffer, symbols),
+            _ => Vec::new(),
+        };
+        self.show_outline(buffer_id, symbols, target);
+    }
+
+    /// Show `lsp_symbols` (or tree-sitter symbols when empty) of a buffer in `target`
+    fn show_outline(
+        &mut self,
+        buffer_id: BufferId,
+        lsp_symbols: Vec<OutlineSymbol>,
+        target: OutlineTarget,
+    ) {
+        let Some(state) = self.buffers.get(&buffer_id) else {
+            return;
+        };
+        let symbols = if lsp_symbols.is_empty() {
+            match state.highlighter.language() {
+                Some(language) => tree_sitter_symbols(&state.buffer, language),
+                None => Vec::new(),
+            }
+        } else {
+            lsp_symbols
+        };
+
+        match target {
+            OutlineTarget::Picker => self.open_symbol_picker(buffer_id, symbols),
+            OutlineTarget::Panel => self.fill_outline_panel(buffer_id, symbols),
+        }
+    }
+
+    /// Open the fuzzy picker over the symbols of the active buffer
+    fn open_symbol_picker(&mut self, buffer_id: BufferId, symbols: Vec<OutlineSymbol>) {
+        // The user moved on while the language server was busy
+        if buffer_id != self.active_buffer() || self.prompt.is_some() {
+            return;
+        }
+        if symbols.is_empty() {
+            self.set_status_message(t!("outline.no_symbols").to_string());
+            return;
+        }
+
+        let current = symbol_at(&symbols, self.active_state().cursors.primary().position);
+        let suggestions: Vec<Suggestion> = symbols
+            .into_iter()
+            .map(|symbol| {
+                let kind = kind_label(symbol.kind);
+                Suggestion {
+                    text: format!("{}{}", "  ".repeat(symbol.depth), symbol.name),
+                    description: Some(match symbol.detail {
+                        Some(detail) => format!("{} {}", kind, detail),
+                        None => kind.to_string(),
+                    }),
+                    value: Some(symbol.position.to_string()),
+                    disabled: false,
+                    keybinding: None,
+                    source: None,
+                }
+            })
+            .collect();
+
+        let mut prompt = Prompt::with_suggestions(
+            t!("outline.goto_symbol_prompt").to_string(),
+            PromptType::GotoSymbol,
+            suggestions,
+        );
+        if current.is_some() {
+            prompt.selected_suggestion = current;
+        }
+        self.prompt = Some(prompt);
+    }
+
+    /// Replace the content of the outline panel with the symbols of `source`
+    fn fill_outline_panel(&mut self, source: BufferId, symbols: Vec<OutlineSymbol>) {
+        let Some(panel) = &self.outline_panel else {
+            return;
+        };
+        if panel.source != source {
+            return;
+        }
+        let (buffer_id, split_id) = (panel.buffer_id, panel.split_id);
+
+        let entries = if symbols.is_empty() {
+            vec![TextPropertyEntry::text(format!(
+                "{}\n",
+                t!("outline.no_symbols")
+            ))]
+        } else {
+            symbols
+                .iter()
+                .enumerate()
+                .map(|(i, symbol)| {
+                    TextPropertyEntry::text(format!(
+                        "{}{}\n",
+                        "  ".repeat(symbol.depth),
+                        symbol.name
+                    ))
+                    .with_property("symbol", serde_json::json!(i))
+                })
+                .collect()
+        };
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to set outline content: {}", e);
+            return;
+        }
+
+        let current = self
+            .buffers
+            .get(&source)
+            .and_then(|state| symbol_at(&symbols, state.cursors.primary().position));
+        if let Some(panel) = &mut self.outline_panel {
+            panel.symbols = symbols;
+            panel.current = current;
+        }
+        self.move_outline_cursor(buffer_id, split_id, current.unwrap_or(0));
+    }
+
+    /// Put the outline panel's cursor on the line of a symbol
+    fn move_outline_cursor(&mut self, buffer_id: BufferId, split_id: SplitId, index: usize) {
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        let position = state.buffer.line_start_offset(index).unwrap_or(0);
+        let cursor = state.cursors.primary_mut();
+        cursor.position = position;
+        cursor.anchor = None;
+        cursor.sticky_column = 0;
+        if let Some(view_state) = self.split_view_states.get_mut(&split_id) {
+            view_state.cursors = state.cursors.clone();
+        }
+    }
+}
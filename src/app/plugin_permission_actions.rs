@@ -0,0 +1,193 @@
+//! Asking the user to allow what a plugin's manifest declares
+//!
+//! Plugin ops check their capabilities against the `plugin_permissions`
+//! config (see `services::plugins::permissions`). The first time a plugin
+//! needs a declared entry that isn't allowed yet, it sends a
+//! `RequestPluginPermission` command and waits. Requests are queued and
+//! asked one at a time in a prompt whenever no other prompt is open.
+//! "Yes" stores the entry in the user config, "once" allows it until the
+//! editor is restarted, and anything else denies it until then.
+
+use std::collections::HashMap;
+
+use rust_i18n::t;
+
+use crate::config::PluginPermissions;
+use crate::config_io::{ConfigLayer, ConfigResolver};
+use crate::services::plugins::api::PluginResponse;
+use crate::services::plugins::permissions::Capability;
+use crate::view::prompt::PromptType;
+
+use super::Editor;
+
+/// A manifest entry a plugin is waiting to be allowed
+#[derive(Debug)]
+pub(super) struct PluginPermissionRequest {
+    plugin_name: String,
+    capability: Capability,
+    entry: String,
+    description: String,
+    /// Requests of the plugin waiting for the answer
+    request_ids: Vec<u64>,
+}
+
+/// Answers to the permission prompts of this session
+#[derive(Debug, Default)]
+pub(super) struct PluginPermissionState {
+    /// Requests not answered yet, the first one is being asked
+    pending: Vec<PluginPermissionRequest>,
+    /// Entries allowed until the editor is restarted
+    allowed_once: HashMap<String, PluginPermissions>,
+    /// Entries denied until the editor is restarted
+    denied: HashMap<String, PluginPermissions>,
+}
+
+impl Editor {
+    /// Handle RequestPluginPermission command
+    pub(super) fn handle_request_plugin_permission(
+        &mut self,
+        plugin_name: String,
+        capability: Capability,
+        entry: String,
+        description: String,
+        request_id: u64,
+    ) {
+        let decided = |permissions: &HashMap<String, PluginPermissions>| {
+            permissions
+                .get(&plugin_name)
+                .is_some_and(|p| capability.granted(p).contains(&entry))
+        };
+        let state = &self.plugin_permissions;
+        let answer = if decided(&self.config.plugin_permissions) || decided(&state.allowed_once) {
+            Some(true)
+        } else if decided(&state.denied) {
+            Some(false)
+        } else {
+            None
+        };
+        if let Some(granted) = answer {
+            self.send_plugin_response(PluginResponse::PluginPermission {
+                request_id,
+                granted,
+            });
+            return;
+        }
+
+        // Ask once for all requests of the same entry
+        let pending = &mut self.plugin_permissions.pending;
+        match pending.iter_mut().find(|r| {
+            r.plugin_name == plugin_name && r.capability == capability && r.entry == entry
+        }) {
+            Some(request) => request.request_ids.push(request_id),
+            None => pending.push(PluginPermissionRequest {
+                plugin_name,
+                capability,
+                entry,
+                description,
+                request_ids: vec![request_id],
+            }),
+        }
+    }
+
+    /// Ask about the next permission request if no prompt is open
+    ///
+    /// Returns true if a prompt was started.
+    pub(super) fn poll_plugin_permission_prompt(&mut self) -> bool {
+        if self.prompt.is_some() {
+            return false;
+        }
+        let Some(request) = self.plugin_permissions.pending.first() else {
+            return false;
+        };
+        let plugin = &request.plugin_name;
+        let what = &request.description;
+        let message = match request.capability {
+            Capability::FsRead => t!("plugin.permission_fs_read", plugin = plugin, what = what),
+            Capability::FsWrite => t!("plugin.permission_fs_write", plugin = plugin, what = what),
+            Capability::Process => t!("plugin.permission_process", plugin = plugin, what = what),
+            Capability::Env => t!("plugin.permission_env", plugin = plugin, what = what),
+        };
+        self.start_prompt(message.to_string(), PromptType::ConfirmPluginPermission);
+        true
+    }
+
+    /// Handle the answer to the permission prompt
+    pub(super) fn handle_confirm_plugin_permission(&mut self, input: &str) {
+        if self.plugin_permissions.pending.is_empty() {
+            return;
+        }
+        let request = self.plugin_permissions.pending.remove(0);
+        let input = input.trim().to_lowercase();
+        let granted = match input.as_str() {
+            "y" | "yes" => {
+                let permissions = self
+                    .config
+                    .plugin_permissions
+                    .entry(request.plugin_name.clone())
+                    .or_default();
+                request.capability.grant(permissions, &request.entry);
+                self.save_plugin_permissions_to_config();
+                true
+            }
+            "o" | "once" => {
+                let permissions = self
+                    .plugin_permissions
+                    .allowed_once
+                    .entry(request.plugin_name.clone())
+                    .or_default();
+                request.capability.grant(permissions, &request.entry);
+                true
+            }
+            _ => {
+                let permissions = self
+                    .plugin_permissions
+                    .denied
+                    .entry(request.plugin_name.clone())
+                    .or_default();
+                request.capability.grant(permissions, &request.entry);
+                false
+            }
+        };
+
+        for request_id in request.request_ids {
+            self.send_plugin_response(PluginResponse::PluginPermission {
+                request_id,
+                granted,
+            });
+        }
+        let message = if granted {
+            t!("plugin.permission_allowed", plugin = &request.plugin_name)
+        } else {
+            t!("plugin.permission_denied", plugin = &request.plugin_name)
+        };
+        self.set_status_message(message.to_string());
+    }
+
+    /// Permissions the plugin ops check: the config's and those allowed once
+    #[cfg(feature = "plugins")]
+    pub(super) fn effective_plugin_permissions(&self) -> HashMap<String, PluginPermissions> {
+        let mut permissions = self.config.plugin_permissions.clone();
+        for (plugin_name, once) in &self.plugin_permissions.allowed_once {
+            let permissions = permissions.entry(plugin_name.clone()).or_default();
+            for capability in [
+                Capability::FsRead,
+                Capability::FsWrite,
+                Capability::Process,
+                Capability::Env,
+            ] {
+                for entry in capability.granted(once) {
+                    capability.grant(permissions, entry);
+                }
+            }
+        }
+        permissions
+    }
+
+    /// Save the plugin permissions to the user's config file
+    fn save_plugin_permissions_to_config(&mut self) {
+        let resolver = ConfigResolver::new(self.dir_context.clone(), self.working_dir.clone());
+        if let Err(e) = resolver.save_to_layer(&self.config, ConfigLayer::User) {
+            tracing::warn!("Failed to save plugin permissions to config: {}", e);
+        }
+    }
+}
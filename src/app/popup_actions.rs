@@ -2,9 +2,10 @@
 //!
 //! This module contains handlers for popup-related actions like confirmation and cancellation.
 
+use super::snippet_actions::SNIPPET_CHOICE_TITLE;
 use super::Editor;
 use crate::model::event::Event;
-use crate::primitives::snippet::{expand_snippet, is_snippet};
+use crate::primitives::snippet::is_snippet;
 use crate::primitives::word_navigation::find_completion_word_start;
 use rust_i18n::t;
 
@@ -69,6 +70,19 @@ impl Editor {
             return PopupConfirmResult::EarlyReturn;
         }
 
+        // A choice of a snippet tabstop replaces the tabstop's text
+        let snippet_choice = match self.active_state().popups.top() {
+            Some(popup) if popup.title.as_deref() == Some(SNIPPET_CHOICE_TITLE) => {
+                popup.selected_item().and_then(|item| item.data.clone())
+            }
+            _ => None,
+        };
+        if let Some(choice) = snippet_choice {
+            self.hide_popup();
+            self.replace_selections(&choice, "Snippet choice");
+            return PopupConfirmResult::EarlyReturn;
+        }
+
         // If it's a completion popup, insert the selected item
         let completion_text = if let Some(popup) = self.active_state().popups.top() {
             if let Some(title) = &popup.title {
@@ -94,16 +108,8 @@ impl Editor {
     }
 
     /// Insert completion text, replacing the word prefix at cursor.
-    /// If the text contains LSP snippet syntax, it will be expanded.
+    /// If the text contains LSP snippet syntax, it starts a snippet session.
     fn insert_completion_text(&mut self, text: String) {
-        // Check if this is a snippet and expand it
-        let (insert_text, cursor_offset) = if is_snippet(&text) {
-            let expanded = expand_snippet(&text);
-            (expanded.text, Some(expanded.cursor_offset))
-        } else {
-            (text, None)
-        };
-
         let (cursor_id, cursor_pos, word_start) = {
             let state = self.active_state();
             let cursor_id = state.cursors.primary_id();
@@ -112,6 +118,11 @@ impl Editor {
             (cursor_id, cursor_pos, word_start)
         };
 
+        if is_snippet(&text) {
+            self.insert_snippet(word_start.min(cursor_pos)..cursor_pos, &text);
+            return;
+        }
+
         let deleted_text = if word_start < cursor_pos {
             self.active_state_mut()
                 .get_text_range(word_start, cursor_pos)
@@ -137,31 +148,12 @@ impl Editor {
 
         let insert_event = Event::Insert {
             position: insert_pos,
-            text: insert_text.clone(),
+            text,
             cursor_id,
         };
 
         self.active_event_log_mut().append(insert_event.clone());
         self.apply_event_to_active_buffer(&insert_event);
-
-        // If this was a snippet, position cursor at the snippet's $0 location
-        if let Some(offset) = cursor_offset {
-            let new_cursor_pos = insert_pos + offset;
-            // Get current cursor position after the insert
-            let current_pos = self.active_state().cursors.primary().position;
-            if current_pos != new_cursor_pos {
-                let move_event = Event::MoveCursor {
-                    cursor_id,
-                    old_position: current_pos,
-                    new_position: new_cursor_pos,
-                    old_anchor: None,
-                    new_anchor: None,
-                    old_sticky_column: 0,
-                    new_sticky_column: 0,
-                };
-                self.active_state_mut().apply(&move_event);
-            }
-        }
     }
 
     /// Handle PopupCancel action.
@@ -0,0 +1,201 @@
+//! Saving files the editor's user can't write
+//!
+//! When a save fails because the file isn't writable, the user is offered
+//! to save it as root instead. The content is then written through the
+//! configured helper (`editor.privileged_save_command`, see
+//! `services::privileged_save`), asking for its password in a prompt with
+//! hidden input when it needs one.
+
+use std::io;
+use std::path::Path;
+
+use rust_i18n::t;
+
+use crate::model::buffer::SaveCopy;
+use crate::model::event::BufferId;
+use crate::services::async_bridge::AsyncMessage;
+use crate::services::privileged_save::PrivilegedHelper;
+use crate::view::prompt::PromptType;
+
+use super::Editor;
+
+impl Editor {
+    /// Save the active buffer, offering to save it as root if the file
+    /// can't be written
+    pub(super) fn save_or_offer_save_as_root(&mut self) -> io::Result<()> {
+        match self.save() {
+            // Remote files are written by the remote user, not through a local helper
+            Err(e)
+                if e.kind() == io::ErrorKind::PermissionDenied
+                    && !self.active_state().buffer.is_remote() =>
+            {
+                let helper = self.privileged_helper();
+                self.start_prompt(
+                    t!("file.save_as_root_confirm", helper = helper.program()).to_string(),
+                    PromptType::ConfirmSaveAsRoot,
+                );
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Handle the answer to the "Save as root?" prompt
+    pub(super) fn handle_confirm_save_as_root(&mut self, input: &str) {
+        let input = input.trim().to_lowercase();
+        if input == "y" || input == "yes" {
+            self.start_save_as_root();
+        } else {
+            self.set_status_message(t!("buffer.save_cancelled").to_string());
+        }
+    }
+
+    /// Save the active buffer as root, asking for the helper's password
+    /// first if it needs one
+    ///
+    /// Whether it does is found out on the tokio runtime, see
+    /// `handle_privileged_save_checked`.
+    pub(super) fn start_save_as_root(&mut self) {
+        if self.active_state().buffer.file_path().is_none() {
+            self.set_status_message(t!("file.save_as_root_no_file").to_string());
+            return;
+        }
+        let helper = self.privileged_helper();
+        if !helper.takes_password() {
+            self.save_as_root(None);
+            return;
+        }
+        let (Some(runtime), Some(bridge)) = (&self.tokio_runtime, &self.async_bridge) else {
+            self.set_status_message(
+                t!(
+                    "file.save_as_root_failed",
+                    error = "Async runtime not available"
+                )
+                .to_string(),
+            );
+            return;
+        };
+        let buffer_id = self.active_buffer();
+        let sender = bridge.sender();
+        runtime.spawn(async move {
+            let needs_password = helper.needs_password().await;
+            let _ = sender.send(AsyncMessage::PrivilegedSaveChecked {
+                buffer_id,
+                needs_password,
+            });
+        });
+    }
+
+    /// Go on with "Save as root" once it is known whether the helper asks
+    /// for a password
+    pub(super) fn handle_privileged_save_checked(
+        &mut self,
+        buffer_id: BufferId,
+        needs_password: bool,
+    ) {
+        // The user moved on to another buffer in the meantime
+        if buffer_id != self.active_buffer() {
+            return;
+        }
+        if needs_password {
+            let helper = self.privileged_helper();
+            self.start_prompt(
+                t!("file.save_as_root_password", helper = helper.program()).to_string(),
+                PromptType::SaveAsRootPassword,
+            );
+        } else {
+            self.save_as_root(None);
+        }
+    }
+
+    /// Write the active buffer to a private copy and have the helper put it
+    /// in place on the tokio runtime
+    pub(super) fn save_as_root(&mut self, password: Option<&str>) {
+        let Some(path) = self
+            .active_state()
+            .buffer
+            .file_path()
+            .map(|p| p.to_path_buf())
+        else {
+            return;
+        };
+        let copy = match self.active_state().buffer.write_save_copy(&path) {
+            Ok(copy) => copy,
+            Err(e) => {
+                self.save_as_root_failed(&path, &e);
+                return;
+            }
+        };
+        let (Some(runtime), Some(bridge)) = (&self.tokio_runtime, &self.async_bridge) else {
+            self.set_status_message(
+                t!(
+                    "file.save_as_root_failed",
+                    error = "Async runtime not available"
+                )
+                .to_string(),
+            );
+            return;
+        };
+        let buffer_id = self.active_buffer();
+        let helper = self.privileged_helper();
+        let password = password.map(str::to_string);
+        let sender = bridge.sender();
+        runtime.spawn(async move {
+            let result = helper
+                .install(
+                    &copy.temp_path,
+                    &copy.dest_path,
+                    copy.original_metadata.as_ref(),
+                    password.as_deref(),
+                )
+                .await;
+            let _ = sender.send(AsyncMessage::PrivilegedSaveFinished {
+                buffer_id,
+                copy,
+                result,
+            });
+        });
+    }
+
+    /// Mark the buffer saved once the helper installed its content
+    ///
+    /// Edits made while the helper ran keep the buffer modified.
+    pub(super) fn handle_privileged_save_finished(
+        &mut self,
+        buffer_id: BufferId,
+        copy: SaveCopy,
+        result: io::Result<()>,
+    ) {
+        let path = copy.dest_path.clone();
+        if let Err(e) = result {
+            self.save_as_root_failed(&path, &e);
+            return;
+        }
+        let unchanged = self
+            .buffers
+            .get_mut(&buffer_id)
+            .is_some_and(|state| state.buffer.finish_save_copy(&copy));
+        if unchanged && buffer_id == self.active_buffer() {
+            self.finish_save(Some(path));
+            return;
+        }
+        if unchanged {
+            if let Some(event_log) = self.event_logs.get_mut(&buffer_id) {
+                event_log.mark_saved();
+            }
+            let _ = self.delete_buffer_recovery(buffer_id);
+        }
+        self.set_status_message(t!("status.file_saved").to_string());
+    }
+
+    fn save_as_root_failed(&mut self, path: &Path, error: &io::Error) {
+        tracing::warn!("Failed to save {} as root: {}", path.display(), error);
+        self.set_status_message(
+            t!("file.save_as_root_failed", error = error.to_string()).to_string(),
+        );
+    }
+
+    fn privileged_helper(&self) -> PrivilegedHelper {
+        PrivilegedHelper::new(self.config.editor.privileged_save_command.clone())
+    }
+}
@@ -52,6 +52,15 @@ impl Editor {
                     );
                 }
             }
+            PromptType::CompareWithFile => {
+                let input_path = Path::new(&input);
+                let resolved_path = if input_path.is_absolute() {
+                    normalize_path(input_path)
+                } else {
+                    normalize_path(&self.working_dir.join(input_path))
+                };
+                self.compare_with_file(&resolved_path);
+            }
             PromptType::SwitchProject => {
                 let input_path = Path::new(&input);
                 let resolved_path = if input_path.is_absolute() {
@@ -127,6 +136,10 @@ impl Editor {
                     );
                 }
             }
+            PromptType::UndoEarlier => self.handle_time_travel(&input, false),
+            PromptType::UndoLater => self.handle_time_travel(&input, true),
+            PromptType::HexGotoOffset => self.handle_hex_goto_offset(&input),
+            PromptType::HexSearch => self.handle_hex_search(&input),
             PromptType::GotoLine => match input.trim().parse::<usize>() {
                 Ok(line_num) if line_num > 0 => {
                     self.goto_line_col(line_num, None);
@@ -165,15 +178,13 @@ impl Editor {
             PromptType::SetComposeWidth => {
                 self.handle_set_compose_width(&input);
             }
-            PromptType::RecordMacro => {
-                self.handle_register_input(
-                    &input,
-                    |editor, c| editor.toggle_macro_recording(c),
-                    "Macro",
-                );
-            }
-            PromptType::PlayMacro => {
-                self.handle_register_input(&input, |editor, c| editor.play_macro(c), "Macro");
+            PromptType::RecordMacro
+            | PromptType::PlayMacro
+            | PromptType::EditMacro
+            | PromptType::PlayMacroOnLines
+            | PromptType::PlayMacroTimes
+            | PromptType::MacroCount { .. } => {
+                self.handle_macro_prompt(prompt_type, &input);
             }
             PromptType::SetBookmark => {
                 self.handle_register_input(&input, |editor, c| editor.set_bookmark(c), "Bookmark");
@@ -211,7 +222,7 @@ impl Editor {
             PromptType::ConfirmSaveConflict => {
                 let input_lower = input.trim().to_lowercase();
                 if input_lower == "o" || input_lower == "overwrite" {
-                    if let Err(e) = self.save() {
+                    if let Err(e) = self.save_or_offer_save_as_root() {
                         self.set_status_message(
                             t!("file.save_failed", error = e.to_string()).to_string(),
                         );
@@ -220,6 +231,9 @@ impl Editor {
                     self.set_status_message(t!("buffer.save_cancelled").to_string());
                 }
             }
+            PromptType::ConfirmSaveAsRoot => self.handle_confirm_save_as_root(&input),
+            PromptType::ConfirmPluginPermission => self.handle_confirm_plugin_permission(&input),
+            PromptType::SaveAsRootPassword => self.save_as_root(Some(&input)),
             PromptType::ConfirmOverwriteFile { path } => {
                 let input_lower = input.trim().to_lowercase();
                 if input_lower == "o" || input_lower == "overwrite" {
@@ -287,6 +301,19 @@ impl Editor {
                     self.switch_to_tab(BufferId(id));
                 }
             }
+            PromptType::GotoSymbol => {
+                if let Ok(position) = input.trim().parse::<usize>() {
+                    self.goto_symbol_position(position);
+                }
+            }
+            PromptType::WorkspaceSymbol => {
+                self.goto_workspace_symbol(selected_index);
+            }
+            PromptType::CodeLens => {
+                if let Ok(index) = input.trim().parse::<usize>() {
+                    self.run_code_lens(self.active_buffer(), index);
+                }
+            }
             PromptType::QueryReplaceConfirm => {
                 // This is handled by InsertChar, not PromptConfirm
                 // But if somehow Enter is pressed, treat it as skip (n)
@@ -300,6 +327,17 @@ impl Editor {
             PromptType::SetLineEnding => {
                 self.handle_set_line_ending(&input);
             }
+            PromptType::ReopenWithEncoding => {
+                if let Some(encoding) = self.parse_encoding_input(&input) {
+                    self.reopen_with_encoding(encoding);
+                }
+            }
+            PromptType::SaveWithEncoding => {
+                if let Some(encoding) = self.parse_encoding_input(&input) {
+                    self.save_with_encoding(encoding);
+                }
+            }
+            PromptType::InsertSnippet => self.insert_user_snippet(&input),
             PromptType::ShellCommand { replace } => {
                 self.handle_shell_command(&input, replace);
             }
@@ -9,7 +9,7 @@
 
 use std::io;
 
-use crate::model::event::BufferId;
+use crate::model::event::{BufferId, EventLog};
 
 use super::Editor;
 
@@ -63,6 +63,8 @@ impl Editor {
                             state.buffer.insert(0, &text);
                             // Mark as modified since it differs from disk
                             state.buffer.set_modified(true);
+                            // The undo history saved for the file doesn't apply to the recovered content
+                            *self.active_event_log_mut() = EventLog::new();
                             recovered_count += 1;
                             tracing::info!("Recovered buffer: {}", path.display());
                         }
@@ -63,7 +63,7 @@ impl Editor {
         let has_file_browser = self.prompt.as_ref().map_or(false, |p| {
             matches!(
                 p.prompt_type,
-                PromptType::OpenFile | PromptType::SwitchProject
+                PromptType::OpenFile | PromptType::SwitchProject | PromptType::CompareWithFile
             )
         }) && self.file_open_state.is_some();
 
@@ -435,7 +435,7 @@ impl Editor {
             // For OpenFile/SwitchProject prompt, render the file browser popup
             if matches!(
                 prompt.prompt_type,
-                PromptType::OpenFile | PromptType::SwitchProject
+                PromptType::OpenFile | PromptType::SwitchProject | PromptType::CompareWithFile
             ) {
                 if let Some(file_open_state) = &self.file_open_state {
                     // Calculate popup area: position above prompt line, covering status bar
@@ -501,6 +501,10 @@ impl Editor {
         // Get update availability info
         let update_available = self.latest_version().map(|v| v.to_string());
 
+        // Segments of the status bar and the editor state they show
+        let status_bar_segments = self.config.status_bar.clone();
+        let status_bar_info = self.status_bar_info();
+
         // Render status bar (hidden when suggestions or file browser popup is shown)
         if !has_suggestions && !has_file_browser {
             // Get warning level for colored indicator (respects config setting)
@@ -519,6 +523,7 @@ impl Editor {
             let status_bar_hover = match &self.mouse_state.hover_target {
                 Some(HoverTarget::StatusBarLspIndicator) => StatusBarHover::LspIndicator,
                 Some(HoverTarget::StatusBarWarningBadge) => StatusBarHover::WarningBadge,
+                Some(HoverTarget::StatusBarEncodingIndicator) => StatusBarHover::EncodingIndicator,
                 Some(HoverTarget::StatusBarLineEndingIndicator) => {
                     StatusBarHover::LineEndingIndicator
                 }
@@ -540,6 +545,8 @@ impl Editor {
                 warning_level,               // Pass warning level for colored indicator
                 general_warning_count,       // Pass general warning count for badge
                 status_bar_hover,            // Pass hover state for indicator styling
+                &status_bar_segments,
+                &status_bar_info,
             );
 
             // Store status bar layout for click detection
@@ -548,6 +555,7 @@ impl Editor {
                 Some((status_bar_area.y, status_bar_area.x, status_bar_area.width));
             self.cached_layout.status_bar_lsp_area = status_bar_layout.lsp_indicator;
             self.cached_layout.status_bar_warning_area = status_bar_layout.warning_badge;
+            self.cached_layout.status_bar_encoding_area = status_bar_layout.encoding_indicator;
             self.cached_layout.status_bar_line_ending_area =
                 status_bar_layout.line_ending_indicator;
         }
@@ -602,6 +610,7 @@ impl Editor {
                 prompt.prompt_type,
                 crate::view::prompt::PromptType::OpenFile
                     | crate::view::prompt::PromptType::SwitchProject
+                    | crate::view::prompt::PromptType::CompareWithFile
             ) {
                 if let Some(file_open_state) = &self.file_open_state {
                     StatusBarRenderer::render_file_open_prompt(
@@ -2084,6 +2093,8 @@ impl Editor {
             let state = self.active_state_mut();
             state.cursors.primary_mut().position = match_pos;
             state.cursors.primary_mut().anchor = None;
+            // Reveal matches inside folded regions
+            state.folds.unfold_at(&mut state.marker_list, match_pos);
             // Ensure cursor is visible - get viewport from SplitViewState
             if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
                 let state = self.buffers.get_mut(&active_buffer).unwrap();
@@ -2149,6 +2160,8 @@ impl Editor {
                 let state = self.active_state_mut();
                 state.cursors.primary_mut().position = match_pos;
                 state.cursors.primary_mut().anchor = None;
+                // Reveal matches inside folded regions
+                state.folds.unfold_at(&mut state.marker_list, match_pos);
                 // Ensure cursor is visible - get viewport from SplitViewState
                 if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
                     let state = self.buffers.get_mut(&active_buffer).unwrap();
@@ -2201,6 +2214,8 @@ impl Editor {
                 let state = self.active_state_mut();
                 state.cursors.primary_mut().position = match_pos;
                 state.cursors.primary_mut().anchor = None;
+                // Reveal matches inside folded regions
+                state.folds.unfold_at(&mut state.marker_list, match_pos);
                 // Ensure cursor is visible - get viewport from SplitViewState
                 if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
                     let state = self.buffers.get_mut(&active_buffer).unwrap();
@@ -2501,6 +2516,10 @@ impl Editor {
             let state = self.active_state_mut();
             state.cursors.primary_mut().position = first_match_pos;
             state.cursors.primary_mut().anchor = None;
+            // Reveal matches inside folded regions
+            state
+                .folds
+                .unfold_at(&mut state.marker_list, first_match_pos);
         }
         // Ensure cursor is visible - get viewport from SplitViewState
         if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
@@ -2761,6 +2780,8 @@ impl Editor {
             let state = self.active_state_mut();
             state.cursors.primary_mut().position = match_pos;
             state.cursors.primary_mut().anchor = None;
+            // Reveal matches inside folded regions
+            state.folds.unfold_at(&mut state.marker_list, match_pos);
         }
         // Ensure cursor is visible - get viewport from SplitViewState
         if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
@@ -3227,7 +3248,7 @@ impl Editor {
     }
 
     /// Toggle macro recording for the given register
-    pub(super) fn toggle_macro_recording(&mut self, key: char) {
+    pub(super) fn toggle_macro_recording(&mut self, key: &str) {
         if let Some(state) = &self.macro_recording {
             if state.key == key {
                 // Stop recording
@@ -3244,9 +3265,9 @@ impl Editor {
     }
 
     /// Start recording a macro
-    pub(super) fn start_macro_recording(&mut self, key: char) {
+    pub(super) fn start_macro_recording(&mut self, key: &str) {
         self.macro_recording = Some(MacroRecordingState {
-            key,
+            key: key.to_string(),
             actions: Vec::new(),
         });
 
@@ -3263,7 +3284,7 @@ impl Editor {
     }
 
     /// Build a hint message for how to stop macro recording
-    fn build_macro_stop_hint(&self, _key: char) -> String {
+    fn build_macro_stop_hint(&self, _key: &str) -> String {
         let mut hints = Vec::new();
 
         // Check for F5 (stop_macro_recording)
@@ -3290,8 +3311,8 @@ impl Editor {
         if let Some(state) = self.macro_recording.take() {
             let action_count = state.actions.len();
             let key = state.key;
-            self.macros.insert(key, state.actions);
-            self.last_macro_register = Some(key);
+            self.macros.insert(key.clone(), state.actions);
+            self.last_macro_register = Some(key.clone());
 
             // Build play hint
             let play_hint = self.build_macro_play_hint();
@@ -3304,6 +3325,7 @@ impl Editor {
                 )
                 .to_string(),
             );
+            self.save_macros();
         } else {
             self.set_status_message(t!("macro.not_recording").to_string());
         }
@@ -3320,29 +3342,11 @@ impl Editor {
     }
 
     /// Play back a recorded macro
-    pub(super) fn play_macro(&mut self, key: char) {
-        if let Some(actions) = self.macros.get(&key).cloned() {
-            if actions.is_empty() {
-                self.set_status_message(t!("macro.empty", key = key).to_string());
-                return;
-            }
-
-            // Temporarily disable recording to avoid recording the playback
-            let was_recording = self.macro_recording.take();
-
-            let action_count = actions.len();
-            for action in actions {
-                let _ = self.handle_action(action);
-            }
-
-            // Restore recording state
-            self.macro_recording = was_recording;
-
+    pub(super) fn play_macro(&mut self, key: &str) {
+        if let Some(action_count) = self.replay_macro(key, 1) {
             self.set_status_message(
                 t!("macro.played", key = key, count = action_count).to_string(),
             );
-        } else {
-            self.set_status_message(t!("macro.not_found", key = key).to_string());
         }
     }
 
@@ -3350,97 +3354,10 @@ impl Editor {
     pub(super) fn record_macro_action(&mut self, action: &Action) {
         if let Some(state) = &mut self.macro_recording {
             // Don't record macro control actions themselves
-            match action {
-                Action::StartMacroRecording
-                | Action::StopMacroRecording
-                | Action::PlayMacro(_)
-                | Action::ToggleMacroRecording(_)
-                | Action::ShowMacro(_)
-                | Action::ListMacros
-                | Action::PromptRecordMacro
-                | Action::PromptPlayMacro
-                | Action::PlayLastMacro => {}
-                _ => {
-                    state.actions.push(action.clone());
-                }
-            }
-        }
-    }
-
-    /// Show a macro in a buffer as JSON
-    pub(super) fn show_macro_in_buffer(&mut self, key: char) {
-        // Get macro data and cache what we need before any mutable borrows
-        let (json, actions_len) = match self.macros.get(&key) {
-            Some(actions) => {
-                let json = match serde_json::to_string_pretty(actions) {
-                    Ok(json) => json,
-                    Err(e) => {
-                        self.set_status_message(
-                            t!("macro.serialize_failed", error = e.to_string()).to_string(),
-                        );
-                        return;
-                    }
-                };
-                (json, actions.len())
-            }
-            None => {
-                self.set_status_message(t!("macro.not_found", key = key).to_string());
-                return;
+            if !crate::input::macros::is_macro_control(action) {
+                state.actions.push(action.clone());
             }
-        };
-
-        // Create header with macro info
-        let content = format!(
-            "// Macro '{}' ({} actions)\n// This buffer can be saved as a .json file for persistence\n\n{}",
-            key,
-            actions_len,
-            json
-        );
-
-        // Create a new buffer for the macro
-        let buffer_id = BufferId(self.next_buffer_id);
-        self.next_buffer_id += 1;
-
-        let mut state = EditorState::new(
-            self.terminal_width.into(),
-            self.terminal_height.into(),
-            self.config.editor.large_file_threshold_bytes as usize,
-        );
-        state
-            .margins
-            .set_line_numbers(self.config.editor.line_numbers);
-
-        self.buffers.insert(buffer_id, state);
-        self.event_logs.insert(buffer_id, EventLog::new());
-
-        // Set buffer content
-        if let Some(state) = self.buffers.get_mut(&buffer_id) {
-            state.buffer = crate::model::buffer::Buffer::from_str(
-                &content,
-                self.config.editor.large_file_threshold_bytes as usize,
-            );
         }
-
-        // Set metadata
-        let metadata = BufferMetadata {
-            kind: BufferKind::Virtual {
-                mode: "macro-view".to_string(),
-            },
-            display_name: format!("*Macro {}*", key),
-            lsp_enabled: false,
-            lsp_disabled_reason: Some("Virtual macro buffer".to_string()),
-            read_only: false, // Allow editing for saving
-            binary: false,
-            lsp_opened_with: std::collections::HashSet::new(),
-            hidden_from_tabs: false,
-        };
-        self.buffer_metadata.insert(buffer_id, metadata);
-
-        // Switch to the new buffer
-        self.set_active_buffer(buffer_id);
-        self.set_status_message(
-            t!("macro.shown_buffer", key = key, count = actions_len).to_string(),
-        );
     }
 
     /// List all recorded macros in a buffer
@@ -3451,10 +3368,9 @@ impl Editor {
         }
 
         // Build a summary of all macros
-        let mut content =
-            String::from("// Recorded Macros\n// Use ShowMacro(key) to see details\n\n");
+        let mut content = String::from("// Recorded Macros\n// Use Edit Macro to change one\n\n");
 
-        let mut keys: Vec<char> = self.macros.keys().copied().collect();
+        let mut keys: Vec<String> = self.macros.keys().cloned().collect();
         keys.sort();
 
         for key in keys {
@@ -3508,6 +3424,7 @@ impl Editor {
             binary: false,
             lsp_opened_with: std::collections::HashSet::new(),
             hidden_from_tabs: false,
+            chosen_encoding: None,
         };
         self.buffer_metadata.insert(buffer_id, metadata);
 
@@ -0,0 +1,218 @@
+//! Semantic token highlighting for the Editor.
+//!
+//! The active buffer's semantic tokens are requested from its language server
+//! when it is first shown and again once edits pause for a moment. Buffers
+//! with many lines only request the lines around the viewport
+//! (`textDocument/semanticTokens/range`) and request again when scrolled past
+//! them. Tokens are drawn as foreground overlays, so they layer over the
+//! tree-sitter or TextMate colors and below diagnostics.
+
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use lsp_types::{Position, SemanticTokensLegend};
+
+use crate::model::event::BufferId;
+use crate::model::piece_tree::PieceTreeNode;
+use crate::primitives::semantic_tokens::{self, AbsoluteToken};
+use crate::view::overlay::{Overlay, OverlayFace, OverlayNamespace};
+
+use super::Editor;
+
+/// How long edits must pause before tokens are requested again
+const SEMANTIC_TOKENS_REFRESH_DELAY: Duration = Duration::from_millis(300);
+
+/// Buffers with more lines than this only request tokens around the viewport
+const RANGE_REQUEST_MIN_LINES: usize = 5_000;
+
+/// Below every other overlay (diagnostics, search matches, plugins...)
+const SEMANTIC_TOKEN_PRIORITY: i32 = -10;
+
+/// Namespace of the semantic token overlays
+fn semantic_tokens_namespace() -> OverlayNamespace {
+    OverlayNamespace::from_string("lsp-semantic-tokens".to_string())
+}
+
+/// Semantic token requests of one buffer
+#[derive(Debug, Default)]
+pub(super) struct SemanticTokenState {
+    /// Content the shown tokens (or the pending request) were requested for
+    requested: Option<Arc<PieceTreeNode>>,
+    /// Lines covered by the shown tokens (None for the whole document)
+    lines: Option<Range<usize>>,
+    /// Content last seen by the poller and when it was first seen
+    seen: Option<(Arc<PieceTreeNode>, Instant)>,
+    /// In-flight request ID
+    pending: Option<u64>,
+}
+
+impl Editor {
+    /// Request semantic tokens for the active buffer when they are out of date
+    ///
+    /// Called from the main loop; tokens are applied when the response arrives.
+    pub(super) fn poll_semantic_tokens(&mut self) {
+        if !self.config.editor.enable_semantic_tokens {
+            return;
+        }
+
+        let buffer_id = self.active_buffer();
+        let viewport = self.active_viewport();
+        let (top_byte, height) = (viewport.top_byte, viewport.visible_line_count());
+        let Some(state) = self.buffers.get(&buffer_id) else {
+            return;
+        };
+        let content = state.buffer.content_root();
+        let range_mode = state
+            .buffer
+            .line_count()
+            .is_none_or(|count| count > RANGE_REQUEST_MIN_LINES);
+        let top_line = state.buffer.get_line_number(top_byte);
+        let now = self.time_source.now();
+
+        let tokens = self.semantic_tokens.entry(buffer_id).or_default();
+        if tokens.pending.is_some() {
+            return;
+        }
+        if !tokens
+            .seen
+            .as_ref()
+            .is_some_and(|(seen, _)| Arc::ptr_eq(seen, &content))
+        {
+            tokens.seen = Some((content.clone(), now));
+        }
+
+        let changed = !tokens
+            .requested
+            .as_ref()
+            .is_some_and(|requested| Arc::ptr_eq(requested, &content));
+        let scrolled_away = tokens
+            .lines
+            .as_ref()
+            .is_some_and(|lines| top_line < lines.start || top_line + height > lines.end);
+        if !changed && !scrolled_away {
+            return;
+        }
+        // Newly shown buffers get their tokens right away, edits are debounced
+        if changed && tokens.requested.is_some() {
+            let seen_at = tokens.seen.as_ref().map_or(now, |(_, at)| *at);
+            if self.time_source.elapsed_since(seen_at) < SEMANTIC_TOKENS_REFRESH_DELAY {
+                return;
+            }
+        }
+
+        // Include a screen above and below so that small scrolls need no request
+        let lines = top_line.saturating_sub(height)..top_line + 2 * height;
+        self.request_semantic_tokens(buffer_id, content, lines, range_mode);
+    }
+
+    /// Request the tokens of every buffer again
+    ///
+    /// Requests sent before a server finished initializing get no tokens, and
+    /// those sent to a server that went away get no response at all.
+    pub(super) fn reset_semantic_tokens(&mut self) {
+        for tokens in self.semantic_tokens.values_mut() {
+            tokens.requested = None;
+            tokens.pending = None;
+        }
+    }
+
+    fn request_semantic_tokens(
+        &mut self,
+        buffer_id: BufferId,
+        content: Arc<PieceTreeNode>,
+        lines: Range<usize>,
+        range_mode: bool,
+    ) {
+        let request_id = self.next_lsp_request_id;
+        let viewport = lsp_types::Range {
+            start: Position::new(lines.start as u32, 0),
+            end: Position::new(lines.end as u32, 0),
+        };
+
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+                let result = handle.semantic_tokens(request_id, uri.clone(), viewport, range_mode);
+                if let Err(e) = &result {
+                    tracing::debug!("Failed to request semantic tokens: {}", e);
+                }
+                result.is_ok()
+            })
+            .unwrap_or(false);
+
+        let tokens = self.semantic_tokens.entry(buffer_id).or_default();
+        tokens.requested = Some(content);
+        if sent {
+            self.next_lsp_request_id += 1;
+            tokens.pending = Some(request_id);
+        }
+    }
+
+    /// Handle LSP semantic tokens response
+    pub(super) fn handle_lsp_semantic_tokens(
+        &mut self,
+        request_id: u64,
+        uri: String,
+        legend: SemanticTokensLegend,
+        range: Option<lsp_types::Range>,
+        tokens: Result<Vec<AbsoluteToken>, String>,
+    ) {
+        let Some((&buffer_id, state)) = self
+            .semantic_tokens
+            .iter_mut()
+            .find(|(_, state)| state.pending == Some(request_id))
+        else {
+            tracing::debug!(
+                "Ignoring stale semantic tokens response (request_id={})",
+                request_id
+            );
+            return;
+        };
+        state.pending = None;
+        let tokens = match tokens {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                tracing::debug!("No semantic tokens for {}: {}", uri, e);
+                return;
+            }
+        };
+        let Some(requested) = state.requested.clone() else {
+            return;
+        };
+        state.lines = range.map(|range| range.start.line as usize..range.end.line as usize);
+
+        let Some(buffer_state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        // Positions refer to the requested content; newer edits are requested again
+        if !Arc::ptr_eq(&buffer_state.buffer.content_root(), &requested) {
+            return;
+        }
+
+        tracing::debug!(
+            "Received {} semantic tokens for {} (request_id={})",
+            tokens.len(),
+            uri,
+            request_id
+        );
+        let spans =
+            semantic_tokens::token_spans(&buffer_state.buffer, &legend, &tokens, &self.theme);
+        let ns = semantic_tokens_namespace();
+        buffer_state
+            .overlays
+            .clear_namespace(&ns, &mut buffer_state.marker_list);
+        let overlays: Vec<_> = spans
+            .into_iter()
+            .map(|span| {
+                Overlay::with_namespace(
+                    &mut buffer_state.marker_list,
+                    span.range,
+                    OverlayFace::Foreground { color: span.color },
+                    ns.clone(),
+                )
+                .with_priority_value(SEMANTIC_TOKEN_PRIORITY)
+            })
+            .collect();
+        buffer_state.overlays.extend(overlays);
+    }
+}
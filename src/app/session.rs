@@ -32,9 +32,10 @@ use crate::model::event::{BufferId, SplitDirection, SplitId};
 use crate::services::terminal::TerminalId;
 use crate::session::{
     FileExplorerState, PersistedFileSession, SearchOptions, SerializedBookmark, SerializedCursor,
-    SerializedFileState, SerializedScroll, SerializedSplitDirection, SerializedSplitNode,
-    SerializedSplitViewState, SerializedTabRef, SerializedTerminalSession, SerializedViewMode,
-    Session, SessionConfigOverrides, SessionError, SessionHistories, SESSION_VERSION,
+    SerializedFileState, SerializedFold, SerializedScroll, SerializedSplitDirection,
+    SerializedSplitNode, SerializedSplitViewState, SerializedTabRef, SerializedTerminalSession,
+    SerializedViewMode, Session, SessionConfigOverrides, SessionError, SessionHistories,
+    SESSION_VERSION,
 };
 use crate::state::ViewMode;
 use crate::view::split::{SplitNode, SplitViewState};
@@ -169,6 +170,7 @@ impl Editor {
             let active_buffer = active_buffers.get(split_id).copied();
             let serialized = serialize_split_view_state(
                 view_state,
+                &self.buffers,
                 &self.buffer_metadata,
                 &self.working_dir,
                 active_buffer,
@@ -336,6 +338,16 @@ impl Editor {
                 top_view_line_offset: view_state.viewport.top_view_line_offset,
                 left_column: view_state.viewport.left_column,
             },
+            folds: self
+                .buffers
+                .get(&buffer_id)
+                .map(serialize_folds)
+                .unwrap_or_default(),
+            encoding: self
+                .buffer_metadata
+                .get(&buffer_id)
+                .and_then(|metadata| metadata.chosen_encoding)
+                .map(|encoding| encoding.display_name().to_string()),
         };
 
         // Save to disk immediately
@@ -928,6 +940,7 @@ impl Editor {
                             file_state.cursor.anchor.map(|a| a.min(max_pos));
                         editor_state.cursors.primary_mut().sticky_column =
                             file_state.cursor.sticky_column;
+                        restore_folds(editor_state, &file_state.folds);
                         // Note: viewport is now exclusively owned by SplitViewState (restored above)
                     }
                     break;
@@ -1042,6 +1055,7 @@ fn serialize_split_node(
 
 fn serialize_split_view_state(
     view_state: &crate::view::split::SplitViewState,
+    buffers: &HashMap<BufferId, EditorState>,
     buffer_metadata: &HashMap<BufferId, super::types::BufferMetadata>,
     working_dir: &Path,
     active_buffer: Option<BufferId>,
@@ -1119,6 +1133,13 @@ fn serialize_split_view_state(
                                 top_view_line_offset: view_state.viewport.top_view_line_offset,
                                 left_column: view_state.viewport.left_column,
                             },
+                            folds: buffers
+                                .get(&active_id)
+                                .map(serialize_folds)
+                                .unwrap_or_default(),
+                            encoding: meta
+                                .chosen_encoding
+                                .map(|encoding| encoding.display_name().to_string()),
                         },
                     );
                 }
@@ -1141,6 +1162,29 @@ fn serialize_split_view_state(
     }
 }
 
+/// Serialize the folded regions of a buffer
+pub(super) fn serialize_folds(state: &EditorState) -> Vec<SerializedFold> {
+    state
+        .folds
+        .folded_ranges(&state.marker_list, &state.buffer)
+        .into_iter()
+        .map(|range| SerializedFold {
+            header_line: range.start_line,
+            end_line: range.end_line,
+        })
+        .collect()
+}
+
+/// Re-fold saved regions (folds beyond the end of the buffer are skipped)
+pub(super) fn restore_folds(state: &mut EditorState, folds: &[SerializedFold]) {
+    for fold in folds {
+        let range = crate::primitives::folding::FoldRange::new(fold.header_line, fold.end_line);
+        state
+            .folds
+            .fold_range(&mut state.marker_list, &state.buffer, &range);
+    }
+}
+
 fn serialize_bookmarks(
     bookmarks: &HashMap<char, Bookmark>,
     buffer_metadata: &HashMap<BufferId, super::types::BufferMetadata>,
@@ -0,0 +1,363 @@
+//! Snippet sessions for the Editor.
+//!
+//! Snippets come from LSP completions, and from the user's snippet files in
+//! `<config dir>/snippets/<language>.json`: they are inserted with "Insert
+//! Snippet", or by pressing Tab after one of their prefixes. Inserting a
+//! snippet with tabstops starts a session on the buffer (see
+//! `primitives::snippet::SnippetSession`): Tab and Shift+Tab move between the
+//! tabstops, with a cursor on every copy of a mirrored one, and a popup offers
+//! the choices of a choice tabstop. The session ends on the final tabstop, on
+//! Escape, or when Tab is pressed outside the snippet.
+
+use std::ops::Range;
+
+use rust_i18n::t;
+
+use crate::input::commands::Suggestion;
+use crate::input::multi_cursor::select_ranges;
+use crate::model::event::{
+    Event, PopupContentData, PopupData, PopupListItemData, PopupPositionData,
+};
+use crate::primitives::snippet::{load_user_snippets, parse_snippet, SnippetSession, UserSnippet};
+use crate::primitives::word_navigation::{find_word_end, find_word_start};
+use crate::services::lsp::manager::detect_language;
+use crate::view::prompt::{Prompt, PromptType};
+
+use super::Editor;
+
+/// Title of the popup offering the choices of a snippet tabstop
+pub(super) const SNIPPET_CHOICE_TITLE: &str = "Snippet Choice";
+
+impl Editor {
+    /// The user's snippets for the language of the active buffer
+    fn user_snippets(&self) -> Vec<UserSnippet> {
+        self.active_state()
+            .buffer
+            .file_path()
+            .and_then(|path| detect_language(path, &self.config.languages))
+            .map(|language| load_user_snippets(&self.dir_context.snippets_dir(), &language))
+            .unwrap_or_default()
+    }
+
+    /// Start the prompt picking one of the user's snippets to insert
+    pub(super) fn start_insert_snippet_prompt(&mut self) {
+        let snippets = self.user_snippets();
+        if snippets.is_empty() {
+            let path = self.dir_context.snippets_dir();
+            self.set_status_message(
+                t!("snippet.none", path = path.display().to_string()).to_string(),
+            );
+            return;
+        }
+
+        let suggestions = snippets
+            .into_iter()
+            .map(|snippet| Suggestion {
+                description: Some(match &snippet.description {
+                    Some(description) => {
+                        format!("{} - {}", snippet.prefixes.join(", "), description)
+                    }
+                    None => snippet.prefixes.join(", "),
+                }),
+                value: Some(snippet.name.clone()),
+                text: snippet.name,
+                disabled: false,
+                keybinding: None,
+                source: None,
+            })
+            .collect();
+        self.prompt = Some(Prompt::with_suggestions(
+            t!("snippet.insert_prompt").to_string(),
+            PromptType::InsertSnippet,
+            suggestions,
+        ));
+    }
+
+    /// Insert the user snippet picked in the Insert Snippet prompt
+    pub(super) fn insert_user_snippet(&mut self, input: &str) {
+        let input = input.trim();
+        let snippet = self
+            .user_snippets()
+            .into_iter()
+            .find(|s| s.name == input || s.prefixes.iter().any(|p| p == input));
+        match snippet {
+            Some(snippet) => {
+                let cursor = *self.active_state().cursors.primary();
+                let range = cursor
+                    .selection_range()
+                    .unwrap_or(cursor.position..cursor.position);
+                self.insert_snippet(range, &snippet.body);
+            }
+            None => self.set_status_message(t!("snippet.not_found", name = input).to_string()),
+        }
+    }
+
+    /// Expand the user snippet whose prefix is right before the cursor.
+    /// Returns false if there is none.
+    pub(super) fn expand_snippet_prefix(&mut self) -> bool {
+        let state = self.active_state();
+        if state.cursors.count() > 1 || state.cursors.primary().selection_range().is_some() {
+            return false;
+        }
+        let position = state.cursors.primary().position;
+        let snippets = self.user_snippets();
+        if snippets.is_empty() {
+            return false;
+        }
+
+        let line_start = self.line_start(position);
+        let before = self.active_state_mut().get_text_range(line_start, position);
+        let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+        let matched = snippets
+            .iter()
+            .flat_map(|snippet| snippet.prefixes.iter().map(move |p| (p, snippet)))
+            .filter(|(prefix, _)| {
+                !prefix.is_empty()
+                    && before.ends_with(prefix.as_str())
+                    && !before[..before.len() - prefix.len()]
+                        .chars()
+                        .next_back()
+                        .is_some_and(is_word_char)
+            })
+            .max_by_key(|(prefix, _)| prefix.len());
+
+        match matched {
+            Some((prefix, snippet)) => {
+                let body = snippet.body.clone();
+                self.insert_snippet(position - prefix.len()..position, &body);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Byte offset of the start of the line containing `position`
+    fn line_start(&mut self, position: usize) -> usize {
+        self.active_state_mut()
+            .buffer
+            .line_iterator(position, 80)
+            .next()
+            .map(|(line_start, _)| line_start)
+            .unwrap_or(position)
+    }
+
+    /// Replace `range` with a snippet and start a session on its tabstops
+    pub(super) fn insert_snippet(&mut self, range: Range<usize>, body: &str) {
+        if self.is_editing_disabled() {
+            self.set_status_message(t!("buffer.editing_disabled").to_string());
+            return;
+        }
+
+        // Lines after the first keep the indentation of the line the snippet starts on
+        let line_start = self.line_start(range.start);
+        let line = self
+            .active_state_mut()
+            .get_text_range(line_start, range.start);
+        let line_indent: String = line
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        let state = self.active_state();
+        let newline = format!("{}{}", state.buffer.line_ending().as_str(), line_indent);
+        let indent = if state.use_tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(state.tab_size)
+        };
+
+        let variables = self.snippet_variables(body);
+        let snippet = parse_snippet(body, &newline, &indent, |name| {
+            variables
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, value)| value.clone())
+        });
+
+        let cursor_id = self.active_state().cursors.primary_id();
+        let mut events = Vec::new();
+        if !range.is_empty() {
+            events.push(Event::Delete {
+                deleted_text: self
+                    .active_state_mut()
+                    .get_text_range(range.start, range.end),
+                range: range.clone(),
+                cursor_id,
+            });
+        }
+        events.push(Event::Insert {
+            position: range.start,
+            text: snippet.text.clone(),
+            cursor_id,
+        });
+        let batch = Event::Batch {
+            events,
+            description: "Insert snippet".to_string(),
+        };
+        self.active_event_log_mut().append(batch.clone());
+        self.apply_event_to_active_buffer(&batch);
+
+        self.active_state_mut().snippet_session = Some(SnippetSession::new(&snippet, range.start));
+        self.select_snippet_tabstop();
+    }
+
+    /// Values of the variables used in a snippet body
+    fn snippet_variables(&mut self, body: &str) -> Vec<(&'static str, String)> {
+        let mut variables = Vec::new();
+
+        if let Some(path) = self
+            .active_state()
+            .buffer
+            .file_path()
+            .map(|p| p.to_path_buf())
+        {
+            let file_name = path.file_name().map(|n| n.to_string_lossy().to_string());
+            let file_stem = path.file_stem().map(|n| n.to_string_lossy().to_string());
+            let directory = path.parent().map(|d| d.to_string_lossy().to_string());
+            let relative = path
+                .strip_prefix(&self.working_dir)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string();
+            variables.extend(file_name.map(|v| ("TM_FILENAME", v)));
+            variables.extend(file_stem.map(|v| ("TM_FILENAME_BASE", v)));
+            variables.extend(directory.map(|v| ("TM_DIRECTORY", v)));
+            variables.push(("TM_FILEPATH", path.to_string_lossy().to_string()));
+            variables.push(("RELATIVE_FILEPATH", relative));
+        }
+        if let Some(name) = self.working_dir.file_name() {
+            variables.push(("WORKSPACE_NAME", name.to_string_lossy().to_string()));
+        }
+        variables.push((
+            "WORKSPACE_FOLDER",
+            self.working_dir.to_string_lossy().to_string(),
+        ));
+
+        let cursor = *self.active_state().cursors.primary();
+        let line_start = self.line_start(cursor.position);
+        let state = self.active_state_mut();
+        let line_number = state.buffer.get_line_number(cursor.position);
+        variables.push(("TM_LINE_INDEX", line_number.to_string()));
+        variables.push(("TM_LINE_NUMBER", (line_number + 1).to_string()));
+        let line = state
+            .buffer
+            .line_iterator(line_start, 80)
+            .next()
+            .map(|(_, content)| content.trim_end_matches(['\n', '\r']).to_string())
+            .unwrap_or_default();
+        variables.push(("TM_CURRENT_LINE", line));
+        let word_start = find_word_start(&state.buffer, cursor.position);
+        let word_end = find_word_end(&state.buffer, cursor.position);
+        if word_start < word_end {
+            let word = state.get_text_range(word_start, word_end);
+            variables.push(("TM_CURRENT_WORD", word));
+        }
+        if let Some(selection) = cursor.selection_range() {
+            let selected = state.get_text_range(selection.start, selection.end);
+            variables.push(("TM_SELECTED_TEXT", selected));
+        }
+
+        // Reading the system clipboard can be slow; only do it when needed
+        if body.contains("CLIPBOARD") {
+            if let Some(text) = self.clipboard.paste() {
+                variables.push(("CLIPBOARD", text));
+            }
+        }
+
+        let now = chrono::Local::now();
+        for (name, format) in [
+            ("CURRENT_YEAR", "%Y"),
+            ("CURRENT_YEAR_SHORT", "%y"),
+            ("CURRENT_MONTH", "%m"),
+            ("CURRENT_MONTH_NAME", "%B"),
+            ("CURRENT_MONTH_NAME_SHORT", "%b"),
+            ("CURRENT_DATE", "%d"),
+            ("CURRENT_DAY_NAME", "%A"),
+            ("CURRENT_DAY_NAME_SHORT", "%a"),
+            ("CURRENT_HOUR", "%H"),
+            ("CURRENT_MINUTE", "%M"),
+            ("CURRENT_SECOND", "%S"),
+            ("CURRENT_SECONDS_UNIX", "%s"),
+        ] {
+            variables.push((name, now.format(format).to_string()));
+        }
+
+        variables
+    }
+
+    /// Put the cursors on the current tabstop of the snippet session
+    fn select_snippet_tabstop(&mut self) {
+        let state = self.active_state();
+        let Some(session) = &state.snippet_session else {
+            return;
+        };
+        let tabstop = session.current().clone();
+        let is_final = session.is_final();
+
+        let events = select_ranges(state, &tabstop.ranges);
+        if !events.is_empty() {
+            let batch = Event::Batch {
+                events,
+                description: "Snippet tabstop".to_string(),
+            };
+            self.active_event_log_mut().append(batch.clone());
+            self.apply_event_to_active_buffer(&batch);
+        }
+
+        if is_final {
+            self.active_state_mut().snippet_session = None;
+        } else if !tabstop.choices.is_empty() {
+            self.show_snippet_choices(tabstop.choices);
+        }
+    }
+
+    /// Move to the next or previous tabstop of the snippet session.
+    /// Returns false if there is no session at the cursor.
+    pub(super) fn move_to_snippet_tabstop(&mut self, forward: bool) -> bool {
+        let position = self.active_state().cursors.primary().position;
+        let state = self.active_state_mut();
+        let Some(session) = &mut state.snippet_session else {
+            return false;
+        };
+        if !session.contains(position) {
+            // The cursor left the snippet
+            state.snippet_session = None;
+            return false;
+        }
+        if session.advance(forward) {
+            self.select_snippet_tabstop();
+        }
+        true
+    }
+
+    /// Show a popup with the choices of the current tabstop
+    fn show_snippet_choices(&mut self, choices: Vec<String>) {
+        let width = choices
+            .iter()
+            .map(|choice| choice.chars().count())
+            .max()
+            .unwrap_or(0)
+            .clamp(10, 50) as u16
+            + 4;
+        self.show_popup(PopupData {
+            title: Some(SNIPPET_CHOICE_TITLE.to_string()),
+            description: None,
+            transient: false,
+            content: PopupContentData::List {
+                items: choices
+                    .into_iter()
+                    .map(|choice| PopupListItemData {
+                        text: choice.clone(),
+                        detail: None,
+                        icon: None,
+                        data: Some(choice),
+                    })
+                    .collect(),
+                selected: 0,
+            },
+            position: PopupPositionData::BelowCursor,
+            width,
+            max_height: 10,
+            bordered: true,
+        });
+    }
+}
@@ -0,0 +1,120 @@
+//! Editor state shown by the status bar segments
+//!
+//! The segments themselves are drawn by `view::ui::status_bar`; this module
+//! keeps what they show up to date: the git branch, the clock and the
+//! segments set by plugins.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::services::git::current_branch;
+use crate::services::lsp::manager::detect_language;
+use crate::view::ui::status_bar::{clock_text, StatusBarInfo};
+
+use super::Editor;
+
+/// How often the git branch is read again while the active buffer stays
+/// in the same directory
+const GIT_BRANCH_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The git branch shown in the status bar and where it was read from
+#[derive(Debug, Default)]
+pub(super) struct GitBranchState {
+    /// Directory the branch was read for
+    dir: Option<PathBuf>,
+    /// Branch checked out there
+    branch: Option<String>,
+    /// When the branch was read
+    read_at: Option<std::time::Instant>,
+}
+
+impl Editor {
+    /// Whether any side of the status bar shows the segment
+    fn status_bar_shows(&self, name: &str) -> bool {
+        let segments = &self.config.status_bar;
+        segments
+            .left
+            .iter()
+            .chain(&segments.center)
+            .chain(&segments.right)
+            .any(|segment| segment.segment == name)
+    }
+
+    /// Keep the git branch and the clock of the status bar up to date
+    ///
+    /// Returns true if the status bar needs to be drawn again.
+    pub(super) fn poll_status_bar(&mut self) -> bool {
+        let mut changed = false;
+
+        if self.status_bar_shows("git_branch") {
+            let dir = self
+                .buffer_metadata
+                .get(&self.active_buffer())
+                .and_then(|metadata| metadata.file_path())
+                .and_then(|path| path.parent())
+                .map(PathBuf::from)
+                .unwrap_or_else(|| self.working_dir.clone());
+            let git = &self.git_branch;
+            let stale = git.dir.as_ref() != Some(&dir)
+                || git.read_at.is_none_or(|read_at| {
+                    self.time_source.elapsed_since(read_at) >= GIT_BRANCH_POLL_INTERVAL
+                });
+            if stale {
+                let branch = current_branch(&dir);
+                changed |= branch != self.git_branch.branch;
+                self.git_branch = GitBranchState {
+                    dir: Some(dir),
+                    branch,
+                    read_at: Some(self.time_source.now()),
+                };
+            }
+        }
+
+        // Only draw again when the time shown by a clock segment changes
+        let segments = &self.config.status_bar;
+        let now = chrono::Local::now();
+        let clock: String = segments
+            .left
+            .iter()
+            .chain(&segments.center)
+            .chain(&segments.right)
+            .filter(|segment| segment.segment == "clock")
+            .filter_map(|segment| clock_text(now, segment.format.as_deref()))
+            .collect();
+        changed |= clock != self.status_bar_clock;
+        self.status_bar_clock = clock;
+
+        changed
+    }
+
+    /// Editor state shown by the status bar segments
+    pub(super) fn status_bar_info(&self) -> StatusBarInfo {
+        let language = self
+            .buffer_metadata
+            .get(&self.active_buffer())
+            .and_then(|metadata| metadata.file_path())
+            .and_then(|path| detect_language(path, &self.config.languages));
+        StatusBarInfo {
+            language,
+            git_branch: self.git_branch.branch.clone(),
+            recording_macro: self.macro_recording.as_ref().map(|state| state.key.clone()),
+            plugin_segments: self.plugin_status_segments.clone(),
+        }
+    }
+
+    /// Set the text of a status bar segment of a plugin (empty to remove it)
+    pub(super) fn handle_set_status_segment(&mut self, name: String, text: String) {
+        let existing = self
+            .plugin_status_segments
+            .iter()
+            .position(|(segment, _)| *segment == name);
+        match (existing, text.trim().is_empty()) {
+            (Some(index), true) => {
+                self.plugin_status_segments.remove(index);
+            }
+            (Some(index), false) => self.plugin_status_segments[index].1 = text,
+            (None, false) => self.plugin_status_segments.push((name, text)),
+            (None, true) => {}
+        }
+    }
+}
@@ -159,8 +159,11 @@ impl Editor {
             self.set_status_message(t!("toggle.inlay_hints_enabled").to_string());
         } else {
             // Clear inlay hints from all buffers
+            let ns = Self::inlay_hints_namespace();
             for state in self.buffers.values_mut() {
-                state.virtual_texts.clear(&mut state.marker_list);
+                state
+                    .virtual_texts
+                    .clear_namespace(&mut state.marker_list, &ns);
             }
             self.set_status_message(t!("toggle.inlay_hints_disabled").to_string());
         }
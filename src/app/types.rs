@@ -1,5 +1,6 @@
 use crate::app::file_open::SortMode;
 use crate::input::keybindings::Action;
+use crate::model::encoding::Encoding;
 use crate::model::event::{BufferId, SplitDirection, SplitId};
 use crate::services::async_bridge::LspMessageType;
 use ratatui::layout::Rect;
@@ -111,6 +112,10 @@ pub struct BufferMetadata {
 
     /// Whether this buffer should be hidden from tabs (used for composite source buffers)
     pub hidden_from_tabs: bool,
+
+    /// Encoding the user picked for the file (instead of the detected one).
+    /// Kept when the file is reloaded, and remembered with the per-file session.
+    pub chosen_encoding: Option<Encoding>,
 }
 
 impl BufferMetadata {
@@ -159,6 +164,7 @@ impl BufferMetadata {
             binary: false,
             lsp_opened_with: HashSet::new(),
             hidden_from_tabs: false,
+            chosen_encoding: None,
         }
     }
 
@@ -177,6 +183,7 @@ impl BufferMetadata {
             binary: false,
             lsp_opened_with: HashSet::new(),
             hidden_from_tabs: false,
+            chosen_encoding: None,
         }
     }
 
@@ -207,6 +214,7 @@ impl BufferMetadata {
             binary: false,
             lsp_opened_with: HashSet::new(),
             hidden_from_tabs: false,
+            chosen_encoding: None,
         }
     }
 
@@ -256,6 +264,7 @@ impl BufferMetadata {
             binary: false,
             lsp_opened_with: HashSet::new(),
             hidden_from_tabs: false,
+            chosen_encoding: None,
         }
     }
 
@@ -272,6 +281,7 @@ impl BufferMetadata {
             binary: false,
             lsp_opened_with: HashSet::new(),
             hidden_from_tabs: true,
+            chosen_encoding: None,
         }
     }
 
@@ -285,8 +295,8 @@ impl BufferMetadata {
 /// State for macro recording
 #[derive(Debug, Clone)]
 pub(super) struct MacroRecordingState {
-    /// The register key for this macro
-    pub key: char,
+    /// The register or name of this macro
+    pub key: String,
     /// Actions recorded so far
     pub actions: Vec<Action>,
 }
@@ -355,6 +365,8 @@ pub enum HoverTarget {
     StatusBarLspIndicator,
     /// Hovering over the status bar warning badge
     StatusBarWarningBadge,
+    /// Hovering over the status bar encoding indicator
+    StatusBarEncodingIndicator,
     /// Hovering over the status bar line ending indicator
     StatusBarLineEndingIndicator,
     /// Hovering over the search options "Case Sensitive" checkbox
@@ -625,6 +637,8 @@ pub(crate) struct CachedLayout {
     pub status_bar_lsp_area: Option<(u16, u16, u16)>,
     /// Status bar warning badge area (row, start_col, end_col)
     pub status_bar_warning_area: Option<(u16, u16, u16)>,
+    /// Status bar encoding indicator area (row, start_col, end_col)
+    pub status_bar_encoding_area: Option<(u16, u16, u16)>,
     /// Status bar line ending indicator area (row, start_col, end_col)
     pub status_bar_line_ending_area: Option<(u16, u16, u16)>,
     /// Search options layout for checkbox hit testing
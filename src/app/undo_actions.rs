@@ -1,6 +1,16 @@
 //! Undo and redo action handlers.
+//!
+//! Also keeps file undo histories across restarts (see
+//! `services::undo_history`): a file's history is saved when its buffer is
+//! closed or the editor exits, and loaded again when the file is opened.
+
+use std::path::Path;
+use std::time::Duration;
 
 use super::Editor;
+use crate::model::event::{BufferId, Event, EventLog, TimeTravel};
+use crate::services::undo_history::{self, UndoHistoryLimits};
+use crate::view::prompt::PromptType;
 use rust_i18n::t;
 
 impl Editor {
@@ -11,6 +21,9 @@ impl Editor {
             return;
         }
 
+        // The snippet tabstops don't follow the history
+        self.active_state_mut().snippet_session = None;
+
         let event_log = self.active_event_log_mut();
         let before_idx = event_log.current_index();
         let can_undo = event_log.can_undo();
@@ -42,9 +55,14 @@ impl Editor {
             return;
         }
 
+        self.active_state_mut().snippet_session = None;
         let events = self.active_event_log_mut().redo();
+        self.apply_history_events(events);
+    }
 
-        // Apply all events collected during redo
+    /// Apply events collected from the event log while moving through the
+    /// history (redo, time travel, jumping to a state)
+    pub(super) fn apply_history_events(&mut self, events: Vec<Event>) {
         for event in events {
             self.apply_event_to_active_buffer(&event);
         }
@@ -52,4 +70,145 @@ impl Editor {
         // Update modified status based on event log position
         self.update_modified_from_event_log();
     }
+
+    /// Ask how far to go back (or forward) in the active buffer's history
+    pub fn start_time_travel_prompt(&mut self, later: bool) {
+        let (message, prompt_type) = if later {
+            (t!("undo.later_prompt"), PromptType::UndoLater)
+        } else {
+            (t!("undo.earlier_prompt"), PromptType::UndoEarlier)
+        };
+        self.start_prompt(message.to_string(), prompt_type);
+    }
+
+    /// Go to an earlier or later state of the active buffer, by a number of
+    /// steps or an amount of time (e.g. "5", "10s", "5 min")
+    ///
+    /// Unlike undo and redo, this follows the order the states were made in,
+    /// across branches of the undo tree.
+    pub fn handle_time_travel(&mut self, input: &str, later: bool) {
+        if self.is_editing_disabled() {
+            self.set_status_message(t!("buffer.editing_disabled").to_string());
+            return;
+        }
+        let Some(by) = parse_time_travel(input) else {
+            self.set_status_message(t!("undo.invalid_time_travel", input = input).to_string());
+            return;
+        };
+
+        self.active_state_mut().snippet_session = None;
+        let event_log = self.active_event_log_mut();
+        let before = event_log.current_state();
+        let events = if later {
+            event_log.later(by)
+        } else {
+            event_log.earlier(by)
+        };
+        if self.active_event_log().current_state() == before {
+            let message = if later {
+                t!("undo.no_later_state")
+            } else {
+                t!("undo.no_earlier_state")
+            };
+            self.set_status_message(message.to_string());
+            return;
+        }
+        self.apply_history_events(events);
+    }
+
+    fn undo_history_limits(&self) -> UndoHistoryLimits {
+        UndoHistoryLimits {
+            max_bytes: self.config.editor.persistent_undo_max_bytes,
+            max_age: Duration::from_secs(
+                u64::from(self.config.editor.persistent_undo_max_age_days) * 24 * 60 * 60,
+            ),
+        }
+    }
+
+    /// Load the undo history saved for a file that was just opened
+    pub(super) fn load_undo_history(&self, path: &Path) -> Option<EventLog> {
+        if !self.config.editor.persistent_undo {
+            return None;
+        }
+        undo_history::load(
+            &self.dir_context.undo_dir(),
+            path,
+            self.undo_history_limits(),
+        )
+    }
+
+    /// Save the undo history of a file buffer, so it can be undone after reopening it
+    pub(super) fn save_undo_history(&self, buffer_id: BufferId) {
+        if !self.config.editor.persistent_undo {
+            return;
+        }
+        let Some(state) = self.buffers.get(&buffer_id) else {
+            return;
+        };
+        let (Some(path), Some(log)) = (state.buffer.file_path(), self.event_logs.get(&buffer_id))
+        else {
+            return;
+        };
+        if state.buffer.is_large_file() || state.buffer.is_remote() {
+            return;
+        }
+        if let Err(e) = undo_history::save(
+            &self.dir_context.undo_dir(),
+            path,
+            log,
+            self.undo_history_limits(),
+        ) {
+            tracing::warn!("Failed to save undo history for {:?}: {}", path, e);
+        }
+    }
+
+    /// Save the undo histories of all open files (when the editor exits)
+    pub fn save_undo_histories(&self) {
+        for buffer_id in self.buffers.keys() {
+            self.save_undo_history(*buffer_id);
+        }
+    }
+}
+
+/// Parse how far to travel through the history: a number of steps ("3",
+/// "3 steps") or an amount of time ("90s", "5 min", "2 hours", "1d")
+fn parse_time_travel(input: &str) -> Option<TimeTravel> {
+    let input = input.trim().to_lowercase();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (count, unit) = input.split_at(split);
+    let count: u64 = count.parse().ok()?;
+    let seconds = match unit.trim() {
+        "" | "step" | "steps" => return Some(TimeTravel::Steps(count as usize)),
+        "s" | "sec" | "secs" | "second" | "seconds" => 1,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+        "d" | "day" | "days" => 24 * 60 * 60,
+        _ => return None,
+    };
+    Some(TimeTravel::Time(Duration::from_secs(
+        count.checked_mul(seconds)?,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_travel() {
+        assert_eq!(parse_time_travel("3"), Some(TimeTravel::Steps(3)));
+        assert_eq!(parse_time_travel(" 1 step "), Some(TimeTravel::Steps(1)));
+        assert_eq!(
+            parse_time_travel("5 Min"),
+            Some(TimeTravel::Time(Duration::from_secs(300)))
+        );
+        assert_eq!(
+            parse_time_travel("2h"),
+            Some(TimeTravel::Time(Duration::from_secs(7200)))
+        );
+        assert_eq!(parse_time_travel("soon"), None);
+        assert_eq!(parse_time_travel("5 weeks"), None);
+    }
 }
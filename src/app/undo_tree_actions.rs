@@ -0,0 +1,518 @@
+//! Undo history panel for the Editor.
+//!
+//! "Undo History" lists every state of the active buffer's undo tree (see
+//! `EventLog::undo_states`) in the `*Undo History*` panel, with the time each
+//! state was made. Branches left behind by undoing and then editing stay in
+//! the tree; they are listed, indented, under the state they branch off from.
+//! A diff view below the buffer compares its text with the state under the
+//! panel cursor, and Enter jumps to that state. The diff view is a composite
+//! buffer over two hidden buffers holding the current and the selected text.
+
+use rust_i18n::t;
+
+use crate::model::buffer::TextBuffer;
+use crate::model::composite_buffer::{
+    CompositeLayout, LineAlignment, PaneStyle, RowType, SourcePane,
+};
+use crate::model::event::{BufferId, Event, SplitDirection, SplitId, UndoNode};
+use crate::model::line_diff::diff_hunks;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::view::split::SplitViewState;
+
+use super::Editor;
+
+/// Name of the undo history panel buffer
+pub(super) const UNDO_HISTORY_BUFFER_NAME: &str = "*Undo History*";
+
+/// Longest text of an edit shown in the panel
+const EDIT_PREVIEW_CHARS: usize = 24;
+
+/// State of the undo history panel
+#[derive(Debug)]
+pub(super) struct UndoHistoryPanel {
+    /// The `*Undo History*` virtual buffer
+    buffer_id: BufferId,
+    /// Split showing the history buffer
+    split_id: SplitId,
+    /// Buffer whose history is listed, and its file name
+    source: BufferId,
+    name: String,
+    /// Split showing the source buffer
+    source_split: SplitId,
+    /// Composite buffer diffing the current text with the selected state
+    preview_id: BufferId,
+    /// Split showing the diff
+    preview_split: SplitId,
+    /// Hidden buffers with the two sides of the diff
+    current_text_id: BufferId,
+    selected_text_id: BufferId,
+    /// Listed states, one per line after the header
+    states: Vec<UndoNode>,
+    /// Log size, current state and saved state the list was made from
+    listed: (usize, UndoNode, Option<UndoNode>),
+    /// State shown in the diff view
+    previewed: Option<UndoNode>,
+}
+
+impl Editor {
+    /// Open the undo history of the active buffer
+    pub(super) fn open_undo_history(&mut self) {
+        let active = self.active_buffer();
+        if self
+            .undo_history_panel
+            .as_ref()
+            .is_some_and(|panel| panel.buffer_id == active || panel.preview_id == active)
+        {
+            return;
+        }
+        self.close_undo_history();
+        if self.is_editing_disabled() {
+            self.set_status_message(t!("buffer.editing_disabled").to_string());
+            return;
+        }
+
+        let source = self.active_buffer();
+        let source_split = self.split_manager.active_split();
+        let display_name = self.get_buffer_display_name(source);
+        let name = std::path::Path::new(&display_name)
+            .file_name()
+            .map_or(display_name.clone(), |name| {
+                name.to_string_lossy().into_owned()
+            });
+
+        let current_text_id = self.create_hidden_text_buffer(format!("*CURRENT:{}*", name));
+        let selected_text_id = self.create_hidden_text_buffer(format!("*UNDO:{}*", name));
+        let preview_id = self.create_composite_buffer(
+            t!("undo_history.preview_name", name = &name).to_string(),
+            "undo-history".to_string(),
+            CompositeLayout::default(),
+            vec![
+                SourcePane::new(current_text_id, t!("undo_history.current"), false)
+                    .with_style(PaneStyle::old_diff()),
+                SourcePane::new(selected_text_id, t!("undo_history.selected"), false)
+                    .with_style(PaneStyle::new_diff()),
+            ],
+        );
+        let buffer_id = self.create_virtual_buffer(
+            UNDO_HISTORY_BUFFER_NAME.to_string(),
+            "undo-history".to_string(),
+            true,
+        );
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = true;
+            state.margins.set_line_numbers(false);
+        }
+        let created = [buffer_id, preview_id, current_text_id, selected_text_id];
+
+        // The list goes to the right of the buffer, the diff below it
+        self.save_current_split_view_state();
+        let splits = self
+            .split_manager
+            .split_active(SplitDirection::Vertical, buffer_id, 0.65)
+            .and_then(|split_id| {
+                self.split_manager.set_active_split(source_split);
+                self.split_manager
+                    .split_active(SplitDirection::Horizontal, preview_id, 0.5)
+                    .map(|preview_split| (split_id, preview_split))
+            });
+        let (split_id, preview_split) = match splits {
+            Ok(splits) => splits,
+            Err(e) => {
+                self.split_manager.set_active_split(source_split);
+                self.restore_current_split_view_state();
+                for id in created {
+                    let _ = self.close_buffer(id);
+                }
+                self.close_composite_buffer(preview_id);
+                self.set_status_message(t!("split.error", error = e.to_string()).to_string());
+                return;
+            }
+        };
+        // The panel buffers only belong in their own splits' tabs
+        if let Some(view_state) = self.split_view_states.get_mut(&source_split) {
+            for id in created {
+                view_state.remove_buffer(id);
+            }
+        }
+        for (split, buffer) in [(split_id, buffer_id), (preview_split, preview_id)] {
+            let mut view_state =
+                SplitViewState::with_buffer(self.terminal_width, self.terminal_height, buffer);
+            view_state.viewport.line_wrap_enabled = false;
+            self.split_view_states.insert(split, view_state);
+        }
+        self.split_manager.set_active_split(split_id);
+        self.restore_current_split_view_state();
+
+        self.undo_history_panel = Some(UndoHistoryPanel {
+            buffer_id,
+            split_id,
+            source,
+            name,
+            source_split,
+            preview_id,
+            preview_split,
+            current_text_id,
+            selected_text_id,
+            states: Vec::new(),
+            listed: (0, None, None),
+            previewed: None,
+        });
+        self.fill_undo_history();
+        self.update_undo_history_panel();
+    }
+
+    /// Go to the state under the cursor of the undo history panel
+    pub(super) fn undo_history_jump(&mut self) {
+        let Some(node) = self.selected_undo_state() else {
+            return;
+        };
+        let Some(panel) = &self.undo_history_panel else {
+            return;
+        };
+        let (source, source_split) = (panel.source, panel.source_split);
+        let return_split = self.split_manager.active_split();
+
+        // Apply the change in the buffer's own split, so it goes through the
+        // usual path (cursors, LSP notifications, plugin hooks)
+        let source_split = if self.split_manager.get_buffer_id(source_split) == Some(source) {
+            Some(source_split)
+        } else {
+            self.split_manager
+                .splits_for_buffer(source)
+                .into_iter()
+                .next()
+        };
+        let Some(source_split) = source_split else {
+            self.set_status_message(t!("undo_history.source_not_visible").to_string());
+            return;
+        };
+
+        self.save_current_split_view_state();
+        self.split_manager.set_active_split(source_split);
+        self.restore_current_split_view_state();
+        self.active_state_mut().snippet_session = None;
+        let events = self.active_event_log_mut().jump_to(node);
+        self.apply_history_events(events);
+
+        self.save_current_split_view_state();
+        self.split_manager.set_active_split(return_split);
+        self.restore_current_split_view_state();
+        self.update_undo_history_panel();
+    }
+
+    /// Close the undo history panel and its diff view
+    pub(super) fn close_undo_history(&mut self) {
+        let Some(panel) = self.undo_history_panel.take() else {
+            return;
+        };
+        let active_split = self.split_manager.active_split();
+        let was_active = active_split == panel.split_id || active_split == panel.preview_split;
+        if was_active
+            && self
+                .split_manager
+                .get_buffer_id(panel.source_split)
+                .is_some()
+        {
+            self.split_manager.set_active_split(panel.source_split);
+        }
+        for split_id in [panel.preview_split, panel.split_id] {
+            if self.split_manager.close_split(split_id).is_ok() {
+                self.split_view_states.remove(&split_id);
+            }
+        }
+        if was_active {
+            self.sync_split_view_state_to_editor_state();
+        }
+        for id in [
+            panel.buffer_id,
+            panel.preview_id,
+            panel.current_text_id,
+            panel.selected_text_id,
+        ] {
+            let _ = self.close_buffer(id);
+        }
+        self.close_composite_buffer(panel.preview_id);
+    }
+
+    /// Keep the undo history panel in sync with its buffer's history and the
+    /// panel cursor
+    ///
+    /// Called from the main loop; returns true if a redraw is needed.
+    pub(super) fn update_undo_history_panel(&mut self) -> bool {
+        let Some(panel) = &self.undo_history_panel else {
+            return false;
+        };
+        let is_open = [
+            (panel.split_id, panel.buffer_id),
+            (panel.preview_split, panel.preview_id),
+        ]
+        .iter()
+        .all(|&(split, buffer)| self.split_manager.get_buffer_id(split) == Some(buffer));
+        let Some(log) = self.event_logs.get(&panel.source).filter(|_| is_open) else {
+            // The panel or its buffer was closed through other means
+            self.close_undo_history();
+            return true;
+        };
+
+        let listed = (log.entries().len(), log.current_state(), log.saved_state());
+        let mut changed = false;
+        if listed != panel.listed {
+            self.fill_undo_history();
+            changed = true;
+        }
+        let selected = self.selected_undo_state();
+        if selected.is_some()
+            && self
+                .undo_history_panel
+                .as_ref()
+                .is_some_and(|panel| panel.previewed != selected)
+        {
+            self.show_undo_preview(selected.flatten());
+            changed = true;
+        }
+        changed
+    }
+
+    /// Create an empty, read-only buffer that is not shown in the tabs
+    fn create_hidden_text_buffer(&mut self, name: String) -> BufferId {
+        let buffer_id = self.create_virtual_buffer(name, "normal".to_string(), true);
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = true;
+        }
+        if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
+            metadata.hidden_from_tabs = true;
+        }
+        buffer_id
+    }
+
+    /// State on the line of the panel cursor
+    fn selected_undo_state(&self) -> Option<UndoNode> {
+        let panel = self.undo_history_panel.as_ref()?;
+        let state = self.buffers.get(&panel.buffer_id)?;
+        // The cursors of the active split live in its buffer's state
+        let position = if self.split_manager.active_split() == panel.split_id {
+            state.cursors.primary().position
+        } else {
+            self.split_view_states
+                .get(&panel.split_id)?
+                .cursors
+                .primary()
+                .position
+        };
+        state
+            .text_properties
+            .get_at(position)
+            .iter()
+            .find_map(|prop| prop.get_as::<usize>("state"))
+            .and_then(|index| panel.states.get(index).copied())
+    }
+
+    /// List the states of the source's undo tree and put the cursor on the
+    /// current one
+    fn fill_undo_history(&mut self) {
+        let Some(panel) = &self.undo_history_panel else {
+            return;
+        };
+        let (buffer_id, split_id) = (panel.buffer_id, panel.split_id);
+        let Some(log) = self.event_logs.get(&panel.source) else {
+            return;
+        };
+        let current = log.current_state();
+        let saved = log.saved_state();
+
+        let mut entries = vec![TextPropertyEntry::text(format!(
+            "{}\n",
+            t!("undo_history.header", name = &panel.name)
+        ))];
+        let mut states = Vec::new();
+        let mut current_line = 1;
+        for (index, state) in log.undo_states().into_iter().enumerate() {
+            let entry = state.node.and_then(|entry| log.entry(entry));
+            let (time, summary) = match entry {
+                Some(entry) => (
+                    format_state_time(entry.timestamp),
+                    edit_summary(&entry.event),
+                ),
+                None => (String::new(), t!("undo_history.initial_state").to_string()),
+            };
+            if state.node == current {
+                current_line = index + 1;
+            }
+            let marker = if state.node == current { "●" } else { " " };
+            let saved = if saved == Some(state.node) {
+                format!("  {}", t!("undo_history.saved"))
+            } else {
+                String::new()
+            };
+            entries.push(
+                TextPropertyEntry::text(format!(
+                    "{} {}{:>4}  {:<8}  {}{}\n",
+                    marker,
+                    "  ".repeat(state.depth),
+                    state.seq,
+                    time,
+                    summary,
+                    saved
+                ))
+                .with_property("state", serde_json::json!(index)),
+            );
+            states.push(state.node);
+        }
+        let listed = (log.entries().len(), current, saved);
+
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to set undo history content: {}", e);
+            return;
+        }
+        if let Some(panel) = &mut self.undo_history_panel {
+            panel.states = states;
+            panel.listed = listed;
+            panel.previewed = None;
+        }
+
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        let position = state.buffer.line_start_offset(current_line).unwrap_or(0);
+        let cursor = state.cursors.primary_mut();
+        cursor.position = position;
+        cursor.anchor = None;
+        cursor.sticky_column = 0;
+        if let Some(view_state) = self.split_view_states.get_mut(&split_id) {
+            view_state.cursors = state.cursors.clone();
+        }
+    }
+
+    /// Show the diff between the source's text and its text at `node`
+    fn show_undo_preview(&mut self, node: UndoNode) {
+        let Some(panel) = &mut self.undo_history_panel else {
+            return;
+        };
+        panel.previewed = Some(node);
+        let (source, preview_id, preview_split) =
+            (panel.source, panel.preview_id, panel.preview_split);
+        let (current_text_id, selected_text_id) = (panel.current_text_id, panel.selected_text_id);
+
+        let (Some(state), Some(log)) = (self.buffers.get(&source), self.event_logs.get(&source))
+        else {
+            return;
+        };
+        // Large files aren't fully loaded; there is nothing to compare
+        let Some(current) = state.buffer.to_string() else {
+            return;
+        };
+        let mut selected = current.clone().into_bytes();
+        for event in log.events_to(node) {
+            apply_to_text(&mut selected, &event, &state.buffer);
+        }
+        let hunks = diff_hunks(current.as_bytes(), &selected);
+        let selected = String::from_utf8_lossy(&selected).into_owned();
+
+        for (buffer_id, text) in [(current_text_id, current), (selected_text_id, selected)] {
+            if let Err(e) =
+                self.set_virtual_buffer_content(buffer_id, vec![TextPropertyEntry::text(text)])
+            {
+                tracing::error!("Failed to set undo preview content: {}", e);
+                return;
+            }
+        }
+        let line_count = |id| {
+            self.buffers
+                .get(&id)
+                .and_then(|state| state.buffer.line_count())
+                .unwrap_or(0)
+        };
+        let alignment = LineAlignment::from_hunks(
+            &hunks,
+            line_count(current_text_id),
+            line_count(selected_text_id),
+        );
+
+        // Show the first change
+        let first_change = alignment
+            .rows
+            .iter()
+            .position(|row| row.row_type == RowType::HunkHeader)
+            .unwrap_or(0);
+        self.set_composite_alignment(preview_id, alignment);
+        if let Some(view_state) = self.get_composite_view_state(preview_split, preview_id) {
+            view_state.scroll_row = first_change.saturating_sub(2);
+            view_state.cursor_row = first_change;
+        }
+    }
+}
+
+/// Apply the buffer edits of `event` to `text`
+fn apply_to_text(text: &mut Vec<u8>, event: &Event, buffer: &TextBuffer) {
+    match event {
+        Event::Insert {
+            position,
+            text: inserted,
+            ..
+        } if *position <= text.len() => {
+            text.splice(*position..*position, inserted.bytes());
+        }
+        Event::Delete { range, .. } if range.start <= range.end && range.end <= text.len() => {
+            text.drain(range.clone());
+        }
+        Event::Batch { events, .. } => {
+            for event in events {
+                apply_to_text(text, event, buffer);
+            }
+        }
+        Event::BulkEdit {
+            new_tree: Some(tree),
+            ..
+        } => {
+            if let Some(content) = buffer.snapshot_text(tree) {
+                *text = content;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Short description of the edit that made a state
+fn edit_summary(event: &Event) -> String {
+    match event {
+        Event::Insert { text, .. } => t!("undo_history.inserted", text = edit_preview(text)),
+        Event::Delete { deleted_text, .. } => {
+            t!("undo_history.deleted", text = edit_preview(deleted_text))
+        }
+        Event::Batch { description, .. } | Event::BulkEdit { description, .. } => {
+            description.as_str().into()
+        }
+        _ => t!("undo_history.cursors"),
+    }
+    .to_string()
+}
+
+/// The start of an edit's text on a single line
+fn edit_preview(text: &str) -> String {
+    let mut preview: String = text
+        .chars()
+        .take(EDIT_PREVIEW_CHARS)
+        .map(|c| match c {
+            '\n' => '⏎',
+            '\t' => '→',
+            c => c,
+        })
+        .collect();
+    if text.chars().nth(EDIT_PREVIEW_CHARS).is_some() {
+        preview.push('…');
+    }
+    preview
+}
+
+/// When a state was made: the time of day, with the date if it wasn't today
+fn format_state_time(timestamp: u64) -> String {
+    let Some(time) = chrono::DateTime::from_timestamp_millis(timestamp as i64) else {
+        return String::new();
+    };
+    let time = time.with_timezone(&chrono::Local);
+    if time.date_naive() == chrono::Local::now().date_naive() {
+        time.format("%H:%M:%S").to_string()
+    } else {
+        time.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
@@ -0,0 +1,213 @@
+//! Popup listing the keys that can follow a chord prefix
+//!
+//! After the first key of a multi-key binding (e.g. Ctrl+X in the Emacs
+//! keymap) the editor waits for the rest of the sequence. If no key follows
+//! within `which_key_delay_ms`, a popup lists the keys that can come next
+//! and the commands they run. The popup is transient, so the next key press
+//! closes it and carries on with the chord.
+
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use rust_i18n::t;
+
+use crate::input::keybindings::{format_keybinding, Action, KeybindingResolver};
+use crate::view::popup::{Popup, PopupPosition};
+use crate::view::theme::Theme;
+
+use super::Editor;
+
+/// Longest command name shown before it is cut off
+const MAX_LABEL_WIDTH: usize = 32;
+
+/// Space between the columns of the popup
+const COLUMN_GAP: usize = 3;
+
+/// The chord prefix the popup is for
+#[derive(Debug, Default)]
+pub(super) struct WhichKeyState {
+    /// Keys of the chord pressed so far
+    prefix: Vec<(KeyCode, KeyModifiers)>,
+    /// When the prefix was first seen
+    since: Option<Instant>,
+    /// Whether the popup was shown for the prefix
+    shown: bool,
+}
+
+impl Editor {
+    /// Show the keys that can follow a chord prefix once it has been
+    /// pending for the configured delay
+    ///
+    /// Returns true if the popup was shown.
+    pub(super) fn poll_which_key(&mut self) -> bool {
+        if !self.config.editor.which_key_enabled {
+            return false;
+        }
+        if self.chord_state != self.which_key.prefix {
+            self.which_key = WhichKeyState {
+                prefix: self.chord_state.clone(),
+                since: Some(self.time_source.now()),
+                shown: false,
+            };
+        }
+        if self.chord_state.is_empty() || self.which_key.shown {
+            return false;
+        }
+        let delay = Duration::from_millis(self.config.editor.which_key_delay_ms);
+        if self
+            .which_key
+            .since
+            .is_some_and(|since| self.time_source.elapsed_since(since) < delay)
+        {
+            return false;
+        }
+
+        self.which_key.shown = true;
+        let entries = self.which_key_entries();
+        if entries.is_empty() {
+            return false;
+        }
+        let title = self
+            .chord_state
+            .iter()
+            .map(|(code, modifiers)| format_keybinding(code, modifiers))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let popup = which_key_popup(&entries, self.terminal_width, &self.theme)
+            .with_title(title)
+            .with_transient(true)
+            .with_position(PopupPosition::BottomRight);
+        self.active_state_mut().popups.show(popup);
+        true
+    }
+
+    /// Keys that can follow the pending chord prefix, with what they do
+    fn which_key_entries(&self) -> Vec<(String, String)> {
+        let continuations = self
+            .editor_mode
+            .as_ref()
+            .map(|mode| {
+                // Chords of the editor mode are tried first when keys are handled
+                self.mode_registry
+                    .chord_continuations(mode, &self.chord_state)
+                    .into_iter()
+                    .map(|(key, command)| {
+                        let action = command.map(|command| {
+                            Action::from_str(&command, &Default::default())
+                                .unwrap_or(Action::PluginAction(command))
+                        });
+                        (key, action)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|continuations| !continuations.is_empty())
+            .unwrap_or_else(|| {
+                self.keybindings.chord_continuations(
+                    &self.chord_state,
+                    self.get_key_context(),
+                    &self.get_when_context(),
+                )
+            });
+
+        let commands = self.command_registry.read().unwrap().get_all();
+        continuations
+            .into_iter()
+            .map(|((code, modifiers), action)| {
+                let label = match action {
+                    Some(action) => commands
+                        .iter()
+                        .find(|command| command.action == action)
+                        .map(|command| command.get_localized_name())
+                        .unwrap_or_else(|| KeybindingResolver::format_action(&action)),
+                    None => t!("which_key.more_keys").to_string(),
+                };
+                (format_keybinding(&code, &modifiers), label)
+            })
+            .collect()
+    }
+}
+
+/// Popup laying the entries out in as many columns as fit the terminal
+fn which_key_popup(entries: &[(String, String)], terminal_width: u16, theme: &Theme) -> Popup {
+    let key_width = entries
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+    let cells: Vec<String> = entries
+        .iter()
+        .map(|(key, label)| {
+            let label: String = label.chars().take(MAX_LABEL_WIDTH).collect();
+            format!("{:<key_width$}  {}", key, label)
+        })
+        .collect();
+    let cell_width = cells.iter().map(|c| c.chars().count()).max().unwrap_or(0);
+
+    // Leave room for the border
+    let available = (terminal_width as usize).saturating_sub(2);
+    let columns = ((available + COLUMN_GAP) / (cell_width + COLUMN_GAP))
+        .min(cells.len())
+        .max(1);
+    let rows = cells.len().div_ceil(columns);
+    let lines: Vec<String> = (0..rows)
+        .map(|row| {
+            (0..columns)
+                .filter_map(|column| cells.get(column * rows + row))
+                .map(|cell| format!("{:<cell_width$}", cell))
+                .collect::<Vec<_>>()
+                .join(&" ".repeat(COLUMN_GAP))
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+
+    let width = columns * cell_width + (columns - 1) * COLUMN_GAP + 2;
+    Popup::text(lines, theme)
+        .with_width(width as u16)
+        .with_max_height(rows as u16 + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::popup::PopupContent;
+
+    fn entries(count: usize) -> Vec<(String, String)> {
+        (0..count)
+            .map(|i| (format!("K{}", i), format!("Command {}", i)))
+            .collect()
+    }
+
+    #[test]
+    fn test_which_key_popup_columns() {
+        let popup = which_key_popup(&entries(3), 80, &Theme::dark());
+        assert_eq!(
+            popup.content,
+            PopupContent::Text(vec![
+                "K0  Command 0   K1  Command 1   K2  Command 2".to_string()
+            ])
+        );
+        assert_eq!(popup.width, 47);
+
+        // Entries fill the columns top to bottom
+        let popup = which_key_popup(&entries(3), 31, &Theme::dark());
+        assert_eq!(
+            popup.content,
+            PopupContent::Text(vec![
+                "K0  Command 0   K2  Command 2".to_string(),
+                "K1  Command 1".to_string(),
+            ])
+        );
+        assert_eq!(popup.max_height, 4);
+
+        // A narrow terminal still gets one column
+        let popup = which_key_popup(&entries(2), 5, &Theme::dark());
+        assert_eq!(
+            popup.content,
+            PopupContent::Text(vec![
+                "K0  Command 0".to_string(),
+                "K1  Command 1".to_string()
+            ])
+        );
+    }
+}
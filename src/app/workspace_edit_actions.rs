@@ -0,0 +1,358 @@
+//! Applying LSP workspace edits.
+//!
+//! Used for rename results and for `workspace/applyEdit` requests, which
+//! servers send while executing commands and code actions. Text edits open the
+//! files they touch in the background and become one undo step per document.
+//! Create, rename and delete operations act on disk and keep open buffers in
+//! sync. Changes are applied in order and stop at the first failure, whose
+//! index is reported back to the server.
+
+use std::path::{Path, PathBuf};
+
+use lsp_types::{
+    CreateFile, DeleteFile, DocumentChangeOperation, DocumentChanges, OneOf, RenameFile,
+    ResourceOp, WorkspaceEdit,
+};
+use rust_i18n::t;
+
+use crate::model::event::BufferId;
+use crate::services::lsp::manager::detect_language;
+
+use super::types::BufferMetadata;
+use super::{uri_to_path, Editor};
+
+/// Why a workspace edit was not fully applied
+#[derive(Debug, Clone)]
+pub(super) struct WorkspaceEditFailure {
+    /// Index of the change that failed, if the edit had an order
+    pub index: Option<u32>,
+    pub reason: String,
+}
+
+impl Editor {
+    /// Apply a workspace edit, returning the number of text edits made
+    ///
+    /// `document_changes` takes precedence over `changes` when both are set.
+    pub(super) fn apply_workspace_edit(
+        &mut self,
+        edit: WorkspaceEdit,
+        description: &str,
+    ) -> Result<usize, WorkspaceEditFailure> {
+        let mut total_changes = 0;
+
+        if let Some(document_changes) = edit.document_changes {
+            let operations = match document_changes {
+                DocumentChanges::Edits(edits) => edits
+                    .into_iter()
+                    .map(DocumentChangeOperation::Edit)
+                    .collect(),
+                DocumentChanges::Operations(ops) => ops,
+            };
+
+            for (index, operation) in operations.into_iter().enumerate() {
+                let result = match operation {
+                    DocumentChangeOperation::Edit(edit) => {
+                        let edits = edit
+                            .edits
+                            .into_iter()
+                            .map(|edit| match edit {
+                                OneOf::Left(text_edit) => text_edit,
+                                OneOf::Right(annotated) => annotated.text_edit,
+                            })
+                            .collect();
+                        self.apply_document_edits(
+                            &edit.text_document.uri,
+                            edit.text_document.version,
+                            edits,
+                            description,
+                        )
+                    }
+                    DocumentChangeOperation::Op(op) => self.apply_resource_op(op).map(|()| 0),
+                };
+                match result {
+                    Ok(changes) => total_changes += changes,
+                    Err(reason) => {
+                        return Err(WorkspaceEditFailure {
+                            index: Some(index as u32),
+                            reason,
+                        })
+                    }
+                }
+            }
+        } else if let Some(changes) = edit.changes {
+            for (uri, edits) in changes {
+                total_changes += self
+                    .apply_document_edits(&uri, None, edits, description)
+                    .map_err(|reason| WorkspaceEditFailure {
+                        index: None,
+                        reason,
+                    })?;
+            }
+        }
+
+        Ok(total_changes)
+    }
+
+    /// Apply a workspace/applyEdit request and tell the server how it went
+    pub(super) fn handle_lsp_apply_edit(
+        &mut self,
+        language: String,
+        server_request_id: i64,
+        label: Option<String>,
+        edit: WorkspaceEdit,
+    ) {
+        let description = label.clone().unwrap_or_else(|| "LSP Edit".to_string());
+        let response = match self.apply_workspace_edit(edit, &description) {
+            Ok(count) => {
+                if let Some(label) = label {
+                    self.set_status_message(
+                        t!("lsp.workspace_edit_applied", label = label, count = count).to_string(),
+                    );
+                }
+                lsp_types::ApplyWorkspaceEditResponse {
+                    applied: true,
+                    failure_reason: None,
+                    failed_change: None,
+                }
+            }
+            Err(failure) => {
+                tracing::warn!(
+                    "Failed to apply workspace edit from {}: {}",
+                    language,
+                    failure.reason
+                );
+                self.set_status_message(
+                    t!("lsp.workspace_edit_failed", error = &failure.reason).to_string(),
+                );
+                lsp_types::ApplyWorkspaceEditResponse {
+                    applied: false,
+                    failure_reason: Some(failure.reason),
+                    failed_change: failure.index,
+                }
+            }
+        };
+
+        let result = serde_json::to_value(response).unwrap_or(serde_json::Value::Null);
+        let Some(handle) = self
+            .lsp
+            .as_mut()
+            .and_then(|lsp| lsp.get_handle_mut(&language))
+        else {
+            tracing::warn!("No {} server to reply to workspace/applyEdit", language);
+            return;
+        };
+        if let Err(e) = handle.reply_to_server(server_request_id, result) {
+            tracing::warn!("Failed to reply to workspace/applyEdit: {}", e);
+        }
+    }
+
+    /// Apply text edits to the document at `uri`, opening it if needed
+    ///
+    /// Edits made for a `version` of the document other than the one the
+    /// server knows are refused: they were computed against stale text.
+    fn apply_document_edits(
+        &mut self,
+        uri: &lsp_types::Uri,
+        version: Option<i32>,
+        edits: Vec<lsp_types::TextEdit>,
+        description: &str,
+    ) -> Result<usize, String> {
+        let path = uri_to_path(uri)?;
+        if self.find_buffer_by_path(&path).is_none() && !path.exists() {
+            return Err(format!("{} does not exist", path.display()));
+        }
+        let buffer_id = self
+            .open_file_no_focus(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        if let Some(version) = version {
+            let current = self.lsp_document_version(buffer_id);
+            if current.is_some_and(|current| current != version) {
+                return Err(format!(
+                    "{} changed since the edit was made (version {} instead of {})",
+                    path.display(),
+                    current.unwrap_or_default(),
+                    version
+                ));
+            }
+        }
+        self.apply_lsp_text_edits(buffer_id, edits, description)
+            .map_err(|e| format!("Failed to edit {}: {}", path.display(), e))
+    }
+
+    /// Perform a create, rename or delete file operation
+    fn apply_resource_op(&mut self, op: ResourceOp) -> Result<(), String> {
+        match op {
+            ResourceOp::Create(create) => self.create_file_op(create),
+            ResourceOp::Rename(rename) => self.rename_file_op(rename),
+            ResourceOp::Delete(delete) => self.delete_file_op(delete),
+        }
+    }
+
+    fn create_file_op(&mut self, create: CreateFile) -> Result<(), String> {
+        let path = uri_to_path(&create.uri)?;
+        let options = create.options.as_ref();
+        let overwrite = options.and_then(|o| o.overwrite).unwrap_or(false);
+        let ignore_if_exists = options.and_then(|o| o.ignore_if_exists).unwrap_or(false);
+        // Overwrite wins over ignoreIfExists
+        if path.exists() && !overwrite {
+            if ignore_if_exists {
+                return Ok(());
+            }
+            return Err(format!("{} already exists", path.display()));
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&path, "")
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+
+        // An open buffer for an overwritten file would otherwise keep stale text
+        if let Some(buffer_id) = self.find_buffer_by_path(&path) {
+            self.force_close_buffer(buffer_id)
+                .map_err(|e| format!("Failed to close {}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+
+    fn rename_file_op(&mut self, rename: RenameFile) -> Result<(), String> {
+        let old_path = uri_to_path(&rename.old_uri)?;
+        let new_path = uri_to_path(&rename.new_uri)?;
+        let options = rename.options.as_ref();
+        let overwrite = options.and_then(|o| o.overwrite).unwrap_or(false);
+        let ignore_if_exists = options.and_then(|o| o.ignore_if_exists).unwrap_or(false);
+        if new_path.exists() {
+            if !overwrite {
+                if ignore_if_exists {
+                    return Ok(());
+                }
+                return Err(format!("{} already exists", new_path.display()));
+            }
+            if let Some(buffer_id) = self.find_buffer_by_path(&new_path) {
+                self.force_close_buffer(buffer_id)
+                    .map_err(|e| format!("Failed to close {}: {}", new_path.display(), e))?;
+            }
+        }
+
+        // Open buffers store canonical paths
+        let old_path = old_path
+            .canonicalize()
+            .map_err(|e| format!("Cannot rename {}: {}", old_path.display(), e))?;
+        if let Some(parent) = new_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::rename(&old_path, &new_path).map_err(|e| {
+            format!(
+                "Failed to rename {} to {}: {}",
+                old_path.display(),
+                new_path.display(),
+                e
+            )
+        })?;
+        let new_path = new_path.canonicalize().unwrap_or(new_path);
+
+        // Move every buffer at or below the old path (directories can be renamed)
+        for (buffer_id, path) in self.buffers_under_path(&old_path) {
+            let Ok(relative) = path.strip_prefix(&old_path) else {
+                continue;
+            };
+            let moved = if relative.as_os_str().is_empty() {
+                new_path.clone()
+            } else {
+                new_path.join(relative)
+            };
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.buffer.set_file_path(moved.clone());
+            }
+            let mut metadata = BufferMetadata::with_file(moved.clone(), &self.working_dir);
+            if let Some(old) = self.buffer_metadata.get(&buffer_id) {
+                metadata.read_only = old.read_only;
+                metadata.binary = old.binary;
+            }
+            self.buffer_metadata.insert(buffer_id, metadata);
+            if let Some(mtime) = self.file_mod_times.remove(&path) {
+                self.file_mod_times.insert(moved, mtime);
+            }
+        }
+        Ok(())
+    }
+
+    fn delete_file_op(&mut self, delete: DeleteFile) -> Result<(), String> {
+        let path = uri_to_path(&delete.uri)?;
+        let options = delete.options.as_ref();
+        let recursive = options.and_then(|o| o.recursive).unwrap_or(false);
+        // Not following symlinks: a link is deleted, not what it points to
+        let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+            if options
+                .and_then(|o| o.ignore_if_not_exists)
+                .unwrap_or(false)
+            {
+                return Ok(());
+            }
+            return Err(format!("{} does not exist", path.display()));
+        };
+
+        // Open buffers store canonical paths; those of a link's target stay open
+        let buffers_path = if metadata.is_symlink() {
+            path.clone()
+        } else {
+            path.canonicalize().unwrap_or_else(|_| path.clone())
+        };
+        let result = if metadata.is_dir() {
+            if recursive {
+                std::fs::remove_dir_all(&path)
+            } else {
+                std::fs::remove_dir(&path)
+            }
+        } else {
+            std::fs::remove_file(&path)
+        };
+        result.map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+
+        for (buffer_id, buffer_path) in self.buffers_under_path(&buffers_path) {
+            self.file_mod_times.remove(&buffer_path);
+            self.force_close_buffer(buffer_id)
+                .map_err(|e| format!("Failed to close {}: {}", buffer_path.display(), e))?;
+        }
+        Ok(())
+    }
+
+    /// Version of a buffer's document known to its language server, if open there
+    fn lsp_document_version(&self, buffer_id: BufferId) -> Option<i32> {
+        let metadata = self.buffer_metadata.get(&buffer_id)?;
+        let uri = metadata.file_uri()?;
+        let language = detect_language(metadata.file_path()?, &self.config.languages)?;
+        self.lsp
+            .as_ref()?
+            .get_handle(&language)?
+            .document_version(uri)
+    }
+
+    /// Buffer showing the file at `path`, if any
+    fn find_buffer_by_path(&self, path: &Path) -> Option<BufferId> {
+        let canonical = path.canonicalize().ok();
+        self.buffers
+            .iter()
+            .find(|(_, state)| {
+                let buffer_path = state.buffer.file_path();
+                buffer_path == Some(path)
+                    || (canonical.is_some() && buffer_path == canonical.as_deref())
+            })
+            .map(|(id, _)| *id)
+    }
+
+    /// Buffers whose file is `path` or inside the directory `path`
+    fn buffers_under_path(&self, path: &Path) -> Vec<(BufferId, PathBuf)> {
+        self.buffers
+            .iter()
+            .filter_map(|(id, state)| {
+                let buffer_path = state.buffer.file_path()?;
+                buffer_path
+                    .starts_with(path)
+                    .then(|| (*id, buffer_path.to_path_buf()))
+            })
+            .collect()
+    }
+}
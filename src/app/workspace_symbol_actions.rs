@@ -0,0 +1,316 @@
+//! Workspace symbol search for the Editor.
+//!
+//! "Go to Symbol in Workspace" sends `workspace/symbol` requests to every
+//! running language server as the user types. Requests are debounced, and the
+//! ones made stale by further typing are cancelled with `$/cancelRequest`.
+//! Responses arrive one server at a time and are merged into the prompt's
+//! suggestions as they come in. Symbols reported without a range are
+//! resolved (`workspaceSymbol/resolve`) before jumping to them.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use lsp_types::{OneOf, WorkspaceSymbol};
+use rust_i18n::t;
+
+use crate::input::commands::Suggestion;
+use crate::input::fuzzy::fuzzy_match;
+use crate::primitives::outline::kind_label;
+use crate::services::lsp::manager::merge_workspace_symbols;
+use crate::view::prompt::{Prompt, PromptType};
+
+use super::{uri_to_path, Editor};
+
+/// How long the query must stay unchanged before it is sent to the servers
+const WORKSPACE_SYMBOL_DELAY: Duration = Duration::from_millis(150);
+
+/// State of an open workspace symbol prompt
+#[derive(Debug, Default)]
+pub(super) struct WorkspaceSymbolSearch {
+    /// Query the shown symbols are ranked against
+    query: String,
+    /// Time of the last query change not yet sent to the servers
+    edited_at: Option<Instant>,
+    /// Requests still waiting for a response: (language, request ID)
+    requests: Vec<(String, u64)>,
+    /// Latest symbols reported by each server
+    results: HashMap<String, Vec<WorkspaceSymbol>>,
+    /// Symbols listed in the prompt, indexed by the suggestion values
+    symbols: Vec<WorkspaceSymbol>,
+}
+
+impl Editor {
+    /// Open the workspace symbol prompt
+    pub(super) fn start_workspace_symbol_prompt(&mut self) {
+        let has_servers = self
+            .lsp
+            .as_ref()
+            .is_some_and(|lsp| !lsp.running_servers().is_empty());
+        if !has_servers {
+            self.set_status_message(t!("lsp.no_servers_running").to_string());
+            return;
+        }
+
+        self.cancel_workspace_symbol_search();
+        self.prompt = Some(Prompt::new(
+            t!("workspace_symbol.prompt").to_string(),
+            PromptType::WorkspaceSymbol,
+        ));
+        self.workspace_symbol_search = Some(WorkspaceSymbolSearch {
+            edited_at: Some(self.time_source.now()),
+            ..Default::default()
+        });
+    }
+
+    /// Note a change of the query; it is sent once the user pauses typing
+    pub(super) fn workspace_symbol_query_changed(&mut self, query: String) {
+        let now = self.time_source.now();
+        if let Some(search) = &mut self.workspace_symbol_search {
+            search.query = query;
+            search.edited_at = Some(now);
+        }
+        self.refresh_workspace_symbol_suggestions();
+    }
+
+    /// Send the current query again, e.g. after a server became ready
+    pub(super) fn resend_workspace_symbol_query(&mut self) {
+        let now = self.time_source.now();
+        if let Some(search) = &mut self.workspace_symbol_search {
+            search.edited_at = Some(now);
+        }
+    }
+
+    /// Send the debounced query and drop the search once its prompt is gone
+    ///
+    /// Called from the main loop; returns true if a redraw is needed.
+    pub(super) fn poll_workspace_symbol_search(&mut self) -> bool {
+        if self.workspace_symbol_search.is_none() {
+            return false;
+        }
+        let prompt_open = self
+            .prompt
+            .as_ref()
+            .is_some_and(|prompt| prompt.prompt_type == PromptType::WorkspaceSymbol);
+        if !prompt_open {
+            self.cancel_workspace_symbol_search();
+            return false;
+        }
+
+        let Some(search) = &mut self.workspace_symbol_search else {
+            return false;
+        };
+        let Some(edited_at) = search.edited_at else {
+            return false;
+        };
+        if self.time_source.elapsed_since(edited_at) < WORKSPACE_SYMBOL_DELAY {
+            return false;
+        }
+        search.edited_at = None;
+
+        let Some(lsp) = &self.lsp else {
+            return false;
+        };
+        lsp.cancel_requests(&search.requests);
+        search.requests =
+            lsp.request_workspace_symbols(&search.query, &mut self.next_lsp_request_id);
+        tracing::debug!(
+            "Sent workspace symbol query {:?} to {} server(s)",
+            search.query,
+            search.requests.len()
+        );
+        false
+    }
+
+    /// Handle one server's response to a workspace symbol request
+    pub(super) fn handle_lsp_workspace_symbols(
+        &mut self,
+        request_id: u64,
+        language: String,
+        symbols: Vec<WorkspaceSymbol>,
+    ) {
+        let Some(search) = &mut self.workspace_symbol_search else {
+            return;
+        };
+        let Some(index) = search
+            .requests
+            .iter()
+            .position(|(lang, id)| *id == request_id && *lang == language)
+        else {
+            tracing::debug!(
+                "Ignoring stale workspace symbol response (request_id={})",
+                request_id
+            );
+            return;
+        };
+        search.requests.remove(index);
+
+        tracing::debug!(
+            "Received {} workspace symbols from {} (request_id={})",
+            symbols.len(),
+            language,
+            request_id
+        );
+        search.results.insert(language, symbols);
+        self.refresh_workspace_symbol_suggestions();
+    }
+
+    /// Jump to the workspace symbol selected in the prompt
+    ///
+    /// Symbols reported without a range are resolved first
+    /// (`workspaceSymbol/resolve`) by the server that reported them.
+    pub(super) fn goto_workspace_symbol(&mut self, selected: Option<usize>) {
+        let Some(search) = self.workspace_symbol_search.take() else {
+            return;
+        };
+        if let Some(lsp) = &self.lsp {
+            lsp.cancel_requests(&search.requests);
+        }
+        let Some(symbol) = selected.and_then(|i| search.symbols.get(i)) else {
+            return;
+        };
+        if matches!(symbol.location, OneOf::Left(_)) {
+            self.goto_resolved_workspace_symbol(symbol);
+            return;
+        }
+
+        let language = search
+            .results
+            .iter()
+            .find(|(_, symbols)| symbols.contains(symbol))
+            .map(|(language, _)| language);
+        let request_id = self.next_lsp_request_id;
+        let sent = language
+            .and_then(|language| self.lsp.as_ref()?.get_handle(language))
+            .is_some_and(|handle| {
+                let result = handle.resolve_workspace_symbol(request_id, symbol.clone());
+                if let Err(e) = &result {
+                    tracing::debug!("Failed to resolve workspace symbol: {}", e);
+                }
+                result.is_ok()
+            });
+        if sent {
+            self.next_lsp_request_id += 1;
+            self.workspace_symbol_resolve = Some(request_id);
+        } else {
+            self.goto_resolved_workspace_symbol(symbol);
+        }
+    }
+
+    /// Handle the response to a workspace symbol resolve request
+    pub(super) fn handle_lsp_workspace_symbol_resolved(
+        &mut self,
+        request_id: u64,
+        symbol: WorkspaceSymbol,
+    ) {
+        if self.workspace_symbol_resolve != Some(request_id) {
+            return;
+        }
+        self.workspace_symbol_resolve = None;
+        self.goto_resolved_workspace_symbol(&symbol);
+    }
+
+    /// Open the file of a symbol at its range, or at its name if the server
+    /// didn't tell the range
+    fn goto_resolved_workspace_symbol(&mut self, symbol: &WorkspaceSymbol) {
+        let (uri, range) = match &symbol.location {
+            OneOf::Left(location) => (&location.uri, Some(location.range.start)),
+            OneOf::Right(location) => (&location.uri, None),
+        };
+        let Ok(path) = uri_to_path(uri) else {
+            self.set_status_message(t!("lsp.cannot_open_definition").to_string());
+            return;
+        };
+        if let Err(e) = self.open_file(&path) {
+            self.set_status_message(t!("file.error_opening", error = e.to_string()).to_string());
+            return;
+        }
+        let buffer = &self.active_state().buffer;
+        let position = match range {
+            Some(start) => {
+                buffer.lsp_position_to_byte(start.line as usize, start.character as usize)
+            }
+            None => buffer.find_next(&symbol.name, 0).unwrap_or(0),
+        };
+        self.goto_symbol_position(position);
+    }
+
+    /// Drop the current search and cancel its outstanding requests
+    fn cancel_workspace_symbol_search(&mut self) {
+        if let Some(search) = self.workspace_symbol_search.take() {
+            if let Some(lsp) = &self.lsp {
+                lsp.cancel_requests(&search.requests);
+            }
+        }
+    }
+
+    /// Rank the merged results against the query and show them in the prompt
+    fn refresh_workspace_symbol_suggestions(&mut self) {
+        let Some(search) = &mut self.workspace_symbol_search else {
+            return;
+        };
+
+        let mut languages: Vec<&String> = search.results.keys().collect();
+        languages.sort();
+        let merged = merge_workspace_symbols(
+            languages
+                .iter()
+                .map(|lang| search.results[*lang].as_slice()),
+        );
+
+        // Servers match loosely and may still hold results of an older query
+        let mut ranked: Vec<(WorkspaceSymbol, i32)> = merged
+            .into_iter()
+            .filter_map(|symbol| {
+                let result = fuzzy_match(&search.query, &symbol.name);
+                result.matched.then_some((symbol, result.score))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1));
+        search.symbols = ranked.into_iter().map(|(symbol, _)| symbol).collect();
+
+        let suggestions: Vec<Suggestion> = search
+            .symbols
+            .iter()
+            .enumerate()
+            .map(|(i, symbol)| Suggestion {
+                text: symbol.name.clone(),
+                description: Some(format!(
+                    "{} {}",
+                    kind_label(symbol.kind),
+                    symbol_location(symbol, &self.working_dir)
+                )),
+                value: Some(i.to_string()),
+                disabled: false,
+                keybinding: None,
+                source: None,
+            })
+            .collect();
+
+        if let Some(prompt) = &mut self.prompt {
+            if prompt.prompt_type == PromptType::WorkspaceSymbol {
+                prompt.selected_suggestion = (!suggestions.is_empty()).then_some(0);
+                prompt.suggestions = suggestions;
+            }
+        }
+    }
+}
+
+/// Location of a symbol as "path:line", relative to the working directory
+fn symbol_location(symbol: &WorkspaceSymbol, working_dir: &Path) -> String {
+    let (uri, line) = match &symbol.location {
+        OneOf::Left(location) => (&location.uri, Some(location.range.start.line + 1)),
+        OneOf::Right(location) => (&location.uri, None),
+    };
+    let path = match uri_to_path(uri) {
+        Ok(path) => path
+            .strip_prefix(working_dir)
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| path.display().to_string()),
+        Err(_) => uri.as_str().to_string(),
+    };
+    match line {
+        Some(line) => format!("{}:{}", path, line),
+        None => path,
+    }
+}
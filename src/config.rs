@@ -370,6 +370,15 @@ pub struct Config {
     /// Warning notification settings
     #[serde(default)]
     pub warnings: WarningsConfig,
+
+    /// Status bar segments
+    #[serde(default)]
+    pub status_bar: StatusBarConfig,
+
+    /// What plugins were allowed to do, by plugin name ("*" for all plugins)
+    /// Only read from the user config, never from a project's config
+    #[serde(default)]
+    pub plugin_permissions: HashMap<String, PluginPermissions>,
 }
 
 fn default_keybinding_map_name() -> KeybindingMapName {
@@ -439,10 +448,22 @@ pub struct EditorConfig {
     #[serde(default = "default_estimated_line_length")]
     pub estimated_line_length: usize,
 
+    /// Whether files detected as binary open in the hex view instead of as text
+    #[serde(default = "default_true")]
+    pub hex_view_binary_files: bool,
+
     /// Whether to enable LSP inlay hints (type hints, parameter hints, etc.)
     #[serde(default = "default_true")]
     pub enable_inlay_hints: bool,
 
+    /// Whether to color text with LSP semantic tokens on top of syntax highlighting
+    #[serde(default = "default_true")]
+    pub enable_semantic_tokens: bool,
+
+    /// Whether to show LSP code lenses (such as "Run test") above the lines they belong to
+    #[serde(default = "default_true")]
+    pub enable_code_lens: bool,
+
     /// Whether to enable file recovery (Emacs-style auto-save)
     /// When enabled, buffers are periodically saved to recovery files
     /// so they can be recovered if the editor crashes.
@@ -456,6 +477,23 @@ pub struct EditorConfig {
     #[serde(default = "default_auto_save_interval")]
     pub auto_save_interval_secs: u32,
 
+    /// Whether to keep each file's undo history when it is closed or the editor exits,
+    /// so its edits can still be undone after reopening it.
+    /// The history is dropped if the file is changed outside the editor.
+    #[serde(default = "default_true")]
+    pub persistent_undo: bool,
+
+    /// Maximum size in bytes of the undo history kept for each file.
+    /// The oldest edits are dropped to stay under it.
+    /// Default: 1MB
+    #[serde(default = "default_persistent_undo_max_bytes")]
+    pub persistent_undo_max_bytes: u64,
+
+    /// Number of days edits are kept in the persistent undo history.
+    /// Default: 30 days
+    #[serde(default = "default_persistent_undo_max_age_days")]
+    pub persistent_undo_max_age_days: u32,
+
     /// Number of bytes to look back/forward from the viewport for syntax highlighting context.
     /// Larger values improve accuracy for multi-line constructs (strings, comments, nested blocks)
     /// but may slow down highlighting for very large files.
@@ -475,21 +513,42 @@ pub struct EditorConfig {
     #[serde(default = "default_mouse_hover_delay")]
     pub mouse_hover_delay_ms: u64,
 
+    /// Whether to show the keys that can follow after the first key of a
+    /// multi-key binding (e.g. Ctrl+X in the Emacs keymap).
+    /// Default: true
+    #[serde(default = "default_true")]
+    pub which_key_enabled: bool,
+
+    /// Delay in milliseconds before the keys that can follow are shown.
+    /// Default: 1000ms
+    #[serde(default = "default_which_key_delay")]
+    pub which_key_delay_ms: u64,
+
     /// Time window in milliseconds for detecting double-clicks.
     /// Two clicks within this time are treated as a double-click (word selection).
     /// Default: 500ms
     #[serde(default = "default_double_click_time")]
     pub double_click_time_ms: u64,
 
+    /// Watch for file changes with operating system notifications (inotify on Linux)
+    /// instead of polling. Changes are then seen right away by auto-revert, the file
+    /// explorer and language servers. Directories on network filesystems, and those
+    /// that cannot be watched, are still polled.
+    /// Default: true
+    #[serde(default = "default_true")]
+    pub native_file_watching: bool,
+
     /// Poll interval in milliseconds for auto-reverting open buffers.
-    /// When auto-revert is enabled, file modification times are checked at this interval.
+    /// When auto-revert is enabled, file modification times of open files that are not
+    /// watched are checked at this interval.
     /// Lower values detect external changes faster but use more CPU.
     /// Default: 2000ms (2 seconds)
     #[serde(default = "default_auto_revert_poll_interval")]
     pub auto_revert_poll_interval_ms: u64,
 
     /// Poll interval in milliseconds for refreshing expanded directories in the file explorer.
-    /// Directory modification times are checked at this interval to detect new/deleted files.
+    /// Modification times of directories that are not watched are checked at this interval
+    /// to detect new/deleted files.
     /// Lower values detect changes faster but use more CPU.
     /// Default: 3000ms (3 seconds)
     #[serde(default = "default_file_tree_poll_interval")]
@@ -502,6 +561,14 @@ pub struct EditorConfig {
     #[serde(default)]
     pub default_line_ending: LineEndingOption,
 
+    /// Command used by "Save as Root" to write files the editor's user can't write.
+    /// "sudo" asks for the password in the editor; "doas" must not need one, and
+    /// "pkexec" asks through the desktop's authentication agent. Any other command
+    /// that runs its arguments as root also works.
+    /// Default: "sudo"
+    #[serde(default = "default_privileged_save_command")]
+    pub privileged_save_command: String,
+
     /// Cursor style for the terminal cursor.
     /// Options: blinking_block, steady_block, blinking_bar, steady_bar, blinking_underline, steady_underline
     /// Default: blinking_block
@@ -509,6 +576,10 @@ pub struct EditorConfig {
     pub cursor_style: CursorStyle,
 }
 
+fn default_privileged_save_command() -> String {
+    "sudo".to_string()
+}
+
 fn default_tab_size() -> usize {
     4
 }
@@ -550,6 +621,14 @@ fn default_auto_save_interval() -> u32 {
     2 // Auto-save every 2 seconds for fast recovery
 }
 
+fn default_persistent_undo_max_bytes() -> u64 {
+    1024 * 1024 // 1MB of history per file
+}
+
+fn default_persistent_undo_max_age_days() -> u32 {
+    30
+}
+
 fn default_highlight_context_bytes() -> usize {
     10_000 // 10KB context for accurate syntax highlighting
 }
@@ -558,6 +637,10 @@ fn default_mouse_hover_delay() -> u64 {
     500 // 500ms delay before showing hover info
 }
 
+fn default_which_key_delay() -> u64 {
+    1000 // 1s, so quickly typed chords never show it
+}
+
 fn default_double_click_time() -> u64 {
     500 // 500ms window for detecting double-clicks
 }
@@ -584,16 +667,26 @@ impl Default for EditorConfig {
             snapshot_interval: default_snapshot_interval(),
             large_file_threshold_bytes: default_large_file_threshold(),
             estimated_line_length: default_estimated_line_length(),
+            hex_view_binary_files: true,
             enable_inlay_hints: true,
+            enable_semantic_tokens: true,
+            enable_code_lens: true,
             recovery_enabled: true,
             auto_save_interval_secs: default_auto_save_interval(),
+            persistent_undo: true,
+            persistent_undo_max_bytes: default_persistent_undo_max_bytes(),
+            persistent_undo_max_age_days: default_persistent_undo_max_age_days(),
             highlight_context_bytes: default_highlight_context_bytes(),
             mouse_hover_enabled: true,
             mouse_hover_delay_ms: default_mouse_hover_delay(),
+            which_key_enabled: true,
+            which_key_delay_ms: default_which_key_delay(),
             double_click_time_ms: default_double_click_time(),
+            native_file_watching: true,
             auto_revert_poll_interval_ms: default_auto_revert_poll_interval(),
             file_tree_poll_interval_ms: default_file_tree_poll_interval(),
             default_line_ending: LineEndingOption::default(),
+            privileged_save_command: default_privileged_save_command(),
             cursor_style: CursorStyle::default(),
         }
     }
@@ -661,6 +754,103 @@ impl Default for WarningsConfig {
     }
 }
 
+/// Status bar configuration
+///
+/// Each side lists the segments shown there, in order. Segments with nothing
+/// to show (e.g. the cursor count with a single cursor) are left out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct StatusBarConfig {
+    /// Segments on the left, separated by " | "
+    #[serde(default = "default_status_bar_left")]
+    pub left: Vec<StatusBarSegment>,
+
+    /// Segments centered in the space left between the two sides
+    #[serde(default)]
+    pub center: Vec<StatusBarSegment>,
+
+    /// Segments on the right
+    #[serde(default = "default_status_bar_right")]
+    pub right: Vec<StatusBarSegment>,
+}
+
+fn default_status_bar_left() -> Vec<StatusBarSegment> {
+    vec![
+        StatusBarSegment::new("file"),
+        StatusBarSegment::new("position"),
+        StatusBarSegment::new("diagnostics"),
+        StatusBarSegment::new("cursors"),
+        StatusBarSegment::new("conflicts"),
+        StatusBarSegment::new("plugins"),
+        StatusBarSegment::new("chord"),
+        StatusBarSegment::new("messages"),
+    ]
+}
+
+fn default_status_bar_right() -> Vec<StatusBarSegment> {
+    vec![
+        StatusBarSegment::new("encoding"),
+        StatusBarSegment::new("line_ending"),
+        StatusBarSegment::new("lsp"),
+        StatusBarSegment::new("warnings"),
+        StatusBarSegment::new("update"),
+        StatusBarSegment::new("palette"),
+    ]
+}
+
+impl Default for StatusBarConfig {
+    fn default() -> Self {
+        Self {
+            left: default_status_bar_left(),
+            center: Vec::new(),
+            right: default_status_bar_right(),
+        }
+    }
+}
+
+/// A segment of the status bar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(extend("x-display-field" = "/segment"))]
+pub struct StatusBarSegment {
+    /// What the segment shows: "file", "position", "selection", "diagnostics",
+    /// "cursors", "conflicts", "chord", "messages", "line_ending", "encoding",
+    /// "language", "lsp", "git_branch", "macro", "clock", "warnings", "update",
+    /// "palette", "plugins" (the plugin segments not placed elsewhere), or the
+    /// name of a segment set by a plugin
+    pub segment: String,
+
+    /// Text of the segment, with `{name}` replaced by the values of the
+    /// segment (e.g. "Ln {line}, Col {col}"). For "clock" this is a strftime
+    /// format (default "%H:%M"). Default: the built-in text of the segment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+
+    /// Text put before the segment on the left side (default " | ", or " "
+    /// before "chord")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+
+    /// Theme key of the text color (e.g. "diagnostic.error_fg")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fg: Option<String>,
+
+    /// Theme key of the background color (e.g. "ui.status_bar_bg")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bg: Option<String>,
+}
+
+impl StatusBarSegment {
+    /// A segment with its built-in text and colors
+    pub fn new(segment: &str) -> Self {
+        Self {
+            segment: segment.to_string(),
+            format: None,
+            separator: None,
+            fg: None,
+            bg: None,
+        }
+    }
+}
+
 impl Default for FileExplorerConfig {
     fn default() -> Self {
         Self {
@@ -687,6 +877,33 @@ impl Default for FileBrowserConfig {
     }
 }
 
+/// What a plugin was allowed to do
+///
+/// The lists hold entries of the plugin's manifest (`<plugin>.manifest.json`)
+/// that the user allowed when the plugin first needed them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PluginPermissions {
+    /// Allow everything without asking, whether declared in a manifest or not
+    #[serde(default)]
+    pub trusted: bool,
+
+    /// Directories the plugin may read (e.g. "${workspace}")
+    #[serde(default)]
+    pub fs_read: Vec<String>,
+
+    /// Directories the plugin may write
+    #[serde(default)]
+    pub fs_write: Vec<String>,
+
+    /// Programs the plugin may run
+    #[serde(default)]
+    pub process: Vec<String>,
+
+    /// Environment variables the plugin may read
+    #[serde(default)]
+    pub env: Vec<String>,
+}
+
 /// A single key in a sequence
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct KeyPress {
@@ -721,7 +938,8 @@ pub struct Keybinding {
     #[serde(default)]
     pub args: HashMap<String, serde_json::Value>,
 
-    /// Optional condition (e.g., "mode == insert")
+    /// Optional condition: a key context (e.g., "prompt") and/or an expression
+    /// over context keys (e.g., "editorHasSelection && editorLangId == rust")
     #[serde(default)]
     pub when: Option<String>,
 }
@@ -739,11 +957,28 @@ pub struct KeymapConfig {
     pub bindings: Vec<Keybinding>,
 }
 
+/// Where a formatter gets the formatted text from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FormatterSource {
+    /// Run `command` and replace the buffer with its output
+    #[default]
+    Command,
+    /// Ask the language server (textDocument/formatting)
+    Lsp,
+}
+
 /// Formatter configuration for a language
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[schemars(extend("x-display-field" = "/command"))]
 pub struct FormatterConfig {
+    /// Where formatting comes from: "command" (default) or "lsp"
+    #[serde(default)]
+    pub source: FormatterSource,
+
     /// The formatter command to run (e.g., "rustfmt", "prettier")
+    /// Not used when `source` is "lsp"
+    #[serde(default)]
     pub command: String,
 
     /// Arguments to pass to the formatter
@@ -759,6 +994,25 @@ pub struct FormatterConfig {
     /// Timeout in milliseconds (default: 10000)
     #[serde(default = "default_on_save_timeout")]
     pub timeout_ms: u64,
+
+    /// Reformat while typing using the language server's trigger characters
+    /// (textDocument/onTypeFormatting), regardless of `source`
+    #[serde(default)]
+    pub on_type: bool,
+}
+
+impl FormatterConfig {
+    /// A formatter backed by the language server
+    pub fn lsp() -> Self {
+        Self {
+            source: FormatterSource::Lsp,
+            command: String::new(),
+            args: Vec::new(),
+            stdin: true,
+            timeout_ms: default_on_save_timeout(),
+            on_type: false,
+        }
+    }
 }
 
 /// Action to run when a file is saved (for linters, etc.)
@@ -1113,6 +1367,8 @@ impl Default for Config {
             languages: Self::default_languages(),
             lsp: Self::default_lsp_config(),
             warnings: WarningsConfig::default(),
+            status_bar: StatusBarConfig::default(),
+            plugin_permissions: HashMap::new(),
         }
     }
 }
@@ -1304,6 +1560,13 @@ impl MenuConfig {
                         when: Some(context_keys::FORMATTER_AVAILABLE.to_string()),
                         checkbox: None,
                     },
+                    MenuItem::Action {
+                        label: t!("menu.edit.format_selection").to_string(),
+                        action: "format_selection".to_string(),
+                        args: HashMap::new(),
+                        when: Some(context_keys::HAS_SELECTION.to_string()),
+                        checkbox: None,
+                    },
                 ],
             },
             // View menu
@@ -1435,6 +1698,46 @@ impl MenuConfig {
                         checkbox: None,
                     },
                     MenuItem::Separator { separator: true },
+                    MenuItem::Action {
+                        label: t!("menu.view.outline").to_string(),
+                        action: "toggle_outline".to_string(),
+                        args: HashMap::new(),
+                        when: None,
+                        checkbox: None,
+                    },
+                    MenuItem::Submenu {
+                        label: t!("menu.view.folding").to_string(),
+                        items: vec![
+                            MenuItem::Action {
+                                label: t!("action.fold").to_string(),
+                                action: "fold".to_string(),
+                                args: HashMap::new(),
+                                when: None,
+                                checkbox: None,
+                            },
+                            MenuItem::Action {
+                                label: t!("action.unfold").to_string(),
+                                action: "unfold".to_string(),
+                                args: HashMap::new(),
+                                when: None,
+                                checkbox: None,
+                            },
+                            MenuItem::Action {
+                                label: t!("action.fold_all").to_string(),
+                                action: "fold_all".to_string(),
+                                args: HashMap::new(),
+                                when: None,
+                                checkbox: None,
+                            },
+                            MenuItem::Action {
+                                label: t!("action.unfold_all").to_string(),
+                                action: "unfold_all".to_string(),
+                                args: HashMap::new(),
+                                when: None,
+                                checkbox: None,
+                            },
+                        ],
+                    },
                     MenuItem::Submenu {
                         label: t!("menu.terminal").to_string(),
                         items: vec![
@@ -1579,6 +1882,20 @@ impl MenuConfig {
                         when: None,
                         checkbox: None,
                     },
+                    MenuItem::Action {
+                        label: t!("menu.go.goto_symbol").to_string(),
+                        action: "goto_symbol".to_string(),
+                        args: HashMap::new(),
+                        when: None,
+                        checkbox: None,
+                    },
+                    MenuItem::Action {
+                        label: t!("menu.go.workspace_symbol").to_string(),
+                        action: "workspace_symbol".to_string(),
+                        args: HashMap::new(),
+                        when: None,
+                        checkbox: None,
+                    },
                     MenuItem::Action {
                         label: t!("menu.go.goto_definition").to_string(),
                         action: "lsp_goto_definition".to_string(),
@@ -1644,6 +1961,20 @@ impl MenuConfig {
                         when: Some(context_keys::LSP_AVAILABLE.to_string()),
                         checkbox: None,
                     },
+                    MenuItem::Action {
+                        label: t!("menu.lsp.call_hierarchy").to_string(),
+                        action: "call_hierarchy".to_string(),
+                        args: HashMap::new(),
+                        when: Some(context_keys::LSP_AVAILABLE.to_string()),
+                        checkbox: None,
+                    },
+                    MenuItem::Action {
+                        label: t!("menu.lsp.type_hierarchy").to_string(),
+                        action: "type_hierarchy".to_string(),
+                        args: HashMap::new(),
+                        when: Some(context_keys::LSP_AVAILABLE.to_string()),
+                        checkbox: None,
+                    },
                     MenuItem::Action {
                         label: t!("menu.lsp.rename_symbol").to_string(),
                         action: "lsp_rename".to_string(),
@@ -1673,6 +2004,13 @@ impl MenuConfig {
                         when: Some(context_keys::LSP_AVAILABLE.to_string()),
                         checkbox: None,
                     },
+                    MenuItem::Action {
+                        label: t!("menu.lsp.code_lens").to_string(),
+                        action: "lsp_code_lens".to_string(),
+                        args: HashMap::new(),
+                        when: Some(context_keys::LSP_AVAILABLE.to_string()),
+                        checkbox: None,
+                    },
                     MenuItem::Separator { separator: true },
                     MenuItem::Action {
                         label: t!("menu.lsp.toggle_inlay_hints").to_string(),
@@ -1908,10 +2246,12 @@ impl Config {
                 use_tabs: false,
                 tab_size: None,
                 formatter: Some(FormatterConfig {
+                    source: FormatterSource::Command,
                     command: "rustfmt".to_string(),
                     args: vec!["--edition".to_string(), "2021".to_string()],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_type: false,
                 }),
                 format_on_save: false,
                 on_save: vec![],
@@ -1932,10 +2272,12 @@ impl Config {
                 use_tabs: false,
                 tab_size: None,
                 formatter: Some(FormatterConfig {
+                    source: FormatterSource::Command,
                     command: "prettier".to_string(),
                     args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_type: false,
                 }),
                 format_on_save: false,
                 on_save: vec![],
@@ -1956,10 +2298,12 @@ impl Config {
                 use_tabs: false,
                 tab_size: None,
                 formatter: Some(FormatterConfig {
+                    source: FormatterSource::Command,
                     command: "prettier".to_string(),
                     args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_type: false,
                 }),
                 format_on_save: false,
                 on_save: vec![],
@@ -1980,6 +2324,7 @@ impl Config {
                 use_tabs: false,
                 tab_size: None,
                 formatter: Some(FormatterConfig {
+                    source: FormatterSource::Command,
                     command: "ruff".to_string(),
                     args: vec![
                         "format".to_string(),
@@ -1988,6 +2333,7 @@ impl Config {
                     ],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_type: false,
                 }),
                 format_on_save: false,
                 on_save: vec![],
@@ -2008,10 +2354,12 @@ impl Config {
                 use_tabs: false,
                 tab_size: None,
                 formatter: Some(FormatterConfig {
+                    source: FormatterSource::Command,
                     command: "clang-format".to_string(),
                     args: vec![],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_type: false,
                 }),
                 format_on_save: false,
                 on_save: vec![],
@@ -2039,10 +2387,12 @@ impl Config {
                 use_tabs: false,
                 tab_size: None,
                 formatter: Some(FormatterConfig {
+                    source: FormatterSource::Command,
                     command: "clang-format".to_string(),
                     args: vec![],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_type: false,
                 }),
                 format_on_save: false,
                 on_save: vec![],
@@ -2062,7 +2412,7 @@ impl Config {
                 show_whitespace_tabs: true,
                 use_tabs: false,
                 tab_size: None,
-                formatter: None,
+                formatter: Some(FormatterConfig::lsp()),
                 format_on_save: false,
                 on_save: vec![],
             },
@@ -2158,10 +2508,12 @@ impl Config {
                 use_tabs: false,
                 tab_size: None,
                 formatter: Some(FormatterConfig {
+                    source: FormatterSource::Command,
                     command: "prettier".to_string(),
                     args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_type: false,
                 }),
                 format_on_save: false,
                 on_save: vec![],
@@ -2201,10 +2553,12 @@ impl Config {
                 use_tabs: false,
                 tab_size: None,
                 formatter: Some(FormatterConfig {
+                    source: FormatterSource::Command,
                     command: "prettier".to_string(),
                     args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_type: false,
                 }),
                 format_on_save: false,
                 on_save: vec![],
@@ -2245,10 +2599,12 @@ impl Config {
                 use_tabs: true,    // Go convention is to use tabs
                 tab_size: Some(8), // Go convention is 8-space tab width
                 formatter: Some(FormatterConfig {
+                    source: FormatterSource::Command,
                     command: "gofmt".to_string(),
                     args: vec![],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_type: false,
                 }),
                 format_on_save: false,
                 on_save: vec![],
@@ -2727,10 +3083,12 @@ mod tests {
                 use_tabs: true,              // Go uses tabs
                 tab_size: Some(8),           // Go uses 8-space tabs
                 formatter: Some(FormatterConfig {
+                    source: FormatterSource::Command,
                     command: "gofmt".to_string(),
                     args: vec![],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_type: false,
                 }),
                 format_on_save: true,
                 on_save: vec![],
@@ -166,10 +166,13 @@ impl ConfigResolver {
     pub fn resolve(&self) -> Result<Config, ConfigError> {
         // Start with highest precedence layer (Session)
         let mut merged = self.load_session_layer()?.unwrap_or_default();
+        // A project must not grant its own plugins permissions
+        merged.plugin_permissions = None;
 
         // Merge in Project layer (fills missing values)
-        if let Some(project_partial) = self.load_project_layer()? {
+        if let Some(mut project_partial) = self.load_project_layer()? {
             tracing::debug!("Loaded project config layer");
+            project_partial.plugin_permissions = None;
             merged.merge_from(&project_partial);
         }
 
@@ -725,6 +728,11 @@ impl DirectoryContext {
         self.data_dir.join("sessions")
     }
 
+    /// Get the persistent undo history directory path
+    pub fn undo_dir(&self) -> std::path::PathBuf {
+        self.data_dir.join("undo")
+    }
+
     /// Get the search history file path
     pub fn search_history_path(&self) -> std::path::PathBuf {
         self.data_dir.join("search_history.json")
@@ -761,10 +769,20 @@ impl DirectoryContext {
         self.config_dir.join("grammars")
     }
 
+    /// Get the saved keyboard macros file path
+    pub fn macros_path(&self) -> std::path::PathBuf {
+        self.config_dir.join("macros.json")
+    }
+
     /// Get the plugins directory path
     pub fn plugins_dir(&self) -> std::path::PathBuf {
         self.config_dir.join("plugins")
     }
+
+    /// Get the user snippets directory path
+    pub fn snippets_dir(&self) -> std::path::PathBuf {
+        self.config_dir.join("snippets")
+    }
 }
 
 #[cfg(test)]
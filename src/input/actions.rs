@@ -2086,6 +2086,7 @@ pub fn action_to_events(
         Action::Quit
         | Action::Save
         | Action::SaveAs
+        | Action::SaveAsRoot
         | Action::Open
         | Action::SwitchProject
         | Action::New
@@ -2119,11 +2120,28 @@ pub fn action_to_events(
         | Action::ToggleLineWrap
         | Action::ToggleComposeMode
         | Action::SetComposeWidth
+        | Action::ToggleHexView
         | Action::IncreaseSplitSize
         | Action::DecreaseSplitSize
         | Action::ToggleMaximizeSplit
         | Action::Undo
         | Action::Redo
+        | Action::UndoEarlier
+        | Action::UndoLater
+        | Action::UndoHistory
+        | Action::UndoHistoryJump
+        | Action::CloseUndoHistory
+        | Action::CompareWithFile
+        | Action::CompareWithSaved
+        | Action::DiffNextHunk
+        | Action::DiffPrevHunk
+        | Action::DiffTakeLeft
+        | Action::DiffTakeRight
+        | Action::MergeNextConflict
+        | Action::MergePrevConflict
+        | Action::MergeAcceptLocal
+        | Action::MergeAcceptRemote
+        | Action::MergeAcceptBoth
         | Action::GoToMatchingBracket
         | Action::JumpToNextError
         | Action::JumpToPreviousError
@@ -2133,6 +2151,21 @@ pub fn action_to_events(
         | Action::ClearWarnings
         | Action::SmartHome
         | Action::ToggleComment
+        | Action::InsertSnippet
+        | Action::Fold
+        | Action::Unfold
+        | Action::FoldAll
+        | Action::UnfoldAll
+        | Action::GotoSymbol
+        | Action::WorkspaceSymbol
+        | Action::ToggleOutline
+        | Action::OutlineJump
+        | Action::CallHierarchy
+        | Action::TypeHierarchy
+        | Action::HierarchyToggle
+        | Action::HierarchyJump
+        | Action::HierarchyFlipDirection
+        | Action::CloseHierarchy
         | Action::SetBookmark(_)
         | Action::JumpToBookmark(_)
         | Action::ClearBookmark(_)
@@ -2144,11 +2177,15 @@ pub fn action_to_events(
         | Action::StartMacroRecording
         | Action::StopMacroRecording
         | Action::PlayMacro(_)
+        | Action::PlayNamedMacro(_)
         | Action::ToggleMacroRecording(_)
         | Action::ShowMacro(_)
         | Action::ListMacros
         | Action::PromptRecordMacro
         | Action::PromptPlayMacro
+        | Action::PromptEditMacro
+        | Action::PromptPlayMacroOnLines
+        | Action::PromptPlayMacroTimes
         | Action::PlayLastMacro
         | Action::PromptSetBookmark
         | Action::PromptJumpToBookmark
@@ -2214,6 +2251,7 @@ pub fn action_to_events(
         | Action::LspHover
         | Action::LspSignatureHelp
         | Action::LspCodeActions
+        | Action::LspCodeLens
         | Action::LspRestart
         | Action::LspStop
         | Action::ToggleInlayHints
@@ -2249,6 +2287,7 @@ pub fn action_to_events(
         | Action::Revert
         | Action::ToggleAutoRevert
         | Action::FormatBuffer
+        | Action::FormatSelection
         | Action::OpenTerminal
         | Action::CloseTerminal
         | Action::FocusTerminal
@@ -2267,6 +2306,8 @@ pub fn action_to_events(
         | Action::SettingsDecrement
         | Action::SetTabSize
         | Action::SetLineEnding
+        | Action::ReopenWithEncoding
+        | Action::SaveWithEncoding
         | Action::ToggleIndentationStyle
         | Action::ToggleTabIndicators
         | Action::ToggleDebugHighlights
@@ -2366,9 +2407,63 @@ pub fn action_to_events(
         }
     }
 
+    // Cursors never come to rest inside folded lines
+    if !state.folds.is_empty() {
+        skip_folded_lines(state, &mut events, estimated_line_length);
+    }
+
     Some(events)
 }
 
+/// Move cursor destinations out of folded lines.
+///
+/// A cursor moving forward into a fold continues on the first line after it,
+/// one moving backward lands on the fold header. The visual column is kept
+/// where the target line is long enough.
+fn skip_folded_lines(state: &mut EditorState, events: &mut [Event], estimated_line_length: usize) {
+    let hidden = state.folds.hidden_ranges(&state.marker_list);
+    let is_hidden = |pos: usize| hidden.iter().find(|r| r.contains(&pos)).cloned();
+
+    for event in events.iter_mut() {
+        let Event::MoveCursor {
+            old_position,
+            new_position,
+            ..
+        } = event
+        else {
+            continue;
+        };
+        let Some(range) = is_hidden(*new_position) else {
+            continue;
+        };
+        let (visual_column, _) =
+            calculate_visual_column(&mut state.buffer, *new_position, estimated_line_length);
+
+        let mut position_on_line = |byte_in_line: usize| {
+            let mut iter = state
+                .buffer
+                .line_iterator(byte_in_line, estimated_line_length);
+            let line_start = iter.current_position();
+            let text = iter.next().map(|(_, content)| content).unwrap_or_default();
+            let text = &text[..content_len_without_line_ending(&text)];
+            line_start + byte_offset_at_visual_column(text, visual_column)
+        };
+
+        let header = position_on_line(range.start.saturating_sub(1));
+        *new_position = if *new_position >= *old_position {
+            // Nothing visible after a fold that runs to the end of the buffer
+            let after = position_on_line(range.end);
+            if is_hidden(after).is_some() {
+                header
+            } else {
+                after
+            }
+        } else {
+            header
+        };
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,6 +106,67 @@ impl ModeRegistry {
 
         registry.register(special_mode);
 
+        // Built-in "outline" mode for the outline panel
+        let outline_mode = BufferMode::new("outline")
+            .with_parent("special")
+            .with_read_only(true)
+            .with_binding(KeyCode::Enter, KeyModifiers::NONE, "outline_jump")
+            .with_binding(KeyCode::Char('q'), KeyModifiers::NONE, "toggle_outline");
+
+        registry.register(outline_mode);
+
+        // Built-in "undo-history" mode for the undo history panel and its diff
+        let undo_history_mode = BufferMode::new("undo-history")
+            .with_parent("special")
+            .with_read_only(true)
+            .with_binding(KeyCode::Enter, KeyModifiers::NONE, "undo_history_jump")
+            .with_binding(KeyCode::Char('q'), KeyModifiers::NONE, "close_undo_history");
+
+        registry.register(undo_history_mode);
+
+        // Built-in "hierarchy" mode for the call/type hierarchy panel
+        let hierarchy_mode = BufferMode::new("hierarchy")
+            .with_parent("special")
+            .with_read_only(true)
+            .with_binding(KeyCode::Enter, KeyModifiers::NONE, "hierarchy_jump")
+            .with_binding(KeyCode::Tab, KeyModifiers::NONE, "hierarchy_toggle")
+            .with_binding(KeyCode::Char(' '), KeyModifiers::NONE, "hierarchy_toggle")
+            .with_binding(
+                KeyCode::Char('d'),
+                KeyModifiers::NONE,
+                "hierarchy_flip_direction",
+            )
+            .with_binding(KeyCode::Char('q'), KeyModifiers::NONE, "close_hierarchy");
+
+        registry.register(hierarchy_mode);
+
+        // Built-in "diff" mode for diff views, which can be typed in
+        let diff_mode = BufferMode::new("diff")
+            .with_binding(KeyCode::Char('n'), KeyModifiers::ALT, "diff_next_hunk")
+            .with_binding(KeyCode::Char('p'), KeyModifiers::ALT, "diff_prev_hunk")
+            .with_binding(KeyCode::Left, KeyModifiers::ALT, "diff_take_left")
+            .with_binding(KeyCode::Right, KeyModifiers::ALT, "diff_take_right");
+
+        registry.register(diff_mode);
+
+        // Built-in "merge" mode for merge views, whose result can be typed in
+        let merge_mode = BufferMode::new("merge")
+            .with_binding(
+                KeyCode::Char('n'),
+                KeyModifiers::ALT,
+                "merge_view_next_conflict",
+            )
+            .with_binding(
+                KeyCode::Char('p'),
+                KeyModifiers::ALT,
+                "merge_view_prev_conflict",
+            )
+            .with_binding(KeyCode::Left, KeyModifiers::ALT, "merge_accept_local")
+            .with_binding(KeyCode::Right, KeyModifiers::ALT, "merge_accept_remote")
+            .with_binding(KeyCode::Char('b'), KeyModifiers::ALT, "merge_accept_both");
+
+        registry.register(merge_mode);
+
         registry
     }
 
@@ -280,6 +341,50 @@ impl ModeRegistry {
         None
     }
 
+    /// Keys that can follow a pending chord prefix in a mode
+    ///
+    /// Each key comes with the command it completes the chord with, or None
+    /// if more keys follow it. Bindings of a mode override its parents'.
+    pub fn chord_continuations(
+        &self,
+        mode_name: &str,
+        chord_state: &[(KeyCode, KeyModifiers)],
+    ) -> Vec<((KeyCode, KeyModifiers), Option<String>)> {
+        let prefix: Vec<(KeyCode, KeyModifiers)> = chord_state
+            .iter()
+            .map(|(c, m)| Self::normalize_key(*c, *m))
+            .collect();
+
+        let mut continuations: Vec<((KeyCode, KeyModifiers), Option<String>)> = Vec::new();
+        let mut current_mode_name = Some(mode_name);
+
+        // Walk up the inheritance chain
+        while let Some(name) = current_mode_name {
+            let Some(mode) = self.modes.get(name) else {
+                break;
+            };
+            for (chord_seq, command) in &mode.chord_keybindings {
+                if chord_seq.len() <= prefix.len() || !chord_seq.starts_with(&prefix) {
+                    continue;
+                }
+                let key = chord_seq[prefix.len()];
+                let completes = (chord_seq.len() == prefix.len() + 1).then(|| command.clone());
+                match continuations.iter_mut().find(|(k, _)| *k == key) {
+                    Some(existing) if existing.1.is_none() && completes.is_some() => {
+                        existing.1 = completes;
+                    }
+                    Some(_) => {}
+                    None => continuations.push((key, completes)),
+                }
+            }
+            current_mode_name = mode.parent.as_deref();
+        }
+
+        continuations
+            .sort_by_key(|((code, modifiers), _)| (modifiers.bits(), format!("{:?}", code)));
+        continuations
+    }
+
     /// List all registered mode names
     pub fn list_modes(&self) -> Vec<String> {
         self.modes.keys().cloned().collect()
@@ -432,4 +537,39 @@ mod tests {
             Some(&"child:action".to_string())
         );
     }
+
+    #[test]
+    fn test_chord_continuations() {
+        let mut registry = ModeRegistry::new();
+        let g = (KeyCode::Char('g'), KeyModifiers::NONE);
+        let key = |c| (KeyCode::Char(c), KeyModifiers::NONE);
+
+        registry.register(
+            BufferMode::new("vi-base")
+                .with_chord_binding(vec![g, key('g')], "move_document_start")
+                .with_chord_binding(vec![g, key('u')], "lowercase"),
+        );
+        registry.register(
+            BufferMode::new("vi-normal")
+                .with_parent("vi-base")
+                .with_chord_binding(vec![g, key('u')], "vi_lowercase")
+                .with_chord_binding(vec![g, key('z'), key('z')], "center"),
+        );
+
+        assert_eq!(
+            registry.chord_continuations("vi-normal", &[g]),
+            vec![
+                (key('g'), Some("move_document_start".to_string())),
+                (key('u'), Some("vi_lowercase".to_string())),
+                (key('z'), None),
+            ]
+        );
+        assert_eq!(
+            registry.chord_continuations("vi-normal", &[g, key('z')]),
+            vec![(key('z'), Some("center".to_string()))]
+        );
+        assert!(registry
+            .chord_continuations("vi-normal", &[key('x')])
+            .is_empty());
+    }
 }
@@ -189,6 +189,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.save_file_as_root").to_string(),
+            description: t!("cmd.save_file_as_root_desc").to_string(),
+            action: Action::SaveAsRoot,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.new_file").to_string(),
             description: t!("cmd.new_file_desc").to_string(),
@@ -237,6 +245,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.format_selection").to_string(),
+            description: t!("cmd.format_selection_desc").to_string(),
+            action: Action::FormatSelection,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.quit").to_string(),
             description: t!("cmd.quit_desc").to_string(),
@@ -262,6 +278,119 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.undo_history").to_string(),
+            description: t!("cmd.undo_history_desc").to_string(),
+            action: Action::UndoHistory,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.undo_earlier").to_string(),
+            description: t!("cmd.undo_earlier_desc").to_string(),
+            action: Action::UndoEarlier,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.undo_later").to_string(),
+            description: t!("cmd.undo_later_desc").to_string(),
+            action: Action::UndoLater,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        // Diff view
+        Command {
+            name: t!("cmd.compare_with_file").to_string(),
+            description: t!("cmd.compare_with_file_desc").to_string(),
+            action: Action::CompareWithFile,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.compare_with_saved").to_string(),
+            description: t!("cmd.compare_with_saved_desc").to_string(),
+            action: Action::CompareWithSaved,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.diff_next_hunk").to_string(),
+            description: t!("cmd.diff_next_hunk_desc").to_string(),
+            action: Action::DiffNextHunk,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.diff_prev_hunk").to_string(),
+            description: t!("cmd.diff_prev_hunk_desc").to_string(),
+            action: Action::DiffPrevHunk,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.diff_take_left").to_string(),
+            description: t!("cmd.diff_take_left_desc").to_string(),
+            action: Action::DiffTakeLeft,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.diff_take_right").to_string(),
+            description: t!("cmd.diff_take_right_desc").to_string(),
+            action: Action::DiffTakeRight,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.merge_next_conflict").to_string(),
+            description: t!("cmd.merge_next_conflict_desc").to_string(),
+            action: Action::MergeNextConflict,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.merge_prev_conflict").to_string(),
+            description: t!("cmd.merge_prev_conflict_desc").to_string(),
+            action: Action::MergePrevConflict,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.merge_accept_local").to_string(),
+            description: t!("cmd.merge_accept_local_desc").to_string(),
+            action: Action::MergeAcceptLocal,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.merge_accept_remote").to_string(),
+            description: t!("cmd.merge_accept_remote_desc").to_string(),
+            action: Action::MergeAcceptRemote,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.merge_accept_both").to_string(),
+            description: t!("cmd.merge_accept_both_desc").to_string(),
+            action: Action::MergeAcceptBoth,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.copy").to_string(),
             description: t!("cmd.copy_desc").to_string(),
@@ -572,6 +701,22 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.reopen_with_encoding").to_string(),
+            description: t!("cmd.reopen_with_encoding_desc").to_string(),
+            action: Action::ReopenWithEncoding,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.save_with_encoding").to_string(),
+            description: t!("cmd.save_with_encoding_desc").to_string(),
+            action: Action::SaveWithEncoding,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.toggle_indentation").to_string(),
             description: t!("cmd.toggle_indentation_desc").to_string(),
@@ -742,6 +887,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.toggle_hex_view").to_string(),
+            description: t!("cmd.toggle_hex_view_desc").to_string(),
+            action: Action::ToggleHexView,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // Note: Compose mode commands removed - markdown_compose plugin provides these
         Command {
             name: t!("cmd.set_background").to_string(),
@@ -875,6 +1028,22 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.call_hierarchy").to_string(),
+            description: t!("cmd.call_hierarchy_desc").to_string(),
+            action: Action::CallHierarchy,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.type_hierarchy").to_string(),
+            description: t!("cmd.type_hierarchy_desc").to_string(),
+            action: Action::TypeHierarchy,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.show_signature_help").to_string(),
             description: t!("cmd.show_signature_help_desc").to_string(),
@@ -891,6 +1060,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.code_lens").to_string(),
+            description: t!("cmd.code_lens_desc").to_string(),
+            action: Action::LspCodeLens,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.start_restart_lsp").to_string(),
             description: t!("cmd.start_restart_lsp_desc").to_string(),
@@ -948,6 +1125,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.insert_snippet").to_string(),
+            description: t!("cmd.insert_snippet_desc").to_string(),
+            action: Action::InsertSnippet,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.goto_matching_bracket").to_string(),
             description: t!("cmd.goto_matching_bracket_desc").to_string(),
@@ -956,6 +1141,64 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        // Folding
+        Command {
+            name: t!("cmd.fold").to_string(),
+            description: t!("cmd.fold_desc").to_string(),
+            action: Action::Fold,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.unfold").to_string(),
+            description: t!("cmd.unfold_desc").to_string(),
+            action: Action::Unfold,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.fold_all").to_string(),
+            description: t!("cmd.fold_all_desc").to_string(),
+            action: Action::FoldAll,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.unfold_all").to_string(),
+            description: t!("cmd.unfold_all_desc").to_string(),
+            action: Action::UnfoldAll,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        // Outline
+        Command {
+            name: t!("cmd.goto_symbol").to_string(),
+            description: t!("cmd.goto_symbol_desc").to_string(),
+            action: Action::GotoSymbol,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.workspace_symbol").to_string(),
+            description: t!("cmd.workspace_symbol_desc").to_string(),
+            action: Action::WorkspaceSymbol,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.toggle_outline").to_string(),
+            description: t!("cmd.toggle_outline_desc").to_string(),
+            action: Action::ToggleOutline,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // Error navigation
         Command {
             name: t!("cmd.jump_to_next_error").to_string(),
@@ -1031,6 +1274,30 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.play_macro_on_lines").to_string(),
+            description: t!("cmd.play_macro_on_lines_desc").to_string(),
+            action: Action::PromptPlayMacroOnLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.play_macro_times").to_string(),
+            description: t!("cmd.play_macro_times_desc").to_string(),
+            action: Action::PromptPlayMacroTimes,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.edit_macro").to_string(),
+            description: t!("cmd.edit_macro_desc").to_string(),
+            action: Action::PromptEditMacro,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.set_bookmark").to_string(),
             description: t!("cmd.set_bookmark_desc").to_string(),
@@ -1,3 +1,4 @@
+use super::when_clause::{WhenClause, WhenContext};
 use crate::config::Config;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use rust_i18n::t;
@@ -288,6 +289,7 @@ pub enum Action {
     // File operations
     Save,
     SaveAs,
+    SaveAsRoot,
     Open,
     SwitchProject,
     New,
@@ -297,9 +299,12 @@ pub enum Action {
     Revert,
     ToggleAutoRevert,
     FormatBuffer,
+    FormatSelection,
 
     // Navigation
     GotoLine,
+    GotoSymbol,
+    WorkspaceSymbol,
     GoToMatchingBracket,
     JumpToNextError,
     JumpToPreviousError,
@@ -308,6 +313,23 @@ pub enum Action {
     SmartHome,
     DedentSelection,
     ToggleComment,
+    InsertSnippet,
+
+    // Folding
+    Fold,
+    Unfold,
+    FoldAll,
+    UnfoldAll,
+
+    // Outline
+    ToggleOutline,
+    OutlineJump,
+    CallHierarchy,
+    TypeHierarchy,
+    HierarchyToggle,
+    HierarchyJump,
+    HierarchyFlipDirection,
+    CloseHierarchy,
 
     // Bookmarks
     SetBookmark(char),
@@ -325,11 +347,15 @@ pub enum Action {
     StartMacroRecording,
     StopMacroRecording,
     PlayMacro(char),
+    PlayNamedMacro(String),
     ToggleMacroRecording(char),
     ShowMacro(char),
     ListMacros,
     PromptRecordMacro,
     PromptPlayMacro,
+    PromptEditMacro,
+    PromptPlayMacroOnLines,
+    PromptPlayMacroTimes,
     PlayLastMacro,
 
     // Bookmarks (prompt-based)
@@ -339,6 +365,26 @@ pub enum Action {
     // Undo/redo
     Undo,
     Redo,
+    UndoEarlier,
+    UndoLater,
+    UndoHistory,
+    UndoHistoryJump,
+    CloseUndoHistory,
+
+    // Diff view
+    CompareWithFile,
+    CompareWithSaved,
+    DiffNextHunk,
+    DiffPrevHunk,
+    DiffTakeLeft,
+    DiffTakeRight,
+
+    // Merge view
+    MergeNextConflict,
+    MergePrevConflict,
+    MergeAcceptLocal,
+    MergeAcceptRemote,
+    MergeAcceptBoth,
 
     // View
     ScrollUp,
@@ -352,6 +398,7 @@ pub enum Action {
     ToggleLineWrap,
     ToggleComposeMode,
     SetComposeWidth,
+    ToggleHexView,
     SelectTheme,
     SelectKeybindingMap,
     SelectCursorStyle,
@@ -453,6 +500,7 @@ pub enum Action {
     LspHover,
     LspSignatureHelp,
     LspCodeActions,
+    LspCodeLens,
     LspRestart,
     LspStop,
     ToggleInlayHints,
@@ -468,6 +516,8 @@ pub enum Action {
     // Buffer settings (per-buffer overrides)
     SetTabSize,
     SetLineEnding,
+    ReopenWithEncoding,
+    SaveWithEncoding,
     ToggleIndentationStyle,
     ToggleTabIndicators,
     ResetBufferSettings,
@@ -625,6 +675,7 @@ impl Action {
 
             "save" => Self::Save,
             "save_as" => Self::SaveAs,
+            "save_as_root" => Self::SaveAsRoot,
             "open" => Self::Open,
             "switch_project" => Self::SwitchProject,
             "new" => Self::New,
@@ -634,7 +685,10 @@ impl Action {
             "revert" => Self::Revert,
             "toggle_auto_revert" => Self::ToggleAutoRevert,
             "format_buffer" => Self::FormatBuffer,
+            "format_selection" => Self::FormatSelection,
             "goto_line" => Self::GotoLine,
+            "goto_symbol" => Self::GotoSymbol,
+            "workspace_symbol" => Self::WorkspaceSymbol,
             "goto_matching_bracket" => Self::GoToMatchingBracket,
             "jump_to_next_error" => Self::JumpToNextError,
             "jump_to_previous_error" => Self::JumpToPreviousError,
@@ -642,6 +696,21 @@ impl Action {
             "smart_home" => Self::SmartHome,
             "dedent_selection" => Self::DedentSelection,
             "toggle_comment" => Self::ToggleComment,
+            "insert_snippet" => Self::InsertSnippet,
+
+            "fold" => Self::Fold,
+            "unfold" => Self::Unfold,
+            "fold_all" => Self::FoldAll,
+            "unfold_all" => Self::UnfoldAll,
+
+            "toggle_outline" => Self::ToggleOutline,
+            "outline_jump" => Self::OutlineJump,
+            "call_hierarchy" => Self::CallHierarchy,
+            "type_hierarchy" => Self::TypeHierarchy,
+            "hierarchy_toggle" => Self::HierarchyToggle,
+            "hierarchy_jump" => Self::HierarchyJump,
+            "hierarchy_flip_direction" => Self::HierarchyFlipDirection,
+            "close_hierarchy" => Self::CloseHierarchy,
 
             "set_bookmark" => return Self::with_char(args, Self::SetBookmark),
             "jump_to_bookmark" => return Self::with_char(args, Self::JumpToBookmark),
@@ -657,6 +726,10 @@ impl Action {
             "start_macro_recording" => Self::StartMacroRecording,
             "stop_macro_recording" => Self::StopMacroRecording,
             "play_macro" => return Self::with_char(args, Self::PlayMacro),
+            "play_named_macro" => {
+                let name = args.get("name")?.as_str()?;
+                Self::PlayNamedMacro(name.to_string())
+            }
             "toggle_macro_recording" => return Self::with_char(args, Self::ToggleMacroRecording),
 
             "show_macro" => return Self::with_char(args, Self::ShowMacro),
@@ -664,12 +737,32 @@ impl Action {
             "list_macros" => Self::ListMacros,
             "prompt_record_macro" => Self::PromptRecordMacro,
             "prompt_play_macro" => Self::PromptPlayMacro,
+            "prompt_edit_macro" => Self::PromptEditMacro,
+            "prompt_play_macro_on_lines" => Self::PromptPlayMacroOnLines,
+            "prompt_play_macro_times" => Self::PromptPlayMacroTimes,
             "play_last_macro" => Self::PlayLastMacro,
             "prompt_set_bookmark" => Self::PromptSetBookmark,
             "prompt_jump_to_bookmark" => Self::PromptJumpToBookmark,
 
             "undo" => Self::Undo,
             "redo" => Self::Redo,
+            "undo_earlier" => Self::UndoEarlier,
+            "undo_later" => Self::UndoLater,
+            "undo_history" => Self::UndoHistory,
+            "undo_history_jump" => Self::UndoHistoryJump,
+            "close_undo_history" => Self::CloseUndoHistory,
+
+            "compare_with_file" => Self::CompareWithFile,
+            "compare_with_saved" => Self::CompareWithSaved,
+            "diff_next_hunk" => Self::DiffNextHunk,
+            "diff_prev_hunk" => Self::DiffPrevHunk,
+            "diff_take_left" => Self::DiffTakeLeft,
+            "diff_take_right" => Self::DiffTakeRight,
+            "merge_view_next_conflict" => Self::MergeNextConflict,
+            "merge_view_prev_conflict" => Self::MergePrevConflict,
+            "merge_accept_local" => Self::MergeAcceptLocal,
+            "merge_accept_remote" => Self::MergeAcceptRemote,
+            "merge_accept_both" => Self::MergeAcceptBoth,
 
             "scroll_up" => Self::ScrollUp,
             "scroll_down" => Self::ScrollDown,
@@ -682,6 +775,7 @@ impl Action {
             "toggle_line_wrap" => Self::ToggleLineWrap,
             "toggle_compose_mode" => Self::ToggleComposeMode,
             "set_compose_width" => Self::SetComposeWidth,
+            "toggle_hex_view" => Self::ToggleHexView,
 
             "next_buffer" => Self::NextBuffer,
             "prev_buffer" => Self::PrevBuffer,
@@ -761,6 +855,7 @@ impl Action {
             "lsp_hover" => Self::LspHover,
             "lsp_signature_help" => Self::LspSignatureHelp,
             "lsp_code_actions" => Self::LspCodeActions,
+            "lsp_code_lens" => Self::LspCodeLens,
             "lsp_restart" => Self::LspRestart,
             "lsp_stop" => Self::LspStop,
             "toggle_inlay_hints" => Self::ToggleInlayHints,
@@ -778,6 +873,8 @@ impl Action {
             // Buffer settings
             "set_tab_size" => Self::SetTabSize,
             "set_line_ending" => Self::SetLineEnding,
+            "reopen_with_encoding" => Self::ReopenWithEncoding,
+            "save_with_encoding" => Self::SaveWithEncoding,
             "toggle_indentation_style" => Self::ToggleIndentationStyle,
             "toggle_tab_indicators" => Self::ToggleTabIndicators,
             "reset_buffer_settings" => Self::ResetBufferSettings,
@@ -873,6 +970,49 @@ pub struct KeybindingResolver {
 
     /// Default chord bindings for each context
     default_chord_bindings: HashMap<KeyContext, HashMap<Vec<(KeyCode, KeyModifiers)>, Action>>,
+
+    /// Bindings whose `when` clause has a condition beyond the key context,
+    /// in load order (later bindings win)
+    conditional_bindings: Vec<ConditionalBinding>,
+
+    /// Default bindings whose `when` clause has a condition
+    default_conditional_bindings: Vec<ConditionalBinding>,
+}
+
+/// A binding that only applies while its `when` condition holds
+#[derive(Debug, Clone)]
+struct ConditionalBinding {
+    /// Key context the binding belongs to
+    context: KeyContext,
+    /// Condition checked in that context
+    condition: WhenClause,
+    /// Key, or key sequence of a chord binding
+    keys: Vec<(KeyCode, KeyModifiers)>,
+    /// Whether this is a chord binding
+    chord: bool,
+    action: Action,
+}
+
+/// Find the action of the last conditional binding for the keys whose
+/// condition holds
+fn conditional_action<'a>(
+    bindings: &'a [ConditionalBinding],
+    bind_context: KeyContext,
+    keys: &[(KeyCode, KeyModifiers)],
+    chord: bool,
+    context: KeyContext,
+    values: &WhenContext,
+) -> Option<&'a Action> {
+    bindings
+        .iter()
+        .rev()
+        .find(|binding| {
+            binding.context == bind_context
+                && binding.chord == chord
+                && binding.keys == keys
+                && binding.condition.evaluate(context, values)
+        })
+        .map(|binding| &binding.action)
 }
 
 impl KeybindingResolver {
@@ -883,6 +1023,8 @@ impl KeybindingResolver {
             default_bindings: HashMap::new(),
             chord_bindings: HashMap::new(),
             default_chord_bindings: HashMap::new(),
+            conditional_bindings: Vec::new(),
+            default_conditional_bindings: Vec::new(),
         };
 
         // Load bindings from the active keymap (with inheritance resolution) into default_bindings
@@ -898,11 +1040,9 @@ impl KeybindingResolver {
     /// Load default bindings from a vector of keybinding definitions (into default_bindings/default_chord_bindings)
     fn load_default_bindings_from_vec(&mut self, bindings: &[crate::config::Keybinding]) {
         for binding in bindings {
-            // Determine context from "when" clause
-            let context = if let Some(ref when) = binding.when {
-                KeyContext::from_when_clause(when).unwrap_or(KeyContext::Normal)
-            } else {
-                KeyContext::Normal
+            // Determine context and condition from "when" clause
+            let Some((context, condition)) = Self::parse_when(binding.when.as_deref()) else {
+                continue;
             };
 
             if let Some(action) = Action::from_str(&binding.action, &binding.args) {
@@ -922,15 +1062,41 @@ impl KeybindingResolver {
 
                     // Only add if all keys in sequence were valid
                     if sequence.len() == binding.keys.len() && !sequence.is_empty() {
-                        self.default_chord_bindings
-                            .entry(context)
-                            .or_insert_with(HashMap::new)
-                            .insert(sequence, action);
+                        if let Some(condition) = condition {
+                            self.default_conditional_bindings.push(ConditionalBinding {
+                                context,
+                                condition,
+                                keys: sequence,
+                                chord: true,
+                                action,
+                            });
+                        } else {
+                            self.default_chord_bindings
+                                .entry(context)
+                                .or_default()
+                                .insert(sequence, action);
+                        }
                     }
                 } else if let Some(key_code) = Self::parse_key(&binding.key) {
                     // Single key binding (legacy format)
                     let modifiers = Self::parse_modifiers(&binding.modifiers);
 
+                    if let Some(condition) = condition {
+                        // Conditional bindings get the terminal key equivalents too
+                        for keys in std::iter::once((key_code, modifiers))
+                            .chain(terminal_key_equivalents(key_code, modifiers))
+                        {
+                            self.default_conditional_bindings.push(ConditionalBinding {
+                                context,
+                                condition: condition.clone(),
+                                keys: vec![keys],
+                                chord: false,
+                                action: action.clone(),
+                            });
+                        }
+                        continue;
+                    }
+
                     // Insert the primary binding
                     self.insert_binding_with_equivalents(
                         context,
@@ -993,11 +1159,9 @@ impl KeybindingResolver {
     /// Load custom bindings from a vector of keybinding definitions (into bindings/chord_bindings)
     fn load_bindings_from_vec(&mut self, bindings: &[crate::config::Keybinding]) {
         for binding in bindings {
-            // Determine context from "when" clause
-            let context = if let Some(ref when) = binding.when {
-                KeyContext::from_when_clause(when).unwrap_or(KeyContext::Normal)
-            } else {
-                KeyContext::Normal
+            // Determine context and condition from "when" clause
+            let Some((context, condition)) = Self::parse_when(binding.when.as_deref()) else {
+                continue;
             };
 
             if let Some(action) = Action::from_str(&binding.action, &binding.args) {
@@ -1017,23 +1181,73 @@ impl KeybindingResolver {
 
                     // Only add if all keys in sequence were valid
                     if sequence.len() == binding.keys.len() && !sequence.is_empty() {
-                        self.chord_bindings
-                            .entry(context)
-                            .or_insert_with(HashMap::new)
-                            .insert(sequence, action);
+                        if let Some(condition) = condition {
+                            self.conditional_bindings.push(ConditionalBinding {
+                                context,
+                                condition,
+                                keys: sequence,
+                                chord: true,
+                                action,
+                            });
+                        } else {
+                            self.chord_bindings
+                                .entry(context)
+                                .or_default()
+                                .insert(sequence, action);
+                        }
                     }
                 } else if let Some(key_code) = Self::parse_key(&binding.key) {
                     // Single key binding (legacy format)
                     let modifiers = Self::parse_modifiers(&binding.modifiers);
-                    self.bindings
-                        .entry(context)
-                        .or_insert_with(HashMap::new)
-                        .insert((key_code, modifiers), action);
+                    self.insert_custom_binding(context, condition, key_code, modifiers, action);
                 }
             }
         }
     }
 
+    /// Key context and condition of a binding's `when` clause
+    ///
+    /// Returns None (after logging why) if the clause does not parse, so the
+    /// binding is skipped rather than applied everywhere.
+    fn parse_when(when: Option<&str>) -> Option<(KeyContext, Option<WhenClause>)> {
+        let Some(when) = when.filter(|when| !when.trim().is_empty()) else {
+            return Some((KeyContext::Normal, None));
+        };
+        match WhenClause::parse(when) {
+            Ok(clause) => Some(clause.split_context()),
+            Err(error) => {
+                tracing::warn!("Ignoring keybinding with invalid when clause: {}", error);
+                None
+            }
+        }
+    }
+
+    /// Insert a custom single key binding, conditional or not
+    fn insert_custom_binding(
+        &mut self,
+        context: KeyContext,
+        condition: Option<WhenClause>,
+        key_code: KeyCode,
+        modifiers: KeyModifiers,
+        action: Action,
+    ) {
+        match condition {
+            Some(condition) => self.conditional_bindings.push(ConditionalBinding {
+                context,
+                condition,
+                keys: vec![(key_code, modifiers)],
+                chord: false,
+                action,
+            }),
+            None => {
+                self.bindings
+                    .entry(context)
+                    .or_default()
+                    .insert((key_code, modifiers), action);
+            }
+        }
+    }
+
     /// Check if an action is application-wide (should be accessible in all contexts)
     fn is_application_wide_action(action: &Action) -> bool {
         matches!(
@@ -1098,6 +1312,18 @@ impl KeybindingResolver {
         chord_state: &[(KeyCode, KeyModifiers)],
         event: &KeyEvent,
         context: KeyContext,
+    ) -> ChordResolution {
+        self.resolve_chord_when(chord_state, event, context, &WhenContext::default())
+    }
+
+    /// Resolve a key event with chord state, checking the `when` conditions
+    /// of conditional bindings against the given context keys
+    pub fn resolve_chord_when(
+        &self,
+        chord_state: &[(KeyCode, KeyModifiers)],
+        event: &KeyEvent,
+        context: KeyContext,
+        values: &WhenContext,
     ) -> ChordResolution {
         // Build the full sequence: existing chord state + new key
         let mut full_sequence = chord_state.to_vec();
@@ -1111,20 +1337,60 @@ impl KeybindingResolver {
 
         // Check all chord binding sources in priority order
         let search_order = vec![
-            (&self.chord_bindings, &KeyContext::Global, "custom global"),
+            (
+                &self.chord_bindings,
+                &self.conditional_bindings,
+                KeyContext::Global,
+                "custom global",
+            ),
             (
                 &self.default_chord_bindings,
-                &KeyContext::Global,
+                &self.default_conditional_bindings,
+                KeyContext::Global,
                 "default global",
             ),
-            (&self.chord_bindings, &context, "custom context"),
-            (&self.default_chord_bindings, &context, "default context"),
+            (
+                &self.chord_bindings,
+                &self.conditional_bindings,
+                context,
+                "custom context",
+            ),
+            (
+                &self.default_chord_bindings,
+                &self.default_conditional_bindings,
+                context,
+                "default context",
+            ),
         ];
 
         let mut has_partial_match = false;
 
-        for (binding_map, bind_context, label) in search_order {
-            if let Some(context_chords) = binding_map.get(bind_context) {
+        for (binding_map, conditional, bind_context, label) in search_order {
+            // Conditional bindings whose condition holds come first
+            if let Some(action) = conditional_action(
+                conditional,
+                bind_context,
+                &full_sequence,
+                true,
+                context,
+                values,
+            ) {
+                tracing::trace!(
+                    "  -> Complete conditional chord match in {}: {:?}",
+                    label,
+                    action
+                );
+                return ChordResolution::Complete(action.clone());
+            }
+            has_partial_match |= conditional.iter().any(|binding| {
+                binding.chord
+                    && binding.context == bind_context
+                    && binding.keys.len() > full_sequence.len()
+                    && binding.keys[..full_sequence.len()] == full_sequence[..]
+                    && binding.condition.evaluate(context, values)
+            });
+
+            if let Some(context_chords) = binding_map.get(&bind_context) {
                 // Check for exact match
                 if let Some(action) = context_chords.get(&full_sequence) {
                     tracing::trace!("  -> Complete chord match in {}: {:?}", label, action);
@@ -1152,8 +1418,90 @@ impl KeybindingResolver {
         }
     }
 
+    /// Keys that can follow a pending chord prefix in the given context
+    ///
+    /// Each key comes with the action it completes the chord with, or None
+    /// if more keys follow it. As in `resolve_chord_when`, a key completing
+    /// a chord wins over one starting a longer sequence, and earlier binding
+    /// sources win over later ones.
+    pub fn chord_continuations(
+        &self,
+        chord_state: &[(KeyCode, KeyModifiers)],
+        context: KeyContext,
+        values: &WhenContext,
+    ) -> Vec<((KeyCode, KeyModifiers), Option<Action>)> {
+        let sources = [
+            (
+                &self.chord_bindings,
+                &self.conditional_bindings,
+                KeyContext::Global,
+            ),
+            (
+                &self.default_chord_bindings,
+                &self.default_conditional_bindings,
+                KeyContext::Global,
+            ),
+            (&self.chord_bindings, &self.conditional_bindings, context),
+            (
+                &self.default_chord_bindings,
+                &self.default_conditional_bindings,
+                context,
+            ),
+        ];
+
+        let mut continuations: Vec<((KeyCode, KeyModifiers), Option<Action>)> = Vec::new();
+        for (binding_map, conditional, bind_context) in sources {
+            let conditional_chords = conditional
+                .iter()
+                .rev()
+                .filter(|binding| {
+                    binding.chord
+                        && binding.context == bind_context
+                        && binding.condition.evaluate(context, values)
+                })
+                .map(|binding| (&binding.keys, &binding.action));
+            let chords = binding_map.get(&bind_context).into_iter().flatten();
+
+            for (sequence, action) in conditional_chords.chain(chords) {
+                if sequence.len() <= chord_state.len() || !sequence.starts_with(chord_state) {
+                    continue;
+                }
+                let key = sequence[chord_state.len()];
+                let completes = (sequence.len() == chord_state.len() + 1).then(|| action.clone());
+                match continuations.iter_mut().find(|(k, _)| *k == key) {
+                    Some(existing) if existing.1.is_none() && completes.is_some() => {
+                        existing.1 = completes;
+                    }
+                    Some(_) => {}
+                    None => continuations.push((key, completes)),
+                }
+            }
+        }
+
+        continuations.sort_by_key(|((key_code, modifiers), _)| {
+            (modifiers.bits(), Self::key_code_sort_key(key_code))
+        });
+        continuations
+    }
+
     /// Resolve a key event to an action in the given context
     pub fn resolve(&self, event: &KeyEvent, context: KeyContext) -> Action {
+        self.resolve_when(event, context, &WhenContext::default())
+    }
+
+    /// Resolve a key event to an action in the given context, checking the
+    /// `when` conditions of conditional bindings against the given context keys
+    pub fn resolve_when(
+        &self,
+        event: &KeyEvent,
+        context: KeyContext,
+        values: &WhenContext,
+    ) -> Action {
+        let key = [(event.code, event.modifiers)];
+        let conditional = |bindings: &[ConditionalBinding], bind_context: KeyContext| {
+            conditional_action(bindings, bind_context, &key, false, context, values).cloned()
+        };
+
         tracing::trace!(
             "KeybindingResolver.resolve: code={:?}, modifiers={:?}, context={:?}",
             event.code,
@@ -1162,6 +1510,14 @@ impl KeybindingResolver {
         );
 
         // Check Global bindings first (highest priority - work in all contexts)
+        if let Some(action) = conditional(&self.conditional_bindings, KeyContext::Global) {
+            tracing::trace!(
+                "  -> Found in custom conditional global bindings: {:?}",
+                action
+            );
+            return action;
+        }
+
         if let Some(global_bindings) = self.bindings.get(&KeyContext::Global) {
             if let Some(action) = global_bindings.get(&(event.code, event.modifiers)) {
                 tracing::trace!("  -> Found in custom global bindings: {:?}", action);
@@ -1169,6 +1525,14 @@ impl KeybindingResolver {
             }
         }
 
+        if let Some(action) = conditional(&self.default_conditional_bindings, KeyContext::Global) {
+            tracing::trace!(
+                "  -> Found in default conditional global bindings: {:?}",
+                action
+            );
+            return action;
+        }
+
         if let Some(global_bindings) = self.default_bindings.get(&KeyContext::Global) {
             if let Some(action) = global_bindings.get(&(event.code, event.modifiers)) {
                 tracing::trace!("  -> Found in default global bindings: {:?}", action);
@@ -1176,7 +1540,16 @@ impl KeybindingResolver {
             }
         }
 
-        // Try context-specific custom bindings
+        // Try context-specific custom bindings, conditional ones first
+        if let Some(action) = conditional(&self.conditional_bindings, context) {
+            tracing::trace!(
+                "  -> Found in custom conditional {} bindings: {:?}",
+                context.to_when_clause(),
+                action
+            );
+            return action;
+        }
+
         if let Some(context_bindings) = self.bindings.get(&context) {
             if let Some(action) = context_bindings.get(&(event.code, event.modifiers)) {
                 tracing::trace!(
@@ -1188,7 +1561,16 @@ impl KeybindingResolver {
             }
         }
 
-        // Try context-specific default bindings
+        // Try context-specific default bindings, conditional ones first
+        if let Some(action) = conditional(&self.default_conditional_bindings, context) {
+            tracing::trace!(
+                "  -> Found in default conditional {} bindings: {:?}",
+                context.to_when_clause(),
+                action
+            );
+            return action;
+        }
+
         if let Some(context_bindings) = self.default_bindings.get(&context) {
             if let Some(action) = context_bindings.get(&(event.code, event.modifiers)) {
                 tracing::trace!(
@@ -1526,6 +1908,30 @@ impl KeybindingResolver {
             }
         }
 
+        // Conditional bindings, with the condition they apply under
+        for binding in self
+            .default_conditional_bindings
+            .iter()
+            .chain(&self.conditional_bindings)
+            .filter(|binding| !binding.chord)
+        {
+            let (key_code, modifiers) = binding.keys[0];
+            let context_str = if binding.context != KeyContext::Normal {
+                format!("[{}] ", binding.context.to_when_clause())
+            } else {
+                String::new()
+            };
+            bindings.push((
+                Self::format_key(key_code, modifiers),
+                format!(
+                    "{}{} (when {})",
+                    context_str,
+                    Self::format_action(&binding.action),
+                    binding.condition
+                ),
+            ));
+        }
+
         // Sort by action description for easier browsing
         bindings.sort_by(|a, b| a.1.cmp(&b.1));
 
@@ -1538,7 +1944,7 @@ impl KeybindingResolver {
     }
 
     /// Format an action as a readable description
-    fn format_action(action: &Action) -> String {
+    pub(crate) fn format_action(action: &Action) -> String {
         match action {
             Action::InsertChar(c) => t!("action.insert_char", char = c),
             Action::InsertNewline => t!("action.insert_newline"),
@@ -1601,6 +2007,7 @@ impl KeybindingResolver {
             Action::RemoveSecondaryCursors => t!("action.remove_secondary_cursors"),
             Action::Save => t!("action.save"),
             Action::SaveAs => t!("action.save_as"),
+            Action::SaveAsRoot => t!("action.save_as_root"),
             Action::Open => t!("action.open"),
             Action::SwitchProject => t!("action.switch_project"),
             Action::New => t!("action.new"),
@@ -1610,13 +2017,29 @@ impl KeybindingResolver {
             Action::Revert => t!("action.revert"),
             Action::ToggleAutoRevert => t!("action.toggle_auto_revert"),
             Action::FormatBuffer => t!("action.format_buffer"),
+            Action::FormatSelection => t!("action.format_selection"),
             Action::GotoLine => t!("action.goto_line"),
+            Action::GotoSymbol => t!("action.goto_symbol"),
+            Action::WorkspaceSymbol => t!("action.workspace_symbol"),
             Action::GoToMatchingBracket => t!("action.goto_matching_bracket"),
             Action::JumpToNextError => t!("action.jump_to_next_error"),
             Action::JumpToPreviousError => t!("action.jump_to_previous_error"),
             Action::SmartHome => t!("action.smart_home"),
             Action::DedentSelection => t!("action.dedent_selection"),
             Action::ToggleComment => t!("action.toggle_comment"),
+            Action::InsertSnippet => t!("action.insert_snippet"),
+            Action::Fold => t!("action.fold"),
+            Action::Unfold => t!("action.unfold"),
+            Action::FoldAll => t!("action.fold_all"),
+            Action::UnfoldAll => t!("action.unfold_all"),
+            Action::ToggleOutline => t!("action.toggle_outline"),
+            Action::OutlineJump => t!("action.outline_jump"),
+            Action::CallHierarchy => t!("action.call_hierarchy"),
+            Action::TypeHierarchy => t!("action.type_hierarchy"),
+            Action::HierarchyToggle => t!("action.hierarchy_toggle"),
+            Action::HierarchyJump => t!("action.hierarchy_jump"),
+            Action::HierarchyFlipDirection => t!("action.hierarchy_flip_direction"),
+            Action::CloseHierarchy => t!("action.close_hierarchy"),
             Action::SetBookmark(c) => t!("action.set_bookmark", key = c),
             Action::JumpToBookmark(c) => t!("action.jump_to_bookmark", key = c),
             Action::ClearBookmark(c) => t!("action.clear_bookmark", key = c),
@@ -1628,16 +2051,36 @@ impl KeybindingResolver {
             Action::StartMacroRecording => t!("action.start_macro_recording"),
             Action::StopMacroRecording => t!("action.stop_macro_recording"),
             Action::PlayMacro(c) => t!("action.play_macro", key = c),
+            Action::PlayNamedMacro(name) => t!("action.play_macro", key = name),
             Action::ToggleMacroRecording(c) => t!("action.toggle_macro_recording", key = c),
             Action::ShowMacro(c) => t!("action.show_macro", key = c),
             Action::ListMacros => t!("action.list_macros"),
             Action::PromptRecordMacro => t!("action.prompt_record_macro"),
             Action::PromptPlayMacro => t!("action.prompt_play_macro"),
+            Action::PromptEditMacro => t!("action.prompt_edit_macro"),
+            Action::PromptPlayMacroOnLines => t!("action.prompt_play_macro_on_lines"),
+            Action::PromptPlayMacroTimes => t!("action.prompt_play_macro_times"),
             Action::PlayLastMacro => t!("action.play_last_macro"),
             Action::PromptSetBookmark => t!("action.prompt_set_bookmark"),
             Action::PromptJumpToBookmark => t!("action.prompt_jump_to_bookmark"),
             Action::Undo => t!("action.undo"),
             Action::Redo => t!("action.redo"),
+            Action::UndoEarlier => t!("action.undo_earlier"),
+            Action::UndoLater => t!("action.undo_later"),
+            Action::UndoHistory => t!("action.undo_history"),
+            Action::UndoHistoryJump => t!("action.undo_history_jump"),
+            Action::CloseUndoHistory => t!("action.close_undo_history"),
+            Action::CompareWithFile => t!("action.compare_with_file"),
+            Action::CompareWithSaved => t!("action.compare_with_saved"),
+            Action::DiffNextHunk => t!("action.diff_next_hunk"),
+            Action::DiffPrevHunk => t!("action.diff_prev_hunk"),
+            Action::DiffTakeLeft => t!("action.diff_take_left"),
+            Action::DiffTakeRight => t!("action.diff_take_right"),
+            Action::MergeNextConflict => t!("action.merge_next_conflict"),
+            Action::MergePrevConflict => t!("action.merge_prev_conflict"),
+            Action::MergeAcceptLocal => t!("action.merge_accept_local"),
+            Action::MergeAcceptRemote => t!("action.merge_accept_remote"),
+            Action::MergeAcceptBoth => t!("action.merge_accept_both"),
             Action::ScrollUp => t!("action.scroll_up"),
             Action::ScrollDown => t!("action.scroll_down"),
             Action::ShowHelp => t!("action.show_help"),
@@ -1649,6 +2092,7 @@ impl KeybindingResolver {
             Action::ToggleLineWrap => t!("action.toggle_line_wrap"),
             Action::ToggleComposeMode => t!("action.toggle_compose_mode"),
             Action::SetComposeWidth => t!("action.set_compose_width"),
+            Action::ToggleHexView => t!("action.toggle_hex_view"),
             Action::NextBuffer => t!("action.next_buffer"),
             Action::PrevBuffer => t!("action.prev_buffer"),
             Action::NavigateBack => t!("action.navigate_back"),
@@ -1721,6 +2165,7 @@ impl KeybindingResolver {
             Action::LspHover => t!("action.lsp_hover"),
             Action::LspSignatureHelp => t!("action.lsp_signature_help"),
             Action::LspCodeActions => t!("action.lsp_code_actions"),
+            Action::LspCodeLens => t!("action.lsp_code_lens"),
             Action::LspRestart => t!("action.lsp_restart"),
             Action::LspStop => t!("action.lsp_stop"),
             Action::ToggleInlayHints => t!("action.toggle_inlay_hints"),
@@ -1732,6 +2177,8 @@ impl KeybindingResolver {
             Action::SetBackgroundBlend => t!("action.set_background_blend"),
             Action::SetTabSize => t!("action.set_tab_size"),
             Action::SetLineEnding => t!("action.set_line_ending"),
+            Action::ReopenWithEncoding => t!("action.reopen_with_encoding"),
+            Action::SaveWithEncoding => t!("action.save_with_encoding"),
             Action::ToggleIndentationStyle => t!("action.toggle_indentation_style"),
             Action::ToggleTabIndicators => t!("action.toggle_tab_indicators"),
             Action::ResetBufferSettings => t!("action.reset_buffer_settings"),
@@ -1871,21 +2318,17 @@ impl KeybindingResolver {
     /// Reload bindings from config (for hot reload)
     pub fn reload(&mut self, config: &Config) {
         self.bindings.clear();
+        self.conditional_bindings.clear();
         for binding in &config.keybindings {
             if let Some(key_code) = Self::parse_key(&binding.key) {
                 let modifiers = Self::parse_modifiers(&binding.modifiers);
                 if let Some(action) = Action::from_str(&binding.action, &binding.args) {
-                    // Determine context from "when" clause
-                    let context = if let Some(ref when) = binding.when {
-                        KeyContext::from_when_clause(when).unwrap_or(KeyContext::Normal)
-                    } else {
-                        KeyContext::Normal
+                    // Determine context and condition from "when" clause
+                    let Some((context, condition)) = Self::parse_when(binding.when.as_deref())
+                    else {
+                        continue;
                     };
-
-                    self.bindings
-                        .entry(context)
-                        .or_insert_with(HashMap::new)
-                        .insert((key_code, modifiers), action);
+                    self.insert_custom_binding(context, condition, key_code, modifiers, action);
                 }
             }
         }
@@ -2080,6 +2523,171 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_conditional_keybindings() {
+        use crate::config::{KeyPress, Keybinding};
+        use crate::input::when_clause::{keys, WhenContext};
+
+        let binding = |key: &str, action: &str, when: &str| Keybinding {
+            key: key.to_string(),
+            modifiers: vec![],
+            keys: vec![],
+            action: action.to_string(),
+            args: HashMap::new(),
+            when: Some(when.to_string()),
+        };
+        let mut config = Config::default();
+        config.keybindings.extend([
+            binding("tab", "dedent_selection", "editorHasSelection && !readOnly"),
+            binding("f6", "save", "editorLangId == rust || fileExplorer"),
+            binding("f7", "quit", "has_selection &&"),
+            Keybinding {
+                key: String::new(),
+                keys: vec![
+                    KeyPress {
+                        key: "g".to_string(),
+                        modifiers: vec!["ctrl".to_string()],
+                    },
+                    KeyPress {
+                        key: "t".to_string(),
+                        modifiers: vec![],
+                    },
+                ],
+                ..binding("", "toggle_line_wrap", "testsRunning")
+            },
+        ]);
+        let resolver = KeybindingResolver::new(&config);
+
+        let tab = KeyEvent::new(KeyCode::Tab, KeyModifiers::empty());
+        let f6 = KeyEvent::new(KeyCode::F(6), KeyModifiers::empty());
+        let mut values = WhenContext::new();
+        assert_eq!(
+            resolver.resolve_when(&tab, KeyContext::Normal, &values),
+            Action::InsertTab
+        );
+        assert_eq!(
+            resolver.resolve_when(&f6, KeyContext::Normal, &values),
+            Action::None
+        );
+
+        values
+            .set_flag(keys::EDITOR_HAS_SELECTION, true)
+            .set(keys::EDITOR_LANG_ID, "rust");
+        assert_eq!(
+            resolver.resolve_when(&tab, KeyContext::Normal, &values),
+            Action::DedentSelection
+        );
+        assert_eq!(
+            resolver.resolve_when(&f6, KeyContext::Normal, &values),
+            Action::Save
+        );
+        // The condition only applies in the context the binding is in
+        assert_eq!(
+            resolver.resolve_when(&tab, KeyContext::Prompt, &values),
+            Action::PromptAcceptSuggestion
+        );
+        values.set_flag(keys::READ_ONLY, true);
+        assert_eq!(
+            resolver.resolve_when(&tab, KeyContext::Normal, &values),
+            Action::InsertTab
+        );
+
+        // A context named inside `||` is checked in every context
+        values.set(keys::EDITOR_LANG_ID, "python");
+        assert_eq!(
+            resolver.resolve_when(&f6, KeyContext::FileExplorer, &values),
+            Action::Save
+        );
+        assert_eq!(
+            resolver.resolve_when(&f6, KeyContext::Normal, &values),
+            Action::None
+        );
+
+        // Bindings with invalid clauses are skipped
+        let f7 = KeyEvent::new(KeyCode::F(7), KeyModifiers::empty());
+        assert_eq!(
+            resolver.resolve_when(&f7, KeyContext::Normal, &values),
+            Action::None
+        );
+
+        // Chords only start while their condition holds
+        let ctrl_g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL);
+        let t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::empty());
+        let ctrl_g_state = [(KeyCode::Char('g'), KeyModifiers::CONTROL)];
+        assert_eq!(
+            resolver.resolve_chord_when(&[], &ctrl_g, KeyContext::Normal, &values),
+            ChordResolution::NoMatch
+        );
+        values.set_flag("testsRunning", true);
+        assert_eq!(
+            resolver.resolve_chord_when(&[], &ctrl_g, KeyContext::Normal, &values),
+            ChordResolution::Partial
+        );
+        assert_eq!(
+            resolver.resolve_chord_when(&ctrl_g_state, &t, KeyContext::Normal, &values),
+            ChordResolution::Complete(Action::ToggleLineWrap)
+        );
+    }
+
+    #[test]
+    fn test_chord_continuations() {
+        use crate::config::{KeyPress, Keybinding};
+
+        let mut config = Config::default();
+        config.active_keybinding_map = "emacs".to_string().into();
+        let press = |key: &str, modifiers: &[&str]| KeyPress {
+            key: key.to_string(),
+            modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+        };
+        // A custom binding replaces the keymap's, and one adds a longer chord
+        config.keybindings = vec![
+            Keybinding {
+                key: String::new(),
+                modifiers: vec![],
+                keys: vec![press("x", &["ctrl"]), press("k", &[])],
+                action: "close_split".to_string(),
+                args: HashMap::new(),
+                when: Some("normal".to_string()),
+            },
+            Keybinding {
+                key: String::new(),
+                modifiers: vec![],
+                keys: vec![press("x", &["ctrl"]), press("r", &[]), press("t", &[])],
+                action: "toggle_line_wrap".to_string(),
+                args: HashMap::new(),
+                when: None,
+            },
+        ];
+        let resolver = KeybindingResolver::new(&config);
+        let ctrl_x = [(KeyCode::Char('x'), KeyModifiers::CONTROL)];
+        let values = WhenContext::new();
+
+        let continuations = resolver.chord_continuations(&ctrl_x, KeyContext::Normal, &values);
+        let find = |c: char, modifiers: KeyModifiers| {
+            continuations
+                .iter()
+                .find(|(key, _)| *key == (KeyCode::Char(c), modifiers))
+                .map(|(_, action)| action.clone())
+        };
+        assert_eq!(find('s', KeyModifiers::CONTROL), Some(Some(Action::Save)));
+        assert_eq!(
+            find('k', KeyModifiers::NONE),
+            Some(Some(Action::CloseSplit))
+        );
+        assert_eq!(find('r', KeyModifiers::NONE), Some(None));
+        assert_eq!(find('z', KeyModifiers::NONE), None);
+        // Keys without modifiers come first
+        assert_eq!(continuations[0].0 .1, KeyModifiers::NONE);
+
+        assert!(resolver
+            .chord_continuations(&ctrl_x, KeyContext::Prompt, &values)
+            .is_empty());
+        assert!(resolver
+            .chord_continuations(&[], KeyContext::Normal, &values)
+            .iter()
+            .any(|(key, action)| *key == ctrl_x[0] && action.is_none()));
+    }
+
     #[test]
     fn test_character_input_in_contexts() {
         let config = Config::default();
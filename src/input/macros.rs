@@ -0,0 +1,164 @@
+//! Keyboard macros kept across sessions
+//!
+//! Recorded macros are saved to `macros.json` in the config directory, a
+//! map from macro name to its actions with one action per line:
+//!
+//! ```json
+//! {
+//!   "wrap-line": [
+//!     "MoveLineStart",
+//!     {"InsertChar":"("},
+//!     "MoveLineEnd",
+//!     {"InsertChar":")"}
+//!   ]
+//! }
+//! ```
+//!
+//! Macros recorded into a register (`0`-`9`) are named after the register.
+//! The same one-action-per-line form is used by the buffer editing a macro.
+
+use crate::input::keybindings::Action;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Recorded macros by name
+pub type Macros = HashMap<String, Vec<Action>>;
+
+/// Whether an action controls macros rather than editing, and so is never
+/// recorded into (or played back from) a macro
+pub fn is_macro_control(action: &Action) -> bool {
+    matches!(
+        action,
+        Action::StartMacroRecording
+            | Action::StopMacroRecording
+            | Action::PlayMacro(_)
+            | Action::PlayNamedMacro(_)
+            | Action::ToggleMacroRecording(_)
+            | Action::ShowMacro(_)
+            | Action::ListMacros
+            | Action::PromptRecordMacro
+            | Action::PromptPlayMacro
+            | Action::PromptEditMacro
+            | Action::PromptPlayMacroOnLines
+            | Action::PromptPlayMacroTimes
+            | Action::PlayLastMacro
+    )
+}
+
+/// Format actions as a JSON array with one action per line, each line
+/// starting with `indent`
+pub fn format_actions(actions: &[Action], indent: &str) -> String {
+    if actions.is_empty() {
+        return "[]".to_string();
+    }
+    let lines: Vec<String> = actions
+        .iter()
+        .map(|action| {
+            let json = serde_json::to_string(action).unwrap_or_else(|_| "\"None\"".to_string());
+            format!("{}  {}", indent, json)
+        })
+        .collect();
+    format!("[\n{}\n{}]", lines.join(",\n"), indent)
+}
+
+/// Parse the text of a macro edit buffer back into actions.
+///
+/// Lines starting with `//` are comments.
+pub fn parse_actions(text: &str) -> Result<Vec<Action>, serde_json::Error> {
+    // Blank out comments rather than dropping them so errors keep their line
+    let json: Vec<&str> = text
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with("//") {
+                ""
+            } else {
+                line
+            }
+        })
+        .collect();
+    serde_json::from_str(&json.join("\n"))
+}
+
+/// Load the macros saved in `path` (empty if there are none)
+pub fn load_macros(path: &Path) -> Macros {
+    if !path.exists() {
+        return Macros::new();
+    }
+    let loaded = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<Macros>(&json).map_err(|e| e.to_string()));
+    match loaded {
+        Ok(macros) => macros,
+        Err(e) => {
+            tracing::warn!("Failed to load macros from {:?}: {}", path, e);
+            Macros::new()
+        }
+    }
+}
+
+/// Save macros to `path`, sorted by name
+pub fn save_macros(path: &Path, macros: &Macros) -> std::io::Result<()> {
+    let sorted: BTreeMap<&String, &Vec<Action>> = macros.iter().collect();
+    let entries: Vec<String> = sorted
+        .into_iter()
+        .map(|(name, actions)| {
+            let name = serde_json::to_string(name).unwrap_or_default();
+            format!("  {}: {}", name, format_actions(actions, "  "))
+        })
+        .collect();
+    let json = if entries.is_empty() {
+        "{}\n".to_string()
+    } else {
+        format!("{{\n{}\n}}\n", entries.join(",\n"))
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_and_parse_actions() {
+        let actions = vec![
+            Action::MoveLineStart,
+            Action::InsertChar('"'),
+            Action::CopyWithTheme("dracula".to_string()),
+        ];
+        let text = format_actions(&actions, "");
+        assert_eq!(
+            text,
+            "[\n  \"MoveLineStart\",\n  {\"InsertChar\":\"\\\"\"},\n  {\"CopyWithTheme\":\"dracula\"}\n]"
+        );
+        let edited = format!("// Macro 'q'\n{}", text);
+        assert_eq!(parse_actions(&edited).unwrap(), actions);
+        assert_eq!(parse_actions("[]").unwrap(), vec![]);
+
+        let error = parse_actions("// comment\n[\n  \"NoSuchAction\"\n]").unwrap_err();
+        assert_eq!(error.line(), 3);
+    }
+
+    #[test]
+    fn test_save_and_load_macros() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config").join("macros.json");
+        assert!(load_macros(&path).is_empty());
+
+        let mut macros = Macros::new();
+        macros.insert("wrap".to_string(), vec![Action::InsertChar('(')]);
+        macros.insert("1".to_string(), vec![]);
+        save_macros(&path, &macros).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\n  \"1\": [],\n  \"wrap\": [\n    {\"InsertChar\":\"(\"}\n  ]\n}\n"
+        );
+        assert_eq!(load_macros(&path), macros);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(load_macros(&path).is_empty());
+    }
+}
@@ -12,5 +12,7 @@ pub mod handler;
 pub mod input_history;
 pub mod key_translator;
 pub mod keybindings;
+pub mod macros;
 pub mod multi_cursor;
 pub mod position_history;
+pub mod when_clause;
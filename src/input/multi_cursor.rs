@@ -1,6 +1,9 @@
 //! Multi-cursor operations for adding cursors at various positions
 
+use std::ops::Range;
+
 use crate::model::cursor::Cursor;
+use crate::model::event::{CursorId, Event};
 use crate::state::EditorState;
 
 /// Result of attempting to add a cursor
@@ -172,3 +175,48 @@ pub fn add_cursor_below(state: &mut EditorState) -> AddCursorResult {
         }
     }
 }
+
+/// Events that leave one cursor selecting each of `ranges`
+///
+/// The primary cursor selects the first range, the other cursors are removed
+/// and new ones added for the rest (used for the copies of a mirrored snippet
+/// tabstop). Empty ranges get a cursor without a selection.
+pub fn select_ranges(state: &EditorState, ranges: &[Range<usize>]) -> Vec<Event> {
+    let Some((first, rest)) = ranges.split_first() else {
+        return Vec::new();
+    };
+    let anchor = |range: &Range<usize>| (range.start < range.end).then_some(range.start);
+
+    let primary_id = state.cursors.primary_id();
+    let mut next_id = 0;
+    let mut events = Vec::new();
+    for (cursor_id, cursor) in state.cursors.iter() {
+        next_id = next_id.max(cursor_id.0 + 1);
+        if cursor_id != primary_id {
+            events.push(Event::RemoveCursor {
+                cursor_id,
+                position: cursor.position,
+                anchor: cursor.anchor,
+            });
+        }
+    }
+
+    let primary = state.cursors.primary();
+    events.push(Event::MoveCursor {
+        cursor_id: primary_id,
+        old_position: primary.position,
+        new_position: first.end,
+        old_anchor: primary.anchor,
+        new_anchor: anchor(first),
+        old_sticky_column: primary.sticky_column,
+        new_sticky_column: 0,
+    });
+    for (i, range) in rest.iter().enumerate() {
+        events.push(Event::AddCursor {
+            cursor_id: CursorId(next_id + i),
+            position: range.end,
+            anchor: anchor(range),
+        });
+    }
+    events
+}
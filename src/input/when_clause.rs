@@ -0,0 +1,480 @@
+//! Conditions of keybinding `when` clauses
+//!
+//! A `when` clause is an expression over context keys:
+//!
+//! ```text
+//! editorHasSelection && !readOnly
+//! editorLangId == rust || editorLangId == toml
+//! fileExplorer || (normal && multiCursor)
+//! ```
+//!
+//! `&&` binds tighter than `||`, `!` negates, and parentheses group. A bare
+//! key is true when the key is set to anything but `false` or an empty
+//! value; `key == value` and `key != value` compare the value of the key
+//! (the value may be quoted). The names of the key contexts (`normal`,
+//! `prompt`, `fileExplorer`, ...) are true while that context is active.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::keybindings::KeyContext;
+
+/// Context keys set by the editor
+pub mod keys {
+    /// Any cursor of the active buffer has a selection
+    pub const EDITOR_HAS_SELECTION: &str = "editorHasSelection";
+    /// Language of the active buffer (e.g. "rust")
+    pub const EDITOR_LANG_ID: &str = "editorLangId";
+    /// The active buffer has more than one cursor
+    pub const MULTI_CURSOR: &str = "multiCursor";
+    /// The active buffer cannot be edited
+    pub const READ_ONLY: &str = "readOnly";
+    /// An LSP server is ready for the language of the active buffer
+    pub const LSP_AVAILABLE: &str = "lspAvailable";
+    /// Editor mode set by a plugin (e.g. "vi-normal"), or the mode of the buffer
+    pub const MODE: &str = "mode";
+}
+
+/// Values of the context keys a `when` clause is evaluated against
+#[derive(Debug, Clone, Default)]
+pub struct WhenContext {
+    values: HashMap<String, String>,
+}
+
+impl WhenContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of a key
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.values.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set a key to `true` or `false`
+    pub fn set_flag(&mut self, key: impl Into<String>, value: bool) -> &mut Self {
+        self.set(key, value.to_string())
+    }
+
+    /// Value of a key, if it is set
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Whether a key is set to a true value
+    fn is_true(&self, key: &str) -> bool {
+        self.get(key)
+            .is_some_and(|value| !value.is_empty() && value != "false")
+    }
+}
+
+/// A parsed `when` clause
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WhenClause {
+    /// A key (or key context) that must be true
+    Key(String),
+    /// `key == value`
+    Equals(String, String),
+    /// `key != value`
+    NotEquals(String, String),
+    /// `!clause`
+    Not(Box<WhenClause>),
+    /// All of the clauses
+    And(Vec<WhenClause>),
+    /// Any of the clauses
+    Or(Vec<WhenClause>),
+}
+
+impl WhenClause {
+    /// Parse a `when` clause
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let clause = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(clause),
+            Some(token) => Err(format!("unexpected {} in \"{}\"", token, text.trim())),
+        }
+    }
+
+    /// Whether the clause holds in the key context with the given key values
+    pub fn evaluate(&self, context: KeyContext, values: &WhenContext) -> bool {
+        match self {
+            Self::Key(key) => match KeyContext::from_when_clause(key) {
+                Some(KeyContext::Global) => true,
+                Some(key_context) => key_context == context,
+                None => values.is_true(key),
+            },
+            Self::Equals(key, value) => values.get(key) == Some(value.as_str()),
+            Self::NotEquals(key, value) => values.get(key) != Some(value.as_str()),
+            Self::Not(clause) => !clause.evaluate(context, values),
+            Self::And(clauses) => clauses.iter().all(|c| c.evaluate(context, values)),
+            Self::Or(clauses) => clauses.iter().any(|c| c.evaluate(context, values)),
+        }
+    }
+
+    /// Split the clause into the key context its binding belongs to and the
+    /// condition left to check in that context
+    ///
+    /// A key context named on its own, or as one of the terms joined by
+    /// `&&`, is where the binding goes. A clause that names key contexts
+    /// only in other ways (e.g. `prompt || popup`) is checked in every
+    /// context; any other clause applies to normal editing.
+    pub fn split_context(self) -> (KeyContext, Option<WhenClause>) {
+        if let Some(context) = self.as_key_context() {
+            return (context, None);
+        }
+        if let Self::And(mut clauses) = self {
+            if let Some(index) = clauses.iter().position(|c| c.as_key_context().is_some()) {
+                let context = clauses.remove(index).as_key_context().unwrap();
+                let rest = if clauses.len() == 1 {
+                    clauses.pop().unwrap()
+                } else {
+                    Self::And(clauses)
+                };
+                return (context, Some(rest));
+            }
+            return Self::And(clauses).split_context_anywhere();
+        }
+        self.split_context_anywhere()
+    }
+
+    fn split_context_anywhere(self) -> (KeyContext, Option<WhenClause>) {
+        let context = if self.names_key_context() {
+            KeyContext::Global
+        } else {
+            KeyContext::Normal
+        };
+        (context, Some(self))
+    }
+
+    /// The key context this clause names on its own
+    fn as_key_context(&self) -> Option<KeyContext> {
+        match self {
+            Self::Key(key) => KeyContext::from_when_clause(key),
+            _ => None,
+        }
+    }
+
+    /// Whether the clause names a key context anywhere
+    fn names_key_context(&self) -> bool {
+        match self {
+            Self::Key(_) => self.as_key_context().is_some(),
+            Self::Equals(..) | Self::NotEquals(..) => false,
+            Self::Not(clause) => clause.names_key_context(),
+            Self::And(clauses) | Self::Or(clauses) => {
+                clauses.iter().any(WhenClause::names_key_context)
+            }
+        }
+    }
+}
+
+impl fmt::Display for WhenClause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key) => write!(f, "{}", key),
+            Self::Equals(key, value) => write!(f, "{} == {}", key, display_value(value)),
+            Self::NotEquals(key, value) => write!(f, "{} != {}", key, display_value(value)),
+            Self::Not(clause) => match clause.as_ref() {
+                Self::And(_) | Self::Or(_) | Self::Equals(..) | Self::NotEquals(..) => {
+                    write!(f, "!({})", clause)
+                }
+                _ => write!(f, "!{}", clause),
+            },
+            Self::And(clauses) => {
+                for (i, clause) in clauses.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " && ")?;
+                    }
+                    match clause {
+                        Self::Or(_) => write!(f, "({})", clause)?,
+                        _ => write!(f, "{}", clause)?,
+                    }
+                }
+                Ok(())
+            }
+            Self::Or(clauses) => {
+                for (i, clause) in clauses.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " || ")?;
+                    }
+                    write!(f, "{}", clause)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A value as written in a clause, quoted if it is not a plain word
+fn display_value(value: &str) -> String {
+    if !value.is_empty() && value.chars().all(is_word_char) {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value)
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    And,
+    Or,
+    Not,
+    Equals,
+    NotEquals,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Word(word) => write!(f, "\"{}\"", word),
+            Self::Quoted(text) => write!(f, "\"{}\"", text),
+            Self::And => write!(f, "\"&&\""),
+            Self::Or => write!(f, "\"||\""),
+            Self::Not => write!(f, "\"!\""),
+            Self::Equals => write!(f, "\"==\""),
+            Self::NotEquals => write!(f, "\"!=\""),
+            Self::Open => write!(f, "\"(\""),
+            Self::Close => write!(f, "\")\""),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            '=' if chars.next_if_eq(&'=').is_some() => Token::Equals,
+            '!' if chars.next_if_eq(&'=').is_some() => Token::NotEquals,
+            '!' => Token::Not,
+            '"' | '\'' => {
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some(end) if end == c => break,
+                        Some(ch) => quoted.push(ch),
+                        None => return Err(format!("unterminated string in \"{}\"", text)),
+                    }
+                }
+                Token::Quoted(quoted)
+            }
+            c if is_word_char(c) => {
+                let mut word = c.to_string();
+                while let Some(ch) = chars.next_if(|&ch| is_word_char(ch)) {
+                    word.push(ch);
+                }
+                Token::Word(word)
+            }
+            c => return Err(format!("unexpected '{}' in \"{}\"", c, text.trim())),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the tokens of a clause
+///
+/// ```text
+/// or      := and ('||' and)*
+/// and     := unary ('&&' unary)*
+/// unary   := '!' unary | primary
+/// primary := '(' or ')' | key [('==' | '!=') value]
+/// ```
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<WhenClause, String> {
+        let mut clauses = vec![self.parse_and()?];
+        while self.eat(&Token::Or) {
+            clauses.push(self.parse_and()?);
+        }
+        Ok(if clauses.len() == 1 {
+            clauses.pop().unwrap()
+        } else {
+            WhenClause::Or(clauses)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<WhenClause, String> {
+        let mut clauses = vec![self.parse_unary()?];
+        while self.eat(&Token::And) {
+            clauses.push(self.parse_unary()?);
+        }
+        Ok(if clauses.len() == 1 {
+            clauses.pop().unwrap()
+        } else {
+            WhenClause::And(clauses)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<WhenClause, String> {
+        if self.eat(&Token::Not) {
+            return Ok(WhenClause::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<WhenClause, String> {
+        match self.next() {
+            Some(Token::Open) => {
+                let clause = self.parse_or()?;
+                if !self.eat(&Token::Close) {
+                    return Err("missing \")\"".to_string());
+                }
+                Ok(clause)
+            }
+            Some(Token::Word(key)) => {
+                if self.eat(&Token::Equals) {
+                    Ok(WhenClause::Equals(key, self.parse_value()?))
+                } else if self.eat(&Token::NotEquals) {
+                    Ok(WhenClause::NotEquals(key, self.parse_value()?))
+                } else {
+                    Ok(WhenClause::Key(key))
+                }
+            }
+            Some(token) => Err(format!("expected a key, found {}", token)),
+            None => Err("expected a key, found the end of the clause".to_string()),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => Ok(value),
+            Some(token) => Err(format!("expected a value, found {}", token)),
+            None => Err("expected a value, found the end of the clause".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> WhenClause {
+        WhenClause::Key(name.to_string())
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(WhenClause::parse("  readOnly ").unwrap(), key("readOnly"));
+        assert_eq!(
+            WhenClause::parse("a || b && !c").unwrap(),
+            WhenClause::Or(vec![
+                key("a"),
+                WhenClause::And(vec![key("b"), WhenClause::Not(Box::new(key("c")))]),
+            ])
+        );
+        assert_eq!(
+            WhenClause::parse("(a || b) && editorLangId == rust").unwrap(),
+            WhenClause::And(vec![
+                WhenClause::Or(vec![key("a"), key("b")]),
+                WhenClause::Equals("editorLangId".to_string(), "rust".to_string()),
+            ])
+        );
+        assert_eq!(
+            WhenClause::parse("editorLangId != \"c++\"").unwrap(),
+            WhenClause::NotEquals("editorLangId".to_string(), "c++".to_string())
+        );
+
+        for invalid in ["", "a &&", "(a || b", "a b", "a & b", "== rust", "a == \"x"] {
+            assert!(WhenClause::parse(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut values = WhenContext::new();
+        values
+            .set_flag(keys::EDITOR_HAS_SELECTION, true)
+            .set_flag(keys::READ_ONLY, false)
+            .set(keys::EDITOR_LANG_ID, "rust");
+        let holds = |text: &str, context: KeyContext| {
+            WhenClause::parse(text).unwrap().evaluate(context, &values)
+        };
+
+        assert!(holds("editorHasSelection", KeyContext::Normal));
+        assert!(!holds("readOnly", KeyContext::Normal));
+        assert!(!holds("multiCursor", KeyContext::Normal));
+        assert!(holds("editorHasSelection && !readOnly", KeyContext::Normal));
+        assert!(holds("editorLangId == rust", KeyContext::Normal));
+        assert!(holds("editorLangId != 'python'", KeyContext::Normal));
+        assert!(!holds(
+            "editorLangId == python || multiCursor",
+            KeyContext::Normal
+        ));
+        assert!(holds("prompt || popup", KeyContext::Popup));
+        assert!(!holds("prompt || popup", KeyContext::Normal));
+        assert!(holds("global && !fileExplorer", KeyContext::Normal));
+    }
+
+    #[test]
+    fn test_split_context() {
+        let split = |text: &str| WhenClause::parse(text).unwrap().split_context();
+
+        assert_eq!(split("prompt"), (KeyContext::Prompt, None));
+        assert_eq!(
+            split("readOnly"),
+            (KeyContext::Normal, Some(key("readOnly")))
+        );
+        assert_eq!(
+            split("multiCursor && fileExplorer && readOnly"),
+            (
+                KeyContext::FileExplorer,
+                Some(WhenClause::And(vec![key("multiCursor"), key("readOnly")]))
+            )
+        );
+        assert_eq!(
+            split("prompt || popup"),
+            (
+                KeyContext::Global,
+                Some(WhenClause::parse("prompt || popup").unwrap())
+            )
+        );
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for text in [
+            "a && (b || !c)",
+            "!(editorLangId == rust) || mode != \"vi normal\"",
+            "fileExplorer && !readOnly",
+        ] {
+            let clause = WhenClause::parse(text).unwrap();
+            assert_eq!(clause.to_string(), text);
+            assert_eq!(WhenClause::parse(&clause.to_string()).unwrap(), clause);
+        }
+    }
+}
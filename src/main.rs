@@ -10,8 +10,11 @@ use crossterm::{
     ExecutableCommand,
 };
 use fresh::input::key_translator::KeyTranslator;
+use fresh::services::fs::{FsBackend, SftpClient, SftpFsBackend, SshTarget};
 #[cfg(target_os = "linux")]
 use fresh::services::gpm::{gpm_to_crossterm, GpmClient};
+#[cfg(unix)]
+use fresh::services::instance_server;
 use fresh::services::tracing_setup;
 use fresh::{
     app::Editor, config, config_io::DirectoryContext, services::release_checker,
@@ -20,7 +23,8 @@ use fresh::{
 use ratatui::Terminal;
 use std::{
     io::{self, stdout},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    sync::Arc,
     time::Duration,
 };
 
@@ -30,7 +34,8 @@ use std::{
 #[command(about = "A terminal text editor with multi-cursor support", long_about = None)]
 #[command(version)]
 struct Args {
-    /// Files to open (use "-" to read from stdin)
+    /// Files to open (use "-" to read from stdin, or ssh://[user@]host[:port]/path
+    /// to open a file or directory on another host)
     #[arg(value_name = "FILES")]
     files: Vec<String>,
 
@@ -65,10 +70,40 @@ struct Args {
     /// Print the directories used by Fresh and exit
     #[arg(long)]
     show_paths: bool,
+
+    /// Open the files in the editor running on this project (or the one
+    /// whose terminal this is run from) instead of starting a new editor
+    #[arg(long)]
+    remote: bool,
+
+    /// With --remote, wait until the files are closed in the editor
+    #[arg(long, requires = "remote")]
+    wait: bool,
+
+    /// Show the differences between two files, which can be edited
+    /// (usable as `git difftool`)
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["LEFT", "RIGHT"],
+        conflicts_with_all = ["files", "stdin", "remote"]
+    )]
+    diff: Option<Vec<PathBuf>>,
+
+    /// Merge the changes from BASE to LOCAL and to REMOTE into OUTPUT
+    /// (usable as `git mergetool`; exits with 1 unless OUTPUT was saved
+    /// without conflicts)
+    #[arg(
+        long,
+        num_args = 4,
+        value_names = ["BASE", "LOCAL", "REMOTE", "OUTPUT"],
+        conflicts_with_all = ["files", "stdin", "remote", "diff"]
+    )]
+    merge: Option<Vec<PathBuf>>,
 }
 
 /// Parsed file location from CLI argument in file:line:col format
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct FileLocation {
     path: PathBuf,
     line: Option<usize>,
@@ -90,6 +125,8 @@ struct SetupState {
     show_file_explorer: bool,
     dir_context: DirectoryContext,
     current_working_dir: Option<PathBuf>,
+    /// Filesystem of the host given with an `ssh://` argument
+    fs_backend: Option<Arc<dyn FsBackend>>,
     /// Stdin streaming state (if --stdin flag or "-" file was used)
     /// Contains temp file path and background thread handle
     stdin_stream: Option<StdinStreamState>,
@@ -279,6 +316,14 @@ fn handle_first_run_setup(
         }
     }
 
+    if let Some([left, right]) = args.diff.as_deref() {
+        editor.open_diff(left, right)?;
+    }
+
+    if let Some([base, local, remote, output]) = args.merge.as_deref() {
+        editor.open_merge(base, local, remote, output)?;
+    }
+
     if show_file_explorer {
         editor.show_file_explorer();
     }
@@ -457,20 +502,39 @@ fn initialize_app(args: &Args) -> io::Result<SetupState> {
         None
     };
 
+    // Connect to the host of an ssh:// argument while the terminal is still
+    // usable for ssh's prompts
+    let remote = connect_remote(args).inspect_err(|e| eprintln!("Error: {}", e))?;
+
     // Determine working directory early for config loading
     // Filter out "-" from files list since it's handled via stdin_stream
-    let file_locations: Vec<FileLocation> = args
-        .files
-        .iter()
-        .filter(|f| *f != "-")
-        .map(|f| parse_file_location(f))
-        .collect();
+    let file_locations: Vec<FileLocation> = match &remote {
+        Some(remote) if remote.is_dir => Vec::new(),
+        Some(remote) => vec![remote.location.clone()],
+        None => args
+            .files
+            .iter()
+            .filter(|f| *f != "-")
+            .map(|f| parse_file_location(f))
+            .collect(),
+    };
 
     let mut working_dir = None;
     let mut show_file_explorer = false;
 
+    // A remote directory is opened as the project, a remote file in its directory
+    if let Some(remote) = &remote {
+        let path = &remote.location.path;
+        working_dir = if remote.is_dir {
+            show_file_explorer = true;
+            Some(path.clone())
+        } else {
+            path.parent().map(Path::to_path_buf)
+        };
+    }
+
     // Only set working_dir if exactly one parameter is passed and it's a directory
-    if file_locations.len() == 1 {
+    if remote.is_none() && file_locations.len() == 1 {
         if let Some(first_loc) = file_locations.first() {
             if first_loc.path.is_dir() {
                 working_dir = Some(first_loc.path.clone());
@@ -480,8 +544,10 @@ fn initialize_app(args: &Args) -> io::Result<SetupState> {
     }
 
     // Load config using the layered config system
+    // (project config of a remote working directory isn't read)
     let effective_working_dir = working_dir
         .as_ref()
+        .filter(|_| remote.is_none())
         .cloned()
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
@@ -569,12 +635,104 @@ fn initialize_app(args: &Args) -> io::Result<SetupState> {
         show_file_explorer,
         dir_context,
         current_working_dir,
+        fs_backend: remote.map(|remote| remote.backend),
         stdin_stream,
         key_translator,
         gpm_client,
     })
 }
 
+/// A file or directory on another host, given as an `ssh://` argument
+struct RemoteLocation {
+    backend: Arc<dyn FsBackend>,
+    /// Location with the absolute path on the host
+    location: FileLocation,
+    is_dir: bool,
+}
+
+/// Connect to the host of an `ssh://` argument, if there is one
+fn connect_remote(args: &Args) -> io::Result<Option<RemoteLocation>> {
+    let Some(url) = args.files.iter().find(|f| f.starts_with("ssh://")) else {
+        return Ok(None);
+    };
+    if args.files.len() > 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "an ssh:// location must be the only file argument",
+        ));
+    }
+    let target = SshTarget::parse(url).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "invalid location {} (expected ssh://[user@]host[:port]/path)",
+                url
+            ),
+        )
+    })?;
+    let location = parse_file_location(&target.path);
+
+    let mut client = SftpClient::connect(&target)?;
+    let path = match client.realpath(&location.path) {
+        Ok(path) => path,
+        // A file that doesn't exist yet is created on save, in an existing directory
+        Err(_) => {
+            let parent = location
+                .path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            let name = location.path.file_name().unwrap_or_default();
+            client.realpath(parent)?.join(name)
+        }
+    };
+    let is_dir = client.stat(&path).is_ok_and(|attrs| attrs.is_dir());
+    tracing::info!("Opening {} on {}", path.display(), target.host);
+
+    Ok(Some(RemoteLocation {
+        backend: Arc::new(SftpFsBackend::new(client)),
+        location: FileLocation { path, ..location },
+        is_dir,
+    }))
+}
+
+/// Send the files to the editor running on this project, if there is one
+///
+/// Returns `None` when there's no editor to send them to, and a new one
+/// should be started instead.
+#[cfg(unix)]
+fn open_in_running_editor(args: &Args) -> Option<io::Result<()>> {
+    // Stdin and remote hosts are only read by a new editor
+    if args.stdin
+        || args
+            .files
+            .iter()
+            .any(|f| f == "-" || f.starts_with("ssh://"))
+    {
+        return None;
+    }
+    let cwd = std::env::current_dir().ok()?;
+    let stream = instance_server::connect(&cwd)?;
+
+    let files = args
+        .files
+        .iter()
+        .map(|f| {
+            let location = parse_file_location(f);
+            instance_server::OpenFile {
+                path: cwd.join(location.path),
+                line: location.line,
+                column: location.column,
+            }
+        })
+        .collect();
+    let request = instance_server::OpenRequest {
+        files,
+        wait: args.wait,
+    };
+    Some(instance_server::send_request(stream, &request))
+}
+
 #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
 fn run_editor_iteration(
     editor: &mut Editor,
@@ -651,6 +809,14 @@ fn main() -> io::Result<()> {
         }
     }
 
+    // Hand the files to a running editor if there is one
+    #[cfg(unix)]
+    if args.remote {
+        if let Some(result) = open_in_running_editor(&args) {
+            return result.inspect_err(|e| eprintln!("Error: {}", e));
+        }
+    }
+
     let SetupState {
         config,
         mut warning_log_handle,
@@ -660,6 +826,7 @@ fn main() -> io::Result<()> {
         show_file_explorer,
         dir_context,
         current_working_dir: initial_working_dir,
+        fs_backend,
         mut stdin_stream,
         key_translator,
         #[cfg(target_os = "linux")]
@@ -668,6 +835,13 @@ fn main() -> io::Result<()> {
         gpm_client,
     } = initialize_app(&args)?;
 
+    // The merge output counts as resolved once it is saved without conflicts
+    let merge_output = args.merge.as_deref().map(|paths| {
+        let output = paths[3].clone();
+        let modified = std::fs::metadata(&output).and_then(|m| m.modified()).ok();
+        (output, modified)
+    });
+
     let mut current_working_dir = initial_working_dir;
     let (terminal_width, terminal_height) = terminal_size;
 
@@ -681,7 +855,12 @@ fn main() -> io::Result<()> {
     // Returns (loop_result, last_update_result) tuple
     let (result, last_update_result) = loop {
         let first_run = is_first_run;
-        let session_enabled = !args.no_session && file_locations.is_empty();
+        // Sessions are kept for local projects only
+        let session_enabled = !args.no_session
+            && file_locations.is_empty()
+            && args.diff.is_none()
+            && args.merge.is_none()
+            && fs_backend.is_none();
 
         // Detect terminal color capability
         let color_capability = fresh::view::color_support::ColorCapability::detect();
@@ -694,6 +873,7 @@ fn main() -> io::Result<()> {
             dir_context.clone(),
             !args.no_plugins,
             color_capability,
+            fs_backend.clone(),
         )?;
 
         #[cfg(target_os = "linux")]
@@ -701,6 +881,15 @@ fn main() -> io::Result<()> {
             editor.set_gpm_active(true);
         }
 
+        // Let `fresh --remote` open files in this editor
+        #[cfg(unix)]
+        if fs_backend.is_none() {
+            let socket = instance_server::socket_path(editor.working_dir());
+            if let Err(e) = editor.start_instance_server(&socket) {
+                tracing::info!("Not listening for files to open: {}", e);
+            }
+        }
+
         if first_run {
             handle_first_run_setup(
                 &mut editor,
@@ -798,9 +987,28 @@ fn main() -> io::Result<()> {
         }
     }
 
+    // Tell `git mergetool` whether the merge was resolved
+    if let Some((output, modified_before)) = merge_output {
+        if result.is_ok() && !merge_resolved(&output, modified_before) {
+            std::process::exit(1);
+        }
+    }
+
     result
 }
 
+/// Whether the output of `--merge` was saved since `modified_before`, with
+/// no conflict markers left
+fn merge_resolved(output: &Path, modified_before: Option<std::time::SystemTime>) -> bool {
+    let saved = std::fs::metadata(output)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| Some(modified) != modified_before);
+    saved
+        && std::fs::read(output).is_ok_and(|content| {
+            fresh::model::merge::conflict_blocks(&String::from_utf8_lossy(&content)).is_empty()
+        })
+}
+
 /// Main event loop
 #[cfg(target_os = "linux")]
 fn run_event_loop(
@@ -890,6 +1098,7 @@ where
         }
 
         if editor.should_quit() {
+            editor.save_undo_histories();
             if session_enabled {
                 if let Err(e) = editor.save_session() {
                     tracing::warn!("Failed to save session: {}", e);
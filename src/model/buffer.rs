@@ -1,14 +1,16 @@
 /// Text buffer that uses PieceTree with integrated line tracking
 /// Architecture where the tree is the single source of truth for text and line information
+use crate::model::encoding::{Encoding, ENCODING_SAMPLE_SIZE};
 use crate::model::piece_tree::{
     BufferData, BufferLocation, Cursor, PieceInfo, PieceRangeIter, PieceTree, Position,
     StringBuffer, TreeStats,
 };
 use crate::model::piece_tree_diff::PieceTreeDiff;
 use crate::primitives::grapheme;
+use crate::services::fs::BlockingFs;
 use anyhow::{Context, Result};
 use regex::bytes::Regex;
-use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::io::{self, Write};
 use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -101,6 +103,30 @@ impl LineNumber {
     }
 }
 
+/// The buffer content written to a private temp file by
+/// `TextBuffer::write_save_copy`
+///
+/// The temp file is removed when the copy is dropped.
+#[derive(Debug)]
+pub struct SaveCopy {
+    /// The temp file holding the content
+    pub temp_path: PathBuf,
+    /// The file the content is saved to
+    pub dest_path: PathBuf,
+    /// Metadata of the file being replaced, if it exists
+    pub original_metadata: Option<std::fs::Metadata>,
+    /// The tree that was written
+    root: Arc<crate::model::piece_tree::PieceTreeNode>,
+    /// The line ending the content was written with
+    line_ending: LineEnding,
+}
+
+impl Drop for SaveCopy {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.temp_path);
+    }
+}
+
 /// A text buffer that manages document content using a piece table
 /// with integrated line tracking
 pub struct TextBuffer {
@@ -144,10 +170,17 @@ pub struct TextBuffer {
     /// changed the line ending format and needs conversion on save.
     original_line_ending: LineEnding,
 
+    /// Encoding of the file on disk (the buffer itself always holds UTF-8)
+    encoding: Encoding,
+
     /// The file size on disk after the last save.
     /// Used for chunked recovery to know the original file size for reconstruction.
     /// Updated when loading from file or after saving.
     saved_file_size: Option<usize>,
+
+    /// Filesystem the file is read from and saved to, `BlockingFs::local`
+    /// if not set
+    fs: Option<BlockingFs>,
 }
 
 impl TextBuffer {
@@ -168,7 +201,9 @@ impl TextBuffer {
             is_binary: false,
             line_ending,
             original_line_ending: line_ending,
+            encoding: Encoding::default(),
             saved_file_size: None,
+            fs: None,
         }
     }
 
@@ -194,6 +229,7 @@ impl TextBuffer {
         TextBuffer {
             line_ending,
             original_line_ending: line_ending,
+            encoding: Encoding::default(),
             piece_tree,
             saved_root,
             buffers: vec![buffer],
@@ -204,6 +240,7 @@ impl TextBuffer {
             large_file: false,
             is_binary: false,
             saved_file_size: Some(bytes), // Treat initial content as "saved" state
+            fs: None,
         }
     }
 
@@ -229,20 +266,58 @@ impl TextBuffer {
             is_binary: false,
             line_ending,
             original_line_ending: line_ending,
+            encoding: Encoding::default(),
             saved_file_size: None,
+            fs: None,
         }
     }
 
-    /// Load a text buffer from a file
+    /// Load a text buffer from a file, detecting its encoding
     pub fn load_from_file<P: AsRef<Path>>(
         path: P,
         large_file_threshold: usize,
     ) -> io::Result<Self> {
-        let path = path.as_ref();
+        Self::load_from_file_with_encoding(path, large_file_threshold, None)
+    }
+
+    /// Load a text buffer from a file in the given encoding (detected if None)
+    ///
+    /// Fails with `InvalidData` if the file is not valid in the given encoding.
+    pub fn load_from_file_with_encoding<P: AsRef<Path>>(
+        path: P,
+        large_file_threshold: usize,
+        encoding: Option<Encoding>,
+    ) -> io::Result<Self> {
+        Self::load(
+            BlockingFs::local(),
+            path.as_ref(),
+            large_file_threshold,
+            encoding,
+        )
+    }
+
+    /// Load a text buffer from a file on `fs`, e.g. a remote host
+    ///
+    /// The buffer keeps `fs` to load the rest of large files and to save.
+    pub fn load_from_backend(
+        fs: BlockingFs,
+        path: &Path,
+        large_file_threshold: usize,
+        encoding: Option<Encoding>,
+    ) -> io::Result<Self> {
+        let mut buffer = Self::load(&fs, path, large_file_threshold, encoding)?;
+        buffer.fs = Some(fs);
+        Ok(buffer)
+    }
 
+    fn load(
+        fs: &BlockingFs,
+        path: &Path,
+        large_file_threshold: usize,
+        encoding: Option<Encoding>,
+    ) -> io::Result<Self> {
         // Get file size to determine loading strategy
-        let metadata = std::fs::metadata(path)?;
-        let file_size = metadata.len() as usize;
+        let file_size = fs.size(path)? as usize;
 
         // Use threshold parameter or default
         let threshold = if large_file_threshold > 0 {
@@ -253,18 +328,67 @@ impl TextBuffer {
 
         // Choose loading strategy based on file size
         if file_size >= threshold {
-            Self::load_large_file(path, file_size)
+            // Only UTF-8 can be loaded lazily, other encodings are converted up front
+            let encoding = match encoding {
+                Some(encoding) => encoding,
+                None => {
+                    let sample = fs.read_range(path, 0, ENCODING_SAMPLE_SIZE)?;
+                    Self::detect_encoding(&sample)
+                }
+            };
+            if encoding == Encoding::Utf8 {
+                return Self::load_large_file(fs, path, file_size);
+            }
+            tracing::info!(
+                "Loading large {} file {} into memory",
+                encoding.display_name(),
+                path.display()
+            );
+            Self::load_small_file(fs, path, Some(encoding))
         } else {
-            Self::load_small_file(path)
+            Self::load_small_file(fs, path, encoding)
         }
     }
 
     /// Load a small file with full eager loading and line indexing
-    fn load_small_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
-        let path = path.as_ref();
-        let mut file = std::fs::File::open(path)?;
-        let mut contents = Vec::new();
-        file.read_to_end(&mut contents)?;
+    fn load_small_file(
+        fs: &BlockingFs,
+        path: &Path,
+        encoding: Option<Encoding>,
+    ) -> io::Result<Self> {
+        let contents = fs.read(path)?;
+
+        // Convert the content to UTF-8
+        let (encoding, contents) = match encoding {
+            Some(encoding) => match encoding.decode(&contents) {
+                Some(decoded) => (encoding, decoded.into_owned()),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("File is not valid {}", encoding.display_name()),
+                    ))
+                }
+            },
+            None => {
+                let detected =
+                    Self::detect_encoding(&contents[..contents.len().min(ENCODING_SAMPLE_SIZE)]);
+                match detected.decode(&contents) {
+                    Some(decoded) => (detected, decoded.into_owned()),
+                    // Binary, or invalid past the sample; the bytes are kept as they are
+                    None if detected.is_utf8() => {
+                        let mut contents = contents;
+                        contents.drain(..detected.bom().len());
+                        (detected, contents)
+                    }
+                    // A wrong guess; Latin-1 accepts any bytes and saves them back unchanged
+                    None => {
+                        let decoded = Encoding::Latin1.decode(&contents);
+                        let decoded = decoded.expect("Latin-1 decodes any bytes");
+                        (Encoding::Latin1, decoded.into_owned())
+                    }
+                }
+            }
+        };
 
         // Detect if this is a binary file
         let is_binary = Self::detect_binary(&contents);
@@ -280,22 +404,18 @@ impl TextBuffer {
         buffer.is_binary = is_binary;
         buffer.line_ending = line_ending;
         buffer.original_line_ending = line_ending;
+        buffer.encoding = encoding;
         Ok(buffer)
     }
 
     /// Load a large file with unloaded buffer (no line indexing, lazy loading)
-    fn load_large_file<P: AsRef<Path>>(path: P, file_size: usize) -> io::Result<Self> {
+    fn load_large_file(fs: &BlockingFs, path: &Path, file_size: usize) -> io::Result<Self> {
         use crate::model::piece_tree::{BufferData, BufferLocation};
 
-        let path = path.as_ref();
-
         // Read a sample of the file to detect if it's binary and line ending format
         // We read the first 8KB for both binary and line ending detection
         let (is_binary, line_ending) = {
-            let mut file = std::fs::File::open(path)?;
-            let sample_size = file_size.min(8 * 1024);
-            let mut sample = vec![0u8; sample_size];
-            file.read_exact(&mut sample)?;
+            let sample = fs.read_range(path, 0, file_size.min(8 * 1024))?;
             let is_binary = Self::detect_binary(&sample);
             let line_ending = Self::detect_line_ending(&sample);
             (is_binary, line_ending)
@@ -338,7 +458,9 @@ impl TextBuffer {
             is_binary,
             line_ending,
             original_line_ending: line_ending,
+            encoding: Encoding::default(),
             saved_file_size: Some(file_size),
+            fs: None,
         })
     }
 
@@ -356,44 +478,159 @@ impl TextBuffer {
 
     /// Save the buffer to a specific file
     ///
-    /// This uses incremental saving for large files: instead of loading the entire
-    /// file into memory, it streams unmodified regions directly from the source file
-    /// and only keeps edited regions in memory.
+    /// Unloaded regions of large files are read from their source file while
+    /// the content is put together, so saving works on partially loaded
+    /// files. The filesystem then replaces the file, keeping its metadata.
     ///
     /// If the line ending format has been changed (via set_line_ending), all content
-    /// will be converted to the new format during save.
+    /// will be converted to the new format during save. Content is written in the
+    /// buffer's encoding.
     pub fn save_to_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
         let dest_path = path.as_ref();
-        let total = self.total_bytes();
-
-        // Get original file metadata (permissions, owner, etc.) before writing
-        // so we can preserve it after creating/renaming the temp file
-        let original_metadata = std::fs::metadata(dest_path).ok();
 
         // Check if we need to convert line endings
         let needs_conversion = self.line_ending != self.original_line_ending;
+        let mut content = self.encoding.bom().to_vec();
+        content.extend(self.encoded_content(needs_conversion, self.line_ending)?);
+        self.fs().write(dest_path, &content)?;
+
+        tracing::debug!(
+            "Buffer::save: updating saved_file_size from {:?} to {}",
+            self.saved_file_size,
+            content.len()
+        );
+        self.saved_file_size = Some(content.len());
+        self.file_path = Some(dest_path.to_path_buf());
+        self.mark_saved_snapshot();
+
+        // Update original_line_ending to match what we just saved
+        // This prevents repeated conversions on subsequent saves
+        self.original_line_ending = self.line_ending;
+
+        Ok(())
+    }
+
+    /// Write the buffer to a private temp file, to be put in place at
+    /// `dest_path` by something that can write there, e.g. through `sudo`
+    ///
+    /// The install step is expected to keep the metadata of the file it
+    /// replaces, like a normal save does. Once it succeeded, pass
+    /// the copy to `finish_save_copy` to mark the buffer saved.
+    pub fn write_save_copy(&self, dest_path: &Path) -> io::Result<SaveCopy> {
+        if self.is_remote() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "remote files can't be saved through a local helper",
+            ));
+        }
+        let needs_conversion = self.line_ending != self.original_line_ending;
         let target_ending = self.line_ending;
 
-        if total == 0 {
-            // Empty file - just create it
-            std::fs::File::create(dest_path)?;
-            if let Some(ref meta) = original_metadata {
-                Self::restore_file_metadata(dest_path, meta)?;
-            }
-            self.file_path = Some(dest_path.to_path_buf());
-            self.mark_saved_snapshot();
-            self.saved_file_size = Some(0);
-            // Update original_line_ending to match the new format
-            self.original_line_ending = self.line_ending;
-            return Ok(());
+        let temp_path = std::env::temp_dir().join(format!(
+            "fresh-save-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default()
+        ));
+        // Only readable by us: the content may be something we can't read
+        // once it is installed
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let copy = SaveCopy {
+            temp_path,
+            dest_path: dest_path.to_path_buf(),
+            original_metadata: std::fs::metadata(dest_path).ok(),
+            root: self.piece_tree.root(),
+            line_ending: target_ending,
+        };
+        self.write_encoded_file(
+            || options.open(&copy.temp_path),
+            needs_conversion,
+            target_ending,
+        )?;
+        Ok(copy)
+    }
+
+    /// Mark the content of `copy` as saved to its destination, after it was
+    /// installed there
+    ///
+    /// Returns whether the buffer is still unchanged since the copy was
+    /// written; otherwise it stays modified.
+    pub fn finish_save_copy(&mut self, copy: &SaveCopy) -> bool {
+        self.saved_file_size = std::fs::metadata(&copy.dest_path)
+            .ok()
+            .map(|meta| meta.len() as usize);
+        self.file_path = Some(copy.dest_path.clone());
+        self.saved_root = copy.root.clone();
+        self.original_line_ending = copy.line_ending;
+        let unchanged = Arc::ptr_eq(&copy.root, &self.piece_tree.root())
+            && self.line_ending == copy.line_ending;
+        if unchanged {
+            self.modified = false;
+        }
+        unchanged
+    }
+
+    /// Write the buffer content in its encoding, byte order mark included, to
+    /// the file made by `create` and sync it to disk
+    ///
+    /// The content is encoded before the file is created, so unencodable
+    /// content fails cleanly.
+    fn write_encoded_file(
+        &self,
+        create: impl FnOnce() -> io::Result<std::fs::File>,
+        needs_conversion: bool,
+        target_ending: LineEnding,
+    ) -> io::Result<()> {
+        let encoded = if self.encoding.is_utf8() {
+            None
+        } else {
+            Some(self.encoded_content(needs_conversion, target_ending)?)
+        };
+
+        let mut out_file = create()?;
+        out_file.write_all(self.encoding.bom())?;
+        match encoded {
+            Some(encoded) => out_file.write_all(&encoded)?,
+            None => self.write_content(&mut out_file, needs_conversion, target_ending)?,
         }
 
-        // Use a temp file to avoid corrupting the original if something goes wrong
-        let temp_path = dest_path.with_extension("tmp");
-        let mut out_file = std::fs::File::create(&temp_path)?;
+        // Ensure all data is written
+        out_file.sync_all()
+    }
 
-        // Cache for open source files (for streaming unloaded regions)
-        let mut source_file_cache: Option<(PathBuf, std::fs::File)> = None;
+    /// The buffer content in its encoding, without byte order mark
+    fn encoded_content(
+        &self,
+        needs_conversion: bool,
+        target_ending: LineEnding,
+    ) -> io::Result<Vec<u8>> {
+        let mut content = Vec::with_capacity(self.total_bytes());
+        self.write_content(&mut content, needs_conversion, target_ending)?;
+        if self.encoding.is_utf8() {
+            return Ok(content);
+        }
+        Ok(self.encoding.encode(&content)?.into_owned())
+    }
+
+    /// Write the buffer content (UTF-8), converting line endings if needed
+    ///
+    /// Unloaded regions are streamed from their source file.
+    fn write_content<W: Write>(
+        &self,
+        out: &mut W,
+        needs_conversion: bool,
+        target_ending: LineEnding,
+    ) -> io::Result<()> {
+        let total = self.total_bytes();
+        let fs = self.fs();
 
         // Iterate through all pieces and write them
         for piece_view in self.piece_tree.iter_pieces_in_range(0, total) {
@@ -414,10 +651,10 @@ impl TextBuffer {
                     if needs_conversion {
                         // Convert line endings before writing
                         let converted = Self::convert_line_endings_to(chunk, target_ending);
-                        out_file.write_all(&converted)?;
+                        out.write_all(&converted)?;
                     } else {
                         // Write directly without conversion
-                        out_file.write_all(chunk)?;
+                        out.write_all(chunk)?;
                     }
                 }
                 BufferData::Unloaded {
@@ -425,37 +662,23 @@ impl TextBuffer {
                     file_offset,
                     ..
                 } => {
-                    // Stream from source file
-                    let source_file = match &mut source_file_cache {
-                        Some((cached_path, file)) if cached_path == file_path => file,
-                        _ => {
-                            let file = std::fs::File::open(file_path)?;
-                            source_file_cache = Some((file_path.clone(), file));
-                            &mut source_file_cache.as_mut().unwrap().1
-                        }
-                    };
-
-                    // Seek to the right position in source file
-                    let read_offset = *file_offset + piece_view.buffer_offset;
-                    source_file.seek(SeekFrom::Start(read_offset as u64))?;
-
-                    // Stream in chunks
+                    // Stream from source file, in chunks
                     const STREAM_CHUNK_SIZE: usize = 64 * 1024; // 64KB chunks
+                    let mut read_offset = (*file_offset + piece_view.buffer_offset) as u64;
                     let mut remaining = piece_view.bytes;
-                    let mut chunk_buf = vec![0u8; STREAM_CHUNK_SIZE.min(remaining)];
 
                     while remaining > 0 {
-                        let to_read = remaining.min(chunk_buf.len());
-                        source_file.read_exact(&mut chunk_buf[..to_read])?;
+                        let to_read = remaining.min(STREAM_CHUNK_SIZE);
+                        let chunk = fs.read_exact_at(file_path, read_offset, to_read)?;
+                        read_offset += to_read as u64;
 
                         if needs_conversion {
                             // Convert line endings before writing
-                            let converted =
-                                Self::convert_line_endings_to(&chunk_buf[..to_read], target_ending);
-                            out_file.write_all(&converted)?;
+                            let converted = Self::convert_line_endings_to(&chunk, target_ending);
+                            out.write_all(&converted)?;
                         } else {
                             // Write directly without conversion
-                            out_file.write_all(&chunk_buf[..to_read])?;
+                            out.write_all(&chunk)?;
                         }
                         remaining -= to_read;
                     }
@@ -463,58 +686,6 @@ impl TextBuffer {
             }
         }
 
-        // Ensure all data is written
-        out_file.sync_all()?;
-        drop(out_file);
-
-        // Restore original file permissions/owner before renaming
-        if let Some(ref meta) = original_metadata {
-            Self::restore_file_metadata(&temp_path, meta)?;
-        }
-
-        // Atomically replace the original file
-        std::fs::rename(&temp_path, dest_path)?;
-
-        // Update saved file size to match the file on disk
-        let new_size = std::fs::metadata(dest_path)?.len() as usize;
-        tracing::debug!(
-            "Buffer::save: updating saved_file_size from {:?} to {}",
-            self.saved_file_size,
-            new_size
-        );
-        self.saved_file_size = Some(new_size);
-
-        self.file_path = Some(dest_path.to_path_buf());
-        self.mark_saved_snapshot();
-
-        // Update original_line_ending to match what we just saved
-        // This prevents repeated conversions on subsequent saves
-        self.original_line_ending = self.line_ending;
-
-        Ok(())
-    }
-
-    /// Restore file metadata (permissions, owner/group) from original file
-    fn restore_file_metadata(path: &Path, original_meta: &std::fs::Metadata) -> io::Result<()> {
-        // Restore permissions (works cross-platform)
-        std::fs::set_permissions(path, original_meta.permissions())?;
-
-        // On Unix, also restore owner and group
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::MetadataExt;
-            let uid = original_meta.uid();
-            let gid = original_meta.gid();
-            // Use libc to set owner/group - ignore errors since we may not have permission
-            // (e.g., only root can chown to a different user)
-            unsafe {
-                use std::os::unix::ffi::OsStrExt;
-                let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
-                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
-                libc::chown(c_path.as_ptr(), uid, gid);
-            }
-        }
-
         Ok(())
     }
 
@@ -970,6 +1141,19 @@ impl TextBuffer {
         Arc::new(self.piece_tree.clone())
     }
 
+    /// Get the content of a piece tree snapshot (from `snapshot_piece_tree`)
+    /// Returns None if any buffers are unloaded (lazy loading)
+    pub fn snapshot_text(&self, tree: &PieceTree) -> Option<Vec<u8>> {
+        self.get_tree_text_range(tree, 0, tree.total_bytes())
+    }
+
+    /// Get the root of the current piece tree
+    /// Every edit builds a new root, so `Arc::ptr_eq` on two roots tells whether
+    /// the content may have changed in between - O(1)
+    pub fn content_root(&self) -> Arc<crate::model::piece_tree::PieceTreeNode> {
+        self.piece_tree.root()
+    }
+
     /// Apply bulk edits efficiently in a single pass
     /// Returns the net change in bytes
     pub fn apply_bulk_edits(&mut self, edits: &[(usize, usize, &str)]) -> isize {
@@ -1014,6 +1198,17 @@ impl TextBuffer {
     /// Returns None if any buffer in the range is unloaded
     /// PRIVATE: External code should use get_text_range_mut() which handles lazy loading
     fn get_text_range(&self, offset: usize, bytes: usize) -> Option<Vec<u8>> {
+        self.get_tree_text_range(&self.piece_tree, offset, bytes)
+    }
+
+    /// Get text from a byte offset range of `tree`, which must be this buffer's
+    /// piece tree or a snapshot of it
+    fn get_tree_text_range(
+        &self,
+        tree: &PieceTree,
+        offset: usize,
+        bytes: usize,
+    ) -> Option<Vec<u8>> {
         if bytes == 0 {
             return Some(Vec::new());
         }
@@ -1023,7 +1218,7 @@ impl TextBuffer {
         let mut collected = 0;
 
         // Use the efficient piece iterator (single O(log n) traversal + O(N) iteration)
-        for piece_view in self.piece_tree.iter_pieces_in_range(offset, end_offset) {
+        for piece_view in tree.iter_pieces_in_range(offset, end_offset) {
             let buffer_id = piece_view.location.buffer_id();
             if let Some(buffer) = self.buffers.get(buffer_id) {
                 // Calculate the range to read from this piece
@@ -1152,22 +1347,26 @@ impl TextBuffer {
                         );
 
                         // Load the chunk buffer
-                        self.buffers
-                            .get_mut(new_buffer_id)
-                            .context("Chunk buffer not found")?
-                            .load()
-                            .context("Failed to load chunk")?;
+                        load_buffer(
+                            self.buffers
+                                .get_mut(new_buffer_id)
+                                .context("Chunk buffer not found")?,
+                            self.fs.as_ref(),
+                        )
+                        .context("Failed to load chunk")?;
 
                         // Restart iteration with the modified tree
                         restarted_iteration = true;
                         break;
                     } else {
                         // Piece is small enough, load the entire buffer
-                        self.buffers
-                            .get_mut(buffer_id)
-                            .context("Buffer not found")?
-                            .load()
-                            .context("Failed to load buffer")?;
+                        load_buffer(
+                            self.buffers
+                                .get_mut(buffer_id)
+                                .context("Buffer not found")?,
+                            self.fs.as_ref(),
+                        )
+                        .context("Failed to load buffer")?;
                     }
                 }
 
@@ -1305,6 +1504,22 @@ impl TextBuffer {
         self.file_path.as_deref()
     }
 
+    /// Whether the buffer's file is on a filesystem that isn't local
+    pub fn is_remote(&self) -> bool {
+        self.fs.as_ref().is_some_and(|fs| !fs.is_local())
+    }
+
+    /// The filesystem the buffer's file is read from and saved to
+    fn fs(&self) -> &BlockingFs {
+        self.fs.as_ref().unwrap_or_else(|| BlockingFs::local())
+    }
+
+    /// Read and save the buffer's file on `fs`, e.g. the editor's backend for
+    /// a new file
+    pub fn set_fs(&mut self, fs: BlockingFs) {
+        self.fs = Some(fs);
+    }
+
     /// Set the file path for this buffer
     pub fn set_file_path(&mut self, path: PathBuf) {
         self.file_path = Some(path);
@@ -1461,6 +1676,21 @@ impl TextBuffer {
         self.recovery_pending = true;
     }
 
+    /// Get the encoding the file is saved in
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Set the encoding the file is saved in
+    ///
+    /// Like `set_line_ending`, this marks the buffer as modified; the content
+    /// is converted to the new encoding on save.
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.encoding = encoding;
+        self.modified = true;
+        self.recovery_pending = true;
+    }
+
     /// Set the default line ending format for a new/empty buffer
     ///
     /// Unlike `set_line_ending`, this does NOT mark the buffer as modified.
@@ -1537,6 +1767,17 @@ impl TextBuffer {
         false
     }
 
+    /// Detect the encoding of a file from a sample of its bytes
+    ///
+    /// Binary content is kept as it is (UTF-8) unless it starts with a byte
+    /// order mark, since UTF-16 text is full of null bytes.
+    pub fn detect_encoding(bytes: &[u8]) -> Encoding {
+        if Encoding::from_bom(bytes).is_none() && Self::detect_binary(bytes) {
+            return Encoding::Utf8;
+        }
+        Encoding::detect(bytes)
+    }
+
     /// Detect the line ending format from a sample of bytes
     ///
     /// Uses majority voting: counts CRLF, LF-only, and CR-only occurrences
@@ -1791,6 +2032,106 @@ impl TextBuffer {
         None
     }
 
+    /// Read a byte range without loading it into the buffer
+    ///
+    /// Unlike `get_text_range_mut`, unloaded regions are read from their source
+    /// file and not kept, so large files can be scanned without growing the
+    /// buffer's memory use.
+    pub fn read_range(&self, offset: usize, bytes: usize) -> io::Result<Vec<u8>> {
+        let end = offset.saturating_add(bytes).min(self.total_bytes());
+        let mut result = Vec::with_capacity(end.saturating_sub(offset));
+        let fs = self.fs();
+
+        for piece_view in self.piece_tree.iter_pieces_in_range(offset, end) {
+            let buffer_id = piece_view.location.buffer_id();
+            let buffer = self.buffers.get(buffer_id).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Buffer {} not found", buffer_id),
+                )
+            })?;
+
+            // Clip the piece to the requested range
+            let start_in_piece = offset.saturating_sub(piece_view.doc_offset);
+            let end_in_piece = (end - piece_view.doc_offset).min(piece_view.bytes);
+            if end_in_piece <= start_in_piece {
+                continue;
+            }
+            let start = piece_view.buffer_offset + start_in_piece;
+            let len = end_in_piece - start_in_piece;
+
+            match &buffer.data {
+                BufferData::Loaded { data, .. } => {
+                    result.extend_from_slice(&data[start..start + len]);
+                }
+                BufferData::Unloaded {
+                    file_path,
+                    file_offset,
+                    ..
+                } => {
+                    result.extend(fs.read_exact_at(
+                        file_path,
+                        (*file_offset + start) as u64,
+                        len,
+                    )?);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Find a byte pattern in `range`, searching forward from its start, or
+    /// backward from its end for the last occurrence
+    ///
+    /// Reads with `read_range`, so even multi-GB files are searched without
+    /// loading them.
+    pub fn find_bytes(
+        &self,
+        pattern: &[u8],
+        range: Range<usize>,
+        backward: bool,
+    ) -> io::Result<Option<usize>> {
+        const CHUNK_SIZE: usize = 1024 * 1024;
+        let end = range.end.min(self.total_bytes());
+        if pattern.is_empty() || range.start >= end {
+            return Ok(None);
+        }
+        // Consecutive chunks overlap, so matches across chunk boundaries are found
+        let overlap = pattern.len() - 1;
+        let matches = |window: &[u8]| window == pattern;
+
+        if backward {
+            let mut chunk_end = end;
+            loop {
+                let chunk_start = chunk_end
+                    .saturating_sub(CHUNK_SIZE + overlap)
+                    .max(range.start);
+                let data = self.read_range(chunk_start, chunk_end - chunk_start)?;
+                if let Some(pos) = data.windows(pattern.len()).rposition(matches) {
+                    return Ok(Some(chunk_start + pos));
+                }
+                if chunk_start == range.start {
+                    return Ok(None);
+                }
+                chunk_end = chunk_start + overlap;
+            }
+        } else {
+            let mut chunk_start = range.start;
+            loop {
+                let chunk_end = (chunk_start + CHUNK_SIZE + overlap).min(end);
+                let data = self.read_range(chunk_start, chunk_end - chunk_start)?;
+                if let Some(pos) = data.windows(pattern.len()).position(matches) {
+                    return Ok(Some(chunk_start + pos));
+                }
+                if chunk_end == end {
+                    return Ok(None);
+                }
+                chunk_start += CHUNK_SIZE;
+            }
+        }
+    }
+
     /// Find the next occurrence of a regex pattern, with wrap-around
     pub fn find_next_regex(&self, regex: &Regex, start_pos: usize) -> Option<usize> {
         let buffer_len = self.len();
@@ -2438,6 +2779,13 @@ impl TextBuffer {
     }
 }
 
+/// Load an unloaded string buffer from its file, on `fs` if given or else
+/// on the local filesystem
+fn load_buffer(buffer: &mut StringBuffer, fs: Option<&BlockingFs>) -> io::Result<()> {
+    let fs = fs.unwrap_or_else(|| BlockingFs::local());
+    buffer.load_with(|path, offset, len| fs.read_exact_at(path, offset, len))
+}
+
 /// Type alias for backwards compatibility
 pub type Buffer = TextBuffer;
 
@@ -3403,6 +3751,42 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_find_bytes_without_loading() {
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("huge.bin");
+
+            // The pattern straddles the first chunk boundary and is repeated near the end
+            let mut content = vec![0u8; LOAD_CHUNK_SIZE * 3];
+            content[LOAD_CHUNK_SIZE - 2..LOAD_CHUNK_SIZE + 2].copy_from_slice(b"\xDE\xAD\xBE\xEF");
+            let last = content.len() - 10;
+            content[last..last + 4].copy_from_slice(b"\xDE\xAD\xBE\xEF");
+            File::create(&file_path)
+                .unwrap()
+                .write_all(&content)
+                .unwrap();
+
+            let mut buffer = TextBuffer::load_from_file(&file_path, 1).unwrap();
+            buffer.insert_bytes(0, b"\xDE\xAD".to_vec());
+            let pattern = b"\xDE\xAD\xBE\xEF";
+            let len = buffer.total_bytes();
+
+            let first = buffer.find_bytes(pattern, 0..len, false).unwrap();
+            assert_eq!(first, Some(LOAD_CHUNK_SIZE - 2 + 2));
+            let next = buffer.find_bytes(pattern, first.unwrap() + 1..len, false);
+            assert_eq!(next.unwrap(), Some(last + 2));
+            let previous = buffer.find_bytes(pattern, 0..last + 2, true).unwrap();
+            assert_eq!(previous, first);
+            assert_eq!(buffer.find_bytes(b"\xFF", 0..len, false).unwrap(), None);
+
+            // Reading ranges leaves the file unloaded
+            assert_eq!(
+                buffer.read_range(LOAD_CHUNK_SIZE, 4).unwrap(),
+                b"\xDE\xAD\xBE\xEF"
+            );
+            assert!(!buffer.buffers[0].is_loaded());
+        }
+
         /// Test that save_to_file works correctly with partially loaded large files
         /// This is a regression test for a bug where saving would silently produce
         /// an empty file if any buffer regions were still unloaded.
@@ -3469,6 +3853,40 @@ mod tests {
             );
         }
 
+        /// A large file loaded from a filesystem backend reads its chunks and
+        /// saves through the backend, also when the backend is local
+        #[test]
+        fn test_large_file_through_backend() {
+            use crate::services::fs::{BlockingFs, LocalFsBackend, SlowFsBackend, SlowFsConfig};
+
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("remote.txt");
+            let mut content = vec![b'A'; 1000];
+            content.extend(vec![b'B'; 1000]);
+            std::fs::write(&file_path, &content).unwrap();
+
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            let backend = SlowFsBackend::new(
+                Arc::new(LocalFsBackend::new()),
+                SlowFsConfig::uniform(std::time::Duration::ZERO),
+            );
+            let metrics = backend.metrics_arc();
+            let fs = BlockingFs::new(Arc::new(backend), runtime.handle().clone());
+
+            let mut buffer = TextBuffer::load_from_backend(fs, &file_path, 100, None).unwrap();
+            assert!(buffer.large_file && !buffer.is_remote());
+            assert_eq!(buffer.get_text_range_mut(1500, 10).unwrap(), b"BBBBBBBBBB");
+            buffer.insert_bytes(0, b"PREFIX_".to_vec());
+            buffer.save().unwrap();
+
+            let saved = std::fs::read(&file_path).unwrap();
+            assert_eq!(&saved[..7], b"PREFIX_");
+            assert_eq!(&saved[7..], &content[..]);
+            let metrics = runtime.block_on(async { metrics.lock().await.clone() });
+            assert_eq!(metrics.write_calls, 1);
+            assert!(metrics.read_calls >= 2);
+        }
+
         /// Test that save_to_file handles edits at multiple positions
         #[test]
         fn test_large_file_save_with_multiple_edits() {
@@ -4005,6 +4423,38 @@ mod tests {
             let saved_bytes = std::fs::read(&file_path).unwrap();
             assert_eq!(&saved_bytes, b"Line 1\nLine 2\nLine 3\n");
         }
+
+        /// Edits made while a save copy is being installed keep the buffer
+        /// modified, and the temp file goes away with the copy
+        #[test]
+        fn test_save_copy_edited_meanwhile() {
+            use tempfile::TempDir;
+
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("root_owned.txt");
+            std::fs::write(&file_path, "old\n").unwrap();
+
+            let mut buffer =
+                TextBuffer::load_from_file(&file_path, DEFAULT_LARGE_FILE_THRESHOLD).unwrap();
+            buffer.insert_bytes(0, b"new ".to_vec());
+            let copy = buffer.write_save_copy(&file_path).unwrap();
+            assert_eq!(std::fs::read(&copy.temp_path).unwrap(), b"new old\n");
+
+            // The helper installs the copy while the user keeps typing
+            std::fs::copy(&copy.temp_path, &file_path).unwrap();
+            buffer.insert_bytes(0, b"more ".to_vec());
+            assert!(!buffer.finish_save_copy(&copy));
+            assert!(buffer.is_modified());
+
+            let temp_path = copy.temp_path.clone();
+            drop(copy);
+            assert!(!temp_path.exists());
+
+            let copy = buffer.write_save_copy(&file_path).unwrap();
+            std::fs::copy(&copy.temp_path, &file_path).unwrap();
+            assert!(buffer.finish_save_copy(&copy));
+            assert!(!buffer.is_modified());
+        }
     }
 }
 
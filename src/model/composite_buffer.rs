@@ -5,6 +5,7 @@
 //! within a single tab.
 
 use crate::model::event::BufferId;
+use crate::model::merge::Region;
 use serde::{Deserialize, Serialize};
 use std::ops::Range;
 
@@ -354,6 +355,45 @@ impl LineAlignment {
         Self { rows }
     }
 
+    /// Create alignment from the regions of a base text and its versions
+    /// (see `merge::regions`)
+    ///
+    /// Pane 0 shows the base and pane `i + 1` version `i`. Each changed
+    /// region starts with a hunk header and is padded to its longest side.
+    pub fn from_regions(regions: &[Region]) -> Self {
+        let line_ref = |line| SourceLineRef {
+            line,
+            byte_range: 0..0,
+        };
+        let mut rows = Vec::new();
+        for region in regions {
+            let spans: Vec<&Range<usize>> =
+                std::iter::once(&region.base).chain(&region.sides).collect();
+            let changed = region.is_changed();
+            if changed {
+                rows.push(AlignedRow {
+                    pane_lines: vec![None; spans.len()],
+                    row_type: RowType::HunkHeader,
+                });
+            }
+            let height = spans.iter().map(|span| span.len()).max().unwrap_or(0);
+            for offset in 0..height {
+                rows.push(AlignedRow {
+                    pane_lines: spans
+                        .iter()
+                        .map(|span| (offset < span.len()).then(|| line_ref(span.start + offset)))
+                        .collect(),
+                    row_type: if changed {
+                        RowType::Modification
+                    } else {
+                        RowType::Context
+                    },
+                });
+            }
+        }
+        Self { rows }
+    }
+
     /// Get the aligned row at the given display index
     pub fn get_row(&self, display_row: usize) -> Option<&AlignedRow> {
         self.rows.get(display_row)
@@ -518,6 +558,46 @@ mod tests {
         assert_eq!(alignment.rows[2].row_type, RowType::HunkHeader);
     }
 
+    #[test]
+    fn test_line_alignment_from_regions() {
+        let regions = vec![
+            Region {
+                base: 0..1,
+                sides: vec![0..1, 0..1],
+                changed: vec![false, false],
+            },
+            Region {
+                base: 1..2,
+                sides: vec![1..3, 1..1],
+                changed: vec![true, true],
+            },
+        ];
+        let alignment = LineAlignment::from_regions(&regions);
+        let types: Vec<_> = alignment.rows.iter().map(|row| row.row_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                RowType::Context,
+                RowType::HunkHeader,
+                RowType::Modification,
+                RowType::Modification
+            ]
+        );
+        // Every pane has a column, padded where it has fewer lines
+        let lines: Vec<Vec<Option<usize>>> = alignment.rows[2..]
+            .iter()
+            .map(|row| {
+                (0..3)
+                    .map(|pane| row.get_pane_line(pane).map(|line| line.line))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            vec![vec![Some(1), Some(1), None], vec![None, Some(2), None]]
+        );
+    }
+
     #[test]
     fn test_composite_buffer_focus() {
         let sources = vec![
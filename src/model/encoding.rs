@@ -0,0 +1,334 @@
+//! Text encodings of files on disk
+//!
+//! Buffers always hold UTF-8. Files in other encodings are decoded when they
+//! are loaded and encoded again when they are saved, the same way line
+//! endings are converted on save.
+
+use std::borrow::Cow;
+use std::io;
+
+/// Number of bytes looked at to detect the encoding of a file
+pub const ENCODING_SAMPLE_SIZE: usize = 64 * 1024;
+
+/// Encoding of a file on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// UTF-8 without byte order mark
+    #[default]
+    Utf8,
+    /// UTF-8 with byte order mark
+    Utf8Bom,
+    /// UTF-16 little endian (with byte order mark)
+    Utf16Le,
+    /// UTF-16 big endian (with byte order mark)
+    Utf16Be,
+    /// ISO-8859-1, every byte is the code point of the same value
+    Latin1,
+    /// Windows-1252, Western European
+    Windows1252,
+    /// Windows-1250, Central European
+    Windows1250,
+    /// Windows-1251, Cyrillic
+    Windows1251,
+    /// Shift-JIS, Japanese
+    ShiftJis,
+    /// EUC-JP, Japanese
+    EucJp,
+    /// GB18030, Simplified Chinese
+    Gb18030,
+    /// Big5, Traditional Chinese
+    Big5,
+    /// EUC-KR, Korean
+    EucKr,
+}
+
+impl Encoding {
+    /// All supported encodings, in the order they are offered to the user
+    pub const ALL: [Encoding; 13] = [
+        Self::Utf8,
+        Self::Utf8Bom,
+        Self::Utf16Le,
+        Self::Utf16Be,
+        Self::Latin1,
+        Self::Windows1252,
+        Self::Windows1250,
+        Self::Windows1251,
+        Self::ShiftJis,
+        Self::EucJp,
+        Self::Gb18030,
+        Self::Big5,
+        Self::EucKr,
+    ];
+
+    /// Get the display name for status bar
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf8Bom => "UTF-8 BOM",
+            Self::Utf16Le => "UTF-16 LE",
+            Self::Utf16Be => "UTF-16 BE",
+            Self::Latin1 => "Latin-1",
+            Self::Windows1252 => "Windows-1252",
+            Self::Windows1250 => "Windows-1250",
+            Self::Windows1251 => "Windows-1251",
+            Self::ShiftJis => "Shift-JIS",
+            Self::EucJp => "EUC-JP",
+            Self::Gb18030 => "GB18030",
+            Self::Big5 => "Big5",
+            Self::EucKr => "EUC-KR",
+        }
+    }
+
+    /// Parse an encoding from its display name or a common label
+    /// (case, spaces, dashes and underscores are ignored)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalized: String = name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        let by_display_name = Self::ALL.into_iter().find(|encoding| {
+            encoding
+                .display_name()
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .map(|c| c.to_ascii_lowercase())
+                .eq(normalized.chars())
+        });
+        if by_display_name.is_some() {
+            return by_display_name;
+        }
+        match normalized.as_str() {
+            "utf8bom" | "utf8sig" => Some(Self::Utf8Bom),
+            "utf16" => Some(Self::Utf16Le),
+            "latin1" | "iso88591" => Some(Self::Latin1),
+            "cp1252" => Some(Self::Windows1252),
+            "sjis" => Some(Self::ShiftJis),
+            _ => encoding_rs::Encoding::for_label(name.trim().as_bytes())
+                .and_then(Self::from_encoding_rs),
+        }
+    }
+
+    fn from_encoding_rs(encoding: &'static encoding_rs::Encoding) -> Option<Self> {
+        let encoding = if encoding == encoding_rs::UTF_8 {
+            Self::Utf8
+        } else if encoding == encoding_rs::UTF_16LE {
+            Self::Utf16Le
+        } else if encoding == encoding_rs::UTF_16BE {
+            Self::Utf16Be
+        } else if encoding == encoding_rs::WINDOWS_1252 {
+            Self::Windows1252
+        } else if encoding == encoding_rs::WINDOWS_1250 {
+            Self::Windows1250
+        } else if encoding == encoding_rs::WINDOWS_1251 {
+            Self::Windows1251
+        } else if encoding == encoding_rs::SHIFT_JIS {
+            Self::ShiftJis
+        } else if encoding == encoding_rs::EUC_JP {
+            Self::EucJp
+        } else if encoding == encoding_rs::GB18030 || encoding == encoding_rs::GBK {
+            Self::Gb18030
+        } else if encoding == encoding_rs::BIG5 {
+            Self::Big5
+        } else if encoding == encoding_rs::EUC_KR {
+            Self::EucKr
+        } else {
+            return None;
+        };
+        Some(encoding)
+    }
+
+    /// The `encoding_rs` codec (it only decodes UTF-16, encoding is done here)
+    fn codec(&self) -> Option<&'static encoding_rs::Encoding> {
+        match self {
+            Self::Utf16Le => Some(encoding_rs::UTF_16LE),
+            Self::Utf16Be => Some(encoding_rs::UTF_16BE),
+            Self::Windows1252 => Some(encoding_rs::WINDOWS_1252),
+            Self::Windows1250 => Some(encoding_rs::WINDOWS_1250),
+            Self::Windows1251 => Some(encoding_rs::WINDOWS_1251),
+            Self::ShiftJis => Some(encoding_rs::SHIFT_JIS),
+            Self::EucJp => Some(encoding_rs::EUC_JP),
+            Self::Gb18030 => Some(encoding_rs::GB18030),
+            Self::Big5 => Some(encoding_rs::BIG5),
+            Self::EucKr => Some(encoding_rs::EUC_KR),
+            Self::Utf8 | Self::Utf8Bom | Self::Latin1 => None,
+        }
+    }
+
+    /// Whether the buffer content is written as is (apart from the byte order mark)
+    pub fn is_utf8(&self) -> bool {
+        matches!(self, Self::Utf8 | Self::Utf8Bom)
+    }
+
+    /// Byte order mark written at the start of the file
+    pub fn bom(&self) -> &'static [u8] {
+        match self {
+            Self::Utf8Bom => b"\xEF\xBB\xBF",
+            Self::Utf16Le => b"\xFF\xFE",
+            Self::Utf16Be => b"\xFE\xFF",
+            _ => b"",
+        }
+    }
+
+    /// The encoding announced by a byte order mark at the start of `bytes`
+    pub fn from_bom(bytes: &[u8]) -> Option<Self> {
+        [Self::Utf8Bom, Self::Utf16Le, Self::Utf16Be]
+            .into_iter()
+            .find(|encoding| bytes.starts_with(encoding.bom()))
+    }
+
+    /// Guess the encoding of text from a sample of its bytes
+    ///
+    /// A byte order mark decides; otherwise text that is valid UTF-8 is UTF-8,
+    /// and anything else is given to a statistical detector.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if let Some(encoding) = Self::from_bom(bytes) {
+            return encoding;
+        }
+        match std::str::from_utf8(bytes) {
+            Ok(_) => return Self::Utf8,
+            // The sample may end in the middle of a character
+            Err(e) if e.error_len().is_none() && bytes.len() >= ENCODING_SAMPLE_SIZE => {
+                return Self::Utf8
+            }
+            Err(_) => {}
+        }
+        let mut detector = chardetng::EncodingDetector::new();
+        detector.feed(bytes, true);
+        Self::from_encoding_rs(detector.guess(None, false)).unwrap_or(Self::Windows1252)
+    }
+
+    /// Decode file content to UTF-8, without the byte order mark
+    ///
+    /// Returns None if the content is not valid in this encoding.
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+        let bytes = bytes.strip_prefix(self.bom()).unwrap_or(bytes);
+        let text = match self {
+            Self::Utf8 | Self::Utf8Bom => {
+                return std::str::from_utf8(bytes)
+                    .ok()
+                    .map(|_| Cow::Borrowed(bytes))
+            }
+            Self::Latin1 => Cow::Owned(bytes.iter().map(|&b| b as char).collect()),
+            _ => self
+                .codec()?
+                .decode_without_bom_handling_and_without_replacement(bytes)?,
+        };
+        Some(match text {
+            Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+            Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+        })
+    }
+
+    /// Encode UTF-8 buffer content for writing, without the byte order mark
+    ///
+    /// Fails on characters the encoding cannot represent.
+    pub fn encode<'a>(&self, bytes: &'a [u8]) -> io::Result<Cow<'a, [u8]>> {
+        if self.is_utf8() {
+            return Ok(Cow::Borrowed(bytes));
+        }
+        let text = std::str::from_utf8(bytes).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Content is not valid text for {}", self.display_name()),
+            )
+        })?;
+        let unmappable = |c: char| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("'{}' cannot be saved as {}", c, self.display_name()),
+            )
+        };
+        let encoded = match self {
+            Self::Utf16Le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+            Self::Utf16Be => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            Self::Latin1 => text
+                .chars()
+                .map(|c| u8::try_from(c).map_err(|_| unmappable(c)))
+                .collect::<io::Result<Vec<u8>>>()?,
+            _ => {
+                let codec = self.codec().expect("legacy encodings have a codec");
+                let (encoded, _, had_errors) = codec.encode(text);
+                if had_errors {
+                    let c = text
+                        .chars()
+                        .find(|c| codec.encode(c.encode_utf8(&mut [0; 4])).2)
+                        .unwrap_or(char::REPLACEMENT_CHARACTER);
+                    return Err(unmappable(c));
+                }
+                encoded.into_owned()
+            }
+        };
+        Ok(Cow::Owned(encoded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(Encoding::detect(b"plain ascii"), Encoding::Utf8);
+        assert_eq!(Encoding::detect("caf\u{e9}".as_bytes()), Encoding::Utf8);
+        assert_eq!(Encoding::detect(b"\xEF\xBB\xBFbom"), Encoding::Utf8Bom);
+        assert_eq!(Encoding::detect(b"\xFF\xFEa\0b\0"), Encoding::Utf16Le);
+        assert_eq!(Encoding::detect(b"\xFE\xFF\0a\0b"), Encoding::Utf16Be);
+        assert_eq!(
+            Encoding::detect(b"Le caf\xE9 est tr\xE8s bon, n'est-ce pas ?\n"),
+            Encoding::Windows1252
+        );
+        // "日本語のテキストです" in Shift-JIS
+        let shift_jis =
+            b"\x93\xfa\x96\x7b\x8c\xea\x82\xcc\x83\x65\x83\x4c\x83\x58\x83\x67\x82\xc5\x82\xb7";
+        assert_eq!(Encoding::detect(shift_jis), Encoding::ShiftJis);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let cases: [(Encoding, &[u8]); 5] = [
+            (Encoding::Utf16Le, b"h\0\xe9\0\n\0"),
+            (Encoding::Utf16Be, b"\0h\0\xe9\0\n"),
+            (Encoding::Latin1, b"caf\xe9 \x85"),
+            (Encoding::Windows1252, b"caf\xe9 \x80 \x81"),
+            (Encoding::ShiftJis, b"\x93\xfa\x96\x7b"),
+        ];
+        for (encoding, bytes) in cases {
+            let decoded = encoding.decode(bytes).unwrap();
+            assert!(std::str::from_utf8(&decoded).is_ok(), "{:?}", encoding);
+            assert_eq!(
+                &*encoding.encode(&decoded).unwrap(),
+                bytes,
+                "{:?}",
+                encoding
+            );
+        }
+        assert_eq!(
+            &*Encoding::Utf16Le.decode(b"\xFF\xFEh\0i\0").unwrap(),
+            b"hi"
+        );
+    }
+
+    #[test]
+    fn test_decode_and_encode_errors() {
+        // Odd number of bytes
+        assert!(Encoding::Utf16Le.decode(b"h\0i").is_none());
+        // Latin-1 bytes
+        assert!(Encoding::Utf8.decode(b"caf\xe9").is_none());
+        let err = Encoding::Latin1.encode("\u{263a}".as_bytes()).unwrap_err();
+        assert!(err.to_string().contains('\u{263a}'));
+        assert!(Encoding::ShiftJis.encode("\u{e9}".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_from_name() {
+        for encoding in Encoding::ALL {
+            assert_eq!(Encoding::from_name(encoding.display_name()), Some(encoding));
+        }
+        assert_eq!(Encoding::from_name("utf-16le"), Some(Encoding::Utf16Le));
+        assert_eq!(Encoding::from_name("ISO-8859-1"), Some(Encoding::Latin1));
+        assert_eq!(Encoding::from_name("shift_jis"), Some(Encoding::ShiftJis));
+        assert_eq!(Encoding::from_name("cp1251"), Some(Encoding::Windows1251));
+        assert_eq!(Encoding::from_name("klingon"), None);
+    }
+}
@@ -470,6 +470,16 @@ impl Event {
         }
     }
 
+    /// Returns true if this event is (or contains) a bulk edit, whose buffer
+    /// snapshots are not serialized
+    fn contains_bulk_edit(&self) -> bool {
+        match self {
+            Self::BulkEdit { .. } => true,
+            Self::Batch { events, .. } => events.iter().any(|e| e.contains_bulk_edit()),
+            _ => false,
+        }
+    }
+
     /// Returns true if this event is a write action (modifies state in a way that should be undoable)
     /// Returns false for readonly actions like cursor movement, scrolling, viewport changes, etc.
     ///
@@ -556,12 +566,47 @@ pub struct Snapshot {
     pub cursor_positions: Vec<(CursorId, usize, Option<usize>)>,
 }
 
+/// A state in the undo tree: the state right after an entry of the log was
+/// applied, or the initial state (`None`)
+pub type UndoNode = Option<usize>;
+
+/// A state of the undo tree, as listed by `EventLog::undo_states`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UndoState {
+    /// The state; `Some` states were made by a write action
+    pub node: UndoNode,
+    /// Number of the state in the order states were made (0 for the initial state)
+    pub seq: usize,
+    /// Nesting level: branches are listed under the state they branch off from,
+    /// indented unless they are the most recent one
+    pub depth: usize,
+}
+
+/// How far `EventLog::earlier` and `EventLog::later` move through the history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeTravel {
+    /// A number of undo states, in the order they were made
+    Steps(usize),
+    /// A span of time
+    Time(std::time::Duration),
+}
+
 /// The event log - append-only log of all events
+///
+/// Undone events stay in the log when new ones are logged after them, so the
+/// log is a tree: every entry knows the entry before it on its branch. Undo
+/// and redo move along the current branch; `jump_to` switches branches.
 pub struct EventLog {
-    /// All logged events
+    /// All logged events, in the order they were logged
     entries: Vec<LogEntry>,
 
-    /// Current position in the log (for undo/redo)
+    /// For each entry, the entry before it on its branch
+    parents: Vec<Option<usize>>,
+
+    /// Entries of the current branch, oldest first (indexes into `entries`)
+    branch: Vec<usize>,
+
+    /// Current position in the current branch (for undo/redo)
     current_index: usize,
 
     /// Periodic snapshots for fast seeking
@@ -573,9 +618,10 @@ pub struct EventLog {
     /// Optional file for streaming events to disk
     stream_file: Option<std::fs::File>,
 
-    /// Index at which the buffer was last saved (for tracking modified status)
-    /// When current_index equals saved_at_index, the buffer is not modified
-    saved_at_index: Option<usize>,
+    /// Last buffer edit before the position where the buffer was last saved
+    /// (for tracking modified status). When the current position has the
+    /// same last edit, the buffer is not modified
+    saved_at: Option<UndoNode>,
 }
 
 impl EventLog {
@@ -583,42 +629,69 @@ impl EventLog {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
+            parents: Vec::new(),
+            branch: Vec::new(),
             current_index: 0,
             snapshots: Vec::new(),
             snapshot_interval: 100,
             stream_file: None,
-            saved_at_index: Some(0), // New buffer starts at "saved" state (index 0)
+            saved_at: Some(None), // New buffer starts at "saved" state (no edits)
         }
     }
 
     /// Mark the current position as the saved point
     /// Call this when the buffer is saved to disk
     pub fn mark_saved(&mut self) {
-        self.saved_at_index = Some(self.current_index);
+        self.saved_at = Some(self.last_edit(self.node_at(self.current_index)));
     }
 
     /// Check if the buffer is at the saved position (not modified)
-    /// Returns true if we're at the saved position OR if all events between
-    /// saved_at_index and current_index are readonly (don't modify buffer content)
+    /// Returns true if we're at the saved position OR if only readonly events
+    /// (that don't modify buffer content) lie between the two
     pub fn is_at_saved_position(&self) -> bool {
-        match self.saved_at_index {
-            None => false,
-            Some(saved_idx) if saved_idx == self.current_index => true,
-            Some(saved_idx) => {
-                // Check if all events between saved position and current position
-                // are readonly (don't modify buffer content)
-                let (start, end) = if saved_idx < self.current_index {
-                    (saved_idx, self.current_index)
-                } else {
-                    (self.current_index, saved_idx)
-                };
+        self.saved_at == Some(self.last_edit(self.node_at(self.current_index)))
+    }
+
+    /// The undo state at `position` in the current branch
+    fn node_at(&self, position: usize) -> UndoNode {
+        position.checked_sub(1).map(|i| self.branch[i])
+    }
 
-                // All events in range [start, end) must be readonly
-                self.entries[start..end]
-                    .iter()
-                    .all(|entry| !entry.event.modifies_buffer())
+    /// Position of `node` in the current branch, if it is on it
+    fn branch_position(&self, node: UndoNode) -> Option<usize> {
+        match node {
+            None => Some(0),
+            // Entries are logged after the entries before them, so the branch is sorted
+            Some(entry) => self.branch.binary_search(&entry).ok().map(|i| i + 1),
+        }
+    }
+
+    /// The closest state at or before `node` that was made by an entry passing `filter`
+    fn last_matching(&self, node: UndoNode, filter: impl Fn(&Event) -> bool) -> UndoNode {
+        let mut node = node;
+        while let Some(entry) = node {
+            if filter(&self.entries[entry].event) {
+                break;
             }
+            node = self.parents[entry];
         }
+        node
+    }
+
+    /// The last buffer edit at or before `node`
+    fn last_edit(&self, node: UndoNode) -> UndoNode {
+        self.last_matching(node, Event::modifies_buffer)
+    }
+
+    /// The current undo state: the state made by the last write action before
+    /// the current position
+    pub fn current_state(&self) -> UndoNode {
+        self.last_matching(self.node_at(self.current_index), Event::is_write_action)
+    }
+
+    /// The undo state the buffer was last saved at, if any
+    pub fn saved_state(&self) -> Option<UndoNode> {
+        self.saved_at
     }
 
     /// Enable streaming events to a file
@@ -697,10 +770,9 @@ impl EventLog {
 
     /// Append an event to the log
     pub fn append(&mut self, event: Event) -> usize {
-        // If we're not at the end, truncate future events
-        if self.current_index < self.entries.len() {
-            self.entries.truncate(self.current_index);
-        }
+        // If we're not at the end, start a new branch; the undone events stay
+        // in the log
+        self.branch.truncate(self.current_index);
 
         // Stream event to file if enabled
         if let Some(ref mut file) = self.stream_file {
@@ -722,8 +794,10 @@ impl EventLog {
         }
 
         let entry = LogEntry::new(event);
+        self.parents.push(self.branch.last().copied());
+        self.branch.push(self.entries.len());
         self.entries.push(entry);
-        self.current_index = self.entries.len();
+        self.current_index = self.branch.len();
 
         // Check if we should create a snapshot
         if self.entries.len() % self.snapshot_interval == 0 {
@@ -739,9 +813,9 @@ impl EventLog {
         self.current_index
     }
 
-    /// Get the number of events in the log
+    /// Get the number of events in the current branch
     pub fn len(&self) -> usize {
-        self.entries.len()
+        self.branch.len()
     }
 
     /// Can we undo?
@@ -751,7 +825,7 @@ impl EventLog {
 
     /// Can we redo?
     pub fn can_redo(&self) -> bool {
-        self.current_index < self.entries.len()
+        self.current_index < self.branch.len()
     }
 
     /// Move back through events (for undo)
@@ -764,7 +838,7 @@ impl EventLog {
         // Keep moving backward until we find a write action
         while self.can_undo() && !found_write_action {
             self.current_index -= 1;
-            let event = &self.entries[self.current_index].event;
+            let event = &self.entries[self.branch[self.current_index]].event;
 
             // Check if this is a write action - we'll stop after processing it
             if event.is_write_action() {
@@ -790,7 +864,7 @@ impl EventLog {
 
         // Keep moving forward to collect write action and subsequent readonly events
         while self.can_redo() {
-            let event = self.entries[self.current_index].event.clone();
+            let event = self.entries[self.branch[self.current_index]].event.clone();
 
             // If we've already found a write action and this is another write action, stop
             if found_write_action && event.is_write_action() {
@@ -811,30 +885,219 @@ impl EventLog {
         events
     }
 
-    /// Get all events from the log
+    /// Get all events from the log, including undone branches, in the order
+    /// they were logged
     pub fn entries(&self) -> &[LogEntry] {
         &self.entries
     }
 
-    /// Get events in a range
+    /// Get an event of the log by its index in `entries()`
+    pub fn entry(&self, index: usize) -> Option<&LogEntry> {
+        self.entries.get(index)
+    }
+
+    /// Get events in a range of `entries()`
     pub fn range(&self, range: Range<usize>) -> &[LogEntry] {
         &self.entries[range]
     }
 
     /// Get the most recent event
     pub fn last_event(&self) -> Option<&Event> {
-        if self.current_index > 0 {
-            Some(&self.entries[self.current_index - 1].event)
-        } else {
-            None
-        }
+        self.node_at(self.current_index)
+            .map(|entry| &self.entries[entry].event)
     }
 
     /// Clear all events (for testing or reset)
     pub fn clear(&mut self) {
         self.entries.clear();
+        self.parents.clear();
+        self.branch.clear();
         self.current_index = 0;
         self.snapshots.clear();
+        self.saved_at = Some(None);
+    }
+
+    /// The entries from the first one up to `node`
+    fn path_to(&self, node: UndoNode) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut next = node;
+        while let Some(entry) = next {
+            path.push(entry);
+            next = self.parents[entry];
+        }
+        path.reverse();
+        path
+    }
+
+    /// The branch through `node`: the current branch if `node` is on it,
+    /// otherwise the path to `node` continued to its most recent descendant
+    fn branch_through(&self, node: UndoNode) -> Vec<usize> {
+        if self.branch_position(node).is_some() {
+            return self.branch.clone();
+        }
+        let mut below = vec![false; self.entries.len()];
+        let mut newest = node;
+        for entry in node.map_or(0, |node| node + 1)..self.entries.len() {
+            let parent = self.parents[entry];
+            below[entry] = parent == node || parent.is_some_and(|parent| below[parent]);
+            if below[entry] {
+                newest = Some(entry);
+            }
+        }
+        self.path_to(newest)
+    }
+
+    /// The events that take the buffer from the current position to `node`,
+    /// with the branch through `node` and its position there
+    fn plan_jump(&self, node: UndoNode) -> (Vec<Event>, Vec<usize>, usize) {
+        if node.is_some_and(|entry| entry >= self.entries.len()) || node == self.current_state() {
+            return (Vec::new(), self.branch.clone(), self.current_index);
+        }
+        let branch = self.branch_through(node);
+        let target = node.map_or(0, |entry| branch.partition_point(|&e| e <= entry));
+
+        // Undo back to where the two branches meet, then redo along the new one
+        let common = self
+            .branch
+            .iter()
+            .zip(&branch)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let meet = self.current_index.min(target).min(common);
+        // Only buffer changes are replayed: cursor moves, scrolling and mode
+        // changes of another branch mean nothing at the state jumped to
+        let mut events: Vec<Event> = self.branch[meet..self.current_index]
+            .iter()
+            .rev()
+            .map(|&entry| &self.entries[entry].event)
+            .filter(|event| event.modifies_buffer())
+            .filter_map(|event| {
+                let inverse = event.inverse();
+                debug_assert!(inverse.is_some(), "buffer change without inverse");
+                inverse
+            })
+            .collect();
+        events.extend(
+            branch[meet..target]
+                .iter()
+                .map(|&entry| &self.entries[entry].event)
+                .filter(|event| event.modifies_buffer())
+                .cloned(),
+        );
+        (events, branch, target)
+    }
+
+    /// The events that would take the buffer from the current position to
+    /// `node`, without moving there (for previewing a state)
+    pub fn events_to(&self, node: UndoNode) -> Vec<Event> {
+        self.plan_jump(node).0
+    }
+
+    /// Move to `node`, on any branch, returning the events to apply
+    ///
+    /// The branch through `node` becomes the current one, so redo continues
+    /// along it.
+    pub fn jump_to(&mut self, node: UndoNode) -> Vec<Event> {
+        let (events, branch, target) = self.plan_jump(node);
+        self.branch = branch;
+        self.current_index = target;
+        events
+    }
+
+    /// Move to an undo state made before the current one, in the order states
+    /// were made rather than along the current branch (like Vim's `:earlier`)
+    pub fn earlier(&mut self, by: TimeTravel) -> Vec<Event> {
+        let node = self.travel_target(by, false);
+        self.jump_to(node)
+    }
+
+    /// Move to an undo state made after the current one (like Vim's `:later`)
+    pub fn later(&mut self, by: TimeTravel) -> Vec<Event> {
+        let node = self.travel_target(by, true);
+        self.jump_to(node)
+    }
+
+    fn travel_target(&self, by: TimeTravel, later: bool) -> UndoNode {
+        let states: Vec<usize> = (0..self.entries.len())
+            .filter(|&entry| self.entries[entry].event.is_write_action())
+            .collect();
+        // Number of states made up to the current one
+        let current = match self.current_state() {
+            None => 0,
+            Some(entry) => states.binary_search(&entry).map_or(0, |i| i + 1),
+        };
+        let target = match by {
+            TimeTravel::Steps(steps) if later => (current + steps).min(states.len()),
+            TimeTravel::Steps(steps) => current.saturating_sub(steps),
+            TimeTravel::Time(span) => {
+                let span = span.as_millis() as u64;
+                // The initial state counts as made with the first edit
+                let now = states
+                    .get(current.saturating_sub(1))
+                    .map(|&entry| self.entries[entry].timestamp)?;
+                if later {
+                    let until = now.saturating_add(span);
+                    states
+                        .partition_point(|&entry| self.entries[entry].timestamp <= until)
+                        .max(current)
+                } else if current == 0 {
+                    0
+                } else {
+                    let until = now.saturating_sub(span);
+                    states[..current]
+                        .partition_point(|&entry| self.entries[entry].timestamp <= until)
+                }
+            }
+        };
+        target.checked_sub(1).map(|i| states[i])
+    }
+
+    /// Every undo state of the tree, depth first: the initial state, then the
+    /// states made from each state, with the most recent branch continuing at
+    /// the same depth after the older (indented) ones
+    pub fn undo_states(&self) -> Vec<UndoState> {
+        let mut state_of: Vec<UndoNode> = Vec::with_capacity(self.entries.len());
+        let mut seq = vec![0; self.entries.len()];
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); self.entries.len()];
+        let mut root_children = Vec::new();
+        let mut count = 0;
+        for (entry, log_entry) in self.entries.iter().enumerate() {
+            let parent = self.parents[entry].and_then(|parent| state_of[parent]);
+            if log_entry.event.is_write_action() {
+                count += 1;
+                seq[entry] = count;
+                match parent {
+                    Some(parent) => children[parent].push(entry),
+                    None => root_children.push(entry),
+                }
+                state_of.push(Some(entry));
+            } else {
+                state_of.push(parent);
+            }
+        }
+
+        let push_children = |stack: &mut Vec<(usize, usize)>, children: &[usize], depth| {
+            if let Some((&newest, older)) = children.split_last() {
+                stack.push((newest, depth));
+                stack.extend(older.iter().rev().map(|&child| (child, depth + 1)));
+            }
+        };
+        let mut states = vec![UndoState {
+            node: None,
+            seq: 0,
+            depth: 0,
+        }];
+        let mut stack = Vec::new();
+        push_children(&mut stack, &root_children, 0);
+        while let Some((entry, depth)) = stack.pop() {
+            states.push(UndoState {
+                node: Some(entry),
+                seq: seq[entry],
+                depth,
+            });
+            push_children(&mut stack, &children[entry], depth);
+        }
+        states
     }
 
     /// Save event log to JSON Lines format
@@ -843,8 +1106,8 @@ impl EventLog {
         let file = std::fs::File::create(path)?;
         let mut writer = std::io::BufWriter::new(file);
 
-        for entry in &self.entries {
-            let json = serde_json::to_string(entry)?;
+        for &entry in &self.branch {
+            let json = serde_json::to_string(&self.entries[entry])?;
             writeln!(writer, "{json}")?;
         }
 
@@ -857,7 +1120,7 @@ impl EventLog {
         let file = std::fs::File::open(path)?;
         let reader = std::io::BufReader::new(file);
 
-        let mut log = Self::new();
+        let mut entries = Vec::new();
 
         for line in reader.lines() {
             let line = line?;
@@ -865,12 +1128,66 @@ impl EventLog {
                 continue;
             }
             let entry: LogEntry = serde_json::from_str(&line)?;
-            log.entries.push(entry);
+            entries.push(entry);
         }
 
-        log.current_index = log.entries.len();
+        Ok(Self::linear(entries))
+    }
+
+    /// The buffer edits around the saved position, with the index of the saved
+    /// position among them (for persisting the undo history)
+    ///
+    /// Returns None if the log has no saved position.
+    /// Bulk edits can't be serialized, so the history stops at the nearest ones.
+    pub fn saved_history(&self) -> Option<(Vec<LogEntry>, usize)> {
+        let saved_node = self.saved_at?;
+        let branch: Vec<&LogEntry> = self
+            .branch_through(saved_node)
+            .into_iter()
+            .map(|entry| &self.entries[entry])
+            .collect();
+        let saved = self.path_to(saved_node).len();
+        let start = branch[..saved]
+            .iter()
+            .rposition(|entry| entry.event.contains_bulk_edit())
+            .map_or(0, |i| i + 1);
+        let end = branch[saved..]
+            .iter()
+            .position(|entry| entry.event.contains_bulk_edit())
+            .map_or(branch.len(), |i| saved + i);
+
+        let mut entries = Vec::new();
+        let mut saved_index = 0;
+        for (i, entry) in branch[start..end].iter().enumerate() {
+            if entry.event.modifies_buffer() {
+                if start + i < saved {
+                    saved_index += 1;
+                }
+                entries.push((*entry).clone());
+            }
+        }
+        Some((entries, saved_index))
+    }
+
+    /// Create a log from a persisted undo history, positioned at
+    /// `current_index`, which matches the buffer's saved contents
+    pub fn from_history(entries: Vec<LogEntry>, current_index: usize) -> Self {
+        let mut log = Self::linear(entries);
+        log.current_index = current_index.min(log.entries.len());
+        log.mark_saved();
+        log
+    }
 
-        Ok(log)
+    /// Create a log with a single branch of `entries`, positioned at its end
+    fn linear(entries: Vec<LogEntry>) -> Self {
+        let len = entries.len();
+        Self {
+            entries,
+            parents: (0..len).map(|i| i.checked_sub(1)).collect(),
+            branch: (0..len).collect(),
+            current_index: len,
+            ..Self::new()
+        }
     }
 
     /// Set snapshot interval
@@ -888,6 +1205,7 @@ impl Default for EventLog {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     // Property-based tests
     #[cfg(test)]
@@ -971,9 +1289,9 @@ mod tests {
                 assert_eq!(redo_count, events.len());
             }
 
-            /// Appending after undo should truncate redo history
+            /// Appending after undo should start a new branch with nothing to redo
             #[test]
-            fn append_after_undo_truncates(
+            fn append_after_undo_starts_branch(
                 initial_events in prop::collection::vec(arb_event(), 2..10),
                 new_event in arb_event()
             ) {
@@ -1068,33 +1386,138 @@ mod tests {
         }
     }
 
+    fn insert(position: usize, text: &str) -> Event {
+        Event::Insert {
+            position,
+            text: text.to_string(),
+            cursor_id: CursorId(0),
+        }
+    }
+
     #[test]
-    fn test_truncate_on_new_event_after_undo() {
+    fn test_new_event_after_undo_starts_branch() {
         let mut log = EventLog::new();
+        log.append(insert(0, "a"));
+        log.append(insert(1, "b"));
 
-        log.append(Event::Insert {
-            position: 0,
-            text: "a".to_string(),
-            cursor_id: CursorId(0),
-        });
+        log.undo();
+        log.append(insert(1, "c"));
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.current_index(), 2);
+        assert!(!log.can_redo());
 
-        log.append(Event::Insert {
-            position: 1,
-            text: "b".to_string(),
-            cursor_id: CursorId(0),
+        // The undone "b" is kept on its own branch
+        assert_eq!(log.entries().len(), 3);
+        let events = log.jump_to(Some(1));
+        assert!(matches!(
+            &events[..],
+            [Event::Delete { deleted_text, .. }, Event::Insert { text, .. }]
+                if deleted_text == "c" && text == "b"
+        ));
+        assert_eq!(log.current_state(), Some(1));
+
+        // Undo and redo follow the branch jumped to
+        log.undo();
+        let events = log.redo();
+        assert!(matches!(&events[..], [Event::Insert { text, .. }] if text == "b"));
+    }
+
+    #[test]
+    fn test_jump_replays_only_buffer_changes() {
+        let mut log = EventLog::new();
+        log.append(insert(0, "a"));
+        log.append(insert(1, "b"));
+        log.append(Event::Scroll { line_offset: 2 });
+        log.append(Event::ChangeMode {
+            mode: "insert".to_string(),
         });
+        log.undo();
+        log.append(Event::Scroll { line_offset: 5 });
+        log.append(Event::SetViewport { top_line: 7 });
+        log.append(insert(1, "c"));
+
+        // Scrolling and mode changes on either branch are not replayed
+        let events = log.jump_to(Some(3));
+        assert!(matches!(
+            &events[..],
+            [Event::Delete { deleted_text, .. }, Event::Insert { text, .. }]
+                if deleted_text == "c" && text == "b"
+        ));
+        let events = log.jump_to(Some(6));
+        assert!(matches!(
+            &events[..],
+            [Event::Delete { deleted_text, .. }, Event::Insert { text, .. }]
+                if deleted_text == "b" && text == "c"
+        ));
+        assert_eq!(log.current_state(), Some(6));
+    }
 
+    #[test]
+    fn test_undo_states_nest_older_branches() {
+        let mut log = EventLog::new();
+        log.append(insert(0, "a"));
+        log.append(insert(1, "b"));
         log.undo();
-        assert_eq!(log.entries().len(), 2);
+        log.append(insert(1, "c"));
+
+        let states: Vec<(UndoNode, usize, usize)> = log
+            .undo_states()
+            .iter()
+            .map(|state| (state.node, state.seq, state.depth))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (None, 0, 0),
+                (Some(0), 1, 0),
+                (Some(1), 2, 1),
+                (Some(2), 3, 0)
+            ]
+        );
+    }
 
-        // Adding new event should truncate the future
-        log.append(Event::Insert {
-            position: 1,
-            text: "c".to_string(),
-            cursor_id: CursorId(0),
-        });
+    #[test]
+    fn test_saved_position_on_other_branch() {
+        let mut log = EventLog::new();
+        log.append(insert(0, "a"));
+        log.mark_saved();
+        log.undo();
+        log.append(insert(0, "b"));
+        assert!(!log.is_at_saved_position());
 
-        assert_eq!(log.entries().len(), 2);
-        assert_eq!(log.current_index(), 2);
+        log.jump_to(Some(0));
+        assert!(log.is_at_saved_position());
+        assert_eq!(log.saved_state(), Some(Some(0)));
+    }
+
+    #[test]
+    fn test_earlier_and_later() {
+        const START: u64 = 1_700_000_000_000;
+        let mut log = EventLog::new();
+        for (i, text) in ["a", "b", "c", "d"].iter().enumerate() {
+            log.append(insert(i, text));
+            log.entries[i].timestamp = START + i as u64 * 60_000;
+        }
+        // "b" replaced by "x": states in order a, b, c, d, x
+        log.undo();
+        log.undo();
+        log.undo();
+        log.append(insert(1, "x"));
+        log.entries[4].timestamp = START + 10 * 60_000;
+
+        log.earlier(TimeTravel::Steps(2));
+        assert_eq!(log.current_state(), Some(2));
+        log.later(TimeTravel::Steps(1));
+        assert_eq!(log.current_state(), Some(3));
+
+        // Two minutes before "d" was typed, "b" was typed
+        log.earlier(TimeTravel::Time(Duration::from_secs(120)));
+        assert_eq!(log.current_state(), Some(1));
+        log.later(TimeTravel::Time(Duration::from_secs(90)));
+        assert_eq!(log.current_state(), Some(2));
+        log.earlier(TimeTravel::Time(Duration::from_secs(3600)));
+        assert_eq!(log.current_state(), None);
+        log.later(TimeTravel::Steps(10));
+        assert_eq!(log.current_state(), Some(4));
     }
 }
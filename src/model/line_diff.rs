@@ -6,6 +6,8 @@
 
 use std::ops::Range;
 
+use crate::model::composite_buffer::DiffHunk;
+
 /// Type of change detected for a line range
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChangeType {
@@ -82,6 +84,64 @@ pub fn diff_lines(saved: &[u8], current: &[u8]) -> LineDiff {
     }
 }
 
+/// Largest number of lines (old times new) compared with the LCS table;
+/// bigger changes become a single hunk
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Compare two byte slices line by line and return the hunks that turn `old`
+/// into `new` (for aligning the two sides of a diff view)
+///
+/// Unchanged lines at the start and end are skipped before looking for the
+/// longest common subsequence of the rest, so small edits of large texts
+/// stay cheap.
+pub fn diff_hunks(old: &[u8], new: &[u8]) -> Vec<DiffHunk> {
+    let old_lines: Vec<&[u8]> = old.split(|&b| b == b'\n').collect();
+    let new_lines: Vec<&[u8]> = new.split(|&b| b == b'\n').collect();
+
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old_lines[prefix..old_lines.len() - suffix];
+    let new_middle = &new_lines[prefix..new_lines.len() - suffix];
+    if old_middle.is_empty() && new_middle.is_empty() {
+        return Vec::new();
+    }
+
+    let lcs = if old_middle.len().saturating_mul(new_middle.len()) <= MAX_LCS_CELLS {
+        longest_common_subsequence(old_middle, new_middle)
+    } else {
+        Vec::new()
+    };
+
+    let mut hunks = Vec::new();
+    let (mut old_idx, mut new_idx) = (0, 0);
+    let ends = LineMatch {
+        saved_idx: old_middle.len(),
+        current_idx: new_middle.len(),
+    };
+    for m in lcs.iter().chain(std::iter::once(&ends)) {
+        if m.saved_idx > old_idx || m.current_idx > new_idx {
+            hunks.push(DiffHunk::new(
+                prefix + old_idx,
+                m.saved_idx - old_idx,
+                prefix + new_idx,
+                m.current_idx - new_idx,
+            ));
+        }
+        old_idx = m.saved_idx + 1;
+        new_idx = m.current_idx + 1;
+    }
+    hunks
+}
+
 /// Represents a match between saved and current line indices
 #[derive(Debug, Clone, Copy)]
 struct LineMatch {
@@ -311,6 +371,18 @@ mod tests {
         assert!(diff.changed_lines.is_empty());
     }
 
+    #[test]
+    fn test_diff_hunks() {
+        let hunks = diff_hunks(b"a\nb\nc\nd\n", b"a\nx\nc\nd\ne\n");
+        let ranges: Vec<_> = hunks
+            .iter()
+            .map(|h| (h.old_start, h.old_count, h.new_start, h.new_count))
+            .collect();
+        assert_eq!(ranges, vec![(1, 1, 1, 1), (4, 0, 4, 1)]);
+
+        assert!(diff_hunks(b"same\n", b"same\n").is_empty());
+    }
+
     #[test]
     fn test_empty_files() {
         let diff = diff_lines(b"", b"");
@@ -0,0 +1,395 @@
+//! Three-way merge of texts by lines
+//!
+//! The changes from a common base to two versions of it ("local" and
+//! "remote") are lined up by the base lines they touch. Changes to separate
+//! parts of the base are both taken, while changes to the same or adjacent
+//! lines conflict unless they are the same, as in `git merge`. Conflicts are
+//! written with the usual `<<<<<<<`, `=======` and `>>>>>>>` markers.
+
+use std::ops::Range;
+
+use crate::model::composite_buffer::DiffHunk;
+use crate::model::line_diff::diff_hunks;
+
+/// Marker line starting a conflict (followed by the local lines)
+pub const CONFLICT_START: &str = "<<<<<<<";
+/// Marker line starting the base lines of a conflict (diff3 style)
+pub const CONFLICT_BASE: &str = "|||||||";
+/// Marker line between the local and remote lines of a conflict
+pub const CONFLICT_SEPARATOR: &str = "=======";
+/// Marker line ending a conflict
+pub const CONFLICT_END: &str = ">>>>>>>";
+
+/// Lines of a base text and of versions of it that correspond to each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// Lines of the base
+    pub base: Range<usize>,
+    /// Lines of each version
+    pub sides: Vec<Range<usize>>,
+    /// Whether each version changed these lines
+    pub changed: Vec<bool>,
+}
+
+impl Region {
+    /// Whether any version changed these lines
+    pub fn is_changed(&self) -> bool {
+        self.changed.iter().any(|&changed| changed)
+    }
+}
+
+/// Split a base text into regions, given the hunks that turn it into each
+/// of its versions
+///
+/// Hunks of different versions that touch the same or adjacent base lines
+/// end up in one changed region; the lines between them are unchanged in
+/// every version.
+pub fn regions(side_hunks: &[Vec<DiffHunk>], base_line_count: usize) -> Vec<Region> {
+    let side_count = side_hunks.len();
+    let mut hunks: Vec<(usize, &DiffHunk)> = side_hunks
+        .iter()
+        .enumerate()
+        .flat_map(|(side, hunks)| hunks.iter().map(move |hunk| (side, hunk)))
+        .collect();
+    hunks.sort_by_key(|(_, hunk)| (hunk.old_start, hunk.old_start + hunk.old_count));
+
+    // How many lines each version has gained so far
+    let mut deltas = vec![0isize; side_count];
+    let shift = |line: usize, delta: isize| (line as isize + delta).max(0) as usize;
+    let unchanged = |base: Range<usize>, deltas: &[isize]| Region {
+        sides: deltas
+            .iter()
+            .map(|&delta| shift(base.start, delta)..shift(base.end, delta))
+            .collect(),
+        base,
+        changed: vec![false; side_count],
+    };
+
+    let mut regions = Vec::new();
+    let mut base_line = 0;
+    let mut index = 0;
+    while index < hunks.len() {
+        let start = hunks[index].1.old_start;
+        let mut end = start + hunks[index].1.old_count;
+        let mut group_end = index + 1;
+        while let Some((_, hunk)) = hunks.get(group_end) {
+            if hunk.old_start > end {
+                break;
+            }
+            end = end.max(hunk.old_start + hunk.old_count);
+            group_end += 1;
+        }
+
+        if start > base_line {
+            regions.push(unchanged(base_line..start, &deltas));
+        }
+        let mut sides = Vec::with_capacity(side_count);
+        let mut changed = vec![false; side_count];
+        for side in 0..side_count {
+            let side_start = shift(start, deltas[side]);
+            if let Some((_, last)) = hunks[index..group_end]
+                .iter()
+                .rev()
+                .find(|(hunk_side, _)| *hunk_side == side)
+            {
+                deltas[side] = (last.new_start + last.new_count) as isize
+                    - (last.old_start + last.old_count) as isize;
+                changed[side] = true;
+            }
+            sides.push(side_start..shift(end, deltas[side]));
+        }
+        regions.push(Region {
+            base: start..end,
+            sides,
+            changed,
+        });
+        base_line = end;
+        index = group_end;
+    }
+    if base_line < base_line_count {
+        regions.push(unchanged(base_line..base_line_count, &deltas));
+    }
+    regions
+}
+
+/// How a part of a three-way merge was resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkKind {
+    /// Neither version changed the base
+    Unchanged,
+    /// Only the local version changed the base
+    Local,
+    /// Only the remote version changed the base
+    Remote,
+    /// Both versions made the same change
+    Same,
+    /// The versions made different changes
+    Conflict,
+}
+
+/// A part of a three-way merge, with the lines of each text it covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeChunk {
+    pub kind: ChunkKind,
+    pub base: Range<usize>,
+    pub local: Range<usize>,
+    pub remote: Range<usize>,
+}
+
+/// The merged text of a three-way merge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merge {
+    /// The merged text, with markers around the conflicts
+    pub text: String,
+    /// Number of conflicts
+    pub conflicts: usize,
+}
+
+/// Split a three-way merge into chunks (texts are split into lines at `\n`,
+/// like `diff_hunks` does)
+pub fn merge_chunks(base: &str, local: &str, remote: &str) -> Vec<MergeChunk> {
+    let local_lines: Vec<&str> = local.split('\n').collect();
+    let remote_lines: Vec<&str> = remote.split('\n').collect();
+    let side_hunks = vec![
+        diff_hunks(base.as_bytes(), local.as_bytes()),
+        diff_hunks(base.as_bytes(), remote.as_bytes()),
+    ];
+    regions(&side_hunks, base.split('\n').count())
+        .into_iter()
+        .map(|region| {
+            let local = region.sides[0].clone();
+            let remote = region.sides[1].clone();
+            let kind = match (region.changed[0], region.changed[1]) {
+                (false, false) => ChunkKind::Unchanged,
+                (true, false) => ChunkKind::Local,
+                (false, true) => ChunkKind::Remote,
+                (true, true) if local_lines[local.clone()] == remote_lines[remote.clone()] => {
+                    ChunkKind::Same
+                }
+                (true, true) => ChunkKind::Conflict,
+            };
+            MergeChunk {
+                kind,
+                base: region.base,
+                local,
+                remote,
+            }
+        })
+        .collect()
+}
+
+/// Merge the changes from `base` to `local` and to `remote`
+pub fn merge(base: &str, local: &str, remote: &str) -> Merge {
+    let base_lines: Vec<&str> = base.split('\n').collect();
+    let local_lines: Vec<&str> = local.split('\n').collect();
+    let remote_lines: Vec<&str> = remote.split('\n').collect();
+    let local_start = format!("{} LOCAL", CONFLICT_START);
+    let remote_end = format!("{} REMOTE", CONFLICT_END);
+
+    let mut lines: Vec<&str> = Vec::with_capacity(base_lines.len());
+    let mut conflicts = 0;
+    for chunk in merge_chunks(base, local, remote) {
+        match chunk.kind {
+            ChunkKind::Unchanged => lines.extend(&base_lines[chunk.base]),
+            ChunkKind::Local | ChunkKind::Same => lines.extend(&local_lines[chunk.local]),
+            ChunkKind::Remote => lines.extend(&remote_lines[chunk.remote]),
+            ChunkKind::Conflict => {
+                conflicts += 1;
+                lines.push(&local_start);
+                lines.extend(&local_lines[chunk.local]);
+                lines.push(CONFLICT_SEPARATOR);
+                lines.extend(&remote_lines[chunk.remote]);
+                lines.push(&remote_end);
+            }
+        }
+    }
+    Merge {
+        text: lines.join("\n"),
+        conflicts,
+    }
+}
+
+/// A conflict left in a text, by line numbers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictBlock {
+    /// All lines of the conflict, including the markers
+    pub lines: Range<usize>,
+    /// The local lines
+    pub local: Range<usize>,
+    /// The remote lines
+    pub remote: Range<usize>,
+}
+
+/// Which side of a conflict to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Local,
+    Remote,
+    /// The local lines followed by the remote ones
+    Both,
+}
+
+/// Find the conflicts marked in a text (incomplete ones are skipped)
+pub fn conflict_blocks(text: &str) -> Vec<ConflictBlock> {
+    let mut blocks = Vec::new();
+    let mut start = None;
+    let mut local_end = None;
+    let mut separator = None;
+    for (index, line) in text.split('\n').enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with(CONFLICT_START) {
+            start = Some(index);
+            local_end = None;
+            separator = None;
+        } else if line.starts_with(CONFLICT_BASE) && start.is_some() && separator.is_none() {
+            local_end.get_or_insert(index);
+        } else if line == CONFLICT_SEPARATOR && start.is_some() && separator.is_none() {
+            separator = Some(index);
+        } else if line.starts_with(CONFLICT_END) {
+            if let (Some(start), Some(separator)) = (start, separator) {
+                blocks.push(ConflictBlock {
+                    lines: start..index + 1,
+                    local: start + 1..local_end.unwrap_or(separator),
+                    remote: separator + 1..index,
+                });
+            }
+            start = None;
+            local_end = None;
+            separator = None;
+        }
+    }
+    blocks
+}
+
+/// `text` with a conflict replaced by the lines of the chosen side
+pub fn resolve_conflict(text: &str, block: &ConflictBlock, resolution: Resolution) -> String {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let (local, remote) = (block.local.clone(), block.remote.clone());
+    let kept: Vec<&str> = match resolution {
+        Resolution::Local => lines[local].to_vec(),
+        Resolution::Remote => lines[remote].to_vec(),
+        Resolution::Both => lines[local].iter().chain(&lines[remote]).copied().collect(),
+    };
+    lines[..block.lines.start]
+        .iter()
+        .chain(&kept)
+        .chain(&lines[block.lines.end..])
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_separate_changes() {
+        let base = "one\ntwo\nthree\nfour\nfive\n";
+        let local = "ONE\ntwo\nthree\nfour\nfive\n";
+        let remote = "one\ntwo\nthree\nfour\nFIVE\nsix\n";
+        let merged = merge(base, local, remote);
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "ONE\ntwo\nthree\nfour\nFIVE\nsix\n");
+    }
+
+    #[test]
+    fn test_merge_conflict() {
+        let base = "a\nb\nc\n";
+        let local = "a\nlocal\nc\n";
+        let remote = "a\nremote\nc\n";
+        let merged = merge(base, local, remote);
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.text,
+            "a\n<<<<<<< LOCAL\nlocal\n=======\nremote\n>>>>>>> REMOTE\nc\n"
+        );
+
+        // The same change on both sides is not a conflict
+        let merged = merge(base, local, local);
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, local);
+    }
+
+    #[test]
+    fn test_adjacent_changes_conflict() {
+        let chunks = merge_chunks("a\nb\nc\nd\n", "a\nB\nc\nd\n", "a\nb\nC\nd\n");
+        let kinds: Vec<_> = chunks.iter().map(|chunk| chunk.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ChunkKind::Unchanged,
+                ChunkKind::Conflict,
+                ChunkKind::Unchanged
+            ]
+        );
+        assert_eq!(chunks[1].base, 1..3);
+        assert_eq!(chunks[1].local, 1..3);
+        assert_eq!(chunks[1].remote, 1..3);
+    }
+
+    #[test]
+    fn test_regions_follow_line_shifts() {
+        // The first version inserts two lines, the second deletes one
+        let base = "a\nb\nc\nd\n";
+        let first = "a\nx\ny\nb\nc\nd\n";
+        let second = "a\nb\nc\n";
+        let side_hunks = vec![
+            diff_hunks(base.as_bytes(), first.as_bytes()),
+            diff_hunks(base.as_bytes(), second.as_bytes()),
+        ];
+        let regions = regions(&side_hunks, 5);
+        let spans: Vec<_> = regions
+            .iter()
+            .map(|region| (region.base.clone(), region.sides.clone()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (0..1, vec![0..1, 0..1]),
+                (1..1, vec![1..3, 1..1]),
+                (1..3, vec![3..5, 1..3]),
+                (3..4, vec![5..6, 3..3]),
+                (4..5, vec![6..7, 3..4]),
+            ]
+        );
+        assert!(regions[1].is_changed());
+        assert!(!regions[2].is_changed());
+    }
+
+    #[test]
+    fn test_conflict_blocks_and_resolution() {
+        let text = "a\n<<<<<<< LOCAL\nl1\nl2\n=======\nr\n>>>>>>> REMOTE\nb\n";
+        let blocks = conflict_blocks(text);
+        assert_eq!(
+            blocks,
+            vec![ConflictBlock {
+                lines: 1..7,
+                local: 2..4,
+                remote: 5..6,
+            }]
+        );
+        let block = &blocks[0];
+        assert_eq!(
+            resolve_conflict(text, block, Resolution::Local),
+            "a\nl1\nl2\nb\n"
+        );
+        assert_eq!(
+            resolve_conflict(text, block, Resolution::Remote),
+            "a\nr\nb\n"
+        );
+        assert_eq!(
+            resolve_conflict(text, block, Resolution::Both),
+            "a\nl1\nl2\nr\nb\n"
+        );
+
+        // diff3 style conflicts have the base lines after the local ones
+        let text = "<<<<<<< ours\nl\n||||||| base\nb\n=======\nr\n>>>>>>> theirs";
+        let blocks = conflict_blocks(text);
+        assert_eq!(blocks[0].local, 1..2);
+        assert_eq!(blocks[0].remote, 5..6);
+
+        // Unfinished conflicts are not counted
+        assert!(conflict_blocks("<<<<<<< LOCAL\nl\n=======\n").is_empty());
+    }
+}
@@ -8,9 +8,11 @@ pub mod control_event;
 pub mod cursor;
 pub mod document_model;
 pub mod edit;
+pub mod encoding;
 pub mod event;
 pub mod line_diff;
 pub mod marker;
 pub mod marker_tree;
+pub mod merge;
 pub mod piece_tree;
 pub mod piece_tree_diff;
@@ -1,5 +1,5 @@
 use std::io::{self, Read, Seek, SeekFrom};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// A position in the document (line and column)
@@ -101,6 +101,22 @@ impl StringBuffer {
     /// Load buffer data from file (for unloaded buffers)
     /// Returns error if buffer is not unloaded or if I/O fails
     pub fn load(&mut self) -> io::Result<()> {
+        self.load_with(|file_path, file_offset, bytes| {
+            let mut file = std::fs::File::open(file_path)?;
+            file.seek(SeekFrom::Start(file_offset))?;
+
+            let mut buffer = vec![0u8; bytes];
+            file.read_exact(&mut buffer)?;
+            Ok(buffer)
+        })
+    }
+
+    /// Load buffer data with `read`, which is given the file path, offset and
+    /// length and returns exactly that many bytes (e.g. from a remote file)
+    pub fn load_with<F>(&mut self, read: F) -> io::Result<()>
+    where
+        F: FnOnce(&Path, u64, usize) -> io::Result<Vec<u8>>,
+    {
         match &self.data {
             BufferData::Loaded { .. } => Ok(()), // Already loaded
             BufferData::Unloaded {
@@ -108,12 +124,7 @@ impl StringBuffer {
                 file_offset,
                 bytes,
             } => {
-                // Load from file
-                let mut file = std::fs::File::open(file_path)?;
-                file.seek(SeekFrom::Start(*file_offset as u64))?;
-
-                let mut buffer = vec![0u8; *bytes];
-                file.read_exact(&mut buffer)?;
+                let buffer = read(file_path, *file_offset as u64, *bytes)?;
 
                 // Replace with loaded data (no line indexing for lazy-loaded chunks)
                 self.data = BufferData::Loaded {
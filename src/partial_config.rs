@@ -6,7 +6,8 @@
 use crate::config::{
     CursorStyle, FileBrowserConfig, FileExplorerConfig, FormatterConfig, HighlighterPreference,
     Keybinding, KeybindingMapName, KeymapConfig, LanguageConfig, LineEndingOption, OnSaveAction,
-    TerminalConfig, ThemeName, WarningsConfig,
+    PluginPermissions, StatusBarConfig, StatusBarSegment, TerminalConfig, ThemeName,
+    WarningsConfig,
 };
 use crate::types::LspServerConfig;
 use serde::{Deserialize, Serialize};
@@ -87,6 +88,8 @@ pub struct PartialConfig {
     pub languages: Option<HashMap<String, PartialLanguageConfig>>,
     pub lsp: Option<HashMap<String, LspServerConfig>>,
     pub warnings: Option<PartialWarningsConfig>,
+    pub status_bar: Option<PartialStatusBarConfig>,
+    pub plugin_permissions: Option<HashMap<String, PluginPermissions>>,
 }
 
 impl Merge for PartialConfig {
@@ -102,6 +105,7 @@ impl Merge for PartialConfig {
         merge_partial(&mut self.file_browser, &other.file_browser);
         merge_partial(&mut self.terminal, &other.terminal);
         merge_partial(&mut self.warnings, &other.warnings);
+        merge_partial(&mut self.status_bar, &other.status_bar);
 
         // Lists: higher precedence replaces (per design doc)
         self.keybindings.merge_from(&other.keybindings);
@@ -110,6 +114,7 @@ impl Merge for PartialConfig {
         merge_hashmap(&mut self.keybinding_maps, &other.keybinding_maps);
         merge_hashmap_recursive(&mut self.languages, &other.languages);
         merge_hashmap_recursive(&mut self.lsp, &other.lsp);
+        merge_hashmap(&mut self.plugin_permissions, &other.plugin_permissions);
 
         self.active_keybinding_map
             .merge_from(&other.active_keybinding_map);
@@ -140,16 +145,26 @@ pub struct PartialEditorConfig {
     pub snapshot_interval: Option<usize>,
     pub large_file_threshold_bytes: Option<u64>,
     pub estimated_line_length: Option<usize>,
+    pub hex_view_binary_files: Option<bool>,
     pub enable_inlay_hints: Option<bool>,
+    pub enable_semantic_tokens: Option<bool>,
+    pub enable_code_lens: Option<bool>,
     pub recovery_enabled: Option<bool>,
     pub auto_save_interval_secs: Option<u32>,
+    pub persistent_undo: Option<bool>,
+    pub persistent_undo_max_bytes: Option<u64>,
+    pub persistent_undo_max_age_days: Option<u32>,
     pub highlight_context_bytes: Option<usize>,
     pub mouse_hover_enabled: Option<bool>,
     pub mouse_hover_delay_ms: Option<u64>,
+    pub which_key_enabled: Option<bool>,
+    pub which_key_delay_ms: Option<u64>,
     pub double_click_time_ms: Option<u64>,
+    pub native_file_watching: Option<bool>,
     pub auto_revert_poll_interval_ms: Option<u64>,
     pub file_tree_poll_interval_ms: Option<u64>,
     pub default_line_ending: Option<LineEndingOption>,
+    pub privileged_save_command: Option<String>,
     pub cursor_style: Option<CursorStyle>,
 }
 
@@ -171,25 +186,42 @@ impl Merge for PartialEditorConfig {
             .merge_from(&other.large_file_threshold_bytes);
         self.estimated_line_length
             .merge_from(&other.estimated_line_length);
+        self.hex_view_binary_files
+            .merge_from(&other.hex_view_binary_files);
         self.enable_inlay_hints
             .merge_from(&other.enable_inlay_hints);
+        self.enable_semantic_tokens
+            .merge_from(&other.enable_semantic_tokens);
+        self.enable_code_lens.merge_from(&other.enable_code_lens);
         self.recovery_enabled.merge_from(&other.recovery_enabled);
         self.auto_save_interval_secs
             .merge_from(&other.auto_save_interval_secs);
+        self.persistent_undo.merge_from(&other.persistent_undo);
+        self.persistent_undo_max_bytes
+            .merge_from(&other.persistent_undo_max_bytes);
+        self.persistent_undo_max_age_days
+            .merge_from(&other.persistent_undo_max_age_days);
         self.highlight_context_bytes
             .merge_from(&other.highlight_context_bytes);
         self.mouse_hover_enabled
             .merge_from(&other.mouse_hover_enabled);
         self.mouse_hover_delay_ms
             .merge_from(&other.mouse_hover_delay_ms);
+        self.which_key_enabled.merge_from(&other.which_key_enabled);
+        self.which_key_delay_ms
+            .merge_from(&other.which_key_delay_ms);
         self.double_click_time_ms
             .merge_from(&other.double_click_time_ms);
+        self.native_file_watching
+            .merge_from(&other.native_file_watching);
         self.auto_revert_poll_interval_ms
             .merge_from(&other.auto_revert_poll_interval_ms);
         self.file_tree_poll_interval_ms
             .merge_from(&other.file_tree_poll_interval_ms);
         self.default_line_ending
             .merge_from(&other.default_line_ending);
+        self.privileged_save_command
+            .merge_from(&other.privileged_save_command);
         self.cursor_style.merge_from(&other.cursor_style);
     }
 }
@@ -257,6 +289,24 @@ impl Merge for PartialWarningsConfig {
     }
 }
 
+/// Partial status bar configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialStatusBarConfig {
+    pub left: Option<Vec<StatusBarSegment>>,
+    pub center: Option<Vec<StatusBarSegment>>,
+    pub right: Option<Vec<StatusBarSegment>>,
+}
+
+impl Merge for PartialStatusBarConfig {
+    fn merge_from(&mut self, other: &Self) {
+        // Each side is a list: higher precedence replaces it
+        self.left.merge_from(&other.left);
+        self.center.merge_from(&other.center);
+        self.right.merge_from(&other.right);
+    }
+}
+
 /// Partial language configuration.
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(default)]
@@ -330,16 +380,26 @@ impl From<&crate::config::EditorConfig> for PartialEditorConfig {
             snapshot_interval: Some(cfg.snapshot_interval),
             large_file_threshold_bytes: Some(cfg.large_file_threshold_bytes),
             estimated_line_length: Some(cfg.estimated_line_length),
+            hex_view_binary_files: Some(cfg.hex_view_binary_files),
             enable_inlay_hints: Some(cfg.enable_inlay_hints),
+            enable_semantic_tokens: Some(cfg.enable_semantic_tokens),
+            enable_code_lens: Some(cfg.enable_code_lens),
             recovery_enabled: Some(cfg.recovery_enabled),
             auto_save_interval_secs: Some(cfg.auto_save_interval_secs),
+            persistent_undo: Some(cfg.persistent_undo),
+            persistent_undo_max_bytes: Some(cfg.persistent_undo_max_bytes),
+            persistent_undo_max_age_days: Some(cfg.persistent_undo_max_age_days),
             highlight_context_bytes: Some(cfg.highlight_context_bytes),
             mouse_hover_enabled: Some(cfg.mouse_hover_enabled),
             mouse_hover_delay_ms: Some(cfg.mouse_hover_delay_ms),
+            which_key_enabled: Some(cfg.which_key_enabled),
+            which_key_delay_ms: Some(cfg.which_key_delay_ms),
             double_click_time_ms: Some(cfg.double_click_time_ms),
+            native_file_watching: Some(cfg.native_file_watching),
             auto_revert_poll_interval_ms: Some(cfg.auto_revert_poll_interval_ms),
             file_tree_poll_interval_ms: Some(cfg.file_tree_poll_interval_ms),
             default_line_ending: Some(cfg.default_line_ending.clone()),
+            privileged_save_command: Some(cfg.privileged_save_command.clone()),
             cursor_style: Some(cfg.cursor_style),
         }
     }
@@ -370,13 +430,27 @@ impl PartialEditorConfig {
             estimated_line_length: self
                 .estimated_line_length
                 .unwrap_or(defaults.estimated_line_length),
+            hex_view_binary_files: self
+                .hex_view_binary_files
+                .unwrap_or(defaults.hex_view_binary_files),
             enable_inlay_hints: self
                 .enable_inlay_hints
                 .unwrap_or(defaults.enable_inlay_hints),
+            enable_semantic_tokens: self
+                .enable_semantic_tokens
+                .unwrap_or(defaults.enable_semantic_tokens),
+            enable_code_lens: self.enable_code_lens.unwrap_or(defaults.enable_code_lens),
             recovery_enabled: self.recovery_enabled.unwrap_or(defaults.recovery_enabled),
             auto_save_interval_secs: self
                 .auto_save_interval_secs
                 .unwrap_or(defaults.auto_save_interval_secs),
+            persistent_undo: self.persistent_undo.unwrap_or(defaults.persistent_undo),
+            persistent_undo_max_bytes: self
+                .persistent_undo_max_bytes
+                .unwrap_or(defaults.persistent_undo_max_bytes),
+            persistent_undo_max_age_days: self
+                .persistent_undo_max_age_days
+                .unwrap_or(defaults.persistent_undo_max_age_days),
             highlight_context_bytes: self
                 .highlight_context_bytes
                 .unwrap_or(defaults.highlight_context_bytes),
@@ -386,9 +460,16 @@ impl PartialEditorConfig {
             mouse_hover_delay_ms: self
                 .mouse_hover_delay_ms
                 .unwrap_or(defaults.mouse_hover_delay_ms),
+            which_key_enabled: self.which_key_enabled.unwrap_or(defaults.which_key_enabled),
+            which_key_delay_ms: self
+                .which_key_delay_ms
+                .unwrap_or(defaults.which_key_delay_ms),
             double_click_time_ms: self
                 .double_click_time_ms
                 .unwrap_or(defaults.double_click_time_ms),
+            native_file_watching: self
+                .native_file_watching
+                .unwrap_or(defaults.native_file_watching),
             auto_revert_poll_interval_ms: self
                 .auto_revert_poll_interval_ms
                 .unwrap_or(defaults.auto_revert_poll_interval_ms),
@@ -398,6 +479,9 @@ impl PartialEditorConfig {
             default_line_ending: self
                 .default_line_ending
                 .unwrap_or(defaults.default_line_ending.clone()),
+            privileged_save_command: self
+                .privileged_save_command
+                .unwrap_or(defaults.privileged_save_command.clone()),
             cursor_style: self.cursor_style.unwrap_or(defaults.cursor_style),
         }
     }
@@ -481,6 +565,26 @@ impl PartialWarningsConfig {
     }
 }
 
+impl From<&StatusBarConfig> for PartialStatusBarConfig {
+    fn from(cfg: &StatusBarConfig) -> Self {
+        Self {
+            left: Some(cfg.left.clone()),
+            center: Some(cfg.center.clone()),
+            right: Some(cfg.right.clone()),
+        }
+    }
+}
+
+impl PartialStatusBarConfig {
+    pub fn resolve(self, defaults: &StatusBarConfig) -> StatusBarConfig {
+        StatusBarConfig {
+            left: self.left.unwrap_or_else(|| defaults.left.clone()),
+            center: self.center.unwrap_or_else(|| defaults.center.clone()),
+            right: self.right.unwrap_or_else(|| defaults.right.clone()),
+        }
+    }
+}
+
 impl From<&LanguageConfig> for PartialLanguageConfig {
     fn from(cfg: &LanguageConfig) -> Self {
         Self {
@@ -551,6 +655,8 @@ impl From<&crate::config::Config> for PartialConfig {
             ),
             lsp: Some(cfg.lsp.clone()),
             warnings: Some(PartialWarningsConfig::from(&cfg.warnings)),
+            status_bar: Some(PartialStatusBarConfig::from(&cfg.status_bar)),
+            plugin_permissions: Some(cfg.plugin_permissions.clone()),
         }
     }
 }
@@ -639,6 +745,13 @@ impl PartialConfig {
                 .warnings
                 .map(|e| e.resolve(&defaults.warnings))
                 .unwrap_or_else(|| defaults.warnings.clone()),
+            status_bar: self
+                .status_bar
+                .map(|e| e.resolve(&defaults.status_bar))
+                .unwrap_or_else(|| defaults.status_bar.clone()),
+            plugin_permissions: self
+                .plugin_permissions
+                .unwrap_or_else(|| defaults.plugin_permissions.clone()),
         }
     }
 }
@@ -0,0 +1,234 @@
+//! Tree-sitter grammars loaded at runtime
+//!
+//! Besides the grammars compiled into Fresh, tree-sitter grammars can be
+//! installed as shared libraries in the grammars directory
+//! (`~/.config/fresh/grammars/`):
+//!
+//! ```text
+//! grammars/
+//!   zig.so                  (or libtree-sitter-zig.so, zig.dylib, zig.dll)
+//!   queries/
+//!     zig/
+//!       highlights.scm      syntax highlighting
+//!       indents.scm         auto-indentation (@indent / @dedent captures)
+//!       locals.scm          scope-aware word highlighting
+//! ```
+//!
+//! The library must export `tree_sitter_<name>` (with `-` in the name
+//! written as `_`), as built by `tree-sitter build`. The queries are
+//! optional. A language refers to the grammar by name through the `grammar`
+//! field of its `LanguageConfig`.
+//!
+//! Loaded grammars are kept for the life of the process, since their
+//! `tree_sitter::Language` points into the library.
+
+use once_cell::sync::Lazy;
+use std::path::Path;
+use std::sync::RwLock;
+
+/// Subdirectory of the grammars directory holding the queries of each grammar
+const QUERIES_DIR: &str = "queries";
+
+/// Extension of shared libraries on this platform
+const LIBRARY_EXTENSION: &str = std::env::consts::DLL_EXTENSION;
+
+/// A tree-sitter grammar loaded from a shared library
+pub struct DynamicGrammar {
+    /// Name the grammar is referred to by
+    pub name: String,
+    /// The grammar
+    pub language: tree_sitter::Language,
+    /// Highlights query (empty if there is none)
+    pub highlights_query: String,
+    /// Indents query
+    pub indents_query: Option<String>,
+    /// Locals query
+    pub locals_query: Option<String>,
+    /// Keeps the grammar's code loaded
+    _library: libloading::Library,
+}
+
+impl std::fmt::Debug for DynamicGrammar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynamicGrammar")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The grammars loaded so far (never removed, see the module docs)
+static GRAMMARS: Lazy<RwLock<Vec<&'static DynamicGrammar>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Find a loaded grammar by name
+pub fn find(name: &str) -> Option<&'static DynamicGrammar> {
+    GRAMMARS
+        .read()
+        .ok()?
+        .iter()
+        .find(|grammar| grammar.name == name)
+        .copied()
+}
+
+/// Load the grammars in a grammars directory that aren't loaded yet
+///
+/// Grammars that fail to load are logged and skipped. Returns the number
+/// of grammars loaded.
+pub fn load_grammars(dir: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut loaded = 0;
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some(LIBRARY_EXTENSION) {
+            continue;
+        }
+        let Some(name) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(grammar_name)
+        else {
+            continue;
+        };
+        if find(&name).is_some() {
+            continue;
+        }
+        match load_grammar(&path, &name, &dir.join(QUERIES_DIR).join(&name)) {
+            Ok(grammar) => {
+                tracing::info!("Loaded tree-sitter grammar {} from {:?}", name, path);
+                if register(grammar) {
+                    loaded += 1;
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load tree-sitter grammar {:?}: {}", path, e);
+            }
+        }
+    }
+    loaded
+}
+
+/// Add a grammar to the loaded ones
+fn register(grammar: DynamicGrammar) -> bool {
+    let Ok(mut grammars) = GRAMMARS.write() else {
+        return false;
+    };
+    grammars.push(Box::leak(Box::new(grammar)));
+    true
+}
+
+/// Name of the grammar in a library file, without the `lib` and
+/// `tree-sitter-` prefixes (`libtree-sitter-zig` -> `zig`)
+fn grammar_name(file_stem: &str) -> String {
+    let name = file_stem.strip_prefix("lib").unwrap_or(file_stem);
+    let name = name.strip_prefix("tree-sitter-").unwrap_or(name);
+    name.to_string()
+}
+
+/// Load a grammar library and the queries found in `queries_dir`
+fn load_grammar(path: &Path, name: &str, queries_dir: &Path) -> Result<DynamicGrammar, String> {
+    let symbol = format!("tree_sitter_{}", name.replace('-', "_"));
+    // SAFETY: a tree-sitter grammar library exports `tree_sitter_<name>` as a
+    // function without arguments returning its `TSLanguage`. The library is
+    // stored with the language and never unloaded.
+    let (library, language) = unsafe {
+        let library = libloading::Library::new(path).map_err(|e| e.to_string())?;
+        let language_fn = *library
+            .get::<unsafe extern "C" fn() -> *const ()>(symbol.as_bytes())
+            .map_err(|e| e.to_string())?;
+        let language =
+            tree_sitter::Language::new(tree_sitter_language::LanguageFn::from_raw(language_fn));
+        (library, language)
+    };
+
+    // Grammars generated for another tree-sitter ABI can't be parsed with
+    let version = language.abi_version();
+    if !(tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+        .contains(&version)
+    {
+        return Err(format!(
+            "grammar ABI version {} is not supported (expected {} to {})",
+            version,
+            tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION,
+            tree_sitter::LANGUAGE_VERSION
+        ));
+    }
+
+    let query = |file: &str| std::fs::read_to_string(queries_dir.join(file)).ok();
+    Ok(DynamicGrammar {
+        name: name.to_string(),
+        language,
+        highlights_query: query("highlights.scm").unwrap_or_default(),
+        indents_query: query("indents.scm"),
+        locals_query: query("locals.scm"),
+        _library: library,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grammar_name() {
+        assert_eq!(grammar_name("zig"), "zig");
+        assert_eq!(grammar_name("libtree-sitter-zig"), "zig");
+        assert_eq!(grammar_name("tree-sitter-elixir"), "elixir");
+        assert_eq!(grammar_name("libkotlin"), "kotlin");
+    }
+
+    #[test]
+    fn test_invalid_libraries_are_skipped() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path()
+                .join(format!("not-a-grammar.{}", LIBRARY_EXTENSION)),
+            "not a library",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "text").unwrap();
+
+        assert_eq!(load_grammars(dir.path()), 0);
+        assert!(find("not-a-grammar").is_none());
+        assert_eq!(load_grammars(&dir.path().join("missing")), 0);
+    }
+
+    /// A grammar registered like a loaded one is used by a language naming
+    /// it, here the Rust grammar linked into the editor under another name
+    #[cfg(unix)]
+    #[test]
+    fn test_configured_language_uses_grammar() {
+        use crate::model::buffer::Buffer;
+        use crate::primitives::highlighter::{Highlighter, Language};
+        use crate::primitives::indent::IndentCalculator;
+        use crate::view::theme::Theme;
+
+        assert!(register(DynamicGrammar {
+            name: "linked-rust".to_string(),
+            language: tree_sitter_rust::LANGUAGE.into(),
+            highlights_query: tree_sitter_rust::HIGHLIGHTS_QUERY.to_string(),
+            indents_query: Some(include_str!("../../queries/rust/indents.scm").to_string()),
+            locals_query: None,
+            _library: libloading::os::unix::Library::this().into(),
+        }));
+        let config: crate::config::LanguageConfig = serde_json::from_value(
+            serde_json::json!({"extensions": ["lrs"], "grammar": "linked-rust"}),
+        )
+        .unwrap();
+        let languages = std::collections::HashMap::from([("linked".to_string(), config)]);
+
+        let language =
+            Language::from_path_with_languages(Path::new("main.lrs"), &languages).unwrap();
+        assert!(matches!(&language, Language::Dynamic(grammar) if grammar.name == "linked-rust"));
+
+        let buffer = Buffer::from_str_test("fn main() {\n    let x = 1;\n}");
+        let theme = Theme::dark();
+        let spans = Highlighter::new(language.clone())
+            .unwrap()
+            .highlight_viewport(&buffer, 0, buffer.len(), &theme, 100_000);
+        assert!(spans.iter().any(|span| span.color == theme.syntax_keyword));
+
+        let buffer = Buffer::from_str_test("fn main() {");
+        let indent = IndentCalculator::new().calculate_indent(&buffer, buffer.len(), &language, 4);
+        assert_eq!(indent, Some(4));
+    }
+}
@@ -0,0 +1,214 @@
+//! Foldable range computation
+//!
+//! A fold range describes a block of lines that can be collapsed under its first
+//! line (the fold header). Ranges come from the language server
+//! (`textDocument/foldingRange`) when one is available; otherwise they are
+//! derived from the tree-sitter syntax tree, where every named node spanning
+//! several lines is considered foldable.
+
+use crate::model::buffer::Buffer;
+use crate::primitives::highlighter::Language;
+use std::collections::BTreeMap;
+use tree_sitter::{Node, Parser};
+
+/// Don't parse buffers larger than this for tree-sitter fold ranges
+const MAX_PARSE_BYTES: usize = 4 * 1024 * 1024;
+
+/// A foldable region of a buffer (0-indexed lines)
+///
+/// `start_line` stays visible as the fold header, lines
+/// `start_line + 1..=end_line` are hidden when the range is folded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoldRange {
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Text shown after the header while folded (defaults to "⋯")
+    pub collapsed_text: Option<String>,
+}
+
+impl FoldRange {
+    pub fn new(start_line: usize, end_line: usize) -> Self {
+        Self {
+            start_line,
+            end_line,
+            collapsed_text: None,
+        }
+    }
+
+    /// Convert an LSP folding range (returns None for single-line ranges)
+    pub fn from_lsp(range: &lsp_types::FoldingRange) -> Option<Self> {
+        let start_line = range.start_line as usize;
+        let end_line = range.end_line as usize;
+        if end_line <= start_line {
+            return None;
+        }
+        Some(Self {
+            start_line,
+            end_line,
+            collapsed_text: range.collapsed_text.clone(),
+        })
+    }
+
+    /// Whether `line` is the header or one of the hidden lines of this range
+    pub fn contains_line(&self, line: usize) -> bool {
+        line >= self.start_line && line <= self.end_line
+    }
+}
+
+/// Pick the range to fold for a cursor on `line`
+///
+/// Returns the innermost range containing the line whose header is not in
+/// `folded_headers`, so that folding repeatedly works outwards.
+pub fn innermost_range_at_line<'a>(
+    ranges: &'a [FoldRange],
+    line: usize,
+    folded_headers: &[usize],
+) -> Option<&'a FoldRange> {
+    ranges
+        .iter()
+        .filter(|r| r.contains_line(line) && !folded_headers.contains(&r.start_line))
+        .max_by(|a, b| {
+            a.start_line
+                .cmp(&b.start_line)
+                .then(b.end_line.cmp(&a.end_line))
+        })
+}
+
+/// Compute fold ranges from the tree-sitter syntax tree of a buffer
+///
+/// Ranges are keyed by header line; when several nodes start on the same line
+/// the largest one wins. A closing delimiter that sits alone on the last line
+/// (`}`, `end`, `)`) is left visible below the fold header, and ranges whose
+/// body isn't indented deeper than the header are dropped (e.g. a Python block
+/// starting on the same line as its first statement).
+pub fn tree_sitter_fold_ranges(buffer: &Buffer, language: &Language) -> Vec<FoldRange> {
+    if buffer.len() > MAX_PARSE_BYTES {
+        return Vec::new();
+    }
+    let Some(text) = buffer.to_string() else {
+        return Vec::new();
+    };
+
+    let mut parser = Parser::new();
+    if parser
+        .set_language(&language.tree_sitter_language())
+        .is_err()
+    {
+        tracing::error!("Failed to set tree-sitter language for folding");
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(&text, None) else {
+        return Vec::new();
+    };
+
+    let mut by_start: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut cursor = tree.walk();
+    // Depth-first walk over every node below the root
+    if cursor.goto_first_child() {
+        loop {
+            let node = cursor.node();
+            if node.is_named() {
+                let start = node.start_position().row;
+                let end = fold_end_row(node, &text);
+                if end > start {
+                    let entry = by_start.entry(start).or_insert(end);
+                    *entry = (*entry).max(end);
+                }
+            }
+
+            if cursor.goto_first_child() {
+                continue;
+            }
+            if cursor.goto_next_sibling() {
+                continue;
+            }
+            let mut done = true;
+            while cursor.goto_parent() {
+                if cursor.goto_next_sibling() {
+                    done = false;
+                    break;
+                }
+            }
+            if done {
+                break;
+            }
+        }
+    }
+
+    let lines: Vec<&str> = text.split('\n').collect();
+    by_start
+        .into_iter()
+        .filter(|&(start, end)| body_is_indented(&lines, start, end))
+        .map(|(start, end)| FoldRange::new(start, end))
+        .collect()
+}
+
+/// Whether the first non-blank line after `start` is indented deeper than `start`
+fn body_is_indented(lines: &[&str], start: usize, end: usize) -> bool {
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let Some(header) = lines.get(start) else {
+        return false;
+    };
+    lines
+        .iter()
+        .take(end + 1)
+        .skip(start + 1)
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| indent(line) > indent(header))
+}
+
+/// Last line a fold over `node` should hide
+fn fold_end_row(node: Node, text: &str) -> usize {
+    let end = node.end_position();
+    // A node ending at column 0 doesn't actually cover its last row
+    let mut row = if end.column == 0 {
+        end.row.saturating_sub(1)
+    } else {
+        end.row
+    };
+
+    // Keep a trailing closing delimiter visible when it's alone on its line
+    let mut last = node;
+    while let Some(child) = last.child(last.child_count().saturating_sub(1)) {
+        last = child;
+    }
+    if last.id() != node.id() && !last.is_named() && last.start_position().row == row {
+        let start = last.start_byte();
+        let line_start = text[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        if text[line_start..start].trim().is_empty() {
+            row = row.saturating_sub(1);
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_sitter_fold_ranges_rust() {
+        let buffer = Buffer::from_str_test(
+            "fn main() {\n    let x = 1;\n    if x > 0 {\n        println!();\n    }\n}\n",
+        );
+        let ranges = tree_sitter_fold_ranges(&buffer, &Language::Rust);
+        // Closing braces stay visible
+        assert_eq!(ranges, vec![FoldRange::new(0, 4), FoldRange::new(2, 3)]);
+    }
+
+    #[test]
+    fn test_tree_sitter_fold_ranges_python() {
+        let buffer = Buffer::from_str_test("def f():\n    a = 1\n    return a\n\nx = 2\n");
+        let ranges = tree_sitter_fold_ranges(&buffer, &Language::Python);
+        assert_eq!(ranges, vec![FoldRange::new(0, 2)]);
+    }
+
+    #[test]
+    fn test_innermost_range_at_line() {
+        let ranges = vec![FoldRange::new(0, 10), FoldRange::new(2, 5)];
+        assert_eq!(innermost_range_at_line(&ranges, 3, &[]), Some(&ranges[1]));
+        assert_eq!(innermost_range_at_line(&ranges, 3, &[2]), Some(&ranges[0]));
+        assert_eq!(innermost_range_at_line(&ranges, 7, &[]), Some(&ranges[0]));
+        assert_eq!(innermost_range_at_line(&ranges, 11, &[]), None);
+    }
+}
@@ -0,0 +1,353 @@
+//! Call and type hierarchy trees
+//!
+//! The LSP call hierarchy (`callHierarchy/incomingCalls`, `outgoingCalls`) and
+//! type hierarchy (`typeHierarchy/supertypes`, `subtypes`) both start from an
+//! item prepared at a position and are explored one level at a time. This
+//! module holds the lazily expanded tree shown by the hierarchy panel: nodes
+//! whose children have not been requested yet have `children: None`.
+
+use lsp_types::{
+    CallHierarchyItem, Location, Range, SymbolKind, SymbolTag, TypeHierarchyItem, Uri,
+};
+
+/// Which relation a hierarchy tree follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyDirection {
+    /// Functions calling the item
+    IncomingCalls,
+    /// Functions called by the item
+    OutgoingCalls,
+    /// Types the item derives from
+    Supertypes,
+    /// Types deriving from the item
+    Subtypes,
+}
+
+impl HierarchyDirection {
+    /// Whether this is a call hierarchy (as opposed to a type hierarchy)
+    pub fn is_call(self) -> bool {
+        matches!(self, Self::IncomingCalls | Self::OutgoingCalls)
+    }
+
+    /// The other direction of the same hierarchy
+    pub fn flipped(self) -> Self {
+        match self {
+            Self::IncomingCalls => Self::OutgoingCalls,
+            Self::OutgoingCalls => Self::IncomingCalls,
+            Self::Supertypes => Self::Subtypes,
+            Self::Subtypes => Self::Supertypes,
+        }
+    }
+
+    /// LSP method listing the children of an item
+    pub fn method(self) -> &'static str {
+        match self {
+            Self::IncomingCalls => "callHierarchy/incomingCalls",
+            Self::OutgoingCalls => "callHierarchy/outgoingCalls",
+            Self::Supertypes => "typeHierarchy/supertypes",
+            Self::Subtypes => "typeHierarchy/subtypes",
+        }
+    }
+}
+
+/// An item of either hierarchy
+///
+/// `CallHierarchyItem` and `TypeHierarchyItem` carry the same fields; items
+/// are converted back to the LSP type of their hierarchy when asking the
+/// server for their children, so `data` must be kept untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchyItem {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub tags: Option<Vec<SymbolTag>>,
+    pub detail: Option<String>,
+    pub uri: Uri,
+    /// Range of the whole item (e.g. including the body)
+    pub range: Range,
+    /// Range of the item's name
+    pub selection_range: Range,
+    pub data: Option<serde_json::Value>,
+}
+
+impl From<CallHierarchyItem> for HierarchyItem {
+    fn from(item: CallHierarchyItem) -> Self {
+        Self {
+            name: item.name,
+            kind: item.kind,
+            tags: item.tags,
+            detail: item.detail,
+            uri: item.uri,
+            range: item.range,
+            selection_range: item.selection_range,
+            data: item.data,
+        }
+    }
+}
+
+impl From<HierarchyItem> for CallHierarchyItem {
+    fn from(item: HierarchyItem) -> Self {
+        Self {
+            name: item.name,
+            kind: item.kind,
+            tags: item.tags,
+            detail: item.detail,
+            uri: item.uri,
+            range: item.range,
+            selection_range: item.selection_range,
+            data: item.data,
+        }
+    }
+}
+
+impl From<TypeHierarchyItem> for HierarchyItem {
+    fn from(item: TypeHierarchyItem) -> Self {
+        Self {
+            name: item.name,
+            kind: item.kind,
+            tags: item.tags.map(|tag| vec![tag]),
+            detail: item.detail,
+            uri: item.uri,
+            range: item.range,
+            selection_range: item.selection_range,
+            data: item.data,
+        }
+    }
+}
+
+impl From<HierarchyItem> for TypeHierarchyItem {
+    fn from(item: HierarchyItem) -> Self {
+        Self {
+            name: item.name,
+            kind: item.kind,
+            tags: item.tags.and_then(|tags| tags.into_iter().next()),
+            detail: item.detail,
+            uri: item.uri,
+            range: item.range,
+            selection_range: item.selection_range,
+            data: item.data,
+        }
+    }
+}
+
+/// A child reported by the server for an item
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchyChild {
+    pub item: HierarchyItem,
+    /// Where the call happens (call hierarchy only)
+    pub call_site: Option<Location>,
+}
+
+/// A node of a hierarchy tree
+#[derive(Debug, Clone)]
+pub struct HierarchyNode {
+    pub item: HierarchyItem,
+    /// Where the call happens, jumped to instead of the item when set
+    pub call_site: Option<Location>,
+    /// Nesting level (roots are 0)
+    pub depth: usize,
+    /// Child nodes, `None` until requested from the server
+    pub children: Option<Vec<usize>>,
+    /// Whether the children are shown
+    pub expanded: bool,
+}
+
+impl HierarchyNode {
+    /// Location to jump to for this node
+    pub fn target(&self) -> Location {
+        self.call_site.clone().unwrap_or_else(|| Location {
+            uri: self.item.uri.clone(),
+            range: self.item.selection_range,
+        })
+    }
+}
+
+/// What toggling a node requires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyToggle {
+    /// The children must be requested from the server first
+    NeedsChildren,
+    /// The node was expanded or collapsed
+    Toggled,
+    /// The node has no children
+    Leaf,
+}
+
+/// A lazily expanded hierarchy
+#[derive(Debug, Clone)]
+pub struct HierarchyTree {
+    pub direction: HierarchyDirection,
+    /// All nodes ever loaded; roots come first
+    nodes: Vec<HierarchyNode>,
+    /// Number of roots
+    roots: usize,
+}
+
+impl HierarchyTree {
+    /// Create a tree from the items returned by the prepare request
+    pub fn new(direction: HierarchyDirection, roots: Vec<HierarchyItem>) -> Self {
+        let roots_len = roots.len();
+        let nodes = roots
+            .into_iter()
+            .map(|item| HierarchyNode {
+                item,
+                call_site: None,
+                depth: 0,
+                children: None,
+                expanded: false,
+            })
+            .collect();
+        Self {
+            direction,
+            nodes,
+            roots: roots_len,
+        }
+    }
+
+    /// The items the tree was prepared from
+    pub fn root_items(&self) -> Vec<HierarchyItem> {
+        self.nodes[..self.roots]
+            .iter()
+            .map(|node| node.item.clone())
+            .collect()
+    }
+
+    pub fn node(&self, index: usize) -> Option<&HierarchyNode> {
+        self.nodes.get(index)
+    }
+
+    /// Store the children of a node and expand it
+    pub fn set_children(&mut self, index: usize, children: Vec<HierarchyChild>) {
+        let Some(depth) = self.nodes.get(index).map(|node| node.depth + 1) else {
+            return;
+        };
+        let first = self.nodes.len();
+        let count = children.len();
+        self.nodes
+            .extend(children.into_iter().map(|child| HierarchyNode {
+                item: child.item,
+                call_site: child.call_site,
+                depth,
+                children: None,
+                expanded: false,
+            }));
+        let node = &mut self.nodes[index];
+        node.children = Some((first..first + count).collect());
+        node.expanded = true;
+    }
+
+    /// Expand or collapse a node
+    pub fn toggle(&mut self, index: usize) -> HierarchyToggle {
+        let Some(node) = self.nodes.get_mut(index) else {
+            return HierarchyToggle::Leaf;
+        };
+        match &node.children {
+            None => HierarchyToggle::NeedsChildren,
+            Some(children) if children.is_empty() => HierarchyToggle::Leaf,
+            Some(_) => {
+                node.expanded = !node.expanded;
+                HierarchyToggle::Toggled
+            }
+        }
+    }
+
+    /// Nodes to display, in order
+    pub fn visible(&self) -> Vec<usize> {
+        let mut visible = Vec::new();
+        let mut stack: Vec<usize> = (0..self.roots).rev().collect();
+        while let Some(index) = stack.pop() {
+            visible.push(index);
+            let node = &self.nodes[index];
+            if let (true, Some(children)) = (node.expanded, &node.children) {
+                stack.extend(children.iter().rev());
+            }
+        }
+        visible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    fn item(name: &str) -> HierarchyItem {
+        let range = Range::new(Position::new(0, 0), Position::new(0, 4));
+        HierarchyItem {
+            name: name.to_string(),
+            kind: SymbolKind::FUNCTION,
+            tags: None,
+            detail: None,
+            uri: "file:///a.rs".parse().unwrap(),
+            range,
+            selection_range: range,
+            data: Some(serde_json::json!({ "id": name })),
+        }
+    }
+
+    fn child(name: &str) -> HierarchyChild {
+        HierarchyChild {
+            item: item(name),
+            call_site: None,
+        }
+    }
+
+    fn names(tree: &HierarchyTree) -> Vec<String> {
+        tree.visible()
+            .into_iter()
+            .map(|i| {
+                let node = tree.node(i).unwrap();
+                format!("{}{}", "  ".repeat(node.depth), node.item.name)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lazy_expansion() {
+        let mut tree = HierarchyTree::new(HierarchyDirection::IncomingCalls, vec![item("main")]);
+        assert_eq!(names(&tree), vec!["main"]);
+        assert_eq!(tree.toggle(0), HierarchyToggle::NeedsChildren);
+
+        tree.set_children(0, vec![child("a"), child("b")]);
+        assert_eq!(names(&tree), vec!["main", "  a", "  b"]);
+
+        // Children of "a" are listed right below it
+        tree.set_children(1, vec![child("c")]);
+        assert_eq!(names(&tree), vec!["main", "  a", "    c", "  b"]);
+
+        // Collapsing hides the whole subtree, expanding restores it
+        assert_eq!(tree.toggle(0), HierarchyToggle::Toggled);
+        assert_eq!(names(&tree), vec!["main"]);
+        assert_eq!(tree.toggle(0), HierarchyToggle::Toggled);
+        assert_eq!(names(&tree), vec!["main", "  a", "    c", "  b"]);
+
+        tree.set_children(2, Vec::new());
+        assert_eq!(tree.toggle(2), HierarchyToggle::Leaf);
+    }
+
+    #[test]
+    fn test_target_prefers_call_site() {
+        let mut tree = HierarchyTree::new(HierarchyDirection::IncomingCalls, vec![item("main")]);
+        let site = Location {
+            uri: "file:///b.rs".parse().unwrap(),
+            range: Range::new(Position::new(7, 2), Position::new(7, 6)),
+        };
+        tree.set_children(
+            0,
+            vec![HierarchyChild {
+                item: item("caller"),
+                call_site: Some(site.clone()),
+            }],
+        );
+        assert_eq!(tree.node(0).unwrap().target().uri.as_str(), "file:///a.rs");
+        assert_eq!(tree.node(1).unwrap().target(), site);
+    }
+
+    #[test]
+    fn test_item_round_trip_keeps_data() {
+        let original = item("main");
+        let call: CallHierarchyItem = original.clone().into();
+        assert_eq!(HierarchyItem::from(call), original);
+        let ty: TypeHierarchyItem = original.clone().into();
+        assert_eq!(HierarchyItem::from(ty), original);
+    }
+}
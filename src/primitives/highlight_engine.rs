@@ -523,7 +523,7 @@ impl HighlightEngine {
                 Self::textmate_for_file_with_languages(path, registry, languages)
             }
             HighlighterPreference::TreeSitter => {
-                if let Some(lang) = Language::from_path(path) {
+                if let Some(lang) = Language::from_path_with_languages(path, languages) {
                     if let Ok(highlighter) = Highlighter::new(lang) {
                         return Self::TreeSitter(highlighter);
                     }
@@ -603,7 +603,7 @@ impl HighlightEngine {
         let syntax_set = registry.syntax_set_arc();
 
         // Detect tree-sitter language for non-highlighting features
-        let ts_language = Language::from_path(path);
+        let ts_language = Language::from_path_with_languages(path, languages);
 
         // Find syntax by file extension, checking languages config first
         if let Some(syntax) = registry.find_syntax_for_file_with_languages(path, languages) {
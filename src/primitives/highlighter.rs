@@ -11,6 +11,7 @@
 
 use crate::config::LARGE_FILE_THRESHOLD_BYTES;
 use crate::model::buffer::Buffer;
+use crate::primitives::dynamic_grammar::DynamicGrammar;
 use crate::view::theme::Theme;
 use ratatui::style::Color;
 use std::ops::Range;
@@ -88,6 +89,35 @@ impl HighlightCategory {
         }
     }
 
+    /// Map an LSP semantic token type and its modifiers to a category
+    ///
+    /// Returns None for types outside the standard set, whose text keeps its
+    /// syntax highlighting color.
+    pub fn from_semantic_token(token_type: &str, modifiers: &[&str]) -> Option<Self> {
+        match token_type {
+            "namespace" | "type" | "class" | "enum" | "interface" | "struct" | "typeParameter" => {
+                Some(Self::Type)
+            }
+            "parameter" | "variable" => {
+                if modifiers.contains(&"readonly") || modifiers.contains(&"static") {
+                    Some(Self::Constant)
+                } else {
+                    Some(Self::Variable)
+                }
+            }
+            "property" | "event" => Some(Self::Property),
+            "enumMember" => Some(Self::Constant),
+            "function" | "method" | "macro" => Some(Self::Function),
+            "keyword" | "modifier" => Some(Self::Keyword),
+            "comment" => Some(Self::Comment),
+            "string" | "regexp" => Some(Self::String),
+            "number" => Some(Self::Number),
+            "operator" => Some(Self::Operator),
+            "decorator" => Some(Self::Attribute),
+            _ => None,
+        }
+    }
+
     /// Get the color for this category from the theme
     pub fn color(&self, theme: &Theme) -> Color {
         match self {
@@ -145,6 +175,8 @@ pub enum Language {
     Lua,
     Pascal,
     // Markdown,  // Disabled due to tree-sitter version conflict
+    /// A grammar loaded at runtime (see `dynamic_grammar`)
+    Dynamic(&'static DynamicGrammar),
 }
 
 impl Language {
@@ -173,6 +205,81 @@ impl Language {
         }
     }
 
+    /// Find a language by tree-sitter grammar name, as used by the `grammar`
+    /// field of `LanguageConfig`, including grammars loaded at runtime
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rust" => Some(Language::Rust),
+            "python" => Some(Language::Python),
+            "javascript" => Some(Language::JavaScript),
+            "typescript" => Some(Language::TypeScript),
+            "html" => Some(Language::HTML),
+            "css" => Some(Language::CSS),
+            "c" => Some(Language::C),
+            "cpp" => Some(Language::Cpp),
+            "go" => Some(Language::Go),
+            "json" => Some(Language::Json),
+            "java" => Some(Language::Java),
+            "c_sharp" | "csharp" => Some(Language::CSharp),
+            "php" => Some(Language::Php),
+            "ruby" => Some(Language::Ruby),
+            "bash" => Some(Language::Bash),
+            "lua" => Some(Language::Lua),
+            "pascal" => Some(Language::Pascal),
+            _ => crate::primitives::dynamic_grammar::find(name).map(Language::Dynamic),
+        }
+    }
+
+    /// Detect language from the languages configuration, then from the file
+    /// extension
+    ///
+    /// A language whose `filenames` or `extensions` match the file is used if
+    /// its `grammar` names a tree-sitter grammar. When several match, the
+    /// first by language name wins.
+    pub fn from_path_with_languages(
+        path: &std::path::Path,
+        languages: &std::collections::HashMap<String, crate::config::LanguageConfig>,
+    ) -> Option<Self> {
+        let file_name = path.file_name().and_then(|name| name.to_str());
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        let mut configs: Vec<_> = languages.iter().collect();
+        configs.sort_by_key(|(name, _)| name.as_str());
+        let configured = configs
+            .iter()
+            .filter(|(_, config)| {
+                file_name.is_some_and(|name| config.filenames.iter().any(|f| f == name))
+            })
+            .chain(configs.iter().filter(|(_, config)| {
+                extension.is_some_and(|ext| config.extensions.iter().any(|e| e == ext))
+            }))
+            .find_map(|(_, config)| Self::from_name(&config.grammar));
+        configured.or_else(|| Self::from_path(path))
+    }
+
+    /// Get the tree-sitter grammar for this language
+    pub fn tree_sitter_language(&self) -> tree_sitter::Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::HTML => tree_sitter_html::LANGUAGE.into(),
+            Self::CSS => tree_sitter_css::LANGUAGE.into(),
+            Self::C => tree_sitter_c::LANGUAGE.into(),
+            Self::Cpp => tree_sitter_cpp::LANGUAGE.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+            Self::Json => tree_sitter_json::LANGUAGE.into(),
+            Self::Java => tree_sitter_java::LANGUAGE.into(),
+            Self::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
+            Self::Php => tree_sitter_php::LANGUAGE_PHP.into(),
+            Self::Ruby => tree_sitter_ruby::LANGUAGE.into(),
+            Self::Bash => tree_sitter_bash::LANGUAGE.into(),
+            Self::Lua => tree_sitter_lua::LANGUAGE.into(),
+            Self::Pascal => tree_sitter_pascal::LANGUAGE.into(),
+            Self::Dynamic(grammar) => grammar.language.clone(),
+        }
+    }
+
     /// Get tree-sitter highlight configuration for this language
     fn highlight_config(&self) -> Result<HighlightConfiguration, String> {
         match self {
@@ -650,6 +757,33 @@ impl Language {
                     "variable",
                 ]);
 
+                Ok(config)
+            }
+            Self::Dynamic(grammar) => {
+                let mut config = HighlightConfiguration::new(
+                    grammar.language.clone(),
+                    &grammar.name,
+                    &grammar.highlights_query,
+                    "", // injections query
+                    grammar.locals_query.as_deref().unwrap_or(""),
+                )
+                .map_err(|e| format!("Failed to create {} highlight config: {e}", grammar.name))?;
+
+                // Captures like `function.method` map to the closest of these
+                config.configure(&[
+                    "attribute",
+                    "comment",
+                    "constant",
+                    "function",
+                    "keyword",
+                    "number",
+                    "operator",
+                    "property",
+                    "string",
+                    "type",
+                    "variable",
+                ]);
+
                 Ok(config)
             } // Language::Markdown => {
               //     // Disabled due to tree-sitter version conflict
@@ -954,6 +1088,23 @@ mod tests {
         assert!(Language::from_path(path).is_none());
     }
 
+    #[test]
+    fn test_configured_language_order() {
+        let config = |grammar: &str| -> crate::config::LanguageConfig {
+            serde_json::from_value(serde_json::json!({"extensions": ["x"], "grammar": grammar}))
+                .unwrap()
+        };
+        let mut languages = std::collections::HashMap::new();
+        for (name, grammar) in [("b", "python"), ("a", "rust"), ("c", "go")] {
+            languages.insert(name.to_string(), config(grammar));
+        }
+        let path = std::path::Path::new("file.x");
+        assert!(matches!(
+            Language::from_path_with_languages(path, &languages),
+            Some(Language::Rust)
+        ));
+    }
+
     #[test]
     fn test_highlighter_basic() {
         let buffer = Buffer::from_str_test("fn main() {\n    println!(\"Hello\");\n}");
@@ -150,6 +150,11 @@ impl IndentCalculator {
                 tree_sitter_pascal::LANGUAGE.into(),
                 include_str!("../../queries/pascal/indents.scm"),
             ),
+            Language::Dynamic(grammar) => (
+                grammar.name.as_str(),
+                grammar.language.clone(),
+                grammar.indents_query.as_deref()?,
+            ),
         };
 
         // Check if we already have this config
@@ -6,14 +6,19 @@
 pub mod ansi;
 pub mod ansi_background;
 pub mod display_width;
+pub mod dynamic_grammar;
+pub mod folding;
 pub mod grammar_registry;
 pub mod grapheme;
+pub mod hierarchy;
 pub mod highlight_engine;
 pub mod highlighter;
 pub mod indent;
 pub mod line_iterator;
 pub mod line_wrapping;
+pub mod outline;
 pub mod semantic_highlight;
+pub mod semantic_tokens;
 pub mod snippet;
 pub mod text_property;
 pub mod visual_layout;
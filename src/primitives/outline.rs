@@ -0,0 +1,421 @@
+//! Document outline (symbols defined in a buffer)
+//!
+//! Symbols come from the language server (`textDocument/documentSymbol`) when
+//! one is available. Otherwise they are extracted with the tree-sitter tags
+//! queries in `queries/<language>/tags.scm`, which use the captures:
+//! - `@name`: the symbol's name
+//! - `@definition.<kind>`: the whole definition, where kind is one of
+//!   `module`, `class`, `struct`, `enum`, `variant`, `interface`, `impl`,
+//!   `type`, `function`, `method`, `constructor`, `field`, `property`,
+//!   `constant` or `macro`
+//!
+//! Either way the result is a flat list in document order, with a nesting
+//! depth per symbol so the hierarchy can be displayed.
+
+use std::ops::Range;
+
+use lsp_types::{DocumentSymbol, DocumentSymbolResponse, SymbolKind};
+use tree_sitter::{Parser, Query, QueryCursor, StreamingIterator};
+
+use crate::model::buffer::Buffer;
+use crate::primitives::highlighter::Language;
+
+/// Don't parse buffers larger than this for tree-sitter symbols
+const MAX_PARSE_BYTES: usize = 4 * 1024 * 1024;
+
+/// A symbol defined in a buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Extra information from the language server (e.g. a signature)
+    pub detail: Option<String>,
+    /// Nesting level (0 for top-level symbols)
+    pub depth: usize,
+    /// Bytes covered by the whole definition
+    pub range: Range<usize>,
+    /// Start of the symbol's name, where "go to symbol" jumps
+    pub position: usize,
+}
+
+/// Short, language-neutral label for a symbol kind
+pub fn kind_label(kind: SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::FILE => "file",
+        SymbolKind::MODULE => "module",
+        SymbolKind::NAMESPACE => "namespace",
+        SymbolKind::PACKAGE => "package",
+        SymbolKind::CLASS => "class",
+        SymbolKind::METHOD => "method",
+        SymbolKind::PROPERTY => "property",
+        SymbolKind::FIELD => "field",
+        SymbolKind::CONSTRUCTOR => "constructor",
+        SymbolKind::ENUM => "enum",
+        SymbolKind::INTERFACE => "interface",
+        SymbolKind::FUNCTION => "function",
+        SymbolKind::VARIABLE => "variable",
+        SymbolKind::CONSTANT => "constant",
+        SymbolKind::ENUM_MEMBER => "variant",
+        SymbolKind::STRUCT => "struct",
+        SymbolKind::EVENT => "event",
+        SymbolKind::OPERATOR => "operator",
+        SymbolKind::TYPE_PARAMETER => "type",
+        SymbolKind::OBJECT => "object",
+        _ => "symbol",
+    }
+}
+
+/// Index of the innermost symbol whose definition contains `position`
+pub fn symbol_at(symbols: &[OutlineSymbol], position: usize) -> Option<usize> {
+    symbols
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.range.start <= position && position < s.range.end.max(s.range.start + 1))
+        .max_by_key(|(_, s)| s.depth)
+        .map(|(i, _)| i)
+}
+
+/// Convert a `textDocument/documentSymbol` response
+pub fn symbols_from_lsp(buffer: &Buffer, response: DocumentSymbolResponse) -> Vec<OutlineSymbol> {
+    let to_byte = |pos: lsp_types::Position| {
+        buffer.lsp_position_to_byte(pos.line as usize, pos.character as usize)
+    };
+
+    match response {
+        DocumentSymbolResponse::Nested(symbols) => {
+            fn walk(
+                symbols: Vec<DocumentSymbol>,
+                depth: usize,
+                to_byte: &dyn Fn(lsp_types::Position) -> usize,
+                out: &mut Vec<OutlineSymbol>,
+            ) {
+                let mut symbols = symbols;
+                symbols.sort_by_key(|s| (s.range.start.line, s.range.start.character));
+                for symbol in symbols {
+                    out.push(OutlineSymbol {
+                        name: symbol.name,
+                        kind: symbol.kind,
+                        detail: symbol.detail.filter(|d| !d.is_empty()),
+                        depth,
+                        range: to_byte(symbol.range.start)..to_byte(symbol.range.end),
+                        position: to_byte(symbol.selection_range.start),
+                    });
+                    if let Some(children) = symbol.children {
+                        walk(children, depth + 1, to_byte, out);
+                    }
+                }
+            }
+            let mut out = Vec::new();
+            walk(symbols, 0, &to_byte, &mut out);
+            out
+        }
+        DocumentSymbolResponse::Flat(symbols) => {
+            let symbols = symbols
+                .into_iter()
+                .map(|symbol| {
+                    let range =
+                        to_byte(symbol.location.range.start)..to_byte(symbol.location.range.end);
+                    OutlineSymbol {
+                        name: symbol.name,
+                        kind: symbol.kind,
+                        detail: symbol.container_name,
+                        depth: 0,
+                        position: range.start,
+                        range,
+                    }
+                })
+                .collect();
+            nest_by_range(symbols)
+        }
+    }
+}
+
+/// Extract symbols with the language's tree-sitter tags query
+pub fn tree_sitter_symbols(buffer: &Buffer, language: &Language) -> Vec<OutlineSymbol> {
+    let Some(query_str) = tags_query(language) else {
+        return Vec::new();
+    };
+    if buffer.len() > MAX_PARSE_BYTES {
+        return Vec::new();
+    }
+    let Some(text) = buffer.to_string() else {
+        return Vec::new();
+    };
+
+    let ts_language = language.tree_sitter_language();
+    let mut parser = Parser::new();
+    if parser.set_language(&ts_language).is_err() {
+        tracing::error!("Failed to set tree-sitter language for outline");
+        return Vec::new();
+    }
+    let query = match Query::new(&ts_language, query_str) {
+        Ok(query) => query,
+        Err(e) => {
+            tracing::error!("Failed to create tags query for {:?}: {:?}", language, e);
+            return Vec::new();
+        }
+    };
+    let Some(tree) = parser.parse(&text, None) else {
+        return Vec::new();
+    };
+
+    let capture_names = query.capture_names();
+    // (definition bytes, pattern index) of each symbol, to drop nodes matched
+    // by more than one pattern
+    let mut seen: Vec<(Range<usize>, usize)> = Vec::new();
+    let mut symbols = Vec::new();
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&query, tree.root_node(), text.as_bytes());
+    while let Some(m) = matches.next() {
+        let mut name_node = None;
+        let mut definition = None;
+        for capture in m.captures {
+            let capture_name = capture_names[capture.index as usize];
+            if capture_name == "name" {
+                name_node = Some(capture.node);
+            } else if let Some(kind) = capture_name.strip_prefix("definition.") {
+                definition = Some((capture.node, kind));
+            }
+        }
+        let (Some(name_node), Some((node, kind))) = (name_node, definition) else {
+            continue;
+        };
+
+        let range = node.byte_range();
+        if let Some(existing) = seen.iter_mut().find(|(r, _)| *r == range) {
+            // Same node matched by several patterns: the first pattern wins
+            if m.pattern_index < existing.1 {
+                existing.1 = m.pattern_index;
+                if let Some(symbol) = symbols
+                    .iter_mut()
+                    .find(|s: &&mut OutlineSymbol| s.range == range)
+                {
+                    symbol.kind = capture_kind(kind);
+                }
+            }
+            continue;
+        }
+        seen.push((range.clone(), m.pattern_index));
+
+        let name = text[name_node.byte_range()].to_string();
+        let name = if kind == "impl" {
+            format!("impl {}", name)
+        } else {
+            name
+        };
+        symbols.push(OutlineSymbol {
+            name,
+            kind: capture_kind(kind),
+            detail: None,
+            depth: 0,
+            position: name_node.start_byte(),
+            range,
+        });
+    }
+
+    let mut symbols = nest_by_range(symbols);
+    // Functions defined inside a type are methods
+    for i in 0..symbols.len() {
+        if symbols[i].kind != SymbolKind::FUNCTION || symbols[i].depth == 0 {
+            continue;
+        }
+        let parent = symbols[..i]
+            .iter()
+            .rev()
+            .find(|s| s.depth < symbols[i].depth)
+            .map(|s| s.kind);
+        if matches!(
+            parent,
+            Some(
+                SymbolKind::CLASS
+                    | SymbolKind::STRUCT
+                    | SymbolKind::INTERFACE
+                    | SymbolKind::OBJECT
+                    | SymbolKind::ENUM
+            )
+        ) {
+            symbols[i].kind = SymbolKind::METHOD;
+        }
+    }
+    symbols
+}
+
+/// Sort symbols in document order and derive depths from range containment
+fn nest_by_range(mut symbols: Vec<OutlineSymbol>) -> Vec<OutlineSymbol> {
+    symbols.sort_by_key(|s| (s.range.start, std::cmp::Reverse(s.range.end)));
+    let mut open: Vec<usize> = Vec::new();
+    for symbol in &mut symbols {
+        while open.last().is_some_and(|&end| end <= symbol.range.start) {
+            open.pop();
+        }
+        symbol.depth = open.len();
+        open.push(symbol.range.end);
+    }
+    symbols
+}
+
+fn capture_kind(kind: &str) -> SymbolKind {
+    match kind {
+        "module" => SymbolKind::MODULE,
+        "class" => SymbolKind::CLASS,
+        "struct" => SymbolKind::STRUCT,
+        "enum" => SymbolKind::ENUM,
+        "variant" => SymbolKind::ENUM_MEMBER,
+        "interface" => SymbolKind::INTERFACE,
+        "impl" => SymbolKind::OBJECT,
+        "type" => SymbolKind::TYPE_PARAMETER,
+        "method" => SymbolKind::METHOD,
+        "constructor" => SymbolKind::CONSTRUCTOR,
+        "field" => SymbolKind::FIELD,
+        "property" => SymbolKind::PROPERTY,
+        "constant" => SymbolKind::CONSTANT,
+        _ => SymbolKind::FUNCTION,
+    }
+}
+
+/// Tags query for a language, if there is one
+fn tags_query(language: &Language) -> Option<&'static str> {
+    match language {
+        Language::Rust => Some(include_str!("../../queries/rust/tags.scm")),
+        Language::Python => Some(include_str!("../../queries/python/tags.scm")),
+        Language::JavaScript => Some(include_str!("../../queries/javascript/tags.scm")),
+        Language::TypeScript => Some(include_str!("../../queries/typescript/tags.scm")),
+        Language::C => Some(include_str!("../../queries/c/tags.scm")),
+        Language::Cpp => Some(include_str!("../../queries/cpp/tags.scm")),
+        Language::Go => Some(include_str!("../../queries/go/tags.scm")),
+        Language::Java => Some(include_str!("../../queries/java/tags.scm")),
+        Language::CSharp => Some(include_str!("../../queries/csharp/tags.scm")),
+        Language::Ruby => Some(include_str!("../../queries/ruby/tags.scm")),
+        Language::Php => Some(include_str!("../../queries/php/tags.scm")),
+        Language::Lua => Some(include_str!("../../queries/lua/tags.scm")),
+        Language::Bash => Some(include_str!("../../queries/bash/tags.scm")),
+        Language::HTML
+        | Language::CSS
+        | Language::Json
+        | Language::Pascal
+        | Language::Dynamic(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(symbols: &[OutlineSymbol]) -> Vec<(usize, &str, &str)> {
+        symbols
+            .iter()
+            .map(|s| (s.depth, s.name.as_str(), kind_label(s.kind)))
+            .collect()
+    }
+
+    #[test]
+    fn test_all_tags_queries_compile() {
+        for language in [
+            Language::Rust,
+            Language::Python,
+            Language::JavaScript,
+            Language::TypeScript,
+            Language::C,
+            Language::Cpp,
+            Language::Go,
+            Language::Java,
+            Language::CSharp,
+            Language::Ruby,
+            Language::Php,
+            Language::Lua,
+            Language::Bash,
+        ] {
+            let query = tags_query(&language).unwrap();
+            if let Err(e) = Query::new(&language.tree_sitter_language(), query) {
+                panic!("tags query for {:?} failed to compile: {:?}", language, e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tree_sitter_symbols_rust() {
+        let buffer = Buffer::from_str_test(
+            "struct Point {\n    x: i32,\n}\n\nimpl Point {\n    fn new() -> Self {\n        todo!()\n    }\n}\n\nfn main() {}\n",
+        );
+        let symbols = tree_sitter_symbols(&buffer, &Language::Rust);
+        assert_eq!(
+            names(&symbols),
+            vec![
+                (0, "Point", "struct"),
+                (1, "x", "field"),
+                (0, "impl Point", "object"),
+                (1, "new", "method"),
+                (0, "main", "function"),
+            ]
+        );
+        // Jump target is the name, not the start of the definition
+        assert_eq!(
+            symbols[3].position,
+            buffer.to_string().unwrap().find("new").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tree_sitter_symbols_python() {
+        let buffer = Buffer::from_str_test(
+            "LIMIT = 3\n\nclass A:\n    def run(self):\n        pass\n\ndef helper():\n    pass\n",
+        );
+        let symbols = tree_sitter_symbols(&buffer, &Language::Python);
+        assert_eq!(
+            names(&symbols),
+            vec![
+                (0, "LIMIT", "constant"),
+                (0, "A", "class"),
+                (1, "run", "method"),
+                (0, "helper", "function"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_symbols_from_lsp_nested() {
+        #[allow(deprecated)]
+        fn symbol(
+            name: &str,
+            kind: SymbolKind,
+            lines: (u32, u32),
+            children: Option<Vec<DocumentSymbol>>,
+        ) -> DocumentSymbol {
+            let range = lsp_types::Range::new(
+                lsp_types::Position::new(lines.0, 0),
+                lsp_types::Position::new(lines.1, 1),
+            );
+            DocumentSymbol {
+                name: name.to_string(),
+                detail: None,
+                kind,
+                tags: None,
+                deprecated: None,
+                range,
+                selection_range: range,
+                children,
+            }
+        }
+
+        let buffer = Buffer::from_str_test("class A {\n  f() {}\n}\nfunction g() {}\n");
+        let response = DocumentSymbolResponse::Nested(vec![
+            symbol("g", SymbolKind::FUNCTION, (3, 3), None),
+            symbol(
+                "A",
+                SymbolKind::CLASS,
+                (0, 2),
+                Some(vec![symbol("f", SymbolKind::METHOD, (1, 1), None)]),
+            ),
+        ]);
+        let symbols = symbols_from_lsp(&buffer, response);
+        assert_eq!(
+            names(&symbols),
+            vec![(0, "A", "class"), (1, "f", "method"), (0, "g", "function")]
+        );
+        assert_eq!(symbols[1].position, 10);
+        assert_eq!(symbol_at(&symbols, 10), Some(1));
+        assert_eq!(symbol_at(&symbols, 12), Some(0));
+        assert_eq!(symbol_at(&symbols, 0), Some(0));
+    }
+}
@@ -0,0 +1,296 @@
+//! LSP semantic tokens
+//!
+//! Servers send semantic tokens as a flat array of five integers per token,
+//! with each position relative to the previous token and the token type and
+//! modifiers given as indices into the legend the server announced at
+//! initialization. This module decodes that array into absolute positions,
+//! applies the edits of `textDocument/semanticTokens/full/delta` responses to
+//! a previous result, and turns tokens into colored byte ranges.
+//!
+//! Token colors come from per-theme overrides (see
+//! `Theme::semantic_token_color`) or from the `HighlightCategory` the token
+//! maps to. Tokens without either keep their tree-sitter or TextMate color.
+
+use crate::model::buffer::Buffer;
+use crate::primitives::highlighter::{HighlightCategory, HighlightSpan};
+use crate::view::theme::Theme;
+use lsp_types::{
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensEdit,
+    SemanticTokensLegend,
+};
+use ratatui::style::Color;
+
+/// Token types the editor announces to servers
+pub const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::CLASS,
+    SemanticTokenType::ENUM,
+    SemanticTokenType::INTERFACE,
+    SemanticTokenType::STRUCT,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::ENUM_MEMBER,
+    SemanticTokenType::EVENT,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::METHOD,
+    SemanticTokenType::MACRO,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::MODIFIER,
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::STRING,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::REGEXP,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::DECORATOR,
+];
+
+/// Token modifiers the editor announces to servers
+pub const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::DEFINITION,
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::STATIC,
+    SemanticTokenModifier::DEPRECATED,
+    SemanticTokenModifier::ABSTRACT,
+    SemanticTokenModifier::ASYNC,
+    SemanticTokenModifier::MODIFICATION,
+    SemanticTokenModifier::DOCUMENTATION,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
+];
+
+/// A semantic token at an absolute position (0-indexed line, UTF-16 column)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbsoluteToken {
+    pub line: u32,
+    pub start: u32,
+    /// Length in UTF-16 code units
+    pub length: u32,
+    /// Index into the legend's token types
+    pub token_type: u32,
+    /// Bit set of indices into the legend's token modifiers
+    pub modifiers: u32,
+}
+
+/// Convert relative tokens to absolute positions
+pub fn decode(data: &[SemanticToken]) -> Vec<AbsoluteToken> {
+    let mut line = 0;
+    let mut start = 0;
+    data.iter()
+        .map(|token| {
+            if token.delta_line > 0 {
+                line += token.delta_line;
+                start = token.delta_start;
+            } else {
+                start += token.delta_start;
+            }
+            AbsoluteToken {
+                line,
+                start,
+                length: token.length,
+                token_type: token.token_type,
+                modifiers: token.token_modifiers_bitset,
+            }
+        })
+        .collect()
+}
+
+/// Apply `full/delta` edits to the tokens of the previous result
+///
+/// Edit offsets count integers of the flat array, so they must fall on token
+/// boundaries. On error the previous tokens should be dropped and the whole
+/// document requested again.
+pub fn apply_edits(
+    data: &mut Vec<SemanticToken>,
+    mut edits: Vec<SemanticTokensEdit>,
+) -> Result<(), String> {
+    // All offsets refer to the previous result, so apply from the back
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.start));
+    for edit in edits {
+        if edit.start % 5 != 0 || edit.delete_count % 5 != 0 {
+            return Err(format!(
+                "edit at {} deleting {} is not aligned to tokens",
+                edit.start, edit.delete_count
+            ));
+        }
+        let start = (edit.start / 5) as usize;
+        let end = start + (edit.delete_count / 5) as usize;
+        if end > data.len() {
+            return Err(format!(
+                "edit at {} deleting {} is past the end of {} tokens",
+                edit.start,
+                edit.delete_count,
+                data.len()
+            ));
+        }
+        data.splice(start..end, edit.data.unwrap_or_default());
+    }
+    Ok(())
+}
+
+/// Names of the modifiers set in `bitset`
+pub fn modifier_names(legend: &SemanticTokensLegend, bitset: u32) -> Vec<&str> {
+    legend
+        .token_modifiers
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index < 32 && bitset & (1 << index) != 0)
+        .map(|(_, modifier)| modifier.as_str())
+        .collect()
+}
+
+/// Color of a token, or None to keep the syntax highlighting color
+pub fn token_color(
+    legend: &SemanticTokensLegend,
+    token: &AbsoluteToken,
+    theme: &Theme,
+) -> Option<Color> {
+    let token_type = legend.token_types.get(token.token_type as usize)?.as_str();
+    let modifiers = modifier_names(legend, token.modifiers);
+    theme
+        .semantic_token_color(token_type, &modifiers)
+        .or_else(|| {
+            HighlightCategory::from_semantic_token(token_type, &modifiers)
+                .map(|category| category.color(theme))
+        })
+}
+
+/// Colored byte ranges of the tokens that have a color
+pub fn token_spans(
+    buffer: &Buffer,
+    legend: &SemanticTokensLegend,
+    tokens: &[AbsoluteToken],
+    theme: &Theme,
+) -> Vec<HighlightSpan> {
+    tokens
+        .iter()
+        .filter(|token| token.length > 0)
+        .filter_map(|token| {
+            let color = token_color(legend, token, theme)?;
+            let line = token.line as usize;
+            let start = buffer.lsp_position_to_byte(line, token.start as usize);
+            let end = buffer.lsp_position_to_byte(line, (token.start + token.length) as usize);
+            (start < end).then_some(HighlightSpan {
+                range: start..end,
+                color,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(delta_line: u32, delta_start: u32, length: u32, token_type: u32) -> SemanticToken {
+        SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    fn legend() -> SemanticTokensLegend {
+        SemanticTokensLegend {
+            token_types: vec![SemanticTokenType::VARIABLE, SemanticTokenType::FUNCTION],
+            token_modifiers: vec![
+                SemanticTokenModifier::DECLARATION,
+                SemanticTokenModifier::READONLY,
+            ],
+        }
+    }
+
+    #[test]
+    fn test_decode_relative_positions() {
+        let tokens = decode(&[token(1, 4, 3, 0), token(0, 6, 2, 1), token(2, 1, 5, 0)]);
+        let positions: Vec<_> = tokens.iter().map(|t| (t.line, t.start)).collect();
+        assert_eq!(positions, vec![(1, 4), (1, 10), (3, 1)]);
+    }
+
+    #[test]
+    fn test_apply_edits() {
+        let mut data = vec![token(0, 0, 1, 0), token(0, 2, 1, 0), token(0, 2, 1, 0)];
+        // Replace the second token and append one, both relative to the old array
+        let edits = vec![
+            SemanticTokensEdit {
+                start: 5,
+                delete_count: 5,
+                data: Some(vec![token(0, 3, 2, 1)]),
+            },
+            SemanticTokensEdit {
+                start: 15,
+                delete_count: 0,
+                data: Some(vec![token(1, 0, 4, 1)]),
+            },
+        ];
+        apply_edits(&mut data, edits).unwrap();
+        assert_eq!(
+            data,
+            vec![
+                token(0, 0, 1, 0),
+                token(0, 3, 2, 1),
+                token(0, 2, 1, 0),
+                token(1, 0, 4, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_edits_rejects_bad_offsets() {
+        let mut data = vec![token(0, 0, 1, 0)];
+        let misaligned = SemanticTokensEdit {
+            start: 2,
+            delete_count: 0,
+            data: None,
+        };
+        assert!(apply_edits(&mut data, vec![misaligned]).is_err());
+        let past_end = SemanticTokensEdit {
+            start: 5,
+            delete_count: 5,
+            data: None,
+        };
+        assert!(apply_edits(&mut data, vec![past_end]).is_err());
+    }
+
+    #[test]
+    fn test_modifier_names() {
+        assert_eq!(modifier_names(&legend(), 0b10), vec!["readonly"]);
+        assert_eq!(
+            modifier_names(&legend(), 0b111),
+            vec!["declaration", "readonly"]
+        );
+    }
+
+    #[test]
+    fn test_token_spans_use_utf16_columns() {
+        let buffer = Buffer::from_str_test("let é = f();\n");
+        let theme = Theme::dark();
+        let tokens = decode(&[token(0, 4, 1, 0), token(0, 4, 1, 1)]);
+        let spans = token_spans(&buffer, &legend(), &tokens, &theme);
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].range, 4..6);
+        assert_eq!(spans[0].color, theme.syntax_variable);
+        assert_eq!(spans[1].range, 9..10);
+        assert_eq!(spans[1].color, theme.syntax_function);
+    }
+
+    #[test]
+    fn test_readonly_variable_is_constant() {
+        let theme = Theme::dark();
+        let readonly = AbsoluteToken {
+            line: 0,
+            start: 0,
+            length: 1,
+            token_type: 0,
+            modifiers: 0b10,
+        };
+        assert_eq!(
+            token_color(&legend(), &readonly, &theme),
+            Some(theme.syntax_constant)
+        );
+    }
+}
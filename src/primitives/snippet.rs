@@ -1,12 +1,26 @@
-//! LSP snippet parser and expander.
+//! LSP snippet parser and snippet sessions.
 //!
-//! Parses LSP snippet syntax and expands it to plain text with cursor positioning.
+//! Parses LSP snippet syntax into plain text and tabstops.
 //! Supports:
 //! - `$0` - final cursor position
-//! - `$n` - tabstops (expanded as empty, cursor goes to $0 or end)
-//! - `${n:text}` - tabstops with default text (uses the default)
-//! - `${n|choice1,choice2|}` - choices (uses first choice)
+//! - `$n` - tabstops; a tabstop used more than once is mirrored
+//! - `${n:text}` - tabstops with placeholder text (may contain other tabstops)
+//! - `${n|choice1,choice2|}` - choices (the first one is inserted)
+//! - `$NAME` / `${NAME:default}` - variables, resolved by the caller
 //! - `\\$` - escaped dollar sign
+//!
+//! Transforms (`${n/regex/format/}`) are parsed but not applied.
+//!
+//! A [`SnippetSession`] keeps the tabstops of an inserted snippet in place as
+//! the buffer is edited, so Tab and Shift+Tab can walk through them.
+
+use std::collections::{BTreeMap, HashMap};
+use std::iter::Peekable;
+use std::ops::Range;
+use std::path::Path;
+use std::str::Chars;
+
+use serde::Deserialize;
 
 /// Result of expanding a snippet
 #[derive(Debug, Clone, PartialEq)]
@@ -17,6 +31,40 @@ pub struct ExpandedSnippet {
     pub cursor_offset: usize,
 }
 
+/// A tabstop of a parsed snippet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tabstop {
+    /// Byte ranges of the tabstop (more than one when it is mirrored)
+    pub ranges: Vec<Range<usize>>,
+    /// Choices offered at the tabstop
+    pub choices: Vec<String>,
+}
+
+/// A snippet parsed into text and tabstops
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snippet {
+    /// The text to insert
+    pub text: String,
+    /// Tabstops in the order Tab visits them, ranges relative to the start of
+    /// `text`. The last one is the final cursor position (`$0`, or the end).
+    pub tabstops: Vec<Tabstop>,
+}
+
+/// Syntax tree of a snippet
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Tabstop {
+        index: u32,
+        placeholder: Option<Vec<Node>>,
+        choices: Vec<String>,
+    },
+    Variable {
+        name: String,
+        default: Option<Vec<Node>>,
+    },
+}
+
 /// Expand an LSP snippet to plain text
 ///
 /// # Examples
@@ -34,141 +82,326 @@ pub struct ExpandedSnippet {
 /// assert_eq!(result.cursor_offset, 8); // cursor at end (no $0)
 /// ```
 pub fn expand_snippet(snippet: &str) -> ExpandedSnippet {
-    let mut result = String::new();
-    let mut cursor_offset: Option<usize> = None;
-    let mut chars = snippet.chars().peekable();
+    let snippet = parse_snippet(snippet, "\n", "\t", |_| None);
+    ExpandedSnippet {
+        cursor_offset: snippet.final_offset(),
+        text: snippet.text,
+    }
+}
+
+/// Parse an LSP snippet into text and tabstops
+///
+/// Line breaks in the snippet are replaced by `newline` (the line ending plus
+/// the indentation of the line the snippet is inserted on) and tabs by
+/// `indent`. `variable` resolves variables like `TM_FILENAME`; when it returns
+/// None the variable's default is used.
+pub fn parse_snippet(
+    snippet: &str,
+    newline: &str,
+    indent: &str,
+    variable: impl Fn(&str) -> Option<String>,
+) -> Snippet {
+    let nodes = parse_nodes(&mut snippet.chars().peekable(), false);
+
+    let mut placeholders = HashMap::new();
+    collect_placeholders(&nodes, &mut placeholders);
+
+    let mut renderer = Renderer {
+        text: String::new(),
+        ranges: BTreeMap::new(),
+        choices: HashMap::new(),
+        placeholders: &placeholders,
+        newline,
+        indent,
+        variable: &variable,
+    };
+    renderer.render(&nodes, true);
+
+    let Renderer {
+        text,
+        mut ranges,
+        mut choices,
+        ..
+    } = renderer;
+    // Without a $0 the cursor ends up after the snippet
+    let end = text.len();
+    let final_ranges = ranges
+        .remove(&0)
+        .unwrap_or_else(|| vec![Range { start: end, end }]);
+    let mut tabstops: Vec<Tabstop> = ranges
+        .into_iter()
+        .map(|(index, ranges)| Tabstop {
+            ranges,
+            choices: choices.remove(&index).unwrap_or_default(),
+        })
+        .collect();
+    tabstops.push(Tabstop {
+        ranges: final_ranges,
+        choices: Vec::new(),
+    });
+
+    Snippet { text, tabstops }
+}
+
+impl Snippet {
+    /// Offset of the final cursor position in the text
+    pub fn final_offset(&self) -> usize {
+        self.tabstops
+            .last()
+            .and_then(|tabstop| tabstop.ranges.first())
+            .map(|range| range.start)
+            .unwrap_or(self.text.len())
+    }
+}
+
+/// Parse snippet nodes up to the end, or up to the closing `}` of a placeholder
+fn parse_nodes(chars: &mut Peekable<Chars>, in_placeholder: bool) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let mut text = String::new();
 
     while let Some(c) = chars.next() {
-        if c == '\\' {
-            // Escape sequence
-            if let Some(&next) = chars.peek() {
-                if next == '$' || next == '\\' || next == '}' {
-                    result.push(chars.next().unwrap());
-                    continue;
+        match c {
+            '\\' => match chars.peek() {
+                Some(&next) if next == '$' || next == '\\' || next == '}' => {
+                    text.push(next);
+                    chars.next();
                 }
-            }
-            result.push(c);
-        } else if c == '$' {
-            // Snippet placeholder
-            if let Some(&next) = chars.peek() {
-                if next == '{' {
-                    // ${...} syntax
-                    chars.next(); // consume '{'
-                    let (expanded, is_final) = parse_brace_placeholder(&mut chars);
-                    if is_final {
-                        cursor_offset = Some(result.len());
-                    }
-                    result.push_str(&expanded);
-                } else if next.is_ascii_digit() {
-                    // $n syntax
-                    let mut num = String::new();
-                    while let Some(&d) = chars.peek() {
-                        if d.is_ascii_digit() {
-                            num.push(chars.next().unwrap());
-                        } else {
-                            break;
-                        }
-                    }
-                    if num == "0" {
-                        cursor_offset = Some(result.len());
+                _ => text.push(c),
+            },
+            '}' if in_placeholder => break,
+            '$' => match parse_dollar(chars) {
+                Some(node) => {
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(std::mem::take(&mut text)));
                     }
-                    // Other tabstops expand to nothing
-                } else {
-                    // Not a valid placeholder, keep the $
-                    result.push(c);
+                    nodes.push(node);
                 }
-            } else {
-                result.push(c);
-            }
-        } else {
-            result.push(c);
+                // Not a valid placeholder, keep the $
+                None => text.push(c),
+            },
+            _ => text.push(c),
         }
     }
 
-    ExpandedSnippet {
-        cursor_offset: cursor_offset.unwrap_or(result.len()),
-        text: result,
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
     }
+    nodes
 }
 
-/// Parse a ${...} placeholder
-/// Returns (expanded_text, is_final_cursor)
-fn parse_brace_placeholder(chars: &mut std::iter::Peekable<std::str::Chars>) -> (String, bool) {
-    let mut content = String::new();
-    let mut depth = 1;
+/// Parse what follows a `$`, or None if it does not start a placeholder
+fn parse_dollar(chars: &mut Peekable<Chars>) -> Option<Node> {
+    match *chars.peek()? {
+        c if c.is_ascii_digit() => Some(Node::Tabstop {
+            index: parse_number(chars),
+            placeholder: None,
+            choices: Vec::new(),
+        }),
+        c if is_variable_start(c) => Some(Node::Variable {
+            name: parse_name(chars),
+            default: None,
+        }),
+        '{' => {
+            // Only consume the brace if a number or a name follows
+            let mut lookahead = chars.clone();
+            lookahead.next();
+            let next = *lookahead.peek()?;
+            if !next.is_ascii_digit() && !is_variable_start(next) {
+                return None;
+            }
+            chars.next();
+            Some(parse_brace_placeholder(chars))
+        }
+        _ => None,
+    }
+}
+
+/// Parse the inside of a `${...}` placeholder, after the `{`
+fn parse_brace_placeholder(chars: &mut Peekable<Chars>) -> Node {
+    if chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+        let index = parse_number(chars);
+        let mut placeholder = None;
+        let mut choices = Vec::new();
+        match chars.next() {
+            Some(':') => placeholder = Some(parse_nodes(chars, true)),
+            Some('|') => choices = parse_choices(chars),
+            Some('/') => skip_transform(chars),
+            _ => {}
+        }
+        Node::Tabstop {
+            index,
+            placeholder,
+            choices,
+        }
+    } else {
+        let name = parse_name(chars);
+        let mut default = None;
+        match chars.next() {
+            Some(':') => default = Some(parse_nodes(chars, true)),
+            Some('/') => skip_transform(chars),
+            _ => {}
+        }
+        Node::Variable { name, default }
+    }
+}
 
-    // Collect everything until matching '}'
+/// Parse `one,two|}` after the `|` of a choice tabstop
+fn parse_choices(chars: &mut Peekable<Chars>) -> Vec<String> {
+    let mut choices = Vec::new();
+    let mut choice = String::new();
     while let Some(c) = chars.next() {
-        if c == '{' {
-            depth += 1;
-            content.push(c);
-        } else if c == '}' {
-            depth -= 1;
-            if depth == 0 {
-                break;
-            }
-            content.push(c);
-        } else if c == '\\' {
-            // Handle escapes inside braces
-            if let Some(&next) = chars.peek() {
-                if next == '$' || next == '\\' || next == '}' || next == '|' {
-                    content.push(chars.next().unwrap());
-                    continue;
+        match c {
+            '\\' => match chars.peek() {
+                Some(&next) if matches!(next, '$' | '\\' | '}' | ',' | '|') => {
+                    choice.push(next);
+                    chars.next();
+                }
+                _ => choice.push(c),
+            },
+            ',' => choices.push(std::mem::take(&mut choice)),
+            '|' => {
+                choices.push(std::mem::take(&mut choice));
+                if chars.peek() == Some(&'}') {
+                    chars.next();
                 }
+                return choices;
             }
-            content.push(c);
-        } else {
-            content.push(c);
+            _ => choice.push(c),
         }
     }
+    choices.push(choice);
+    choices
+}
 
-    // Parse the content: n or n:default or n|choices|
-    let (tabstop, default) = parse_placeholder_content(&content);
+/// Skip the rest of a transform (`regex/format/options}`)
+fn skip_transform(chars: &mut Peekable<Chars>) {
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '}' => return,
+            _ => {}
+        }
+    }
+}
 
-    let is_final = tabstop == Some(0);
-    (default, is_final)
+fn parse_number(chars: &mut Peekable<Chars>) -> u32 {
+    let mut num = String::new();
+    while let Some(&d) = chars.peek() {
+        if !d.is_ascii_digit() {
+            break;
+        }
+        num.push(d);
+        chars.next();
+    }
+    num.parse().unwrap_or(u32::MAX)
 }
 
-/// Parse placeholder content like "1", "1:default", or "1|a,b,c|"
-/// Returns (tabstop_number, default_text)
-fn parse_placeholder_content(content: &str) -> (Option<u32>, String) {
-    // Find the tabstop number
-    let mut chars = content.chars().peekable();
-    let mut num_str = String::new();
+fn is_variable_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
 
+fn parse_name(chars: &mut Peekable<Chars>) -> String {
+    let mut name = String::new();
     while let Some(&c) = chars.peek() {
-        if c.is_ascii_digit() {
-            num_str.push(chars.next().unwrap());
-        } else {
+        if !c.is_ascii_alphanumeric() && c != '_' {
             break;
         }
+        name.push(c);
+        chars.next();
     }
+    name
+}
 
-    let tabstop = num_str.parse::<u32>().ok();
-
-    // Check what follows
-    match chars.peek() {
-        Some(':') => {
-            // Default text: ${n:default}
-            chars.next(); // consume ':'
-            let default: String = chars.collect();
-            // Recursively expand nested snippets in default
-            let expanded = expand_snippet(&default);
-            (tabstop, expanded.text)
-        }
-        Some('|') => {
-            // Choices: ${n|choice1,choice2|}
-            chars.next(); // consume '|'
-            let choices: String = chars.collect();
-            // Remove trailing '|' if present
-            let choices = choices.trim_end_matches('|');
-            // Use first choice
-            let first_choice = choices.split(',').next().unwrap_or("");
-            (tabstop, first_choice.to_string())
+/// The first placeholder or choice list given for each tabstop, which its
+/// mirrors repeat
+fn collect_placeholders<'a>(nodes: &'a [Node], placeholders: &mut HashMap<u32, &'a Node>) {
+    for node in nodes {
+        match node {
+            Node::Tabstop {
+                index,
+                placeholder,
+                choices,
+            } => {
+                if placeholder.is_some() || !choices.is_empty() {
+                    placeholders.entry(*index).or_insert(node);
+                }
+                if let Some(children) = placeholder {
+                    collect_placeholders(children, placeholders);
+                }
+            }
+            Node::Variable {
+                default: Some(children),
+                ..
+            } => collect_placeholders(children, placeholders),
+            _ => {}
         }
-        _ => {
-            // Just a tabstop number, no default
-            (tabstop, String::new())
+    }
+}
+
+struct Renderer<'a, F> {
+    text: String,
+    ranges: BTreeMap<u32, Vec<Range<usize>>>,
+    choices: HashMap<u32, Vec<String>>,
+    placeholders: &'a HashMap<u32, &'a Node>,
+    newline: &'a str,
+    indent: &'a str,
+    variable: &'a F,
+}
+
+impl<F: Fn(&str) -> Option<String>> Renderer<'_, F> {
+    /// Render nodes into the text, recording tabstop ranges if `record` is set
+    /// (it is not for the copy of a placeholder shown by a mirror)
+    fn render(&mut self, nodes: &[Node], record: bool) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => {
+                    for c in text.chars() {
+                        match c {
+                            '\n' => self.text.push_str(self.newline),
+                            '\t' => self.text.push_str(self.indent),
+                            _ => self.text.push(c),
+                        }
+                    }
+                }
+                Node::Tabstop {
+                    index,
+                    placeholder,
+                    choices,
+                } => {
+                    let start = self.text.len();
+                    if let Some(children) = placeholder {
+                        self.render(children, record);
+                    } else if let Some(first) = choices.first() {
+                        self.text.push_str(first);
+                    } else if let Some(&defined) = self.placeholders.get(index) {
+                        // A mirror; nested tabstops only count where they are defined
+                        if record {
+                            self.render(std::slice::from_ref(defined), false);
+                        }
+                    }
+                    if record {
+                        self.ranges
+                            .entry(*index)
+                            .or_default()
+                            .push(start..self.text.len());
+                        if !choices.is_empty() {
+                            self.choices
+                                .entry(*index)
+                                .or_insert_with(|| choices.clone());
+                        }
+                    }
+                }
+                Node::Variable { name, default } => match (self.variable)(name) {
+                    Some(value) => self.text.push_str(&value),
+                    None => {
+                        if let Some(children) = default {
+                            self.render(children, record);
+                        }
+                    }
+                },
+            }
         }
     }
 }
@@ -194,6 +427,174 @@ pub fn is_snippet(text: &str) -> bool {
     false
 }
 
+/// Tabstops of a snippet inserted in a buffer
+///
+/// The ranges are buffer offsets, adjusted for every edit so they stay on
+/// the text they mark. Text typed at the edge of the current tabstop becomes
+/// part of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnippetSession {
+    tabstops: Vec<Tabstop>,
+    current: usize,
+    /// The whole snippet
+    bounds: Range<usize>,
+}
+
+impl SnippetSession {
+    /// Start a session for `snippet` inserted at `position`, on its first tabstop
+    pub fn new(snippet: &Snippet, position: usize) -> Self {
+        let tabstops = snippet
+            .tabstops
+            .iter()
+            .map(|tabstop| Tabstop {
+                ranges: tabstop
+                    .ranges
+                    .iter()
+                    .map(|range| range.start + position..range.end + position)
+                    .collect(),
+                choices: tabstop.choices.clone(),
+            })
+            .collect();
+        Self {
+            tabstops,
+            current: 0,
+            bounds: position..position + snippet.text.len(),
+        }
+    }
+
+    /// The tabstop the cursor is on
+    pub fn current(&self) -> &Tabstop {
+        &self.tabstops[self.current]
+    }
+
+    /// Whether the cursor is on the final position, which ends the session
+    pub fn is_final(&self) -> bool {
+        self.current + 1 == self.tabstops.len()
+    }
+
+    /// Move to the next or previous tabstop. Returns false at the first one.
+    pub fn advance(&mut self, forward: bool) -> bool {
+        if forward {
+            self.current = (self.current + 1).min(self.tabstops.len() - 1);
+            true
+        } else if self.current > 0 {
+            self.current -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether `position` is within the snippet
+    pub fn contains(&self, position: usize) -> bool {
+        self.bounds.start <= position && position <= self.bounds.end
+    }
+
+    /// Adjust the ranges for `deleted` bytes being replaced by `inserted` bytes at `position`
+    pub fn adjust_for_edit(&mut self, position: usize, deleted: usize, inserted: usize) {
+        for (i, tabstop) in self.tabstops.iter_mut().enumerate() {
+            let is_current = i == self.current;
+            for range in &mut tabstop.ranges {
+                adjust_range(range, position, deleted, inserted, is_current);
+            }
+        }
+        adjust_range(&mut self.bounds, position, deleted, inserted, true);
+    }
+}
+
+/// Adjust a range for an edit; `grow` makes text inserted at its edges part of it
+fn adjust_range(
+    range: &mut Range<usize>,
+    position: usize,
+    deleted: usize,
+    inserted: usize,
+    grow: bool,
+) {
+    let delete = |offset: usize| {
+        if offset <= position {
+            offset
+        } else {
+            offset.saturating_sub(deleted).max(position)
+        }
+    };
+    range.start = delete(range.start);
+    range.end = delete(range.end);
+
+    if position < range.start || (position == range.start && !grow) {
+        range.start += inserted;
+        range.end += inserted;
+    } else if position < range.end || (position == range.end && grow) {
+        range.end += inserted;
+    }
+}
+
+/// A snippet from the user's snippet files
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserSnippet {
+    /// Name of the snippet (its key in the file)
+    pub name: String,
+    /// Words that expand to the snippet when Tab is pressed after them
+    pub prefixes: Vec<String>,
+    /// The snippet, in LSP snippet syntax
+    pub body: String,
+    /// Shown next to the snippet in the Insert Snippet prompt
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::One(s) => vec![s],
+            Self::Many(v) => v,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SnippetFileEntry {
+    #[serde(default)]
+    prefix: Option<OneOrMany>,
+    body: OneOrMany,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// Load the user's snippets for `language` from `<dir>/<language>.json`
+///
+/// The file uses the VS Code snippet format: an object mapping snippet names
+/// to `{ "prefix": ..., "body": ..., "description": ... }`, where the prefix
+/// may be a list and the body a list of lines.
+pub fn load_user_snippets(dir: &Path, language: &str) -> Vec<UserSnippet> {
+    let path = dir.join(format!("{}.json", language));
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    let entries: BTreeMap<String, SnippetFileEntry> = match serde_json::from_str(&content) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::warn!("Failed to parse snippets file {:?}: {}", path, e);
+            return Vec::new();
+        }
+    };
+    entries
+        .into_iter()
+        .map(|(name, entry)| UserSnippet {
+            prefixes: entry.prefix.map(OneOrMany::into_vec).unwrap_or_default(),
+            body: entry.body.into_vec().join("\n"),
+            description: entry.description,
+            name,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -277,4 +678,96 @@ mod tests {
         assert_eq!(result.text, "foo()");
         assert_eq!(result.cursor_offset, 5);
     }
+
+    #[test]
+    fn test_tabstop_order_and_mirrors() {
+        let snippet = parse_snippet("${2:b} ${1:a} $1 $0!", "\n", "\t", |_| None);
+        assert_eq!(snippet.text, "b a a !");
+        let ranges: Vec<_> = snippet.tabstops.iter().map(|t| t.ranges.clone()).collect();
+        assert_eq!(ranges, vec![vec![2..3, 4..5], vec![0..1], vec![6..6]]);
+    }
+
+    #[test]
+    fn test_mirror_before_placeholder() {
+        let snippet = parse_snippet("$1 = ${1:x}", "\n", "\t", |_| None);
+        assert_eq!(snippet.text, "x = x");
+        assert_eq!(snippet.tabstops[0].ranges, vec![0..1, 4..5]);
+    }
+
+    #[test]
+    fn test_choice_tabstop() {
+        let snippet = parse_snippet("${1|a\\,b,c|} $1", "\n", "\t", |_| None);
+        assert_eq!(snippet.text, "a,b a,b");
+        assert_eq!(snippet.tabstops[0].choices, vec!["a,b", "c"]);
+    }
+
+    #[test]
+    fn test_variables() {
+        let variable = |name: &str| (name == "TM_FILENAME").then(|| "main.rs".to_string());
+        let snippet = parse_snippet(
+            "// $TM_FILENAME ${TM_SELECTED_TEXT:${1:none}} $UNKNOWN ${2/a/b/}",
+            "\n",
+            "\t",
+            variable,
+        );
+        assert_eq!(snippet.text, "// main.rs none  ");
+        assert_eq!(snippet.tabstops[0].ranges, vec![11..15]);
+    }
+
+    #[test]
+    fn test_newline_and_indent() {
+        let snippet = parse_snippet("if $1 {\n\t$0\n}", "\r\n  ", "    ", |_| None);
+        assert_eq!(snippet.text, "if  {\r\n      \r\n  }");
+        assert_eq!(snippet.final_offset(), 13);
+    }
+
+    #[test]
+    fn test_session_tracks_edits() {
+        let snippet = parse_snippet("${1:a}(${2:b}) $1$0", "\n", "\t", |_| None);
+        assert_eq!(snippet.text, "a(b) a");
+        let mut session = SnippetSession::new(&snippet, 10);
+        assert_eq!(session.current().ranges, vec![10..11, 15..16]);
+
+        // Replace both copies of the placeholder with "xy", as the cursors on them would
+        session.adjust_for_edit(15, 1, 2);
+        session.adjust_for_edit(10, 1, 2);
+        assert_eq!(session.current().ranges, vec![10..12, 16..18]);
+
+        assert!(session.advance(true));
+        assert_eq!(session.current().ranges, vec![13..14]);
+        // Typing before the snippet moves it
+        session.adjust_for_edit(0, 0, 3);
+        assert_eq!(session.current().ranges, vec![16..17]);
+
+        assert!(session.advance(true));
+        assert!(session.is_final());
+        assert_eq!(session.current().ranges, vec![21..21]);
+        assert!(session.contains(21));
+        assert!(!session.contains(22));
+    }
+
+    #[test]
+    fn test_load_user_snippets() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("rust.json"),
+            r##"{
+                "Test function": {
+                    "prefix": ["test", "tfn"],
+                    "body": ["#[test]", "fn ${1:name}() {", "\t$0", "}"],
+                    "description": "A test function"
+                },
+                "Print": { "prefix": "pr", "body": "println!(\"$1\");" }
+            }"##,
+        )
+        .unwrap();
+
+        let snippets = load_user_snippets(dir.path(), "rust");
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].name, "Print");
+        assert_eq!(snippets[0].prefixes, vec!["pr"]);
+        assert_eq!(snippets[1].prefixes, vec!["test", "tfn"]);
+        assert_eq!(snippets[1].body, "#[test]\nfn ${1:name}() {\n\t$0\n}");
+        assert!(load_user_snippets(dir.path(), "python").is_empty());
+    }
 }
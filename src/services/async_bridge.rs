@@ -106,12 +106,110 @@ pub enum AsyncMessage {
         hints: Vec<InlayHint>,
     },
 
+    /// LSP folding range response (textDocument/foldingRange)
+    LspFoldingRanges {
+        request_id: u64,
+        uri: String,
+        /// Folding ranges for the whole document
+        ranges: Vec<lsp_types::FoldingRange>,
+    },
+
+    /// LSP document symbol response (textDocument/documentSymbol)
+    LspDocumentSymbols {
+        request_id: u64,
+        uri: String,
+        /// Symbols of the document (None if unsupported or failed)
+        symbols: Option<lsp_types::DocumentSymbolResponse>,
+    },
+
+    /// LSP call/type hierarchy prepare response
+    LspHierarchyItems {
+        request_id: u64,
+        /// Items at the requested position (empty if none or unsupported)
+        items: Vec<crate::primitives::hierarchy::HierarchyItem>,
+    },
+
+    /// LSP incoming/outgoing calls, supertypes or subtypes response
+    LspHierarchyChildren {
+        request_id: u64,
+        children: Vec<crate::primitives::hierarchy::HierarchyChild>,
+    },
+
+    /// LSP formatting, range formatting or on-type formatting response
+    LspFormatting {
+        request_id: u64,
+        result: Result<Vec<lsp_types::TextEdit>, String>,
+    },
+
+    /// LSP semantic tokens response (textDocument/semanticTokens/*)
+    LspSemanticTokens {
+        request_id: u64,
+        uri: String,
+        /// Legend the token types and modifiers index into
+        legend: lsp_types::SemanticTokensLegend,
+        /// Range the tokens cover (None for the whole document)
+        range: Option<lsp_types::Range>,
+        tokens: Result<Vec<crate::primitives::semantic_tokens::AbsoluteToken>, String>,
+    },
+
+    /// LSP code lens response (textDocument/codeLens)
+    LspCodeLenses {
+        request_id: u64,
+        uri: String,
+        /// Lenses of the document, possibly without commands yet
+        lenses: Vec<lsp_types::CodeLens>,
+    },
+
+    /// LSP code lens resolve response (codeLens/resolve)
+    LspCodeLensesResolved {
+        request_id: u64,
+        uri: String,
+        /// The requested lenses in the same order, unchanged if resolving failed
+        lenses: Vec<lsp_types::CodeLens>,
+    },
+
+    /// LSP workspace/executeCommand response
+    LspExecuteCommand {
+        request_id: u64,
+        result: Result<(), String>,
+    },
+
+    /// Server asked for all code lenses to be requested again
+    /// (workspace/codeLens/refresh)
+    LspCodeLensRefresh { language: String },
+
+    /// LSP workspace/symbol response from one server
+    LspWorkspaceSymbols {
+        request_id: u64,
+        language: String,
+        /// Matching symbols (empty if unsupported, failed or cancelled)
+        symbols: Vec<lsp_types::WorkspaceSymbol>,
+    },
+
+    /// LSP workspaceSymbol/resolve response
+    LspWorkspaceSymbolResolved {
+        request_id: u64,
+        /// The symbol with its range, unchanged if resolving failed
+        symbol: lsp_types::WorkspaceSymbol,
+    },
+
+    /// LSP server asked to be notified of file changes (client/registerCapability)
+    LspFileWatchersRegistered {
+        language: String,
+        /// Registration ID, used to unregister
+        id: String,
+        watchers: Vec<lsp_types::FileSystemWatcher>,
+    },
+
+    /// LSP server no longer wants the file changes of a registration
+    LspFileWatchersUnregistered { language: String, id: String },
+
     /// LSP server status became quiescent (project fully loaded)
     /// This is a rust-analyzer specific notification (experimental/serverStatus)
     LspServerQuiescent { language: String },
 
-    /// File changed externally (future: file watching)
-    FileChanged { path: String },
+    /// Files or directories changed on disk (from the file watcher)
+    FilesChanged(Vec<crate::services::file_watcher::WatchEvent>),
 
     /// Git status updated (future: git integration)
     GitStatusChanged { status: String },
@@ -174,6 +272,15 @@ pub enum AsyncMessage {
         params: Option<Value>,
     },
 
+    /// workspace/applyEdit request from a server; the editor must reply
+    /// with the outcome using `server_request_id`
+    LspApplyEdit {
+        language: String,
+        server_request_id: i64,
+        label: Option<String>,
+        edit: lsp_types::WorkspaceEdit,
+    },
+
     /// LSP server request (server -> client)
     /// Used for custom/extension methods that plugins can handle
     LspServerRequest {
@@ -195,11 +302,29 @@ pub enum AsyncMessage {
     /// File open dialog: directory listing completed
     FileOpenDirectoryLoaded(std::io::Result<Vec<crate::services::fs::FsEntry>>),
 
+    /// Save as root: whether the helper asks for a password before saving
+    /// `buffer_id`
+    PrivilegedSaveChecked {
+        buffer_id: crate::model::event::BufferId,
+        needs_password: bool,
+    },
+
+    /// Save as root: the helper finished installing `copy`
+    PrivilegedSaveFinished {
+        buffer_id: crate::model::event::BufferId,
+        copy: crate::model::buffer::SaveCopy,
+        result: std::io::Result<()>,
+    },
+
     /// Terminal output received (triggers redraw)
     TerminalOutput { terminal_id: TerminalId },
 
     /// Terminal process exited
     TerminalExited { terminal_id: TerminalId },
+
+    /// Files to open, sent by `fresh --remote`
+    #[cfg(unix)]
+    OpenFiles(crate::services::instance_server::PendingRequest),
 }
 
 /// LSP progress value types
@@ -0,0 +1,478 @@
+//! File system change notifications
+//!
+//! On Linux the kernel reports changes in watched directories through inotify,
+//! so changes made outside the editor are seen as they happen instead of on
+//! the next poll. Directories on network filesystems are never watched:
+//! inotify only sees the changes made by the local machine there. They are
+//! still polled, as is everything on other platforms.
+//!
+//! Watches are not recursive. `watch_tree` walks a directory in the background
+//! (skipping what .gitignore excludes) and also watches the directories later
+//! created inside it.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+
+use crate::services::async_bridge::AsyncMessage;
+
+/// What happened to a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchEventKind {
+    /// Created, or moved into the directory
+    Created,
+    /// Written to
+    Changed,
+    /// Deleted, or moved out of the directory
+    Deleted,
+}
+
+/// A change to a file or directory in a watched directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchEvent {
+    pub path: PathBuf,
+    pub kind: WatchEventKind,
+}
+
+/// Watches directories and sends their changes as `AsyncMessage::FilesChanged`
+///
+/// The background thread stops when the watcher is dropped.
+pub struct FileWatcher(platform::Watcher);
+
+impl FileWatcher {
+    /// Start watching, or None when the platform has no change notifications
+    pub fn new(sender: Sender<AsyncMessage>) -> Option<Self> {
+        platform::Watcher::new(sender).map(Self)
+    }
+
+    /// Watch the entries of `dir`
+    ///
+    /// Returns false when the directory has to be polled instead.
+    pub fn watch_dir(&self, dir: &Path) -> bool {
+        self.0.watch_dir(dir)
+    }
+
+    /// Watch `root` and the directories below it
+    pub fn watch_tree(&self, root: &Path) {
+        self.0.watch_tree(root)
+    }
+
+    /// Whether changes to the entries of `dir` are reported
+    pub fn is_watched(&self, dir: &Path) -> bool {
+        self.0.is_watched(dir)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::collections::HashMap;
+    use std::os::fd::AsFd;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::Sender;
+    use std::sync::{Arc, Mutex};
+
+    use nix::errno::Errno;
+    use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
+    use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, InotifyEvent, WatchDescriptor};
+
+    use super::{WatchEvent, WatchEventKind};
+    use crate::services::async_bridge::AsyncMessage;
+
+    /// How often the reader thread checks whether the watcher was dropped
+    const STOP_CHECK_INTERVAL_MS: u16 = 250;
+
+    /// `statfs` filesystem types of network filesystems
+    const NETWORK_FILESYSTEMS: &[u32] = &[
+        0x6969,     // NFS
+        0x517B,     // SMB
+        0xFF534D42, // CIFS
+        0xFE534D42, // SMB2
+        0x5346414F, // AFS
+        0x73757245, // Coda
+        0x564C,     // NCP
+        0x65735546, // FUSE (sshfs, rclone...)
+    ];
+
+    fn watch_flags() -> AddWatchFlags {
+        AddWatchFlags::IN_CREATE
+            | AddWatchFlags::IN_DELETE
+            | AddWatchFlags::IN_MOVED_FROM
+            | AddWatchFlags::IN_MOVED_TO
+            | AddWatchFlags::IN_CLOSE_WRITE
+            | AddWatchFlags::IN_ONLYDIR
+    }
+
+    fn is_network_filesystem(dir: &Path) -> bool {
+        nix::sys::statfs::statfs(dir)
+            .map(|fs| NETWORK_FILESYSTEMS.contains(&(fs.filesystem_type().0 as u32)))
+            .unwrap_or(false)
+    }
+
+    #[derive(Default)]
+    struct Watches {
+        dirs: HashMap<WatchDescriptor, WatchedDir>,
+        by_path: HashMap<PathBuf, WatchDescriptor>,
+        /// Set once the kernel refused a watch (max_user_watches reached)
+        exhausted: bool,
+    }
+
+    struct WatchedDir {
+        path: PathBuf,
+        /// Directories created inside are watched as well
+        recursive: bool,
+    }
+
+    #[derive(Clone)]
+    struct Shared {
+        inotify: Arc<Inotify>,
+        watches: Arc<Mutex<Watches>>,
+    }
+
+    impl Shared {
+        fn add(&self, dir: &Path, recursive: bool) -> bool {
+            let Ok(mut watches) = self.watches.lock() else {
+                return false;
+            };
+            if let Some(wd) = watches.by_path.get(dir).copied() {
+                if let Some(watched) = watches.dirs.get_mut(&wd) {
+                    watched.recursive |= recursive;
+                }
+                return true;
+            }
+            if watches.exhausted || is_network_filesystem(dir) {
+                return false;
+            }
+            // The lock is held while adding so that the reader thread cannot
+            // see events of the new descriptor before it is known
+            match self.inotify.add_watch(dir, watch_flags()) {
+                // Another path of an already watched directory (through a symlink):
+                // its events carry the other path, so keep polling this one
+                Ok(wd) if watches.dirs.contains_key(&wd) => false,
+                Ok(wd) => {
+                    watches.by_path.insert(dir.to_path_buf(), wd);
+                    watches.dirs.insert(
+                        wd,
+                        WatchedDir {
+                            path: dir.to_path_buf(),
+                            recursive,
+                        },
+                    );
+                    true
+                }
+                Err(Errno::ENOSPC) => {
+                    tracing::warn!(
+                        "inotify watch limit reached, polling the remaining directories \
+                         (raise fs.inotify.max_user_watches to watch more)"
+                    );
+                    watches.exhausted = true;
+                    false
+                }
+                Err(e) => {
+                    tracing::debug!("Cannot watch {:?}: {}", dir, e);
+                    false
+                }
+            }
+        }
+
+        /// Watch `root` and the directories below it, returning the files found
+        fn add_tree(&self, root: &Path) -> Vec<PathBuf> {
+            let mut files = Vec::new();
+            let walker = ignore::WalkBuilder::new(root)
+                .hidden(false)
+                .filter_entry(|entry| entry.file_name() != ".git")
+                .build();
+            for entry in walker.flatten() {
+                if entry.file_type().is_some_and(|t| t.is_dir()) {
+                    if !self.add(entry.path(), true) && self.is_exhausted() {
+                        break;
+                    }
+                } else {
+                    files.push(entry.into_path());
+                }
+            }
+            files
+        }
+
+        fn is_exhausted(&self) -> bool {
+            self.watches.lock().map(|w| w.exhausted).unwrap_or(true)
+        }
+
+        /// Translate kernel events, watching new directories of recursive watches
+        fn translate(&self, raw: Vec<InotifyEvent>) -> Vec<WatchEvent> {
+            let mut events: Vec<WatchEvent> = Vec::new();
+            let mut new_trees = Vec::new();
+            {
+                let Ok(mut watches) = self.watches.lock() else {
+                    return events;
+                };
+                for event in raw {
+                    if event.mask.contains(AddWatchFlags::IN_Q_OVERFLOW) {
+                        tracing::warn!("inotify queue overflowed, some changes were missed");
+                        continue;
+                    }
+                    if event.mask.contains(AddWatchFlags::IN_IGNORED) {
+                        // The directory is gone or its filesystem was unmounted
+                        if let Some(watched) = watches.dirs.remove(&event.wd) {
+                            watches.by_path.remove(&watched.path);
+                        }
+                        continue;
+                    }
+                    let (Some(watched), Some(name)) = (watches.dirs.get(&event.wd), event.name)
+                    else {
+                        continue;
+                    };
+                    let path = watched.path.join(name);
+                    let kind = if event
+                        .mask
+                        .intersects(AddWatchFlags::IN_CREATE | AddWatchFlags::IN_MOVED_TO)
+                    {
+                        if watched.recursive && event.mask.contains(AddWatchFlags::IN_ISDIR) {
+                            new_trees.push(path.clone());
+                        }
+                        WatchEventKind::Created
+                    } else if event
+                        .mask
+                        .intersects(AddWatchFlags::IN_DELETE | AddWatchFlags::IN_MOVED_FROM)
+                    {
+                        WatchEventKind::Deleted
+                    } else {
+                        WatchEventKind::Changed
+                    };
+                    // A new file is usually created and written at once
+                    let repeated =
+                        events
+                            .iter()
+                            .rev()
+                            .find(|e| e.path == path)
+                            .is_some_and(|last| {
+                                last.kind == kind
+                                    || (last.kind == WatchEventKind::Created
+                                        && kind == WatchEventKind::Changed)
+                            });
+                    if !repeated {
+                        events.push(WatchEvent { path, kind });
+                    }
+                }
+            }
+            // Files may have been created in a new directory before it was watched
+            for dir in new_trees {
+                events.extend(self.add_tree(&dir).into_iter().map(|path| WatchEvent {
+                    path,
+                    kind: WatchEventKind::Created,
+                }));
+            }
+            events
+        }
+    }
+
+    pub(super) struct Watcher {
+        shared: Shared,
+        stop: Arc<AtomicBool>,
+    }
+
+    impl Watcher {
+        pub(super) fn new(sender: Sender<AsyncMessage>) -> Option<Self> {
+            let inotify = match Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC) {
+                Ok(inotify) => inotify,
+                Err(e) => {
+                    tracing::warn!("inotify unavailable, polling for file changes: {}", e);
+                    return None;
+                }
+            };
+            let shared = Shared {
+                inotify: Arc::new(inotify),
+                watches: Arc::new(Mutex::new(Watches::default())),
+            };
+            let stop = Arc::new(AtomicBool::new(false));
+
+            let thread_shared = shared.clone();
+            let thread_stop = stop.clone();
+            std::thread::Builder::new()
+                .name("file-watcher".to_string())
+                .spawn(move || read_events(thread_shared, sender, thread_stop))
+                .map_err(|e| tracing::warn!("Failed to start the file watcher: {}", e))
+                .ok()?;
+
+            Some(Self { shared, stop })
+        }
+
+        pub(super) fn watch_dir(&self, dir: &Path) -> bool {
+            self.shared.add(dir, false)
+        }
+
+        pub(super) fn watch_tree(&self, root: &Path) {
+            // Changes at the top are seen right away, the rest once walked
+            self.shared.add(root, true);
+            let shared = self.shared.clone();
+            let root = root.to_path_buf();
+            let spawned = std::thread::Builder::new()
+                .name("file-watcher-walk".to_string())
+                .spawn(move || {
+                    shared.add_tree(&root);
+                    tracing::debug!(
+                        "Watching {} directories under {:?}",
+                        shared.watches.lock().map(|w| w.dirs.len()).unwrap_or(0),
+                        root
+                    );
+                });
+            if let Err(e) = spawned {
+                tracing::warn!("Failed to start watching the workspace: {}", e);
+            }
+        }
+
+        pub(super) fn is_watched(&self, dir: &Path) -> bool {
+            self.shared
+                .watches
+                .lock()
+                .is_ok_and(|watches| watches.by_path.contains_key(dir))
+        }
+    }
+
+    impl Drop for Watcher {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn read_events(shared: Shared, sender: Sender<AsyncMessage>, stop: Arc<AtomicBool>) {
+        while !stop.load(Ordering::Relaxed) {
+            let mut fds = [PollFd::new(shared.inotify.as_fd(), PollFlags::POLLIN)];
+            match poll(&mut fds, PollTimeout::from(STOP_CHECK_INTERVAL_MS)) {
+                Ok(0) | Err(Errno::EINTR) => continue,
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!("File watcher stopped: {}", e);
+                    return;
+                }
+            }
+            let raw = match shared.inotify.read_events() {
+                Ok(raw) => raw,
+                Err(Errno::EAGAIN) => continue,
+                Err(e) => {
+                    tracing::warn!("File watcher stopped: {}", e);
+                    return;
+                }
+            };
+            let events = shared.translate(raw);
+            if !events.is_empty() && sender.send(AsyncMessage::FilesChanged(events)).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use std::path::Path;
+    use std::sync::mpsc::Sender;
+
+    use crate::services::async_bridge::AsyncMessage;
+
+    pub(super) struct Watcher;
+
+    impl Watcher {
+        pub(super) fn new(_sender: Sender<AsyncMessage>) -> Option<Self> {
+            None
+        }
+
+        pub(super) fn watch_dir(&self, _dir: &Path) -> bool {
+            false
+        }
+
+        pub(super) fn watch_tree(&self, _root: &Path) {}
+
+        pub(super) fn is_watched(&self, _dir: &Path) -> bool {
+            false
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    /// Collect events until `done` returns true or a few seconds pass
+    fn wait_for(
+        receiver: &mpsc::Receiver<AsyncMessage>,
+        mut done: impl FnMut(&[WatchEvent]) -> bool,
+    ) -> Vec<WatchEvent> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut events = Vec::new();
+        while Instant::now() < deadline && !done(&events) {
+            if let Ok(AsyncMessage::FilesChanged(batch)) =
+                receiver.recv_timeout(Duration::from_millis(50))
+            {
+                events.extend(batch);
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_watch_dir_reports_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().canonicalize().unwrap();
+        let file = dir_path.join("a.txt");
+        std::fs::write(&file, "one").unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let watcher = FileWatcher::new(sender).unwrap();
+        assert!(watcher.watch_dir(&dir_path));
+        assert!(watcher.is_watched(&dir_path));
+
+        std::fs::write(&file, "two").unwrap();
+        std::fs::remove_file(&file).unwrap();
+        let events = wait_for(&receiver, |events| {
+            events.iter().any(|e| e.kind == WatchEventKind::Deleted)
+        });
+        assert_eq!(
+            events,
+            vec![
+                WatchEvent {
+                    path: file.clone(),
+                    kind: WatchEventKind::Changed
+                },
+                WatchEvent {
+                    path: file,
+                    kind: WatchEventKind::Deleted
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watch_tree_follows_new_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("ignored")).unwrap();
+        std::fs::write(root.join(".ignore"), "ignored/\n").unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let watcher = FileWatcher::new(sender).unwrap();
+        watcher.watch_tree(&root);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !watcher.is_watched(&root) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!watcher.is_watched(&root.join("ignored")));
+
+        std::fs::create_dir(root.join("src")).unwrap();
+        let created = WatchEvent {
+            path: root.join("src"),
+            kind: WatchEventKind::Created,
+        };
+        // The directory may be watched before its event comes through
+        let events = wait_for(&receiver, |events| {
+            watcher.is_watched(&root.join("src")) && events.contains(&created)
+        });
+        assert!(events.contains(&created));
+        assert!(watcher.is_watched(&root.join("src")));
+
+        let file = root.join("src/lib.rs");
+        std::fs::write(&file, "").unwrap();
+        let events = wait_for(&receiver, |events| events.iter().any(|e| e.path == file));
+        assert_eq!(events.last().map(|e| e.kind), Some(WatchEventKind::Created));
+    }
+}
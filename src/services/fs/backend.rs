@@ -136,6 +136,33 @@ pub trait FsBackend: Send + Sync {
 
     /// Get canonical (absolute, normalized) path
     async fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Read up to `len` bytes of a file, starting at `offset`
+    ///
+    /// Returns fewer bytes only when the file ends first.
+    async fn read_range(&self, path: &Path, offset: u64, len: usize) -> io::Result<Vec<u8>>;
+
+    /// Replace the content of a file with `data`, creating it if needed
+    ///
+    /// The data is written next to the file and renamed over it, so the file
+    /// is never left partly written. An existing file keeps its permissions.
+    async fn write(&self, path: &Path, data: &[u8]) -> io::Result<()>;
+
+    /// Rename a file or directory, replacing `to` if it is a file
+    async fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Remove a file, or a directory and everything in it
+    async fn remove(&self, path: &Path) -> io::Result<()>;
+
+    /// Create a directory (its parent must exist)
+    async fn mkdir(&self, path: &Path) -> io::Result<()>;
+
+    /// Whether paths are on the local filesystem
+    ///
+    /// Buffers of local files read and write them with `std::fs` directly,
+    /// which lets large files be streamed; other backends are used for all
+    /// of a buffer's I/O.
+    fn is_local(&self) -> bool;
 }
 
 #[cfg(test)]
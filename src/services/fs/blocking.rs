@@ -0,0 +1,119 @@
+//! Blocking access to a filesystem backend
+//!
+//! `TextBuffer` reads and writes files synchronously, so it goes through
+//! `BlockingFs`, which runs the backend's operations on the editor's runtime
+//! and waits for them. Buffers made outside an editor use `BlockingFs::local`.
+
+use super::backend::FsBackend;
+use super::local::LocalFsBackend;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use tokio::runtime::{Handle, Runtime};
+
+/// A filesystem backend with the runtime its operations run on
+#[derive(Clone)]
+pub struct BlockingFs {
+    backend: Arc<dyn FsBackend>,
+    runtime: Handle,
+}
+
+impl fmt::Debug for BlockingFs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockingFs")
+            .field("backend", &"<dyn FsBackend>")
+            .finish()
+    }
+}
+
+impl BlockingFs {
+    pub fn new(backend: Arc<dyn FsBackend>, runtime: Handle) -> Self {
+        Self { backend, runtime }
+    }
+
+    /// The local filesystem, on a small runtime of its own that is started
+    /// on first use
+    pub fn local() -> &'static Self {
+        static LOCAL: OnceLock<(Runtime, BlockingFs)> = OnceLock::new();
+        let (_, fs) = LOCAL.get_or_init(|| {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("local-fs")
+                .enable_all()
+                .build()
+                .expect("failed to start the local filesystem runtime");
+            let fs = BlockingFs::new(Arc::new(LocalFsBackend::new()), runtime.handle().clone());
+            (runtime, fs)
+        });
+        fs
+    }
+
+    /// Whether the backend is the local filesystem
+    pub fn is_local(&self) -> bool {
+        self.backend.is_local()
+    }
+
+    /// Run a backend operation to completion
+    ///
+    /// Works from async code on the runtime as well, by letting the worker
+    /// block (only a multi-threaded runtime allows that).
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        if Handle::try_current().is_ok() {
+            tokio::task::block_in_place(|| self.runtime.block_on(future))
+        } else {
+            self.runtime.block_on(future)
+        }
+    }
+
+    /// Size of a file in bytes
+    pub fn size(&self, path: &Path) -> io::Result<u64> {
+        let entry = self.block_on(self.backend.get_entry(path))?;
+        if entry.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is a directory", path.display()),
+            ));
+        }
+        Ok(entry.metadata.and_then(|m| m.size).unwrap_or(0))
+    }
+
+    pub fn exists(&self, path: &Path) -> bool {
+        self.block_on(self.backend.exists(path))
+    }
+
+    pub fn is_dir(&self, path: &Path) -> bool {
+        self.block_on(self.backend.is_dir(path)).unwrap_or(false)
+    }
+
+    pub fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.block_on(self.backend.canonicalize(path))
+    }
+
+    /// Read exactly `len` bytes at `offset`, failing if the file is shorter
+    pub fn read_exact_at(&self, path: &Path, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        let data = self.read_range(path, offset, len)?;
+        if data.len() < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{} is shorter than expected", path.display()),
+            ));
+        }
+        Ok(data)
+    }
+
+    pub fn read_range(&self, path: &Path, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        self.block_on(self.backend.read_range(path, offset, len))
+    }
+
+    /// Read a whole file
+    pub fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let len = self.size(path)?;
+        self.read_range(path, 0, len as usize)
+    }
+
+    pub fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.block_on(self.backend.write(path, data))
+    }
+}
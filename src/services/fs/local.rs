@@ -67,6 +67,11 @@ impl LocalFsBackend {
         );
     }
 
+    /// Drop the cached metadata of a path that was just changed
+    async fn forget_metadata(&self, path: &Path) {
+        self.metadata_cache.write().await.pop(path);
+    }
+
     /// Read metadata for a single path
     async fn read_metadata(&self, path: &Path) -> io::Result<FsMetadata> {
         // Check cache first
@@ -201,6 +206,62 @@ impl FsBackend for LocalFsBackend {
     async fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
         fs::canonicalize(path).await
     }
+
+    async fn read_range(&self, path: &Path, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+        let mut file = fs::File::open(path).await?;
+        file.seek(io::SeekFrom::Start(offset)).await?;
+        let mut data = Vec::with_capacity(len);
+        file.take(len as u64).read_to_end(&mut data).await?;
+        Ok(data)
+    }
+
+    /// Replace the file through a temp file next to it, so a failed write
+    /// leaves it intact, keeping the permissions and, where allowed, the
+    /// owner of the file it replaces
+    async fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let original = fs::metadata(path).await.ok();
+        let temp_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&temp_path).await?;
+        file.write_all(data).await?;
+        file.sync_all().await?;
+        drop(file);
+        if let Some(original) = original {
+            fs::set_permissions(&temp_path, original.permissions()).await?;
+            restore_owner(&temp_path, &original);
+        }
+        fs::rename(&temp_path, path).await?;
+        self.forget_metadata(path).await;
+        Ok(())
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to).await?;
+        self.forget_metadata(from).await;
+        self.forget_metadata(to).await;
+        Ok(())
+    }
+
+    async fn remove(&self, path: &Path) -> io::Result<()> {
+        if fs::symlink_metadata(path).await?.is_dir() {
+            fs::remove_dir_all(path).await?;
+        } else {
+            fs::remove_file(path).await?;
+        }
+        self.forget_metadata(path).await;
+        Ok(())
+    }
+
+    async fn mkdir(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir(path).await
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
 }
 
 // Clone implementation for LocalFsBackend to enable parallel operations
@@ -213,6 +274,18 @@ impl Clone for LocalFsBackend {
     }
 }
 
+/// Give `path` the owner and group of the file it replaces
+///
+/// Errors are ignored: only root can give a file to another user.
+#[cfg(unix)]
+fn restore_owner(path: &Path, original: &std::fs::Metadata) {
+    use std::os::unix::fs::MetadataExt;
+    let _ = std::os::unix::fs::chown(path, Some(original.uid()), Some(original.gid()));
+}
+
+#[cfg(not(unix))]
+fn restore_owner(_path: &Path, _original: &std::fs::Metadata) {}
+
 /// Check if a file is hidden (starts with . on Unix, or has hidden attribute on Windows)
 fn is_hidden_file(path: &Path) -> bool {
     // Check for dot-prefix (works on all platforms)
@@ -418,6 +491,67 @@ mod tests {
         assert!(duration.as_secs() < 1);
     }
 
+    #[tokio::test]
+    async fn test_read_range() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("file.txt");
+        std_fs::write(&file_path, "0123456789").unwrap();
+
+        let backend = LocalFsBackend::new();
+        assert_eq!(backend.read_range(&file_path, 2, 3).await.unwrap(), b"234");
+        // Short at the end of the file
+        assert_eq!(backend.read_range(&file_path, 8, 5).await.unwrap(), b"89");
+        assert!(backend
+            .read_range(&file_path, 20, 5)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("script.sh");
+        std_fs::write(&file_path, "old").unwrap();
+        std_fs::set_permissions(&file_path, std_fs::Permissions::from_mode(0o750)).unwrap();
+
+        let backend = LocalFsBackend::new();
+        backend.write(&file_path, b"new").await.unwrap();
+        assert_eq!(std_fs::read_to_string(&file_path).unwrap(), "new");
+        let mode = std_fs::metadata(&file_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+
+        let new_path = temp_dir.path().join("new.txt");
+        backend.write(&new_path, b"created").await.unwrap();
+        assert_eq!(std_fs::read_to_string(&new_path).unwrap(), "created");
+    }
+
+    #[tokio::test]
+    async fn test_rename_remove_mkdir() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        let backend = LocalFsBackend::new();
+
+        let dir = temp_path.join("dir");
+        backend.mkdir(&dir).await.unwrap();
+        assert!(backend.mkdir(&temp_path.join("missing/dir")).await.is_err());
+        std_fs::write(dir.join("a.txt"), "a").unwrap();
+
+        backend
+            .rename(&dir.join("a.txt"), &dir.join("b.txt"))
+            .await
+            .unwrap();
+        assert!(!dir.join("a.txt").exists());
+        assert!(dir.join("b.txt").exists());
+
+        // Directories are removed with their content
+        backend.remove(&dir).await.unwrap();
+        assert!(!dir.exists());
+    }
+
     #[test]
     fn test_is_hidden_file() {
         assert!(is_hidden_file(Path::new(".hidden")));
@@ -2,7 +2,7 @@ use super::backend::{FsBackend, FsEntry, FsMetadata};
 use std::collections::HashMap;
 use std::fmt;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{oneshot, Mutex};
 
@@ -125,6 +125,26 @@ impl FsManager {
         self.backend.canonicalize(path).await
     }
 
+    /// Replace the content of a file, creating it if needed
+    pub async fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.backend.write(path, data).await
+    }
+
+    /// Rename a file or directory
+    pub async fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.backend.rename(from, to).await
+    }
+
+    /// Remove a file, or a directory and everything in it
+    pub async fn remove(&self, path: &Path) -> io::Result<()> {
+        self.backend.remove(path).await
+    }
+
+    /// Create a directory
+    pub async fn mkdir(&self, path: &Path) -> io::Result<()> {
+        self.backend.mkdir(path).await
+    }
+
     /// List directory and fetch metadata for all entries in parallel
     ///
     /// This is a convenience method that combines `list_dir` with
@@ -4,11 +4,15 @@
 // designed to work efficiently with both local and network filesystems.
 
 pub mod backend;
+pub mod blocking;
 pub mod local;
 pub mod manager;
+pub mod sftp;
 pub mod slow;
 
 pub use backend::{FsBackend, FsEntry, FsEntryType, FsMetadata};
+pub use blocking::BlockingFs;
 pub use local::LocalFsBackend;
 pub use manager::FsManager;
+pub use sftp::{SftpClient, SftpFsBackend, SshTarget};
 pub use slow::{BackendMetrics, SlowFsBackend, SlowFsConfig};
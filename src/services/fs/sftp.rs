@@ -0,0 +1,1014 @@
+//! Remote filesystem backend over SFTP
+//!
+//! `fresh ssh://[user@]host[:port]/path` runs `ssh host -s sftp` and speaks
+//! SFTP (version 3, which every server supports) over its stdin and stdout.
+//! Going through the system `ssh` means its configuration, keys, agent and
+//! password prompts all work as they do for `ssh` itself.
+//!
+//! The protocol is request/response, with an id on each request. Reads and
+//! writes of large ranges are split into chunks that are sent a window at a
+//! time without waiting for each reply, so latency is paid per window rather
+//! than per chunk.
+//!
+//! `SftpClient` is synchronous; `SftpFsBackend` runs it on tokio's blocking
+//! threads, one request at a time.
+
+use super::backend::{FsBackend, FsEntry, FsEntryType, FsMetadata};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+
+const SFTP_VERSION: u32 = 3;
+
+/// Bytes per READ or WRITE request (servers must accept at least 32 KiB)
+const CHUNK_SIZE: usize = 32 * 1024;
+
+/// Requests sent before waiting for replies
+const WINDOW: usize = 16;
+
+// Packet types
+const SSH_FXP_INIT: u8 = 1;
+const SSH_FXP_VERSION: u8 = 2;
+const SSH_FXP_OPEN: u8 = 3;
+const SSH_FXP_CLOSE: u8 = 4;
+const SSH_FXP_READ: u8 = 5;
+const SSH_FXP_WRITE: u8 = 6;
+const SSH_FXP_LSTAT: u8 = 7;
+const SSH_FXP_FSETSTAT: u8 = 10;
+const SSH_FXP_OPENDIR: u8 = 11;
+const SSH_FXP_READDIR: u8 = 12;
+const SSH_FXP_REMOVE: u8 = 13;
+const SSH_FXP_MKDIR: u8 = 14;
+const SSH_FXP_RMDIR: u8 = 15;
+const SSH_FXP_REALPATH: u8 = 16;
+const SSH_FXP_STAT: u8 = 17;
+const SSH_FXP_RENAME: u8 = 18;
+const SSH_FXP_STATUS: u8 = 101;
+const SSH_FXP_HANDLE: u8 = 102;
+const SSH_FXP_DATA: u8 = 103;
+const SSH_FXP_NAME: u8 = 104;
+const SSH_FXP_ATTRS: u8 = 105;
+const SSH_FXP_EXTENDED: u8 = 200;
+
+// Status codes
+const SSH_FX_OK: u32 = 0;
+const SSH_FX_EOF: u32 = 1;
+const SSH_FX_NO_SUCH_FILE: u32 = 2;
+const SSH_FX_PERMISSION_DENIED: u32 = 3;
+const SSH_FX_OP_UNSUPPORTED: u32 = 8;
+
+// Open flags
+const SSH_FXF_READ: u32 = 0x01;
+const SSH_FXF_WRITE: u32 = 0x02;
+const SSH_FXF_CREAT: u32 = 0x08;
+const SSH_FXF_TRUNC: u32 = 0x10;
+
+// Attribute flags
+const SSH_FILEXFER_ATTR_SIZE: u32 = 0x01;
+const SSH_FILEXFER_ATTR_UIDGID: u32 = 0x02;
+const SSH_FILEXFER_ATTR_PERMISSIONS: u32 = 0x04;
+const SSH_FILEXFER_ATTR_ACMODTIME: u32 = 0x08;
+const SSH_FILEXFER_ATTR_EXTENDED: u32 = 0x8000_0000;
+
+const S_IFMT: u32 = 0o170_000;
+const S_IFDIR: u32 = 0o040_000;
+const S_IFLNK: u32 = 0o120_000;
+
+/// Renames over an existing file, where plain RENAME fails
+const POSIX_RENAME: &str = "posix-rename@openssh.com";
+
+/// A remote location given as `ssh://[user@]host[:port]/path`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshTarget {
+    pub user: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+    /// Path on the host; relative paths are in the user's home directory
+    pub path: String,
+}
+
+impl SshTarget {
+    /// Parse an `ssh://` URL
+    ///
+    /// `ssh://host/etc/hosts` is the absolute path `/etc/hosts`, while
+    /// `ssh://host/~/notes` and `ssh://host` are in the home directory.
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("ssh://")?;
+        let (authority, path) = match rest.find('/') {
+            Some(slash) => (&rest[..slash], &rest[slash..]),
+            None => (rest, ""),
+        };
+        let (user, host_port) = match authority.rsplit_once('@') {
+            Some((user, host_port)) => (Some(user.to_string()), host_port),
+            None => (None, authority),
+        };
+        // IPv6 addresses are in brackets, as in `ssh://[::1]:2222/`
+        let (host, port) = if let Some(bracketed) = host_port.strip_prefix('[') {
+            let (host, after) = bracketed.split_once(']')?;
+            (host, after.strip_prefix(':'))
+        } else {
+            match host_port.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (host_port, None),
+            }
+        };
+        // A leading `-` would make ssh read the host or user as an option
+        // such as `-oProxyCommand=...`
+        if host.is_empty() || host.starts_with('-') {
+            return None;
+        }
+        if user
+            .as_deref()
+            .is_some_and(|user| user.is_empty() || user.starts_with('-'))
+        {
+            return None;
+        }
+        let port = match port {
+            Some(port) => Some(port.parse().ok()?),
+            None => None,
+        };
+        let path = match path {
+            "" | "/" | "/~" | "/~/" => ".".to_string(),
+            _ => match path.strip_prefix("/~/") {
+                Some(relative) => relative.to_string(),
+                None => path.to_string(),
+            },
+        };
+        Some(Self {
+            user,
+            host: host.to_string(),
+            port,
+            path,
+        })
+    }
+
+    /// The `ssh` command that starts the SFTP subsystem on the host
+    pub fn ssh_command(&self) -> Command {
+        let mut command = Command::new("ssh");
+        if let Some(port) = self.port {
+            command.arg("-p").arg(port.to_string());
+        }
+        if let Some(user) = &self.user {
+            command.arg("-l").arg(user);
+        }
+        // `--` ends the options, so the host is never read as one
+        command.args(["-s", "--"]).arg(&self.host).arg("sftp");
+        command
+    }
+}
+
+/// File attributes, as sent over SFTP
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SftpAttrs {
+    pub size: Option<u64>,
+    pub uid_gid: Option<(u32, u32)>,
+    pub permissions: Option<u32>,
+    /// Access and modification time, in seconds since the epoch
+    pub atime_mtime: Option<(u32, u32)>,
+}
+
+impl SftpAttrs {
+    pub fn is_dir(&self) -> bool {
+        self.permissions.is_some_and(|p| p & S_IFMT == S_IFDIR)
+    }
+
+    fn entry_type(&self) -> FsEntryType {
+        match self.permissions.map(|p| p & S_IFMT) {
+            Some(S_IFDIR) => FsEntryType::Directory,
+            Some(S_IFLNK) => FsEntryType::Symlink,
+            _ => FsEntryType::File,
+        }
+    }
+
+    fn metadata(&self, name: &str) -> FsMetadata {
+        let mut metadata = FsMetadata::new()
+            .with_hidden(name.starts_with('.'))
+            .with_readonly(self.permissions.is_some_and(|p| p & 0o222 == 0));
+        if let Some(size) = self.size {
+            metadata = metadata.with_size(size);
+        }
+        if let Some((_, mtime)) = self.atime_mtime {
+            metadata = metadata.with_modified(UNIX_EPOCH + Duration::from_secs(mtime.into()));
+        }
+        metadata
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        let mut flags = 0;
+        if self.size.is_some() {
+            flags |= SSH_FILEXFER_ATTR_SIZE;
+        }
+        if self.uid_gid.is_some() {
+            flags |= SSH_FILEXFER_ATTR_UIDGID;
+        }
+        if self.permissions.is_some() {
+            flags |= SSH_FILEXFER_ATTR_PERMISSIONS;
+        }
+        if self.atime_mtime.is_some() {
+            flags |= SSH_FILEXFER_ATTR_ACMODTIME;
+        }
+        put_u32(out, flags);
+        if let Some(size) = self.size {
+            out.extend_from_slice(&size.to_be_bytes());
+        }
+        if let Some((uid, gid)) = self.uid_gid {
+            put_u32(out, uid);
+            put_u32(out, gid);
+        }
+        if let Some(permissions) = self.permissions {
+            put_u32(out, permissions);
+        }
+        if let Some((atime, mtime)) = self.atime_mtime {
+            put_u32(out, atime);
+            put_u32(out, mtime);
+        }
+    }
+
+    fn decode(reader: &mut PacketReader) -> io::Result<Self> {
+        let flags = reader.u32()?;
+        let mut attrs = Self::default();
+        if flags & SSH_FILEXFER_ATTR_SIZE != 0 {
+            attrs.size = Some(reader.u64()?);
+        }
+        if flags & SSH_FILEXFER_ATTR_UIDGID != 0 {
+            attrs.uid_gid = Some((reader.u32()?, reader.u32()?));
+        }
+        if flags & SSH_FILEXFER_ATTR_PERMISSIONS != 0 {
+            attrs.permissions = Some(reader.u32()?);
+        }
+        if flags & SSH_FILEXFER_ATTR_ACMODTIME != 0 {
+            attrs.atime_mtime = Some((reader.u32()?, reader.u32()?));
+        }
+        if flags & SSH_FILEXFER_ATTR_EXTENDED != 0 {
+            for _ in 0..reader.u32()? {
+                reader.string()?;
+                reader.string()?;
+            }
+        }
+        Ok(attrs)
+    }
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_string(out: &mut Vec<u8>, value: &[u8]) {
+    put_u32(out, value.len() as u32);
+    out.extend_from_slice(value);
+}
+
+fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Reads the fields of a received packet
+struct PacketReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PacketReader<'a> {
+    fn bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(invalid_data("truncated SFTP packet"));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok((u64::from(self.u32()?) << 32) | u64::from(self.u32()?))
+    }
+
+    fn string(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.bytes(len)
+    }
+}
+
+/// The reply to a request
+#[derive(Debug)]
+enum Reply {
+    Status { code: u32, message: String },
+    Handle(Vec<u8>),
+    Data(Vec<u8>),
+    Name(Vec<(String, SftpAttrs)>),
+    Attrs(SftpAttrs),
+}
+
+impl Reply {
+    fn decode(kind: u8, reader: &mut PacketReader) -> io::Result<Self> {
+        Ok(match kind {
+            SSH_FXP_STATUS => {
+                let code = reader.u32()?;
+                // Version 3 servers may leave out the message
+                let message = reader
+                    .string()
+                    .map(|m| String::from_utf8_lossy(m).into_owned())
+                    .unwrap_or_default();
+                Reply::Status { code, message }
+            }
+            SSH_FXP_HANDLE => Reply::Handle(reader.string()?.to_vec()),
+            SSH_FXP_DATA => Reply::Data(reader.string()?.to_vec()),
+            SSH_FXP_NAME => {
+                let count = reader.u32()?;
+                let mut names = Vec::new();
+                for _ in 0..count {
+                    let name = String::from_utf8_lossy(reader.string()?).into_owned();
+                    reader.string()?; // long name, as shown by `ls -l`
+                    names.push((name, SftpAttrs::decode(reader)?));
+                }
+                Reply::Name(names)
+            }
+            SSH_FXP_ATTRS => Reply::Attrs(SftpAttrs::decode(reader)?),
+            _ => return Err(invalid_data("unexpected SFTP reply")),
+        })
+    }
+
+    /// Turn a status reply into an error (or `Ok` for `SSH_FX_OK`)
+    fn status(self) -> io::Result<()> {
+        match self {
+            Reply::Status {
+                code: SSH_FX_OK, ..
+            } => Ok(()),
+            Reply::Status { code, message } => Err(status_error(code, message)),
+            _ => Err(invalid_data("expected an SFTP status")),
+        }
+    }
+
+    fn handle(self) -> io::Result<Vec<u8>> {
+        match self {
+            Reply::Handle(handle) => Ok(handle),
+            other => Err(other.unexpected()),
+        }
+    }
+
+    fn attrs(self) -> io::Result<SftpAttrs> {
+        match self {
+            Reply::Attrs(attrs) => Ok(attrs),
+            other => Err(other.unexpected()),
+        }
+    }
+
+    fn names(self) -> io::Result<Vec<(String, SftpAttrs)>> {
+        match self {
+            Reply::Name(names) => Ok(names),
+            other => Err(other.unexpected()),
+        }
+    }
+
+    /// The error for a reply of the wrong kind, which is a failed status
+    /// when the request failed
+    fn unexpected(self) -> io::Error {
+        match self {
+            Reply::Status { code, message } => status_error(code, message),
+            _ => invalid_data("unexpected SFTP reply"),
+        }
+    }
+}
+
+fn status_error(code: u32, message: String) -> io::Error {
+    let kind = match code {
+        SSH_FX_EOF => io::ErrorKind::UnexpectedEof,
+        SSH_FX_NO_SUCH_FILE => io::ErrorKind::NotFound,
+        SSH_FX_PERMISSION_DENIED => io::ErrorKind::PermissionDenied,
+        SSH_FX_OP_UNSUPPORTED => io::ErrorKind::Unsupported,
+        _ => io::ErrorKind::Other,
+    };
+    let message = if message.is_empty() {
+        format!("SFTP error {}", code)
+    } else {
+        message
+    };
+    io::Error::new(kind, message)
+}
+
+/// A synchronous SFTP client
+pub struct SftpClient {
+    reader: Box<dyn Read + Send>,
+    writer: Box<dyn Write + Send>,
+    next_id: u32,
+    posix_rename: bool,
+    /// The `ssh` process the session runs over, if any
+    child: Option<Child>,
+}
+
+impl SftpClient {
+    /// Start an SFTP session with `ssh`
+    ///
+    /// Blocks while `ssh` connects, which may include asking for a password
+    /// on the terminal.
+    pub fn connect(target: &SshTarget) -> io::Result<Self> {
+        let mut child = target
+            .ssh_command()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("failed to run ssh: {}", e)))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+
+        // Keep ssh's messages out of the terminal, the last one is shown
+        // if the session can't be started
+        let last_error = Arc::new(Mutex::new(String::new()));
+        let last_error_writer = Arc::clone(&last_error);
+        std::thread::spawn(move || {
+            use std::io::BufRead;
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                tracing::warn!("ssh: {}", line);
+                if let Ok(mut last) = last_error_writer.lock() {
+                    *last = line;
+                }
+            }
+        });
+
+        let mut client = Self::new(stdout, stdin);
+        client.child = Some(child);
+        if let Err(e) = client.init() {
+            // Let the stderr thread see ssh's last words
+            if let Some(child) = client.child.as_mut() {
+                let _ = child.wait();
+            }
+            std::thread::sleep(Duration::from_millis(50));
+            let last = last_error.lock().map(|l| l.clone()).unwrap_or_default();
+            let message = if last.is_empty() {
+                format!("failed to start SFTP on {}: {}", target.host, e)
+            } else {
+                format!("failed to start SFTP on {}: {}", target.host, last)
+            };
+            return Err(io::Error::new(e.kind(), message));
+        }
+        Ok(client)
+    }
+
+    /// Start an SFTP session over any pair of streams, e.g. to a server in
+    /// the same process
+    pub fn over(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+    ) -> io::Result<Self> {
+        let mut client = Self::new(reader, writer);
+        client.init()?;
+        Ok(client)
+    }
+
+    fn new(reader: impl Read + Send + 'static, writer: impl Write + Send + 'static) -> Self {
+        Self {
+            reader: Box::new(BufReader::new(reader)),
+            writer: Box::new(BufWriter::new(writer)),
+            next_id: 0,
+            posix_rename: false,
+            child: None,
+        }
+    }
+
+    fn init(&mut self) -> io::Result<()> {
+        let mut body = Vec::new();
+        put_u32(&mut body, SFTP_VERSION);
+        self.send(SSH_FXP_INIT, &body)?;
+        self.writer.flush()?;
+
+        let (kind, data) = self.receive()?;
+        if kind != SSH_FXP_VERSION {
+            return Err(invalid_data("expected an SFTP version"));
+        }
+        let mut reader = PacketReader { data: &data };
+        let version = reader.u32()?;
+        if version < SFTP_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("unsupported SFTP version {}", version),
+            ));
+        }
+        while let (Ok(name), Ok(_)) = (reader.string(), reader.string()) {
+            if name == POSIX_RENAME.as_bytes() {
+                self.posix_rename = true;
+            }
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, kind: u8, body: &[u8]) -> io::Result<()> {
+        let len = body.len() as u32 + 1;
+        self.writer.write_all(&len.to_be_bytes())?;
+        self.writer.write_all(&[kind])?;
+        self.writer.write_all(body)
+    }
+
+    fn receive(&mut self) -> io::Result<(u8, Vec<u8>)> {
+        let mut len = [0u8; 4];
+        self.reader.read_exact(&mut len).map_err(|e| {
+            if e.kind() == io::ErrorKind::UnexpectedEof {
+                io::Error::new(io::ErrorKind::BrokenPipe, "SFTP connection closed")
+            } else {
+                e
+            }
+        })?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len == 0 {
+            return Err(invalid_data("empty SFTP packet"));
+        }
+        let mut packet = vec![0u8; len];
+        self.reader.read_exact(&mut packet)?;
+        let kind = packet[0];
+        packet.remove(0);
+        Ok((kind, packet))
+    }
+
+    /// Send requests and wait for all their replies, a window at a time
+    ///
+    /// Each request is a packet type and the body after the request id.
+    /// Replies are returned in the order of the requests.
+    fn requests(&mut self, requests: Vec<(u8, Vec<u8>)>) -> io::Result<Vec<Reply>> {
+        let mut replies = Vec::with_capacity(requests.len());
+        let mut requests = requests.into_iter().peekable();
+        while requests.peek().is_some() {
+            let mut pending = HashMap::new();
+            let mut ids = Vec::new();
+            for (kind, body) in requests.by_ref().take(WINDOW) {
+                let id = self.next_id;
+                self.next_id = self.next_id.wrapping_add(1);
+                let mut packet = Vec::with_capacity(body.len() + 4);
+                put_u32(&mut packet, id);
+                packet.extend_from_slice(&body);
+                self.send(kind, &packet)?;
+                ids.push(id);
+            }
+            self.writer.flush()?;
+
+            while pending.len() < ids.len() {
+                let (kind, data) = self.receive()?;
+                let mut reader = PacketReader { data: &data };
+                let id = reader.u32()?;
+                if !ids.contains(&id) {
+                    return Err(invalid_data("SFTP reply to an unknown request"));
+                }
+                pending.insert(id, Reply::decode(kind, &mut reader)?);
+            }
+            for id in ids {
+                replies.push(pending.remove(&id).expect("every request has a reply"));
+            }
+        }
+        Ok(replies)
+    }
+
+    fn request(&mut self, kind: u8, body: Vec<u8>) -> io::Result<Reply> {
+        Ok(self
+            .requests(vec![(kind, body)])?
+            .pop()
+            .expect("one request has one reply"))
+    }
+
+    fn path_request(&mut self, kind: u8, path: &Path) -> io::Result<Reply> {
+        let mut body = Vec::new();
+        put_string(&mut body, &path_bytes(path));
+        self.request(kind, body)
+    }
+
+    /// The absolute, normalized form of a path
+    pub fn realpath(&mut self, path: &Path) -> io::Result<PathBuf> {
+        let names = self.path_request(SSH_FXP_REALPATH, path)?.names()?;
+        names
+            .into_iter()
+            .next()
+            .map(|(name, _)| PathBuf::from(name))
+            .ok_or_else(|| invalid_data("empty SFTP realpath reply"))
+    }
+
+    /// Attributes of a path, following symlinks
+    pub fn stat(&mut self, path: &Path) -> io::Result<SftpAttrs> {
+        self.path_request(SSH_FXP_STAT, path)?.attrs()
+    }
+
+    /// Attributes of many paths, following symlinks
+    pub fn stat_all(&mut self, paths: &[PathBuf]) -> io::Result<Vec<io::Result<SftpAttrs>>> {
+        let requests = paths
+            .iter()
+            .map(|path| {
+                let mut body = Vec::new();
+                put_string(&mut body, &path_bytes(path));
+                (SSH_FXP_STAT, body)
+            })
+            .collect();
+        Ok(self
+            .requests(requests)?
+            .into_iter()
+            .map(Reply::attrs)
+            .collect())
+    }
+
+    /// Entries of a directory, without `.` and `..`
+    pub fn read_dir(&mut self, path: &Path) -> io::Result<Vec<(String, SftpAttrs)>> {
+        let handle = self.path_request(SSH_FXP_OPENDIR, path)?.handle()?;
+        let mut entries = Vec::new();
+        let result = loop {
+            let mut body = Vec::new();
+            put_string(&mut body, &handle);
+            match self.request(SSH_FXP_READDIR, body)? {
+                Reply::Name(names) => entries.extend(
+                    names
+                        .into_iter()
+                        .filter(|(name, _)| name != "." && name != ".."),
+                ),
+                Reply::Status {
+                    code: SSH_FX_EOF, ..
+                } => break Ok(entries),
+                other => break Err(other.unexpected()),
+            }
+        };
+        self.close(&handle)?;
+        result
+    }
+
+    fn open(&mut self, path: &Path, flags: u32, attrs: &SftpAttrs) -> io::Result<Vec<u8>> {
+        let mut body = Vec::new();
+        put_string(&mut body, &path_bytes(path));
+        put_u32(&mut body, flags);
+        attrs.encode(&mut body);
+        self.request(SSH_FXP_OPEN, body)?.handle()
+    }
+
+    fn close(&mut self, handle: &[u8]) -> io::Result<()> {
+        let mut body = Vec::new();
+        put_string(&mut body, handle);
+        self.request(SSH_FXP_CLOSE, body)?.status()
+    }
+
+    /// Read up to `len` bytes at `offset`, fewer only at the end of the file
+    pub fn read_range(&mut self, path: &Path, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        let handle = self.open(path, SSH_FXF_READ, &SftpAttrs::default())?;
+        let result = self.read_handle(&handle, offset, len);
+        self.close(&handle)?;
+        result
+    }
+
+    fn read_handle(&mut self, handle: &[u8], offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(len);
+        while data.len() < len {
+            let start = offset + data.len() as u64;
+            let remaining = len - data.len();
+            let chunks: Vec<(u64, usize)> = (0..remaining.div_ceil(CHUNK_SIZE).min(WINDOW))
+                .map(|i| {
+                    let chunk_start = i * CHUNK_SIZE;
+                    (
+                        start + chunk_start as u64,
+                        CHUNK_SIZE.min(remaining - chunk_start),
+                    )
+                })
+                .collect();
+            let requests = chunks
+                .iter()
+                .map(|&(chunk_offset, chunk_len)| {
+                    let mut body = Vec::new();
+                    put_string(&mut body, handle);
+                    body.extend_from_slice(&chunk_offset.to_be_bytes());
+                    put_u32(&mut body, chunk_len as u32);
+                    (SSH_FXP_READ, body)
+                })
+                .collect();
+
+            // A short read ends this window; the rest is asked for again, so
+            // the file ending is seen as an empty read
+            let mut progressed = false;
+            for (reply, &(_, chunk_len)) in self.requests(requests)?.into_iter().zip(&chunks) {
+                match reply {
+                    Reply::Data(bytes) => {
+                        let short = bytes.len() < chunk_len;
+                        progressed |= !bytes.is_empty();
+                        data.extend_from_slice(&bytes);
+                        if short {
+                            break;
+                        }
+                    }
+                    Reply::Status {
+                        code: SSH_FX_EOF, ..
+                    } => break,
+                    other => return Err(other.unexpected()),
+                }
+            }
+            if !progressed {
+                break;
+            }
+        }
+        Ok(data)
+    }
+
+    /// Replace the content of a file, through a temporary file renamed over
+    /// it that gets the original's permissions and, if allowed, owner
+    pub fn write(&mut self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let original = self.stat(path).ok();
+        let temp_path = path.with_extension("tmp");
+        let handle = self.open(
+            &temp_path,
+            SSH_FXF_WRITE | SSH_FXF_CREAT | SSH_FXF_TRUNC,
+            &SftpAttrs::default(),
+        )?;
+        let result = self.write_handle(&handle, data, original.as_ref());
+        let closed = self.close(&handle);
+        if let Err(e) = result.and(closed) {
+            let _ = self.path_request(SSH_FXP_REMOVE, &temp_path);
+            return Err(e);
+        }
+        self.rename(&temp_path, path)
+    }
+
+    fn write_handle(
+        &mut self,
+        handle: &[u8],
+        data: &[u8],
+        original: Option<&SftpAttrs>,
+    ) -> io::Result<()> {
+        let requests = data
+            .chunks(CHUNK_SIZE)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let mut body = Vec::with_capacity(chunk.len() + 32);
+                put_string(&mut body, handle);
+                body.extend_from_slice(&((i * CHUNK_SIZE) as u64).to_be_bytes());
+                put_string(&mut body, chunk);
+                (SSH_FXP_WRITE, body)
+            })
+            .collect();
+        for reply in self.requests(requests)? {
+            reply.status()?;
+        }
+
+        if let Some(original) = original {
+            let set = |attrs: SftpAttrs| {
+                let mut body = Vec::new();
+                put_string(&mut body, handle);
+                attrs.encode(&mut body);
+                (SSH_FXP_FSETSTAT, body)
+            };
+            let permissions = set(SftpAttrs {
+                permissions: original.permissions.map(|p| p & 0o7777),
+                ..Default::default()
+            });
+            // Only root can give files away, so this may fail
+            let owner = set(SftpAttrs {
+                uid_gid: original.uid_gid,
+                ..Default::default()
+            });
+            let mut replies = self.requests(vec![permissions, owner])?.into_iter();
+            replies.next().map_or(Ok(()), Reply::status)?;
+        }
+        Ok(())
+    }
+
+    /// Rename a file or directory, replacing `to` if it is a file
+    pub fn rename(&mut self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut body = Vec::new();
+        if self.posix_rename {
+            put_string(&mut body, POSIX_RENAME.as_bytes());
+            put_string(&mut body, &path_bytes(from));
+            put_string(&mut body, &path_bytes(to));
+            return self.request(SSH_FXP_EXTENDED, body)?.status();
+        }
+        // Plain RENAME fails if the target exists
+        if self.stat(to).is_ok_and(|attrs| !attrs.is_dir()) {
+            self.path_request(SSH_FXP_REMOVE, to)?.status()?;
+        }
+        put_string(&mut body, &path_bytes(from));
+        put_string(&mut body, &path_bytes(to));
+        self.request(SSH_FXP_RENAME, body)?.status()
+    }
+
+    /// Remove a file, or a directory and everything in it
+    pub fn remove(&mut self, path: &Path) -> io::Result<()> {
+        let attrs = self.path_request(SSH_FXP_LSTAT, path)?.attrs()?;
+        if attrs.is_dir() {
+            for (name, _) in self.read_dir(path)? {
+                self.remove(&path.join(name))?;
+            }
+            self.path_request(SSH_FXP_RMDIR, path)?.status()
+        } else {
+            self.path_request(SSH_FXP_REMOVE, path)?.status()
+        }
+    }
+
+    /// Create a directory
+    pub fn mkdir(&mut self, path: &Path) -> io::Result<()> {
+        let mut body = Vec::new();
+        put_string(&mut body, &path_bytes(path));
+        SftpAttrs::default().encode(&mut body);
+        self.request(SSH_FXP_MKDIR, body)?.status()
+    }
+}
+
+impl Drop for SftpClient {
+    fn drop(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Filesystem backend for the files of a host reached over SFTP
+pub struct SftpFsBackend {
+    client: Arc<Mutex<SftpClient>>,
+}
+
+impl SftpFsBackend {
+    pub fn new(client: SftpClient) -> Self {
+        Self {
+            client: Arc::new(Mutex::new(client)),
+        }
+    }
+
+    /// Run `f` with the client on a blocking thread
+    async fn run<T, F>(&self, f: F) -> io::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut SftpClient) -> io::Result<T> + Send + 'static,
+    {
+        let client = Arc::clone(&self.client);
+        tokio::task::spawn_blocking(move || {
+            let mut client = client
+                .lock()
+                .map_err(|_| io::Error::other("SFTP session is unusable"))?;
+            f(&mut client)
+        })
+        .await
+        .map_err(|e| io::Error::other(e.to_string()))?
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+#[async_trait]
+impl FsBackend for SftpFsBackend {
+    async fn read_dir(&self, path: &Path) -> io::Result<Vec<FsEntry>> {
+        let dir = path.to_path_buf();
+        let entries = self.run(move |client| client.read_dir(&dir)).await?;
+        Ok(entries
+            .into_iter()
+            .map(|(name, attrs)| FsEntry::new(path.join(&name), name, attrs.entry_type()))
+            .collect())
+    }
+
+    async fn get_metadata_batch(&self, paths: &[PathBuf]) -> Vec<io::Result<FsMetadata>> {
+        let owned = paths.to_vec();
+        match self.run(move |client| client.stat_all(&owned)).await {
+            Ok(results) => results
+                .into_iter()
+                .zip(paths)
+                .map(|(attrs, path)| attrs.map(|attrs| attrs.metadata(&file_name(path))))
+                .collect(),
+            Err(e) => paths
+                .iter()
+                .map(|_| Err(io::Error::new(e.kind(), e.to_string())))
+                .collect(),
+        }
+    }
+
+    async fn exists(&self, path: &Path) -> bool {
+        let path = path.to_path_buf();
+        self.run(move |client| client.stat(&path)).await.is_ok()
+    }
+
+    async fn is_dir(&self, path: &Path) -> io::Result<bool> {
+        let path = path.to_path_buf();
+        Ok(self.run(move |client| client.stat(&path)).await?.is_dir())
+    }
+
+    async fn get_entry(&self, path: &Path) -> io::Result<FsEntry> {
+        let owned = path.to_path_buf();
+        let attrs = self.run(move |client| client.stat(&owned)).await?;
+        let name = file_name(path);
+        let metadata = attrs.metadata(&name);
+        Ok(FsEntry::new(path.to_path_buf(), name, attrs.entry_type()).with_metadata(metadata))
+    }
+
+    async fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = path.to_path_buf();
+        self.run(move |client| client.realpath(&path)).await
+    }
+
+    async fn read_range(&self, path: &Path, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        let path = path.to_path_buf();
+        self.run(move |client| client.read_range(&path, offset, len))
+            .await
+    }
+
+    async fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let (path, data) = (path.to_path_buf(), data.to_vec());
+        self.run(move |client| client.write(&path, &data)).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let (from, to) = (from.to_path_buf(), to.to_path_buf());
+        self.run(move |client| client.rename(&from, &to)).await
+    }
+
+    async fn remove(&self, path: &Path) -> io::Result<()> {
+        let path = path.to_path_buf();
+        self.run(move |client| client.remove(&path)).await
+    }
+
+    async fn mkdir(&self, path: &Path) -> io::Result<()> {
+        let path = path.to_path_buf();
+        self.run(move |client| client.mkdir(&path)).await
+    }
+
+    fn is_local(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ssh_target() {
+        assert_eq!(
+            SshTarget::parse("ssh://alice@example.com:2222/etc/hosts"),
+            Some(SshTarget {
+                user: Some("alice".to_string()),
+                host: "example.com".to_string(),
+                port: Some(2222),
+                path: "/etc/hosts".to_string(),
+            })
+        );
+        let home = SshTarget::parse("ssh://example.com").unwrap();
+        assert_eq!(
+            (home.user, home.port, home.path.as_str()),
+            (None, None, ".")
+        );
+        assert_eq!(
+            SshTarget::parse("ssh://example.com/~/notes/todo.md")
+                .unwrap()
+                .path,
+            "notes/todo.md"
+        );
+        let ipv6 = SshTarget::parse("ssh://[::1]:22/srv").unwrap();
+        assert_eq!((ipv6.host.as_str(), ipv6.port), ("::1", Some(22)));
+
+        assert_eq!(SshTarget::parse("/local/path"), None);
+        assert_eq!(SshTarget::parse("ssh:///path"), None);
+        assert_eq!(SshTarget::parse("ssh://host:port/path"), None);
+    }
+
+    #[test]
+    fn test_parse_rejects_option_like_host_and_user() {
+        assert_eq!(
+            SshTarget::parse("ssh://-oProxyCommand=touch%20x/path"),
+            None
+        );
+        assert_eq!(SshTarget::parse("ssh://-oProxyCommand=x@host/path"), None);
+        assert_eq!(SshTarget::parse("ssh://[-oProxyCommand=x]/path"), None);
+
+        let command = SshTarget::parse("ssh://alice@host:2222")
+            .unwrap()
+            .ssh_command();
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(
+            args,
+            ["-p", "2222", "-l", "alice", "-s", "--", "host", "sftp"]
+        );
+    }
+
+    #[test]
+    fn test_attrs_round_trip() {
+        let attrs = SftpAttrs {
+            size: Some(1 << 40),
+            uid_gid: Some((1000, 100)),
+            permissions: Some(S_IFDIR | 0o755),
+            atime_mtime: Some((1, 2)),
+        };
+        let mut encoded = Vec::new();
+        attrs.encode(&mut encoded);
+        let decoded = SftpAttrs::decode(&mut PacketReader { data: &encoded }).unwrap();
+        assert_eq!(decoded, attrs);
+        assert!(decoded.is_dir());
+        assert_eq!(decoded.entry_type(), FsEntryType::Directory);
+
+        let metadata = SftpAttrs {
+            permissions: Some(0o100_444),
+            ..Default::default()
+        }
+        .metadata(".hidden");
+        assert!(metadata.is_hidden && metadata.is_readonly);
+    }
+}
@@ -28,6 +28,10 @@ pub struct SlowFsConfig {
     pub get_entry_delay: Duration,
     /// Delay for canonicalize operations
     pub canonicalize_delay: Duration,
+    /// Delay for read_range operations
+    pub read_delay: Duration,
+    /// Delay for write, rename, remove and mkdir operations
+    pub write_delay: Duration,
 }
 
 impl SlowFsConfig {
@@ -40,6 +44,8 @@ impl SlowFsConfig {
             is_dir_delay: delay,
             get_entry_delay: delay,
             canonicalize_delay: delay,
+            read_delay: delay,
+            write_delay: delay,
         }
     }
 
@@ -57,6 +63,8 @@ impl SlowFsConfig {
             is_dir_delay: Duration::from_millis(30),
             get_entry_delay: Duration::from_millis(100),
             canonicalize_delay: Duration::from_millis(50),
+            read_delay: Duration::from_millis(100),
+            write_delay: Duration::from_millis(100),
         }
     }
 
@@ -69,6 +77,8 @@ impl SlowFsConfig {
             is_dir_delay: Duration::from_millis(10),
             get_entry_delay: Duration::from_millis(50),
             canonicalize_delay: Duration::from_millis(20),
+            read_delay: Duration::from_millis(20),
+            write_delay: Duration::from_millis(20),
         }
     }
 }
@@ -96,6 +106,10 @@ pub struct BackendMetrics {
     pub get_entry_calls: usize,
     /// Number of canonicalize calls
     pub canonicalize_calls: usize,
+    /// Number of read_range calls
+    pub read_calls: usize,
+    /// Number of write, rename, remove and mkdir calls
+    pub write_calls: usize,
     /// Total time spent in artificial delays
     pub total_delay_time: Duration,
 }
@@ -119,6 +133,8 @@ impl BackendMetrics {
             + self.is_dir_calls
             + self.get_entry_calls
             + self.canonicalize_calls
+            + self.read_calls
+            + self.write_calls
     }
 }
 
@@ -218,6 +234,40 @@ impl FsBackend for SlowFsBackend {
         self.metrics.lock().await.canonicalize_calls += 1;
         self.inner.canonicalize(path).await
     }
+
+    async fn read_range(&self, path: &Path, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        self.add_delay(self.config.read_delay).await;
+        self.metrics.lock().await.read_calls += 1;
+        self.inner.read_range(path, offset, len).await
+    }
+
+    async fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.add_delay(self.config.write_delay).await;
+        self.metrics.lock().await.write_calls += 1;
+        self.inner.write(path, data).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.add_delay(self.config.write_delay).await;
+        self.metrics.lock().await.write_calls += 1;
+        self.inner.rename(from, to).await
+    }
+
+    async fn remove(&self, path: &Path) -> io::Result<()> {
+        self.add_delay(self.config.write_delay).await;
+        self.metrics.lock().await.write_calls += 1;
+        self.inner.remove(path).await
+    }
+
+    async fn mkdir(&self, path: &Path) -> io::Result<()> {
+        self.add_delay(self.config.write_delay).await;
+        self.metrics.lock().await.write_calls += 1;
+        self.inner.mkdir(path).await
+    }
+
+    fn is_local(&self) -> bool {
+        self.inner.is_local()
+    }
 }
 
 #[cfg(test)]
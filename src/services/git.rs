@@ -0,0 +1,98 @@
+//! Git repository information read from the `.git` directory
+//!
+//! Only what the status bar shows is read, without running `git`: the
+//! branch checked out is the `ref:` line of `HEAD`, or the start of the
+//! commit hash when the head is detached.
+
+use std::path::{Path, PathBuf};
+
+/// Number of hash digits shown for a detached head
+const SHORT_HASH_LEN: usize = 7;
+
+/// Branch checked out in the repository containing `dir`, if any
+pub fn current_branch(dir: &Path) -> Option<String> {
+    let git_dir = find_git_dir(dir)?;
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    branch_from_head(&head)
+}
+
+/// Find the git directory of the repository containing `dir`
+///
+/// Worktrees and submodules have a `.git` file pointing to their git
+/// directory (`gitdir: <path>`) instead of a `.git` directory.
+fn find_git_dir(dir: &Path) -> Option<PathBuf> {
+    for ancestor in dir.ancestors() {
+        let dot_git = ancestor.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        if dot_git.is_file() {
+            let text = std::fs::read_to_string(&dot_git).ok()?;
+            let git_dir = text.trim().strip_prefix("gitdir:")?.trim();
+            return Some(ancestor.join(git_dir));
+        }
+    }
+    None
+}
+
+/// Branch named by the contents of a `HEAD` file
+fn branch_from_head(head: &str) -> Option<String> {
+    let head = head.trim();
+    if let Some(reference) = head.strip_prefix("ref:") {
+        let reference = reference.trim();
+        let branch = reference.strip_prefix("refs/heads/").unwrap_or(reference);
+        return Some(branch.to_string());
+    }
+    if head.len() >= SHORT_HASH_LEN && head.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Some(head[..SHORT_HASH_LEN].to_string());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branch_from_head() {
+        assert_eq!(
+            branch_from_head("ref: refs/heads/main\n").as_deref(),
+            Some("main")
+        );
+        assert_eq!(
+            branch_from_head("ref: refs/heads/feature/status-bar").as_deref(),
+            Some("feature/status-bar")
+        );
+        assert_eq!(
+            branch_from_head("3f2a9c1d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a39\n").as_deref(),
+            Some("3f2a9c1")
+        );
+        assert_eq!(branch_from_head("garbage"), None);
+    }
+
+    #[test]
+    fn test_current_branch() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = dir.path().join("repo");
+        let nested = repo.join("src").join("app");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(current_branch(&nested), None);
+
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::write(repo.join(".git").join("HEAD"), "ref: refs/heads/dev\n").unwrap();
+        assert_eq!(current_branch(&nested).as_deref(), Some("dev"));
+
+        // A worktree points to its git directory
+        let worktree = dir.path().join("worktree");
+        let worktree_git = repo.join(".git").join("worktrees").join("wt");
+        std::fs::create_dir_all(&worktree).unwrap();
+        std::fs::create_dir_all(&worktree_git).unwrap();
+        std::fs::write(worktree_git.join("HEAD"), "ref: refs/heads/fix\n").unwrap();
+        std::fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", worktree_git.display()),
+        )
+        .unwrap();
+        assert_eq!(current_branch(&worktree).as_deref(), Some("fix"));
+    }
+}
@@ -0,0 +1,350 @@
+//! Single-instance server
+//!
+//! A running editor listens on a Unix socket so that `fresh --remote FILE`
+//! opens files in it instead of starting another editor. Each project has
+//! its own socket, named after its working directory, and the editor's
+//! built-in terminals get the socket's path in `FRESH_SERVER`, so
+//! `fresh --remote` run in one of them reaches the editor it belongs to.
+//!
+//! A connection carries one JSON request line and gets one JSON reply line.
+//! For a request with `wait`, the reply is only sent once all the buffers it
+//! opened have been closed, which lets `fresh --remote --wait` be `$EDITOR`.
+
+use crate::services::async_bridge::AsyncMessage;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Environment variable with the socket of the editor a terminal belongs to
+pub const SERVER_ENV: &str = "FRESH_SERVER";
+
+/// How long a client has to send its request once connected
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A file to open, with the position to go to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenFile {
+    /// Absolute path
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// Files sent to a running editor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenRequest {
+    pub files: Vec<OpenFile>,
+    /// Answer only once the opened buffers are closed
+    #[serde(default)]
+    pub wait: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenReply {
+    error: Option<String>,
+}
+
+/// A request received by the server, with the connection to answer on
+#[derive(Debug)]
+pub struct PendingRequest {
+    pub request: OpenRequest,
+    stream: UnixStream,
+}
+
+impl PendingRequest {
+    /// Answer the client, with the error if the files couldn't be opened
+    pub fn reply(self, error: Option<String>) {
+        let _ = write_reply(self.stream, error);
+    }
+}
+
+fn write_reply(mut stream: UnixStream, error: Option<String>) -> io::Result<()> {
+    let mut line = serde_json::to_vec(&OpenReply { error })?;
+    line.push(b'\n');
+    stream.write_all(&line)
+}
+
+/// Directory of the editors' sockets, private to the user
+fn socket_dir() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir).join("fresh"),
+        _ => std::env::temp_dir().join(format!("fresh-{}", unsafe { libc::getuid() })),
+    }
+}
+
+/// The socket of the editor working in `working_dir`
+///
+/// Named after an FNV-1a hash of the path, since socket paths are limited
+/// to about a hundred bytes.
+pub fn socket_path(working_dir: &Path) -> PathBuf {
+    let hash = working_dir
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf29ce484222325u64, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    socket_dir().join(format!("{:016x}.sock", hash))
+}
+
+/// The listening side, running on its own thread until dropped
+pub struct InstanceServer {
+    path: PathBuf,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl InstanceServer {
+    /// Listen on `path`, sending requests to the editor through `sender`
+    ///
+    /// Fails with `AddrInUse` if another editor is listening there already,
+    /// and with `PermissionDenied` if the socket's directory could be used by
+    /// other users. A socket left behind by an editor that didn't exit cleanly is replaced.
+    pub fn start(path: &Path, sender: mpsc::Sender<AsyncMessage>) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(parent)?;
+            check_private_dir(parent)?;
+        }
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("another editor is listening on {}", path.display()),
+                ));
+            }
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        tracing::info!("Listening for files to open on {}", path.display());
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread_shutdown = shutdown.clone();
+        std::thread::Builder::new()
+            .name("instance-server".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if thread_shutdown.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            tracing::warn!("Failed to accept a connection: {}", e);
+                            continue;
+                        }
+                    };
+                    match read_request(&stream) {
+                        Ok(request) => {
+                            let pending = PendingRequest { request, stream };
+                            if sender.send(AsyncMessage::OpenFiles(pending)).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!("Invalid request to open files: {}", e);
+                            let _ = write_reply(stream, Some(e.to_string()));
+                        }
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            shutdown,
+        })
+    }
+
+    /// Path of the socket
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for InstanceServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        // Wake the thread up so it sees the flag
+        let _ = UnixStream::connect(&self.path);
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Make sure only we can use `dir`: in a shared temp dir, another user could
+/// have made it first, or put a symlink there, to receive our requests
+fn check_private_dir(dir: &Path) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::symlink_metadata(dir)?;
+    let problem = if !metadata.is_dir() {
+        "is not a directory"
+    } else if metadata.uid() != unsafe { libc::getuid() } {
+        "is owned by another user"
+    } else if metadata.mode() & 0o077 != 0 {
+        "can be used by other users"
+    } else {
+        return Ok(());
+    };
+    Err(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("socket directory {} {}", dir.display(), problem),
+    ))
+}
+
+fn read_request(stream: &UnixStream) -> io::Result<OpenRequest> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    stream.set_read_timeout(None)?;
+    serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Connect to the editor to send files to
+///
+/// That's the one in `FRESH_SERVER` when run in a built-in terminal, or else
+/// the one working in `dir` or in its closest ancestor that has an editor.
+pub fn connect(dir: &Path) -> Option<UnixStream> {
+    if let Some(path) = std::env::var_os(SERVER_ENV) {
+        if let Ok(stream) = UnixStream::connect(&path) {
+            return Some(stream);
+        }
+    }
+    dir.ancestors()
+        .find_map(|dir| UnixStream::connect(socket_path(dir)).ok())
+}
+
+/// Send a request and wait for the answer
+///
+/// If the editor exits before answering, the buffers are as good as closed.
+pub fn send_request(stream: UnixStream, request: &OpenRequest) -> io::Result<()> {
+    let mut line = serde_json::to_vec(request)?;
+    line.push(b'\n');
+    (&stream).write_all(&line)?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply)?;
+    if reply.is_empty() {
+        return Ok(());
+    }
+    let reply: OpenReply =
+        serde_json::from_str(&reply).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    match reply.error {
+        Some(error) => Err(io::Error::other(error)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(path: &str) -> OpenRequest {
+        OpenRequest {
+            files: vec![OpenFile {
+                path: PathBuf::from(path),
+                line: Some(3),
+                column: None,
+            }],
+            wait: false,
+        }
+    }
+
+    /// A temp dir only we can use, as sockets are only made in one
+    fn private_dir() -> tempfile::TempDir {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o700)).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_socket_path_per_project() {
+        let a = socket_path(Path::new("/home/user/a"));
+        assert_eq!(a, socket_path(Path::new("/home/user/a")));
+        assert_ne!(a, socket_path(Path::new("/home/user/b")));
+        assert!(a.as_os_str().len() < 100);
+    }
+
+    #[test]
+    fn test_request_round_trip() {
+        let dir = private_dir();
+        let path = dir.path().join("editor.sock");
+        let (sender, receiver) = mpsc::channel();
+        let server = InstanceServer::start(&path, sender).unwrap();
+
+        let client = std::thread::spawn(move || {
+            let stream = UnixStream::connect(&path).unwrap();
+            send_request(stream, &request("/tmp/a.txt"))
+        });
+        let Ok(AsyncMessage::OpenFiles(pending)) = receiver.recv() else {
+            panic!("expected a request");
+        };
+        assert_eq!(pending.request, request("/tmp/a.txt"));
+        pending.reply(Some("no such file".to_string()));
+
+        let error = client.join().unwrap().unwrap_err();
+        assert_eq!(error.to_string(), "no such file");
+        drop(server);
+        assert!(!dir.path().join("editor.sock").exists());
+    }
+
+    #[test]
+    fn test_second_server_is_refused() {
+        let dir = private_dir();
+        let path = dir.path().join("editor.sock");
+        let (sender, _receiver) = mpsc::channel();
+        let _server = InstanceServer::start(&path, sender.clone()).unwrap();
+
+        let error = InstanceServer::start(&path, sender).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+    }
+
+    #[test]
+    fn test_stale_socket_is_replaced() {
+        let dir = private_dir();
+        let path = dir.path().join("editor.sock");
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let (sender, _receiver) = mpsc::channel();
+        assert!(InstanceServer::start(&path, sender).is_ok());
+    }
+
+    #[test]
+    fn test_shared_socket_dir_is_refused() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let (sender, _receiver) = mpsc::channel();
+
+        // A directory others can get into
+        let open = dir.path().join("open");
+        std::fs::create_dir(&open).unwrap();
+        std::fs::set_permissions(&open, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let error = InstanceServer::start(&open.join("editor.sock"), sender.clone())
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+
+        // A symlink to a private directory
+        let private = dir.path().join("private");
+        std::fs::create_dir(&private).unwrap();
+        std::fs::set_permissions(&private, std::fs::Permissions::from_mode(0o700)).unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&private, &link).unwrap();
+        let error = InstanceServer::start(&link.join("editor.sock"), sender.clone())
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+
+        assert!(InstanceServer::start(&private.join("editor.sock"), sender).is_ok());
+    }
+}
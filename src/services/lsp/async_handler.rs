@@ -11,6 +11,8 @@
 //! - LspHandle: Sync handle that can send commands to the task
 //! - Uses tokio channels for command/response communication
 
+use crate::primitives::hierarchy::{HierarchyChild, HierarchyDirection, HierarchyItem};
+use crate::primitives::semantic_tokens;
 use crate::services::async_bridge::{
     AsyncBridge, AsyncMessage, LspMessageType, LspProgressValue, LspServerStatus,
 };
@@ -21,10 +23,11 @@ use lsp_types::{
         PublishDiagnostics,
     },
     request::{Initialize, Request, Shutdown},
-    ClientCapabilities, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
-    DidSaveTextDocumentParams, InitializeParams, InitializeResult, InitializedParams,
-    PublishDiagnosticsParams, ServerCapabilities, TextDocumentContentChangeEvent,
-    TextDocumentIdentifier, TextDocumentItem, Uri, VersionedTextDocumentIdentifier,
+    ClientCapabilities, CodeLens, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
+    DidSaveTextDocumentParams, DocumentOnTypeFormattingParams, FormattingOptions, InitializeParams,
+    InitializeResult, InitializedParams, Position, PublishDiagnosticsParams, Range, SemanticTokens,
+    ServerCapabilities, TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, TextEdit, Uri, VersionedTextDocumentIdentifier,
     WindowClientCapabilities, WorkspaceFolder,
 };
 use serde::{Deserialize, Serialize};
@@ -196,8 +199,17 @@ impl LspClientState {
 /// Create common LSP client capabilities with workDoneProgress support
 fn create_client_capabilities() -> ClientCapabilities {
     use lsp_types::{
-        GeneralClientCapabilities, RenameClientCapabilities, TextDocumentClientCapabilities,
-        WorkspaceClientCapabilities, WorkspaceEditClientCapabilities,
+        CallHierarchyClientCapabilities, CodeLensClientCapabilities,
+        CodeLensWorkspaceClientCapabilities, DidChangeWatchedFilesClientCapabilities,
+        DocumentFormattingClientCapabilities, DocumentOnTypeFormattingClientCapabilities,
+        DocumentRangeFormattingClientCapabilities, DocumentSymbolClientCapabilities,
+        ExecuteCommandClientCapabilities, FailureHandlingKind, FoldingRangeClientCapabilities,
+        GeneralClientCapabilities, RenameClientCapabilities, ResourceOperationKind,
+        SemanticTokensClientCapabilities, SemanticTokensClientCapabilitiesRequests,
+        SemanticTokensFullOptions, TextDocumentClientCapabilities, TokenFormat,
+        TypeHierarchyClientCapabilities, WorkspaceClientCapabilities,
+        WorkspaceEditClientCapabilities, WorkspaceSymbolClientCapabilities,
+        WorkspaceSymbolResolveSupportCapability,
     };
 
     ClientCapabilities {
@@ -209,8 +221,30 @@ fn create_client_capabilities() -> ClientCapabilities {
             apply_edit: Some(true),
             workspace_edit: Some(WorkspaceEditClientCapabilities {
                 document_changes: Some(true),
+                resource_operations: Some(vec![
+                    ResourceOperationKind::Create,
+                    ResourceOperationKind::Rename,
+                    ResourceOperationKind::Delete,
+                ]),
+                // Changes before a failing one stay applied
+                failure_handling: Some(FailureHandlingKind::Abort),
                 ..Default::default()
             }),
+            symbol: Some(WorkspaceSymbolClientCapabilities {
+                // Symbols may come without a range, filled in by workspaceSymbol/resolve
+                resolve_support: Some(WorkspaceSymbolResolveSupportCapability {
+                    properties: vec!["location.range".to_string()],
+                }),
+                ..Default::default()
+            }),
+            execute_command: Some(ExecuteCommandClientCapabilities::default()),
+            code_lens: Some(CodeLensWorkspaceClientCapabilities {
+                refresh_support: Some(true),
+            }),
+            did_change_watched_files: Some(DidChangeWatchedFilesClientCapabilities {
+                dynamic_registration: Some(true),
+                relative_pattern_support: Some(true),
+            }),
             ..Default::default()
         }),
         text_document: Some(TextDocumentClientCapabilities {
@@ -220,6 +254,35 @@ fn create_client_capabilities() -> ClientCapabilities {
                 honors_change_annotations: Some(true),
                 ..Default::default()
             }),
+            // Folds hide whole lines, so character offsets are not useful
+            folding_range: Some(FoldingRangeClientCapabilities {
+                line_folding_only: Some(true),
+                ..Default::default()
+            }),
+            document_symbol: Some(DocumentSymbolClientCapabilities {
+                hierarchical_document_symbol_support: Some(true),
+                ..Default::default()
+            }),
+            call_hierarchy: Some(CallHierarchyClientCapabilities::default()),
+            type_hierarchy: Some(TypeHierarchyClientCapabilities::default()),
+            formatting: Some(DocumentFormattingClientCapabilities::default()),
+            range_formatting: Some(DocumentRangeFormattingClientCapabilities::default()),
+            on_type_formatting: Some(DocumentOnTypeFormattingClientCapabilities::default()),
+            code_lens: Some(CodeLensClientCapabilities::default()),
+            semantic_tokens: Some(SemanticTokensClientCapabilities {
+                requests: SemanticTokensClientCapabilitiesRequests {
+                    range: Some(true),
+                    full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                },
+                token_types: semantic_tokens::TOKEN_TYPES.to_vec(),
+                token_modifiers: semantic_tokens::TOKEN_MODIFIERS.to_vec(),
+                formats: vec![TokenFormat::RELATIVE],
+                overlapping_token_support: Some(false),
+                multiline_token_support: Some(false),
+                // Tokens are drawn over tree-sitter highlighting
+                augments_syntax_tokens: Some(true),
+                ..Default::default()
+            }),
             ..Default::default()
         }),
         general: Some(GeneralClientCapabilities {
@@ -259,6 +322,9 @@ enum LspCommand {
     /// Notify document saved
     DidSave { uri: Uri, text: Option<String> },
 
+    /// Notify watched files changed on disk
+    DidChangeWatchedFiles { changes: Vec<lsp_types::FileEvent> },
+
     /// Request completion at position
     Completion {
         request_id: u64,
@@ -338,6 +404,84 @@ enum LspCommand {
         end_char: u32,
     },
 
+    /// Request folding ranges for a document
+    FoldingRange { request_id: u64, uri: Uri },
+
+    /// Request the symbols defined in a document
+    DocumentSymbol { request_id: u64, uri: Uri },
+
+    /// Request semantic tokens for a document
+    SemanticTokens {
+        request_id: u64,
+        uri: Uri,
+        /// Visible range, requested on its own if `prefer_range` is set or
+        /// the server cannot send whole documents
+        viewport: Range,
+        prefer_range: bool,
+    },
+
+    /// Request the code lenses of a document
+    CodeLens { request_id: u64, uri: Uri },
+
+    /// Fill in the commands of unresolved code lenses
+    CodeLensResolve {
+        request_id: u64,
+        uri: Uri,
+        lenses: Vec<CodeLens>,
+    },
+
+    /// Run a command of the server (workspace/executeCommand)
+    ExecuteCommand {
+        request_id: u64,
+        command: lsp_types::Command,
+    },
+
+    /// Search the symbols of the whole workspace
+    WorkspaceSymbol { request_id: u64, query: String },
+
+    /// Fill in the range of a workspace symbol
+    WorkspaceSymbolResolve {
+        request_id: u64,
+        symbol: lsp_types::WorkspaceSymbol,
+    },
+
+    /// Prepare a call or type hierarchy at a position
+    PrepareHierarchy {
+        request_id: u64,
+        direction: HierarchyDirection,
+        uri: Uri,
+        line: u32,
+        character: u32,
+    },
+
+    /// List the callers, callees, supertypes or subtypes of a hierarchy item
+    HierarchyChildren {
+        request_id: u64,
+        direction: HierarchyDirection,
+        item: HierarchyItem,
+    },
+
+    /// Request edits formatting a whole document
+    Formatting {
+        request_id: u64,
+        uri: Uri,
+        options: FormattingOptions,
+    },
+
+    /// Request edits formatting a range of a document
+    RangeFormatting {
+        request_id: u64,
+        uri: Uri,
+        range: Range,
+        options: FormattingOptions,
+    },
+
+    /// Request edits after a character was typed
+    OnTypeFormatting {
+        request_id: u64,
+        params: DocumentOnTypeFormattingParams,
+    },
+
     /// Cancel a pending request
     CancelRequest {
         /// Editor's request ID to cancel
@@ -351,6 +495,9 @@ enum LspCommand {
         params: Option<Value>,
     },
 
+    /// Reply to a server-to-client request the editor answered
+    ServerReply { id: i64, result: Value },
+
     /// Shutdown the server
     Shutdown,
 }
@@ -385,6 +532,9 @@ struct LspState {
     /// Mapping from editor request_id to LSP JSON-RPC id for cancellation
     /// Key: editor request_id, Value: LSP JSON-RPC id
     active_requests: HashMap<u64, i64>,
+
+    /// Last full semantic tokens per document URI, for delta requests
+    semantic_tokens: HashMap<String, SemanticTokens>,
 }
 
 impl LspState {
@@ -491,6 +641,39 @@ impl LspState {
         pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
         editor_request_id: Option<u64>,
     ) -> Result<R, String> {
+        let (id, rx) = self
+            .start_request(method, params, pending, editor_request_id)
+            .await?;
+
+        tracing::trace!("Sent LSP request id={}, waiting for response...", id);
+
+        // Await response (this is OK now because the reader task will send it)
+        let result = rx
+            .await
+            .map_err(|_| "Response channel closed".to_string())??;
+
+        tracing::trace!("Received LSP response for request id={}", id);
+
+        // Remove tracking after response received
+        if let Some(editor_id) = editor_request_id {
+            self.active_requests.remove(&editor_id);
+            tracing::trace!("Completed request: editor_id={}, lsp_id={}", editor_id, id);
+        }
+
+        serde_json::from_value(result).map_err(|e| format!("Failed to deserialize response: {}", e))
+    }
+
+    /// Write a request without waiting for its response
+    ///
+    /// Returns the JSON-RPC id and the channel the reader task will deliver the
+    /// response on.
+    async fn start_request<P: Serialize>(
+        &mut self,
+        method: &str,
+        params: Option<P>,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+        editor_request_id: Option<u64>,
+    ) -> Result<(i64, oneshot::Receiver<Result<Value, String>>), String> {
         let id = self.next_id;
         self.next_id += 1;
 
@@ -516,22 +699,7 @@ impl LspState {
 
         self.write_message(&request).await?;
 
-        tracing::trace!("Sent LSP request id={}, waiting for response...", id);
-
-        // Await response (this is OK now because the reader task will send it)
-        let result = rx
-            .await
-            .map_err(|_| "Response channel closed".to_string())??;
-
-        tracing::trace!("Received LSP response for request id={}", id);
-
-        // Remove tracking after response received
-        if let Some(editor_id) = editor_request_id {
-            self.active_requests.remove(&editor_id);
-            tracing::trace!("Completed request: editor_id={}, lsp_id={}", editor_id, id);
-        }
-
-        serde_json::from_value(result).map_err(|e| format!("Failed to deserialize response: {}", e))
+        Ok((id, rx))
     }
 
     /// Handle initialize command
@@ -696,6 +864,19 @@ impl LspState {
         self.send_notification::<DidSaveTextDocument>(params).await
     }
 
+    /// Handle did_change_watched_files command
+    async fn handle_did_change_watched_files(
+        &mut self,
+        changes: Vec<lsp_types::FileEvent>,
+    ) -> Result<(), String> {
+        tracing::trace!("LSP: did_change_watched_files ({} changes)", changes.len());
+
+        self.send_notification::<lsp_types::notification::DidChangeWatchedFiles>(
+            lsp_types::DidChangeWatchedFilesParams { changes },
+        )
+        .await
+    }
+
     /// Handle completion request
     async fn handle_completion(
         &mut self,
@@ -1465,107 +1646,1173 @@ impl LspState {
         }
     }
 
-    /// Handle a plugin-initiated request by forwarding it to the server
-    async fn handle_plugin_request(
+    /// Handle folding range request
+    async fn handle_folding_range(
         &mut self,
         request_id: u64,
-        method: String,
-        params: Option<Value>,
+        uri: Uri,
         pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
-    ) {
-        tracing::trace!(
-            "Plugin request {} => method={} params={:?}",
-            request_id,
-            method,
-            params
-        );
-        let result = self
-            .send_request_sequential_tracked::<Value, Value>(
-                &method,
-                params,
-                pending,
-                Some(request_id),
-            )
-            .await;
-
-        tracing::trace!(
-            "Plugin request {} completed with result {:?}",
-            request_id,
-            &result
-        );
-        let _ = self.async_tx.send(AsyncMessage::PluginLspResponse {
-            language: self.language.clone(),
-            request_id,
-            result,
-        });
-    }
-
-    /// Handle shutdown command
-    async fn handle_shutdown(&mut self) -> Result<(), String> {
-        tracing::info!("Shutting down async LSP server");
-
-        let notification = JsonRpcNotification {
-            jsonrpc: "2.0".to_string(),
-            method: "shutdown".to_string(),
-            params: None,
+    ) -> Result<(), String> {
+        use lsp_types::{
+            FoldingRangeParams, PartialResultParams, TextDocumentIdentifier, WorkDoneProgressParams,
         };
 
-        self.write_message(&notification).await?;
+        // Without foldingRangeProvider, reply with no ranges so the editor
+        // falls back to tree-sitter
+        if self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.folding_range_provider.as_ref())
+            .is_none()
+        {
+            tracing::trace!(
+                "LSP: server does not support folding ranges for {}",
+                uri.as_str()
+            );
+            let _ = self.async_tx.send(AsyncMessage::LspFoldingRanges {
+                request_id,
+                uri: uri.as_str().to_string(),
+                ranges: Vec::new(),
+            });
+            return Ok(());
+        }
 
-        let exit = JsonRpcNotification {
-            jsonrpc: "2.0".to_string(),
-            method: "exit".to_string(),
-            params: None,
+        tracing::trace!("LSP: folding range request for {}", uri.as_str());
+
+        let params = FoldingRangeParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
         };
 
-        self.write_message(&exit).await
+        match self
+            .send_request_sequential::<_, Option<Vec<lsp_types::FoldingRange>>>(
+                "textDocument/foldingRange",
+                Some(params),
+                pending,
+            )
+            .await
+        {
+            Ok(ranges) => {
+                let ranges = ranges.unwrap_or_default();
+                tracing::trace!(
+                    "LSP: received {} folding ranges for {}",
+                    ranges.len(),
+                    uri.as_str()
+                );
+                let _ = self.async_tx.send(AsyncMessage::LspFoldingRanges {
+                    request_id,
+                    uri: uri.as_str().to_string(),
+                    ranges,
+                });
+                Ok(())
+            }
+            Err(e) => {
+                tracing::error!("Folding range request failed: {}", e);
+                let _ = self.async_tx.send(AsyncMessage::LspFoldingRanges {
+                    request_id,
+                    uri: uri.as_str().to_string(),
+                    ranges: Vec::new(),
+                });
+                Err(e)
+            }
+        }
     }
 
-    /// Send a cancel request notification to the server
-    async fn send_cancel_request(&mut self, lsp_id: i64) -> Result<(), String> {
-        tracing::trace!("Sending $/cancelRequest for LSP id {}", lsp_id);
-
-        let notification = JsonRpcNotification {
-            jsonrpc: "2.0".to_string(),
-            method: "$/cancelRequest".to_string(),
-            params: Some(serde_json::json!({ "id": lsp_id })),
+    /// Handle document symbol request
+    async fn handle_document_symbol(
+        &mut self,
+        request_id: u64,
+        uri: Uri,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        use lsp_types::{
+            DocumentSymbolParams, DocumentSymbolResponse, PartialResultParams,
+            TextDocumentIdentifier, WorkDoneProgressParams,
         };
 
-        self.write_message(&notification).await
-    }
-
-    /// Cancel a request by editor request_id
-    async fn handle_cancel_request(&mut self, request_id: u64) -> Result<(), String> {
-        if let Some(lsp_id) = self.active_requests.remove(&request_id) {
-            tracing::info!(
-                "Cancelling request: editor_id={}, lsp_id={}",
-                request_id,
-                lsp_id
-            );
-            self.send_cancel_request(lsp_id).await
-        } else {
+        // Without documentSymbolProvider, reply with no symbols so the editor
+        // falls back to tree-sitter
+        if self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.document_symbol_provider.as_ref())
+            .is_none()
+        {
             tracing::trace!(
-                "Cancel request ignored: no active LSP request for editor_id={}",
-                request_id
+                "LSP: server does not support document symbols for {}",
+                uri.as_str()
             );
-            Ok(())
+            let _ = self.async_tx.send(AsyncMessage::LspDocumentSymbols {
+                request_id,
+                uri: uri.as_str().to_string(),
+                symbols: None,
+            });
+            return Ok(());
         }
-    }
-}
 
-/// Async LSP task that handles all I/O
-struct LspTask {
-    /// Process handle
-    process: Child,
+        tracing::trace!("LSP: document symbol request for {}", uri.as_str());
 
-    /// Stdin for sending messages
-    stdin: ChildStdin,
+        let params = DocumentSymbolParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
 
-    /// Stdout for receiving messages
-    stdout: BufReader<ChildStdout>,
+        match self
+            .send_request_sequential::<_, Option<DocumentSymbolResponse>>(
+                "textDocument/documentSymbol",
+                Some(params),
+                pending,
+            )
+            .await
+        {
+            Ok(symbols) => {
+                let _ = self.async_tx.send(AsyncMessage::LspDocumentSymbols {
+                    request_id,
+                    uri: uri.as_str().to_string(),
+                    symbols,
+                });
+                Ok(())
+            }
+            Err(e) => {
+                tracing::error!("Document symbol request failed: {}", e);
+                let _ = self.async_tx.send(AsyncMessage::LspDocumentSymbols {
+                    request_id,
+                    uri: uri.as_str().to_string(),
+                    symbols: None,
+                });
+                Err(e)
+            }
+        }
+    }
 
-    /// Next request ID
-    next_id: i64,
+    /// Handle semantic tokens request
+    ///
+    /// Only the viewport is requested (`/range`) when asked to or when the
+    /// server cannot send whole documents. Whole-document results with a
+    /// `resultId` are kept so that the next request can use `/full/delta` and
+    /// receive just the tokens that changed.
+    async fn handle_semantic_tokens(
+        &mut self,
+        request_id: u64,
+        uri: Uri,
+        viewport: Range,
+        prefer_range: bool,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        use lsp_types::{SemanticTokensFullOptions, SemanticTokensServerCapabilities};
+
+        let options = self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.semantic_tokens_provider.as_ref())
+            .map(|provider| match provider {
+                SemanticTokensServerCapabilities::SemanticTokensOptions(options) => options.clone(),
+                SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+                    options.semantic_tokens_options.clone()
+                }
+            });
+        let Some(options) = options else {
+            tracing::trace!(
+                "LSP: server does not support semantic tokens for {}",
+                uri.as_str()
+            );
+            let _ = self.async_tx.send(AsyncMessage::LspSemanticTokens {
+                request_id,
+                uri: uri.as_str().to_string(),
+                legend: Default::default(),
+                range: None,
+                tokens: Ok(Vec::new()),
+            });
+            return Ok(());
+        };
+
+        let supports_range = options.range.unwrap_or(false);
+        let (supports_full, supports_delta) = match options.full {
+            Some(SemanticTokensFullOptions::Bool(full)) => (full, false),
+            Some(SemanticTokensFullOptions::Delta { delta }) => (true, delta.unwrap_or(false)),
+            None => (false, false),
+        };
+
+        tracing::trace!("LSP: semantic tokens request for {}", uri.as_str());
+
+        let use_range = supports_range && (prefer_range || !supports_full);
+        let result = if use_range {
+            self.request_semantic_tokens_range(&uri, viewport, pending)
+                .await
+        } else if supports_full {
+            self.request_semantic_tokens_full(&uri, supports_delta, pending)
+                .await
+        } else {
+            Ok(Vec::new())
+        };
+
+        if let Err(e) = &result {
+            tracing::error!("Semantic tokens request failed: {}", e);
+        }
+        let _ = self.async_tx.send(AsyncMessage::LspSemanticTokens {
+            request_id,
+            uri: uri.as_str().to_string(),
+            legend: options.legend,
+            range: use_range.then_some(viewport),
+            tokens: result
+                .as_deref()
+                .map(semantic_tokens::decode)
+                .map_err(|e| e.clone()),
+        });
+        result.map(|_| ())
+    }
+
+    /// Request the semantic tokens of a range of a document
+    async fn request_semantic_tokens_range(
+        &mut self,
+        uri: &Uri,
+        range: Range,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<Vec<lsp_types::SemanticToken>, String> {
+        use lsp_types::{
+            PartialResultParams, SemanticTokensRangeParams, SemanticTokensRangeResult,
+            WorkDoneProgressParams,
+        };
+
+        let params = SemanticTokensRangeParams {
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            range,
+        };
+        let result = self
+            .send_request_sequential::<_, Option<SemanticTokensRangeResult>>(
+                "textDocument/semanticTokens/range",
+                Some(params),
+                pending,
+            )
+            .await?;
+        Ok(match result {
+            Some(SemanticTokensRangeResult::Tokens(tokens)) => tokens.data,
+            Some(SemanticTokensRangeResult::Partial(partial)) => partial.data,
+            None => Vec::new(),
+        })
+    }
+
+    /// Request the semantic tokens of a whole document, as a delta if possible
+    async fn request_semantic_tokens_full(
+        &mut self,
+        uri: &Uri,
+        use_delta: bool,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<Vec<lsp_types::SemanticToken>, String> {
+        use lsp_types::{
+            PartialResultParams, SemanticTokensDeltaParams, SemanticTokensFullDeltaResult,
+            SemanticTokensParams, SemanticTokensResult, WorkDoneProgressParams,
+        };
+
+        let previous = self
+            .semantic_tokens
+            .remove(uri.as_str())
+            .filter(|_| use_delta);
+
+        let mut tokens = None;
+        if let Some(mut previous) = previous {
+            let params = SemanticTokensDeltaParams {
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                previous_result_id: previous.result_id.clone().unwrap_or_default(),
+            };
+            let result = self
+                .send_request_sequential::<_, Option<SemanticTokensFullDeltaResult>>(
+                    "textDocument/semanticTokens/full/delta",
+                    Some(params),
+                    pending,
+                )
+                .await?;
+            let (result_id, edits) = match result {
+                Some(SemanticTokensFullDeltaResult::Tokens(full)) => {
+                    (full.result_id, Err(full.data))
+                }
+                Some(SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+                    (delta.result_id, Ok(delta.edits))
+                }
+                Some(SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => {
+                    (None, Ok(edits))
+                }
+                None => (None, Ok(Vec::new())),
+            };
+            tokens = match edits {
+                Err(data) => Some(SemanticTokens { result_id, data }),
+                Ok(edits) => match semantic_tokens::apply_edits(&mut previous.data, edits) {
+                    Ok(()) => Some(SemanticTokens {
+                        result_id,
+                        data: previous.data,
+                    }),
+                    Err(e) => {
+                        // Fall back to requesting the whole document
+                        tracing::warn!(
+                            "Discarding semantic tokens delta for {}: {}",
+                            uri.as_str(),
+                            e
+                        );
+                        None
+                    }
+                },
+            };
+        }
+
+        let tokens = match tokens {
+            Some(tokens) => tokens,
+            None => {
+                let params = SemanticTokensParams {
+                    work_done_progress_params: WorkDoneProgressParams::default(),
+                    partial_result_params: PartialResultParams::default(),
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                };
+                match self
+                    .send_request_sequential::<_, Option<SemanticTokensResult>>(
+                        "textDocument/semanticTokens/full",
+                        Some(params),
+                        pending,
+                    )
+                    .await?
+                {
+                    Some(SemanticTokensResult::Tokens(tokens)) => tokens,
+                    Some(SemanticTokensResult::Partial(partial)) => SemanticTokens {
+                        result_id: None,
+                        data: partial.data,
+                    },
+                    None => SemanticTokens::default(),
+                }
+            }
+        };
+
+        if use_delta && tokens.result_id.is_some() {
+            self.semantic_tokens
+                .insert(uri.as_str().to_string(), tokens.clone());
+        }
+        Ok(tokens.data)
+    }
+
+    /// Handle code lens request
+    async fn handle_code_lens(
+        &mut self,
+        request_id: u64,
+        uri: Uri,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        use lsp_types::{CodeLensParams, PartialResultParams, WorkDoneProgressParams};
+
+        if self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.code_lens_provider.as_ref())
+            .is_none()
+        {
+            tracing::trace!(
+                "LSP: server does not support code lens for {}",
+                uri.as_str()
+            );
+            let _ = self.async_tx.send(AsyncMessage::LspCodeLenses {
+                request_id,
+                uri: uri.as_str().to_string(),
+                lenses: Vec::new(),
+            });
+            return Ok(());
+        }
+
+        tracing::trace!("LSP: code lens request for {}", uri.as_str());
+
+        let params = CodeLensParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let result = self
+            .send_request_sequential::<_, Option<Vec<CodeLens>>>(
+                "textDocument/codeLens",
+                Some(params),
+                pending,
+            )
+            .await;
+
+        if let Err(e) = &result {
+            tracing::error!("Code lens request failed: {}", e);
+        }
+        let _ = self.async_tx.send(AsyncMessage::LspCodeLenses {
+            request_id,
+            uri: uri.as_str().to_string(),
+            lenses: result.clone().ok().flatten().unwrap_or_default(),
+        });
+        result.map(|_| ())
+    }
+
+    /// Handle code lens resolve request
+    ///
+    /// Lenses are resolved one after the other; those the server fails to
+    /// resolve (or all of them, without `resolveProvider`) are sent back as is.
+    async fn handle_code_lens_resolve(
+        &mut self,
+        request_id: u64,
+        uri: Uri,
+        lenses: Vec<CodeLens>,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) {
+        let supports_resolve = self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.code_lens_provider.as_ref())
+            .and_then(|provider| provider.resolve_provider)
+            .unwrap_or(false);
+
+        let mut resolved = Vec::with_capacity(lenses.len());
+        for lens in lenses {
+            if !supports_resolve || lens.command.is_some() {
+                resolved.push(lens);
+                continue;
+            }
+            match self
+                .send_request_sequential::<_, CodeLens>("codeLens/resolve", Some(&lens), pending)
+                .await
+            {
+                Ok(lens) => resolved.push(lens),
+                Err(e) => {
+                    tracing::warn!("Code lens resolve failed: {}", e);
+                    resolved.push(lens);
+                }
+            }
+        }
+
+        let _ = self.async_tx.send(AsyncMessage::LspCodeLensesResolved {
+            request_id,
+            uri: uri.as_str().to_string(),
+            lenses: resolved,
+        });
+    }
+
+    /// Handle a workspace/executeCommand request
+    ///
+    /// Like plugin requests, the response is awaited on a separate task: the
+    /// server usually sends `workspace/applyEdit` and waits for our reply
+    /// before answering.
+    async fn handle_execute_command(
+        &mut self,
+        request_id: u64,
+        command: lsp_types::Command,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) {
+        use lsp_types::{ExecuteCommandParams, WorkDoneProgressParams};
+
+        let supported = self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.execute_command_provider.as_ref())
+            .is_some_and(|provider| provider.commands.contains(&command.command));
+        if !supported {
+            let _ = self.async_tx.send(AsyncMessage::LspExecuteCommand {
+                request_id,
+                result: Err(format!(
+                    "{} is not a command of the language server",
+                    command.command
+                )),
+            });
+            return;
+        }
+
+        tracing::trace!("LSP: executing command {}", command.command);
+
+        let params = ExecuteCommandParams {
+            command: command.command,
+            arguments: command.arguments.unwrap_or_default(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let rx = match self
+            .start_request("workspace/executeCommand", Some(params), pending, None)
+            .await
+        {
+            Ok((_, rx)) => rx,
+            Err(e) => {
+                let _ = self.async_tx.send(AsyncMessage::LspExecuteCommand {
+                    request_id,
+                    result: Err(e),
+                });
+                return;
+            }
+        };
+
+        let async_tx = self.async_tx.clone();
+        tokio::spawn(async move {
+            let result = rx
+                .await
+                .map_err(|_| "Response channel closed".to_string())
+                .and_then(|result| result);
+            let _ = async_tx.send(AsyncMessage::LspExecuteCommand {
+                request_id,
+                result: result.map(|_| ()),
+            });
+        });
+    }
+
+    /// Handle workspace symbol request
+    ///
+    /// Unlike other requests, the response is awaited on a separate task so
+    /// that the command loop stays free to process `CancelRequest` while the
+    /// server is still searching. Results for superseded queries are dropped
+    /// by the editor, which cancels them as the user keeps typing.
+    async fn handle_workspace_symbol(
+        &mut self,
+        request_id: u64,
+        query: String,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        use lsp_types::{
+            OneOf, PartialResultParams, WorkDoneProgressParams, WorkspaceSymbol,
+            WorkspaceSymbolParams, WorkspaceSymbolResponse,
+        };
+
+        let supported = matches!(
+            self.capabilities
+                .as_ref()
+                .and_then(|c| c.workspace_symbol_provider.as_ref()),
+            Some(OneOf::Left(true)) | Some(OneOf::Right(_))
+        );
+        if !supported {
+            tracing::trace!("LSP: server does not support workspace symbols");
+            let _ = self.async_tx.send(AsyncMessage::LspWorkspaceSymbols {
+                request_id,
+                language: self.language.clone(),
+                symbols: Vec::new(),
+            });
+            return Ok(());
+        }
+
+        tracing::trace!("LSP: workspace symbol request for {:?}", query);
+
+        // Requests answered on the detached task never clear their tracking
+        // entry, so forget every request the server has already answered
+        {
+            let pending = pending.lock().unwrap();
+            self.active_requests
+                .retain(|_, lsp_id| pending.contains_key(lsp_id));
+        }
+
+        let params = WorkspaceSymbolParams {
+            query,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+
+        let rx = match self
+            .start_request("workspace/symbol", Some(params), pending, Some(request_id))
+            .await
+        {
+            Ok((_, rx)) => rx,
+            Err(e) => {
+                tracing::error!("Workspace symbol request failed: {}", e);
+                let _ = self.async_tx.send(AsyncMessage::LspWorkspaceSymbols {
+                    request_id,
+                    language: self.language.clone(),
+                    symbols: Vec::new(),
+                });
+                return Err(e);
+            }
+        };
+
+        let async_tx = self.async_tx.clone();
+        let language = self.language.clone();
+        tokio::spawn(async move {
+            let response = rx
+                .await
+                .map_err(|_| "Response channel closed".to_string())
+                .and_then(|result| result)
+                .and_then(|value| {
+                    serde_json::from_value::<Option<WorkspaceSymbolResponse>>(value)
+                        .map_err(|e| format!("Failed to deserialize response: {}", e))
+                });
+
+            let symbols = match response {
+                Ok(Some(WorkspaceSymbolResponse::Nested(symbols))) => symbols,
+                Ok(Some(WorkspaceSymbolResponse::Flat(symbols))) => symbols
+                    .into_iter()
+                    .map(|info| WorkspaceSymbol {
+                        name: info.name,
+                        kind: info.kind,
+                        tags: info.tags,
+                        container_name: info.container_name,
+                        location: OneOf::Left(info.location),
+                        data: None,
+                    })
+                    .collect(),
+                Ok(None) => Vec::new(),
+                Err(e) => {
+                    // Cancelled requests end up here too
+                    tracing::debug!("Workspace symbol request {} failed: {}", request_id, e);
+                    Vec::new()
+                }
+            };
+
+            let _ = async_tx.send(AsyncMessage::LspWorkspaceSymbols {
+                request_id,
+                language,
+                symbols,
+            });
+        });
+
+        Ok(())
+    }
+
+    /// Handle workspace symbol resolve request
+    ///
+    /// The symbol is sent back as is if the server can't resolve it.
+    async fn handle_workspace_symbol_resolve(
+        &mut self,
+        request_id: u64,
+        symbol: lsp_types::WorkspaceSymbol,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) {
+        use lsp_types::{OneOf, WorkspaceSymbol};
+
+        let supports_resolve = matches!(
+            self.capabilities
+                .as_ref()
+                .and_then(|c| c.workspace_symbol_provider.as_ref()),
+            Some(OneOf::Right(options)) if options.resolve_provider == Some(true)
+        );
+        let symbol = if supports_resolve {
+            match self
+                .send_request_sequential::<_, WorkspaceSymbol>(
+                    "workspaceSymbol/resolve",
+                    Some(&symbol),
+                    pending,
+                )
+                .await
+            {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    tracing::warn!("Workspace symbol resolve failed: {}", e);
+                    symbol
+                }
+            }
+        } else {
+            symbol
+        };
+
+        let _ = self
+            .async_tx
+            .send(AsyncMessage::LspWorkspaceSymbolResolved { request_id, symbol });
+    }
+
+    /// Handle call/type hierarchy prepare request
+    async fn handle_prepare_hierarchy(
+        &mut self,
+        request_id: u64,
+        direction: HierarchyDirection,
+        uri: Uri,
+        line: u32,
+        character: u32,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        use lsp_types::{
+            CallHierarchyItem, CallHierarchyPrepareParams, CallHierarchyServerCapability, Position,
+            TextDocumentIdentifier, TextDocumentPositionParams, TypeHierarchyItem,
+            TypeHierarchyPrepareParams, WorkDoneProgressParams,
+        };
+
+        let text_document_position_params = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position: Position { line, character },
+        };
+
+        let result = if direction.is_call() {
+            let supported = matches!(
+                self.capabilities
+                    .as_ref()
+                    .and_then(|c| c.call_hierarchy_provider.as_ref()),
+                Some(CallHierarchyServerCapability::Simple(true))
+                    | Some(CallHierarchyServerCapability::Options(_))
+            );
+            if !supported {
+                tracing::trace!("LSP: server does not support call hierarchy");
+                let _ = self.async_tx.send(AsyncMessage::LspHierarchyItems {
+                    request_id,
+                    items: Vec::new(),
+                });
+                return Ok(());
+            }
+            let params = CallHierarchyPrepareParams {
+                text_document_position_params,
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            };
+            self.send_request_sequential::<_, Option<Vec<CallHierarchyItem>>>(
+                "textDocument/prepareCallHierarchy",
+                Some(params),
+                pending,
+            )
+            .await
+            .map(|items| {
+                items
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(Into::into)
+                    .collect()
+            })
+        } else {
+            // lsp-types doesn't expose typeHierarchyProvider, so just ask: servers
+            // without support answer with MethodNotFound
+            let params = TypeHierarchyPrepareParams {
+                text_document_position_params,
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            };
+            self.send_request_sequential::<_, Option<Vec<TypeHierarchyItem>>>(
+                "textDocument/prepareTypeHierarchy",
+                Some(params),
+                pending,
+            )
+            .await
+            .map(|items| {
+                items
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(Into::into)
+                    .collect()
+            })
+        };
+
+        match result {
+            Ok(items) => {
+                let _ = self
+                    .async_tx
+                    .send(AsyncMessage::LspHierarchyItems { request_id, items });
+                Ok(())
+            }
+            Err(e) => {
+                tracing::error!("Hierarchy prepare request failed: {}", e);
+                let _ = self.async_tx.send(AsyncMessage::LspHierarchyItems {
+                    request_id,
+                    items: Vec::new(),
+                });
+                Err(e)
+            }
+        }
+    }
+
+    /// Handle request for the children of a hierarchy item
+    async fn handle_hierarchy_children(
+        &mut self,
+        request_id: u64,
+        direction: HierarchyDirection,
+        item: HierarchyItem,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        use lsp_types::{
+            CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyOutgoingCall,
+            CallHierarchyOutgoingCallsParams, Location, PartialResultParams, TypeHierarchyItem,
+            TypeHierarchySubtypesParams, TypeHierarchySupertypesParams, WorkDoneProgressParams,
+        };
+
+        tracing::trace!(
+            "LSP: {} request for {}",
+            direction.method(),
+            item.name.as_str()
+        );
+
+        let parent_uri = item.uri.clone();
+        let result = match direction {
+            HierarchyDirection::IncomingCalls => {
+                let params = CallHierarchyIncomingCallsParams {
+                    item: item.into(),
+                    work_done_progress_params: WorkDoneProgressParams::default(),
+                    partial_result_params: PartialResultParams::default(),
+                };
+                self.send_request_sequential::<_, Option<Vec<CallHierarchyIncomingCall>>>(
+                    direction.method(),
+                    Some(params),
+                    pending,
+                )
+                .await
+                .map(|calls| {
+                    // The call sites are in the caller
+                    calls
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|call| HierarchyChild {
+                            call_site: call.from_ranges.first().map(|range| Location {
+                                uri: call.from.uri.clone(),
+                                range: *range,
+                            }),
+                            item: call.from.into(),
+                        })
+                        .collect()
+                })
+            }
+            HierarchyDirection::OutgoingCalls => {
+                let params = CallHierarchyOutgoingCallsParams {
+                    item: item.into(),
+                    work_done_progress_params: WorkDoneProgressParams::default(),
+                    partial_result_params: PartialResultParams::default(),
+                };
+                self.send_request_sequential::<_, Option<Vec<CallHierarchyOutgoingCall>>>(
+                    direction.method(),
+                    Some(params),
+                    pending,
+                )
+                .await
+                .map(|calls| {
+                    // The call sites are in the item being expanded
+                    calls
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|call| HierarchyChild {
+                            call_site: call.from_ranges.first().map(|range| Location {
+                                uri: parent_uri.clone(),
+                                range: *range,
+                            }),
+                            item: call.to.into(),
+                        })
+                        .collect()
+                })
+            }
+            HierarchyDirection::Supertypes | HierarchyDirection::Subtypes => {
+                let response = if direction == HierarchyDirection::Supertypes {
+                    let params = TypeHierarchySupertypesParams {
+                        item: item.into(),
+                        work_done_progress_params: WorkDoneProgressParams::default(),
+                        partial_result_params: PartialResultParams::default(),
+                    };
+                    self.send_request_sequential::<_, Option<Vec<TypeHierarchyItem>>>(
+                        direction.method(),
+                        Some(params),
+                        pending,
+                    )
+                    .await
+                } else {
+                    let params = TypeHierarchySubtypesParams {
+                        item: item.into(),
+                        work_done_progress_params: WorkDoneProgressParams::default(),
+                        partial_result_params: PartialResultParams::default(),
+                    };
+                    self.send_request_sequential::<_, Option<Vec<TypeHierarchyItem>>>(
+                        direction.method(),
+                        Some(params),
+                        pending,
+                    )
+                    .await
+                };
+                response.map(|items| {
+                    items
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|item| HierarchyChild {
+                            item: item.into(),
+                            call_site: None,
+                        })
+                        .collect()
+                })
+            }
+        };
+
+        match result {
+            Ok(children) => {
+                let _ = self.async_tx.send(AsyncMessage::LspHierarchyChildren {
+                    request_id,
+                    children,
+                });
+                Ok(())
+            }
+            Err(e) => {
+                tracing::error!("{} request failed: {}", direction.method(), e);
+                let _ = self.async_tx.send(AsyncMessage::LspHierarchyChildren {
+                    request_id,
+                    children: Vec::new(),
+                });
+                Err(e)
+            }
+        }
+    }
+
+    /// Handle whole-document formatting request
+    async fn handle_formatting(
+        &mut self,
+        request_id: u64,
+        uri: Uri,
+        options: FormattingOptions,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        use lsp_types::{DocumentFormattingParams, OneOf, WorkDoneProgressParams};
+
+        let supported = matches!(
+            self.capabilities
+                .as_ref()
+                .and_then(|c| c.document_formatting_provider.as_ref()),
+            Some(OneOf::Left(true)) | Some(OneOf::Right(_))
+        );
+        if !supported {
+            tracing::trace!("LSP: server does not support formatting");
+            let _ = self.async_tx.send(AsyncMessage::LspFormatting {
+                request_id,
+                result: Err(format!(
+                    "{} server does not support formatting",
+                    self.language
+                )),
+            });
+            return Ok(());
+        }
+
+        tracing::trace!("LSP: formatting request for {}", uri.as_str());
+        let params = DocumentFormattingParams {
+            text_document: TextDocumentIdentifier { uri },
+            options,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let result = self
+            .send_request_sequential::<_, Option<Vec<TextEdit>>>(
+                "textDocument/formatting",
+                Some(params),
+                pending,
+            )
+            .await;
+        self.send_formatting_result(request_id, result)
+    }
+
+    /// Handle range formatting request
+    async fn handle_range_formatting(
+        &mut self,
+        request_id: u64,
+        uri: Uri,
+        range: Range,
+        options: FormattingOptions,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        use lsp_types::{DocumentRangeFormattingParams, OneOf, WorkDoneProgressParams};
+
+        let supported = matches!(
+            self.capabilities
+                .as_ref()
+                .and_then(|c| c.document_range_formatting_provider.as_ref()),
+            Some(OneOf::Left(true)) | Some(OneOf::Right(_))
+        );
+        if !supported {
+            tracing::trace!("LSP: server does not support range formatting");
+            let _ = self.async_tx.send(AsyncMessage::LspFormatting {
+                request_id,
+                result: Err(format!(
+                    "{} server does not support range formatting",
+                    self.language
+                )),
+            });
+            return Ok(());
+        }
+
+        tracing::trace!(
+            "LSP: range formatting request for {} lines {}-{}",
+            uri.as_str(),
+            range.start.line,
+            range.end.line
+        );
+        let params = DocumentRangeFormattingParams {
+            text_document: TextDocumentIdentifier { uri },
+            range,
+            options,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let result = self
+            .send_request_sequential::<_, Option<Vec<TextEdit>>>(
+                "textDocument/rangeFormatting",
+                Some(params),
+                pending,
+            )
+            .await;
+        self.send_formatting_result(request_id, result)
+    }
+
+    /// Handle on-type formatting request
+    ///
+    /// The editor asks after every typed character; only the server's trigger
+    /// characters are forwarded, the others get an empty answer right away.
+    async fn handle_on_type_formatting(
+        &mut self,
+        request_id: u64,
+        params: DocumentOnTypeFormattingParams,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        let ch = &params.ch;
+        let is_trigger = self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.document_on_type_formatting_provider.as_ref())
+            .is_some_and(|provider| {
+                provider.first_trigger_character == *ch
+                    || provider
+                        .more_trigger_character
+                        .as_ref()
+                        .is_some_and(|more| more.contains(ch))
+            });
+        if !is_trigger {
+            let _ = self.async_tx.send(AsyncMessage::LspFormatting {
+                request_id,
+                result: Ok(Vec::new()),
+            });
+            return Ok(());
+        }
+
+        let position = &params.text_document_position;
+        tracing::trace!(
+            "LSP: on-type formatting request at {}:{}:{} for {:?}",
+            position.text_document.uri.as_str(),
+            position.position.line,
+            position.position.character,
+            ch
+        );
+        let result = self
+            .send_request_sequential::<_, Option<Vec<TextEdit>>>(
+                "textDocument/onTypeFormatting",
+                Some(params),
+                pending,
+            )
+            .await;
+        self.send_formatting_result(request_id, result)
+    }
+
+    /// Forward the edits of a formatting request to the main loop
+    fn send_formatting_result(
+        &self,
+        request_id: u64,
+        result: Result<Option<Vec<TextEdit>>, String>,
+    ) -> Result<(), String> {
+        match result {
+            Ok(edits) => {
+                let _ = self.async_tx.send(AsyncMessage::LspFormatting {
+                    request_id,
+                    result: Ok(edits.unwrap_or_default()),
+                });
+                Ok(())
+            }
+            Err(e) => {
+                tracing::error!("Formatting request failed: {}", e);
+                let _ = self.async_tx.send(AsyncMessage::LspFormatting {
+                    request_id,
+                    result: Err(e.clone()),
+                });
+                Err(e)
+            }
+        }
+    }
+
+    /// Handle a plugin-initiated request by forwarding it to the server
+    async fn handle_plugin_request(
+        &mut self,
+        request_id: u64,
+        method: String,
+        params: Option<Value>,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) {
+        tracing::trace!(
+            "Plugin request {} => method={} params={:?}",
+            request_id,
+            method,
+            params
+        );
+
+        // Awaited on a separate task: a request such as workspace/executeCommand
+        // can make the server send workspace/applyEdit and wait for our reply
+        // before answering, which the command loop must be free to write
+        let rx = match self.start_request(&method, params, pending, None).await {
+            Ok((_, rx)) => rx,
+            Err(e) => {
+                let _ = self.async_tx.send(AsyncMessage::PluginLspResponse {
+                    language: self.language.clone(),
+                    request_id,
+                    result: Err(e),
+                });
+                return;
+            }
+        };
+
+        let async_tx = self.async_tx.clone();
+        let language = self.language.clone();
+        tokio::spawn(async move {
+            let result = rx
+                .await
+                .map_err(|_| "Response channel closed".to_string())
+                .and_then(|result| result);
+            tracing::trace!(
+                "Plugin request {} completed with result {:?}",
+                request_id,
+                &result
+            );
+            let _ = async_tx.send(AsyncMessage::PluginLspResponse {
+                language,
+                request_id,
+                result,
+            });
+        });
+    }
+
+    /// Handle shutdown command
+    async fn handle_shutdown(&mut self) -> Result<(), String> {
+        tracing::info!("Shutting down async LSP server");
+
+        let notification = JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "shutdown".to_string(),
+            params: None,
+        };
+
+        self.write_message(&notification).await?;
+
+        let exit = JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "exit".to_string(),
+            params: None,
+        };
+
+        self.write_message(&exit).await
+    }
+
+    /// Send a cancel request notification to the server
+    async fn send_cancel_request(&mut self, lsp_id: i64) -> Result<(), String> {
+        tracing::trace!("Sending $/cancelRequest for LSP id {}", lsp_id);
+
+        let notification = JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "$/cancelRequest".to_string(),
+            params: Some(serde_json::json!({ "id": lsp_id })),
+        };
+
+        self.write_message(&notification).await
+    }
+
+    /// Cancel a request by editor request_id
+    async fn handle_cancel_request(&mut self, request_id: u64) -> Result<(), String> {
+        if let Some(lsp_id) = self.active_requests.remove(&request_id) {
+            tracing::info!(
+                "Cancelling request: editor_id={}, lsp_id={}",
+                request_id,
+                lsp_id
+            );
+            self.send_cancel_request(lsp_id).await
+        } else {
+            tracing::trace!(
+                "Cancel request ignored: no active LSP request for editor_id={}",
+                request_id
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Async LSP task that handles all I/O
+struct LspTask {
+    /// Process handle
+    process: Child,
+
+    /// Stdin for sending messages
+    stdin: ChildStdin,
+
+    /// Stdout for receiving messages
+    stdout: BufReader<ChildStdout>,
+
+    /// Next request ID
+    next_id: i64,
 
     /// Pending requests waiting for response
     pending: HashMap<i64, oneshot::Sender<Result<Value, String>>>,
@@ -1790,6 +3037,7 @@ impl LspTask {
             async_tx: self.async_tx.clone(),
             language: self.language.clone(),
             active_requests: HashMap::new(),
+            semantic_tokens: HashMap::new(),
         };
 
         let pending = Arc::new(Mutex::new(self.pending));
@@ -1901,6 +3149,12 @@ impl LspTask {
                                 pending_commands.push(LspCommand::DidSave { uri, text });
                             }
                         }
+                        LspCommand::DidChangeWatchedFiles { changes } => {
+                            // Servers register for changes after initialization
+                            if state.initialized {
+                                let _ = state.handle_did_change_watched_files(changes).await;
+                            }
+                        }
                         LspCommand::Completion {
                             request_id,
                             uri,
@@ -1997,132 +3251,407 @@ impl LspTask {
                             character,
                         } => {
                             if state.initialized {
-                                tracing::info!("Processing References request for {}", uri.as_str());
+                                tracing::info!("Processing References request for {}", uri.as_str());
+                                let _ = state
+                                    .handle_references(request_id, uri, line, character, &pending)
+                                    .await;
+                            } else {
+                                tracing::trace!("LSP not initialized, cannot get references");
+                                let _ = state.async_tx.send(AsyncMessage::LspReferences {
+                                    request_id,
+                                    locations: Vec::new(),
+                                });
+                            }
+                        }
+                        LspCommand::SignatureHelp {
+                            request_id,
+                            uri,
+                            line,
+                            character,
+                        } => {
+                            if state.initialized {
+                                tracing::info!("Processing SignatureHelp request for {}", uri.as_str());
+                                let _ = state
+                                    .handle_signature_help(request_id, uri, line, character, &pending)
+                                    .await;
+                            } else {
+                                tracing::trace!("LSP not initialized, cannot get signature help");
+                                let _ = state.async_tx.send(AsyncMessage::LspSignatureHelp {
+                                    request_id,
+                                    signature_help: None,
+                                });
+                            }
+                        }
+                        LspCommand::CodeActions {
+                            request_id,
+                            uri,
+                            start_line,
+                            start_char,
+                            end_line,
+                            end_char,
+                            diagnostics,
+                        } => {
+                            if state.initialized {
+                                tracing::info!("Processing CodeActions request for {}", uri.as_str());
+                                let _ = state
+                                    .handle_code_actions(
+                                        request_id,
+                                        uri,
+                                        start_line,
+                                        start_char,
+                                        end_line,
+                                        end_char,
+                                        diagnostics,
+                                        &pending,
+                                    )
+                                    .await;
+                            } else {
+                                tracing::trace!("LSP not initialized, cannot get code actions");
+                                let _ = state.async_tx.send(AsyncMessage::LspCodeActions {
+                                    request_id,
+                                    actions: Vec::new(),
+                                });
+                            }
+                        }
+                        LspCommand::DocumentDiagnostic {
+                            request_id,
+                            uri,
+                            previous_result_id,
+                        } => {
+                            if state.initialized {
+                                tracing::info!(
+                                    "Processing DocumentDiagnostic request for {}",
+                                    uri.as_str()
+                                );
+                                let _ = state
+                                    .handle_document_diagnostic(
+                                        request_id,
+                                        uri,
+                                        previous_result_id,
+                                        &pending,
+                                    )
+                                    .await;
+                            } else {
+                                tracing::trace!(
+                                    "LSP not initialized, cannot get document diagnostics"
+                                );
+                                let _ = state.async_tx.send(AsyncMessage::LspPulledDiagnostics {
+                                    request_id,
+                                    uri: uri.as_str().to_string(),
+                                    result_id: None,
+                                    diagnostics: Vec::new(),
+                                    unchanged: false,
+                                });
+                            }
+                        }
+                        LspCommand::InlayHints {
+                            request_id,
+                            uri,
+                            start_line,
+                            start_char,
+                            end_line,
+                            end_char,
+                        } => {
+                            if state.initialized {
+                                tracing::info!(
+                                    "Processing InlayHints request for {}",
+                                    uri.as_str()
+                                );
+                                let _ = state
+                                    .handle_inlay_hints(
+                                        request_id,
+                                        uri,
+                                        start_line,
+                                        start_char,
+                                        end_line,
+                                        end_char,
+                                        &pending,
+                                    )
+                                    .await;
+                            } else {
+                                tracing::trace!(
+                                    "LSP not initialized, cannot get inlay hints"
+                                );
+                                let _ = state.async_tx.send(AsyncMessage::LspInlayHints {
+                                    request_id,
+                                    uri: uri.as_str().to_string(),
+                                    hints: Vec::new(),
+                                });
+                            }
+                        }
+                        LspCommand::FoldingRange { request_id, uri } => {
+                            if state.initialized {
+                                tracing::info!(
+                                    "Processing FoldingRange request for {}",
+                                    uri.as_str()
+                                );
+                                let _ = state
+                                    .handle_folding_range(request_id, uri, &pending)
+                                    .await;
+                            } else {
+                                tracing::trace!(
+                                    "LSP not initialized, cannot get folding ranges"
+                                );
+                                let _ = state.async_tx.send(AsyncMessage::LspFoldingRanges {
+                                    request_id,
+                                    uri: uri.as_str().to_string(),
+                                    ranges: Vec::new(),
+                                });
+                            }
+                        }
+                        LspCommand::SemanticTokens {
+                            request_id,
+                            uri,
+                            viewport,
+                            prefer_range,
+                        } => {
+                            if state.initialized {
+                                tracing::trace!(
+                                    "Processing SemanticTokens request for {}",
+                                    uri.as_str()
+                                );
+                                let _ = state
+                                    .handle_semantic_tokens(
+                                        request_id,
+                                        uri,
+                                        viewport,
+                                        prefer_range,
+                                        &pending,
+                                    )
+                                    .await;
+                            } else {
+                                tracing::trace!(
+                                    "LSP not initialized, cannot get semantic tokens"
+                                );
+                                let _ = state.async_tx.send(AsyncMessage::LspSemanticTokens {
+                                    request_id,
+                                    uri: uri.as_str().to_string(),
+                                    legend: Default::default(),
+                                    range: None,
+                                    tokens: Ok(Vec::new()),
+                                });
+                            }
+                        }
+                        LspCommand::CodeLens { request_id, uri } => {
+                            if state.initialized {
+                                tracing::trace!(
+                                    "Processing CodeLens request for {}",
+                                    uri.as_str()
+                                );
+                                let _ = state.handle_code_lens(request_id, uri, &pending).await;
+                            } else {
+                                tracing::trace!("LSP not initialized, cannot get code lenses");
+                                let _ = state.async_tx.send(AsyncMessage::LspCodeLenses {
+                                    request_id,
+                                    uri: uri.as_str().to_string(),
+                                    lenses: Vec::new(),
+                                });
+                            }
+                        }
+                        LspCommand::CodeLensResolve {
+                            request_id,
+                            uri,
+                            lenses,
+                        } => {
+                            if state.initialized {
+                                tracing::trace!(
+                                    "Processing CodeLensResolve request for {}",
+                                    uri.as_str()
+                                );
+                                state
+                                    .handle_code_lens_resolve(request_id, uri, lenses, &pending)
+                                    .await;
+                            } else {
+                                tracing::trace!("LSP not initialized, cannot resolve code lenses");
+                                let _ =
+                                    state.async_tx.send(AsyncMessage::LspCodeLensesResolved {
+                                        request_id,
+                                        uri: uri.as_str().to_string(),
+                                        lenses,
+                                    });
+                            }
+                        }
+                        LspCommand::ExecuteCommand {
+                            request_id,
+                            command,
+                        } => {
+                            if state.initialized {
+                                tracing::trace!(
+                                    "Processing ExecuteCommand request for {}",
+                                    command.command
+                                );
+                                state
+                                    .handle_execute_command(request_id, command, &pending)
+                                    .await;
+                            } else {
+                                tracing::trace!("LSP not initialized, cannot execute command");
+                                let _ = state.async_tx.send(AsyncMessage::LspExecuteCommand {
+                                    request_id,
+                                    result: Err("LSP not initialized".to_string()),
+                                });
+                            }
+                        }
+                        LspCommand::DocumentSymbol { request_id, uri } => {
+                            if state.initialized {
+                                tracing::info!(
+                                    "Processing DocumentSymbol request for {}",
+                                    uri.as_str()
+                                );
+                                let _ = state
+                                    .handle_document_symbol(request_id, uri, &pending)
+                                    .await;
+                            } else {
+                                tracing::trace!(
+                                    "LSP not initialized, cannot get document symbols"
+                                );
+                                let _ = state.async_tx.send(AsyncMessage::LspDocumentSymbols {
+                                    request_id,
+                                    uri: uri.as_str().to_string(),
+                                    symbols: None,
+                                });
+                            }
+                        }
+                        LspCommand::PrepareHierarchy {
+                            request_id,
+                            direction,
+                            uri,
+                            line,
+                            character,
+                        } => {
+                            if state.initialized {
+                                tracing::info!(
+                                    "Processing PrepareHierarchy request for {}",
+                                    uri.as_str()
+                                );
+                                let _ = state
+                                    .handle_prepare_hierarchy(
+                                        request_id, direction, uri, line, character, &pending,
+                                    )
+                                    .await;
+                            } else {
+                                tracing::trace!("LSP not initialized, cannot prepare hierarchy");
+                                let _ = state.async_tx.send(AsyncMessage::LspHierarchyItems {
+                                    request_id,
+                                    items: Vec::new(),
+                                });
+                            }
+                        }
+                        LspCommand::HierarchyChildren {
+                            request_id,
+                            direction,
+                            item,
+                        } => {
+                            if state.initialized {
+                                tracing::info!(
+                                    "Processing HierarchyChildren request for {}",
+                                    item.name
+                                );
+                                let _ = state
+                                    .handle_hierarchy_children(
+                                        request_id, direction, item, &pending,
+                                    )
+                                    .await;
+                            } else {
+                                tracing::trace!(
+                                    "LSP not initialized, cannot expand hierarchy"
+                                );
+                                let _ = state.async_tx.send(AsyncMessage::LspHierarchyChildren {
+                                    request_id,
+                                    children: Vec::new(),
+                                });
+                            }
+                        }
+                        LspCommand::Formatting {
+                            request_id,
+                            uri,
+                            options,
+                        } => {
+                            if state.initialized {
+                                tracing::info!("Processing Formatting request for {}", uri.as_str());
                                 let _ = state
-                                    .handle_references(request_id, uri, line, character, &pending)
+                                    .handle_formatting(request_id, uri, options, &pending)
                                     .await;
                             } else {
-                                tracing::trace!("LSP not initialized, cannot get references");
-                                let _ = state.async_tx.send(AsyncMessage::LspReferences {
+                                tracing::trace!("LSP not initialized, cannot format");
+                                let _ = state.async_tx.send(AsyncMessage::LspFormatting {
                                     request_id,
-                                    locations: Vec::new(),
+                                    result: Err("LSP not initialized".to_string()),
                                 });
                             }
                         }
-                        LspCommand::SignatureHelp {
+                        LspCommand::RangeFormatting {
                             request_id,
                             uri,
-                            line,
-                            character,
+                            range,
+                            options,
                         } => {
                             if state.initialized {
-                                tracing::info!("Processing SignatureHelp request for {}", uri.as_str());
+                                tracing::info!(
+                                    "Processing RangeFormatting request for {}",
+                                    uri.as_str()
+                                );
                                 let _ = state
-                                    .handle_signature_help(request_id, uri, line, character, &pending)
+                                    .handle_range_formatting(request_id, uri, range, options, &pending)
                                     .await;
                             } else {
-                                tracing::trace!("LSP not initialized, cannot get signature help");
-                                let _ = state.async_tx.send(AsyncMessage::LspSignatureHelp {
+                                tracing::trace!("LSP not initialized, cannot format range");
+                                let _ = state.async_tx.send(AsyncMessage::LspFormatting {
                                     request_id,
-                                    signature_help: None,
+                                    result: Err("LSP not initialized".to_string()),
                                 });
                             }
                         }
-                        LspCommand::CodeActions {
-                            request_id,
-                            uri,
-                            start_line,
-                            start_char,
-                            end_line,
-                            end_char,
-                            diagnostics,
-                        } => {
+                        LspCommand::OnTypeFormatting { request_id, params } => {
                             if state.initialized {
-                                tracing::info!("Processing CodeActions request for {}", uri.as_str());
                                 let _ = state
-                                    .handle_code_actions(
-                                        request_id,
-                                        uri,
-                                        start_line,
-                                        start_char,
-                                        end_line,
-                                        end_char,
-                                        diagnostics,
-                                        &pending,
-                                    )
+                                    .handle_on_type_formatting(request_id, params, &pending)
                                     .await;
                             } else {
-                                tracing::trace!("LSP not initialized, cannot get code actions");
-                                let _ = state.async_tx.send(AsyncMessage::LspCodeActions {
+                                let _ = state.async_tx.send(AsyncMessage::LspFormatting {
                                     request_id,
-                                    actions: Vec::new(),
+                                    result: Ok(Vec::new()),
                                 });
                             }
                         }
-                        LspCommand::DocumentDiagnostic {
-                            request_id,
-                            uri,
-                            previous_result_id,
-                        } => {
+                        LspCommand::WorkspaceSymbol { request_id, query } => {
                             if state.initialized {
                                 tracing::info!(
-                                    "Processing DocumentDiagnostic request for {}",
-                                    uri.as_str()
+                                    "Processing WorkspaceSymbol request for {:?}",
+                                    query
                                 );
                                 let _ = state
-                                    .handle_document_diagnostic(
-                                        request_id,
-                                        uri,
-                                        previous_result_id,
-                                        &pending,
-                                    )
+                                    .handle_workspace_symbol(request_id, query, &pending)
                                     .await;
                             } else {
                                 tracing::trace!(
-                                    "LSP not initialized, cannot get document diagnostics"
+                                    "LSP not initialized, cannot search workspace symbols"
                                 );
-                                let _ = state.async_tx.send(AsyncMessage::LspPulledDiagnostics {
+                                let _ = state.async_tx.send(AsyncMessage::LspWorkspaceSymbols {
                                     request_id,
-                                    uri: uri.as_str().to_string(),
-                                    result_id: None,
-                                    diagnostics: Vec::new(),
-                                    unchanged: false,
+                                    language: state.language.clone(),
+                                    symbols: Vec::new(),
                                 });
                             }
                         }
-                        LspCommand::InlayHints {
-                            request_id,
-                            uri,
-                            start_line,
-                            start_char,
-                            end_line,
-                            end_char,
-                        } => {
+                        LspCommand::WorkspaceSymbolResolve { request_id, symbol } => {
                             if state.initialized {
-                                tracing::info!(
-                                    "Processing InlayHints request for {}",
-                                    uri.as_str()
+                                tracing::trace!(
+                                    "Processing WorkspaceSymbolResolve request for {:?}",
+                                    symbol.name
                                 );
-                                let _ = state
-                                    .handle_inlay_hints(
-                                        request_id,
-                                        uri,
-                                        start_line,
-                                        start_char,
-                                        end_line,
-                                        end_char,
-                                        &pending,
-                                    )
+                                state
+                                    .handle_workspace_symbol_resolve(request_id, symbol, &pending)
                                     .await;
                             } else {
                                 tracing::trace!(
-                                    "LSP not initialized, cannot get inlay hints"
+                                    "LSP not initialized, cannot resolve workspace symbol"
                                 );
-                                let _ = state.async_tx.send(AsyncMessage::LspInlayHints {
-                                    request_id,
-                                    uri: uri.as_str().to_string(),
-                                    hints: Vec::new(),
-                                });
+                                let _ = state
+                                    .async_tx
+                                    .send(AsyncMessage::LspWorkspaceSymbolResolved {
+                                        request_id,
+                                        symbol,
+                                    });
                             }
                         }
                         LspCommand::CancelRequest { request_id } => {
@@ -2163,6 +3692,18 @@ impl LspTask {
                                 });
                             }
                         }
+                        LspCommand::ServerReply { id, result } => {
+                            tracing::trace!("Sending editor reply to server request id={}", id);
+                            let response = JsonRpcResponse {
+                                jsonrpc: "2.0".to_string(),
+                                id,
+                                result: Some(result),
+                                error: None,
+                            };
+                            if let Err(e) = state.write_message(&response).await {
+                                tracing::error!("Failed to send reply to server: {}", e);
+                            }
+                        }
                         LspCommand::Shutdown => {
                             tracing::info!("Processing Shutdown command");
                             // Set flag before shutdown to prevent spurious error messages
@@ -2892,12 +4433,116 @@ async fn handle_message_dispatch(
                         error: None,
                     }
                 }
+                "workspace/applyEdit" => {
+                    // The editor applies the edit and sends the real outcome
+                    // back with `LspHandle::reply_to_server`
+                    let params = request.params.clone().unwrap_or(Value::Null);
+                    match serde_json::from_value::<lsp_types::ApplyWorkspaceEditParams>(params) {
+                        Ok(params) => {
+                            tracing::trace!("Forwarding workspace/applyEdit (id={})", request.id);
+                            let _ = async_tx.send(AsyncMessage::LspApplyEdit {
+                                language: language.to_string(),
+                                server_request_id: request.id,
+                                label: params.label,
+                                edit: params.edit,
+                            });
+                            return Ok(());
+                        }
+                        Err(e) => {
+                            tracing::warn!("Invalid workspace/applyEdit params: {}", e);
+                            let result = lsp_types::ApplyWorkspaceEditResponse {
+                                applied: false,
+                                failure_reason: Some(format!("Invalid params: {}", e)),
+                                failed_change: None,
+                            };
+                            JsonRpcResponse {
+                                jsonrpc: "2.0".to_string(),
+                                id: request.id,
+                                result: serde_json::to_value(result).ok(),
+                                error: None,
+                            }
+                        }
+                    }
+                }
+                "workspace/codeLens/refresh" => {
+                    tracing::trace!(
+                        "Acknowledging workspace/codeLens/refresh (id={})",
+                        request.id
+                    );
+                    let _ = async_tx.send(AsyncMessage::LspCodeLensRefresh {
+                        language: language.to_string(),
+                    });
+                    JsonRpcResponse {
+                        jsonrpc: "2.0".to_string(),
+                        id: request.id,
+                        result: Some(Value::Null),
+                        error: None,
+                    }
+                }
                 "client/registerCapability" => {
-                    // Server wants to register a capability dynamically - acknowledge
+                    // File watchers are honored, other registrations are only acknowledged
                     tracing::trace!(
                         "Acknowledging client/registerCapability (id={})",
                         request.id
                     );
+                    let registrations = request
+                        .params
+                        .clone()
+                        .and_then(|p| {
+                            serde_json::from_value::<lsp_types::RegistrationParams>(p).ok()
+                        })
+                        .map(|p| p.registrations)
+                        .unwrap_or_default();
+                    for registration in registrations {
+                        if registration.method
+                            != lsp_types::notification::DidChangeWatchedFiles::METHOD
+                        {
+                            continue;
+                        }
+                        let Some(options) = registration.register_options.and_then(|o| {
+                            serde_json::from_value::<
+                                lsp_types::DidChangeWatchedFilesRegistrationOptions,
+                            >(o)
+                            .ok()
+                        }) else {
+                            continue;
+                        };
+                        let _ = async_tx.send(AsyncMessage::LspFileWatchersRegistered {
+                            language: language.to_string(),
+                            id: registration.id,
+                            watchers: options.watchers,
+                        });
+                    }
+                    JsonRpcResponse {
+                        jsonrpc: "2.0".to_string(),
+                        id: request.id,
+                        result: Some(Value::Null),
+                        error: None,
+                    }
+                }
+                "client/unregisterCapability" => {
+                    tracing::trace!(
+                        "Acknowledging client/unregisterCapability (id={})",
+                        request.id
+                    );
+                    let unregistrations = request
+                        .params
+                        .clone()
+                        .and_then(|p| {
+                            serde_json::from_value::<lsp_types::UnregistrationParams>(p).ok()
+                        })
+                        .map(|p| p.unregisterations)
+                        .unwrap_or_default();
+                    for unregistration in unregistrations {
+                        if unregistration.method
+                            == lsp_types::notification::DidChangeWatchedFiles::METHOD
+                        {
+                            let _ = async_tx.send(AsyncMessage::LspFileWatchersUnregistered {
+                                language: language.to_string(),
+                                id: unregistration.id,
+                            });
+                        }
+                    }
                     JsonRpcResponse {
                         jsonrpc: "2.0".to_string(),
                         id: request.id,
@@ -3172,6 +4817,13 @@ pub struct LspHandle {
 
     /// Runtime handle for blocking operations
     runtime: tokio::runtime::Handle,
+
+    /// Version of each document as of the last didOpen/didChange sent, by URI
+    ///
+    /// Counted when the notification is queued, like the task does when
+    /// sending it, so it is the version the server will have seen once it
+    /// answers anything queued later.
+    document_versions: Mutex<HashMap<String, i32>>,
 }
 
 impl LspHandle {
@@ -3244,6 +4896,7 @@ impl LspHandle {
             command_tx,
             state,
             runtime: runtime.clone(),
+            document_versions: Mutex::new(HashMap::new()),
         })
     }
 
@@ -3339,13 +4992,21 @@ impl LspHandle {
     /// Notify document opened
     pub fn did_open(&self, uri: Uri, text: String, language_id: String) -> Result<(), String> {
         // Send command to LspTask which will queue it if not initialized yet
+        let key = uri.as_str().to_string();
         self.command_tx
             .try_send(LspCommand::DidOpen {
                 uri,
                 text,
                 language_id,
             })
-            .map_err(|_| "Failed to send did_open command".to_string())
+            .map_err(|_| "Failed to send did_open command".to_string())?;
+        // The task skips didOpen for documents it already opened
+        self.document_versions
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert(0);
+        Ok(())
     }
 
     /// Notify document changed
@@ -3355,12 +5016,29 @@ impl LspHandle {
         content_changes: Vec<TextDocumentContentChangeEvent>,
     ) -> Result<(), String> {
         // Send command to LspTask which will queue it if not initialized yet
+        let key = uri.as_str().to_string();
         self.command_tx
             .try_send(LspCommand::DidChange {
                 uri,
                 content_changes,
             })
-            .map_err(|_| "Failed to send did_change command".to_string())
+            .map_err(|_| "Failed to send did_change command".to_string())?;
+        *self
+            .document_versions
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert(0) += 1;
+        Ok(())
+    }
+
+    /// Version of a document the server knows, if it was opened
+    pub fn document_version(&self, uri: &Uri) -> Option<i32> {
+        self.document_versions
+            .lock()
+            .unwrap()
+            .get(uri.as_str())
+            .copied()
     }
 
     /// Send didSave notification
@@ -3370,6 +5048,16 @@ impl LspHandle {
             .map_err(|_| "Failed to send did_save command".to_string())
     }
 
+    /// Send workspace/didChangeWatchedFiles notification
+    pub fn did_change_watched_files(
+        &self,
+        changes: Vec<lsp_types::FileEvent>,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::DidChangeWatchedFiles { changes })
+            .map_err(|_| "Failed to send did_change_watched_files command".to_string())
+    }
+
     /// Request completion at position
     pub fn completion(
         &self,
@@ -3547,6 +5235,190 @@ impl LspHandle {
             .map_err(|_| "Failed to send inlay_hints command".to_string())
     }
 
+    /// Request folding ranges for a document
+    pub fn folding_range(&self, request_id: u64, uri: Uri) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::FoldingRange { request_id, uri })
+            .map_err(|_| "Failed to send folding_range command".to_string())
+    }
+
+    /// Request the symbols defined in a document
+    pub fn document_symbol(&self, request_id: u64, uri: Uri) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::DocumentSymbol { request_id, uri })
+            .map_err(|_| "Failed to send document_symbol command".to_string())
+    }
+
+    /// Search the symbols of the whole workspace
+    pub fn workspace_symbol(&self, request_id: u64, query: String) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::WorkspaceSymbol { request_id, query })
+            .map_err(|_| "Failed to send workspace_symbol command".to_string())
+    }
+
+    /// Fill in the range of a workspace symbol (workspaceSymbol/resolve)
+    pub fn resolve_workspace_symbol(
+        &self,
+        request_id: u64,
+        symbol: lsp_types::WorkspaceSymbol,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::WorkspaceSymbolResolve { request_id, symbol })
+            .map_err(|_| "Failed to send workspace_symbol_resolve command".to_string())
+    }
+
+    /// Prepare a call or type hierarchy at a position
+    pub fn prepare_hierarchy(
+        &self,
+        request_id: u64,
+        direction: HierarchyDirection,
+        uri: Uri,
+        line: u32,
+        character: u32,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::PrepareHierarchy {
+                request_id,
+                direction,
+                uri,
+                line,
+                character,
+            })
+            .map_err(|_| "Failed to send prepare_hierarchy command".to_string())
+    }
+
+    /// List the children of a hierarchy item in the given direction
+    pub fn hierarchy_children(
+        &self,
+        request_id: u64,
+        direction: HierarchyDirection,
+        item: HierarchyItem,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::HierarchyChildren {
+                request_id,
+                direction,
+                item,
+            })
+            .map_err(|_| "Failed to send hierarchy_children command".to_string())
+    }
+
+    /// Request edits formatting the whole document
+    pub fn formatting(
+        &self,
+        request_id: u64,
+        uri: Uri,
+        options: FormattingOptions,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::Formatting {
+                request_id,
+                uri,
+                options,
+            })
+            .map_err(|_| "Failed to send formatting command".to_string())
+    }
+
+    /// Request edits formatting a range of the document
+    pub fn range_formatting(
+        &self,
+        request_id: u64,
+        uri: Uri,
+        range: Range,
+        options: FormattingOptions,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::RangeFormatting {
+                request_id,
+                uri,
+                range,
+                options,
+            })
+            .map_err(|_| "Failed to send range_formatting command".to_string())
+    }
+
+    /// Request edits after `ch` was typed at a position
+    pub fn on_type_formatting(
+        &self,
+        request_id: u64,
+        uri: Uri,
+        line: u32,
+        character: u32,
+        ch: String,
+        options: FormattingOptions,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::OnTypeFormatting {
+                request_id,
+                params: DocumentOnTypeFormattingParams {
+                    text_document_position: TextDocumentPositionParams {
+                        text_document: TextDocumentIdentifier { uri },
+                        position: Position { line, character },
+                    },
+                    ch,
+                    options,
+                },
+            })
+            .map_err(|_| "Failed to send on_type_formatting command".to_string())
+    }
+
+    /// Request semantic tokens for a document
+    ///
+    /// With `prefer_range` only `viewport` is requested if the server allows it.
+    pub fn semantic_tokens(
+        &self,
+        request_id: u64,
+        uri: Uri,
+        viewport: Range,
+        prefer_range: bool,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::SemanticTokens {
+                request_id,
+                uri,
+                viewport,
+                prefer_range,
+            })
+            .map_err(|_| "Failed to send semantic_tokens command".to_string())
+    }
+
+    /// Request the code lenses of a document
+    pub fn code_lens(&self, request_id: u64, uri: Uri) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::CodeLens { request_id, uri })
+            .map_err(|_| "Failed to send code_lens command".to_string())
+    }
+
+    /// Fill in the commands of unresolved code lenses
+    pub fn resolve_code_lenses(
+        &self,
+        request_id: u64,
+        uri: Uri,
+        lenses: Vec<CodeLens>,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::CodeLensResolve {
+                request_id,
+                uri,
+                lenses,
+            })
+            .map_err(|_| "Failed to send code_lens_resolve command".to_string())
+    }
+
+    /// Run a command of the server (workspace/executeCommand)
+    pub fn execute_command(
+        &self,
+        request_id: u64,
+        command: lsp_types::Command,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::ExecuteCommand {
+                request_id,
+                command,
+            })
+            .map_err(|_| "Failed to send execute_command command".to_string())
+    }
+
     /// Cancel a pending request by its editor request_id
     ///
     /// This sends a $/cancelRequest notification to the LSP server.
@@ -3588,6 +5460,13 @@ impl LspHandle {
         }
     }
 
+    /// Answer a request the server sent to the editor (e.g. workspace/applyEdit)
+    pub fn reply_to_server(&self, id: i64, result: Value) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::ServerReply { id, result })
+            .map_err(|_| "Failed to send server reply".to_string())
+    }
+
     /// Shutdown the server
     pub fn shutdown(&self) -> Result<(), String> {
         // Transition to Stopping state
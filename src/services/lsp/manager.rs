@@ -475,6 +475,39 @@ impl LspManager {
             .unwrap_or(false)
     }
 
+    /// Send a workspace/symbol request to every server that is ready
+    ///
+    /// Returns the language and editor request id of each request sent, so the
+    /// caller can match responses and cancel requests that become stale.
+    pub fn request_workspace_symbols(
+        &self,
+        query: &str,
+        next_request_id: &mut u64,
+    ) -> Vec<(String, u64)> {
+        let mut sent = Vec::new();
+        for (language, handle) in &self.handles {
+            if !handle.state().can_send_requests() {
+                continue;
+            }
+            let request_id = *next_request_id;
+            *next_request_id += 1;
+            match handle.workspace_symbol(request_id, query.to_string()) {
+                Ok(()) => sent.push((language.clone(), request_id)),
+                Err(e) => tracing::warn!("Workspace symbol request to {} failed: {}", language, e),
+            }
+        }
+        sent
+    }
+
+    /// Cancel requests previously returned by `request_workspace_symbols`
+    pub fn cancel_requests(&self, requests: &[(String, u64)]) {
+        for (language, request_id) in requests {
+            if let Some(handle) = self.handles.get(language) {
+                let _ = handle.cancel_request(*request_id);
+            }
+        }
+    }
+
     /// Shutdown a specific language server
     ///
     /// This marks the server as disabled, preventing auto-restart until the user
@@ -550,6 +583,32 @@ pub fn detect_language(
     None
 }
 
+/// Merge workspace symbols returned by several servers
+///
+/// Servers for related languages (e.g. TypeScript and JavaScript) can report the
+/// same symbol; only the first occurrence of a name at a location is kept.
+pub fn merge_workspace_symbols<'a>(
+    results: impl IntoIterator<Item = &'a [lsp_types::WorkspaceSymbol]>,
+) -> Vec<lsp_types::WorkspaceSymbol> {
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
+    for symbols in results {
+        for symbol in symbols {
+            let (uri, position) = match &symbol.location {
+                lsp_types::OneOf::Left(location) => (
+                    location.uri.as_str(),
+                    Some((location.range.start.line, location.range.start.character)),
+                ),
+                lsp_types::OneOf::Right(location) => (location.uri.as_str(), None),
+            };
+            if seen.insert((symbol.name.clone(), uri.to_string(), position)) {
+                merged.push(symbol.clone());
+            }
+        }
+    }
+    merged
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -658,6 +717,52 @@ mod tests {
         assert_eq!(manager.handles.len(), 0);
     }
 
+    #[test]
+    fn test_merge_workspace_symbols() {
+        fn symbol(name: &str, uri: &str, line: u32) -> lsp_types::WorkspaceSymbol {
+            lsp_types::WorkspaceSymbol {
+                name: name.to_string(),
+                kind: lsp_types::SymbolKind::FUNCTION,
+                tags: None,
+                container_name: None,
+                location: lsp_types::OneOf::Left(lsp_types::Location {
+                    uri: uri.parse().unwrap(),
+                    range: lsp_types::Range {
+                        start: lsp_types::Position { line, character: 0 },
+                        end: lsp_types::Position { line, character: 4 },
+                    },
+                }),
+                data: None,
+            }
+        }
+
+        let typescript = vec![
+            symbol("main", "file:///a.ts", 1),
+            symbol("helper", "file:///a.ts", 5),
+        ];
+        let javascript = vec![
+            symbol("main", "file:///a.ts", 1),
+            symbol("main", "file:///b.js", 1),
+        ];
+
+        let merged = merge_workspace_symbols([typescript.as_slice(), javascript.as_slice()]);
+        let names: Vec<_> = merged
+            .iter()
+            .map(|s| match &s.location {
+                lsp_types::OneOf::Left(l) => format!("{}@{}", s.name, l.uri.as_str()),
+                lsp_types::OneOf::Right(l) => format!("{}@{}", s.name, l.uri.as_str()),
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "main@file:///a.ts",
+                "helper@file:///a.ts",
+                "main@file:///b.js"
+            ]
+        );
+    }
+
     fn test_languages() -> std::collections::HashMap<String, crate::config::LanguageConfig> {
         let mut languages = std::collections::HashMap::new();
         languages.insert(
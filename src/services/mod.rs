@@ -5,12 +5,17 @@
 
 pub mod async_bridge;
 pub mod clipboard;
+pub mod file_watcher;
 pub mod fs;
+pub mod git;
 #[cfg(target_os = "linux")]
 pub mod gpm;
+#[cfg(unix)]
+pub mod instance_server;
 pub mod log_dirs;
 pub mod lsp;
 pub mod plugins;
+pub mod privileged_save;
 pub mod process_limits;
 pub mod recovery;
 pub mod release_checker;
@@ -19,4 +24,5 @@ pub mod styled_html;
 pub mod terminal;
 pub mod time_source;
 pub mod tracing_setup;
+pub mod undo_history;
 pub mod warning_log;
@@ -3,10 +3,12 @@
 //! This module provides a safe, controlled API for plugins (Lua, WASM, etc.)
 //! to interact with the editor without direct access to internal state.
 
+use crate::config::PluginPermissions;
 use crate::input::command_registry::CommandRegistry;
 use crate::input::commands::Command;
 use crate::model::event::{BufferId, SplitId};
 use crate::services::plugins::hooks::{HookCallback, HookRegistry};
+use crate::services::plugins::permissions::Capability;
 use crate::view::overlay::{OverlayHandle, OverlayNamespace};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -44,6 +46,8 @@ pub enum PluginResponse {
         request_id: u64,
         buffer_id: BufferId,
     },
+    /// Response to RequestPluginPermission with the user's answer
+    PluginPermission { request_id: u64, granted: bool },
 }
 
 /// Information about a cursor in the editor
@@ -278,6 +282,10 @@ pub struct EditorStateSnapshot {
     /// Global editor mode for modal editing (e.g., "vi-normal", "vi-insert")
     /// When set, this mode's keybindings take precedence over normal key handling
     pub editor_mode: Option<String>,
+    /// What plugins were allowed to do, from the user config
+    pub plugin_permissions: HashMap<String, PluginPermissions>,
+    /// Editor's config directory (for the "${config}" scope of plugin manifests)
+    pub config_dir: PathBuf,
 }
 
 impl EditorStateSnapshot {
@@ -299,6 +307,8 @@ impl EditorStateSnapshot {
             config: serde_json::Value::Null,
             user_config: serde_json::Value::Null,
             editor_mode: None,
+            plugin_permissions: HashMap::new(),
+            config_dir: PathBuf::new(),
         }
     }
 }
@@ -354,6 +364,9 @@ pub enum PluginCommand {
     /// Set status message
     SetStatus { message: String },
 
+    /// Set the text of a named status bar segment (empty text removes it)
+    SetStatusSegment { name: String, text: String },
+
     /// Apply a theme by name
     ApplyTheme { theme_name: String },
 
@@ -845,6 +858,20 @@ pub enum PluginCommand {
         /// The group ID returned by CreateScrollSyncGroup
         group_id: u32,
     },
+
+    /// Ask the user to allow an entry of a plugin's manifest
+    RequestPluginPermission {
+        /// Name of the plugin
+        plugin_name: String,
+        /// What the plugin wants to do
+        capability: Capability,
+        /// The manifest entry to allow (e.g. "${workspace}" or "git")
+        entry: String,
+        /// The entry as shown to the user (e.g. with paths expanded)
+        description: String,
+        /// Request ID for async response
+        request_id: u64,
+    },
 }
 
 /// Hunk status for Review Diff
@@ -1031,6 +1058,11 @@ impl PluginApi {
         self.send_command(PluginCommand::SetStatus { message })
     }
 
+    /// Set the text of a named status bar segment
+    pub fn set_status_segment(&self, name: String, text: String) -> Result<(), String> {
+        self.send_command(PluginCommand::SetStatusSegment { name, text })
+    }
+
     /// Open a file at a specific line and column (1-indexed)
     /// This is useful for jumping to locations from git grep, LSP definitions, etc.
     pub fn open_file_at_location(
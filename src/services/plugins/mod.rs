@@ -10,6 +10,7 @@ pub mod api;
 pub mod event_hooks;
 pub mod hooks;
 pub mod manager;
+pub mod permissions;
 
 #[cfg(feature = "plugins")]
 pub mod process;
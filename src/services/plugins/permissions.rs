@@ -0,0 +1,393 @@
+//! Plugin capabilities
+//!
+//! Plugins can only read and write files, run processes and read environment
+//! variables that they declare in a manifest next to the plugin file
+//! (`<plugin>.manifest.json`, like the `.i18n.json` translations):
+//!
+//! ```json
+//! {
+//!   "fs_read": ["${workspace}"],
+//!   "fs_write": ["${config}/themes"],
+//!   "process": ["git"],
+//!   "env": ["HOME"]
+//! }
+//! ```
+//!
+//! A declared entry is only used once the user allows it. The first time a
+//! plugin needs an entry the editor asks, and the answer is stored in the
+//! `plugin_permissions` config. Entries marked `trusted` there can do
+//! everything without a manifest.
+
+use crate::config::PluginPermissions;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Name under `plugin_permissions` whose settings apply to every plugin
+pub const ALL_PLUGINS: &str = "*";
+
+/// Something a plugin has to be allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Read files and list directories
+    FsRead,
+    /// Write files
+    FsWrite,
+    /// Run processes
+    Process,
+    /// Read environment variables
+    Env,
+}
+
+impl Capability {
+    /// Entries of the manifest that declare this capability
+    pub fn declared(self, manifest: &PluginManifest) -> &[String] {
+        match self {
+            Self::FsRead => &manifest.fs_read,
+            Self::FsWrite => &manifest.fs_write,
+            Self::Process => &manifest.process,
+            Self::Env => &manifest.env,
+        }
+    }
+
+    /// Entries the user allowed for this capability
+    pub fn granted(self, permissions: &PluginPermissions) -> &[String] {
+        match self {
+            Self::FsRead => &permissions.fs_read,
+            Self::FsWrite => &permissions.fs_write,
+            Self::Process => &permissions.process,
+            Self::Env => &permissions.env,
+        }
+    }
+
+    /// Allow an entry of this capability
+    pub fn grant(self, permissions: &mut PluginPermissions, entry: &str) {
+        let granted = match self {
+            Self::FsRead => &mut permissions.fs_read,
+            Self::FsWrite => &mut permissions.fs_write,
+            Self::Process => &mut permissions.process,
+            Self::Env => &mut permissions.env,
+        };
+        if !granted.iter().any(|e| e == entry) {
+            granted.push(entry.to_string());
+        }
+    }
+
+    fn is_path(self) -> bool {
+        matches!(self, Self::FsRead | Self::FsWrite)
+    }
+}
+
+/// Capabilities a plugin declares in its manifest
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PluginManifest {
+    /// Directories the plugin reads, e.g. "${workspace}"
+    pub fs_read: Vec<String>,
+    /// Directories the plugin writes
+    pub fs_write: Vec<String>,
+    /// Programs the plugin runs, as passed to spawnProcess ("*" for any)
+    pub process: Vec<String>,
+    /// Environment variables the plugin reads ("*" for any)
+    pub env: Vec<String>,
+}
+
+impl PluginManifest {
+    /// Load the manifest next to a plugin file, if there is one
+    pub fn load_for(plugin_path: &Path) -> Result<Option<Self>, String> {
+        let path = plugin_path.with_extension("manifest.json");
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content =
+            std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Directories that the `${...}` variables of path entries stand for
+#[derive(Debug, Clone)]
+pub struct ScopeDirs {
+    /// `${workspace}`: the editor's working directory
+    pub workspace: PathBuf,
+    /// `${config}`: the editor's config directory
+    pub config: PathBuf,
+    /// `${plugin}`: the directory the plugin was loaded from
+    pub plugin: PathBuf,
+}
+
+impl ScopeDirs {
+    /// Turn a path entry into the directory it allows
+    fn expand(&self, entry: &str) -> PathBuf {
+        let home = || dirs::home_dir().unwrap_or_default();
+        let expanded = if let Some(rest) = entry.strip_prefix("~/") {
+            home().join(rest)
+        } else {
+            let expanded = entry
+                .replace("${workspace}", &self.workspace.to_string_lossy())
+                .replace("${config}", &self.config.to_string_lossy())
+                .replace("${plugin}", &self.plugin.to_string_lossy())
+                .replace("${home}", &home().to_string_lossy());
+            self.workspace.join(expanded)
+        };
+        resolve_path(&expanded)
+    }
+
+    /// How an entry is shown when asking the user
+    pub fn describe(&self, capability: Capability, entry: &str) -> String {
+        if capability.is_path() && entry != "*" {
+            self.expand(entry).to_string_lossy().to_string()
+        } else {
+            entry.to_string()
+        }
+    }
+}
+
+/// Whether a plugin may use a capability
+#[derive(Debug, Clone, PartialEq)]
+pub enum Access {
+    /// The plugin was allowed to
+    Granted,
+    /// The manifest declares it, but the user hasn't allowed the entry yet
+    Ask { entry: String },
+    /// The manifest doesn't declare it
+    Undeclared,
+}
+
+/// Check whether a plugin may use a capability on a target (a path, a
+/// program or an environment variable name)
+pub fn check(
+    plugin: &str,
+    manifest: Option<&PluginManifest>,
+    permissions: &HashMap<String, PluginPermissions>,
+    capability: Capability,
+    target: &str,
+    dirs: &ScopeDirs,
+) -> Access {
+    let own = permissions.get(plugin);
+    if own
+        .into_iter()
+        .chain(permissions.get(ALL_PLUGINS))
+        .any(|p| p.trusted)
+    {
+        return Access::Granted;
+    }
+
+    let Some(manifest) = manifest else {
+        return Access::Undeclared;
+    };
+    let target_path = capability
+        .is_path()
+        .then(|| resolve_path(Path::new(target)));
+    let matching: Vec<&String> = capability
+        .declared(manifest)
+        .iter()
+        .filter(|entry| {
+            entry.as_str() == "*"
+                || match &target_path {
+                    Some(path) => path.starts_with(dirs.expand(entry)),
+                    None => entry.as_str() == target,
+                }
+        })
+        .collect();
+
+    let granted = own.map(|p| capability.granted(p)).unwrap_or_default();
+    if matching.iter().any(|entry| granted.contains(entry)) {
+        Access::Granted
+    } else if let Some(entry) = matching.first() {
+        Access::Ask {
+            entry: entry.to_string(),
+        }
+    } else {
+        Access::Undeclared
+    }
+}
+
+/// Make a path absolute and remove `.` and `..` from it, following symlinks
+/// as far as the path exists
+///
+/// Without this "${workspace}/../secret" would pass for a workspace path.
+pub fn resolve_path(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+
+    // Follow symlinks of the part that exists, so a link can't lead out of a scope
+    let mut existing = normalized.as_path();
+    let mut rest = Vec::new();
+    while let Some(parent) = existing.parent() {
+        if existing.exists() {
+            break;
+        }
+        rest.push(existing.file_name().unwrap_or_default().to_owned());
+        existing = parent;
+    }
+    match existing.canonicalize() {
+        Ok(mut resolved) => {
+            resolved.extend(rest.iter().rev());
+            resolved
+        }
+        Err(_) => normalized,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dirs(root: &Path) -> ScopeDirs {
+        ScopeDirs {
+            workspace: root.join("project"),
+            config: root.join("config"),
+            plugin: root.join("plugins"),
+        }
+    }
+
+    fn manifest() -> PluginManifest {
+        serde_json::from_str(
+            r#"{
+                "fs_read": ["${workspace}"],
+                "fs_write": ["${config}/themes"],
+                "process": ["git"],
+                "env": ["HOME"]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check_manifest_and_grants() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("project/src")).unwrap();
+        let dirs = dirs(&root);
+        let manifest = manifest();
+        let mut permissions = HashMap::new();
+        let file = root.join("project/src/main.rs");
+        let file = file.to_str().unwrap();
+        let check = |permissions: &HashMap<_, _>, capability, target: &str| {
+            check(
+                "demo",
+                Some(&manifest),
+                permissions,
+                capability,
+                target,
+                &dirs,
+            )
+        };
+
+        let ask = |entry: &str| Access::Ask {
+            entry: entry.to_string(),
+        };
+        assert_eq!(
+            check(&permissions, Capability::FsRead, file),
+            ask("${workspace}")
+        );
+        assert_eq!(check(&permissions, Capability::Process, "git"), ask("git"));
+        assert_eq!(check(&permissions, Capability::Env, "HOME"), ask("HOME"));
+        assert_eq!(
+            check(&permissions, Capability::FsWrite, file),
+            Access::Undeclared
+        );
+        assert_eq!(
+            check(&permissions, Capability::Process, "sh"),
+            Access::Undeclared
+        );
+        assert_eq!(
+            check(&permissions, Capability::Process, "/tmp/git"),
+            Access::Undeclared
+        );
+
+        // Going up out of the scope doesn't match it
+        let outside = root.join("project/../config/secret");
+        assert_eq!(
+            check(&permissions, Capability::FsRead, outside.to_str().unwrap()),
+            Access::Undeclared
+        );
+        let theme = root.join("config/themes/new.json");
+        assert_eq!(
+            check(&permissions, Capability::FsWrite, theme.to_str().unwrap()),
+            ask("${config}/themes")
+        );
+
+        let mut grants = PluginPermissions::default();
+        Capability::FsRead.grant(&mut grants, "${workspace}");
+        Capability::Process.grant(&mut grants, "git");
+        permissions.insert("demo".to_string(), grants);
+        assert_eq!(
+            check(&permissions, Capability::FsRead, file),
+            Access::Granted
+        );
+        assert_eq!(
+            check(&permissions, Capability::Process, "git"),
+            Access::Granted
+        );
+        assert_eq!(check(&permissions, Capability::Env, "HOME"), ask("HOME"));
+
+        // Another plugin's grants don't count
+        assert_eq!(
+            super::check(
+                "other",
+                Some(&manifest),
+                &permissions,
+                Capability::Process,
+                "git",
+                &dirs
+            ),
+            ask("git")
+        );
+        assert_eq!(
+            super::check(
+                "other",
+                None,
+                &permissions,
+                Capability::Process,
+                "git",
+                &dirs
+            ),
+            Access::Undeclared
+        );
+
+        // Trusted plugins need no manifest
+        permissions.insert(
+            ALL_PLUGINS.to_string(),
+            PluginPermissions {
+                trusted: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            super::check(
+                "other",
+                None,
+                &permissions,
+                Capability::Process,
+                "sh",
+                &dirs
+            ),
+            Access::Granted
+        );
+    }
+
+    #[test]
+    fn test_resolve_path_removes_parent_components() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("a")).unwrap();
+        assert_eq!(
+            resolve_path(&root.join("a/./b/../../c/new.txt")),
+            root.join("c/new.txt")
+        );
+    }
+}
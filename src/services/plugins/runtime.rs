@@ -43,8 +43,10 @@ use crate::input::commands::Suggestion;
 use crate::model::event::BufferId;
 use crate::model::event::SplitId;
 use crate::services::plugins::api::{
-    ActionPopupAction, ActionSpec, EditorStateSnapshot, LayoutHints, PluginCommand, ViewTokenWire,
+    ActionPopupAction, ActionSpec, EditorStateSnapshot, LayoutHints, PluginCommand, PluginResponse,
+    ViewTokenWire,
 };
+use crate::services::plugins::permissions::{self, Access, Capability, PluginManifest, ScopeDirs};
 use anyhow::{anyhow, Result};
 use deno_core::{
     error::ModuleLoaderError, extension, op2, FastString, JsRuntime, ModuleLoadOptions,
@@ -53,7 +55,7 @@ use deno_core::{
 };
 use deno_error::JsErrorBox;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
@@ -145,6 +147,18 @@ struct CancellableProcess {
     output_rx: tokio::sync::oneshot::Receiver<(String, String)>,
 }
 
+/// A loaded plugin, as known to the ops that check capabilities
+struct LoadedPlugin {
+    /// Plugin name
+    name: String,
+    /// Capabilities declared in the plugin's manifest
+    manifest: Option<PluginManifest>,
+    /// Directory the plugin was loaded from
+    dir: std::path::PathBuf,
+    /// Files the plugin opened or edited, which it needs "fs_write" to save
+    touched: HashSet<std::path::PathBuf>,
+}
+
 /// Shared state accessible from ops
 struct TsRuntimeState {
     /// Editor state snapshot (read-only access)
@@ -172,6 +186,264 @@ struct TsRuntimeState {
     process_pids: Rc<RefCell<HashMap<u64, u32>>>,
     /// Next process ID for background processes
     next_process_id: Rc<RefCell<u64>>,
+    /// Loaded plugins by the token their editor object passes to ops that
+    /// need capabilities, so a plugin can't act as another one
+    plugin_tokens: Rc<RefCell<HashMap<String, LoadedPlugin>>>,
+}
+
+impl TsRuntimeState {
+    /// Check that the plugin owning `token` may use a capability on a target
+    ///
+    /// If the user has to be asked, a permission request is sent to the
+    /// editor and the receiver of the answer is returned.
+    fn check_capability(
+        &self,
+        token: &str,
+        capability: Capability,
+        target: &str,
+    ) -> Result<Option<tokio::sync::oneshot::Receiver<PluginResponse>>, JsErrorBox> {
+        let plugins = self.plugin_tokens.borrow();
+        let plugin = plugins.get(token).ok_or_else(|| {
+            JsErrorBox::generic("Capabilities are only available to loaded plugins")
+        })?;
+        let snapshot = self
+            .state_snapshot
+            .read()
+            .map_err(|_| JsErrorBox::generic("Editor state not available"))?;
+        let dirs = ScopeDirs {
+            workspace: snapshot.working_dir.clone(),
+            config: snapshot.config_dir.clone(),
+            plugin: plugin.dir.clone(),
+        };
+
+        let entry = match permissions::check(
+            &plugin.name,
+            plugin.manifest.as_ref(),
+            &snapshot.plugin_permissions,
+            capability,
+            target,
+            &dirs,
+        ) {
+            Access::Granted => return Ok(None),
+            Access::Undeclared => {
+                return Err(capability_error(&plugin.name, capability, target, true))
+            }
+            Access::Ask { entry } => entry,
+        };
+
+        let request_id = {
+            let mut id = self.next_request_id.borrow_mut();
+            let current = *id;
+            *id += 1;
+            current
+        };
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.pending_responses
+            .lock()
+            .unwrap()
+            .insert(request_id, tx);
+        let request = PluginCommand::RequestPluginPermission {
+            plugin_name: plugin.name.clone(),
+            capability,
+            description: dirs.describe(capability, &entry),
+            entry,
+            request_id,
+        };
+        if self.command_sender.send(request).is_err() {
+            self.pending_responses.lock().unwrap().remove(&request_id);
+            return Err(capability_error(&plugin.name, capability, target, false));
+        }
+        Ok(Some(rx))
+    }
+
+    /// Remember that the plugin owning `token` opened or edited a file
+    fn touch(&self, token: &str, path: &std::path::Path) {
+        if let Some(plugin) = self.plugin_tokens.borrow_mut().get_mut(token) {
+            plugin.touched.insert(permissions::resolve_path(path));
+        }
+    }
+
+    /// Remember that the plugin owning `token` edited a buffer
+    fn touch_buffer(&self, token: &str, buffer_id: BufferId) {
+        let path = self.state_snapshot.read().ok().and_then(|snapshot| {
+            snapshot
+                .buffers
+                .get(&buffer_id)
+                .and_then(|info| info.path.clone())
+        });
+        if let Some(path) = path {
+            self.touch(token, &path);
+        }
+    }
+
+    /// Name of the plugin owning `token`
+    fn plugin_name(&self, token: &str) -> String {
+        self.plugin_tokens
+            .borrow()
+            .get(token)
+            .map(|plugin| plugin.name.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Error thrown to a plugin that may not use a capability
+fn capability_error(
+    plugin: &str,
+    capability: Capability,
+    target: &str,
+    undeclared: bool,
+) -> JsErrorBox {
+    let action = match capability {
+        Capability::FsRead => "read",
+        Capability::FsWrite => "write",
+        Capability::Process => "run",
+        Capability::Env => "read the environment variable",
+    };
+    let reason = if undeclared {
+        format!(" (not declared in {}.manifest.json)", plugin)
+    } else {
+        String::new()
+    };
+    JsErrorBox::generic(format!(
+        "Plugin '{}' is not allowed to {} {}{}",
+        plugin, action, target, reason
+    ))
+}
+
+/// Check that the plugin owning `token` may use a capability on a target,
+/// waiting for the user's answer the first time it needs it
+async fn require_capability(
+    state: &Rc<RefCell<OpState>>,
+    token: &str,
+    capability: Capability,
+    target: &str,
+) -> Result<(), JsErrorBox> {
+    let (receiver, plugin) = {
+        let state = state.borrow();
+        let runtime_state = state
+            .try_borrow::<Rc<RefCell<TsRuntimeState>>>()
+            .ok_or_else(|| JsErrorBox::generic("Runtime state not available"))?;
+        let runtime_state = runtime_state.borrow();
+        (
+            runtime_state.check_capability(token, capability, target)?,
+            runtime_state.plugin_name(token),
+        )
+    };
+    let Some(receiver) = receiver else {
+        return Ok(());
+    };
+    match receiver.await {
+        Ok(PluginResponse::PluginPermission { granted: true, .. }) => Ok(()),
+        _ => Err(capability_error(&plugin, capability, target, false)),
+    }
+}
+
+/// Check a capability in a synchronous op, which can't wait for the user
+///
+/// The first time, the user is asked and the op fails; once allowed, later
+/// calls succeed.
+fn require_capability_now(
+    state: &mut OpState,
+    token: &str,
+    capability: Capability,
+    target: &str,
+) -> Result<(), JsErrorBox> {
+    let runtime_state = state
+        .try_borrow::<Rc<RefCell<TsRuntimeState>>>()
+        .ok_or_else(|| JsErrorBox::generic("Runtime state not available"))?;
+    let runtime_state = runtime_state.borrow();
+    match runtime_state.check_capability(token, capability, target)? {
+        None => Ok(()),
+        Some(_) => Err(capability_error(
+            &runtime_state.plugin_name(token),
+            capability,
+            target,
+            false,
+        )),
+    }
+}
+
+/// Resolve a path a plugin passed against the editor's working directory,
+/// the way the editor itself resolves it
+fn working_dir_path(state: &OpState, path: &str) -> std::path::PathBuf {
+    if std::path::Path::new(path).is_absolute() {
+        return std::path::PathBuf::from(path);
+    }
+    let working_dir = state
+        .try_borrow::<Rc<RefCell<TsRuntimeState>>>()
+        .and_then(|runtime_state| {
+            let runtime_state = runtime_state.borrow();
+            runtime_state
+                .state_snapshot
+                .read()
+                .ok()
+                .map(|snapshot| snapshot.working_dir.clone())
+        });
+    match working_dir {
+        Some(wd) => wd.join(path),
+        None => std::path::PathBuf::from(path),
+    }
+}
+
+/// Check that the plugin owning `token` may run an action
+///
+/// Save-type actions write the files of buffers: saving a file the plugin
+/// opened or edited needs "fs_write" for it, and is refused outright for
+/// files in the config directory.
+fn require_action_allowed(
+    state: &mut OpState,
+    token: &str,
+    action: &str,
+) -> Result<(), JsErrorBox> {
+    let (saved, config_dir, plugin) = {
+        let runtime_state = state
+            .try_borrow::<Rc<RefCell<TsRuntimeState>>>()
+            .ok_or_else(|| JsErrorBox::generic("Runtime state not available"))?;
+        let runtime_state = runtime_state.borrow();
+        let snapshot = runtime_state
+            .state_snapshot
+            .read()
+            .map_err(|_| JsErrorBox::generic("Editor state not available"))?;
+        let saved: Vec<std::path::PathBuf> = match action {
+            "save" | "save_as_root" => snapshot
+                .buffers
+                .get(&snapshot.active_buffer_id)
+                .and_then(|info| info.path.clone())
+                .into_iter()
+                .collect(),
+            "save_all" => snapshot
+                .buffers
+                .values()
+                .filter(|info| info.modified)
+                .filter_map(|info| info.path.clone())
+                .collect(),
+            _ => return Ok(()),
+        };
+        let plugins = runtime_state.plugin_tokens.borrow();
+        let plugin = plugins.get(token).ok_or_else(|| {
+            JsErrorBox::generic("Saving files is only available to loaded plugins")
+        })?;
+        let saved: Vec<_> = saved
+            .iter()
+            .map(|path| permissions::resolve_path(path))
+            .filter(|path| plugin.touched.contains(path))
+            .collect();
+        let config_dir = (!snapshot.config_dir.as_os_str().is_empty())
+            .then(|| permissions::resolve_path(&snapshot.config_dir));
+        (saved, config_dir, plugin.name.clone())
+    };
+
+    for path in saved {
+        let target = path.to_string_lossy();
+        if config_dir.as_ref().is_some_and(|dir| path.starts_with(dir)) {
+            return Err(JsErrorBox::generic(format!(
+                "Plugin '{}' is not allowed to write {} (files in the config directory can't be saved by plugins)",
+                plugin, target
+            )));
+        }
+        require_capability_now(state, token, Capability::FsWrite, &target)?;
+    }
+    Ok(())
 }
 
 /// Display a transient message in the editor's status bar
@@ -190,6 +462,27 @@ fn op_fresh_set_status(state: &mut OpState, #[string] message: String) {
     tracing::info!("TypeScript plugin set_status: {}", message);
 }
 
+/// Set the text of a named segment of the status bar
+///
+/// Unlike the status message, a segment stays until the plugin changes it.
+/// Segments are shown where the `status_bar` config places them by name,
+/// or else by its "plugins" segment. Empty text removes the segment.
+/// @param name - Name of the segment, as used in the `status_bar` config
+/// @param text - Text to display; keep short (status bar has limited width)
+#[op2(fast)]
+fn op_fresh_set_status_segment(
+    state: &mut OpState,
+    #[string] name: String,
+    #[string] text: String,
+) {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let _ = runtime_state
+            .command_sender
+            .send(PluginCommand::SetStatusSegment { name, text });
+    }
+}
+
 /// Apply a theme by name
 ///
 /// Loads and applies the specified theme immediately. The theme can be a built-in
@@ -419,12 +712,14 @@ fn op_fresh_is_buffer_modified(state: &mut OpState, buffer_id: u32) -> bool {
 #[op2(fast)]
 fn op_fresh_insert_text(
     state: &mut OpState,
+    #[string] token: String,
     buffer_id: u32,
     position: u32,
     #[string] text: String,
 ) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
+        runtime_state.touch_buffer(&token, BufferId(buffer_id as usize));
         let result = runtime_state
             .command_sender
             .send(PluginCommand::InsertText {
@@ -446,9 +741,16 @@ fn op_fresh_insert_text(
 /// @param start - Start byte offset (inclusive)
 /// @param end - End byte offset (exclusive)
 #[op2(fast)]
-fn op_fresh_delete_range(state: &mut OpState, buffer_id: u32, start: u32, end: u32) -> bool {
+fn op_fresh_delete_range(
+    state: &mut OpState,
+    #[string] token: String,
+    buffer_id: u32,
+    start: u32,
+    end: u32,
+) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
+        runtime_state.touch_buffer(&token, BufferId(buffer_id as usize));
         let result = runtime_state
             .command_sender
             .send(PluginCommand::DeleteRange {
@@ -979,9 +1281,20 @@ fn op_fresh_clear_view_transform(
 /// @param text - The text to insert
 /// @returns true if insertion succeeded
 #[op2(fast)]
-fn op_fresh_insert_at_cursor(state: &mut OpState, #[string] text: String) -> bool {
+fn op_fresh_insert_at_cursor(
+    state: &mut OpState,
+    #[string] token: String,
+    #[string] text: String,
+) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
+        let active_buffer_id = runtime_state
+            .state_snapshot
+            .read()
+            .map(|snapshot| snapshot.active_buffer_id);
+        if let Ok(buffer_id) = active_buffer_id {
+            runtime_state.touch_buffer(&token, buffer_id);
+        }
         let result = runtime_state
             .command_sender
             .send(PluginCommand::InsertAtCursor { text });
@@ -1087,9 +1400,10 @@ fn op_fresh_unregister_command(state: &mut OpState, #[string] name: String) -> b
     false
 }
 
-/// Set or unset a custom context for command visibility
+/// Set or unset a custom context for command visibility and keybindings
 /// Custom contexts allow plugins to control when their commands are available.
 /// For example, setting "config-editor" context makes config editor commands visible.
+/// Keybinding `when` clauses can check the context by name (e.g. "config-editor && !readOnly").
 /// @param name - Context name (e.g., "config-editor")
 /// @param active - Whether the context is active (true = set, false = unset)
 /// @returns true if the context was updated
@@ -1106,18 +1420,30 @@ fn op_fresh_set_context(state: &mut OpState, #[string] name: String, active: boo
 }
 
 /// Open a file in the editor, optionally at a specific location
-/// @param path - File path to open
+///
+/// The path must be inside a directory of the plugin's manifest "fs_read"
+/// list. The first call asks the user and throws; once allowed, calls succeed.
+/// @param path - File path to open (absolute or relative to cwd)
 /// @param line - Line number to jump to (0 for no jump)
 /// @param column - Column number to jump to (0 for no jump)
 /// @returns true if file was opened
 #[op2(fast)]
-fn op_fresh_open_file(state: &mut OpState, #[string] path: String, line: u32, column: u32) -> bool {
+fn op_fresh_open_file(
+    state: &mut OpState,
+    #[string] token: String,
+    #[string] path: String,
+    line: u32,
+    column: u32,
+) -> Result<bool, JsErrorBox> {
+    let path = working_dir_path(state, &path);
+    require_capability_now(state, &token, Capability::FsRead, &path.to_string_lossy())?;
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
+        runtime_state.touch(&token, &path);
         let result = runtime_state
             .command_sender
             .send(PluginCommand::OpenFileAtLocation {
-                path: std::path::PathBuf::from(path),
+                path,
                 line: if line == 0 { None } else { Some(line as usize) },
                 column: if column == 0 {
                     None
@@ -1125,9 +1451,9 @@ fn op_fresh_open_file(state: &mut OpState, #[string] path: String, line: u32, co
                     Some(column as usize)
                 },
             });
-        return result.is_ok();
+        return Ok(result.is_ok());
     }
-    false
+    Ok(false)
 }
 
 /// Get the ID of the focused split pane
@@ -1187,26 +1513,32 @@ fn op_fresh_get_all_cursor_positions(state: &mut OpState) -> Vec<u32> {
 }
 
 /// Open a file in a specific split pane
+///
+/// Needs "fs_read" for the path, like openFile.
 /// @param split_id - The split ID to open the file in
-/// @param path - File path to open
+/// @param path - File path to open (absolute or relative to cwd)
 /// @param line - Line number to jump to (0 for no jump)
 /// @param column - Column number to jump to (0 for no jump)
 /// @returns true if file was opened
 #[op2(fast)]
 fn op_fresh_open_file_in_split(
     state: &mut OpState,
+    #[string] token: String,
     split_id: u32,
     #[string] path: String,
     line: u32,
     column: u32,
-) -> bool {
+) -> Result<bool, JsErrorBox> {
+    let path = working_dir_path(state, &path);
+    require_capability_now(state, &token, Capability::FsRead, &path.to_string_lossy())?;
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
+        runtime_state.touch(&token, &path);
         let result = runtime_state
             .command_sender
             .send(PluginCommand::OpenFileInSplit {
                 split_id: split_id as usize,
-                path: std::path::PathBuf::from(path),
+                path,
                 line: if line == 0 { None } else { Some(line as usize) },
                 column: if column == 0 {
                     None
@@ -1214,9 +1546,9 @@ fn op_fresh_open_file_in_split(
                     Some(column as usize)
                 },
             });
-        return result.is_ok();
+        return Ok(result.is_ok());
     }
-    false
+    Ok(false)
 }
 
 /// Result from spawnProcess
@@ -1243,6 +1575,7 @@ struct BackgroundProcessResult {
 /// in the background and returns immediately with a process ID.
 /// Use killProcess(id) to terminate the process later.
 /// Use isProcessRunning(id) to check if it's still running.
+/// The command must be declared in the plugin's manifest "process" list.
 ///
 /// @param command - Program name (searched in PATH) or absolute path
 /// @param args - Command arguments (each array element is one argument)
@@ -1256,6 +1589,7 @@ struct BackgroundProcessResult {
 #[serde]
 async fn op_fresh_spawn_background_process(
     state: Rc<RefCell<OpState>>,
+    #[string] token: String,
     #[string] command: String,
     #[serde] args: Vec<String>,
     #[string] cwd: Option<String>,
@@ -1263,6 +1597,8 @@ async fn op_fresh_spawn_background_process(
     use std::process::Stdio;
     use tokio::process::Command;
 
+    require_capability(&state, &token, Capability::Process, &command).await?;
+
     // Build the command
     let mut cmd = Command::new(&command);
     cmd.args(&args);
@@ -1409,6 +1745,7 @@ fn op_fresh_is_process_running(state: &mut OpState, #[bigint] process_id: u64) -
 /// Unlike spawnProcess which waits for completion, this starts output collection
 /// in the background and returns immediately with a process ID.
 /// Use spawnProcessWait(id) to get the result, or killProcess(id) to cancel.
+/// The command must be declared in the plugin's manifest "process" list.
 ///
 /// @param command - Program name (searched in PATH) or absolute path
 /// @param args - Command arguments (each array element is one argument)
@@ -1418,6 +1755,7 @@ fn op_fresh_is_process_running(state: &mut OpState, #[bigint] process_id: u64) -
 #[bigint]
 async fn op_fresh_spawn_process_start(
     state: Rc<RefCell<OpState>>,
+    #[string] token: String,
     #[string] command: String,
     #[serde] args: Vec<String>,
     #[string] cwd: Option<String>,
@@ -1426,6 +1764,8 @@ async fn op_fresh_spawn_process_start(
     use tokio::io::{AsyncBufReadExt, BufReader};
     use tokio::process::Command;
 
+    require_capability(&state, &token, Capability::Process, &command).await?;
+
     let spawn_start = std::time::Instant::now();
     tracing::trace!(
         command = %command,
@@ -2161,10 +2501,16 @@ fn op_fresh_set_prompt_suggestions(
 ///
 /// Throws if file doesn't exist, isn't readable, or isn't valid UTF-8.
 /// For binary files, this will fail. For large files, consider memory usage.
+/// The path must be inside a directory of the plugin's manifest "fs_read" list.
 /// @param path - File path (absolute or relative to cwd)
 #[op2(async)]
 #[string]
-async fn op_fresh_read_file(#[string] path: String) -> Result<String, JsErrorBox> {
+async fn op_fresh_read_file(
+    state: Rc<RefCell<OpState>>,
+    #[string] token: String,
+    #[string] path: String,
+) -> Result<String, JsErrorBox> {
+    require_capability(&state, &token, Capability::FsRead, &path).await?;
     tokio::fs::read_to_string(&path)
         .await
         .map_err(|e| JsErrorBox::generic(format!("Failed to read file {}: {}", path, e)))
@@ -2174,13 +2520,17 @@ async fn op_fresh_read_file(#[string] path: String) -> Result<String, JsErrorBox
 ///
 /// Creates parent directories if they don't exist (behavior may vary).
 /// Replaces file contents entirely; use readFile + modify + writeFile for edits.
+/// The path must be inside a directory of the plugin's manifest "fs_write" list.
 /// @param path - Destination path (absolute or relative to cwd)
 /// @param content - UTF-8 string to write
 #[op2(async)]
 async fn op_fresh_write_file(
+    state: Rc<RefCell<OpState>>,
+    #[string] token: String,
     #[string] path: String,
     #[string] content: String,
 ) -> Result<(), JsErrorBox> {
+    require_capability(&state, &token, Capability::FsWrite, &path).await?;
     tokio::fs::write(&path, content)
         .await
         .map_err(|e| JsErrorBox::generic(format!("Failed to write file {}: {}", path, e)))
@@ -2189,7 +2539,7 @@ async fn op_fresh_write_file(
 /// Check if a path exists (file, directory, or symlink)
 ///
 /// Does not follow symlinks; returns true for broken symlinks.
-/// Use fileStat for more detailed information.
+/// Use fileStat for more detailed information. Needs no capability.
 /// @param path - Path to check (absolute or relative to cwd)
 #[op2(fast)]
 fn op_fresh_file_exists(#[string] path: String) -> bool {
@@ -2200,6 +2550,7 @@ fn op_fresh_file_exists(#[string] path: String) -> bool {
 ///
 /// Follows symlinks. Returns exists=false for non-existent paths
 /// rather than throwing. Size is in bytes; directories may report 0.
+/// Needs no capability.
 /// @param path - Path to stat (absolute or relative to cwd)
 #[op2]
 #[serde]
@@ -2224,11 +2575,19 @@ fn op_fresh_file_stat(#[string] path: String) -> FileStat {
 }
 
 /// Get an environment variable
+///
+/// The name must be declared in the plugin's manifest "env" list. The first
+/// call asks the user and returns null; once allowed, calls return the value.
 /// @param name - Name of environment variable
-/// @returns Value if set, null if not set
+/// @returns Value if set and allowed, null otherwise
 #[op2]
 #[string]
-fn op_fresh_get_env(#[string] name: String) -> Option<String> {
+fn op_fresh_get_env(
+    state: &mut OpState,
+    #[string] token: String,
+    #[string] name: String,
+) -> Option<String> {
+    require_capability_now(state, &token, Capability::Env, &name).ok()?;
     std::env::var(&name).ok()
 }
 
@@ -2348,6 +2707,8 @@ struct DirEntry {
 /// Returns unsorted entries with type info. Entry names are relative
 /// to the directory (use pathJoin to construct full paths).
 /// Throws on permission errors or if path is not a directory.
+/// The path must be inside a directory of the plugin's manifest "fs_read"
+/// list. The first call asks the user and throws; once allowed, calls succeed.
 /// @param path - Directory path (absolute or relative to cwd)
 /// @example
 /// const entries = editor.readDir("/home/user");
@@ -2358,31 +2719,18 @@ struct DirEntry {
 #[serde]
 fn op_fresh_read_dir(
     state: &mut OpState,
+    #[string] token: String,
     #[string] path: String,
 ) -> Result<Vec<DirEntry>, JsErrorBox> {
     // Resolve relative paths against the editor's working directory
-    let resolved_path = if std::path::Path::new(&path).is_absolute() {
-        std::path::PathBuf::from(&path)
-    } else {
-        // Try to get the working directory from the editor state
-        let working_dir =
-            state
-                .try_borrow::<Rc<RefCell<TsRuntimeState>>>()
-                .and_then(|runtime_state| {
-                    let runtime_state = runtime_state.borrow();
-                    runtime_state
-                        .state_snapshot
-                        .read()
-                        .ok()
-                        .map(|snapshot| snapshot.working_dir.clone())
-                });
+    let resolved_path = working_dir_path(state, &path);
 
-        if let Some(wd) = working_dir {
-            wd.join(&path)
-        } else {
-            std::path::PathBuf::from(&path)
-        }
-    };
+    require_capability_now(
+        state,
+        &token,
+        Capability::FsRead,
+        &resolved_path.to_string_lossy(),
+    )?;
 
     let entries = std::fs::read_dir(&resolved_path)
         .map_err(|e| JsErrorBox::generic(format!("Failed to read directory {}: {}", path, e)))?;
@@ -3349,29 +3697,46 @@ fn op_fresh_set_virtual_buffer_content(
 /// 3. Gets new cursor position
 /// 4. Deletes from old to new position
 ///
+/// Saving ("save", "save_all", "save_as_root") a file the plugin opened or
+/// edited needs "fs_write" for it, and files in the config directory can't
+/// be saved at all.
+///
 /// @param action_name - Action name (e.g., "move_word_right", "move_line_end")
 /// @returns true if action was sent successfully
 #[op2(fast)]
-fn op_fresh_execute_action(state: &mut OpState, #[string] action_name: String) -> bool {
+fn op_fresh_execute_action(
+    state: &mut OpState,
+    #[string] token: String,
+    #[string] action_name: String,
+) -> Result<bool, JsErrorBox> {
+    require_action_allowed(state, &token, &action_name)?;
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
         let result = runtime_state
             .command_sender
             .send(PluginCommand::ExecuteAction { action_name });
-        return result.is_ok();
+        return Ok(result.is_ok());
     }
-    false
+    Ok(false)
 }
 
 /// Execute multiple actions in sequence, each with an optional repeat count
 ///
 /// Used by vi mode for count prefix (e.g., "3dw" = delete 3 words).
 /// All actions execute atomically with no plugin roundtrips between them.
+/// Save-type actions are limited like in executeAction.
 ///
 /// @param actions - Array of {action: string, count?: number} objects
 /// @returns true if actions were sent successfully
 #[op2]
-fn op_fresh_execute_actions(state: &mut OpState, #[serde] actions: Vec<ActionSpecJs>) -> bool {
+fn op_fresh_execute_actions(
+    state: &mut OpState,
+    #[string] token: String,
+    #[serde] actions: Vec<ActionSpecJs>,
+) -> Result<bool, JsErrorBox> {
+    for action in &actions {
+        require_action_allowed(state, &token, &action.action)?;
+    }
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
         let action_specs: Vec<ActionSpec> = actions
@@ -3386,9 +3751,9 @@ fn op_fresh_execute_actions(state: &mut OpState, #[serde] actions: Vec<ActionSpe
             .send(PluginCommand::ExecuteActions {
                 actions: action_specs,
             });
-        return result.is_ok();
+        return Ok(result.is_ok());
     }
-    false
+    Ok(false)
 }
 
 /// JavaScript representation of ActionSpec (with optional count)
@@ -3628,6 +3993,7 @@ extension!(
     fresh_runtime,
     ops = [
         op_fresh_set_status,
+        op_fresh_set_status_segment,
         op_fresh_apply_theme,
         op_fresh_reload_config,
         op_fresh_get_config,
@@ -3799,6 +4165,7 @@ impl TypeScriptRuntime {
             cancellable_processes: Rc::new(RefCell::new(HashMap::new())),
             process_pids: Rc::new(RefCell::new(HashMap::new())),
             next_process_id: Rc::new(RefCell::new(1)),
+            plugin_tokens: Rc::new(RefCell::new(HashMap::new())),
         }));
 
         tracing::debug!(
@@ -3832,6 +4199,9 @@ impl TypeScriptRuntime {
                     setStatus(message) {
                         core.ops.op_fresh_set_status(message);
                     },
+                    setStatusSegment(name, text) {
+                        core.ops.op_fresh_set_status_segment(name, text);
+                    },
 
                     // Theme operations
                     applyTheme(themeName) {
@@ -3877,14 +4247,6 @@ impl TypeScriptRuntime {
                         return core.ops.op_fresh_is_buffer_modified(bufferId);
                     },
 
-                    // Buffer mutations
-                    insertText(bufferId, position, text) {
-                        return core.ops.op_fresh_insert_text(bufferId, position, text);
-                    },
-                    deleteRange(bufferId, start, end) {
-                        return core.ops.op_fresh_delete_range(bufferId, start, end);
-                    },
-
                     // Overlays
                     addOverlay(bufferId, namespace, start, end, r, g, b, underline, bold = false, italic = false, bg_r = -1, bg_g = -1, bg_b = -1, extend_to_line_end = false) {
                         return core.ops.op_fresh_add_overlay(bufferId, namespace, start, end, r, g, b, bg_r, bg_g, bg_b, underline, bold, italic, extend_to_line_end);
@@ -3949,10 +4311,6 @@ impl TypeScriptRuntime {
                         return core.ops.op_fresh_clear_line_indicators(bufferId, namespace);
                     },
 
-                    insertAtCursor(text) {
-                        return core.ops.op_fresh_insert_at_cursor(text);
-                    },
-
                     unregisterCommand(name) {
                         return core.ops.op_fresh_unregister_command(name);
                     },
@@ -3961,16 +4319,9 @@ impl TypeScriptRuntime {
                         return core.ops.op_fresh_set_context(name, active);
                     },
 
-                    openFile(path, line = 0, column = 0) {
-                        return core.ops.op_fresh_open_file(path, line, column);
-                    },
-
                     getActiveSplitId() {
                         return core.ops.op_fresh_get_active_split_id();
                     },
-                    openFileInSplit(splitId, path, line = 0, column = 0) {
-                        return core.ops.op_fresh_open_file_in_split(splitId, path, line, column);
-                    },
 
                     getCursorLine() {
                         return core.ops.op_fresh_get_cursor_line();
@@ -4008,32 +4359,9 @@ impl TypeScriptRuntime {
                         return core.ops.op_fresh_set_prompt_suggestions(suggestions);
                     },
 
-                    spawnProcess(command, args = [], cwd = null) {
-                        const effectiveCwd = cwd ?? core.ops.op_fresh_get_cwd();
-                        const processId = core.ops.op_fresh_spawn_process_start(command, args, effectiveCwd);
-                        const resultPromise = processId.then(id => core.ops.op_fresh_spawn_process_wait(id));
-                        return {
-                            get processId() { return processId; },
-                            get result() { return resultPromise; },
-                            kill: async () => {
-                                const id = await processId;
-                                return core.ops.op_fresh_kill_process(id);
-                            },
-                            then(onFulfilled, onRejected) {
-                                return resultPromise.then(onFulfilled, onRejected);
-                            },
-                            catch(onRejected) {
-                                return resultPromise.catch(onRejected);
-                            }
-                        };
-                    },
                     delay(ms) {
                         return core.ops.op_fresh_delay(ms);
                     },
-                    spawnBackgroundProcess(command, args = [], cwd = null) {
-                        const effectiveCwd = cwd ?? core.ops.op_fresh_get_cwd();
-                        return core.ops.op_fresh_spawn_background_process(command, args, effectiveCwd);
-                    },
                     killProcess(processId) {
                         return core.ops.op_fresh_kill_process(processId);
                     },
@@ -4044,12 +4372,6 @@ impl TypeScriptRuntime {
                         return core.ops.op_fresh_send_lsp_request(language, method, params);
                     },
 
-                    readFile(path) {
-                        return core.ops.op_fresh_read_file(path);
-                    },
-                    writeFile(path, content) {
-                        return core.ops.op_fresh_write_file(path, content);
-                    },
                     fileExists(path) {
                         return core.ops.op_fresh_file_exists(path);
                     },
@@ -4057,9 +4379,6 @@ impl TypeScriptRuntime {
                         return core.ops.op_fresh_file_stat(path);
                     },
 
-                    getEnv(name) {
-                        return core.ops.op_fresh_get_env(name);
-                    },
                     getCwd() {
                         return core.ops.op_fresh_get_cwd();
                     },
@@ -4079,9 +4398,6 @@ impl TypeScriptRuntime {
                     pathIsAbsolute(path) {
                         return core.ops.op_fresh_path_is_absolute(path);
                     },
-                    readDir(path) {
-                        return core.ops.op_fresh_read_dir(path);
-                    },
 
                     on(eventName, handlerName) {
                         return core.ops.op_fresh_on(eventName, handlerName);
@@ -4152,12 +4468,6 @@ impl TypeScriptRuntime {
                         return core.ops.op_fresh_set_virtual_buffer_content(bufferId, entries);
                     },
 
-                    executeAction(actionName) {
-                        return core.ops.op_fresh_execute_action(actionName);
-                    },
-                    executeActions(actions) {
-                        return core.ops.op_fresh_execute_actions(actions);
-                    },
                     getBufferText(bufferId, start, end) {
                         return core.ops.op_fresh_get_buffer_text(bufferId, start, end);
                     },
@@ -4190,63 +4500,149 @@ impl TypeScriptRuntime {
                     },
                 };
 
-                // Create a plugin-scoped editor object
-                // Each plugin gets its own editor with bound plugin name for i18n and logging
-                globalThis._createPluginEditor = function(pluginName) {
-                    const pluginEditor = {
-                        // Include all core methods
-                        ..._editorCore,
+                // Plugin-scoped editor objects
+                // Each plugin gets its own editor with bound plugin name for i18n and logging.
+                // The token identifies the plugin to ops that need capabilities. The factory
+                // and the editor waiting for a plugin being loaded only live in this closure,
+                // and the functions reaching them can't be replaced, so a plugin can't get
+                // another plugin's token or editor.
+                const defaultEditor = (() => {
+                    const createPluginEditor = function(pluginName, token = "") {
+                        const pluginEditor = {
+                            // Include all core methods
+                            ..._editorCore,
+
+                            // Plugin name for reference
+                            _pluginName: pluginName,
+
+                            // Operations limited to the capabilities of the plugin's manifest
+                            spawnProcess(command, args = [], cwd = null) {
+                                const effectiveCwd = cwd ?? core.ops.op_fresh_get_cwd();
+                                const processId = core.ops.op_fresh_spawn_process_start(token, command, args, effectiveCwd);
+                                const resultPromise = processId.then(id => core.ops.op_fresh_spawn_process_wait(id));
+                                return {
+                                    get processId() { return processId; },
+                                    get result() { return resultPromise; },
+                                    kill: async () => {
+                                        const id = await processId;
+                                        return core.ops.op_fresh_kill_process(id);
+                                    },
+                                    then(onFulfilled, onRejected) {
+                                        return resultPromise.then(onFulfilled, onRejected);
+                                    },
+                                    catch(onRejected) {
+                                        return resultPromise.catch(onRejected);
+                                    }
+                                };
+                            },
+                            spawnBackgroundProcess(command, args = [], cwd = null) {
+                                const effectiveCwd = cwd ?? core.ops.op_fresh_get_cwd();
+                                return core.ops.op_fresh_spawn_background_process(token, command, args, effectiveCwd);
+                            },
+                            readFile(path) {
+                                return core.ops.op_fresh_read_file(token, path);
+                            },
+                            writeFile(path, content) {
+                                return core.ops.op_fresh_write_file(token, path, content);
+                            },
+                            readDir(path) {
+                                return core.ops.op_fresh_read_dir(token, path);
+                            },
+                            getEnv(name) {
+                                return core.ops.op_fresh_get_env(token, name);
+                            },
 
-                        // Plugin name for reference
-                        _pluginName: pluginName,
+                            // Buffer operations, which record the files the plugin
+                            // opened or edited for the checks on saving them
+                            openFile(path, line = 0, column = 0) {
+                                return core.ops.op_fresh_open_file(token, path, line, column);
+                            },
+                            openFileInSplit(splitId, path, line = 0, column = 0) {
+                                return core.ops.op_fresh_open_file_in_split(token, splitId, path, line, column);
+                            },
+                            insertText(bufferId, position, text) {
+                                return core.ops.op_fresh_insert_text(token, bufferId, position, text);
+                            },
+                            deleteRange(bufferId, start, end) {
+                                return core.ops.op_fresh_delete_range(token, bufferId, start, end);
+                            },
+                            insertAtCursor(text) {
+                                return core.ops.op_fresh_insert_at_cursor(token, text);
+                            },
+                            executeAction(actionName) {
+                                return core.ops.op_fresh_execute_action(token, actionName);
+                            },
+                            executeActions(actions) {
+                                return core.ops.op_fresh_execute_actions(token, actions);
+                            },
 
-                        // Plugin-specific logging (prefixed with plugin name)
-                        error(message) {
-                            core.ops.op_fresh_error(`[${pluginName}] ${message}`);
-                        },
-                        warn(message) {
-                            core.ops.op_fresh_warn(`[${pluginName}] ${message}`);
-                        },
-                        info(message) {
-                            core.ops.op_fresh_info(`[${pluginName}] ${message}`);
-                        },
-                        debug(message) {
-                            core.ops.op_fresh_debug(`[${pluginName}] ${message}`);
-                        },
+                            // Plugin-specific logging (prefixed with plugin name)
+                            error(message) {
+                                core.ops.op_fresh_error(`[${pluginName}] ${message}`);
+                            },
+                            warn(message) {
+                                core.ops.op_fresh_warn(`[${pluginName}] ${message}`);
+                            },
+                            info(message) {
+                                core.ops.op_fresh_info(`[${pluginName}] ${message}`);
+                            },
+                            debug(message) {
+                                core.ops.op_fresh_debug(`[${pluginName}] ${message}`);
+                            },
 
-                        // Plugin-specific command registration
-                        registerCommand(name, description, action, contexts = "") {
-                            return core.ops.op_fresh_register_command(name, description, action, contexts, pluginName);
-                        },
+                            // Plugin-specific command registration
+                            registerCommand(name, description, action, contexts = "") {
+                                return core.ops.op_fresh_register_command(name, description, action, contexts, pluginName);
+                            },
 
-                        // Plugin-specific translation
-                        t(key, args = {}) {
-                            return core.ops.op_fresh_plugin_translate(pluginName, key, args);
-                        },
+                            // Plugin-specific translation
+                            t(key, args = {}) {
+                                return core.ops.op_fresh_plugin_translate(pluginName, key, args);
+                            },
 
-                        // For compatibility - returns self since t() is already bound
-                        getL10n() {
-                            return { t: (key, args = {}) => this.t(key, args) };
-                        },
+                            // For compatibility - returns self since t() is already bound
+                            getL10n() {
+                                return { t: (key, args = {}) => this.t(key, args) };
+                            },
+                        };
+                        return pluginEditor;
                     };
-                    return pluginEditor;
-                };
 
-                // Plugins MUST call getEditor() at the top of their file to get their scoped editor.
-                // This captures the editor instance during plugin initialization.
-                // The editor is stored in __pendingEditor during plugin load and cleared after getEditor() is called.
-                globalThis.__pendingEditor = null;
-                globalThis.getEditor = function() {
-                    const editor = globalThis.__pendingEditor;
-                    if (!editor) {
-                        throw new Error('getEditor() must be called at the top of the plugin file during initialization');
-                    }
-                    globalThis.__pendingEditor = null; // Clear after use
-                    return editor;
-                };
+                    // Set by the runtime right before a plugin's module is evaluated
+                    let pendingEditor = null;
+                    const define = (name, value) => Object.defineProperty(globalThis, name, {
+                        value,
+                        writable: false,
+                        configurable: false,
+                        enumerable: false,
+                    });
+
+                    define('__preparePluginEditor', function(pluginName, token = "") {
+                        pendingEditor = createPluginEditor(pluginName, token);
+                    });
+
+                    // Throws if the plugin just loaded didn't take its editor
+                    define('__finishPluginLoad', function() {
+                        const editor = pendingEditor;
+                        pendingEditor = null;
+                        if (editor !== null) {
+                            throw new Error(`Plugin '${editor._pluginName}' must call getEditor() at the top of the file`);
+                        }
+                    });
+
+                    // Plugins MUST call getEditor() at the top of their file to get their scoped editor.
+                    define('getEditor', function() {
+                        const editor = pendingEditor;
+                        if (!editor) {
+                            throw new Error('getEditor() must be called at the top of the plugin file during initialization');
+                        }
+                        pendingEditor = null;
+                        return editor;
+                    });
 
-                // Default editor for system use (console logging)
-                const defaultEditor = globalThis._createPluginEditor('system');
+                    // Default editor for system use (console logging)
+                    return createPluginEditor('system');
+                })();
                 globalThis.console = {
                     log: (...args) => defaultEditor.info(args.map(a => String(a)).join(' ')),
                     warn: (...args) => defaultEditor.warn(args.map(a => String(a)).join(' ')),
@@ -4306,6 +4702,9 @@ impl TypeScriptRuntime {
                 request_id,
                 ..
             } => *request_id,
+            crate::services::plugins::api::PluginResponse::PluginPermission {
+                request_id, ..
+            } => *request_id,
         };
 
         let sender = {
@@ -4347,18 +4746,46 @@ impl TypeScriptRuntime {
         self.load_module_with_source(path, "").await
     }
 
+    /// Register a plugin's capabilities and return the token its editor
+    /// object passes to the ops that check them
+    fn register_plugin(
+        &mut self,
+        name: &str,
+        manifest: Option<PluginManifest>,
+        plugin_path: &Path,
+    ) -> String {
+        use std::hash::{BuildHasher, Hasher};
+        // RandomState is randomly keyed, so its hashes can't be guessed by other plugins
+        let random = || {
+            std::collections::hash_map::RandomState::new()
+                .build_hasher()
+                .finish()
+        };
+        let token = format!("{:016x}{:016x}", random(), random());
+        let dir = plugin_path
+            .parent()
+            .map(permissions::resolve_path)
+            .unwrap_or_default();
+
+        let op_state = self.js_runtime.op_state();
+        let op_state = op_state.borrow();
+        let runtime_state = op_state.borrow::<Rc<RefCell<TsRuntimeState>>>();
+        runtime_state.borrow().plugin_tokens.borrow_mut().insert(
+            token.clone(),
+            LoadedPlugin {
+                name: name.to_string(),
+                manifest,
+                dir,
+                touched: HashSet::new(),
+            },
+        );
+        token
+    }
+
     /// Load and execute a TypeScript/JavaScript module file with explicit plugin source
     pub async fn load_module_with_source(&mut self, path: &str, plugin_source: &str) -> Result<()> {
-        // Create a plugin-scoped editor and store it in __pendingEditor.
-        // Plugins must call getEditor() at the top of their file to retrieve it.
-        let set_editor: FastString = format!(
-            "globalThis.__pendingEditor = globalThis._createPluginEditor(\"{}\");",
-            plugin_source
-        )
-        .into();
-        self.js_runtime
-            .execute_script("<set_pending_editor>", set_editor)
-            .map_err(|e| anyhow!("Failed to set pending editor: {}", e))?;
+        let manifest = PluginManifest::load_for(Path::new(path)).map_err(|e| anyhow!(e))?;
+        let token = self.register_plugin(plugin_source, manifest, Path::new(path));
 
         let module_specifier = deno_core::resolve_path(
             path,
@@ -4373,8 +4800,26 @@ impl TypeScriptRuntime {
             .await
             .map_err(|e| anyhow!("Failed to load module '{}': {}", path, e))?;
 
+        // Create the plugin-scoped editor right before the module runs, so
+        // the plugin's getEditor() at the top of the file is what takes it
+        let set_editor: FastString = format!(
+            "__preparePluginEditor(\"{}\", \"{}\");",
+            plugin_source, token
+        )
+        .into();
+        self.js_runtime
+            .execute_script("<set_pending_editor>", set_editor)
+            .map_err(|e| anyhow!("Failed to set pending editor: {}", e))?;
+
         let result = self.js_runtime.mod_evaluate(mod_id);
 
+        // The top of the module has run, so the plugin has taken its editor.
+        // If it hasn't, the editor is dropped before other plugins' code runs.
+        let taken = self
+            .js_runtime
+            .execute_script("<check_pending_editor>", "__finishPluginLoad();")
+            .map(|_| ());
+
         self.js_runtime
             .run_event_loop(Default::default())
             .await
@@ -4384,19 +4829,7 @@ impl TypeScriptRuntime {
             .await
             .map_err(|e| anyhow!("Module evaluation error: {}", e))?;
 
-        // Check if the plugin forgot to call getEditor()
-        let check_editor: FastString = r#"
-            if (globalThis.__pendingEditor !== null) {
-                const pluginName = globalThis.__pendingEditor._pluginName;
-                globalThis.__pendingEditor = null;
-                throw new Error(`Plugin '${pluginName}' must call getEditor() at the top of the file`);
-            }
-        "#.to_string().into();
-        self.js_runtime
-            .execute_script("<check_pending_editor>", check_editor)
-            .map_err(|e| anyhow!("Plugin initialization error: {}", e))?;
-
-        Ok(())
+        taken.map_err(|e| anyhow!("Plugin initialization error: {}", e))
     }
 
     /// Execute a global function by name (for plugin actions)
@@ -4820,6 +5253,46 @@ impl TypeScriptPluginManager {
 mod tests {
     use super::*;
 
+    /// Trust every plugin with all capabilities
+    fn trust_all_plugins(snapshot: &mut EditorStateSnapshot) {
+        snapshot.plugin_permissions.insert(
+            permissions::ALL_PLUGINS.to_string(),
+            crate::config::PluginPermissions {
+                trusted: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Runtime with a trusted plugin "test" whose editor is `globalThis.__testEditor`
+    async fn trusted_plugin_runtime() -> TypeScriptRuntime {
+        trusted_plugin_runtime_with(EditorStateSnapshot::new())
+            .await
+            .0
+    }
+
+    /// Like `trusted_plugin_runtime`, starting from `snapshot`, with the
+    /// receiver of the commands the plugin sends
+    async fn trusted_plugin_runtime_with(
+        mut snapshot: EditorStateSnapshot,
+    ) -> (TypeScriptRuntime, std::sync::mpsc::Receiver<PluginCommand>) {
+        trust_all_plugins(&mut snapshot);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let state_snapshot = Arc::new(RwLock::new(snapshot));
+        let mut runtime = TypeScriptRuntime::with_state(state_snapshot, tx).unwrap();
+        let token = runtime.register_plugin("test", None, Path::new("test.ts"));
+        runtime
+            .execute_script(
+                "<test_editor>",
+                &format!(
+                    "__preparePluginEditor('test', '{token}'); globalThis.__testEditor = getEditor();"
+                ),
+            )
+            .await
+            .unwrap();
+        (runtime, rx)
+    }
+
     #[tokio::test]
     async fn test_typescript_runtime_creation() {
         let runtime = TypeScriptRuntime::new();
@@ -4960,7 +5433,8 @@ mod tests {
             .execute_script(
                 "<test_state>",
                 r#"
-                var editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                var editor = getEditor();
                 // Test buffer queries
                 const bufferId = editor.getActiveBufferId();
                 if (bufferId !== 42) {
@@ -4998,7 +5472,8 @@ mod tests {
             .execute_script(
                 "<test_commands>",
                 r#"
-                var editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                var editor = getEditor();
                 // Test status command
                 editor.setStatus("Test status from TypeScript");
 
@@ -5109,7 +5584,8 @@ mod tests {
             .execute_script(
                 "<test_api>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 // Verify all API methods exist
                 const methods = [
                     'setStatus', 'debug', 'getActiveBufferId', 'getCursorPosition',
@@ -5138,26 +5614,20 @@ mod tests {
     async fn test_new_ops() {
         use std::path::PathBuf;
 
-        // Create shared state
-        let (tx, rx) = std::sync::mpsc::channel();
-        let state_snapshot = Arc::new(RwLock::new(EditorStateSnapshot::new()));
-
         // Populate state with test data including split ID
-        {
-            let mut snapshot = state_snapshot.write().unwrap();
-            snapshot.active_buffer_id = BufferId(1);
-            snapshot.active_split_id = 5;
-        }
+        let mut snapshot = EditorStateSnapshot::new();
+        snapshot.active_buffer_id = BufferId(1);
+        snapshot.active_split_id = 5;
 
         // Create runtime with state
-        let mut runtime = TypeScriptRuntime::with_state(state_snapshot.clone(), tx).unwrap();
+        let (mut runtime, rx) = trusted_plugin_runtime_with(snapshot).await;
 
         // Test new ops from TypeScript
         let result = runtime
             .execute_script(
                 "<test_new_ops>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 // Test getActiveSplitId
                 const splitId = editor.getActiveSplitId();
                 if (splitId !== 5) {
@@ -5251,7 +5721,8 @@ mod tests {
             .execute_script(
                 "<test_empty_contexts>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 editor.registerCommand("Global Command", "Available everywhere", "global_action", "");
                 "#,
             )
@@ -5284,7 +5755,8 @@ mod tests {
             .execute_script(
                 "<test_all_contexts>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 editor.registerCommand(
                     "All Contexts",
                     "Test all context types",
@@ -5334,7 +5806,8 @@ mod tests {
             .execute_script(
                 "<test_invalid_contexts>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 editor.registerCommand(
                     "Partial Contexts",
                     "Some invalid",
@@ -5363,17 +5836,43 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_open_file_with_zero_values() {
+    async fn test_set_status_segment() {
         let (tx, rx) = std::sync::mpsc::channel();
         let state_snapshot = Arc::new(RwLock::new(EditorStateSnapshot::new()));
         let mut runtime = TypeScriptRuntime::with_state(state_snapshot, tx).unwrap();
 
+        let result = runtime
+            .execute_script(
+                "<test_status_segment>",
+                r#"
+                __preparePluginEditor('test');
+                const editor = getEditor();
+                editor.setStatusSegment("tests", "12 passed");
+                "#,
+            )
+            .await;
+        assert!(result.is_ok());
+
+        let commands: Vec<_> = rx.try_iter().collect();
+        match &commands[0] {
+            PluginCommand::SetStatusSegment { name, text } => {
+                assert_eq!(name, "tests");
+                assert_eq!(text, "12 passed");
+            }
+            _ => panic!("Expected SetStatusSegment"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_file_with_zero_values() {
+        let (mut runtime, rx) = trusted_plugin_runtime_with(EditorStateSnapshot::new()).await;
+
         // Zero values should translate to None (file opening without positioning)
         let result = runtime
             .execute_script(
                 "<test_zero_values>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 editor.openFile("/test/file.txt", 0, 0);
                 "#,
             )
@@ -5393,16 +5892,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_open_file_with_default_params() {
-        let (tx, rx) = std::sync::mpsc::channel();
-        let state_snapshot = Arc::new(RwLock::new(EditorStateSnapshot::new()));
-        let mut runtime = TypeScriptRuntime::with_state(state_snapshot, tx).unwrap();
+        let (mut runtime, rx) = trusted_plugin_runtime_with(EditorStateSnapshot::new()).await;
 
         // Test that JavaScript default parameters work
         let result = runtime
             .execute_script(
                 "<test_default_params>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 // Call with just path (line and column default to 0)
                 editor.openFile("/test/file.txt");
                 "#,
@@ -5423,16 +5920,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_open_file_with_line_only() {
-        let (tx, rx) = std::sync::mpsc::channel();
-        let state_snapshot = Arc::new(RwLock::new(EditorStateSnapshot::new()));
-        let mut runtime = TypeScriptRuntime::with_state(state_snapshot, tx).unwrap();
+        let (mut runtime, rx) = trusted_plugin_runtime_with(EditorStateSnapshot::new()).await;
 
         // Open file at specific line but no column
         let result = runtime
             .execute_script(
                 "<test_line_only>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 editor.openFile("/test/file.txt", 50);
                 "#,
             )
@@ -5460,7 +5955,8 @@ mod tests {
             .execute_script(
                 "<test_case_insensitive>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 editor.registerCommand(
                     "Case Test",
                     "Test case insensitivity",
@@ -5492,14 +5988,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_spawn_process_simple() {
-        let mut runtime = TypeScriptRuntime::new().unwrap();
+        let mut runtime = trusted_plugin_runtime().await;
 
         // Test spawning a simple echo command
         let result = runtime
             .execute_script(
                 "<test_spawn>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 (async () => {
                     const result = await editor.spawnProcess("echo", ["hello", "world"]);
                     if (!result.stdout.includes("hello world")) {
@@ -5518,14 +6014,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_spawn_process_with_stderr() {
-        let mut runtime = TypeScriptRuntime::new().unwrap();
+        let mut runtime = trusted_plugin_runtime().await;
 
         // Test spawning a command that writes to stderr
         let result = runtime
             .execute_script(
                 "<test_spawn_stderr>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 (async () => {
                     const result = await editor.spawnProcess("sh", ["-c", "echo error >&2"]);
                     if (!result.stderr.includes("error")) {
@@ -5541,14 +6037,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_spawn_process_nonzero_exit() {
-        let mut runtime = TypeScriptRuntime::new().unwrap();
+        let mut runtime = trusted_plugin_runtime().await;
 
         // Test spawning a command that exits with non-zero
         let result = runtime
             .execute_script(
                 "<test_spawn_exit>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 (async () => {
                     const result = await editor.spawnProcess("sh", ["-c", "exit 42"]);
                     if (result.exit_code !== 42) {
@@ -5564,14 +6060,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_spawn_process_git_example() {
-        let mut runtime = TypeScriptRuntime::new().unwrap();
+        let mut runtime = trusted_plugin_runtime().await;
 
         // Test a realistic example: git version
         let result = runtime
             .execute_script(
                 "<test_git>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 (async () => {
                     const result = await editor.spawnProcess("git", ["--version"]);
                     if (!result.stdout.includes("git version")) {
@@ -5594,7 +6090,8 @@ mod tests {
             .execute_script(
                 "<test_file_exists>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 // Test existing file
                 const cargoExists = editor.fileExists("Cargo.toml");
                 if (!cargoExists) {
@@ -5622,7 +6119,8 @@ mod tests {
             .execute_script(
                 "<test_file_stat>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 // Test stat on existing file
                 const stat = editor.fileStat("Cargo.toml");
                 if (!stat.exists) {
@@ -5653,13 +6151,13 @@ mod tests {
 
     #[tokio::test]
     async fn test_read_file() {
-        let mut runtime = TypeScriptRuntime::new().unwrap();
+        let mut runtime = trusted_plugin_runtime().await;
 
         let result = runtime
             .execute_script(
                 "<test_read_file>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 (async () => {
                     // Read Cargo.toml which should exist
                     const content = await editor.readFile("Cargo.toml");
@@ -5685,7 +6183,8 @@ mod tests {
             .execute_script(
                 "<test_path_ops>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 // Test pathJoin
                 const joined = editor.pathJoin("src", "ts_runtime.rs");
                 if (!joined.includes("src") || !joined.includes("ts_runtime.rs")) {
@@ -5725,13 +6224,13 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_env() {
-        let mut runtime = TypeScriptRuntime::new().unwrap();
+        let mut runtime = trusted_plugin_runtime().await;
 
         let result = runtime
             .execute_script(
                 "<test_get_env>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 // PATH should always be set
                 const path = editor.getEnv("PATH");
                 if (path === null || path === undefined) {
@@ -5762,7 +6261,8 @@ mod tests {
             .execute_script(
                 "<test_get_cwd>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 const cwd = editor.getCwd();
                 if (!cwd || cwd.length === 0) {
                     throw new Error("getCwd should return non-empty string");
@@ -5776,7 +6276,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_write_file() {
-        let mut runtime = TypeScriptRuntime::new().unwrap();
+        let mut runtime = trusted_plugin_runtime().await;
 
         // Use platform-appropriate temp directory
         let temp_file = std::env::temp_dir().join("fresh_ts_runtime_test_write.txt");
@@ -5787,7 +6287,7 @@ mod tests {
                 "<test_write_file>",
                 &format!(
                     r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 (async () => {{
                     const testFile = "{temp_file_str}";
                     const testContent = "Hello from TypeScript plugin!\nLine 2\n";
@@ -5827,13 +6327,13 @@ mod tests {
 
     #[tokio::test]
     async fn test_read_dir() {
-        let mut runtime = TypeScriptRuntime::new().unwrap();
+        let mut runtime = trusted_plugin_runtime().await;
 
         let result = runtime
             .execute_script(
                 "<test_read_dir>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                const editor = globalThis.__testEditor;
                 // Read current directory (should have Cargo.toml, src/, etc.)
                 const entries = editor.readDir(".");
 
@@ -5872,6 +6372,253 @@ mod tests {
         assert!(result.is_ok(), "Read directory test failed: {:?}", result);
     }
 
+    #[tokio::test]
+    async fn test_undeclared_capabilities_are_refused() {
+        let mut runtime = TypeScriptRuntime::new().unwrap();
+        let manifest = PluginManifest {
+            env: vec!["HOME".to_string()],
+            ..Default::default()
+        };
+        let token = runtime.register_plugin("limited", Some(manifest), Path::new("limited.ts"));
+
+        let result = runtime
+            .execute_script(
+                "<test_undeclared_capabilities>",
+                &format!(
+                    r#"
+                __preparePluginEditor('limited', '{token}');
+                const editor = getEditor();
+                __preparePluginEditor('limited');
+                const unregistered = getEditor();
+                (async () => {{
+                    for (const [name, attempt] of [
+                        ["readFile", () => editor.readFile("Cargo.toml")],
+                        ["writeFile", () => editor.writeFile("never-written.txt", "")],
+                        ["spawnProcess", () => editor.spawnProcess("echo", ["hi"])],
+                        ["unregistered readFile", () => unregistered.readFile("Cargo.toml")],
+                    ]) {{
+                        let refused = false;
+                        try {{
+                            await attempt();
+                        }} catch (e) {{
+                            refused = String(e).includes("not allowed") || String(e).includes("only available");
+                        }}
+                        if (!refused) {{
+                            throw new Error(`${{name}} should have been refused`);
+                        }}
+                    }}
+                    let refused = false;
+                    try {{
+                        editor.readDir(".");
+                    }} catch (e) {{
+                        refused = true;
+                    }}
+                    if (!refused) {{
+                        throw new Error("readDir should have been refused");
+                    }}
+                    if (editor.getEnv("PATH") !== null) {{
+                        throw new Error("getEnv of an undeclared variable should return null");
+                    }}
+                }})()
+                "#
+                ),
+            )
+            .await;
+        assert!(
+            result.is_ok(),
+            "Undeclared capabilities test failed: {:?}",
+            result
+        );
+        assert!(!std::path::Path::new("never-written.txt").exists());
+    }
+
+    /// Snapshot with `path` open, modified and active as buffer 1
+    fn snapshot_with_buffer(path: &Path) -> EditorStateSnapshot {
+        let mut snapshot = EditorStateSnapshot::new();
+        snapshot.active_buffer_id = BufferId(1);
+        snapshot.buffers.insert(
+            BufferId(1),
+            crate::services::plugins::api::BufferInfo {
+                id: BufferId(1),
+                path: Some(path.to_path_buf()),
+                modified: true,
+                length: 0,
+            },
+        );
+        snapshot
+    }
+
+    #[tokio::test]
+    async fn test_undeclared_plugin_cant_open_or_save_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let snapshot = snapshot_with_buffer(&dir.path().join("notes.txt"));
+        let state_snapshot = Arc::new(RwLock::new(snapshot));
+        let mut runtime = TypeScriptRuntime::with_state(state_snapshot, tx).unwrap();
+        let token = runtime.register_plugin("limited", None, Path::new("limited.ts"));
+
+        let result = runtime
+            .execute_script(
+                "<test_undeclared_open_save>",
+                &format!(
+                    r#"
+                __preparePluginEditor('limited', '{token}');
+                const editor = getEditor();
+                const refused = (name, attempt) => {{
+                    try {{
+                        attempt();
+                    }} catch (e) {{
+                        if (String(e).includes("not declared")) return;
+                        throw e;
+                    }}
+                    throw new Error(`${{name}} should have been refused`);
+                }};
+                refused("openFile", () => editor.openFile("/etc/hosts"));
+                refused("openFileInSplit", () => editor.openFileInSplit(1, "/etc/hosts"));
+
+                // The user's own edits can still be saved
+                if (!editor.executeAction("save")) {{
+                    throw new Error("saving a buffer the plugin didn't touch should work");
+                }}
+
+                editor.insertText(1, 0, "edited");
+                refused("executeAction save", () => editor.executeAction("save"));
+                refused("executeAction save_all", () => editor.executeAction("save_all"));
+                refused("executeActions", () => editor.executeActions([
+                    {{ action: "move_right" }},
+                    {{ action: "save" }},
+                ]));
+                "#
+                ),
+            )
+            .await;
+        assert!(
+            result.is_ok(),
+            "Undeclared open/save test failed: {:?}",
+            result
+        );
+
+        let commands: Vec<_> = rx.try_iter().collect();
+        assert!(!commands.iter().any(|c| matches!(
+            c,
+            PluginCommand::OpenFileAtLocation { .. } | PluginCommand::OpenFileInSplit { .. }
+        )));
+        let actions = commands
+            .iter()
+            .filter(|c| {
+                matches!(
+                    c,
+                    PluginCommand::ExecuteAction { .. } | PluginCommand::ExecuteActions { .. }
+                )
+            })
+            .count();
+        assert_eq!(actions, 1, "Only the first save should have been sent");
+    }
+
+    #[tokio::test]
+    async fn test_plugin_cant_save_config_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut snapshot = snapshot_with_buffer(&dir.path().join("config.json"));
+        snapshot.config_dir = dir.path().to_path_buf();
+        let (mut runtime, rx) = trusted_plugin_runtime_with(snapshot).await;
+
+        let result = runtime
+            .execute_script(
+                "<test_save_config>",
+                r#"
+                const editor = globalThis.__testEditor;
+                editor.insertText(1, 0, "{}");
+                let refused = false;
+                try {
+                    editor.executeAction("save");
+                } catch (e) {
+                    refused = String(e).includes("config directory");
+                }
+                if (!refused) {
+                    throw new Error("saving a config file should have been refused");
+                }
+                "#,
+            )
+            .await;
+        assert!(result.is_ok(), "Config save test failed: {:?}", result);
+        assert!(!rx
+            .try_iter()
+            .any(|c| matches!(c, PluginCommand::ExecuteAction { .. })));
+    }
+
+    /// A plugin loaded first can't replace the loader's functions to get the
+    /// token or editor of a plugin loaded after it
+    #[tokio::test]
+    async fn test_plugin_cant_take_another_plugins_editor() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let first = dir.path().join("first.ts");
+        let second = dir.path().join("second.ts");
+        std::fs::write(
+            &first,
+            r#"
+            const editor = getEditor();
+            globalThis.sawPending = typeof pendingEditor !== "undefined";
+            for (const name of ["getEditor", "__preparePluginEditor", "__finishPluginLoad"]) {
+                const hook = (...args) => { globalThis.stolen = args; };
+                try { globalThis[name] = hook; } catch (e) {}
+                try { Object.defineProperty(globalThis, name, { value: hook }); } catch (e) {}
+                try { delete globalThis[name]; } catch (e) {}
+            }
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            &second,
+            "const editor = getEditor();\nglobalThis.secondEditor = editor;\n",
+        )
+        .unwrap();
+
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let mut snapshot = EditorStateSnapshot::new();
+        snapshot.plugin_permissions.insert(
+            "second".to_string(),
+            crate::config::PluginPermissions {
+                trusted: true,
+                ..Default::default()
+            },
+        );
+        let mut runtime =
+            TypeScriptRuntime::with_state(Arc::new(RwLock::new(snapshot)), tx).unwrap();
+        for path in [&first, &second] {
+            let name = path.file_stem().unwrap().to_str().unwrap();
+            runtime
+                .load_module_with_source(path.to_str().unwrap(), name)
+                .await
+                .unwrap();
+        }
+
+        let result = runtime
+            .execute_script(
+                "<test_plugin_isolation>",
+                r#"
+                if (globalThis.sawPending || globalThis.stolen !== undefined) {
+                    throw new Error("the first plugin reached the loader");
+                }
+                if (globalThis.secondEditor._pluginName !== "second") {
+                    throw new Error("the second plugin didn't get its own editor");
+                }
+                if (globalThis.secondEditor.getEnv("PATH") === null) {
+                    throw new Error("the second plugin's editor lost its token");
+                }
+                let taken = false;
+                try {
+                    getEditor();
+                    taken = true;
+                } catch (e) {}
+                if (taken) {
+                    throw new Error("an editor was left for anyone to take");
+                }
+                "#,
+            )
+            .await;
+        assert!(result.is_ok(), "Plugin isolation test failed: {:?}", result);
+    }
+
     #[tokio::test]
     #[cfg(unix)]
     async fn test_path_is_absolute() {
@@ -5881,7 +6628,8 @@ mod tests {
             .execute_script(
                 "<test_path_is_absolute>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 // Test absolute paths (Unix)
                 if (!editor.pathIsAbsolute("/home/user")) {
                     throw new Error("/home/user should be absolute");
@@ -5917,7 +6665,8 @@ mod tests {
             .execute_script(
                 "<test_path_is_absolute>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 // Test absolute paths (Windows)
                 if (!editor.pathIsAbsolute("C:\\Users\\test")) {
                     throw new Error("C:\\Users\\test should be absolute");
@@ -5958,7 +6707,8 @@ mod tests {
             .execute_script(
                 "<test_hook_registration>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 // Register a handler
                 const registered = editor.on("buffer_save", "onBufferSave");
                 if (!registered) {
@@ -6018,7 +6768,8 @@ mod tests {
             .execute_script(
                 "<test_hook_emit_setup>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 globalThis.eventCounter = 0;
                 globalThis.lastEventData = null;
 
@@ -6071,7 +6822,8 @@ mod tests {
             .execute_script(
                 "<test_hook_cancel_setup>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 globalThis.cancelWasCalled = false;
                 globalThis.onCancelEvent = function(data) {
                     globalThis.cancelWasCalled = true;
@@ -6114,7 +6866,8 @@ mod tests {
             .execute_script(
                 "<test_hook_multi_setup>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 globalThis.handler1Called = false;
                 globalThis.handler2Called = false;
 
@@ -6358,7 +7111,8 @@ mod tests {
             .execute_script(
                 "<test_hook_setup>",
                 r#"
-            const editor = globalThis._createPluginEditor('test');
+            __preparePluginEditor('test');
+            const editor = getEditor();
             globalThis.onBufferActivated = function(data) {
                 editor.setStatus("Buffer " + data.buffer_id + " activated");
             };
@@ -6430,7 +7184,8 @@ mod tests {
             .execute_script(
                 "<setup_handler>",
                 r#"
-                const editor = globalThis._createPluginEditor('test');
+                __preparePluginEditor('test');
+                const editor = getEditor();
                 globalThis.onRenderLine = function(data) {
                     // Simulate TODO highlighter: check if line contains keyword
                     if (data.content && data.content.includes("TODO")) {
@@ -6850,6 +7605,7 @@ mod tests {
 
         // Spawn the plugin thread
         let mut handle = PluginThreadHandle::spawn(commands).unwrap();
+        trust_all_plugins(&mut handle.state_snapshot_handle().write().unwrap());
 
         // Create a simple plugin that spawns a process
         let temp_dir = TempDir::new().unwrap();
@@ -6933,6 +7689,7 @@ mod tests {
 
         // Spawn the plugin thread
         let mut handle = PluginThreadHandle::spawn(commands).unwrap();
+        trust_all_plugins(&mut handle.state_snapshot_handle().write().unwrap());
 
         // Create a plugin that runs git log like the git_log plugin does
         let temp_dir = TempDir::new().unwrap();
@@ -7020,6 +7777,7 @@ mod tests {
 
         // Spawn the plugin thread
         let mut handle = PluginThreadHandle::spawn(commands).unwrap();
+        trust_all_plugins(&mut handle.state_snapshot_handle().write().unwrap());
 
         // Create a plugin that mimics git_log with debug logs
         let temp_dir = TempDir::new().unwrap();
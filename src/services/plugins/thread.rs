@@ -457,6 +457,9 @@ fn respond_to_pending(
             request_id,
             ..
         } => *request_id,
+        crate::services::plugins::api::PluginResponse::PluginPermission { request_id, .. } => {
+            *request_id
+        }
     };
 
     let sender = {
@@ -0,0 +1,238 @@
+//! Saving files through a privilege-escalation helper
+//!
+//! When a file can't be written by the editor's user, "Save as Root" writes
+//! the buffer to a private temp file and has a helper (`sudo`, `doas`,
+//! `pkexec` or any command that runs its arguments as root) copy it over
+//! the file with a small shell script.
+//!
+//! Like a normal save, the new content goes to a temp file next to the file
+//! first, gets the mode and owner of the file it replaces and is then
+//! renamed into place. A symlinked file is replaced behind its link.
+//!
+//! `sudo` is run with `-S`, so the password can be asked for in the editor
+//! and passed on its stdin. Other helpers get no stdin: `doas` is run with
+//! `-n` and fails unless it needs no password, and `pkexec` asks through
+//! the desktop's polkit agent.
+//!
+//! The helper can take a while, e.g. while polkit asks, so it is run on the
+//! tokio runtime and never on the UI thread.
+
+use std::io;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Copies `$1` over `$2`, giving it mode `$3` and owner `$4` when they are
+/// set
+///
+/// A symlink at `$2` is resolved first, so the file it points to is replaced
+/// rather than the link. The new content goes to a fresh `mktemp` file next
+/// to it: a fixed name would let anyone who can write to the directory plant
+/// a link there for root to write through.
+const INSTALL_SCRIPT: &str = r#"set -e
+dest="$2"
+if [ -L "$dest" ]; then dest=$(readlink -f -- "$dest"); fi
+tmp=$(mktemp "$(dirname -- "$dest")/.fresh.XXXXXX")
+trap 'rm -f -- "$tmp"' EXIT
+cat -- "$1" > "$tmp"
+chmod "${3:-$(printf '%o' $((0666 & ~$(umask))))}" "$tmp"
+if [ -n "$4" ]; then chown "$4" "$tmp"; fi
+mv -f -- "$tmp" "$dest""#;
+
+/// A command that runs another command as root
+#[derive(Debug, Clone)]
+pub struct PrivilegedHelper {
+    program: String,
+}
+
+impl PrivilegedHelper {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+        }
+    }
+
+    /// The helper command, as configured
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+
+    /// The helper's file name, e.g. `sudo` for `/usr/bin/sudo`
+    fn name(&self) -> &str {
+        Path::new(&self.program)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.program)
+    }
+
+    /// Whether the helper reads a password from its stdin
+    pub fn takes_password(&self) -> bool {
+        self.name() == "sudo"
+    }
+
+    /// Whether the helper will ask for a password, i.e. it takes one and
+    /// has no cached credentials
+    pub async fn needs_password(&self) -> bool {
+        if !self.takes_password() {
+            return false;
+        }
+        !Command::new(&self.program)
+            .args(["-n", "true"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .is_ok_and(|status| status.success())
+    }
+
+    /// Copy `source` over `dest` as root
+    ///
+    /// `original` is the metadata of the file being replaced; its mode and,
+    /// on Unix, its owner and group are given to the new file.
+    pub async fn install(
+        &self,
+        source: &Path,
+        dest: &Path,
+        original: Option<&std::fs::Metadata>,
+        password: Option<&str>,
+    ) -> io::Result<()> {
+        let (mode, owner) = original.map(metadata_args).unwrap_or_default();
+
+        let mut command = Command::new(&self.program);
+        match self.name() {
+            "sudo" => {
+                command.args(["-S", "-p", "", "--"]);
+            }
+            "doas" => {
+                command.args(["-n", "--"]);
+            }
+            _ => {}
+        }
+        command
+            .args(["sh", "-c", INSTALL_SCRIPT, "sh"])
+            .arg(source)
+            .arg(dest)
+            .args([mode, owner])
+            .stdin(if self.takes_password() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+
+        let mut child = command.spawn().map_err(|e| {
+            io::Error::new(e.kind(), format!("failed to run {}: {}", self.program, e))
+        })?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // The helper may exit without reading it, e.g. with cached
+            // credentials, so a broken pipe is not an error
+            let line = format!("{}\n", password.unwrap_or_default());
+            let _ = stdin.write_all(line.as_bytes()).await;
+        }
+        let output = child.wait_with_output().await?;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .map(|line| line.trim().to_string())
+            .unwrap_or_else(|| format!("{} exited with {}", self.program, output.status));
+        Err(io::Error::new(io::ErrorKind::PermissionDenied, message))
+    }
+}
+
+/// The mode and `uid:gid` arguments of the install script for a file with
+/// `metadata`
+#[cfg(unix)]
+fn metadata_args(metadata: &std::fs::Metadata) -> (String, String) {
+    use std::os::unix::fs::MetadataExt;
+    (
+        format!("{:o}", metadata.mode() & 0o7777),
+        format!("{}:{}", metadata.uid(), metadata.gid()),
+    )
+}
+
+#[cfg(not(unix))]
+fn metadata_args(_metadata: &std::fs::Metadata) -> (String, String) {
+    (String::new(), String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_helper_name_and_password() {
+        assert!(PrivilegedHelper::new("sudo").takes_password());
+        assert!(PrivilegedHelper::new("/usr/bin/sudo").takes_password());
+        assert!(!PrivilegedHelper::new("doas").takes_password());
+        assert!(!PrivilegedHelper::new("pkexec").needs_password().await);
+    }
+
+    /// With a helper that runs its arguments as is, installing copies the
+    /// file and keeps the mode of the file it replaces
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_keeps_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = dir.path().join("source");
+        let dest = dir.path().join("dest");
+        std::fs::write(&source, "new").unwrap();
+        std::fs::write(&dest, "old").unwrap();
+        std::fs::set_permissions(&dest, std::fs::Permissions::from_mode(0o640)).unwrap();
+        let original = std::fs::metadata(&dest).unwrap();
+
+        PrivilegedHelper::new("env")
+            .install(&source, &dest, Some(&original), None)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "new");
+        let mode = std::fs::metadata(&dest).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+        // Only the source and the installed file are left
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    /// A symlinked file is replaced behind its link, and a link planted at
+    /// the old temp file name is not written through
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_through_symlink() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = dir.path().join("source");
+        let target = dir.path().join("target");
+        let link = dir.path().join("link");
+        let planted = dir.path().join("planted");
+        std::fs::write(&source, "new").unwrap();
+        std::fs::write(&target, "old").unwrap();
+        std::fs::write(&planted, "untouched").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o600)).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        std::os::unix::fs::symlink(&planted, dir.path().join("link.tmp")).unwrap();
+        let original = std::fs::metadata(&link).unwrap();
+
+        PrivilegedHelper::new("env")
+            .install(&source, &link, Some(&original), None)
+            .await
+            .unwrap();
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+        let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_to_string(&planted).unwrap(), "untouched");
+    }
+}
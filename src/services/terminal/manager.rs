@@ -22,6 +22,7 @@ use super::term::TerminalState;
 use crate::services::async_bridge::AsyncBridge;
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use std::collections::HashMap;
+use std::ffi::OsString;
 use std::io::{Read, Write};
 use std::sync::atomic::AtomicBool;
 use std::sync::mpsc;
@@ -118,6 +119,8 @@ pub struct TerminalManager {
     next_id: usize,
     /// Async bridge for sending notifications to main loop
     async_bridge: Option<AsyncBridge>,
+    /// Extra environment variables for the shells
+    env: Vec<(OsString, OsString)>,
 }
 
 impl TerminalManager {
@@ -127,6 +130,7 @@ impl TerminalManager {
             terminals: HashMap::new(),
             next_id: 0,
             async_bridge: None,
+            env: Vec::new(),
         }
     }
 
@@ -135,6 +139,13 @@ impl TerminalManager {
         self.async_bridge = Some(bridge);
     }
 
+    /// Set an environment variable for terminals spawned from now on
+    pub fn set_env(&mut self, key: impl Into<OsString>, value: impl Into<OsString>) {
+        let key = key.into();
+        self.env.retain(|(k, _)| *k != key);
+        self.env.push((key, value.into()));
+    }
+
     /// Peek at the next terminal ID that would be assigned.
     pub fn next_terminal_id(&self) -> TerminalId {
         TerminalId(self.next_id)
@@ -184,6 +195,9 @@ impl TerminalManager {
             if let Some(ref dir) = cwd {
                 cmd.cwd(dir);
             }
+            for (key, value) in &self.env {
+                cmd.env(key, value);
+            }
 
             // Spawn the shell process
             let mut child = pty_pair
@@ -0,0 +1,273 @@
+//! Persistent undo history
+//!
+//! When a file's buffer is closed, or the editor exits, the buffer's undo log
+//! is written to `<data dir>/undo/{encoded_path}.json` and read back the next
+//! time the file is opened, so edits can be undone across restarts.
+//!
+//! The history is tied to a hash of the file's contents: if the file was
+//! changed by something else in between, the history no longer applies to it
+//! and is dropped instead of undoing edits at the wrong places. The oldest
+//! edits are dropped to keep each history under a size and an age limit.
+//!
+//! Multi-cursor edits keep snapshots of the buffer for undo rather than the
+//! text they changed, so they can't be written out; a persisted history only
+//! reaches back to the last of them.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::model::event::{EventLog, LogEntry};
+use crate::session::encode_path_for_filename;
+
+/// Current undo history file format version
+pub const UNDO_HISTORY_VERSION: u32 = 1;
+
+/// Limits on the persisted undo history of a file
+#[derive(Debug, Clone, Copy)]
+pub struct UndoHistoryLimits {
+    /// Maximum size of the history file in bytes
+    pub max_bytes: u64,
+    /// Edits older than this are dropped
+    pub max_age: Duration,
+}
+
+/// Undo history of a file as stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedUndoHistory {
+    /// Schema version for future migrations
+    version: u32,
+    /// SHA-256 of the file contents the history was saved with
+    content_hash: String,
+    /// Position in `entries` matching those contents
+    current_index: usize,
+    /// The edits, oldest first
+    entries: Vec<LogEntry>,
+}
+
+/// Path of the undo history file for `path`
+fn history_path(undo_dir: &Path, path: &Path) -> PathBuf {
+    undo_dir.join(format!("{}.json", encode_path_for_filename(path)))
+}
+
+fn content_hash(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+/// Keep the edits within `limits`, dropping the oldest undo steps first.
+/// Returns the new current index.
+fn apply_limits(
+    entries: &mut Vec<LogEntry>,
+    current_index: usize,
+    limits: UndoHistoryLimits,
+) -> usize {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let cutoff = now.saturating_sub(limits.max_age.as_millis() as u64);
+    let is_old = |entry: &LogEntry| entry.timestamp < cutoff;
+
+    // Undo steps are kept back from the current position until the first old one,
+    // redo steps forward from it
+    let start = entries[..current_index]
+        .iter()
+        .rposition(is_old)
+        .map_or(0, |i| i + 1);
+    let end = entries[current_index..]
+        .iter()
+        .position(is_old)
+        .map_or(entries.len(), |i| current_index + i);
+    entries.truncate(end);
+    entries.drain(..start);
+    let mut current_index = current_index - start;
+
+    let sizes: Vec<u64> = entries
+        .iter()
+        .map(|entry| serde_json::to_vec(entry).map_or(0, |json| json.len() as u64 + 1))
+        .collect();
+    let mut total: u64 = sizes.iter().sum();
+    let (mut first, mut last) = (0, entries.len());
+    while total > limits.max_bytes && first < last {
+        if first < current_index {
+            total -= sizes[first];
+            first += 1;
+        } else {
+            last -= 1;
+            total -= sizes[last];
+        }
+    }
+    entries.truncate(last);
+    entries.drain(..first);
+    current_index -= first;
+    current_index
+}
+
+/// Save the undo history of the file at `path` from its buffer's event log
+///
+/// The history is saved at the position where the buffer was last saved, so it
+/// matches the file on disk even if the buffer's changes are being discarded.
+/// A history with nothing to undo or redo removes the saved one.
+pub fn save(
+    undo_dir: &Path,
+    path: &Path,
+    log: &EventLog,
+    limits: UndoHistoryLimits,
+) -> io::Result<()> {
+    let history_path = history_path(undo_dir, path);
+    let Some((mut entries, current_index)) = log.saved_history() else {
+        return remove(&history_path);
+    };
+    let current_index = apply_limits(&mut entries, current_index, limits);
+    if entries.is_empty() {
+        return remove(&history_path);
+    }
+    let contents = match std::fs::read(path) {
+        Ok(contents) => contents,
+        Err(_) => return remove(&history_path),
+    };
+
+    let history = PersistedUndoHistory {
+        version: UNDO_HISTORY_VERSION,
+        content_hash: content_hash(&contents),
+        current_index,
+        entries,
+    };
+    let json = serde_json::to_string(&history).map_err(io::Error::other)?;
+
+    // Atomic write: temp file, then rename
+    std::fs::create_dir_all(undo_dir)?;
+    let temp_path = history_path.with_extension("json.tmp");
+    let mut file = std::fs::File::create(&temp_path)?;
+    file.write_all(json.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&temp_path, &history_path)
+}
+
+/// Load the saved undo history of the file at `path`, if it was saved for the
+/// file's current contents. A history that doesn't match them is removed.
+pub fn load(undo_dir: &Path, path: &Path, limits: UndoHistoryLimits) -> Option<EventLog> {
+    let history_path = history_path(undo_dir, path);
+    let json = std::fs::read_to_string(&history_path).ok()?;
+
+    let history = serde_json::from_str::<PersistedUndoHistory>(&json)
+        .ok()
+        .filter(|history| {
+            history.version <= UNDO_HISTORY_VERSION
+                && history.current_index <= history.entries.len()
+        });
+    let history = match (history, std::fs::read(path)) {
+        (Some(history), Ok(contents)) if history.content_hash == content_hash(&contents) => history,
+        _ => {
+            tracing::debug!("Dropping stale undo history for {:?}", path);
+            let _ = remove(&history_path);
+            return None;
+        }
+    };
+
+    let mut entries = history.entries;
+    let current_index = apply_limits(&mut entries, history.current_index, limits);
+    if entries.is_empty() {
+        let _ = remove(&history_path);
+        return None;
+    }
+    Some(EventLog::from_history(entries, current_index))
+}
+
+fn remove(history_path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(history_path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::event::{CursorId, Event};
+
+    const LIMITS: UndoHistoryLimits = UndoHistoryLimits {
+        max_bytes: 1024 * 1024,
+        max_age: Duration::from_secs(24 * 60 * 60),
+    };
+
+    fn insert(position: usize, text: &str) -> Event {
+        Event::Insert {
+            position,
+            text: text.to_string(),
+            cursor_id: CursorId(0),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let undo_dir = dir.path().join("undo");
+        let path = dir.path().join("notes.txt");
+
+        let mut log = EventLog::new();
+        log.append(insert(0, "hello"));
+        log.append(insert(5, " world"));
+        log.mark_saved();
+        std::fs::write(&path, "hello world").unwrap();
+        save(&undo_dir, &path, &log, LIMITS).unwrap();
+
+        let mut loaded = load(&undo_dir, &path, LIMITS).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded.is_at_saved_position());
+        let undone = loaded.undo();
+        assert!(matches!(&undone[..], [Event::Delete { range, .. }] if *range == (5..11)));
+    }
+
+    #[test]
+    fn test_unsaved_edits_are_kept_for_redo() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+
+        let mut log = EventLog::new();
+        log.append(insert(0, "a"));
+        log.mark_saved();
+        log.append(insert(1, "b"));
+        std::fs::write(&path, "a").unwrap();
+        save(dir.path(), &path, &log, LIMITS).unwrap();
+
+        let loaded = load(dir.path(), &path, LIMITS).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.current_index(), 1);
+        assert!(loaded.can_redo());
+    }
+
+    #[test]
+    fn test_changed_file_drops_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+
+        let mut log = EventLog::new();
+        log.append(insert(0, "a"));
+        log.mark_saved();
+        std::fs::write(&path, "a").unwrap();
+        save(dir.path(), &path, &log, LIMITS).unwrap();
+
+        std::fs::write(&path, "changed elsewhere").unwrap();
+        assert!(load(dir.path(), &path, LIMITS).is_none());
+        assert!(!history_path(dir.path(), &path).exists());
+    }
+
+    #[test]
+    fn test_limits_drop_oldest_undo_steps() {
+        let mut entries: Vec<LogEntry> = (0..10).map(|i| LogEntry::new(insert(i, "x"))).collect();
+        entries[0].timestamp = 0;
+        let current = apply_limits(&mut entries, 8, LIMITS);
+        assert_eq!((entries.len(), current), (9, 7));
+
+        let size = serde_json::to_vec(&entries[0]).unwrap().len() as u64 + 1;
+        let limits = UndoHistoryLimits {
+            max_bytes: size * 3,
+            ..LIMITS
+        };
+        let current = apply_limits(&mut entries, 7, limits);
+        assert_eq!((entries.len(), current), (3, 1));
+    }
+}
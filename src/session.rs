@@ -158,6 +158,23 @@ pub struct SerializedFileState {
 
     /// Scroll position (byte offset)
     pub scroll: SerializedScroll,
+
+    /// Folded regions
+    #[serde(default)]
+    pub folds: Vec<SerializedFold>,
+
+    /// Encoding the user picked for the file (display name), if not the detected one
+    #[serde(default)]
+    pub encoding: Option<String>,
+}
+
+/// A folded region (0-indexed lines)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedFold {
+    /// Line that stays visible above the fold
+    pub header_line: usize,
+    /// Last hidden line
+    pub end_line: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -861,6 +878,11 @@ mod tests {
                 top_view_line_offset: 2,
                 left_column: 10,
             },
+            folds: vec![SerializedFold {
+                header_line: 3,
+                end_line: 8,
+            }],
+            encoding: Some("Latin-1".to_string()),
         };
 
         let json = serde_json::to_string(&file_state).unwrap();
@@ -872,6 +894,7 @@ mod tests {
         assert_eq!(restored.additional_cursors.len(), 1);
         assert_eq!(restored.scroll.top_byte, 500);
         assert_eq!(restored.scroll.left_column, 10);
+        assert_eq!(restored.encoding.as_deref(), Some("Latin-1"));
     }
 
     #[test]
@@ -13,7 +13,9 @@ use crate::primitives::highlight_engine::HighlightEngine;
 use crate::primitives::highlighter::Language;
 use crate::primitives::indent::IndentCalculator;
 use crate::primitives::semantic_highlight::SemanticHighlighter;
+use crate::primitives::snippet::SnippetSession;
 use crate::primitives::text_property::TextPropertyManager;
+use crate::view::folding::FoldManager;
 use crate::view::margin::{MarginAnnotation, MarginContent, MarginManager, MarginPosition};
 use crate::view::overlay::{Overlay, OverlayFace, OverlayManager, UnderlineStyle};
 use crate::view::popup::{Popup, PopupContent, PopupListItem, PopupManager, PopupPosition};
@@ -59,6 +61,9 @@ pub struct EditorState {
     /// Virtual text manager for inline hints (type hints, parameter hints, etc.)
     pub virtual_texts: VirtualTextManager,
 
+    /// Folded (collapsed) regions and the buffer's foldable ranges
+    pub folds: FoldManager,
+
     /// Popups for floating windows (completion, documentation, etc.)
     pub popups: PopupManager,
 
@@ -108,6 +113,12 @@ pub struct EditorState {
     /// View mode for this buffer (Source or Compose)
     pub view_mode: ViewMode,
 
+    /// Hex view of the buffer's bytes (None when showing text)
+    pub hex_view: Option<crate::view::hex_view::HexView>,
+
+    /// Conflicts left in the result, when this is a merge view
+    pub merge_conflicts: Option<usize>,
+
     /// Debug mode: show highlight/overlay byte ranges
     /// When enabled, each character shows its byte position and highlight info
     pub debug_highlight_mode: bool,
@@ -126,6 +137,9 @@ pub struct EditorState {
 
     /// Debounced semantic highlight cache
     pub semantic_highlight_cache: SemanticHighlightCache,
+
+    /// Tabstops of the snippet being filled in, if any
+    pub snippet_session: Option<SnippetSession>,
 }
 
 impl EditorState {
@@ -142,6 +156,7 @@ impl EditorState {
             overlays: OverlayManager::new(),
             marker_list: MarkerList::new(),
             virtual_texts: VirtualTextManager::new(),
+            folds: FoldManager::new(),
             popups: PopupManager::new(),
             margins: MarginManager::new(),
             primary_cursor_line_number: LineNumber::Absolute(0), // Start at line 0
@@ -155,12 +170,15 @@ impl EditorState {
             tab_size: 4, // Default tab size
             semantic_highlighter: SemanticHighlighter::new(),
             view_mode: ViewMode::Source,
+            hex_view: None,
+            merge_conflicts: None,
             debug_highlight_mode: false,
             compose_width: None,
             compose_prev_line_numbers: None,
             compose_column_guides: None,
             view_transform: None,
             semantic_highlight_cache: SemanticHighlightCache::new(),
+            snippet_session: None,
         }
     }
 
@@ -237,6 +255,7 @@ impl EditorState {
             overlays: OverlayManager::new(),
             marker_list,
             virtual_texts: VirtualTextManager::new(),
+            folds: FoldManager::new(),
             popups: PopupManager::new(),
             margins: MarginManager::new(),
             primary_cursor_line_number: LineNumber::Absolute(0), // Start at line 0
@@ -250,12 +269,15 @@ impl EditorState {
             tab_size: 4, // Default tab size
             semantic_highlighter,
             view_mode: ViewMode::Source,
+            hex_view: None,
+            merge_conflicts: None,
             debug_highlight_mode: false,
             compose_width: None,
             compose_prev_line_numbers: None,
             compose_column_guides: None,
             view_transform: None,
             semantic_highlight_cache: SemanticHighlightCache::new(),
+            snippet_session: None,
         })
     }
 
@@ -267,14 +289,44 @@ impl EditorState {
     /// Note: width/height parameters are kept for backward compatibility but
     /// are no longer used - viewport is now owned by SplitViewState.
     pub fn from_file_with_languages(
+        path: &std::path::Path,
+        width: u16,
+        height: u16,
+        large_file_threshold: usize,
+        registry: &GrammarRegistry,
+        languages: &std::collections::HashMap<String, crate::config::LanguageConfig>,
+    ) -> std::io::Result<Self> {
+        Self::from_file_with_encoding(
+            path,
+            width,
+            height,
+            large_file_threshold,
+            registry,
+            languages,
+            None,
+            None,
+        )
+    }
+
+    /// Create an editor state from a file in the given encoding (detected if None)
+    ///
+    /// The file is read from `fs` if given, the editor's filesystem backend,
+    /// and from the local filesystem otherwise.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_file_with_encoding(
         path: &std::path::Path,
         _width: u16,
         _height: u16,
         large_file_threshold: usize,
         registry: &GrammarRegistry,
         languages: &std::collections::HashMap<String, crate::config::LanguageConfig>,
+        encoding: Option<crate::model::encoding::Encoding>,
+        fs: Option<crate::services::fs::BlockingFs>,
     ) -> std::io::Result<Self> {
-        let buffer = Buffer::load_from_file(path, large_file_threshold)?;
+        let buffer = match fs {
+            Some(fs) => Buffer::load_from_backend(fs, path, large_file_threshold, encoding)?,
+            None => Buffer::load_from_file_with_encoding(path, large_file_threshold, encoding)?,
+        };
 
         // Create highlighter using HighlightEngine with language config
         let highlighter = HighlightEngine::for_file_with_languages(path, registry, languages);
@@ -285,7 +337,7 @@ impl EditorState {
         );
 
         // Initialize semantic highlighter with language if available
-        let language = Language::from_path(path);
+        let language = Language::from_path_with_languages(path, languages);
         let mut semantic_highlighter = SemanticHighlighter::new();
         if let Some(lang) = language {
             semantic_highlighter.set_language(&lang);
@@ -309,6 +361,7 @@ impl EditorState {
             overlays: OverlayManager::new(),
             marker_list,
             virtual_texts: VirtualTextManager::new(),
+            folds: FoldManager::new(),
             popups: PopupManager::new(),
             margins: MarginManager::new(),
             primary_cursor_line_number: LineNumber::Absolute(0), // Start at line 0
@@ -322,12 +375,15 @@ impl EditorState {
             tab_size: 4, // Default tab size
             semantic_highlighter,
             view_mode: ViewMode::Source,
+            hex_view: None,
+            merge_conflicts: None,
             debug_highlight_mode: false,
             compose_width: None,
             compose_prev_line_numbers: None,
             compose_column_guides: None,
             view_transform: None,
             semantic_highlight_cache: SemanticHighlightCache::new(),
+            snippet_session: None,
         })
     }
 
@@ -343,9 +399,16 @@ impl EditorState {
         // CRITICAL: Adjust markers BEFORE modifying buffer
         self.marker_list.adjust_for_insert(position, text.len());
         self.margins.adjust_for_insert(position, text.len());
+        if let Some(session) = &mut self.snippet_session {
+            session.adjust_for_edit(position, 0, text.len());
+        }
 
         // Insert text into buffer
         self.buffer.insert(position, text);
+        if !self.folds.is_empty() {
+            self.folds
+                .remove_invalid(&mut self.marker_list, &self.buffer);
+        }
 
         // Invalidate highlight cache for edited range
         self.highlighter
@@ -388,9 +451,16 @@ impl EditorState {
         // CRITICAL: Adjust markers BEFORE modifying buffer
         self.marker_list.adjust_for_delete(range.start, len);
         self.margins.adjust_for_delete(range.start, len);
+        if let Some(session) = &mut self.snippet_session {
+            session.adjust_for_edit(range.start, len, 0);
+        }
 
         // Delete from buffer
         self.buffer.delete(range.clone());
+        if !self.folds.is_empty() {
+            self.folds
+                .remove_invalid(&mut self.marker_list, &self.buffer);
+        }
 
         // Invalidate highlight cache for edited range
         self.highlighter.invalidate_range(range.clone());
@@ -687,6 +757,10 @@ impl EditorState {
                 if let Some(tree) = new_tree {
                     self.buffer.restore_piece_tree(tree);
                 }
+                if !self.folds.is_empty() {
+                    self.folds
+                        .remove_invalid(&mut self.marker_list, &self.buffer);
+                }
 
                 // Update cursor positions
                 for (cursor_id, position, anchor) in new_cursors {
@@ -727,6 +801,37 @@ impl EditorState {
         self.cursors.primary_mut()
     }
 
+    /// Show the buffer's bytes in the hex view
+    ///
+    /// Binary files, read-only as text, can be edited there byte by byte.
+    pub fn show_hex_view(&mut self) {
+        if self.hex_view.is_some() {
+            return;
+        }
+        self.hex_view = Some(crate::view::hex_view::HexView {
+            text_editing_disabled: self.editing_disabled,
+            ..Default::default()
+        });
+        if self.buffer.is_binary() {
+            self.editing_disabled = false;
+        }
+        let last_byte = self.buffer.len().saturating_sub(1);
+        let cursor = self.cursors.primary_mut();
+        cursor.position = cursor.position.min(last_byte);
+        cursor.anchor = None;
+    }
+
+    /// Go back from the hex view to showing the buffer as text
+    pub fn hide_hex_view(&mut self) {
+        if let Some(hex_view) = self.hex_view.take() {
+            self.editing_disabled = hex_view.text_editing_disabled;
+            let position = self
+                .buffer
+                .snap_to_char_boundary(self.cursors.primary().position);
+            self.cursors.primary_mut().position = position;
+        }
+    }
+
     /// Called when this buffer loses focus (e.g., switching to another buffer,
     /// opening a prompt, focusing file explorer, etc.)
     /// Dismisses transient popups like Hover and Signature Help.
@@ -0,0 +1,363 @@
+//! Collapsed (folded) regions of a buffer
+//!
+//! A fold hides whole lines below a header line. The hidden region is stored as
+//! a pair of markers: one at the start of the first hidden line and one on the
+//! last hidden byte (normally the newline ending the fold). Markers keep folds
+//! attached to their text while the buffer is edited; edits that break a
+//! fold's line boundaries (e.g. joining the header with the first hidden line)
+//! drop the fold.
+//!
+//! Rendering reads the hidden byte ranges synchronously each frame, so folds
+//! never touch the buffer content.
+
+use std::ops::Range;
+
+use crate::model::buffer::Buffer;
+use crate::model::marker::{MarkerId, MarkerList};
+use crate::primitives::folding::FoldRange;
+
+/// A collapsed region
+#[derive(Debug, Clone)]
+struct Fold {
+    /// Start of the first hidden line
+    start_marker: MarkerId,
+    /// Last hidden byte
+    ///
+    /// Markers move with text inserted at their position, so anchoring on the
+    /// last hidden byte (rather than the start of the next line) keeps text
+    /// typed at the start of the line after the fold outside of it.
+    end_marker: MarkerId,
+    /// Text shown after the header line while folded
+    collapsed_text: Option<String>,
+}
+
+impl Fold {
+    fn hidden(&self, marker_list: &MarkerList) -> Option<Range<usize>> {
+        let start = marker_list.get_position(self.start_marker)?;
+        let last = marker_list.get_position(self.end_marker)?;
+        Some(start..last + 1)
+    }
+}
+
+/// A fold resolved to byte positions, as used by rendering
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoldSpan {
+    /// Bytes of the hidden lines
+    pub hidden: Range<usize>,
+    /// Text shown after the header line while folded
+    pub collapsed_text: Option<String>,
+}
+
+/// Manages the folds and foldable ranges of a buffer
+#[derive(Debug, Default)]
+pub struct FoldManager {
+    folds: Vec<Fold>,
+    /// Foldable ranges last reported by the language server or tree-sitter
+    ranges: Vec<FoldRange>,
+}
+
+impl FoldManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether no region is currently folded
+    pub fn is_empty(&self) -> bool {
+        self.folds.is_empty()
+    }
+
+    /// Number of folded regions
+    pub fn len(&self) -> usize {
+        self.folds.len()
+    }
+
+    /// Foldable ranges from the last fold range computation
+    pub fn ranges(&self) -> &[FoldRange] {
+        &self.ranges
+    }
+
+    /// Replace the foldable ranges
+    pub fn set_ranges(&mut self, ranges: Vec<FoldRange>) {
+        self.ranges = ranges;
+    }
+
+    /// Fold the lines covered by `hidden` (must start and end on line boundaries)
+    ///
+    /// Returns false if the range is empty or already folded.
+    pub fn add(
+        &mut self,
+        marker_list: &mut MarkerList,
+        hidden: Range<usize>,
+        collapsed_text: Option<String>,
+    ) -> bool {
+        if hidden.is_empty() || self.spans(marker_list).iter().any(|s| s.hidden == hidden) {
+            return false;
+        }
+        let start_marker = marker_list.create(hidden.start, false);
+        let end_marker = marker_list.create(hidden.end - 1, true);
+        self.folds.push(Fold {
+            start_marker,
+            end_marker,
+            collapsed_text,
+        });
+        true
+    }
+
+    /// Fold the lines of `range` below its header
+    ///
+    /// Returns false if the range covers no existing line or is already folded.
+    pub fn fold_range(
+        &mut self,
+        marker_list: &mut MarkerList,
+        buffer: &Buffer,
+        range: &FoldRange,
+    ) -> bool {
+        match hidden_bytes_for_range(buffer, range) {
+            Some(hidden) => self.add(marker_list, hidden, range.collapsed_text.clone()),
+            None => false,
+        }
+    }
+
+    /// Folds as line ranges (header line and last hidden line), sorted by header
+    pub fn folded_ranges(&self, marker_list: &MarkerList, buffer: &Buffer) -> Vec<FoldRange> {
+        self.spans(marker_list)
+            .into_iter()
+            .map(|span| FoldRange {
+                start_line: buffer.get_line_number(span.hidden.start - 1),
+                end_line: buffer.get_line_number(span.hidden.end - 1),
+                collapsed_text: span.collapsed_text,
+            })
+            .collect()
+    }
+
+    /// All folds resolved to byte positions, sorted by start
+    pub fn spans(&self, marker_list: &MarkerList) -> Vec<FoldSpan> {
+        let mut spans: Vec<FoldSpan> = self
+            .folds
+            .iter()
+            .filter_map(|fold| {
+                let hidden = fold.hidden(marker_list)?;
+                (!hidden.is_empty()).then(|| FoldSpan {
+                    hidden,
+                    collapsed_text: fold.collapsed_text.clone(),
+                })
+            })
+            .collect();
+        spans.sort_by_key(|s| (s.hidden.start, std::cmp::Reverse(s.hidden.end)));
+        spans
+    }
+
+    /// Folds as displayed: nested and overlapping folds merged into the
+    /// outermost one, sorted by start
+    pub fn collapsed_spans(&self, marker_list: &MarkerList) -> Vec<FoldSpan> {
+        let mut merged: Vec<FoldSpan> = Vec::new();
+        for span in self.spans(marker_list) {
+            match merged.last_mut() {
+                Some(last) if span.hidden.start <= last.hidden.end => {
+                    last.hidden.end = last.hidden.end.max(span.hidden.end);
+                }
+                _ => merged.push(span),
+            }
+        }
+        merged
+    }
+
+    /// Hidden byte ranges (see [`Self::collapsed_spans`])
+    pub fn hidden_ranges(&self, marker_list: &MarkerList) -> Vec<Range<usize>> {
+        self.collapsed_spans(marker_list)
+            .into_iter()
+            .map(|span| span.hidden)
+            .collect()
+    }
+
+    /// Whether `position` lies in a hidden line
+    pub fn is_hidden(&self, marker_list: &MarkerList, position: usize) -> bool {
+        self.hidden_ranges(marker_list)
+            .iter()
+            .any(|r| r.contains(&position))
+    }
+
+    /// Unfold every fold hiding `position` (e.g. to reveal a search match)
+    ///
+    /// Returns the number of folds removed.
+    pub fn unfold_at(&mut self, marker_list: &mut MarkerList, position: usize) -> usize {
+        self.remove_where(marker_list, |hidden| hidden.contains(&position))
+    }
+
+    /// Unfold the fold(s) whose first hidden line starts at `hidden_start`
+    ///
+    /// Returns the number of folds removed.
+    pub fn unfold_starting_at(
+        &mut self,
+        marker_list: &mut MarkerList,
+        hidden_start: usize,
+    ) -> usize {
+        self.remove_where(marker_list, |hidden| hidden.start == hidden_start)
+    }
+
+    /// Remove all folds
+    pub fn clear(&mut self, marker_list: &mut MarkerList) {
+        for fold in self.folds.drain(..) {
+            marker_list.delete(fold.start_marker);
+            marker_list.delete(fold.end_marker);
+        }
+    }
+
+    /// Drop folds whose boundaries no longer sit on line starts after an edit
+    pub fn remove_invalid(&mut self, marker_list: &mut MarkerList, buffer: &Buffer) {
+        let len = buffer.len();
+        let is_line_start =
+            |pos: usize| pos > 0 && pos <= len && buffer.slice_bytes(pos - 1..pos) == b"\n";
+        let mut i = 0;
+        while i < self.folds.len() {
+            let valid = match self.folds[i].hidden(marker_list) {
+                Some(hidden) => {
+                    hidden.start < hidden.end
+                        && is_line_start(hidden.start)
+                        && (hidden.end == len || is_line_start(hidden.end))
+                }
+                None => false,
+            };
+            if valid {
+                i += 1;
+            } else {
+                let fold = self.folds.remove(i);
+                marker_list.delete(fold.start_marker);
+                marker_list.delete(fold.end_marker);
+            }
+        }
+    }
+
+    fn remove_where(
+        &mut self,
+        marker_list: &mut MarkerList,
+        pred: impl Fn(&Range<usize>) -> bool,
+    ) -> usize {
+        let before = self.folds.len();
+        self.folds.retain(|fold| {
+            let Some(hidden) = fold.hidden(marker_list) else {
+                return true;
+            };
+            if pred(&hidden) {
+                marker_list.delete(fold.start_marker);
+                marker_list.delete(fold.end_marker);
+                false
+            } else {
+                true
+            }
+        });
+        before - self.folds.len()
+    }
+}
+
+/// Byte range hidden when folding `range` in `buffer`, if it spans existing lines
+pub fn hidden_bytes_for_range(buffer: &Buffer, range: &FoldRange) -> Option<Range<usize>> {
+    let start = buffer.line_start_offset(range.start_line + 1)?;
+    let end = buffer
+        .line_start_offset(range.end_line + 1)
+        .unwrap_or_else(|| buffer.len());
+    (start < end).then_some(start..end)
+}
+
+/// Number of lines starting inside `hidden`
+pub fn hidden_line_count(buffer: &Buffer, hidden: &Range<usize>) -> usize {
+    let len = buffer.len();
+    let mut count = buffer
+        .get_line_number(hidden.end)
+        .saturating_sub(buffer.get_line_number(hidden.start));
+    // A fold reaching the end of a buffer without a trailing newline also
+    // hides the (unterminated) last line
+    if hidden.end >= len && len > 0 && buffer.slice_bytes(len - 1..len) != b"\n" {
+        count += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(text: &str) -> (Buffer, MarkerList) {
+        let buffer = Buffer::from_str_test(text);
+        let mut marker_list = MarkerList::new();
+        marker_list.adjust_for_insert(0, buffer.len());
+        (buffer, marker_list)
+    }
+
+    #[test]
+    fn test_add_and_merge_hidden_ranges() {
+        let (_, mut markers) = setup("a\nb\nc\nd\ne\n");
+        let mut folds = FoldManager::new();
+        assert!(folds.add(&mut markers, 2..8, None));
+        assert!(folds.add(&mut markers, 4..6, None));
+        assert!(!folds.add(&mut markers, 2..8, None));
+        assert_eq!(folds.len(), 2);
+        assert_eq!(folds.hidden_ranges(&markers), vec![2..8]);
+        assert!(folds.is_hidden(&markers, 5));
+        assert!(!folds.is_hidden(&markers, 8));
+    }
+
+    #[test]
+    fn test_unfold_at_and_starting_at() {
+        let (_, mut markers) = setup("a\nb\nc\nd\ne\n");
+        let mut folds = FoldManager::new();
+        folds.add(&mut markers, 2..8, None);
+        folds.add(&mut markers, 4..6, None);
+        assert_eq!(folds.unfold_starting_at(&mut markers, 4), 1);
+        assert_eq!(folds.hidden_ranges(&markers), vec![2..8]);
+        assert_eq!(folds.unfold_at(&mut markers, 3), 1);
+        assert!(folds.is_empty());
+    }
+
+    #[test]
+    fn test_folds_follow_edits() {
+        let (mut buffer, mut markers) = setup("a\nb\nc\nd\n");
+        let mut folds = FoldManager::new();
+        folds.add(&mut markers, 2..6, None);
+
+        // Insert a line above the fold
+        markers.adjust_for_insert(0, 2);
+        buffer.insert(0, "x\n");
+        folds.remove_invalid(&mut markers, &buffer);
+        assert_eq!(folds.hidden_ranges(&markers), vec![4..8]);
+
+        // Typing at the start of the line after the fold stays visible
+        markers.adjust_for_insert(8, 1);
+        buffer.insert(8, "y");
+        folds.remove_invalid(&mut markers, &buffer);
+        assert_eq!(folds.hidden_ranges(&markers), vec![4..8]);
+
+        // Joining the header with the first hidden line drops the fold
+        markers.adjust_for_delete(3, 1);
+        buffer.delete(3..4);
+        folds.remove_invalid(&mut markers, &buffer);
+        assert!(folds.is_empty());
+    }
+
+    #[test]
+    fn test_hidden_bytes_and_line_count() {
+        let (buffer, _) = setup("a\nb\nc\nd");
+        let range = FoldRange::new(0, 2);
+        let hidden = hidden_bytes_for_range(&buffer, &range).unwrap();
+        assert_eq!(hidden, 2..6);
+        assert_eq!(hidden_line_count(&buffer, &hidden), 2);
+
+        // Fold reaching the unterminated last line
+        let hidden = hidden_bytes_for_range(&buffer, &FoldRange::new(1, 3)).unwrap();
+        assert_eq!(hidden, 4..7);
+        assert_eq!(hidden_line_count(&buffer, &hidden), 2);
+    }
+
+    #[test]
+    fn test_fold_range_round_trip() {
+        let (buffer, mut markers) = setup("a\nb\nc\nd\ne\n");
+        let mut folds = FoldManager::new();
+        assert!(folds.fold_range(&mut markers, &buffer, &FoldRange::new(1, 3)));
+        assert!(folds.fold_range(&mut markers, &buffer, &FoldRange::new(0, 4)));
+        assert!(!folds.fold_range(&mut markers, &buffer, &FoldRange::new(4, 6)));
+        assert_eq!(
+            folds.folded_ranges(&markers, &buffer),
+            vec![FoldRange::new(0, 4), FoldRange::new(1, 3)]
+        );
+    }
+}
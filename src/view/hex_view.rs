@@ -0,0 +1,180 @@
+//! Hex view of a buffer
+//!
+//! Shows the buffer's bytes in rows of `BYTES_PER_ROW`, with the offset of
+//! each row, the bytes in hex and the same bytes as ASCII. The primary cursor
+//! is a byte offset; typing overwrites the nibble (or, in the ASCII column,
+//! the byte) under it, so edits never shift the rest of the file.
+//!
+//! Only the visible rows are read from the buffer, through its chunk loading,
+//! so multi-GB files can be viewed and edited without loading them.
+
+/// Bytes shown on each row
+pub const BYTES_PER_ROW: usize = 16;
+
+/// Hex view state of a buffer
+#[derive(Debug, Clone, Default)]
+pub struct HexView {
+    /// First row shown
+    pub top_row: usize,
+    /// Whether the cursor is on the low (second) nibble of its byte
+    pub low_nibble: bool,
+    /// Whether typing goes to the ASCII column instead of the hex column
+    pub ascii_column: bool,
+    /// The buffer's `editing_disabled` from before the hex view was shown
+    pub text_editing_disabled: bool,
+    /// Last searched byte pattern, for find next/previous
+    pub last_search: Option<Vec<u8>>,
+}
+
+impl HexView {
+    /// Scroll so that the row of `offset` is visible in `height` rows
+    pub fn scroll_to(&mut self, offset: usize, height: usize) {
+        let row = offset / BYTES_PER_ROW;
+        if row < self.top_row {
+            self.top_row = row;
+        } else if height > 0 && row >= self.top_row + height {
+            self.top_row = row + 1 - height;
+        }
+    }
+}
+
+/// Number of rows needed for `len` bytes (an empty buffer still has one)
+pub fn row_count(len: usize) -> usize {
+    len.div_ceil(BYTES_PER_ROW).max(1)
+}
+
+/// Number of hex digits the offsets of a `len` byte buffer are shown with
+pub fn offset_digits(len: usize) -> usize {
+    let last = len.saturating_sub(1);
+    let digits = (usize::BITS - last.leading_zeros()).div_ceil(4) as usize;
+    digits.max(8)
+}
+
+/// Column of byte `index` of a row within the hex column
+///
+/// Each byte takes three columns ("XX "), with an extra space after the
+/// first half of the row.
+pub fn hex_column(index: usize) -> usize {
+    index * 3 + index / (BYTES_PER_ROW / 2)
+}
+
+/// Width of the hex column, including the space after it
+pub const HEX_COLUMN_WIDTH: usize = BYTES_PER_ROW * 3 + 1;
+
+/// How a byte is shown in the ASCII column
+pub fn ascii_char(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+/// Replace the high or low nibble of `byte` with `digit`
+pub fn set_nibble(byte: u8, digit: u8, low: bool) -> u8 {
+    if low {
+        (byte & 0xF0) | digit
+    } else {
+        (byte & 0x0F) | (digit << 4)
+    }
+}
+
+/// Parse an offset to go to: decimal, hex with a `0x` prefix, or relative to
+/// `current` with a leading `+` or `-`
+pub fn parse_offset(input: &str, current: usize) -> Option<usize> {
+    let input = input.trim();
+    let (sign, number) = match input.as_bytes().first() {
+        Some(b'+') => (Some(true), &input[1..]),
+        Some(b'-') => (Some(false), &input[1..]),
+        _ => (None, input),
+    };
+    let number = number.trim();
+    let value = match number
+        .strip_prefix("0x")
+        .or_else(|| number.strip_prefix("0X"))
+    {
+        Some(hex) => usize::from_str_radix(hex, 16).ok()?,
+        None => number.parse().ok()?,
+    };
+    match sign {
+        Some(true) => current.checked_add(value),
+        Some(false) => current.checked_sub(value),
+        None => Some(value),
+    }
+}
+
+/// Parse a search pattern: text in double quotes is searched for as is,
+/// anything made of hex byte pairs (optionally separated by spaces, with an
+/// optional `0x` prefix) as those bytes, and anything else as text
+pub fn parse_search_pattern(input: &str) -> Option<Vec<u8>> {
+    if input.is_empty() {
+        return None;
+    }
+    if let Some(text) = input
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+    {
+        return (!text.is_empty()).then(|| text.as_bytes().to_vec());
+    }
+    let digits: String = input
+        .split_whitespace()
+        .map(|part| part.trim_start_matches("0x").trim_start_matches("0X"))
+        .collect();
+    let is_hex = !digits.is_empty()
+        && digits.len().is_multiple_of(2)
+        && digits.bytes().all(|b| b.is_ascii_hexdigit());
+    if is_hex {
+        (0..digits.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+            .collect()
+    } else {
+        Some(input.as_bytes().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(hex_column(0), 0);
+        assert_eq!(hex_column(7), 21);
+        assert_eq!(hex_column(8), 25);
+        assert_eq!(hex_column(15), 46);
+        assert_eq!(row_count(0), 1);
+        assert_eq!(row_count(17), 2);
+        assert_eq!(offset_digits(100), 8);
+        assert_eq!(offset_digits(0x1_0000_0001), 9);
+    }
+
+    #[test]
+    fn test_set_nibble() {
+        assert_eq!(set_nibble(0x12, 0xA, false), 0xA2);
+        assert_eq!(set_nibble(0x12, 0xA, true), 0x1A);
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("256", 0), Some(256));
+        assert_eq!(parse_offset("0x1F", 0), Some(31));
+        assert_eq!(parse_offset("+16", 4), Some(20));
+        assert_eq!(parse_offset("-8", 4), None);
+        assert_eq!(parse_offset("-2", 4), Some(2));
+        assert_eq!(parse_offset("1F", 0), None);
+    }
+
+    #[test]
+    fn test_parse_search_pattern() {
+        assert_eq!(
+            parse_search_pattern("89 50 4e 47"),
+            Some(vec![0x89, 0x50, 0x4E, 0x47])
+        );
+        assert_eq!(parse_search_pattern("0xCAFE"), Some(vec![0xCA, 0xFE]));
+        assert_eq!(parse_search_pattern("\"cafe\""), Some(b"cafe".to_vec()));
+        assert_eq!(parse_search_pattern("IHDR"), Some(b"IHDR".to_vec()));
+        assert_eq!(parse_search_pattern("abc"), Some(b"abc".to_vec()));
+        assert_eq!(parse_search_pattern(""), None);
+    }
+}
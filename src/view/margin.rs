@@ -2,6 +2,9 @@ use crate::model::marker::{MarkerId, MarkerList};
 use ratatui::style::{Color, Style};
 use std::collections::BTreeMap;
 
+/// Symbol shown in the indicator column of a line whose following lines are folded
+pub const FOLD_INDICATOR: &str = "▸";
+
 /// Position of a margin in the editor
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MarginPosition {
@@ -9,6 +9,8 @@ pub mod controls;
 pub mod dimming;
 pub mod file_browser_input;
 pub mod file_tree;
+pub mod folding;
+pub mod hex_view;
 pub mod margin;
 pub mod markdown;
 pub mod overlay;
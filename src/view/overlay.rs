@@ -246,6 +246,12 @@ impl OverlayManager {
         handle
     }
 
+    /// Add several overlays at once, sorting them only once
+    pub fn extend(&mut self, overlays: impl IntoIterator<Item = Overlay>) {
+        self.overlays.extend(overlays);
+        self.overlays.sort_by_key(|o| o.priority);
+    }
+
     /// Remove an overlay by its handle
     pub fn remove_by_handle(
         &mut self,
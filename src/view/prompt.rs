@@ -13,6 +13,8 @@ pub enum PromptType {
     OpenFile,
     /// Switch to a different project folder (change working directory)
     SwitchProject,
+    /// Choose a file to compare the current buffer with
+    CompareWithFile,
     /// Save current buffer to a new file
     SaveFileAs,
     /// Search for text in buffer
@@ -31,6 +33,14 @@ pub enum PromptType {
     Command,
     /// Go to a specific line number
     GotoLine,
+    /// Go to a byte offset in the hex view
+    HexGotoOffset,
+    /// Search for bytes or text in the hex view
+    HexSearch,
+    /// Go back in the buffer's history by steps or time (e.g. "5 min")
+    UndoEarlier,
+    /// Go forward in the buffer's history by steps or time
+    UndoLater,
     /// Choose an ANSI background file
     SetBackgroundFile,
     /// Set background blend ratio (0-1)
@@ -46,10 +56,18 @@ pub enum PromptType {
         end_pos: usize,
         overlay_handle: crate::view::overlay::OverlayHandle,
     },
-    /// Record a macro - prompts for register (0-9)
+    /// Record a macro - prompts for register (0-9) or name
     RecordMacro,
-    /// Play a macro - prompts for register (0-9)
+    /// Play a macro - prompts for register (0-9) or name
     PlayMacro,
+    /// Open a macro in a buffer for editing (select from list)
+    EditMacro,
+    /// Play a macro on each line of the selection (select from list)
+    PlayMacroOnLines,
+    /// Pick the macro to play several times (select from list)
+    PlayMacroTimes,
+    /// Number of times to play the macro
+    MacroCount { name: String },
     /// Set a bookmark - prompts for register (0-9)
     SetBookmark,
     /// Jump to a bookmark - prompts for register (0-9)
@@ -60,6 +78,12 @@ pub enum PromptType {
     SetTabSize,
     /// Set line ending format for current buffer
     SetLineEnding,
+    /// Reload the current file in another encoding (select from list)
+    ReopenWithEncoding,
+    /// Save the current buffer in another encoding (select from list)
+    SaveWithEncoding,
+    /// Insert one of the user's snippets (select from list)
+    InsertSnippet,
     /// Stop a running LSP server (select from list)
     StopLspServer,
     /// Select a theme (select from list)
@@ -76,6 +100,10 @@ pub enum PromptType {
     ConfirmRevert,
     /// Confirm saving over a file that changed on disk
     ConfirmSaveConflict,
+    /// Confirm saving as root after the file couldn't be written
+    ConfirmSaveAsRoot,
+    /// Password for the helper that saves as root (input is hidden)
+    SaveAsRootPassword,
     /// Confirm overwriting an existing file during SaveAs
     ConfirmOverwriteFile { path: std::path::PathBuf },
     /// Confirm closing a modified buffer (save/discard/cancel)
@@ -85,6 +113,8 @@ pub enum PromptType {
     },
     /// Confirm quitting with modified buffers
     ConfirmQuitWithModified,
+    /// Allow what a plugin's manifest declares (y/o/n)
+    ConfirmPluginPermission,
     /// File Explorer rename operation
     /// Stores the original path and name for the file/directory being renamed
     FileExplorerRename {
@@ -98,6 +128,12 @@ pub enum PromptType {
     },
     /// Switch to a tab by name (from the current split's open buffers)
     SwitchToTab,
+    /// Jump to a symbol of the active buffer (value is the byte offset)
+    GotoSymbol,
+    /// Jump to a symbol found by the language servers (value is the result index)
+    WorkspaceSymbol,
+    /// Run one of the code lenses of the cursor line (value is the lens index)
+    CodeLens,
     /// Run shell command on buffer/selection
     /// If replace is true, replace the input with the output
     /// If replace is false, output goes to a new buffer
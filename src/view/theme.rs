@@ -1,5 +1,6 @@
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 
 /// Convert a ratatui Color to RGB values.
@@ -367,6 +368,9 @@ struct SyntaxColors {
     variable: ColorDef,
     constant: ColorDef,
     operator: ColorDef,
+    /// Semantic token colors keyed by "type", "type.modifier" or "*.modifier"
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    semantic: HashMap<String, ColorDef>,
 }
 
 /// Comprehensive theme structure with all UI colors
@@ -501,6 +505,9 @@ pub struct Theme {
     pub syntax_variable: Color,
     pub syntax_constant: Color,
     pub syntax_operator: Color,
+
+    /// Semantic token color overrides (see `semantic_token_color`)
+    pub syntax_semantic: HashMap<String, Color>,
 }
 
 impl From<ThemeFile> for Theme {
@@ -599,6 +606,12 @@ impl From<ThemeFile> for Theme {
             syntax_variable: file.syntax.variable.into(),
             syntax_constant: file.syntax.constant.into(),
             syntax_operator: file.syntax.operator.into(),
+            syntax_semantic: file
+                .syntax
+                .semantic
+                .into_iter()
+                .map(|(selector, color)| (selector, color.into()))
+                .collect(),
         }
     }
 }
@@ -769,6 +782,7 @@ impl Theme {
             syntax_variable: Color::Rgb(156, 220, 254),
             syntax_constant: Color::Rgb(79, 193, 255),
             syntax_operator: Color::Rgb(212, 212, 212),
+            syntax_semantic: HashMap::new(),
         }
     }
 
@@ -900,6 +914,7 @@ impl Theme {
             syntax_variable: Color::Rgb(0, 16, 128), // Dark blue variables
             syntax_constant: Color::Rgb(0, 112, 193), // Blue constants
             syntax_operator: Color::Rgb(0, 0, 0),    // Black operators
+            syntax_semantic: HashMap::new(),
         }
     }
 
@@ -1031,6 +1046,7 @@ impl Theme {
             syntax_variable: Color::White,
             syntax_constant: Color::LightBlue,
             syntax_operator: Color::White,
+            syntax_semantic: HashMap::new(),
         }
     }
 
@@ -1053,6 +1069,124 @@ impl Theme {
         }
     }
 
+    /// Theme color for a semantic token, if the theme overrides it
+    ///
+    /// Selectors are tried from most to least specific: "type.modifier",
+    /// "*.modifier", then "type".
+    pub fn semantic_token_color(&self, token_type: &str, modifiers: &[&str]) -> Option<Color> {
+        if self.syntax_semantic.is_empty() {
+            return None;
+        }
+        modifiers
+            .iter()
+            .find_map(|m| self.syntax_semantic.get(&format!("{}.{}", token_type, m)))
+            .or_else(|| {
+                modifiers
+                    .iter()
+                    .find_map(|m| self.syntax_semantic.get(&format!("*.{}", m)))
+            })
+            .or_else(|| self.syntax_semantic.get(token_type))
+            .copied()
+    }
+
+    /// Theme color for a key of the theme file, written as "section.name"
+    /// (e.g. "ui.status_bar_fg", "diagnostic.error_fg", "syntax.keyword")
+    pub fn color_by_key(&self, key: &str) -> Option<Color> {
+        let color = match key {
+            "editor.bg" => self.editor_bg,
+            "editor.fg" => self.editor_fg,
+            "editor.cursor" => self.cursor,
+            "editor.inactive_cursor" => self.inactive_cursor,
+            "editor.selection_bg" => self.selection_bg,
+            "editor.current_line_bg" => self.current_line_bg,
+            "editor.line_number_fg" => self.line_number_fg,
+            "editor.line_number_bg" => self.line_number_bg,
+            "editor.diff_add_bg" => self.diff_add_bg,
+            "editor.diff_remove_bg" => self.diff_remove_bg,
+            "editor.diff_modify_bg" => self.diff_modify_bg,
+            "ui.tab_active_fg" => self.tab_active_fg,
+            "ui.tab_active_bg" => self.tab_active_bg,
+            "ui.tab_inactive_fg" => self.tab_inactive_fg,
+            "ui.tab_inactive_bg" => self.tab_inactive_bg,
+            "ui.tab_separator_bg" => self.tab_separator_bg,
+            "ui.tab_close_hover_fg" => self.tab_close_hover_fg,
+            "ui.tab_hover_bg" => self.tab_hover_bg,
+            "ui.menu_bg" => self.menu_bg,
+            "ui.menu_fg" => self.menu_fg,
+            "ui.menu_active_bg" => self.menu_active_bg,
+            "ui.menu_active_fg" => self.menu_active_fg,
+            "ui.menu_dropdown_bg" => self.menu_dropdown_bg,
+            "ui.menu_dropdown_fg" => self.menu_dropdown_fg,
+            "ui.menu_highlight_bg" => self.menu_highlight_bg,
+            "ui.menu_highlight_fg" => self.menu_highlight_fg,
+            "ui.menu_border_fg" => self.menu_border_fg,
+            "ui.menu_separator_fg" => self.menu_separator_fg,
+            "ui.menu_hover_bg" => self.menu_hover_bg,
+            "ui.menu_hover_fg" => self.menu_hover_fg,
+            "ui.menu_disabled_fg" => self.menu_disabled_fg,
+            "ui.menu_disabled_bg" => self.menu_disabled_bg,
+            "ui.status_bar_fg" => self.status_bar_fg,
+            "ui.status_bar_bg" => self.status_bar_bg,
+            "ui.prompt_fg" => self.prompt_fg,
+            "ui.prompt_bg" => self.prompt_bg,
+            "ui.prompt_selection_fg" => self.prompt_selection_fg,
+            "ui.prompt_selection_bg" => self.prompt_selection_bg,
+            "ui.popup_border_fg" => self.popup_border_fg,
+            "ui.popup_bg" => self.popup_bg,
+            "ui.popup_selection_bg" => self.popup_selection_bg,
+            "ui.popup_text_fg" => self.popup_text_fg,
+            "ui.suggestion_bg" => self.suggestion_bg,
+            "ui.suggestion_selected_bg" => self.suggestion_selected_bg,
+            "ui.help_bg" => self.help_bg,
+            "ui.help_fg" => self.help_fg,
+            "ui.help_key_fg" => self.help_key_fg,
+            "ui.help_separator_fg" => self.help_separator_fg,
+            "ui.help_indicator_fg" => self.help_indicator_fg,
+            "ui.help_indicator_bg" => self.help_indicator_bg,
+            "ui.inline_code_bg" => self.inline_code_bg,
+            "ui.split_separator_fg" => self.split_separator_fg,
+            "ui.split_separator_hover_fg" => self.split_separator_hover_fg,
+            "ui.scrollbar_track_fg" => self.scrollbar_track_fg,
+            "ui.scrollbar_thumb_fg" => self.scrollbar_thumb_fg,
+            "ui.scrollbar_track_hover_fg" => self.scrollbar_track_hover_fg,
+            "ui.scrollbar_thumb_hover_fg" => self.scrollbar_thumb_hover_fg,
+            "ui.compose_margin_bg" => self.compose_margin_bg,
+            "ui.semantic_highlight_bg" => self.semantic_highlight_bg,
+            "ui.terminal_bg" => self.terminal_bg,
+            "ui.terminal_fg" => self.terminal_fg,
+            "ui.status_warning_indicator_bg" => self.status_warning_indicator_bg,
+            "ui.status_warning_indicator_fg" => self.status_warning_indicator_fg,
+            "ui.status_error_indicator_bg" => self.status_error_indicator_bg,
+            "ui.status_error_indicator_fg" => self.status_error_indicator_fg,
+            "ui.status_warning_indicator_hover_bg" => self.status_warning_indicator_hover_bg,
+            "ui.status_warning_indicator_hover_fg" => self.status_warning_indicator_hover_fg,
+            "ui.status_error_indicator_hover_bg" => self.status_error_indicator_hover_bg,
+            "ui.status_error_indicator_hover_fg" => self.status_error_indicator_hover_fg,
+            "ui.tab_drop_zone_bg" => self.tab_drop_zone_bg,
+            "ui.tab_drop_zone_border" => self.tab_drop_zone_border,
+            "search.match_bg" => self.search_match_bg,
+            "search.match_fg" => self.search_match_fg,
+            "diagnostic.error_fg" => self.diagnostic_error_fg,
+            "diagnostic.error_bg" => self.diagnostic_error_bg,
+            "diagnostic.warning_fg" => self.diagnostic_warning_fg,
+            "diagnostic.warning_bg" => self.diagnostic_warning_bg,
+            "diagnostic.info_fg" => self.diagnostic_info_fg,
+            "diagnostic.info_bg" => self.diagnostic_info_bg,
+            "diagnostic.hint_fg" => self.diagnostic_hint_fg,
+            "diagnostic.hint_bg" => self.diagnostic_hint_bg,
+            "syntax.keyword" => self.syntax_keyword,
+            "syntax.string" => self.syntax_string,
+            "syntax.comment" => self.syntax_comment,
+            "syntax.function" => self.syntax_function,
+            "syntax.type" => self.syntax_type,
+            "syntax.variable" => self.syntax_variable,
+            "syntax.constant" => self.syntax_constant,
+            "syntax.operator" => self.syntax_operator,
+            _ => return None,
+        };
+        Some(color)
+    }
+
     /// Get all available theme names (builtin + user themes)
     pub fn available_themes() -> Vec<String> {
         let mut themes: Vec<String> = vec![
@@ -1212,6 +1346,7 @@ impl Theme {
             syntax_variable: Color::Rgb(255, 255, 85), // Yellow variables
             syntax_constant: Color::Rgb(255, 0, 255),  // Bright magenta constants
             syntax_operator: Color::Rgb(170, 170, 170), // Light gray operators
+            syntax_semantic: HashMap::new(),
         }
     }
 
@@ -1275,6 +1410,21 @@ mod tests {
         assert_eq!(theme.name, "dark");
     }
 
+    #[test]
+    fn test_color_by_key() {
+        let theme = Theme::dark();
+        assert_eq!(
+            theme.color_by_key("ui.status_bar_bg"),
+            Some(theme.status_bar_bg)
+        );
+        assert_eq!(
+            theme.color_by_key("diagnostic.error_fg"),
+            Some(theme.diagnostic_error_fg)
+        );
+        assert_eq!(theme.color_by_key("syntax.type"), Some(theme.syntax_type));
+        assert_eq!(theme.color_by_key("status_bar_bg"), None);
+    }
+
     #[test]
     fn test_available_themes() {
         let themes = Theme::available_themes();
@@ -1302,4 +1452,32 @@ mod tests {
         let color: Color = ColorDef::Named("Reset".to_string()).into();
         assert_eq!(color, Color::Reset);
     }
+
+    #[test]
+    fn test_semantic_token_color_overrides() {
+        let mut theme = Theme::dark();
+        theme
+            .syntax_semantic
+            .insert("variable".to_string(), Color::Red);
+        theme
+            .syntax_semantic
+            .insert("*.deprecated".to_string(), Color::Gray);
+        theme
+            .syntax_semantic
+            .insert("variable.readonly".to_string(), Color::Blue);
+
+        assert_eq!(
+            theme.semantic_token_color("variable", &[]),
+            Some(Color::Red)
+        );
+        assert_eq!(
+            theme.semantic_token_color("variable", &["readonly"]),
+            Some(Color::Blue)
+        );
+        assert_eq!(
+            theme.semantic_token_color("function", &["deprecated"]),
+            Some(Color::Gray)
+        );
+        assert_eq!(theme.semantic_token_color("function", &[]), None);
+    }
 }
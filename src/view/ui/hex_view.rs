@@ -0,0 +1,133 @@
+//! Hex view rendering
+//!
+//! Renders a buffer shown in its hex view (see `view::hex_view`): each row
+//! has the offset of its first byte, the bytes in hex and the same bytes as
+//! ASCII. Only the visible rows are read from the buffer.
+
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use crate::state::EditorState;
+use crate::view::hex_view::{
+    ascii_char, hex_column, offset_digits, row_count, BYTES_PER_ROW, HEX_COLUMN_WIDTH,
+};
+use crate::view::theme::Theme;
+
+/// Renders the hex view of a buffer
+pub struct HexViewRenderer;
+
+impl HexViewRenderer {
+    /// Column of the first hex digit, after the offset and a gap
+    pub fn hex_start(len: usize) -> usize {
+        offset_digits(len) + 2
+    }
+
+    /// Column of the ASCII column, after the hex column and a gap
+    pub fn ascii_start(len: usize) -> usize {
+        Self::hex_start(len) + HEX_COLUMN_WIDTH + 1
+    }
+
+    /// Render the visible rows of `state`'s hex view into `area`
+    ///
+    /// Returns the number of rows and the first row shown, for the scrollbar.
+    pub fn render(
+        frame: &mut Frame,
+        area: Rect,
+        state: &mut EditorState,
+        theme: &Theme,
+        show_cursor: bool,
+    ) -> (usize, usize) {
+        let len = state.buffer.len();
+        let total_rows = row_count(len);
+        let height = area.height as usize;
+        let cursor = state.cursors.primary().position.min(len.saturating_sub(1));
+        let Some(hex) = state.hex_view.as_mut() else {
+            return (total_rows, 0);
+        };
+        hex.top_row = hex.top_row.min(total_rows.saturating_sub(height));
+        let (top_row, low_nibble, ascii_column) = (hex.top_row, hex.low_nibble, hex.ascii_column);
+
+        let start = top_row * BYTES_PER_ROW;
+        let bytes = match state
+            .buffer
+            .get_text_range_mut(start, height * BYTES_PER_ROW)
+        {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::error!("Failed to read bytes for the hex view: {}", e);
+                Vec::new()
+            }
+        };
+
+        let digits = offset_digits(len);
+        let text = Style::default().fg(theme.editor_fg).bg(theme.editor_bg);
+        let dim = Style::default()
+            .fg(theme.line_number_fg)
+            .bg(theme.editor_bg);
+        let under_cursor = |style: Style| style.bg(theme.selection_bg);
+
+        let mut lines = Vec::with_capacity(height);
+        let rows: Vec<&[u8]> = if bytes.is_empty() {
+            vec![&[]]
+        } else {
+            bytes.chunks(BYTES_PER_ROW).collect()
+        };
+        for (row, row_bytes) in rows.into_iter().enumerate() {
+            let row_offset = start + row * BYTES_PER_ROW;
+            let mut spans = vec![Span::styled(
+                format!("{:0digits$X}  ", row_offset, digits = digits),
+                dim,
+            )];
+
+            let mut hex_spans = Vec::with_capacity(BYTES_PER_ROW * 2);
+            let mut ascii = Vec::with_capacity(BYTES_PER_ROW);
+            for (i, &byte) in row_bytes.iter().enumerate() {
+                let style = if byte == 0 { dim } else { text };
+                let (hex_style, ascii_style) = if row_offset + i == cursor {
+                    (under_cursor(style), under_cursor(style))
+                } else {
+                    (style, style)
+                };
+                let gap = if i + 1 == BYTES_PER_ROW / 2 {
+                    "  "
+                } else {
+                    " "
+                };
+                hex_spans.push(Span::styled(format!("{:02X}", byte), hex_style));
+                hex_spans.push(Span::styled(gap, text));
+                ascii.push(Span::styled(ascii_char(byte).to_string(), ascii_style));
+            }
+            // Pad short rows so the ASCII column lines up
+            let hex_width = row_bytes.len() * 3 + usize::from(row_bytes.len() >= BYTES_PER_ROW / 2);
+            spans.extend(hex_spans);
+            spans.push(Span::styled(
+                " ".repeat(HEX_COLUMN_WIDTH + 1 - hex_width),
+                text,
+            ));
+            spans.extend(ascii);
+            lines.push(Line::from(spans));
+        }
+        frame.render_widget(Paragraph::new(lines).style(text), area);
+
+        let cursor_row = cursor / BYTES_PER_ROW;
+        if show_cursor && cursor_row >= top_row && cursor_row < top_row + height {
+            let index = cursor % BYTES_PER_ROW;
+            let column = if ascii_column {
+                Self::ascii_start(len) + index
+            } else {
+                Self::hex_start(len) + hex_column(index) + usize::from(low_nibble)
+            };
+            if column < area.width as usize {
+                frame.set_cursor_position((
+                    area.x + column as u16,
+                    area.y + (cursor_row - top_row) as u16,
+                ));
+            }
+        }
+
+        (total_rows, top_row)
+    }
+}
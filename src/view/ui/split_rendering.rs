@@ -12,7 +12,9 @@ use crate::primitives::ansi_background::AnsiBackground;
 use crate::primitives::display_width::char_width;
 use crate::services::plugins::api::ViewTransformPayload;
 use crate::state::{EditorState, ViewMode};
+use crate::view::folding::FoldSpan;
 use crate::view::split::SplitManager;
+use crate::view::ui::hex_view::HexViewRenderer;
 use crate::view::ui::tabs::TabsRenderer;
 use crate::view::ui::view_pipeline::{
     should_show_line_number, LineStart, ViewLine, ViewLineIterator,
@@ -326,6 +328,8 @@ struct DecorationContext {
     diagnostic_lines: HashSet<usize>,
     /// Line indicators indexed by line number (highest priority indicator per line)
     line_indicators: BTreeMap<usize, crate::view::margin::LineIndicator>,
+    /// Folded header lines in the viewport -> number of lines hidden below them
+    folded_lines: BTreeMap<usize, usize>,
 }
 
 struct LineRenderOutput {
@@ -407,6 +411,8 @@ struct LeftMarginContext<'a> {
     diagnostic_lines: &'a HashSet<usize>,
     /// Pre-computed line indicators (line_num -> indicator)
     line_indicators: &'a BTreeMap<usize, crate::view::margin::LineIndicator>,
+    /// Folded header lines (line_num -> hidden line count)
+    folded_lines: &'a BTreeMap<usize, usize>,
     /// Line number where the primary cursor is located (for relative line numbers)
     cursor_line: usize,
     /// Whether to show relative line numbers
@@ -441,6 +447,15 @@ fn render_left_margin(
             Style::default().fg(ratatui::style::Color::Red),
             None,
         );
+    } else if ctx.folded_lines.contains_key(&ctx.current_source_line_num) {
+        // Folded region below this line
+        push_span_with_map(
+            line_spans,
+            line_view_map,
+            crate::view::margin::FOLD_INDICATOR.to_string(),
+            Style::default().fg(ctx.theme.line_number_fg),
+            None,
+        );
     } else if let Some(indicator) = ctx.line_indicators.get(&ctx.current_source_line_num) {
         // Show line indicator (git gutter, breakpoints, etc.)
         push_span_with_map(
@@ -877,6 +892,50 @@ impl SplitRenderer {
                     continue;
                 }
 
+                // Buffers in their hex view show rows of bytes instead of lines
+                if state.hex_view.is_some() {
+                    if let Some(split_vs) = split_view_states
+                        .as_deref_mut()
+                        .and_then(|svs| svs.get_mut(&split_id))
+                    {
+                        if split_vs.viewport.width != layout.content_rect.width
+                            || split_vs.viewport.height != layout.content_rect.height
+                        {
+                            split_vs
+                                .viewport
+                                .resize(layout.content_rect.width, layout.content_rect.height);
+                        }
+                    }
+
+                    let show_cursor = is_active && state.show_cursors && !hide_cursor;
+                    let (total_rows, top_row) = HexViewRenderer::render(
+                        frame,
+                        layout.content_rect,
+                        state,
+                        theme,
+                        show_cursor,
+                    );
+                    Self::render_composite_scrollbar(
+                        frame,
+                        layout.scrollbar_rect,
+                        total_rows,
+                        top_row,
+                        layout.content_rect.height as usize,
+                        is_active,
+                    );
+
+                    view_line_mappings.insert(split_id, Vec::new());
+                    split_areas.push((
+                        split_id,
+                        buffer_id,
+                        layout.content_rect,
+                        layout.scrollbar_rect,
+                        0,
+                        0,
+                    ));
+                    continue;
+                }
+
                 // Get viewport from SplitViewState (authoritative source)
                 // We need to get it mutably for sync operations
                 // Use as_deref() to get Option<&HashMap> for read-only operations
@@ -1938,7 +1997,8 @@ impl SplitRenderer {
         let is_binary = state.buffer.is_binary();
         let line_ending = state.buffer.line_ending();
 
-        // Build base token stream from source
+        // Build base token stream from source, skipping folded lines
+        let folds = state.folds.collapsed_spans(&state.marker_list);
         let base_tokens = Self::build_base_tokens(
             &mut state.buffer,
             viewport.top_byte,
@@ -1946,6 +2006,7 @@ impl SplitRenderer {
             visible_count,
             is_binary,
             line_ending,
+            &folds,
         );
 
         // Use plugin transform if available, otherwise use base tokens
@@ -2093,6 +2154,7 @@ impl SplitRenderer {
         visible_count: usize,
         is_binary: bool,
         line_ending: crate::model::buffer::LineEnding,
+        folds: &[FoldSpan],
     ) -> Vec<crate::services::plugins::api::ViewTokenWire> {
         use crate::model::buffer::LineEnding;
         use crate::services::plugins::api::{ViewTokenWire, ViewTokenWireKind};
@@ -2113,9 +2175,25 @@ impl SplitRenderer {
         let mut iter = buffer.line_iterator(top_byte, estimated_line_length);
         let mut lines_seen = 0usize;
         let max_lines = visible_count.saturating_add(4);
+        // End of the last fold we skipped over (the iterator must move past it)
+        let mut skipped_until = 0usize;
 
         while lines_seen < max_lines {
             if let Some((line_start, line_content)) = iter.next() {
+                if line_start < skipped_until {
+                    // Nothing visible after a fold that reaches the end of the buffer
+                    break;
+                }
+                // Folded lines are not emitted: resume iteration after the fold
+                if let Some(fold) = folds.iter().find(|f| f.hidden.contains(&line_start)) {
+                    skipped_until = fold.hidden.end;
+                    iter = buffer.line_iterator(skipped_until, estimated_line_length);
+                    continue;
+                }
+                let header_fold = folds
+                    .iter()
+                    .find(|f| f.hidden.start == line_start + line_content.len());
+
                 let mut byte_offset = 0usize;
                 let content_bytes = line_content.as_bytes();
                 let mut skip_next_lf = false; // Track if we should skip \n after \r in CRLF
@@ -2149,6 +2227,9 @@ impl SplitRenderer {
                             let is_crlf_file = line_ending == LineEnding::CRLF;
                             let next_byte = content_bytes.get(byte_offset + 1);
                             if is_crlf_file && next_byte == Some(&b'\n') {
+                                if let Some(fold) = header_fold {
+                                    tokens.push(Self::fold_placeholder_token(fold));
+                                }
                                 // CRLF: emit Newline token at \r position for cursor visibility
                                 tokens.push(ViewTokenWire {
                                     source_offset,
@@ -2174,6 +2255,9 @@ impl SplitRenderer {
                             continue;
                         }
                         '\n' => {
+                            if let Some(fold) = header_fold {
+                                tokens.push(Self::fold_placeholder_token(fold));
+                            }
                             tokens.push(ViewTokenWire {
                                 source_offset,
                                 kind: ViewTokenWireKind::Newline,
@@ -2243,6 +2327,23 @@ impl SplitRenderer {
         tokens
     }
 
+    /// Token shown at the end of a fold header line in place of the hidden lines
+    fn fold_placeholder_token(fold: &FoldSpan) -> crate::services::plugins::api::ViewTokenWire {
+        use crate::services::plugins::api::{ViewTokenStyle, ViewTokenWire, ViewTokenWireKind};
+
+        let text = fold.collapsed_text.as_deref().unwrap_or("⋯");
+        ViewTokenWire {
+            source_offset: None,
+            kind: ViewTokenWireKind::Text(format!(" {}", text)),
+            style: Some(ViewTokenStyle {
+                fg: None,
+                bg: None,
+                bold: false,
+                italic: true,
+            }),
+        }
+    }
+
     /// Build tokens for binary files by reading raw bytes directly
     /// This preserves byte values >= 0x80 that would be lost by String::from_utf8_lossy
     fn build_base_tokens_binary(
@@ -2422,6 +2523,7 @@ impl SplitRenderer {
             visible_count,
             is_binary,
             line_ending,
+            &[],
         )
     }
 
@@ -2857,6 +2959,20 @@ impl SplitRenderer {
             |byte_offset| state.buffer.get_line_number(byte_offset),
         );
 
+        // Fold headers in the viewport and how many lines each one hides
+        let folded_lines: BTreeMap<usize, usize> = state
+            .folds
+            .collapsed_spans(&state.marker_list)
+            .iter()
+            .filter(|f| f.hidden.start > viewport_start && f.hidden.start <= viewport_end)
+            .map(|f| {
+                let header_line = state.buffer.get_line_number(f.hidden.start - 1);
+                let hidden_count =
+                    crate::view::folding::hidden_line_count(&state.buffer, &f.hidden);
+                (header_line, hidden_count)
+            })
+            .collect();
+
         DecorationContext {
             highlight_spans,
             semantic_spans,
@@ -2864,6 +2980,7 @@ impl SplitRenderer {
             virtual_text_lookup,
             diagnostic_lines,
             line_indicators,
+            folded_lines,
         }
     }
 
@@ -2873,13 +2990,27 @@ impl SplitRenderer {
         estimated_line_length: usize,
         visible_count: usize,
     ) -> usize {
+        let hidden = state.folds.hidden_ranges(&state.marker_list);
         let mut iter_temp = state
             .buffer
             .line_iterator(viewport_start, estimated_line_length);
         let mut viewport_end = viewport_start;
-        for _ in 0..visible_count {
+        let mut lines_seen = 0;
+        let mut skipped_until = 0;
+        while lines_seen < visible_count {
             if let Some((line_start, line_content)) = iter_temp.next() {
+                if line_start < skipped_until {
+                    break;
+                }
+                // Folded lines take no screen space
+                if let Some(range) = hidden.iter().find(|r| r.contains(&line_start)) {
+                    viewport_end = range.end;
+                    skipped_until = range.end;
+                    iter_temp = state.buffer.line_iterator(range.end, estimated_line_length);
+                    continue;
+                }
                 viewport_end = line_start + line_content.len();
+                lines_seen += 1;
             } else {
                 break;
             }
@@ -2921,6 +3052,7 @@ impl SplitRenderer {
         let virtual_text_lookup = &decorations.virtual_text_lookup;
         let diagnostic_lines = &decorations.diagnostic_lines;
         let line_indicators = &decorations.line_indicators;
+        let folded_lines = &decorations.folded_lines;
 
         let mut lines = Vec::new();
         let mut lines_rendered = 0usize;
@@ -2993,7 +3125,11 @@ impl SplitRenderer {
             // 2. The CURRENT line is also a source line
             // This ensures virtual/injected lines don't cause line numbers to skip
             if show_line_number && prev_was_source_line {
-                current_source_line_num += 1;
+                // Skip over lines hidden by a fold below the previous line
+                current_source_line_num += 1 + folded_lines
+                    .get(&current_source_line_num)
+                    .copied()
+                    .unwrap_or(0);
             }
             // Only update the flag when we see a source line - virtual lines
             // between source lines shouldn't reset the tracking
@@ -3029,6 +3165,7 @@ impl SplitRenderer {
                     estimated_lines,
                     diagnostic_lines,
                     line_indicators,
+                    folded_lines,
                     cursor_line,
                     relative_line_numbers,
                 },
@@ -3581,7 +3718,12 @@ impl SplitRenderer {
             if end.terminated_with_newline && lines_rendered < visible_line_count {
                 // Render the implicit line after the newline
                 let mut implicit_line_spans = Vec::new();
-                let implicit_line_num = current_source_line_num + 1;
+                let implicit_line_num = current_source_line_num
+                    + 1
+                    + folded_lines
+                        .get(&current_source_line_num)
+                        .copied()
+                        .unwrap_or(0);
 
                 if state.margins.left_config.enabled {
                     // Indicator column (space)
@@ -1,11 +1,14 @@
 //! Status bar and prompt/minibuffer rendering
 
+use std::collections::HashSet;
 use std::path::Path;
 
 use crate::app::WarningLevel;
+use crate::config::{StatusBarConfig, StatusBarSegment};
+use crate::model::encoding::Encoding;
 use crate::primitives::display_width::{char_width, str_width};
 use crate::state::EditorState;
-use crate::view::prompt::Prompt;
+use crate::view::prompt::{Prompt, PromptType};
 use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
@@ -20,10 +23,25 @@ pub struct StatusBarLayout {
     pub lsp_indicator: Option<(u16, u16, u16)>,
     /// Warning badge area (row, start_col, end_col) - None if no warnings
     pub warning_badge: Option<(u16, u16, u16)>,
+    /// Encoding indicator area (row, start_col, end_col)
+    pub encoding_indicator: Option<(u16, u16, u16)>,
     /// Line ending indicator area (row, start_col, end_col)
     pub line_ending_indicator: Option<(u16, u16, u16)>,
 }
 
+/// Editor state shown by status bar segments besides the active buffer
+#[derive(Debug, Clone, Default)]
+pub struct StatusBarInfo {
+    /// Language of the active buffer
+    pub language: Option<String>,
+    /// Branch checked out in the repository of the active buffer
+    pub git_branch: Option<String>,
+    /// Name of the macro being recorded
+    pub recording_macro: Option<String>,
+    /// Segments set by plugins (name, text), in the order they were added
+    pub plugin_segments: Vec<(String, String)>,
+}
+
 /// Status bar hover state for styling clickable indicators
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum StatusBarHover {
@@ -33,6 +51,8 @@ pub enum StatusBarHover {
     LspIndicator,
     /// Mouse is over the warning badge
     WarningBadge,
+    /// Mouse is over the encoding indicator
+    EncodingIndicator,
     /// Mouse is over the line ending indicator
     LineEndingIndicator,
 }
@@ -248,6 +268,8 @@ impl StatusBarRenderer {
     /// * `update_available` - Optional new version string if an update is available
     /// * `warning_level` - LSP warning level (for coloring LSP indicator)
     /// * `general_warning_count` - Number of general warnings (for badge display)
+    /// * `segments` - Segments shown on each side of the status bar
+    /// * `info` - Editor state shown by segments besides the active buffer
     ///
     /// # Returns
     /// Layout information with positions of clickable indicators
@@ -266,6 +288,8 @@ impl StatusBarRenderer {
         warning_level: WarningLevel,
         general_warning_count: usize,
         hover: StatusBarHover,
+        segments: &StatusBarConfig,
+        info: &StatusBarInfo,
     ) -> StatusBarLayout {
         Self::render_status(
             frame,
@@ -282,6 +306,8 @@ impl StatusBarRenderer {
             warning_level,
             general_warning_count,
             hover,
+            segments,
+            info,
         )
     }
 
@@ -294,6 +320,21 @@ impl StatusBarRenderer {
     ) {
         let base_style = Style::default().fg(theme.prompt_fg).bg(theme.prompt_bg);
 
+        // Show passwords as one '*' per character
+        let masked;
+        let prompt = if prompt.prompt_type == PromptType::SaveAsRootPassword {
+            let chars_before = |pos: usize| prompt.input[..pos].chars().count();
+            masked = Prompt {
+                input: "*".repeat(prompt.input.chars().count()),
+                cursor_pos: chars_before(prompt.cursor_pos),
+                selection_anchor: prompt.selection_anchor.map(chars_before),
+                ..prompt.clone()
+            };
+            &masked
+        } else {
+            prompt
+        };
+
         // Create spans for the prompt
         let mut spans = vec![Span::styled(prompt.message.clone(), base_style)];
 
@@ -463,37 +504,18 @@ impl StatusBarRenderer {
         warning_level: WarningLevel,
         general_warning_count: usize,
         hover: StatusBarHover,
+        segments: &StatusBarConfig,
+        info: &StatusBarInfo,
     ) -> StatusBarLayout {
         // Initialize layout tracking
         let mut layout = StatusBarLayout::default();
-        // Use the pre-computed display name from buffer metadata
-        let filename = display_name;
-
-        let modified = if state.buffer.is_modified() {
-            " [+]"
-        } else {
-            ""
-        };
 
         // Format chord state if present
-        let chord_display = if !chord_state.is_empty() {
-            let chord_str = chord_state
-                .iter()
-                .map(|(code, modifiers)| {
-                    crate::input::keybindings::format_keybinding(code, modifiers)
-                })
-                .collect::<Vec<_>>()
-                .join(" ");
-            format!(" [{}]", chord_str)
-        } else {
-            String::new()
-        };
-
-        // View mode indicator
-        let _mode_label = match state.view_mode {
-            crate::state::ViewMode::Compose => " | Compose",
-            _ => "",
-        };
+        let chord = chord_state
+            .iter()
+            .map(|(code, modifiers)| crate::input::keybindings::format_keybinding(code, modifiers))
+            .collect::<Vec<_>>()
+            .join(" ");
 
         let cursor = *state.primary_cursor();
 
@@ -529,110 +551,82 @@ impl StatusBarRenderer {
             }
         }
 
-        // Build diagnostics summary if there are any
-        let diagnostics_summary = if error_count + warning_count + info_count > 0 {
-            let mut parts = Vec::new();
-            if error_count > 0 {
-                parts.push(format!("E:{}", error_count));
-            }
-            if warning_count > 0 {
-                parts.push(format!("W:{}", warning_count));
-            }
-            if info_count > 0 {
-                parts.push(format!("I:{}", info_count));
+        // Selected bytes and lines over all cursors
+        let mut selection = (0, 0, 0);
+        for (_, cursor) in state.cursors.iter() {
+            if let Some(range) = cursor.selection_range().filter(|range| !range.is_empty()) {
+                let first = state.buffer.get_line_number(range.start);
+                let last = state.buffer.get_line_number(range.end);
+                selection.0 += 1;
+                selection.1 += last - first + 1;
+                selection.2 += range.len();
             }
-            format!(" | {}", parts.join(" "))
-        } else {
-            String::new()
-        };
-
-        // Build cursor count indicator (only show if multiple cursors)
-        let cursor_count_indicator = if state.cursors.count() > 1 {
-            format!(" | {}", t!("status.cursors", count = state.cursors.count()))
-        } else {
-            String::new()
-        };
+        }
 
         // Build status message parts
-        let mut message_parts: Vec<&str> = Vec::new();
+        let mut messages: Vec<&str> = Vec::new();
         if let Some(msg) = status_message {
             if !msg.is_empty() {
-                message_parts.push(msg);
+                messages.push(msg);
             }
         }
         if let Some(msg) = plugin_status_message {
             if !msg.is_empty() {
-                message_parts.push(msg);
+                messages.push(msg);
             }
         }
 
-        let message_suffix = if message_parts.is_empty() {
-            String::new()
-        } else {
-            format!(" | {}", message_parts.join(" | "))
-        };
-
-        // Build left status (file info, position, diagnostics, messages)
-        // Line and column are 0-indexed internally, but displayed as 1-indexed (standard editor convention)
-        let base_status = format!(
-            "{filename}{modified} | Ln {}, Col {}{diagnostics_summary}{cursor_count_indicator}",
-            line + 1,
-            col + 1
-        );
-
-        let left_status = format!("{base_status}{chord_display}{message_suffix}");
-
-        // Build right-side indicators (these stay fixed on the right)
-        // Order: [Line ending] [LSP indicator] [warning badge] [update] [Palette]
-
-        // Line ending indicator (clickable to change format)
-        let line_ending_text = format!(" {} ", state.buffer.line_ending().display_name());
-        let line_ending_width = str_width(&line_ending_text);
-
-        // LSP indicator (right-aligned, with colored background if warning/error)
-        let lsp_indicator = if !lsp_status.is_empty() {
-            format!(" {} ", lsp_status)
-        } else {
-            String::new()
-        };
-        let lsp_indicator_width = str_width(&lsp_indicator);
-
-        // General warning badge (right-aligned)
-        let warning_badge = if general_warning_count > 0 {
-            format!(" [⚠ {}] ", general_warning_count)
-        } else {
-            String::new()
-        };
-        let warning_badge_width = str_width(&warning_badge);
-
-        // Build update indicator for right side (if update available)
-        let update_indicator = update_available
-            .map(|version| format!(" {} ", t!("status.update_available", version = version)));
-        let update_width = update_indicator.as_ref().map(|s| s.len()).unwrap_or(0);
-
-        // Build Command Palette indicator for right side
-        // Always show Command Palette indicator on the right side
-        let cmd_palette_shortcut = keybindings
+        let palette_key = keybindings
             .get_keybinding_for_action(
                 &crate::input::keybindings::Action::CommandPalette,
                 crate::input::keybindings::KeyContext::Global,
             )
             .unwrap_or_else(|| "?".to_string());
-        let cmd_palette_indicator = t!("status.palette", shortcut = cmd_palette_shortcut);
-        let padded_cmd_palette = format!(" {} ", cmd_palette_indicator);
 
-        // Calculate available width and right side width
-        // Right side: [Line ending] [LSP indicator] [warning badge] [update] [Palette]
+        let values = SegmentValues {
+            filename: display_name,
+            modified: state.buffer.is_modified(),
+            line,
+            col,
+            offset: state.hex_view.as_ref().map(|_| cursor.position),
+            selection,
+            errors: error_count,
+            warnings: warning_count,
+            infos: info_count,
+            cursors: state.cursors.count(),
+            conflicts: state.merge_conflicts,
+            chord,
+            messages,
+            encoding: state.buffer.encoding(),
+            line_ending: state.buffer.line_ending().display_name(),
+            lsp_status,
+            warning_level,
+            general_warning_count,
+            update_available,
+            palette_key,
+            info,
+            placed: segments
+                .left
+                .iter()
+                .chain(&segments.center)
+                .chain(&segments.right)
+                .map(|segment| segment.segment.as_str())
+                .collect(),
+        };
+
+        let left = values.pieces(&segments.left, theme, hover, true);
+        let center = values.pieces(&segments.center, theme, hover, false);
+        let right = values.pieces(&segments.right, theme, hover, false);
+
+        let base_style = Style::default()
+            .fg(theme.status_bar_fg)
+            .bg(theme.status_bar_bg);
         let available_width = area.width as usize;
-        let cmd_palette_width = str_width(&padded_cmd_palette);
-        let right_side_width = line_ending_width
-            + lsp_indicator_width
-            + warning_badge_width
-            + update_width
-            + cmd_palette_width;
-
-        // Only show command palette indicator if there's enough space (at least 15 chars for minimal display)
-        let spans = if available_width >= 15 {
+        let right_side_width: usize = right.iter().map(Piece::width).sum();
+        let center_width: usize = center.iter().map(Piece::width).sum();
+
+        // Only show the right side if there's enough space (at least 15 chars for minimal display)
+        let mut pieces = if available_width >= 15 {
             // Reserve space for right side indicators
             let left_max_width = if available_width > right_side_width + 1 {
                 available_width - right_side_width - 1 // -1 for at least one space separator
@@ -640,235 +634,69 @@ impl StatusBarRenderer {
                 1 // Minimal space
             };
 
-            let mut spans = vec![];
-
-            // Truncate left status if it's too long (use visual width, not char count)
-            let left_visual_width = str_width(&left_status);
-            let displayed_left = if left_visual_width > left_max_width {
-                let truncate_at = left_max_width.saturating_sub(3); // -3 for "..."
-                if truncate_at > 0 {
-                    // Take characters up to visual width limit
-                    let mut width = 0;
-                    let truncated: String = left_status
-                        .chars()
-                        .take_while(|ch| {
-                            let w = char_width(*ch);
-                            if width + w <= truncate_at {
-                                width += w;
-                                true
-                            } else {
-                                false
-                            }
-                        })
-                        .collect();
-                    format!("{}...", truncated)
-                } else {
-                    String::from("...")
-                }
-            } else {
-                left_status.clone()
-            };
-
-            let displayed_left_len = str_width(&displayed_left);
-
-            spans.push(Span::styled(
-                displayed_left.clone(),
-                Style::default()
-                    .fg(theme.status_bar_fg)
-                    .bg(theme.status_bar_bg),
-            ));
-
-            // Add spacing to push right side indicators to the right
-            if displayed_left_len + right_side_width < available_width {
-                let padding_len = available_width - displayed_left_len - right_side_width;
-                spans.push(Span::styled(
-                    " ".repeat(padding_len),
-                    Style::default()
-                        .fg(theme.status_bar_fg)
-                        .bg(theme.status_bar_bg),
-                ));
-            } else if displayed_left_len < available_width {
-                // Add minimal space
-                spans.push(Span::styled(
-                    " ",
-                    Style::default()
-                        .fg(theme.status_bar_fg)
-                        .bg(theme.status_bar_bg),
-                ));
-            }
+            let mut pieces = truncate_pieces(left, left_max_width, base_style);
+            let displayed_left_len: usize = pieces.iter().map(Piece::width).sum();
 
-            // Track current column for layout positions
-            let mut current_col = area.x + displayed_left_len as u16;
+            // Add spacing to push right side indicators to the right, with
+            // the center segments in the middle if they fit
             if displayed_left_len + right_side_width < available_width {
-                current_col = area.x + (available_width - right_side_width) as u16;
-            }
-
-            // Add line ending indicator (clickable to change format)
-            {
-                let is_hovering = hover == StatusBarHover::LineEndingIndicator;
-                // Record position for click detection
-                layout.line_ending_indicator =
-                    Some((area.y, current_col, current_col + line_ending_width as u16));
-                let (fg, bg) = if is_hovering {
-                    (theme.menu_hover_fg, theme.menu_hover_bg)
-                } else {
-                    (theme.status_bar_fg, theme.status_bar_bg)
-                };
-                let mut style = Style::default().fg(fg).bg(bg);
-                if is_hovering {
-                    style = style.add_modifier(Modifier::UNDERLINED);
-                }
-                spans.push(Span::styled(line_ending_text.clone(), style));
-                current_col += line_ending_width as u16;
-            }
-
-            // Add LSP indicator with colored background if warning/error
-            if !lsp_indicator.is_empty() {
-                let is_hovering = hover == StatusBarHover::LspIndicator;
-                let (lsp_fg, lsp_bg) = match (warning_level, is_hovering) {
-                    (WarningLevel::Error, true) => (
-                        theme.status_error_indicator_hover_fg,
-                        theme.status_error_indicator_hover_bg,
-                    ),
-                    (WarningLevel::Error, false) => (
-                        theme.status_error_indicator_fg,
-                        theme.status_error_indicator_bg,
-                    ),
-                    (WarningLevel::Warning, true) => (
-                        theme.status_warning_indicator_hover_fg,
-                        theme.status_warning_indicator_hover_bg,
-                    ),
-                    (WarningLevel::Warning, false) => (
-                        theme.status_warning_indicator_fg,
-                        theme.status_warning_indicator_bg,
-                    ),
-                    (WarningLevel::None, _) => (theme.status_bar_fg, theme.status_bar_bg),
-                };
-                // Record LSP indicator position for click detection
-                layout.lsp_indicator = Some((
-                    area.y,
-                    current_col,
-                    current_col + lsp_indicator_width as u16,
-                ));
-                current_col += lsp_indicator_width as u16;
-                let mut style = Style::default().fg(lsp_fg).bg(lsp_bg);
-                if is_hovering && warning_level != WarningLevel::None {
-                    style = style.add_modifier(Modifier::UNDERLINED);
-                }
-                spans.push(Span::styled(lsp_indicator.clone(), style));
-            }
-
-            // Add general warning badge if there are warnings
-            if !warning_badge.is_empty() {
-                let is_hovering = hover == StatusBarHover::WarningBadge;
-                // Record warning badge position for click detection
-                layout.warning_badge = Some((
-                    area.y,
-                    current_col,
-                    current_col + warning_badge_width as u16,
-                ));
-                current_col += warning_badge_width as u16;
-                let (fg, bg) = if is_hovering {
-                    (
-                        theme.status_warning_indicator_hover_fg,
-                        theme.status_warning_indicator_hover_bg,
-                    )
+                let free = available_width - displayed_left_len - right_side_width;
+                if !center.is_empty() && center_width + 2 <= free {
+                    let centered = (available_width.saturating_sub(center_width)) / 2;
+                    let start = centered
+                        .max(displayed_left_len + 1)
+                        .min(available_width - right_side_width - center_width - 1);
+                    pieces.push(Piece::plain(
+                        " ".repeat(start - displayed_left_len),
+                        base_style,
+                    ));
+                    pieces.extend(center);
+                    pieces.push(Piece::plain(
+                        " ".repeat(free - center_width - (start - displayed_left_len)),
+                        base_style,
+                    ));
                 } else {
-                    (
-                        theme.status_warning_indicator_fg,
-                        theme.status_warning_indicator_bg,
-                    )
-                };
-                let mut style = Style::default().fg(fg).bg(bg);
-                if is_hovering {
-                    style = style.add_modifier(Modifier::UNDERLINED);
+                    pieces.push(Piece::plain(" ".repeat(free), base_style));
                 }
-                spans.push(Span::styled(warning_badge.clone(), style));
-            }
-            // Keep current_col in scope to avoid unused warning
-            let _ = current_col;
-
-            // Add update indicator if available (with highlighted styling)
-            if let Some(ref update_text) = update_indicator {
-                spans.push(Span::styled(
-                    update_text.clone(),
-                    Style::default()
-                        .fg(theme.menu_highlight_fg)
-                        .bg(theme.menu_dropdown_bg),
-                ));
+            } else if displayed_left_len < available_width {
+                // Add minimal space
+                pieces.push(Piece::plain(" ".to_string(), base_style));
             }
 
-            // Add command palette indicator with distinct styling and padding
-            spans.push(Span::styled(
-                padded_cmd_palette.clone(),
-                Style::default()
-                    .fg(theme.help_indicator_fg)
-                    .bg(theme.help_indicator_bg),
-            ));
-
-            spans
+            pieces.extend(right);
+            pieces
         } else {
-            // Terminal too narrow or no command palette indicator - fill entire width with left status
-            let mut spans = vec![];
-            let left_visual_width = str_width(&left_status);
-            let displayed_left = if left_visual_width > available_width {
-                let truncate_at = available_width.saturating_sub(3);
-                if truncate_at > 0 {
-                    // Take characters up to visual width limit
-                    let mut width = 0;
-                    let truncated: String = left_status
-                        .chars()
-                        .take_while(|ch| {
-                            let w = char_width(*ch);
-                            if width + w <= truncate_at {
-                                width += w;
-                                true
-                            } else {
-                                false
-                            }
-                        })
-                        .collect();
-                    format!("{}...", truncated)
-                } else {
-                    // Take characters up to available width
-                    let mut width = 0;
-                    left_status
-                        .chars()
-                        .take_while(|ch| {
-                            let w = char_width(*ch);
-                            if width + w <= available_width {
-                                width += w;
-                                true
-                            } else {
-                                false
-                            }
-                        })
-                        .collect()
-                }
-            } else {
-                left_status.clone()
-            };
-
-            spans.push(Span::styled(
-                displayed_left.clone(),
-                Style::default()
-                    .fg(theme.status_bar_fg)
-                    .bg(theme.status_bar_bg),
-            ));
-
-            // Fill remaining width
-            if displayed_left.len() < available_width {
-                spans.push(Span::styled(
-                    " ".repeat(available_width - displayed_left.len()),
-                    Style::default()
-                        .fg(theme.status_bar_fg)
-                        .bg(theme.status_bar_bg),
+            // Terminal too narrow - fill entire width with left status
+            let mut pieces = truncate_pieces(left, available_width, base_style);
+            let displayed_left_len: usize = pieces.iter().map(Piece::width).sum();
+            if displayed_left_len < available_width {
+                pieces.push(Piece::plain(
+                    " ".repeat(available_width - displayed_left_len),
+                    base_style,
                 ));
             }
-
-            spans
+            pieces
         };
 
+        // Record the positions of clickable indicators for click detection
+        let mut current_col = area.x;
+        for piece in &pieces {
+            let end_col = current_col.saturating_add(piece.width() as u16);
+            let position = Some((area.y, current_col, end_col));
+            match piece.indicator {
+                StatusBarHover::LspIndicator => layout.lsp_indicator = position,
+                StatusBarHover::WarningBadge => layout.warning_badge = position,
+                StatusBarHover::EncodingIndicator => layout.encoding_indicator = position,
+                StatusBarHover::LineEndingIndicator => layout.line_ending_indicator = position,
+                StatusBarHover::None => {}
+            }
+            current_col = end_col;
+        }
+
+        let spans: Vec<Span> = pieces
+            .drain(..)
+            .map(|piece| Span::styled(piece.text, piece.style))
+            .collect();
         let status_line = Paragraph::new(Line::from(spans));
 
         frame.render_widget(status_line, area);
@@ -1113,6 +941,425 @@ impl StatusBarRenderer {
     }
 }
 
+/// A piece of text of the status bar
+struct Piece {
+    text: String,
+    style: Style,
+    /// The clickable indicator the piece shows, if any
+    indicator: StatusBarHover,
+}
+
+impl Piece {
+    fn plain(text: String, style: Style) -> Self {
+        Self {
+            text,
+            style,
+            indicator: StatusBarHover::None,
+        }
+    }
+
+    fn width(&self) -> usize {
+        str_width(&self.text)
+    }
+}
+
+/// Truncate pieces to `max_width` columns, ending them with "..." when
+/// there's room for it
+fn truncate_pieces(pieces: Vec<Piece>, max_width: usize, ellipsis_style: Style) -> Vec<Piece> {
+    if pieces.iter().map(Piece::width).sum::<usize>() <= max_width {
+        return pieces;
+    }
+    let (budget, ellipsis) = if max_width > 3 {
+        (max_width - 3, true)
+    } else {
+        (max_width, false)
+    };
+
+    let mut truncated = Vec::new();
+    let mut width = 0;
+    for mut piece in pieces {
+        if width + piece.width() <= budget {
+            width += piece.width();
+            truncated.push(piece);
+            continue;
+        }
+        // Take characters up to the visual width limit
+        piece.text = piece
+            .text
+            .chars()
+            .take_while(|ch| {
+                let w = char_width(*ch);
+                if width + w <= budget {
+                    width += w;
+                    true
+                } else {
+                    false
+                }
+            })
+            .collect();
+        if !piece.text.is_empty() {
+            truncated.push(piece);
+        }
+        break;
+    }
+    if ellipsis {
+        truncated.push(Piece::plain("...".to_string(), ellipsis_style));
+    }
+    truncated
+}
+
+/// Text of a clock segment at `now`, with a strftime `format`
+///
+/// An invalid format shows nothing rather than panicking.
+pub(crate) fn clock_text(
+    now: chrono::DateTime<chrono::Local>,
+    format: Option<&str>,
+) -> Option<String> {
+    use std::fmt::Write;
+    let mut time = String::new();
+    write!(time, "{}", now.format(format.unwrap_or("%H:%M"))).ok()?;
+    Some(time)
+}
+
+/// Replace the `{name}` placeholders of a segment format with their values
+///
+/// Unknown placeholders are kept as they are.
+fn fill_format(format: &str, values: &[(&str, String)]) -> String {
+    let mut result = String::new();
+    let mut rest = format;
+    while let Some(open) = rest.find('{') {
+        result.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after.find('}').and_then(|close| {
+            let name = &after[..close];
+            values
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (value, close))
+        });
+        match value {
+            Some((value, close)) => {
+                result.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// What the segments of the status bar show in this frame
+struct SegmentValues<'a> {
+    filename: &'a str,
+    modified: bool,
+    line: usize,
+    col: usize,
+    /// Cursor offset, in the hex view
+    offset: Option<usize>,
+    /// Selections, selected lines and selected bytes
+    selection: (usize, usize, usize),
+    errors: usize,
+    warnings: usize,
+    infos: usize,
+    cursors: usize,
+    conflicts: Option<usize>,
+    chord: String,
+    messages: Vec<&'a str>,
+    encoding: Encoding,
+    line_ending: &'a str,
+    lsp_status: &'a str,
+    warning_level: WarningLevel,
+    general_warning_count: usize,
+    update_available: Option<&'a str>,
+    palette_key: String,
+    info: &'a StatusBarInfo,
+    /// Segments placed by the config, which the "plugins" segment leaves out
+    placed: HashSet<&'a str>,
+}
+
+impl SegmentValues<'_> {
+    /// Pieces of the segments of one side of the status bar
+    ///
+    /// Segments on the left are put after their separator, the others are
+    /// padded with a space on each side.
+    fn pieces(
+        &self,
+        segments: &[StatusBarSegment],
+        theme: &crate::view::theme::Theme,
+        hover: StatusBarHover,
+        left: bool,
+    ) -> Vec<Piece> {
+        let base_style = Style::default()
+            .fg(theme.status_bar_fg)
+            .bg(theme.status_bar_bg);
+        let mut pieces = Vec::new();
+        for segment in segments {
+            let Some(text) = self.text(segment).filter(|text| !text.is_empty()) else {
+                continue;
+            };
+            let indicator = match segment.segment.as_str() {
+                "lsp" => StatusBarHover::LspIndicator,
+                "warnings" => StatusBarHover::WarningBadge,
+                "encoding" => StatusBarHover::EncodingIndicator,
+                "line_ending" => StatusBarHover::LineEndingIndicator,
+                _ => StatusBarHover::None,
+            };
+            let hovered = indicator != StatusBarHover::None && hover == indicator;
+            let style = self.style(segment, theme, hovered);
+            let text = if left {
+                if !pieces.is_empty() {
+                    let default_separator = if segment.segment == "chord" {
+                        " "
+                    } else {
+                        " | "
+                    };
+                    let separator = segment.separator.as_deref().unwrap_or(default_separator);
+                    pieces.push(Piece::plain(separator.to_string(), base_style));
+                }
+                text
+            } else {
+                format!(" {} ", text)
+            };
+            pieces.push(Piece {
+                text,
+                style,
+                indicator,
+            });
+        }
+        pieces
+    }
+
+    /// Text of a segment, or None when it has nothing to show
+    fn text(&self, segment: &StatusBarSegment) -> Option<String> {
+        let format = segment.format.as_deref();
+        let text = |default: String, values: &[(&str, String)]| match format {
+            Some(format) => fill_format(format, values),
+            None => default,
+        };
+        match segment.segment.as_str() {
+            "file" => {
+                let modified = if self.modified { " [+]" } else { "" };
+                let name = self.filename.rsplit(['/', '\\']).next().unwrap_or_default();
+                Some(text(
+                    format!("{}{}", self.filename, modified),
+                    &[
+                        ("path", self.filename.to_string()),
+                        ("name", name.to_string()),
+                        ("modified", modified.to_string()),
+                    ],
+                ))
+            }
+            // Line and column are 0-indexed internally, but displayed as 1-indexed (standard editor convention)
+            // The hex view shows the cursor's byte offset instead of its line
+            "position" => {
+                let offset = self.offset.map(|offset| format!("0x{:X}", offset));
+                let default = match &offset {
+                    Some(offset) => format!("Offset {}", offset),
+                    None => format!("Ln {}, Col {}", self.line + 1, self.col + 1),
+                };
+                Some(text(
+                    default,
+                    &[
+                        ("line", (self.line + 1).to_string()),
+                        ("col", (self.col + 1).to_string()),
+                        ("offset", offset.unwrap_or_default()),
+                    ],
+                ))
+            }
+            "selection" => {
+                let (count, lines, bytes) = self.selection;
+                if count == 0 {
+                    return None;
+                }
+                let default = if lines > count {
+                    t!("status.selection_lines", lines = lines, bytes = bytes)
+                } else {
+                    t!("status.selection", bytes = bytes)
+                };
+                Some(text(
+                    default.to_string(),
+                    &[
+                        ("count", count.to_string()),
+                        ("lines", lines.to_string()),
+                        ("bytes", bytes.to_string()),
+                    ],
+                ))
+            }
+            // With a format the counts are always shown, even when zero
+            "diagnostics" => {
+                let counts = [
+                    ("errors", self.errors.to_string()),
+                    ("warnings", self.warnings.to_string()),
+                    ("infos", self.infos.to_string()),
+                ];
+                if let Some(format) = format {
+                    return Some(fill_format(format, &counts));
+                }
+                let parts: Vec<String> =
+                    [("E", self.errors), ("W", self.warnings), ("I", self.infos)]
+                        .into_iter()
+                        .filter(|(_, count)| *count > 0)
+                        .map(|(label, count)| format!("{}:{}", label, count))
+                        .collect();
+                Some(parts.join(" "))
+            }
+            // Only shown with multiple cursors
+            "cursors" => (self.cursors > 1).then(|| {
+                text(
+                    t!("status.cursors", count = self.cursors).to_string(),
+                    &[("count", self.cursors.to_string())],
+                )
+            }),
+            // Conflicts left in the result of a merge view
+            "conflicts" => self.conflicts.map(|count| {
+                text(
+                    t!("status.merge_conflicts", count = count).to_string(),
+                    &[("count", count.to_string())],
+                )
+            }),
+            "chord" => (!self.chord.is_empty())
+                .then(|| text(format!("[{}]", self.chord), &[("keys", self.chord.clone())])),
+            "messages" => (!self.messages.is_empty()).then(|| {
+                let message = self.messages.join(" | ");
+                text(message.clone(), &[("message", message)])
+            }),
+            "line_ending" => Some(text(
+                self.line_ending.to_string(),
+                &[("name", self.line_ending.to_string())],
+            )),
+            // Shown for files that are not plain UTF-8, or always with a format
+            "encoding" => {
+                let name = self.encoding.display_name().to_string();
+                match format {
+                    Some(format) => Some(fill_format(format, &[("name", name)])),
+                    None => (self.encoding != Encoding::Utf8).then_some(name),
+                }
+            }
+            "language" => self
+                .info
+                .language
+                .as_ref()
+                .map(|language| text(language.clone(), &[("name", language.clone())])),
+            "lsp" => (!self.lsp_status.is_empty()).then(|| {
+                text(
+                    self.lsp_status.to_string(),
+                    &[("status", self.lsp_status.to_string())],
+                )
+            }),
+            "git_branch" => self
+                .info
+                .git_branch
+                .as_ref()
+                .map(|branch| text(branch.clone(), &[("branch", branch.clone())])),
+            "macro" => self.info.recording_macro.as_ref().map(|name| {
+                text(
+                    t!("status.recording_macro", name = name).to_string(),
+                    &[("name", name.clone())],
+                )
+            }),
+            "clock" => clock_text(chrono::Local::now(), format),
+            "warnings" => (self.general_warning_count > 0).then(|| {
+                text(
+                    format!("[⚠ {}]", self.general_warning_count),
+                    &[("count", self.general_warning_count.to_string())],
+                )
+            }),
+            "update" => self.update_available.map(|version| {
+                text(
+                    t!("status.update_available", version = version).to_string(),
+                    &[("version", version.to_string())],
+                )
+            }),
+            "palette" => Some(text(
+                t!("status.palette", shortcut = &self.palette_key).to_string(),
+                &[("key", self.palette_key.clone())],
+            )),
+            // Plugin segments that the config doesn't place anywhere
+            "plugins" => {
+                let texts: Vec<&str> = self
+                    .info
+                    .plugin_segments
+                    .iter()
+                    .filter(|(name, text)| !self.placed.contains(name.as_str()) && !text.is_empty())
+                    .map(|(_, text)| text.as_str())
+                    .collect();
+                (!texts.is_empty()).then(|| texts.join(" | "))
+            }
+            name => self
+                .info
+                .plugin_segments
+                .iter()
+                .find(|(segment, _)| segment == name)
+                .map(|(_, segment_text)| {
+                    text(segment_text.clone(), &[("text", segment_text.clone())])
+                }),
+        }
+    }
+
+    /// Style of a segment: its built-in colors, replaced by the theme keys
+    /// of the segment unless the mouse is over it
+    fn style(
+        &self,
+        segment: &StatusBarSegment,
+        theme: &crate::view::theme::Theme,
+        hovered: bool,
+    ) -> Style {
+        let (fg, bg) = match segment.segment.as_str() {
+            "encoding" | "line_ending" if hovered => (theme.menu_hover_fg, theme.menu_hover_bg),
+            // LSP indicator with colored background if warning/error
+            "lsp" => match (self.warning_level, hovered) {
+                (WarningLevel::Error, true) => (
+                    theme.status_error_indicator_hover_fg,
+                    theme.status_error_indicator_hover_bg,
+                ),
+                (WarningLevel::Error, false) => (
+                    theme.status_error_indicator_fg,
+                    theme.status_error_indicator_bg,
+                ),
+                (WarningLevel::Warning, true) => (
+                    theme.status_warning_indicator_hover_fg,
+                    theme.status_warning_indicator_hover_bg,
+                ),
+                (WarningLevel::Warning, false) => (
+                    theme.status_warning_indicator_fg,
+                    theme.status_warning_indicator_bg,
+                ),
+                (WarningLevel::None, _) => (theme.status_bar_fg, theme.status_bar_bg),
+            },
+            "warnings" if hovered => (
+                theme.status_warning_indicator_hover_fg,
+                theme.status_warning_indicator_hover_bg,
+            ),
+            "warnings" => (
+                theme.status_warning_indicator_fg,
+                theme.status_warning_indicator_bg,
+            ),
+            "update" => (theme.menu_highlight_fg, theme.menu_dropdown_bg),
+            "palette" => (theme.help_indicator_fg, theme.help_indicator_bg),
+            _ => (theme.status_bar_fg, theme.status_bar_bg),
+        };
+        let mut style = Style::default().fg(fg).bg(bg);
+        if hovered {
+            if segment.segment != "lsp" || self.warning_level != WarningLevel::None {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            return style;
+        }
+        let color = |key: &Option<String>| key.as_deref().and_then(|key| theme.color_by_key(key));
+        if let Some(fg) = color(&segment.fg) {
+            style = style.fg(fg);
+        }
+        if let Some(bg) = color(&segment.bg) {
+            style = style.bg(bg);
+        }
+        style
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1202,4 +1449,39 @@ mod tests {
 
         assert_eq!(truncated.to_string_plain(), "/home/user/project");
     }
+
+    #[test]
+    fn test_fill_format() {
+        let values = [("line", "12".to_string()), ("col", "{col}".to_string())];
+        assert_eq!(
+            fill_format("Ln {line}, Col {col}", &values),
+            "Ln 12, Col {col}"
+        );
+        assert_eq!(fill_format("{unknown} {line", &values), "{unknown} {line");
+        assert_eq!(fill_format("no values", &values), "no values");
+    }
+
+    #[test]
+    fn test_truncate_pieces() {
+        let style = Style::default();
+        let pieces = || {
+            vec![
+                Piece::plain("main.rs".to_string(), style),
+                Piece::plain(" | ".to_string(), style),
+                Piece::plain("Ln 1, Col 1".to_string(), style),
+            ]
+        };
+        let text = |pieces: Vec<Piece>| {
+            pieces
+                .into_iter()
+                .map(|piece| piece.text)
+                .collect::<String>()
+        };
+        assert_eq!(
+            text(truncate_pieces(pieces(), 40, style)),
+            "main.rs | Ln 1, Col 1"
+        );
+        assert_eq!(text(truncate_pieces(pieces(), 12, style)), "main.rs |...");
+        assert_eq!(text(truncate_pieces(pieces(), 3, style)), "mai");
+    }
 }
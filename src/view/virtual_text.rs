@@ -92,6 +92,19 @@ pub struct VirtualText {
     pub namespace: Option<VirtualTextNamespace>,
 }
 
+/// How an inline virtual text added with `add_inline` is drawn
+#[derive(Debug, Clone)]
+pub struct InlineTextOptions {
+    /// Styling (typically dimmed/gray for hints)
+    pub style: Style,
+    /// BeforeChar or AfterChar
+    pub placement: VirtualTextPosition,
+    /// Namespace for bulk removal (e.g., "lsp-inlay-hints")
+    pub namespace: VirtualTextNamespace,
+    /// Ordering when multiple texts are at the same position
+    pub priority: i32,
+}
+
 /// Unique identifier for a virtual text entry
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct VirtualTextId(pub u64);
@@ -194,6 +207,48 @@ impl VirtualTextManager {
         id
     }
 
+    /// Add an inline virtual text (BeforeChar or AfterChar) with namespace for bulk removal
+    ///
+    /// Used for LSP inlay hints, which are replaced together on every response.
+    pub fn add_inline(
+        &mut self,
+        marker_list: &mut MarkerList,
+        position: usize,
+        text: String,
+        options: InlineTextOptions,
+    ) -> VirtualTextId {
+        let InlineTextOptions {
+            style,
+            placement,
+            namespace,
+            priority,
+        } = options;
+        debug_assert!(
+            placement.is_inline(),
+            "add_inline requires BeforeChar or AfterChar"
+        );
+
+        let marker_id = marker_list.create(position, false);
+
+        let id = VirtualTextId(self.next_id);
+        self.next_id += 1;
+
+        self.texts.insert(
+            id,
+            VirtualText {
+                marker_id,
+                text,
+                style,
+                position: placement,
+                priority,
+                string_id: None,
+                namespace: Some(namespace),
+            },
+        );
+
+        id
+    }
+
     /// Add a virtual line (LineAbove or LineBelow) with namespace for bulk removal
     ///
     /// This is the primary API for features like git blame headers.
@@ -242,6 +297,11 @@ impl VirtualTextManager {
         id
     }
 
+    /// Get a virtual text entry
+    pub fn get(&self, id: VirtualTextId) -> Option<&VirtualText> {
+        self.texts.get(&id)
+    }
+
     /// Remove a virtual text entry by its string identifier
     pub fn remove_by_id(&mut self, marker_list: &mut MarkerList, string_id: &str) -> bool {
         // Find the entry with matching string_id
@@ -837,4 +897,40 @@ mod tests {
         assert_eq!(before.unwrap().text, "/*param=*/");
         assert_eq!(after.unwrap().text, ": Type");
     }
+
+    #[test]
+    fn test_clear_namespace_keeps_other_texts() {
+        let mut marker_list = MarkerList::new();
+        let mut manager = VirtualTextManager::new();
+        let hints = VirtualTextNamespace::from_string("hints".to_string());
+        let lenses = VirtualTextNamespace::from_string("lenses".to_string());
+
+        manager.add_inline(
+            &mut marker_list,
+            10,
+            ": i32".to_string(),
+            InlineTextOptions {
+                style: hint_style(),
+                placement: VirtualTextPosition::AfterChar,
+                namespace: hints.clone(),
+                priority: 0,
+            },
+        );
+        manager.add_line(
+            &mut marker_list,
+            0,
+            "Run test".to_string(),
+            hint_style(),
+            VirtualTextPosition::LineAbove,
+            lenses,
+            0,
+        );
+
+        manager.clear_namespace(&mut marker_list, &hints);
+
+        assert_eq!(manager.len(), 1);
+        assert_eq!(marker_list.marker_count(), 1);
+        let lines = manager.query_lines_in_range(&marker_list, 0, 20);
+        assert_eq!(lines[0].1.text, "Run test");
+    }
 }
@@ -959,6 +959,876 @@ done
         std::path::PathBuf::from("/tmp/fake_lsp_log.txt")
     }
 
+    /// Spawn a fake LSP server that supports workspace symbols (workspace/symbol)
+    ///
+    /// The script takes the URI its symbols live in as first argument and a log
+    /// file as second argument. Each query and `$/cancelRequest` is logged, and
+    /// queries containing "slow" are answered after a delay so that the editor
+    /// gets a chance to cancel them. The `Engine` symbol is reported without a
+    /// range, which `workspaceSymbol/resolve` fills in.
+    pub fn spawn_with_workspace_symbols() -> std::io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+
+        // Create a Bash script that supports workspace symbols
+        let script = r#"#!/bin/bash
+
+SYMBOL_URI="$1"
+LOG_FILE="${2:-/tmp/fake_lsp_workspace_symbols_log.txt}"
+
+# Clear log file at start
+> "$LOG_FILE"
+
+# Function to read a message
+read_message() {
+    # Read headers
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        # Empty line marks end of headers
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    # Read content
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+# Function to send a message
+send_message() {
+    local message="$1"
+    local length=${#message}
+    echo -en "Content-Length: $length\r\n\r\n$message"
+}
+
+# Main loop
+while true; do
+    # Read incoming message
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    # Extract method from JSON
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+
+    case "$method" in
+        "initialize")
+            # Send initialize response with workspaceSymbolProvider capability
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"textDocumentSync":1,"workspaceSymbolProvider":{"resolveProvider":true}}}}'
+            ;;
+        "initialized")
+            # No response needed for notification
+            ;;
+        "textDocument/didOpen"|"textDocument/didChange"|"textDocument/didSave")
+            # No response for notifications
+            ;;
+        "workspace/symbol")
+            query=$(echo "$msg" | grep -o '"query":"[^"]*"' | cut -d'"' -f4)
+            echo "workspace/symbol $query" >> "$LOG_FILE"
+            case "$query" in
+                *slow*) sleep 1 ;;
+            esac
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":[{"name":"helper_function","kind":12,"location":{"uri":"'$SYMBOL_URI'","range":{"start":{"line":2,"character":3},"end":{"line":2,"character":18}}}},{"name":"slow_path","kind":12,"containerName":"Engine","location":{"uri":"'$SYMBOL_URI'","range":{"start":{"line":6,"character":7},"end":{"line":6,"character":16}}}},{"name":"Engine","kind":23,"location":{"uri":"'$SYMBOL_URI'"}}]}'
+            ;;
+        "workspaceSymbol/resolve")
+            echo "workspaceSymbol/resolve" >> "$LOG_FILE"
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"name":"Engine","kind":23,"location":{"uri":"'$SYMBOL_URI'","range":{"start":{"line":4,"character":5},"end":{"line":4,"character":11}}}}}'
+            ;;
+        "\$/cancelRequest")
+            echo "\$/cancelRequest" >> "$LOG_FILE"
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+        *)
+            # Answer other requests (e.g. inlay hints) with no result
+            if [ -n "$msg_id" ]; then
+                send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            fi
+            ;;
+    esac
+done
+"#;
+
+        // Write script to a temporary file
+        let script_path = Self::workspace_symbols_script_path();
+        std::fs::write(&script_path, script)?;
+
+        // Make it executable
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&script_path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(&script_path, perms)?;
+        }
+
+        let handle = Some(thread::spawn(move || {
+            // Wait for stop signal
+            let _ = stop_rx.recv();
+        }));
+
+        Ok(Self { handle, stop_tx })
+    }
+
+    /// Get the path to the workspace symbols fake LSP server script
+    pub fn workspace_symbols_script_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("fake_lsp_server_workspace_symbols.sh")
+    }
+
+    /// Spawn a fake LSP server that supports the call hierarchy
+    ///
+    /// The script takes the URI of the file it describes as first argument.
+    /// That file is expected to hold `main` calling `middle` calling `target`
+    /// calling `leaf`, one function after the other starting at line 0 (see
+    /// `tests/e2e/hierarchy.rs`).
+    pub fn spawn_with_call_hierarchy() -> std::io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+
+        // Create a Bash script that supports the call hierarchy
+        let script = r#"#!/bin/bash
+
+URI="$1"
+
+# Function to read a message
+read_message() {
+    # Read headers
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        # Empty line marks end of headers
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    # Read content
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+# Function to send a message
+send_message() {
+    local message="$1"
+    local length=${#message}
+    echo -en "Content-Length: $length\r\n\r\n$message"
+}
+
+# Hierarchy item for a function defined on a line: item NAME LINE
+item() {
+    echo '{"name":"'$1'","kind":12,"uri":"'$URI'","range":{"start":{"line":'$2',"character":0},"end":{"line":'$(($2 + 2))',"character":1}},"selectionRange":{"start":{"line":'$2',"character":3},"end":{"line":'$2',"character":'$((3 + ${#1}))'}},"data":{"fn":"'$1'"}}'
+}
+
+# A call of a function on a line: range LINE
+range() {
+    echo '{"start":{"line":'$1',"character":4},"end":{"line":'$1',"character":10}}'
+}
+
+# Main loop
+while true; do
+    # Read incoming message
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    # Extract method from JSON
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+    fn=$(echo "$msg" | grep -o '"fn":"[^"]*"' | cut -d'"' -f4)
+
+    case "$method" in
+        "initialize")
+            # Send initialize response with callHierarchyProvider capability
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"textDocumentSync":1,"callHierarchyProvider":true}}}'
+            ;;
+        "initialized")
+            # No response needed for notification
+            ;;
+        "textDocument/didOpen"|"textDocument/didChange"|"textDocument/didSave")
+            # No response for notifications
+            ;;
+        "textDocument/prepareCallHierarchy")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":['"$(item target 6)"']}'
+            ;;
+        "callHierarchy/incomingCalls")
+            case "$fn" in
+                target) result='[{"from":'"$(item middle 3)"',"fromRanges":['"$(range 4)"']}]' ;;
+                middle) result='[{"from":'"$(item main 0)"',"fromRanges":['"$(range 1)"']}]' ;;
+                *) result='[]' ;;
+            esac
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":'"$result"'}'
+            ;;
+        "callHierarchy/outgoingCalls")
+            case "$fn" in
+                target) result='[{"to":'"$(item leaf 9)"',"fromRanges":['"$(range 7)"']}]' ;;
+                *) result='[]' ;;
+            esac
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":'"$result"'}'
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+        *)
+            # Answer other requests (e.g. inlay hints) with no result
+            if [ -n "$msg_id" ]; then
+                send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            fi
+            ;;
+    esac
+done
+"#;
+
+        // Write script to a temporary file
+        let script_path = Self::call_hierarchy_script_path();
+        std::fs::write(&script_path, script)?;
+
+        // Make it executable
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&script_path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(&script_path, perms)?;
+        }
+
+        let handle = Some(thread::spawn(move || {
+            // Wait for stop signal
+            let _ = stop_rx.recv();
+        }));
+
+        Ok(Self { handle, stop_tx })
+    }
+
+    /// Get the path to the call hierarchy fake LSP server script
+    pub fn call_hierarchy_script_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("fake_lsp_server_call_hierarchy.sh")
+    }
+
+    /// Spawn a fake LSP server that formats documents
+    ///
+    /// - `textDocument/formatting` inserts a `// formatted` line at the top
+    /// - `textDocument/rangeFormatting` replaces the requested range with `RANGE`
+    /// - `textDocument/onTypeFormatting` (trigger `;`) prefixes the line with `>>`
+    pub fn spawn_with_formatting() -> std::io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+
+        // Create a Bash script that supports the formatting requests
+        let script = r#"#!/bin/bash
+
+# Function to read a message
+read_message() {
+    # Read headers
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        # Empty line marks end of headers
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    # Read content
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+# Function to send a message
+send_message() {
+    local message="$1"
+    # printf keeps the JSON escapes (\n) intact so the length stays correct
+    printf 'Content-Length: %d\r\n\r\n%s' "${#message}" "$message"
+}
+
+# Main loop
+while true; do
+    # Read incoming message
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    # Extract method from JSON
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+
+    case "$method" in
+        "initialize")
+            # Send initialize response with the formatting capabilities
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"textDocumentSync":1,"documentFormattingProvider":true,"documentRangeFormattingProvider":true,"documentOnTypeFormattingProvider":{"firstTriggerCharacter":";"}}}}'
+            ;;
+        "initialized")
+            # No response needed for notification
+            ;;
+        "textDocument/didOpen"|"textDocument/didChange"|"textDocument/didSave")
+            # No response for notifications
+            ;;
+        "textDocument/formatting")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":[{"range":{"start":{"line":0,"character":0},"end":{"line":0,"character":0}},"newText":"// formatted\n"}]}'
+            ;;
+        "textDocument/rangeFormatting")
+            range=$(echo "$msg" | grep -o '"range":{"start":{[^}]*},"end":{[^}]*}}')
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":[{'"$range"',"newText":"RANGE\n"}]}'
+            ;;
+        "textDocument/onTypeFormatting")
+            line=$(echo "$msg" | grep -o '"position":{[^}]*}' | grep -o '"line":[0-9]*' | cut -d':' -f2)
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":[{"range":{"start":{"line":'$line',"character":0},"end":{"line":'$line',"character":0}},"newText":">>"}]}'
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+        *)
+            # Answer other requests (e.g. inlay hints) with no result
+            if [ -n "$msg_id" ]; then
+                send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            fi
+            ;;
+    esac
+done
+"#;
+
+        // Write script to a temporary file
+        let script_path = Self::formatting_script_path();
+        std::fs::write(&script_path, script)?;
+
+        // Make it executable
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&script_path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(&script_path, perms)?;
+        }
+
+        let handle = Some(thread::spawn(move || {
+            // Wait for stop signal
+            let _ = stop_rx.recv();
+        }));
+
+        Ok(Self { handle, stop_tx })
+    }
+
+    /// Get the path to the formatting fake LSP server script
+    pub fn formatting_script_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("fake_lsp_server_formatting.sh")
+    }
+
+    /// Spawn a fake LSP server that sends `workspace/applyEdit` on save
+    ///
+    /// Saving `main.rs` asks the editor to insert a line into it, create and
+    /// fill `extracted.rs`, rename `old.rs` to `new.rs` and delete `gone.rs`.
+    /// Saving `fail.rs` sends an edit whose second change deletes a missing
+    /// file. Saving `link.rs` recursively deletes the symlink `linked`, and
+    /// saving `stale.rs` edits it at a document version it never had. The
+    /// editor's reply is written to the file given as first argument.
+    pub fn spawn_with_apply_edit() -> std::io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+
+        let script = r#"#!/bin/bash
+
+reply_log="$1"
+
+# Function to read a message
+read_message() {
+    # Read headers
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        # Empty line marks end of headers
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    # Read content
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+# Function to send a message
+send_message() {
+    local message="$1"
+    # printf keeps the JSON escapes (\n) intact so the length stays correct
+    printf 'Content-Length: %d\r\n\r\n%s' "${#message}" "$message"
+}
+
+# Text edit inserting $2 at the top of document $1
+insert_at_top() {
+    echo '{"textDocument":{"uri":"'$1'","version":null},"edits":[{"range":{"start":{"line":0,"character":0},"end":{"line":0,"character":0}},"newText":"'$2'"}]}'
+}
+
+# Main loop
+while true; do
+    # Read incoming message
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    # Extract method from JSON
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+
+    case "$method" in
+        "initialize")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"textDocumentSync":{"openClose":true,"change":1,"save":true}}}}'
+            ;;
+        "initialized"|"textDocument/didOpen"|"textDocument/didChange")
+            # No response for notifications
+            ;;
+        "textDocument/didSave")
+            uri=$(echo "$msg" | grep -o '"uri":"[^"]*"' | cut -d'"' -f4)
+            dir="${uri%/*}"
+            case "$uri" in
+                */main.rs)
+                    changes="$(insert_at_top "$uri" 'mod extracted;\n'),"
+                    changes+='{"kind":"create","uri":"'$dir'/extracted.rs"},'
+                    changes+="$(insert_at_top "$dir/extracted.rs" 'pub fn helper() {}\n'),"
+                    changes+='{"kind":"rename","oldUri":"'$dir'/old.rs","newUri":"'$dir'/new.rs"},'
+                    changes+='{"kind":"delete","uri":"'$dir'/gone.rs"}'
+                    ;;
+                */fail.rs)
+                    changes="$(insert_at_top "$uri" 'partial\n'),"
+                    changes+='{"kind":"delete","uri":"'$dir'/missing.rs"}'
+                    ;;
+                */link.rs)
+                    changes='{"kind":"delete","uri":"'$dir'/linked","options":{"recursive":true}}'
+                    ;;
+                */stale.rs)
+                    changes='{"textDocument":{"uri":"'$uri'","version":99},"edits":[{"range":{"start":{"line":0,"character":0},"end":{"line":0,"character":0}},"newText":"stale\n"}]}'
+                    ;;
+            esac
+            send_message '{"jsonrpc":"2.0","id":900,"method":"workspace/applyEdit","params":{"label":"Extract module","edit":{"documentChanges":['"$changes"']}}}'
+            ;;
+        "")
+            # The editor's reply to workspace/applyEdit
+            echo "$msg" > "$reply_log"
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+        *)
+            # Answer other requests (e.g. inlay hints) with no result
+            if [ -n "$msg_id" ]; then
+                send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            fi
+            ;;
+    esac
+done
+"#;
+
+        // Write script to a temporary file
+        let script_path = Self::apply_edit_script_path();
+        std::fs::write(&script_path, script)?;
+
+        // Make it executable
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&script_path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(&script_path, perms)?;
+        }
+
+        let handle = Some(thread::spawn(move || {
+            // Wait for stop signal
+            let _ = stop_rx.recv();
+        }));
+
+        Ok(Self { handle, stop_tx })
+    }
+
+    /// Get the path to the applyEdit fake LSP server script
+    pub fn apply_edit_script_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("fake_lsp_server_apply_edit.sh")
+    }
+
+    /// Spawn a fake LSP server that provides semantic tokens
+    ///
+    /// Every response marks `value` at the start of line 0 (column 4) with a
+    /// token: an enum member for whole documents and ranges, a function after
+    /// a delta. The semantic token methods it receives are appended to the
+    /// file given as first argument.
+    pub fn spawn_with_semantic_tokens() -> std::io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+
+        let script = r#"#!/bin/bash
+
+request_log="$1"
+
+# Function to read a message
+read_message() {
+    # Read headers
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        # Empty line marks end of headers
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    # Read content
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+# Function to send a message
+send_message() {
+    local message="$1"
+    printf 'Content-Length: %d\r\n\r\n%s' "${#message}" "$message"
+}
+
+# Main loop
+while true; do
+    # Read incoming message
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    # Extract method from JSON
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+
+    case "$method" in
+        "initialize")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"textDocumentSync":1,"semanticTokensProvider":{"legend":{"tokenTypes":["variable","function","enumMember"],"tokenModifiers":["readonly"]},"range":true,"full":{"delta":true}}}}}'
+            ;;
+        "initialized")
+            # No response needed for notification
+            ;;
+        "textDocument/didOpen"|"textDocument/didChange"|"textDocument/didSave")
+            # No response for notifications
+            ;;
+        "textDocument/semanticTokens/full"|"textDocument/semanticTokens/range")
+            echo "$method" >> "$request_log"
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"resultId":"1","data":[0,4,5,2,0]}}'
+            ;;
+        "textDocument/semanticTokens/full/delta")
+            echo "$method" >> "$request_log"
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"resultId":"2","edits":[{"start":0,"deleteCount":5,"data":[0,4,5,1,0]}]}}'
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+        *)
+            # Answer other requests (e.g. inlay hints) with no result
+            if [ -n "$msg_id" ]; then
+                send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            fi
+            ;;
+    esac
+done
+"#;
+
+        // Write script to a temporary file
+        let script_path = Self::semantic_tokens_script_path();
+        std::fs::write(&script_path, script)?;
+
+        // Make it executable
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&script_path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(&script_path, perms)?;
+        }
+
+        let handle = Some(thread::spawn(move || {
+            // Wait for stop signal
+            let _ = stop_rx.recv();
+        }));
+
+        Ok(Self { handle, stop_tx })
+    }
+
+    /// Get the path to the semantic tokens fake LSP server script
+    pub fn semantic_tokens_script_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("fake_lsp_server_semantic_tokens.sh")
+    }
+
+    /// Spawn a fake LSP server that provides code lenses
+    ///
+    /// Line 0 gets a resolved "Run test" lens (command `test.run`) and an
+    /// unresolved one that `codeLens/resolve` turns into "3 references"
+    /// (command `refs.show`, which the server does not provide). Running
+    /// `test.run` makes the server send `workspace/codeLens/refresh`, after
+    /// which the first lens reads "Run again". The code lens methods it
+    /// receives, and the commands it runs, are appended to the file given as
+    /// first argument.
+    pub fn spawn_with_code_lens() -> std::io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+
+        let script = r#"#!/bin/bash
+
+request_log="$1"
+run_title="Run test"
+
+# Function to read a message
+read_message() {
+    # Read headers
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        # Empty line marks end of headers
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    # Read content
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+# Function to send a message
+send_message() {
+    local message="$1"
+    printf 'Content-Length: %d\r\n\r\n%s' "${#message}" "$message"
+}
+
+range='{"start":{"line":0,"character":3},"end":{"line":0,"character":7}}'
+
+# Main loop
+while true; do
+    # Read incoming message
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    # Extract method from JSON
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+
+    case "$method" in
+        "initialize")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"textDocumentSync":1,"codeLensProvider":{"resolveProvider":true},"executeCommandProvider":{"commands":["test.run"]}}}}'
+            ;;
+        "initialized")
+            # No response needed for notification
+            ;;
+        "textDocument/didOpen"|"textDocument/didChange"|"textDocument/didSave")
+            # No response for notifications
+            ;;
+        "textDocument/codeLens")
+            echo "$method" >> "$request_log"
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":[{"range":'"$range"',"command":{"title":"'"$run_title"'","command":"test.run","arguments":["main"]}},{"range":'"$range"',"data":"references"}]}'
+            ;;
+        "codeLens/resolve")
+            echo "$method" >> "$request_log"
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"range":'"$range"',"command":{"title":"3 references","command":"refs.show"}}}'
+            ;;
+        "workspace/executeCommand")
+            command=$(echo "$msg" | grep -o '"command":"[^"]*"' | cut -d'"' -f4)
+            echo "$method $command" >> "$request_log"
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            run_title="Run again"
+            send_message '{"jsonrpc":"2.0","id":9001,"method":"workspace/codeLens/refresh"}'
+            ;;
+        "")
+            # Reply to our refresh request
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+        *)
+            # Answer other requests (e.g. inlay hints) with no result
+            if [ -n "$msg_id" ]; then
+                send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            fi
+            ;;
+    esac
+done
+"#;
+
+        // Write script to a temporary file
+        let script_path = Self::code_lens_script_path();
+        std::fs::write(&script_path, script)?;
+
+        // Make it executable
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&script_path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(&script_path, perms)?;
+        }
+
+        let handle = Some(thread::spawn(move || {
+            // Wait for stop signal
+            let _ = stop_rx.recv();
+        }));
+
+        Ok(Self { handle, stop_tx })
+    }
+
+    /// Get the path to the code lens fake LSP server script
+    pub fn code_lens_script_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("fake_lsp_server_code_lens.sh")
+    }
+
+    /// Spawn a fake LSP server that registers file watchers dynamically
+    ///
+    /// After initialization it registers a watcher for `**/*.toml` files. The
+    /// first change it is notified of makes it unregister that watcher and
+    /// register one for `**/*.lock` files instead. Notifications and the
+    /// client's replies to its requests are appended to the file given as
+    /// first argument.
+    pub fn spawn_with_watched_files() -> std::io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+
+        let script = r#"#!/bin/bash
+
+request_log="$1"
+swapped=0
+
+# Function to read a message
+read_message() {
+    # Read headers
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        # Empty line marks end of headers
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    # Read content
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+# Function to send a message
+send_message() {
+    local message="$1"
+    printf 'Content-Length: %d\r\n\r\n%s' "${#message}" "$message"
+}
+
+# Main loop
+while true; do
+    # Read incoming message
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    # Extract method from JSON
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+
+    case "$method" in
+        "initialize")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"textDocumentSync":1}}}'
+            ;;
+        "initialized")
+            send_message '{"jsonrpc":"2.0","id":9101,"method":"client/registerCapability","params":{"registrations":[{"id":"watch-toml","method":"workspace/didChangeWatchedFiles","registerOptions":{"watchers":[{"globPattern":"**/*.toml"}]}}]}}'
+            ;;
+        "workspace/didChangeWatchedFiles")
+            echo "$msg" >> "$request_log"
+            if [ "$swapped" = 0 ]; then
+                swapped=1
+                send_message '{"jsonrpc":"2.0","id":9102,"method":"client/unregisterCapability","params":{"unregisterations":[{"id":"watch-toml","method":"workspace/didChangeWatchedFiles"}]}}'
+                send_message '{"jsonrpc":"2.0","id":9103,"method":"client/registerCapability","params":{"registrations":[{"id":"watch-lock","method":"workspace/didChangeWatchedFiles","registerOptions":{"watchers":[{"globPattern":"**/*.lock","kind":1}]}}]}}'
+            fi
+            ;;
+        "")
+            # Reply to one of our requests
+            echo "reply $msg_id" >> "$request_log"
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+        *)
+            # Answer other requests (e.g. inlay hints) with no result
+            if [ -n "$msg_id" ]; then
+                send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            fi
+            ;;
+    esac
+done
+"#;
+
+        // Write script to a temporary file
+        let script_path = Self::watched_files_script_path();
+        std::fs::write(&script_path, script)?;
+
+        // Make it executable
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&script_path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(&script_path, perms)?;
+        }
+
+        let handle = Some(thread::spawn(move || {
+            // Wait for stop signal
+            let _ = stop_rx.recv();
+        }));
+
+        Ok(Self { handle, stop_tx })
+    }
+
+    /// Get the path to the watched files fake LSP server script
+    pub fn watched_files_script_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("fake_lsp_server_watched_files.sh")
+    }
+
     /// Stop the server
     pub fn stop(&mut self) {
         let _ = self.stop_tx.send(());
@@ -104,6 +104,14 @@ pub fn copy_plugin(plugins_dir: &Path, plugin_name: &str) {
         fs::copy(&i18n_src, &i18n_dest)
             .unwrap_or_else(|e| panic!("Failed to copy {}.i18n.json: {}", plugin_name, e));
     }
+
+    // Copy the .manifest.json file if it exists
+    let manifest_src = source_dir.join(format!("{}.manifest.json", plugin_name));
+    if manifest_src.exists() {
+        let manifest_dest = plugins_dir.join(format!("{}.manifest.json", plugin_name));
+        fs::copy(&manifest_src, &manifest_dest)
+            .unwrap_or_else(|e| panic!("Failed to copy {}.manifest.json: {}", plugin_name, e));
+    }
 }
 
 /// Copy the plugins/lib directory (contains TypeScript declarations like fresh.d.ts)
@@ -147,6 +155,8 @@ pub struct HarnessOptions {
     pub dir_context: Option<DirectoryContext>,
     /// Slow filesystem configuration for performance testing.
     pub slow_fs_config: Option<SlowFsConfig>,
+    /// Filesystem backend to use instead of the local one (e.g. SFTP).
+    pub fs_backend: Option<Arc<dyn FsBackend>>,
     /// Preserve the keybinding map from the config (don't force "default").
     /// Set this when testing a specific keymap like emacs.
     pub preserve_keybinding_map: bool,
@@ -164,6 +174,7 @@ impl HarnessOptions {
             create_empty_plugins_dir: true,
             dir_context: None,
             slow_fs_config: None,
+            fs_backend: None,
             preserve_keybinding_map: false,
         }
     }
@@ -218,6 +229,13 @@ impl HarnessOptions {
         self
     }
 
+    /// Use a custom filesystem backend, e.g. to test remote files.
+    /// A slow filesystem configuration is applied on top of it.
+    pub fn with_fs_backend(mut self, backend: Arc<dyn FsBackend>) -> Self {
+        self.fs_backend = Some(backend);
+        self
+    }
+
     /// Preserve the keybinding map from the config (don't force "default").
     /// Use this when testing a specific keymap like emacs or vscode.
     pub fn with_preserved_keybinding_map(mut self) -> Self {
@@ -375,6 +393,21 @@ impl EditorTestHarness {
         }
         config.check_for_updates = false; // Disable update checking in tests
 
+        // Plugins the test put in its project, or the embedded ones when it has
+        // no plugins directory, use files, processes and the environment
+        // without asking, unless the test configured plugin permissions itself
+        let loads_plugins = std::fs::read_dir(working_dir.join("plugins"))
+            .map_or(true, |mut entries| entries.next().is_some());
+        if loads_plugins && config.plugin_permissions.is_empty() {
+            config.plugin_permissions.insert(
+                "*".to_string(),
+                fresh::config::PluginPermissions {
+                    trusted: true,
+                    ..Default::default()
+                },
+            );
+        }
+
         // Initialize i18n with the config's locale before creating the editor
         // This ensures menu defaults are created with the correct translations
         fresh::i18n::init_with_config(config.locale.as_option());
@@ -385,12 +418,14 @@ impl EditorTestHarness {
             Option<Arc<dyn FsBackend>>,
             Option<Arc<tokio::sync::Mutex<BackendMetrics>>>,
         ) = if let Some(slow_config) = options.slow_fs_config {
-            let local_backend = Arc::new(LocalFsBackend::new());
-            let slow_backend = SlowFsBackend::new(local_backend, slow_config);
+            let inner_backend = options
+                .fs_backend
+                .unwrap_or_else(|| Arc::new(LocalFsBackend::new()));
+            let slow_backend = SlowFsBackend::new(inner_backend, slow_config);
             let metrics = slow_backend.metrics_arc();
             (Some(Arc::new(slow_backend)), Some(metrics))
         } else {
-            (None, None)
+            (options.fs_backend, None)
         };
 
         // Create terminal
@@ -17,6 +17,9 @@ pub mod harness;
 pub mod scrollbar;
 #[cfg(test)]
 #[allow(dead_code)]
+pub mod sftp_server;
+#[cfg(test)]
+#[allow(dead_code)]
 pub mod tracing;
 #[cfg(test)]
 #[allow(dead_code)]
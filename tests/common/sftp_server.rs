@@ -0,0 +1,406 @@
+//! A minimal SFTP (version 3) server for tests
+//!
+//! Serves the local filesystem over an in-process socket pair, so the SFTP
+//! client and backend can be tested without sshd. Paths are used as given,
+//! so tests should use absolute paths (e.g. in a temp dir).
+
+use fresh::services::fs::{FsBackend, SftpClient, SftpFsBackend};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const SSH_FXP_INIT: u8 = 1;
+const SSH_FXP_VERSION: u8 = 2;
+const SSH_FXP_OPEN: u8 = 3;
+const SSH_FXP_CLOSE: u8 = 4;
+const SSH_FXP_READ: u8 = 5;
+const SSH_FXP_WRITE: u8 = 6;
+const SSH_FXP_LSTAT: u8 = 7;
+const SSH_FXP_FSETSTAT: u8 = 10;
+const SSH_FXP_OPENDIR: u8 = 11;
+const SSH_FXP_READDIR: u8 = 12;
+const SSH_FXP_REMOVE: u8 = 13;
+const SSH_FXP_MKDIR: u8 = 14;
+const SSH_FXP_RMDIR: u8 = 15;
+const SSH_FXP_REALPATH: u8 = 16;
+const SSH_FXP_STAT: u8 = 17;
+const SSH_FXP_RENAME: u8 = 18;
+const SSH_FXP_STATUS: u8 = 101;
+const SSH_FXP_HANDLE: u8 = 102;
+const SSH_FXP_DATA: u8 = 103;
+const SSH_FXP_NAME: u8 = 104;
+const SSH_FXP_ATTRS: u8 = 105;
+const SSH_FXP_EXTENDED: u8 = 200;
+
+const SSH_FX_OK: u32 = 0;
+const SSH_FX_EOF: u32 = 1;
+const SSH_FX_NO_SUCH_FILE: u32 = 2;
+const SSH_FX_PERMISSION_DENIED: u32 = 3;
+const SSH_FX_FAILURE: u32 = 4;
+const SSH_FX_OP_UNSUPPORTED: u32 = 8;
+
+const SSH_FXF_READ: u32 = 0x01;
+const SSH_FXF_WRITE: u32 = 0x02;
+const SSH_FXF_CREAT: u32 = 0x08;
+const SSH_FXF_TRUNC: u32 = 0x10;
+
+const ATTR_SIZE: u32 = 0x01;
+const ATTR_UIDGID: u32 = 0x02;
+const ATTR_PERMISSIONS: u32 = 0x04;
+const ATTR_ACMODTIME: u32 = 0x08;
+
+const POSIX_RENAME: &[u8] = b"posix-rename@openssh.com";
+
+/// Server behaviour for tests
+#[derive(Debug, Clone, Copy)]
+pub struct SftpServerOptions {
+    /// Offer the `posix-rename@openssh.com` extension
+    pub posix_rename: bool,
+    /// Largest READ reply, to exercise short reads
+    pub max_read: usize,
+}
+
+impl Default for SftpServerOptions {
+    fn default() -> Self {
+        Self {
+            posix_rename: true,
+            max_read: usize::MAX,
+        }
+    }
+}
+
+/// A client connected to a new server thread
+pub fn sftp_client(options: SftpServerOptions) -> SftpClient {
+    let (client_end, server_end) = UnixStream::pair().expect("socket pair");
+    std::thread::spawn(move || {
+        let reader = server_end.try_clone().expect("clone socket");
+        // The connection closing ends the server
+        let _ = Server::new(options).serve(BufReader::new(reader), BufWriter::new(server_end));
+    });
+    let reader = client_end.try_clone().expect("clone socket");
+    SftpClient::over(reader, client_end).expect("SFTP handshake")
+}
+
+/// An SFTP filesystem backend connected to a new server thread
+pub fn sftp_backend() -> Arc<dyn FsBackend> {
+    Arc::new(SftpFsBackend::new(
+        sftp_client(SftpServerOptions::default()),
+    ))
+}
+
+enum Handle {
+    File(File),
+    Dir(Option<Vec<(String, std::fs::Metadata)>>),
+}
+
+struct Server {
+    options: SftpServerOptions,
+    handles: HashMap<Vec<u8>, Handle>,
+    next_handle: u32,
+}
+
+/// Permissions and owner to set on a file
+type SetAttrs = (Option<u32>, Option<(u32, u32)>);
+
+/// Reads the fields of a request
+struct Fields<'a>(&'a [u8]);
+
+impl<'a> Fields<'a> {
+    fn bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "short packet"));
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_be_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.bytes(len)
+    }
+
+    fn path(&mut self) -> io::Result<PathBuf> {
+        Ok(PathBuf::from(
+            String::from_utf8_lossy(self.string()?).into_owned(),
+        ))
+    }
+
+    /// Permissions and owner from an ATTRS field
+    fn attrs(&mut self) -> io::Result<SetAttrs> {
+        let flags = self.u32()?;
+        if flags & ATTR_SIZE != 0 {
+            self.u64()?;
+        }
+        let owner = if flags & ATTR_UIDGID != 0 {
+            Some((self.u32()?, self.u32()?))
+        } else {
+            None
+        };
+        let permissions = if flags & ATTR_PERMISSIONS != 0 {
+            Some(self.u32()?)
+        } else {
+            None
+        };
+        if flags & ATTR_ACMODTIME != 0 {
+            self.u64()?;
+        }
+        Ok((permissions, owner))
+    }
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_string(out: &mut Vec<u8>, value: &[u8]) {
+    put_u32(out, value.len() as u32);
+    out.extend_from_slice(value);
+}
+
+fn put_attrs(out: &mut Vec<u8>, metadata: &std::fs::Metadata) {
+    put_u32(
+        out,
+        ATTR_SIZE | ATTR_UIDGID | ATTR_PERMISSIONS | ATTR_ACMODTIME,
+    );
+    out.extend_from_slice(&metadata.len().to_be_bytes());
+    put_u32(out, metadata.uid());
+    put_u32(out, metadata.gid());
+    put_u32(out, metadata.mode());
+    put_u32(out, metadata.atime() as u32);
+    put_u32(out, metadata.mtime() as u32);
+}
+
+/// A reply: packet type and body after the request id
+type Reply = (u8, Vec<u8>);
+
+fn status(code: u32) -> Reply {
+    let mut body = Vec::new();
+    put_u32(&mut body, code);
+    put_string(&mut body, b"");
+    put_string(&mut body, b"");
+    (SSH_FXP_STATUS, body)
+}
+
+fn error_status(e: &io::Error) -> Reply {
+    status(match e.kind() {
+        io::ErrorKind::NotFound => SSH_FX_NO_SUCH_FILE,
+        io::ErrorKind::PermissionDenied => SSH_FX_PERMISSION_DENIED,
+        _ => SSH_FX_FAILURE,
+    })
+}
+
+fn done(result: io::Result<()>) -> Reply {
+    match result {
+        Ok(()) => status(SSH_FX_OK),
+        Err(e) => error_status(&e),
+    }
+}
+
+fn attrs_reply(result: io::Result<std::fs::Metadata>) -> Reply {
+    match result {
+        Ok(metadata) => {
+            let mut body = Vec::new();
+            put_attrs(&mut body, &metadata);
+            (SSH_FXP_ATTRS, body)
+        }
+        Err(e) => error_status(&e),
+    }
+}
+
+impl Server {
+    fn new(options: SftpServerOptions) -> Self {
+        Self {
+            options,
+            handles: HashMap::new(),
+            next_handle: 0,
+        }
+    }
+
+    fn serve(mut self, mut reader: impl Read, mut writer: impl Write) -> io::Result<()> {
+        loop {
+            let mut len = [0u8; 4];
+            reader.read_exact(&mut len)?;
+            let mut packet = vec![0u8; u32::from_be_bytes(len) as usize];
+            reader.read_exact(&mut packet)?;
+            let kind = packet[0];
+            let mut fields = Fields(&packet[1..]);
+
+            let (reply_kind, body) = if kind == SSH_FXP_INIT {
+                let mut body = Vec::new();
+                put_u32(&mut body, 3);
+                if self.options.posix_rename {
+                    put_string(&mut body, POSIX_RENAME);
+                    put_string(&mut body, b"1");
+                }
+                (SSH_FXP_VERSION, body)
+            } else {
+                let id = fields.u32()?;
+                let (reply_kind, reply_body) = self
+                    .handle(kind, &mut fields)
+                    .unwrap_or_else(|e| error_status(&e));
+                let mut body = Vec::new();
+                put_u32(&mut body, id);
+                body.extend_from_slice(&reply_body);
+                (reply_kind, body)
+            };
+
+            writer.write_all(&(body.len() as u32 + 1).to_be_bytes())?;
+            writer.write_all(&[reply_kind])?;
+            writer.write_all(&body)?;
+            writer.flush()?;
+        }
+    }
+
+    fn new_handle(&mut self, handle: Handle) -> Reply {
+        let id = self.next_handle.to_string().into_bytes();
+        self.next_handle += 1;
+        self.handles.insert(id.clone(), handle);
+        let mut body = Vec::new();
+        put_string(&mut body, &id);
+        (SSH_FXP_HANDLE, body)
+    }
+
+    fn file(&mut self, handle: &[u8]) -> io::Result<&mut File> {
+        match self.handles.get_mut(handle) {
+            Some(Handle::File(file)) => Ok(file),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "bad handle")),
+        }
+    }
+
+    fn handle(&mut self, kind: u8, fields: &mut Fields) -> io::Result<Reply> {
+        Ok(match kind {
+            SSH_FXP_OPEN => {
+                let path = fields.path()?;
+                let flags = fields.u32()?;
+                let file = OpenOptions::new()
+                    .read(flags & SSH_FXF_READ != 0)
+                    .write(flags & SSH_FXF_WRITE != 0)
+                    .create(flags & SSH_FXF_CREAT != 0)
+                    .truncate(flags & SSH_FXF_TRUNC != 0)
+                    .open(path)?;
+                self.new_handle(Handle::File(file))
+            }
+            SSH_FXP_OPENDIR => {
+                let path = fields.path()?;
+                let mut entries = Vec::new();
+                for entry in std::fs::read_dir(&path)? {
+                    let entry = entry?;
+                    let metadata = std::fs::symlink_metadata(entry.path())?;
+                    entries.push((entry.file_name().to_string_lossy().into_owned(), metadata));
+                }
+                self.new_handle(Handle::Dir(Some(entries)))
+            }
+            SSH_FXP_CLOSE => {
+                let handle = fields.string()?;
+                done(
+                    self.handles
+                        .remove(handle)
+                        .map(|_| ())
+                        .ok_or_else(|| io::Error::other("bad handle")),
+                )
+            }
+            SSH_FXP_READ => {
+                let handle = fields.string()?.to_vec();
+                let offset = fields.u64()?;
+                let len = (fields.u32()? as usize).min(self.options.max_read);
+                let file = self.file(&handle)?;
+                file.seek(SeekFrom::Start(offset))?;
+                let mut data = Vec::with_capacity(len);
+                file.take(len as u64).read_to_end(&mut data)?;
+                if data.is_empty() {
+                    status(SSH_FX_EOF)
+                } else {
+                    let mut body = Vec::new();
+                    put_string(&mut body, &data);
+                    (SSH_FXP_DATA, body)
+                }
+            }
+            SSH_FXP_WRITE => {
+                let handle = fields.string()?.to_vec();
+                let offset = fields.u64()?;
+                let data = fields.string()?;
+                let file = self.file(&handle)?;
+                file.seek(SeekFrom::Start(offset))?;
+                done(file.write_all(data))
+            }
+            SSH_FXP_FSETSTAT => {
+                let handle = fields.string()?.to_vec();
+                let (permissions, owner) = fields.attrs()?;
+                let file = self.file(&handle)?;
+                if let Some(mode) = permissions {
+                    file.set_permissions(std::fs::Permissions::from_mode(mode))?;
+                }
+                if let Some((uid, gid)) = owner {
+                    std::os::unix::fs::fchown(&*file, Some(uid), Some(gid))?;
+                }
+                status(SSH_FX_OK)
+            }
+            SSH_FXP_READDIR => {
+                let handle = fields.string()?;
+                match self.handles.get_mut(handle) {
+                    Some(Handle::Dir(entries)) => match entries.take() {
+                        Some(entries) => {
+                            let mut body = Vec::new();
+                            put_u32(&mut body, entries.len() as u32);
+                            for (name, metadata) in entries {
+                                put_string(&mut body, name.as_bytes());
+                                put_string(&mut body, name.as_bytes());
+                                put_attrs(&mut body, &metadata);
+                            }
+                            (SSH_FXP_NAME, body)
+                        }
+                        None => status(SSH_FX_EOF),
+                    },
+                    _ => status(SSH_FX_FAILURE),
+                }
+            }
+            SSH_FXP_STAT => attrs_reply(std::fs::metadata(fields.path()?)),
+            SSH_FXP_LSTAT => attrs_reply(std::fs::symlink_metadata(fields.path()?)),
+            SSH_FXP_REALPATH => {
+                let path = std::fs::canonicalize(fields.path()?)?;
+                let mut body = Vec::new();
+                put_u32(&mut body, 1);
+                let name = path.to_string_lossy();
+                put_string(&mut body, name.as_bytes());
+                put_string(&mut body, name.as_bytes());
+                put_u32(&mut body, 0);
+                (SSH_FXP_NAME, body)
+            }
+            SSH_FXP_REMOVE => done(std::fs::remove_file(fields.path()?)),
+            SSH_FXP_MKDIR => done(std::fs::create_dir(fields.path()?)),
+            SSH_FXP_RMDIR => done(std::fs::remove_dir(fields.path()?)),
+            SSH_FXP_RENAME => {
+                let (from, to) = (fields.path()?, fields.path()?);
+                // Like OpenSSH, plain RENAME doesn't replace files
+                if to.exists() {
+                    status(SSH_FX_FAILURE)
+                } else {
+                    done(std::fs::rename(from, to))
+                }
+            }
+            SSH_FXP_EXTENDED => {
+                let name = fields.string()?;
+                if name == POSIX_RENAME && self.options.posix_rename {
+                    let (from, to) = (fields.path()?, fields.path()?);
+                    done(std::fs::rename(from, to))
+                } else {
+                    status(SSH_FX_OP_UNSUPPORTED)
+                }
+            }
+            _ => status(SSH_FX_OP_UNSUPPORTED),
+        })
+    }
+}
@@ -1,6 +1,14 @@
 use crate::common::harness::EditorTestHarness;
+use fresh::config::Config;
 use tempfile::TempDir;
 
+/// Harness that shows binary files as text instead of in the hex view
+fn text_view_harness(width: u16, height: u16) -> EditorTestHarness {
+    let mut config = Config::default();
+    config.editor.hex_view_binary_files = false;
+    EditorTestHarness::with_config(width, height, config).unwrap()
+}
+
 /// Test that PNG files are detected as binary and opened in read-only mode
 #[test]
 fn test_png_file_detected_as_binary() {
@@ -23,7 +31,7 @@ fn test_png_file_detected_as_binary() {
     std::fs::write(&png_path, png_data).unwrap();
 
     // Use wider terminal to see full status message
-    let mut harness = EditorTestHarness::new(120, 24).unwrap();
+    let mut harness = text_view_harness(120, 24);
     harness.open_file(&png_path).unwrap();
     harness.render().unwrap();
 
@@ -59,7 +67,7 @@ fn test_jpeg_file_detected_as_binary() {
     ];
     std::fs::write(&jpeg_path, jpeg_data).unwrap();
 
-    let mut harness = EditorTestHarness::new(120, 24).unwrap();
+    let mut harness = text_view_harness(120, 24);
     harness.open_file(&jpeg_path).unwrap();
     harness.render().unwrap();
 
@@ -87,7 +95,7 @@ fn test_elf_executable_detected_as_binary() {
     ];
     std::fs::write(&elf_path, elf_data).unwrap();
 
-    let mut harness = EditorTestHarness::new(120, 24).unwrap();
+    let mut harness = text_view_harness(120, 24);
     harness.open_file(&elf_path).unwrap();
     harness.render().unwrap();
 
@@ -155,7 +163,7 @@ fn test_typing_blocked_in_binary_file() {
     ];
     std::fs::write(&png_path, png_data).unwrap();
 
-    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let mut harness = text_view_harness(80, 24);
     harness.open_file(&png_path).unwrap();
 
     let initial_len = harness.buffer_len();
@@ -192,7 +200,7 @@ fn test_binary_bytes_rendered_as_hex() {
     let bin_data: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x00, 0x01, 0x7F];
     std::fs::write(&bin_path, bin_data).unwrap();
 
-    let mut harness = EditorTestHarness::new(120, 24).unwrap();
+    let mut harness = text_view_harness(120, 24);
     harness.open_file(&bin_path).unwrap();
     harness.render().unwrap();
 
@@ -246,7 +254,7 @@ fn test_binary_file_scrolling_no_artifacts() {
     std::fs::write(&png_path, &png_data).unwrap();
 
     // Use a standard terminal size
-    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let mut harness = text_view_harness(80, 24);
     harness.open_file(&png_path).unwrap();
 
     // Use render_real() which processes through VT100 parser for accurate terminal simulation
@@ -0,0 +1,91 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, Keybinding};
+use fresh::services::plugins::api::PluginCommand;
+use std::collections::HashMap;
+use std::fs;
+use tempfile::TempDir;
+
+fn config_with(bindings: &[(&str, &str, &str)]) -> Config {
+    let keybindings = bindings
+        .iter()
+        .map(|(key, action, when)| Keybinding {
+            key: key.to_string(),
+            modifiers: vec![],
+            keys: vec![],
+            action: action.to_string(),
+            args: HashMap::new(),
+            when: Some(when.to_string()),
+        })
+        .collect();
+    Config {
+        keybindings,
+        ..Config::default()
+    }
+}
+
+/// A binding with a selection condition only replaces Tab while text is selected
+#[test]
+fn test_tab_bound_only_with_selection() {
+    let config = config_with(&[("Tab", "dedent_selection", "editorHasSelection")]);
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.type_text("    one").unwrap();
+
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::SHIFT)
+        .unwrap();
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "one");
+
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "one    ");
+}
+
+/// Language and plugin context keys decide whether a binding applies
+#[test]
+fn test_language_and_plugin_context_keys() {
+    let temp_dir = TempDir::new().unwrap();
+    let rust_file = temp_dir.path().join("main.rs");
+    let text_file = temp_dir.path().join("notes.txt");
+    fs::write(&rust_file, "").unwrap();
+    fs::write(&text_file, "").unwrap();
+
+    let config = config_with(&[
+        ("F6", "insert_tab", "editorLangId == rust && !readOnly"),
+        (
+            "F7",
+            "insert_newline",
+            "normal && (tests-running || multiCursor)",
+        ),
+    ]);
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+
+    harness.open_file(&text_file).unwrap();
+    harness.send_key(KeyCode::F(6), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "");
+
+    harness.open_file(&rust_file).unwrap();
+    harness.send_key(KeyCode::F(6), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "    ");
+
+    harness.send_key(KeyCode::F(7), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "    ");
+
+    let set_context = |harness: &mut EditorTestHarness, active: bool| {
+        harness
+            .editor_mut()
+            .handle_plugin_command(PluginCommand::SetContext {
+                name: "tests-running".to_string(),
+                active,
+            })
+            .unwrap();
+    };
+    set_context(&mut harness, true);
+    harness.send_key(KeyCode::F(7), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "    \n");
+
+    set_context(&mut harness, false);
+    harness.send_key(KeyCode::F(7), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "    \n");
+}
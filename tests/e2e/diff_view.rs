@@ -0,0 +1,128 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use tempfile::TempDir;
+
+fn write_files(
+    temp_dir: &TempDir,
+    left: &str,
+    right: &str,
+) -> (std::path::PathBuf, std::path::PathBuf) {
+    let left_path = temp_dir.path().join("left.txt");
+    let right_path = temp_dir.path().join("right.txt");
+    std::fs::write(&left_path, left).unwrap();
+    std::fs::write(&right_path, right).unwrap();
+    (left_path, right_path)
+}
+
+/// `fresh --diff` shows both files side by side and moves between the changes
+#[test]
+fn test_diff_two_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let (left, right) = write_files(
+        &temp_dir,
+        "alpha\nbeta\ngamma\ndelta\nepsilon\nzeta\neta\n",
+        "alpha\nBETA\ngamma\ndelta\nepsilon\nzeta\neta\ntheta\n",
+    );
+    let mut harness = EditorTestHarness::new(120, 30).unwrap();
+    harness.editor_mut().open_diff(&left, &right).unwrap();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains("*Diff: left.txt vs right.txt*");
+    harness.assert_screen_contains("beta");
+    harness.assert_screen_contains("BETA");
+    harness.assert_screen_contains("theta");
+
+    harness
+        .send_key(KeyCode::Char('n'), KeyModifiers::ALT)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Change 2 of 2");
+    harness
+        .send_key(KeyCode::Char('n'), KeyModifiers::ALT)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("No more changes");
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::ALT)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Change 1 of 2");
+}
+
+/// Taking a side of a change edits the other file, which can then be saved
+#[test]
+fn test_diff_take_left_and_save() {
+    let temp_dir = TempDir::new().unwrap();
+    let (left, right) = write_files(&temp_dir, "one\ntwo\nthree\n", "one\n2\nthree\n");
+    let mut harness = EditorTestHarness::new(120, 30).unwrap();
+    harness.editor_mut().open_diff(&left, &right).unwrap();
+    harness.render().unwrap();
+
+    // The cursor starts on the first change
+    harness.send_key(KeyCode::Left, KeyModifiers::ALT).unwrap();
+    harness.render().unwrap();
+
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&right).unwrap(),
+        "one\ntwo\nthree\n"
+    );
+    assert_eq!(std::fs::read_to_string(&left).unwrap(), "one\ntwo\nthree\n");
+}
+
+/// Typing in a pane edits that file, and the diff follows the edit
+#[test]
+fn test_diff_edit_pane() {
+    let temp_dir = TempDir::new().unwrap();
+    let (left, right) = write_files(&temp_dir, "same\nold\n", "same\nnew\n");
+    let mut harness = EditorTestHarness::new(120, 30).unwrap();
+    harness.editor_mut().open_diff(&left, &right).unwrap();
+    harness.render().unwrap();
+
+    harness.type_text("very ").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("very old");
+
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(std::fs::read_to_string(&left).unwrap(), "same\nvery old\n");
+    assert_eq!(std::fs::read_to_string(&right).unwrap(), "same\nnew\n");
+}
+
+/// "Compare With Saved" shows the unsaved changes; the saved side is read-only
+#[test]
+fn test_compare_with_saved() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("notes.txt");
+    std::fs::write(&file_path, "first\nsecond\n").unwrap();
+    let mut harness = EditorTestHarness::new(120, 30).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("third\n").unwrap();
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.type_text("Compare With Saved").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_prompt_closed().unwrap();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains("notes.txt (saved)");
+    harness.assert_screen_contains("third");
+
+    // Taking the saved side reverts the change in the file's buffer
+    harness.send_key(KeyCode::Left, KeyModifiers::ALT).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("third");
+}
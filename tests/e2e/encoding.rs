@@ -0,0 +1,188 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use tempfile::TempDir;
+
+/// Run an encoding command from the command palette, picking `encoding` in its prompt
+fn run_encoding_command(harness: &mut EditorTestHarness, command: &str, encoding: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.type_text(command).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+
+    harness.type_text(encoding).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_prompt_closed().unwrap();
+    harness.render().unwrap();
+}
+
+/// UTF-16 files with a byte order mark are decoded on open and saved back as UTF-16
+#[test]
+fn test_utf16_file_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("windows.log");
+
+    let mut bytes = vec![0xFF, 0xFE];
+    for unit in "Grüße\r\nzweite Zeile\r\n".encode_utf16() {
+        bytes.extend_from_slice(&unit.to_le_bytes());
+    }
+    std::fs::write(&file_path, &bytes).unwrap();
+
+    let mut harness = EditorTestHarness::new(160, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains("Grüße");
+    harness.assert_screen_contains("zweite Zeile");
+    let status_bar = harness.get_status_bar();
+    assert!(
+        status_bar.contains("UTF-16 LE") && status_bar.contains("CRLF"),
+        "Status bar should show the encoding and line ending: {status_bar}"
+    );
+
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("!").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .wait_until(|h| !h.editor().active_state().buffer.is_modified())
+        .unwrap();
+
+    let mut expected = vec![0xFF, 0xFE];
+    for unit in "Grüße\r\nzweite Zeile\r\n!".encode_utf16() {
+        expected.extend_from_slice(&unit.to_le_bytes());
+    }
+    assert_eq!(std::fs::read(&file_path).unwrap(), expected);
+}
+
+/// Files that are not valid UTF-8 are detected as a legacy encoding and saved back in it
+#[test]
+fn test_latin1_file_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("legacy.c");
+
+    // "café" and "naïve" in ISO-8859-1
+    std::fs::write(&file_path, b"/* caf\xe9 na\xefve */\n").unwrap();
+
+    let mut harness = EditorTestHarness::new(160, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains("/* café naïve */");
+    harness.assert_screen_contains("Windows-1252");
+
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("int ü;").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .wait_until(|h| !h.editor().active_state().buffer.is_modified())
+        .unwrap();
+
+    assert_eq!(
+        std::fs::read(&file_path).unwrap(),
+        b"/* caf\xe9 na\xefve */\nint \xfc;"
+    );
+}
+
+/// Reopen with Encoding reads the file again when detection guessed wrong
+#[test]
+fn test_reopen_with_encoding() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("cyrillic.txt");
+
+    // "Привет" in Windows-1251
+    std::fs::write(&file_path, b"\xcf\xf0\xe8\xe2\xe5\xf2\n").unwrap();
+
+    let mut harness = EditorTestHarness::new(160, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+
+    run_encoding_command(&mut harness, "reopen with enc", "Latin-1");
+    harness.assert_screen_contains("Reopened with Latin-1");
+    assert_eq!(harness.get_buffer_content().unwrap(), "Ïðèâåò\n");
+
+    run_encoding_command(&mut harness, "reopen with enc", "Windows-1251");
+    harness.assert_screen_contains("Reopened with Windows-1251");
+    assert_eq!(harness.get_buffer_content().unwrap(), "Привет\n");
+    assert!(!harness.editor().active_state().buffer.is_modified());
+}
+
+/// Reopen with Encoding leaves the buffer alone if the file is not valid in the encoding
+#[test]
+fn test_reopen_with_invalid_encoding() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("latin1.txt");
+    std::fs::write(&file_path, b"caf\xe9\n").unwrap();
+
+    let mut harness = EditorTestHarness::new(160, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+
+    run_encoding_command(&mut harness, "reopen with enc", "UTF-8");
+    harness.assert_screen_contains("File is not valid UTF-8");
+    assert_eq!(harness.get_buffer_content().unwrap(), "café\n");
+}
+
+/// Save with Encoding converts the file
+#[test]
+fn test_save_with_encoding() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("notes.txt");
+    std::fs::write(&file_path, "日本語\n").unwrap();
+
+    let mut harness = EditorTestHarness::new(160, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+    // Plain UTF-8 is not shown in the status bar
+    assert!(!harness.get_status_bar().contains("UTF-8"));
+
+    run_encoding_command(&mut harness, "save with enc", "Shift-JIS");
+    harness.assert_screen_contains("Saved as Shift-JIS");
+    assert!(harness.get_status_bar().contains("Shift-JIS"));
+    assert_eq!(
+        std::fs::read(&file_path).unwrap(),
+        b"\x93\xfa\x96\x7b\x8c\xea\n"
+    );
+    assert_eq!(
+        harness
+            .editor()
+            .active_state()
+            .buffer
+            .encoding()
+            .display_name(),
+        "Shift-JIS"
+    );
+}
+
+/// Save with Encoding refuses characters the encoding can't represent
+#[test]
+fn test_save_with_encoding_unencodable() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("snowman.txt");
+    std::fs::write(&file_path, "snow ☃\n").unwrap();
+
+    let mut harness = EditorTestHarness::new(160, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+
+    run_encoding_command(&mut harness, "save with enc", "Latin-1");
+    harness.assert_screen_contains("cannot be saved as Latin-1");
+
+    assert_eq!(std::fs::read(&file_path).unwrap(), "snow ☃\n".as_bytes());
+    let buffer = &harness.editor().active_state().buffer;
+    assert_eq!(buffer.encoding().display_name(), "UTF-8");
+    assert!(!buffer.is_modified());
+}
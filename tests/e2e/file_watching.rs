@@ -0,0 +1,115 @@
+//! E2E tests for reacting to changes on disk reported by the file watcher
+
+use crate::common::fake_lsp::FakeLspServer;
+use crate::common::harness::EditorTestHarness;
+use std::path::Path;
+
+/// Long enough that no poll happens during a test
+const NEVER_POLL_MS: u64 = 3_600_000;
+
+fn config_without_polling() -> fresh::config::Config {
+    let mut config = fresh::config::Config::default();
+    config.editor.auto_revert_poll_interval_ms = NEVER_POLL_MS;
+    config.editor.file_tree_poll_interval_ms = NEVER_POLL_MS;
+    config
+}
+
+/// External edits revert the buffer without waiting for a poll
+#[test]
+#[cfg_attr(not(target_os = "linux"), ignore)] // Other platforms only poll
+fn test_auto_revert_without_polling() -> std::io::Result<()> {
+    let mut harness =
+        EditorTestHarness::with_temp_project_and_config(80, 24, config_without_polling())?;
+    let file = harness.project_dir().unwrap().join("notes.txt");
+    std::fs::write(&file, "before")?;
+    harness.open_file(&file)?;
+    harness.assert_buffer_content("before");
+
+    std::fs::write(&file, "after")?;
+    harness.wait_until(|h| h.get_buffer_content().unwrap() == "after")?;
+    Ok(())
+}
+
+/// Open files are still polled when native watching is turned off
+#[test]
+fn test_auto_revert_polls_without_native_watching() -> std::io::Result<()> {
+    let mut config = fresh::config::Config::default();
+    config.editor.native_file_watching = false;
+    config.editor.auto_revert_poll_interval_ms = 100;
+    let mut harness = EditorTestHarness::with_temp_project_and_config(80, 24, config)?;
+    let file = harness.project_dir().unwrap().join("notes.txt");
+    std::fs::write(&file, "before")?;
+    harness.open_file(&file)?;
+
+    std::fs::write(&file, "after")?;
+    harness.wait_until(|h| h.get_buffer_content().unwrap() == "after")?;
+    Ok(())
+}
+
+/// New files appear in the file explorer without waiting for a poll
+#[test]
+#[cfg_attr(not(target_os = "linux"), ignore)] // Other platforms only poll
+fn test_file_explorer_shows_new_file_without_polling() -> std::io::Result<()> {
+    let mut harness =
+        EditorTestHarness::with_temp_project_and_config(100, 30, config_without_polling())?;
+    let project_dir = harness.project_dir().unwrap();
+    std::fs::write(project_dir.join("existing.txt"), "")?;
+
+    harness.editor_mut().focus_file_explorer();
+    harness.wait_for_file_explorer()?;
+    harness.wait_for_file_explorer_item("existing.txt")?;
+
+    std::fs::write(project_dir.join("created.txt"), "")?;
+    harness.wait_for_file_explorer_item("created.txt")?;
+    Ok(())
+}
+
+fn requests(request_log: &Path) -> String {
+    std::fs::read_to_string(request_log).unwrap_or_default()
+}
+
+/// Language servers get the changes they registered for, until they unregister
+#[test]
+#[cfg_attr(not(target_os = "linux"), ignore)] // Uses Bash-based fake LSP server, other platforms only poll
+fn test_lsp_notified_of_watched_file_changes() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_watched_files()?;
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path();
+    let request_log = dir.join("requests.log");
+    let file = dir.join("main.rs");
+    std::fs::write(&file, "fn main() {}\n")?;
+
+    let mut config = config_without_polling();
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: FakeLspServer::watched_files_script_path()
+                .to_string_lossy()
+                .to_string(),
+            args: vec![request_log.to_string_lossy().to_string()],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+        },
+    );
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, config, dir.to_path_buf())?;
+    harness.open_file(&file)?;
+    harness.wait_until(|_| requests(&request_log).contains("reply 9101"))?;
+
+    std::fs::write(dir.join("Cargo.toml"), "[package]\n")?;
+    harness.wait_until(|_| requests(&request_log).contains("Cargo.toml"))?;
+    let log = requests(&request_log);
+    assert!(log.contains(r#""type":1"#), "{}", log);
+    assert!(!log.contains("main.rs"), "{}", log);
+
+    // The server swapped its watcher for one of created lock files
+    harness.wait_until(|_| requests(&request_log).contains("reply 9103"))?;
+    std::fs::write(dir.join("other.toml"), "")?;
+    std::fs::write(dir.join("Cargo.lock"), "")?;
+    harness.wait_until(|_| requests(&request_log).contains("Cargo.lock"))?;
+    let log = requests(&request_log);
+    assert!(!log.contains("other.toml"), "{}", log);
+    Ok(())
+}
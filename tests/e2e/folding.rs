@@ -0,0 +1,116 @@
+//! E2E tests for code folding (tree-sitter fold ranges, no language server)
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use tempfile::TempDir;
+
+const SOURCE: &str =
+    "fn main() {\n    let a = 1;\n    let b = 2;\n}\n\nfn other() {\n    let c = 3;\n}\n";
+
+fn open_source(harness: &mut EditorTestHarness, temp_dir: &TempDir) {
+    let file_path = temp_dir.path().join("test.rs");
+    std::fs::write(&file_path, SOURCE).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+}
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Folding hides the block body, shows a marker and placeholder, and unfolds again
+#[test]
+fn test_fold_and_unfold_at_cursor() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    open_source(&mut harness, &temp_dir);
+
+    // Fold with the cursor on the header line (Alt+-)
+    harness
+        .send_key(KeyCode::Char('-'), KeyModifiers::ALT)
+        .unwrap();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains("fn main() { ⋯");
+    harness.assert_screen_contains("▸");
+    harness.assert_screen_not_contains("let a = 1;");
+    harness.assert_screen_not_contains("let b = 2;");
+    // The closing brace stays visible
+    harness.assert_screen_contains("fn other() {");
+
+    // Line numbers skip the hidden lines
+    let screen = harness.screen_to_string();
+    let brace_row = screen
+        .lines()
+        .find(|line| line.trim_end().ends_with('}'))
+        .expect("closing brace should be visible");
+    assert!(
+        brace_row.contains('4'),
+        "closing brace is line 4: {brace_row}"
+    );
+
+    // Unfold (Alt+=) restores the body; the buffer is never modified
+    harness
+        .send_key(KeyCode::Char('='), KeyModifiers::ALT)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("let a = 1;");
+    harness.assert_screen_not_contains("⋯");
+    harness.assert_buffer_content(SOURCE);
+}
+
+/// Moving down from a fold header skips the hidden lines
+#[test]
+fn test_cursor_skips_folded_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    open_source(&mut harness, &temp_dir);
+
+    harness
+        .send_key(KeyCode::Char('-'), KeyModifiers::ALT)
+        .unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+
+    let closing_brace = SOURCE.find("}\n").unwrap();
+    assert_eq!(harness.cursor_position(), closing_brace);
+
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), 0);
+}
+
+/// Searching for text inside a fold unfolds it
+#[test]
+fn test_search_reveals_folded_match() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    open_source(&mut harness, &temp_dir);
+
+    run_command(&mut harness, "Fold All");
+    harness.assert_screen_not_contains("let a = 1;");
+    harness.assert_screen_not_contains("let c = 3;");
+
+    harness
+        .send_key(KeyCode::Char('f'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("let c").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.process_async_and_render().unwrap();
+
+    assert_eq!(harness.cursor_position(), SOURCE.find("let c").unwrap());
+    harness.assert_screen_contains("let c = 3;");
+    // Only the fold containing the match is opened
+    harness.assert_screen_not_contains("let a = 1;");
+
+    run_command(&mut harness, "Unfold All");
+    harness.assert_screen_contains("let a = 1;");
+}
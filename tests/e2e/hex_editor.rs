@@ -0,0 +1,161 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use tempfile::TempDir;
+
+/// The start of a PNG file: its signature and the IHDR chunk header
+const PNG_START: &[u8] = &[
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, // signature
+    0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52, // chunk length, "IHDR"
+    0x00, 0x00, 0x00, 0x01, // width = 1
+];
+
+fn press(harness: &mut EditorTestHarness, code: KeyCode, modifiers: KeyModifiers) {
+    harness.send_key(code, modifiers).unwrap();
+}
+
+/// Answer the prompt opened by `key` with `input`
+fn prompt(harness: &mut EditorTestHarness, key: char, input: &str) {
+    press(harness, KeyCode::Char(key), KeyModifiers::CONTROL);
+    harness.wait_for_prompt().unwrap();
+    harness.type_text(input).unwrap();
+    press(harness, KeyCode::Enter, KeyModifiers::NONE);
+    harness.wait_for_prompt_closed().unwrap();
+    harness.render().unwrap();
+}
+
+/// Binary files open in the hex view, with offset, hex and ASCII columns
+#[test]
+fn test_binary_file_opens_in_hex_view() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("image.png");
+    std::fs::write(&path, PNG_START).unwrap();
+
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&path).unwrap();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains(
+        "00000000  89 50 4E 47 0D 0A 1A 0A  00 00 00 0D 49 48 44 52  .PNG........IHDR",
+    );
+    harness.assert_screen_contains(&format!("00000010  00 00 00 01{}....", " ".repeat(39)));
+    harness.assert_screen_contains("Offset 0x0");
+
+    // Moving goes by bytes and rows
+    press(&mut harness, KeyCode::Down, KeyModifiers::NONE);
+    press(&mut harness, KeyCode::Right, KeyModifiers::NONE);
+    harness.render().unwrap();
+    harness.assert_screen_contains("Offset 0x11");
+
+    // Toggling shows the file as text again
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.type_text("Toggle Hex View").unwrap();
+    press(&mut harness, KeyCode::Enter, KeyModifiers::NONE);
+    harness.render().unwrap();
+    harness.assert_screen_contains("<89>PNG");
+    harness.assert_screen_not_contains("00000000");
+}
+
+/// Typing overwrites nibbles in the hex column and bytes in the ASCII
+/// column; each byte is one undo step, and saving writes the bytes back
+#[test]
+fn test_hex_editing_undo_and_save() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("image.png");
+    std::fs::write(&path, PNG_START).unwrap();
+
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&path).unwrap();
+
+    // Both nibbles of the first byte, then on to the second
+    harness.type_text("4a").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("00000000  4A 50 4E 47");
+    harness.assert_screen_contains("Offset 0x1");
+
+    // Tab goes to the ASCII column, where characters replace whole bytes
+    press(&mut harness, KeyCode::Tab, KeyModifiers::NONE);
+    harness.type_text("XY").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("00000000  4A 58 59 47");
+    harness.assert_screen_contains("JXYG");
+    assert_eq!(harness.buffer_len(), PNG_START.len());
+
+    press(&mut harness, KeyCode::Char('z'), KeyModifiers::CONTROL);
+    harness.render().unwrap();
+    harness.assert_screen_contains("00000000  4A 58 4E 47");
+
+    press(&mut harness, KeyCode::Char('s'), KeyModifiers::CONTROL);
+    let mut expected = PNG_START.to_vec();
+    expected[0] = 0x4A;
+    expected[1] = b'X';
+    assert_eq!(std::fs::read(&path).unwrap(), expected);
+}
+
+/// Go to Line takes offsets and Find searches for bytes or text
+#[test]
+fn test_hex_goto_offset_and_search() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("image.png");
+    std::fs::write(&path, PNG_START).unwrap();
+
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&path).unwrap();
+
+    prompt(&mut harness, 'g', "0x10");
+    harness.assert_screen_contains("Offset 0x10");
+    prompt(&mut harness, 'g', "-3");
+    harness.assert_screen_contains("Offset 0xD");
+    prompt(&mut harness, 'g', "500");
+    harness.assert_screen_contains("past the end");
+
+    // Hex bytes, searched from the cursor and wrapping around
+    prompt(&mut harness, 'f', "0d 0a");
+    harness.assert_screen_contains("Offset 0x4");
+
+    // Text, and hex-looking text in quotes
+    prompt(&mut harness, 'f', "IHDR");
+    harness.assert_screen_contains("Offset 0xC");
+    prompt(&mut harness, 'f', "\"PNG\"");
+    harness.assert_screen_contains("Offset 0x1");
+
+    prompt(&mut harness, 'f', "00 00");
+    harness.assert_screen_contains("Offset 0x8");
+    press(&mut harness, KeyCode::F(3), KeyModifiers::NONE);
+    harness.render().unwrap();
+    harness.assert_screen_contains("Offset 0x9");
+    press(&mut harness, KeyCode::F(3), KeyModifiers::SHIFT);
+    harness.render().unwrap();
+    harness.assert_screen_contains("Offset 0x8");
+}
+
+/// Large binary files are edited through the hex view without loading
+/// them, and saved by streaming the unchanged parts
+#[test]
+fn test_hex_editing_large_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("disk.img");
+    let mut data: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    data[0] = 0;
+    data[3 * 1024 * 1024..3 * 1024 * 1024 + 6].copy_from_slice(b"MARKER");
+    std::fs::write(&path, &data).unwrap();
+
+    let mut config = Config::default();
+    config.editor.large_file_threshold_bytes = 1024 * 1024;
+    let mut harness = EditorTestHarness::with_config(100, 24, config).unwrap();
+    harness.open_file(&path).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Offset 0x0");
+
+    prompt(&mut harness, 'f', "MARKER");
+    harness.assert_screen_contains("Offset 0x300000");
+    harness.assert_screen_contains("00300000  4D 41 52 4B 45 52");
+
+    harness.type_text("6d").unwrap();
+    press(&mut harness, KeyCode::Char('s'), KeyModifiers::CONTROL);
+    data[3 * 1024 * 1024] = b'm';
+    assert!(std::fs::read(&path).unwrap() == data);
+}
@@ -0,0 +1,109 @@
+//! E2E tests for the call hierarchy panel
+
+use crate::common::fake_lsp::FakeLspServer;
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+
+const SOURCE: &str = "fn main() {\n    middle();\n}\nfn middle() {\n    target();\n}\nfn target() {\n    leaf();\n}\nfn leaf() {}\n";
+
+/// Open SOURCE with the call hierarchy server and show the hierarchy of `target`
+fn open_hierarchy(temp_dir: &std::path::Path) -> std::io::Result<EditorTestHarness> {
+    let file = temp_dir.join("test.rs");
+    std::fs::write(&file, SOURCE)?;
+    let uri = url::Url::from_file_path(&file).unwrap().to_string();
+
+    let mut config = fresh::config::Config::default();
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: FakeLspServer::call_hierarchy_script_path()
+                .to_string_lossy()
+                .to_string(),
+            args: vec![uri],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+        },
+    );
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(120, 30, config, temp_dir.to_path_buf())?;
+    harness.open_file(&file)?;
+    harness.render()?;
+    harness.wait_until(|h| {
+        h.editor()
+            .running_lsp_servers()
+            .contains(&"rust".to_string())
+    })?;
+
+    // Put the cursor on "target" and ask for its callers
+    let line = SOURCE.lines().position(|l| l == "fn target() {").unwrap();
+    for _ in 0..line {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE)?;
+    }
+    harness.send_key(KeyCode::Char('h'), KeyModifiers::ALT | KeyModifiers::SHIFT)?;
+    harness.wait_until(|h| h.screen_to_string().contains("middle  function"))?;
+    Ok(harness)
+}
+
+/// Callers are expanded level by level and Enter jumps to the call site
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_call_hierarchy_expand_and_jump() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_call_hierarchy()?;
+    let temp_dir = tempfile::tempdir()?;
+    let mut harness = open_hierarchy(temp_dir.path())?;
+
+    harness.assert_screen_contains("*Hierarchy*");
+    harness.assert_screen_contains("Incoming calls");
+    harness.assert_screen_contains("▾ target  function  test.rs:7");
+    // The call site of target() in middle()
+    harness.assert_screen_contains("▸ middle  function  test.rs:5");
+    harness.assert_screen_not_contains("▸ main");
+
+    // The panel has the focus: expand middle() to see its callers
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE)?;
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE)?;
+    harness.wait_until(|h| h.screen_to_string().contains("▸ main  function  test.rs:2"))?;
+    harness.assert_screen_contains("▾ middle");
+
+    // Collapse and expand again without asking the server
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE)?;
+    harness.render()?;
+    harness.assert_screen_not_contains("main  function");
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE)?;
+    harness.render()?;
+    harness.assert_screen_contains("▸ main  function  test.rs:2");
+
+    // Jump to the call of middle() in main()
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE)?;
+    harness.send_key(KeyCode::Enter, KeyModifiers::NONE)?;
+    harness.render()?;
+    harness.assert_buffer_content(SOURCE);
+    assert_eq!(harness.cursor_position(), SOURCE.find("middle();").unwrap());
+    Ok(())
+}
+
+/// "d" switches the panel to the functions called by the item
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_call_hierarchy_flip_direction() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_call_hierarchy()?;
+    let temp_dir = tempfile::tempdir()?;
+    let mut harness = open_hierarchy(temp_dir.path())?;
+
+    harness.send_key(KeyCode::Char('d'), KeyModifiers::NONE)?;
+    harness.wait_until(|h| h.screen_to_string().contains("leaf  function"))?;
+    harness.assert_screen_contains("Outgoing calls");
+    // Outgoing calls point at the call site inside target()
+    harness.assert_screen_contains("▸ leaf  function  test.rs:8");
+    harness.assert_screen_not_contains("middle  function");
+
+    // q closes the panel
+    harness.send_key(KeyCode::Char('q'), KeyModifiers::NONE)?;
+    harness.render()?;
+    harness.assert_screen_not_contains("*Hierarchy*");
+    harness.assert_buffer_content(SOURCE);
+    Ok(())
+}
@@ -0,0 +1,139 @@
+// E2E tests for opening files in a running editor (`fresh --remote`)
+//
+// The tests start the editor's server on a socket in a temp dir and send it
+// requests from a client thread, the way `fresh --remote` does.
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::instance_server::{send_request, OpenFile, OpenRequest};
+use portable_pty::{native_pty_system, PtySize};
+use std::io;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use tempfile::TempDir;
+
+/// Send a request from another thread, like `fresh --remote` would
+fn send_from_client(
+    socket: &Path,
+    path: PathBuf,
+    line: Option<usize>,
+    wait: bool,
+) -> JoinHandle<io::Result<()>> {
+    let stream = UnixStream::connect(socket).unwrap();
+    std::thread::spawn(move || {
+        let request = OpenRequest {
+            files: vec![OpenFile {
+                path,
+                line,
+                column: None,
+            }],
+            wait,
+        };
+        send_request(stream, &request)
+    })
+}
+
+/// A file sent to the editor is opened at the given line, and the client
+/// is answered right away
+#[test]
+fn test_open_file_in_running_editor() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("main.rs");
+    std::fs::write(&path, "fn main() {\n    run();\n}\n").unwrap();
+    // The editor makes the socket's directory, only usable by the user
+    let socket = temp_dir.path().join("sockets").join("fresh.sock");
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.editor_mut().start_instance_server(&socket).unwrap();
+
+    let client = send_from_client(&socket, path, Some(2), false);
+    harness.wait_for_screen_contains("run();").unwrap();
+    harness.wait_until(|_| client.is_finished()).unwrap();
+    client.join().unwrap().unwrap();
+
+    // At the start of line 2
+    assert_eq!(harness.cursor_position(), "fn main() {\n".len());
+}
+
+/// With `wait`, the client is answered when the buffer is closed, and the
+/// editor goes back to the terminal it was sent from
+#[test]
+fn test_wait_until_buffer_closed() {
+    if native_pty_system()
+        .openpty(PtySize {
+            rows: 1,
+            cols: 1,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .is_err()
+    {
+        eprintln!("Skipping test: PTY not available in this environment");
+        return;
+    }
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("COMMIT_EDITMSG");
+    std::fs::write(&path, "\n# Please enter the commit message\n").unwrap();
+    let socket = temp_dir.path().join("sockets").join("fresh.sock");
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.editor_mut().start_instance_server(&socket).unwrap();
+    harness.editor_mut().open_terminal();
+    let terminal = harness.editor().active_buffer();
+
+    let client = send_from_client(&socket, path.clone(), None, true);
+    harness
+        .wait_for_screen_contains("Please enter the commit message")
+        .unwrap();
+    assert!(!harness.editor().is_terminal_mode());
+
+    harness.type_text("Fix the build").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.process_async_and_render().unwrap();
+    assert!(!client.is_finished());
+
+    harness
+        .send_key(KeyCode::Char('w'), KeyModifiers::ALT)
+        .unwrap();
+    harness.wait_until(|_| client.is_finished()).unwrap();
+    client.join().unwrap().unwrap();
+
+    assert_eq!(harness.editor().active_buffer(), terminal);
+    assert!(harness.editor().is_terminal_mode());
+    assert!(std::fs::read_to_string(&path)
+        .unwrap()
+        .starts_with("Fix the build\n"));
+}
+
+/// Terminals of an editor with a server know where to reach it
+#[test]
+fn test_terminal_gets_server_socket() {
+    if native_pty_system()
+        .openpty(PtySize {
+            rows: 1,
+            cols: 1,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .is_err()
+    {
+        eprintln!("Skipping test: PTY not available in this environment");
+        return;
+    }
+    let temp_dir = TempDir::new().unwrap();
+    let socket = temp_dir.path().join("sockets").join("fresh.sock");
+
+    let mut harness = EditorTestHarness::new(120, 24).unwrap();
+    harness.editor_mut().start_instance_server(&socket).unwrap();
+    harness.editor_mut().open_terminal();
+
+    harness
+        .editor_mut()
+        .send_terminal_input(b"echo \"server=$FRESH_SERVER\"\n");
+    harness
+        .wait_for_screen_contains(&format!("server={}", socket.display()))
+        .unwrap();
+}
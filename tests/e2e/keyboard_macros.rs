@@ -0,0 +1,216 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, Keybinding};
+use fresh::config_io::DirectoryContext;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Open `file` (written with `text`) in an editor using `dir_context`
+fn open_with_context(
+    temp_dir: &TempDir,
+    dir_context: &DirectoryContext,
+    config: Config,
+    text: &str,
+) -> (EditorTestHarness, PathBuf) {
+    let project = temp_dir.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let file_path = project.join("file.txt");
+    fs::write(&file_path, text).unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_shared_dir_context(100, 24, config, project, dir_context.clone())
+            .unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+    (harness, file_path)
+}
+
+/// Run a command palette command, answering its prompts with `inputs`
+fn run_command(harness: &mut EditorTestHarness, command: &str, inputs: &[&str]) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.type_text(command).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    for input in inputs {
+        harness.wait_for_prompt().unwrap();
+        harness.type_text(input).unwrap();
+        harness
+            .send_key(KeyCode::Enter, KeyModifiers::NONE)
+            .unwrap();
+    }
+    harness.render().unwrap();
+}
+
+/// Record a macro under `name` that appends ";" to the cursor line
+fn record_semicolon_macro(harness: &mut EditorTestHarness, name: &str) {
+    run_command(harness, "record macro", &[name]);
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.type_text(";").unwrap();
+    harness.send_key(KeyCode::F(5), KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+}
+
+fn status(harness: &EditorTestHarness) -> String {
+    harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn macros_file(dir_context: &DirectoryContext) -> String {
+    fs::read_to_string(dir_context.macros_path()).unwrap_or_default()
+}
+
+/// A named macro is saved, and a later session plays it from a keybinding
+#[test]
+fn test_named_macro_persists_and_binds_to_key() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("context"));
+
+    {
+        let (mut harness, _) =
+            open_with_context(&temp_dir, &dir_context, Config::default(), "one\ntwo\n");
+        record_semicolon_macro(&mut harness, "semi");
+        assert_eq!(harness.get_buffer_content().unwrap(), "one;\ntwo\n");
+    }
+    let saved = macros_file(&dir_context);
+    assert!(saved.contains("\"semi\": ["), "got: {}", saved);
+    assert!(saved.contains("{\"InsertChar\":\";\"}"), "got: {}", saved);
+
+    let mut config = Config::default();
+    config.keybindings.push(Keybinding {
+        key: "m".to_string(),
+        modifiers: vec!["alt".to_string()],
+        keys: vec![],
+        action: "play_named_macro".to_string(),
+        args: [("name".to_string(), serde_json::json!("semi"))]
+            .into_iter()
+            .collect(),
+        when: None,
+    });
+    let (mut harness, _) = open_with_context(&temp_dir, &dir_context, config, "a\nb\n");
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Char('m'), KeyModifiers::ALT)
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "a\nb;\n");
+}
+
+/// Replace the text of the active buffer
+fn set_text(harness: &mut EditorTestHarness, text: &str) {
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Delete, KeyModifiers::NONE)
+        .unwrap();
+    harness.editor_mut().paste_text(text.to_string());
+    harness.render().unwrap();
+}
+
+/// Saving the edit buffer of a macro parses it back into the macro
+#[test]
+fn test_edit_macro_buffer() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("context"));
+    let (mut harness, file_path) =
+        open_with_context(&temp_dir, &dir_context, Config::default(), "x\n");
+
+    run_command(&mut harness, "edit macro", &["wrap"]);
+    harness.assert_screen_contains("*Macro wrap*");
+    harness.assert_screen_contains("[]");
+
+    set_text(&mut harness, "[\n  \"Bogus\"\n]");
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert!(
+        status(&harness).contains("Invalid macro"),
+        "got: {}",
+        status(&harness)
+    );
+    assert!(!macros_file(&dir_context).contains("wrap"));
+
+    set_text(
+        &mut harness,
+        "// Wrap a line in parentheses\n[\n  \"MoveLineStart\",\n  {\"InsertChar\":\"(\"},\n  \"MoveLineEnd\",\n  {\"InsertChar\":\")\"}\n]\n",
+    );
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert!(
+        status(&harness).contains("updated"),
+        "got: {}",
+        status(&harness)
+    );
+    assert!(macros_file(&dir_context).contains("\"wrap\""));
+
+    harness.open_file(&file_path).unwrap();
+    run_command(&mut harness, "play macro", &["wrap"]);
+    assert_eq!(harness.get_buffer_content().unwrap(), "(x)\n");
+}
+
+/// Select lines `first` to `last` (0-based) of the active buffer
+fn select_lines(harness: &mut EditorTestHarness, path: &Path, first: usize, last: usize) {
+    harness.open_file(path).unwrap();
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    for _ in 0..first {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
+    for _ in first..=last {
+        harness
+            .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+}
+
+/// A macro plays on each line of the selection, or several times in a row
+#[test]
+fn test_play_macro_on_lines_and_times() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("context"));
+    let (mut harness, file_path) =
+        open_with_context(&temp_dir, &dir_context, Config::default(), "a\nb\nc\nd\n");
+    record_semicolon_macro(&mut harness, "semi");
+
+    select_lines(&mut harness, &file_path, 1, 2);
+    run_command(&mut harness, "play macro on selected lines", &["semi"]);
+    assert_eq!(harness.get_buffer_content().unwrap(), "a;\nb;\nc;\nd\n");
+    assert!(
+        status(&harness).contains("2 lines"),
+        "got: {}",
+        status(&harness)
+    );
+
+    // The cursor is left on the first line of the selection
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    run_command(&mut harness, "play macro n times", &["semi", "3"]);
+    assert_eq!(harness.get_buffer_content().unwrap(), "a;\nb;\nc;\nd;;;\n");
+}
+
+/// A macro can't be played an unbounded number of times
+#[test]
+fn test_play_macro_times_is_capped() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("context"));
+    let (mut harness, _) = open_with_context(&temp_dir, &dir_context, Config::default(), "a\n");
+    record_semicolon_macro(&mut harness, "semi");
+
+    run_command(&mut harness, "play macro n times", &["semi", "1000000"]);
+    assert_eq!(harness.get_buffer_content().unwrap(), "a;\n");
+    assert!(
+        status(&harness).contains("at most 10000 times"),
+        "got: {}",
+        status(&harness)
+    );
+}
@@ -4796,7 +4796,12 @@ fn test_completion_snippet_with_default() -> std::io::Result<()> {
         "Snippet should expand with default text"
     );
 
-    // Verify cursor is at $0 position (after the 4 spaces on line 2)
+    // The first tabstop is selected, Tab moves on to $2 and then to $0
+    assert_eq!(harness.get_selected_text(), "name");
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE)?;
+    let cursor_pos = harness.editor().active_state().cursors.primary().position;
+    assert_eq!(cursor_pos, 8, "Cursor should be at $2 position");
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE)?;
     let cursor_pos = harness.editor().active_state().cursors.primary().position;
     assert_eq!(cursor_pos, 16, "Cursor should be at $0 position");
 
@@ -0,0 +1,173 @@
+//! E2E tests for workspace/applyEdit requests from the language server
+
+use crate::common::fake_lsp::FakeLspServer;
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::path::Path;
+
+/// Start the applyEdit server for Rust, logging the editor's replies to `reply_log`
+fn setup(temp_dir: &Path, reply_log: &Path) -> std::io::Result<EditorTestHarness> {
+    let mut config = fresh::config::Config::default();
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: FakeLspServer::apply_edit_script_path()
+                .to_string_lossy()
+                .to_string(),
+            args: vec![reply_log.to_string_lossy().to_string()],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+        },
+    );
+    EditorTestHarness::with_config_and_working_dir(120, 30, config, temp_dir.to_path_buf())
+}
+
+fn open_and_wait_for_server(harness: &mut EditorTestHarness, file: &Path) -> std::io::Result<()> {
+    harness.open_file(file)?;
+    harness.render()?;
+    harness.wait_until(|h| {
+        h.editor()
+            .running_lsp_servers()
+            .contains(&"rust".to_string())
+    })
+}
+
+/// Text edits, file creation, rename and deletion from one applyEdit request
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_apply_edit_with_resource_operations() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_apply_edit()?;
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path();
+    let reply_log = dir.join("reply.json");
+    let main = dir.join("main.rs");
+    std::fs::write(&main, "fn main() {}\n")?;
+    std::fs::write(dir.join("old.rs"), "// old\n")?;
+    std::fs::write(dir.join("gone.rs"), "// gone\n")?;
+
+    let mut harness = setup(dir, &reply_log)?;
+    // An open buffer follows its file when the server renames it
+    harness.open_file(&dir.join("old.rs"))?;
+    open_and_wait_for_server(&mut harness, &main)?;
+
+    harness.send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)?;
+    harness.wait_until(|_| {
+        std::fs::read_to_string(&reply_log).is_ok_and(|reply| reply.contains("applied"))
+    })?;
+
+    let reply = std::fs::read_to_string(&reply_log)?;
+    assert!(reply.contains(r#""applied":true"#), "reply: {}", reply);
+    harness.assert_buffer_content("mod extracted;\nfn main() {}\n");
+    assert!(!dir.join("old.rs").exists());
+    assert_eq!(std::fs::read_to_string(dir.join("new.rs"))?, "// old\n");
+    assert!(!dir.join("gone.rs").exists());
+
+    // The new file was created on disk and edited in a background buffer
+    assert_eq!(std::fs::read_to_string(dir.join("extracted.rs"))?, "");
+    harness.render()?;
+    harness.assert_screen_contains("extracted.rs");
+    harness.assert_screen_contains("new.rs");
+    harness.assert_screen_not_contains("old.rs");
+
+    // The text edit is a single undo step in the edited buffer
+    harness.send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)?;
+    harness.assert_buffer_content("fn main() {}\n");
+    Ok(())
+}
+
+/// A failing change stops the edit and is reported to the server
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_apply_edit_reports_failure() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_apply_edit()?;
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path();
+    let reply_log = dir.join("reply.json");
+    let file = dir.join("fail.rs");
+    std::fs::write(&file, "fn main() {}\n")?;
+
+    let mut harness = setup(dir, &reply_log)?;
+    open_and_wait_for_server(&mut harness, &file)?;
+
+    harness.send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)?;
+    harness.wait_until(|_| {
+        std::fs::read_to_string(&reply_log).is_ok_and(|reply| reply.contains("applied"))
+    })?;
+
+    let reply = std::fs::read_to_string(&reply_log)?;
+    assert!(reply.contains(r#""applied":false"#), "reply: {}", reply);
+    assert!(reply.contains(r#""failedChange":1"#), "reply: {}", reply);
+    assert!(
+        reply.contains("missing.rs does not exist"),
+        "reply: {}",
+        reply
+    );
+    harness.render()?;
+    harness.assert_screen_contains("Language server edit failed");
+    Ok(())
+}
+
+/// Deleting a symlink removes the link, not the directory it points to
+#[test]
+#[cfg(unix)]
+fn test_apply_edit_deletes_symlink_itself() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_apply_edit()?;
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path();
+    let reply_log = dir.join("reply.json");
+    let file = dir.join("link.rs");
+    std::fs::write(&file, "fn main() {}\n")?;
+    let target = dir.join("target");
+    std::fs::create_dir(&target)?;
+    std::fs::write(target.join("kept.rs"), "// kept\n")?;
+    std::os::unix::fs::symlink(&target, dir.join("linked"))?;
+
+    let mut harness = setup(dir, &reply_log)?;
+    open_and_wait_for_server(&mut harness, &file)?;
+
+    harness.send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)?;
+    harness.wait_until(|_| {
+        std::fs::read_to_string(&reply_log).is_ok_and(|reply| reply.contains("applied"))
+    })?;
+
+    let reply = std::fs::read_to_string(&reply_log)?;
+    assert!(reply.contains(r#""applied":true"#), "reply: {}", reply);
+    assert!(std::fs::symlink_metadata(dir.join("linked")).is_err());
+    assert_eq!(
+        std::fs::read_to_string(target.join("kept.rs"))?,
+        "// kept\n"
+    );
+    Ok(())
+}
+
+/// An edit made against another version of the document is refused
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_apply_edit_refuses_stale_version() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_apply_edit()?;
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path();
+    let reply_log = dir.join("reply.json");
+    let file = dir.join("stale.rs");
+    std::fs::write(&file, "fn main() {}\n")?;
+
+    let mut harness = setup(dir, &reply_log)?;
+    open_and_wait_for_server(&mut harness, &file)?;
+
+    harness.send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)?;
+    harness.wait_until(|_| {
+        std::fs::read_to_string(&reply_log).is_ok_and(|reply| reply.contains("applied"))
+    })?;
+
+    let reply = std::fs::read_to_string(&reply_log)?;
+    assert!(reply.contains(r#""applied":false"#), "reply: {}", reply);
+    assert!(
+        reply.contains("changed since the edit was made"),
+        "reply: {}",
+        reply
+    );
+    harness.assert_buffer_content("fn main() {}\n");
+    Ok(())
+}
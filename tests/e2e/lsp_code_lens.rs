@@ -0,0 +1,123 @@
+//! E2E tests for code lenses from the language server
+
+use crate::common::fake_lsp::FakeLspServer;
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::path::Path;
+
+/// Start the code lens server for Rust, logging its requests to `request_log`
+fn setup(temp_dir: &Path, request_log: &Path) -> std::io::Result<EditorTestHarness> {
+    let mut config = fresh::config::Config::default();
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: FakeLspServer::code_lens_script_path()
+                .to_string_lossy()
+                .to_string(),
+            args: vec![request_log.to_string_lossy().to_string()],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+        },
+    );
+    EditorTestHarness::with_config_and_working_dir(100, 30, config, temp_dir.to_path_buf())
+}
+
+/// Screen position of the first occurrence of `text`
+fn find_on_screen(harness: &EditorTestHarness, text: &str) -> Option<(u16, u16)> {
+    let (first_row, last_row) = harness.content_area_rows();
+    (first_row..=last_row).find_map(|row| {
+        let line = harness.get_row_text(row as u16);
+        let index = line.find(text)?;
+        Some((line[..index].chars().count() as u16, row as u16))
+    })
+}
+
+fn requests(request_log: &Path) -> String {
+    std::fs::read_to_string(request_log).unwrap_or_default()
+}
+
+/// Lenses are shown above their line, unresolved ones once resolved
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_code_lens_shown_above_line() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_code_lens()?;
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path();
+    let request_log = dir.join("requests.log");
+    let file = dir.join("main.rs");
+    std::fs::write(&file, "fn main() {\n    run();\n}\n")?;
+
+    let mut harness = setup(dir, &request_log)?;
+    harness.open_file(&file)?;
+    harness.render()?;
+
+    harness.wait_until(|h| find_on_screen(h, "Run test | 3 references").is_some())?;
+    let (_, lens_row) = find_on_screen(&harness, "Run test").unwrap();
+    let (_, code_row) = find_on_screen(&harness, "fn main").unwrap();
+    assert_eq!(lens_row + 1, code_row);
+    assert!(requests(&request_log).contains("codeLens/resolve"));
+
+    // The lens line is not part of the buffer
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "fn main() {\n    run();\n}\n"
+    );
+    Ok(())
+}
+
+/// Clicking a lens runs its command, and the server can refresh the lenses
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_code_lens_click_runs_command() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_code_lens()?;
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path();
+    let request_log = dir.join("requests.log");
+    let file = dir.join("main.rs");
+    std::fs::write(&file, "fn main() {\n    run();\n}\n")?;
+
+    let mut harness = setup(dir, &request_log)?;
+    harness.open_file(&file)?;
+    harness.render()?;
+    harness.wait_until(|h| find_on_screen(h, "Run test | 3 references").is_some())?;
+
+    let (col, row) = find_on_screen(&harness, "Run test").unwrap();
+    harness.mouse_click(col + 1, row)?;
+    harness.wait_until(|h| find_on_screen(h, "Run again").is_some())?;
+    assert!(requests(&request_log).contains("workspace/executeCommand test.run"));
+    // The click did not move the cursor
+    assert_eq!(harness.cursor_position(), 0);
+    Ok(())
+}
+
+/// "Run Code Lens" asks which lens of the cursor line to run
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_run_code_lens_from_keyboard() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_code_lens()?;
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path();
+    let request_log = dir.join("requests.log");
+    let file = dir.join("main.rs");
+    std::fs::write(&file, "fn main() {\n    run();\n}\n")?;
+
+    let mut harness = setup(dir, &request_log)?;
+    harness.open_file(&file)?;
+    harness.render()?;
+    harness.wait_until(|h| find_on_screen(h, "Run test | 3 references").is_some())?;
+
+    harness.send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)?;
+    harness.type_text("Run Code Lens")?;
+    harness.send_key(KeyCode::Enter, KeyModifiers::NONE)?;
+    harness.render()?;
+    harness.assert_screen_contains("Code lens:");
+
+    // The server does not provide the command of the references lens
+    harness.type_text("references")?;
+    harness.send_key(KeyCode::Enter, KeyModifiers::NONE)?;
+    harness.wait_until(|h| h.get_status_bar().contains("3 references failed"))?;
+    assert!(!requests(&request_log).contains("workspace/executeCommand"));
+    Ok(())
+}
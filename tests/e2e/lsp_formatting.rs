@@ -0,0 +1,134 @@
+//! E2E tests for formatting through the language server
+
+use crate::common::fake_lsp::FakeLspServer;
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::FormatterConfig;
+use std::path::{Path, PathBuf};
+
+const SOURCE: &str = "fn main() {\nlet x = 1\n}\n";
+
+/// Open SOURCE with the formatting server as Rust's formatter
+fn setup(
+    temp_dir: &Path,
+    formatter: FormatterConfig,
+    format_on_save: bool,
+) -> std::io::Result<(EditorTestHarness, PathBuf)> {
+    let file = temp_dir.join("test.rs");
+    std::fs::write(&file, SOURCE)?;
+
+    let mut config = fresh::config::Config::default();
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: FakeLspServer::formatting_script_path()
+                .to_string_lossy()
+                .to_string(),
+            args: vec![],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+        },
+    );
+    let rust = config.languages.get_mut("rust").unwrap();
+    rust.formatter = Some(formatter);
+    rust.format_on_save = format_on_save;
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(120, 30, config, temp_dir.to_path_buf())?;
+    harness.open_file(&file)?;
+    harness.render()?;
+    harness.wait_until(|h| {
+        h.editor()
+            .running_lsp_servers()
+            .contains(&"rust".to_string())
+    })?;
+    Ok((harness, file))
+}
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) -> std::io::Result<()> {
+    harness.send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)?;
+    harness.type_text(name)?;
+    harness.send_key(KeyCode::Enter, KeyModifiers::NONE)?;
+    harness.process_async_and_render()
+}
+
+/// "Format Buffer" applies the server's edits as a single undo step
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_format_buffer_with_lsp() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_formatting()?;
+    let temp_dir = tempfile::tempdir()?;
+    let (mut harness, _file) = setup(temp_dir.path(), FormatterConfig::lsp(), false)?;
+
+    run_command(&mut harness, "Format Buffer")?;
+    let formatted = format!("// formatted\n{}", SOURCE);
+    harness.wait_until(|h| h.get_buffer_content().as_deref() == Some(formatted.as_str()))?;
+    harness.assert_screen_contains("Formatted by language server");
+
+    harness.send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)?;
+    harness.assert_buffer_content(SOURCE);
+    Ok(())
+}
+
+/// "Format Selection" only replaces the selected lines
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_format_selection_with_lsp() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_formatting()?;
+    let temp_dir = tempfile::tempdir()?;
+    let (mut harness, _file) = setup(temp_dir.path(), FormatterConfig::lsp(), false)?;
+
+    // Without a selection there is nothing to format
+    run_command(&mut harness, "Format Selection")?;
+    harness.assert_screen_contains("No selection to format");
+
+    // Select the second line
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE)?;
+    harness.send_key(KeyCode::Down, KeyModifiers::SHIFT)?;
+    run_command(&mut harness, "Format Selection")?;
+    harness.wait_until(|h| h.get_buffer_content().as_deref() == Some("fn main() {\nRANGE\n}\n"))?;
+    Ok(())
+}
+
+/// Typing a trigger character lets the server reformat the line
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_on_type_formatting() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_formatting()?;
+    let temp_dir = tempfile::tempdir()?;
+    let formatter = FormatterConfig {
+        on_type: true,
+        ..FormatterConfig::lsp()
+    };
+    let (mut harness, _file) = setup(temp_dir.path(), formatter, false)?;
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE)?;
+    harness.send_key(KeyCode::End, KeyModifiers::NONE)?;
+    harness.type_text(";")?;
+    harness.wait_until(|h| {
+        h.get_buffer_content().as_deref() == Some("fn main() {\n>>let x = 1;\n}\n")
+    })?;
+
+    // The cursor stays after the typed character
+    harness.type_text(" ")?;
+    harness.assert_buffer_content("fn main() {\n>>let x = 1; \n}\n");
+    Ok(())
+}
+
+/// Format-on-save formats through the server and saves the result
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_format_on_save_with_lsp() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_formatting()?;
+    let temp_dir = tempfile::tempdir()?;
+    let (mut harness, file) = setup(temp_dir.path(), FormatterConfig::lsp(), true)?;
+
+    harness.send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)?;
+    let formatted = format!("// formatted\n{}", SOURCE);
+    harness.wait_until(|_| std::fs::read_to_string(&file).unwrap_or_default() == formatted)?;
+    harness.assert_buffer_content(&formatted);
+    assert!(!harness.editor().active_state().buffer.is_modified());
+    Ok(())
+}
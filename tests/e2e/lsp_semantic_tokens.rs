@@ -0,0 +1,92 @@
+//! E2E tests for semantic token highlighting from the language server
+
+use crate::common::fake_lsp::FakeLspServer;
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::style::Color;
+use std::path::Path;
+
+/// Start the semantic tokens server for Rust, logging its requests to `request_log`
+fn setup(temp_dir: &Path, request_log: &Path) -> std::io::Result<EditorTestHarness> {
+    let mut config = fresh::config::Config::default();
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: FakeLspServer::semantic_tokens_script_path()
+                .to_string_lossy()
+                .to_string(),
+            args: vec![request_log.to_string_lossy().to_string()],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+        },
+    );
+    EditorTestHarness::with_config_and_working_dir(120, 30, config, temp_dir.to_path_buf())
+}
+
+/// Foreground color of the first "value" on screen
+fn value_color(harness: &EditorTestHarness) -> Option<Color> {
+    let (first_row, last_row) = harness.content_area_rows();
+    (first_row..=last_row).find_map(|row| {
+        let text = harness.get_row_text(row as u16);
+        let index = text.find("value")?;
+        let column = text[..index].chars().count() as u16;
+        harness.get_cell_style(column, row as u16)?.fg
+    })
+}
+
+fn requests(request_log: &Path) -> String {
+    std::fs::read_to_string(request_log).unwrap_or_default()
+}
+
+/// Whole-document tokens color text, and edits fetch only the changes
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_semantic_tokens_full_and_delta() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_semantic_tokens()?;
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path();
+    let request_log = dir.join("requests.log");
+    let file = dir.join("main.rs");
+    std::fs::write(&file, "let value = compute();\n")?;
+
+    let mut harness = setup(dir, &request_log)?;
+    let theme = harness.editor().theme().clone();
+    harness.open_file(&file)?;
+    harness.render()?;
+
+    // The enumMember token overrides the tree-sitter variable color
+    harness.wait_until(|h| value_color(h) == Some(theme.syntax_constant))?;
+    assert!(requests(&request_log).contains("textDocument/semanticTokens/full"));
+
+    // After an edit the server answers with a delta turning it into a function
+    harness.send_key(KeyCode::End, KeyModifiers::CONTROL)?;
+    harness.type_text("// edited")?;
+    harness.wait_until(|h| value_color(h) == Some(theme.syntax_function))?;
+    assert!(requests(&request_log).contains("textDocument/semanticTokens/full/delta"));
+    Ok(())
+}
+
+/// Buffers with many lines only request the tokens around the viewport
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_semantic_tokens_range_for_long_files() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_semantic_tokens()?;
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path();
+    let request_log = dir.join("requests.log");
+    let file = dir.join("long.rs");
+    std::fs::write(&file, "let value = compute();\n".repeat(6000))?;
+
+    let mut harness = setup(dir, &request_log)?;
+    let theme = harness.editor().theme().clone();
+    harness.open_file(&file)?;
+    harness.render()?;
+
+    harness.wait_until(|h| value_color(h) == Some(theme.syntax_constant))?;
+    let log = requests(&request_log);
+    assert!(log.contains("textDocument/semanticTokens/range"), "{}", log);
+    assert!(!log.contains("textDocument/semanticTokens/full"), "{}", log);
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use tempfile::TempDir;
+
+/// Write the base, local, remote and output files of a merge
+fn write_files(
+    temp_dir: &TempDir,
+    base: &str,
+    local: &str,
+    remote: &str,
+) -> [std::path::PathBuf; 4] {
+    let paths = ["base.txt", "local.txt", "remote.txt", "output.txt"]
+        .map(|name| temp_dir.path().join(name));
+    for (path, text) in paths.iter().zip([base, local, remote, local]) {
+        std::fs::write(path, text).unwrap();
+    }
+    paths
+}
+
+/// `fresh --merge` shows the four panes and counts the conflicts
+#[test]
+fn test_merge_view_shows_panes_and_conflicts() {
+    let temp_dir = TempDir::new().unwrap();
+    let [base, local, remote, output] = write_files(
+        &temp_dir,
+        "first\nsecond\nthird\nfourth\nfifth\n",
+        "FIRST\nsecond\nmine\nfourth\nfifth\n",
+        "first\nsecond\ntheirs\nfourth\nFIFTH\n",
+    );
+    let mut harness = EditorTestHarness::new(160, 30).unwrap();
+    harness
+        .editor_mut()
+        .open_merge(&base, &local, &remote, &output)
+        .unwrap();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains("*Merge: output.txt*");
+    harness.assert_screen_contains("BASE");
+    harness.assert_screen_contains("LOCAL");
+    harness.assert_screen_contains("RESULT");
+    harness.assert_screen_contains("REMOTE");
+    // The separate changes are merged, the overlapping ones conflict
+    harness.assert_screen_contains("<<<<<<< LOCAL");
+    harness.assert_screen_contains("FIFTH");
+    harness.assert_screen_contains("Conflicts: 1");
+
+    harness
+        .send_key(KeyCode::Char('n'), KeyModifiers::ALT)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("No more conflicts");
+}
+
+/// Accepting a side resolves the conflict, and the result can be saved
+#[test]
+fn test_merge_view_accept_and_save() {
+    let temp_dir = TempDir::new().unwrap();
+    let [base, local, remote, output] =
+        write_files(&temp_dir, "a\nb\nc\n", "a\nlocal\nc\n", "a\nremote\nc\n");
+    let mut harness = EditorTestHarness::new(160, 30).unwrap();
+    harness
+        .editor_mut()
+        .open_merge(&base, &local, &remote, &output)
+        .unwrap();
+    harness.render().unwrap();
+
+    // The cursor starts on the first conflict
+    harness
+        .send_key(KeyCode::Char('b'), KeyModifiers::ALT)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Conflicts: 0");
+    harness.assert_screen_not_contains("<<<<<<<");
+
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&output).unwrap(),
+        "a\nlocal\nremote\nc\n"
+    );
+    // The other files are left alone
+    assert_eq!(std::fs::read_to_string(&local).unwrap(), "a\nlocal\nc\n");
+}
+
+/// The result pane can be typed in
+#[test]
+fn test_merge_view_edit_result() {
+    let temp_dir = TempDir::new().unwrap();
+    let [base, local, remote, output] =
+        write_files(&temp_dir, "a\nb\nc\n", "a\nlocal\nc\n", "a\nremote\nc\n");
+    let mut harness = EditorTestHarness::new(160, 30).unwrap();
+    harness
+        .editor_mut()
+        .open_merge(&base, &local, &remote, &output)
+        .unwrap();
+    harness.render().unwrap();
+
+    harness.send_key(KeyCode::Right, KeyModifiers::ALT).unwrap();
+    harness.type_text("new ").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("new remote");
+}
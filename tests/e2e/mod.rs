@@ -7,26 +7,40 @@ pub mod buffer_lifecycle;
 pub mod buffer_settings_commands;
 pub mod case_conversion;
 pub mod command_palette;
+pub mod conditional_keybindings;
 pub mod crash_repro;
 pub mod crlf_rendering;
+pub mod diff_view;
 pub mod document_model;
 pub mod emacs_actions;
+pub mod encoding;
 pub mod explorer_menu;
 pub mod file_browser;
 pub mod file_explorer;
 pub mod file_permissions;
+pub mod file_watching;
+pub mod folding;
+pub mod hex_editor;
+pub mod hierarchy;
 pub mod indent_dedent;
+pub mod instance_server;
+pub mod keyboard_macros;
 pub mod large_file_mode;
 pub mod lifecycle;
 pub mod line_wrapping;
 pub mod live_grep;
 pub mod locale;
 pub mod lsp;
+pub mod lsp_apply_edit;
+pub mod lsp_code_lens;
+pub mod lsp_formatting;
 pub mod lsp_order;
+pub mod lsp_semantic_tokens;
 pub mod margin;
 pub mod markdown_compose;
 pub mod menu_bar;
 pub mod merge_conflict;
+pub mod merge_view;
 pub mod mouse;
 pub mod movement;
 pub mod multi_file_opening;
@@ -34,7 +48,10 @@ pub mod multibyte_characters;
 pub mod multicursor;
 pub mod on_save_actions;
 pub mod open_folder;
+pub mod outline;
 pub mod paste;
+pub mod persistent_undo;
+pub mod plugin_permissions;
 #[cfg(feature = "plugins")]
 pub mod plugins;
 pub mod position_history;
@@ -44,7 +61,9 @@ pub mod position_history_truncate_debug;
 pub mod prompt;
 pub mod prompt_editing;
 pub mod recovery;
+pub mod remote_sftp;
 pub mod rendering;
+pub mod save_as_root;
 pub mod scroll_clearing;
 pub mod scrolling;
 pub mod search;
@@ -55,9 +74,11 @@ pub mod settings_paste;
 pub mod shell_command;
 pub mod slow_filesystem;
 pub mod smart_editing;
+pub mod snippets;
 pub mod split_tabs;
 pub mod split_view;
 pub mod split_view_expectations;
+pub mod status_bar_segments;
 pub mod stdin_input;
 pub mod tab_config;
 pub mod tab_drag;
@@ -69,6 +90,7 @@ pub mod terminal_resize;
 pub mod test_scrollbar_keybinds_cursor;
 pub mod theme;
 pub mod undo_redo;
+pub mod undo_tree;
 pub mod unicode_cursor;
 pub mod unicode_prompt_bugs;
 pub mod update_notification;
@@ -77,3 +99,5 @@ pub mod vi_mode;
 pub mod virtual_lines;
 pub mod visual_regression;
 pub mod warning_indicators;
+pub mod which_key;
+pub mod workspace_symbols;
@@ -10,7 +10,7 @@
 
 use crate::common::harness::EditorTestHarness;
 use crossterm::event::{KeyCode, KeyModifiers};
-use fresh::config::{Config, FormatterConfig, LanguageConfig, OnSaveAction};
+use fresh::config::{Config, FormatterConfig, FormatterSource, LanguageConfig, OnSaveAction};
 use tempfile::TempDir;
 
 /// Test format_on_save with formatter (replaces buffer content)
@@ -26,10 +26,12 @@ fn test_format_on_save() {
 
     // Configure formatter: sort the file content (stdin -> stdout)
     let formatter = FormatterConfig {
+        source: FormatterSource::Command,
         command: "sort".to_string(),
         args: vec![],
         stdin: true,
         timeout_ms: 5000,
+        on_type: false,
     };
 
     // Create config for "plaintext" language (matches .txt files)
@@ -291,10 +293,12 @@ fn test_formatter_stdin_mode() {
 
     // Configure formatter: convert to uppercase via stdin
     let formatter = FormatterConfig {
+        source: FormatterSource::Command,
         command: "tr".to_string(),
         args: vec!["a-z".to_string(), "A-Z".to_string()],
         stdin: true,
         timeout_ms: 5000,
+        on_type: false,
     };
 
     let mut config = Config::default();
@@ -458,10 +462,12 @@ fn test_formatter_not_found_shows_message() {
 
     // Configure a formatter with a non-existent command
     let formatter = FormatterConfig {
+        source: FormatterSource::Command,
         command: "nonexistent_formatter_xyz_12345".to_string(),
         args: vec![],
         stdin: true,
         timeout_ms: 5000,
+        on_type: false,
     };
 
     let mut config = Config::default();
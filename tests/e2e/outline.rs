@@ -0,0 +1,126 @@
+//! E2E tests for the document outline (tree-sitter tags, no language server)
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use tempfile::TempDir;
+
+const SOURCE: &str = "struct Point {\n    x: i32,\n}\n\nimpl Point {\n    fn origin() -> Self {\n        Point { x: 0 }\n    }\n}\n\nfn main() {\n    let p = Point::origin();\n}\n";
+
+fn open_source(harness: &mut EditorTestHarness, temp_dir: &TempDir) {
+    let file_path = temp_dir.path().join("test.rs");
+    std::fs::write(&file_path, SOURCE).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+}
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.process_async_and_render().unwrap();
+}
+
+/// The picker lists symbols with their kind and jumps to the chosen one
+#[test]
+fn test_goto_symbol_picker() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(100, 30).unwrap();
+    open_source(&mut harness, &temp_dir);
+
+    harness
+        .send_key(
+            KeyCode::Char('o'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        )
+        .unwrap();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains("Go to symbol:");
+    harness.assert_screen_contains("impl Point");
+    harness.assert_screen_contains("origin");
+    harness.assert_screen_contains("method");
+
+    harness.type_text("main").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    assert_eq!(harness.cursor_position(), SOURCE.find("main").unwrap());
+}
+
+/// Jumping to a symbol inside a fold opens the fold
+#[test]
+fn test_goto_symbol_unfolds() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(100, 30).unwrap();
+    open_source(&mut harness, &temp_dir);
+
+    run_command(&mut harness, "Fold All");
+    harness.assert_screen_not_contains("fn origin");
+
+    run_command(&mut harness, "Go to Symbol");
+    harness.type_text("origin").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    assert_eq!(harness.cursor_position(), SOURCE.find("origin").unwrap());
+    harness.assert_screen_contains("fn origin() -> Self {");
+}
+
+/// The outline panel lists the symbols, follows the cursor and jumps on Enter
+#[test]
+fn test_outline_panel() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(120, 30).unwrap();
+    open_source(&mut harness, &temp_dir);
+
+    run_command(&mut harness, "Toggle Outline Panel");
+    harness.assert_screen_contains("*Outline*");
+    harness.assert_screen_contains("  origin");
+
+    // The source keeps the focus
+    harness.assert_buffer_content(SOURCE);
+
+    // Move into main(): the panel cursor follows
+    let main_body = SOURCE.find("let p").unwrap();
+    let line = SOURCE[..main_body].matches('\n').count();
+    for _ in 0..line {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
+    harness.process_async_and_render().unwrap();
+
+    // Focus the panel: its cursor is on "main", the last symbol
+    harness
+        .send_key(KeyCode::Char(']'), KeyModifiers::ALT)
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap().lines().nth(4),
+        Some("main")
+    );
+    let panel_text = harness.get_buffer_content().unwrap();
+    assert_eq!(harness.cursor_position(), panel_text.find("main").unwrap());
+
+    // Pick "Point" (first line) and jump back to the source
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_buffer_content(SOURCE);
+    assert_eq!(harness.cursor_position(), SOURCE.find("Point").unwrap());
+
+    // Toggling again removes the panel
+    run_command(&mut harness, "Toggle Outline Panel");
+    harness.assert_screen_not_contains("*Outline*");
+    harness.assert_buffer_content(SOURCE);
+}
@@ -0,0 +1,129 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn undo(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+}
+
+fn save(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .wait_until(|h| !h.editor().active_state().buffer.is_modified())
+        .unwrap();
+}
+
+/// Close the active buffer, discarding any changes, and open `path` again
+fn reopen(harness: &mut EditorTestHarness, path: &Path) {
+    let buffer_id = harness.editor().active_buffer();
+    // Keep another buffer open, so closing this one doesn't focus the file explorer
+    harness.new_buffer().unwrap();
+    harness.editor_mut().force_close_buffer(buffer_id).unwrap();
+    harness.open_file(path).unwrap();
+    harness.render().unwrap();
+}
+
+/// Edits made before a file was closed can be undone after reopening it
+#[test]
+fn test_undo_after_reopening_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("notes.txt");
+    std::fs::write(&file_path, "first\n").unwrap();
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("second").unwrap();
+    save(&mut harness);
+
+    reopen(&mut harness, &file_path);
+    harness.assert_buffer_content("first\nsecond");
+    assert!(!harness.editor().active_state().buffer.is_modified());
+
+    undo(&mut harness);
+    harness.assert_buffer_content("first\nsecon");
+    assert!(harness.editor().active_state().buffer.is_modified());
+    for _ in 0.."secon".len() {
+        undo(&mut harness);
+    }
+    harness.assert_buffer_content("first\n");
+
+    // Redo brings the saved contents back
+    for _ in 0.."second".len() {
+        harness
+            .send_key(KeyCode::Char('y'), KeyModifiers::CONTROL)
+            .unwrap();
+    }
+    harness.assert_buffer_content("first\nsecond");
+    assert!(!harness.editor().active_state().buffer.is_modified());
+}
+
+/// Discarded changes can be redone after reopening the file
+#[test]
+fn test_discarded_changes_can_be_redone() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("notes.txt");
+    std::fs::write(&file_path, "").unwrap();
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.type_text("kept").unwrap();
+    save(&mut harness);
+    harness.type_text("!").unwrap();
+
+    reopen(&mut harness, &file_path);
+    harness.assert_buffer_content("kept");
+    harness
+        .send_key(KeyCode::Char('y'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("kept!");
+}
+
+/// A file changed outside the editor starts with an empty history
+#[test]
+fn test_external_change_drops_history() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("notes.txt");
+    std::fs::write(&file_path, "").unwrap();
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.type_text("mine").unwrap();
+    save(&mut harness);
+    let buffer_id = harness.editor().active_buffer();
+    harness.new_buffer().unwrap();
+    harness.editor_mut().close_buffer(buffer_id).unwrap();
+
+    std::fs::write(&file_path, "theirs").unwrap();
+    harness.open_file(&file_path).unwrap();
+    undo(&mut harness);
+    harness.assert_buffer_content("theirs");
+    assert!(!harness.editor().active_state().buffer.is_modified());
+}
+
+/// Nothing is kept when persistent undo is turned off
+#[test]
+fn test_persistent_undo_disabled() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("notes.txt");
+    std::fs::write(&file_path, "").unwrap();
+
+    let mut config = Config::default();
+    config.editor.persistent_undo = false;
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.type_text("text").unwrap();
+    save(&mut harness);
+
+    reopen(&mut harness, &file_path);
+    undo(&mut harness);
+    harness.assert_buffer_content("text");
+}
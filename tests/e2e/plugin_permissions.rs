@@ -0,0 +1,125 @@
+use crate::common::harness::{EditorTestHarness, HarnessOptions};
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, PluginPermissions};
+use fresh::config_io::DirectoryContext;
+use fresh::services::plugins::api::PluginCommand;
+use fresh::services::plugins::permissions::Capability;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+/// Config in which plugins have to ask for their capabilities
+fn untrusted_config() -> Config {
+    let mut plugin_permissions = HashMap::new();
+    plugin_permissions.insert("*".to_string(), PluginPermissions::default());
+    Config {
+        plugin_permissions,
+        ..Config::default()
+    }
+}
+
+fn request(harness: &mut EditorTestHarness, capability: Capability, entry: &str, id: u64) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::RequestPluginPermission {
+            plugin_name: "demo".to_string(),
+            capability,
+            entry: entry.to_string(),
+            description: entry.to_string(),
+            request_id: id,
+        })
+        .unwrap();
+    harness.editor_mut().process_async_messages();
+    harness.render().unwrap();
+}
+
+fn answer(harness: &mut EditorTestHarness, text: &str) {
+    harness.type_text(text).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+fn granted(harness: &EditorTestHarness) -> Option<PluginPermissions> {
+    harness
+        .editor()
+        .config()
+        .plugin_permissions
+        .get("demo")
+        .cloned()
+}
+
+/// A declared entry is asked for once; "yes" is remembered in the user config
+#[test]
+fn test_allow_is_saved_to_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(temp_dir.path());
+    let mut harness = EditorTestHarness::create(
+        100,
+        24,
+        HarnessOptions::new()
+            .with_config(untrusted_config())
+            .with_working_dir(temp_dir.path().to_path_buf())
+            .with_shared_dir_context(dir_context.clone()),
+    )
+    .unwrap();
+
+    request(&mut harness, Capability::Process, "git", 1);
+    let prompt = harness.get_prompt_line();
+    assert!(
+        prompt.contains("Plugin 'demo' wants to run git"),
+        "got: {}",
+        prompt
+    );
+
+    // A second request for the same entry waits for the same answer
+    request(&mut harness, Capability::Process, "git", 2);
+    answer(&mut harness, "y");
+    assert!(harness.get_status_bar().contains("Allowed plugin 'demo'"));
+    assert_eq!(granted(&harness).unwrap().process, vec!["git".to_string()]);
+
+    let saved = std::fs::read_to_string(dir_context.config_path()).unwrap();
+    assert!(saved.contains("plugin_permissions"), "got: {}", saved);
+    assert!(saved.contains("\"git\""), "got: {}", saved);
+
+    // Allowed entries are not asked for again
+    request(&mut harness, Capability::Process, "git", 3);
+    assert!(!harness.get_prompt_line().contains("wants to"));
+}
+
+/// "once" allows for this session only, anything else denies
+#[test]
+fn test_allow_once_and_deny() {
+    let mut harness = EditorTestHarness::with_config(100, 24, untrusted_config()).unwrap();
+
+    request(&mut harness, Capability::FsRead, "${workspace}", 1);
+    assert!(harness
+        .get_prompt_line()
+        .contains("Plugin 'demo' wants to read files in ${workspace}"));
+    answer(&mut harness, "o");
+    assert!(granted(&harness).is_none());
+
+    request(&mut harness, Capability::Env, "HOME", 2);
+    answer(&mut harness, "n");
+    assert!(harness.get_status_bar().contains("Denied plugin 'demo'"));
+
+    // Neither is asked for again in this session
+    request(&mut harness, Capability::FsRead, "${workspace}", 3);
+    request(&mut harness, Capability::Env, "HOME", 4);
+    assert!(!harness.get_prompt_line().contains("wants to"));
+    assert!(granted(&harness).is_none());
+}
+
+/// Cancelling the prompt denies the request
+#[test]
+fn test_cancel_denies() {
+    let mut harness = EditorTestHarness::with_config(100, 24, untrusted_config()).unwrap();
+
+    request(&mut harness, Capability::FsWrite, "${config}/themes", 1);
+    assert!(harness.get_prompt_line().contains("wants to"));
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+    assert!(!harness.get_prompt_line().contains("wants to"));
+    assert!(harness.get_status_bar().contains("Denied plugin 'demo'"));
+    assert!(granted(&harness).is_none());
+}
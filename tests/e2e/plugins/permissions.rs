@@ -0,0 +1,96 @@
+//! Capability checks on plugins loaded from a project
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, PluginPermissions};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Reads `secret.txt` from the working directory as soon as it's loaded
+const READER_PLUGIN: &str = r#"
+const editor = getEditor();
+editor.readFile(editor.pathJoin([editor.getCwd(), "secret.txt"])).then(
+    (text) => editor.setStatus(`read: ${text}`),
+    (e) => editor.setStatus(`refused: ${e}`),
+);
+"#;
+
+/// A project with the reader plugin, and its manifest if given
+fn reader_project(manifest: Option<&str>) -> (tempfile::TempDir, PathBuf) {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(project_root.join("secret.txt"), "hunter2").unwrap();
+    fs::write(plugins_dir.join("reader.ts"), READER_PLUGIN).unwrap();
+    if let Some(manifest) = manifest {
+        fs::write(plugins_dir.join("reader.manifest.json"), manifest).unwrap();
+    }
+    (temp_dir, project_root)
+}
+
+fn status(harness: &EditorTestHarness) -> String {
+    harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Config in which plugins have to ask for their capabilities
+fn untrusted_config() -> Config {
+    let mut plugin_permissions = HashMap::new();
+    plugin_permissions.insert("*".to_string(), PluginPermissions::default());
+    Config {
+        plugin_permissions,
+        ..Config::default()
+    }
+}
+
+/// Reading a file the manifest doesn't declare fails without asking
+#[test]
+fn test_undeclared_read_is_denied() {
+    let (_temp_dir, project_root) = reader_project(None);
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(100, 24, untrusted_config(), project_root)
+            .unwrap();
+
+    harness
+        .wait_until(|h| status(h).contains("refused"))
+        .unwrap();
+    assert!(
+        status(&harness).contains("not declared in reader.manifest.json"),
+        "got: {}",
+        status(&harness)
+    );
+    assert!(!harness.get_prompt_line().contains("wants to"));
+}
+
+/// A declared read waits for the user, and goes through once allowed
+#[test]
+fn test_declared_read_asks_and_is_granted() {
+    let (_temp_dir, project_root) = reader_project(Some(r#"{"fs_read": ["${workspace}"]}"#));
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(100, 24, untrusted_config(), project_root)
+            .unwrap();
+
+    harness
+        .wait_until(|h| h.get_prompt_line().contains("wants to"))
+        .unwrap();
+    let prompt = harness.get_prompt_line();
+    assert!(
+        prompt.contains("Plugin 'reader' wants to read files in ${workspace}"),
+        "got: {}",
+        prompt
+    );
+    assert!(!status(&harness).contains("read:"));
+
+    harness.type_text("o").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| status(h) == "read: hunter2")
+        .unwrap();
+}
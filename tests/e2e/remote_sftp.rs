@@ -0,0 +1,193 @@
+// E2E tests for editing files over SFTP
+//
+// The SFTP backend talks to the in-process server in
+// `common::sftp_server`, which serves a temp dir, so these tests exercise
+// the protocol client, the backend and the editor's use of it without sshd.
+
+use crate::common::harness::{EditorTestHarness, HarnessOptions};
+use crate::common::sftp_server::{sftp_backend, sftp_client, SftpServerOptions};
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::fs::{FsBackend, SftpFsBackend, SlowFsConfig};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// A harness whose files are all accessed over SFTP
+fn remote_harness(dir: &Path, options: HarnessOptions) -> EditorTestHarness {
+    EditorTestHarness::create(
+        100,
+        24,
+        options
+            .with_working_dir(dir.to_path_buf())
+            .with_fs_backend(sftp_backend()),
+    )
+    .unwrap()
+}
+
+/// Reads, writes, renames, directories and removal through the backend,
+/// with a file large enough to take several windows of pipelined reads
+#[test]
+fn test_sftp_backend_file_operations() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    // Short reads make the client ask again for the rest
+    let backend = SftpFsBackend::new(sftp_client(SftpServerOptions {
+        max_read: 10_000,
+        ..Default::default()
+    }));
+
+    let data: Vec<u8> = (0..2_000_000u32).map(|i| (i % 251) as u8).collect();
+    let path = dir.join("big.bin");
+    runtime.block_on(backend.write(&path, &data)).unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), data);
+
+    let range = runtime
+        .block_on(backend.read_range(&path, 123_456, 1_000_000))
+        .unwrap();
+    assert_eq!(range, &data[123_456..1_123_456]);
+    // Reading past the end returns what there is
+    let tail = runtime
+        .block_on(backend.read_range(&path, 1_999_990, 100))
+        .unwrap();
+    assert_eq!(tail, &data[1_999_990..]);
+
+    let entry = runtime.block_on(backend.get_entry(&path)).unwrap();
+    assert_eq!(entry.metadata.unwrap().size, Some(data.len() as u64));
+
+    let sub = dir.join("sub");
+    runtime.block_on(backend.mkdir(&sub)).unwrap();
+    let moved = sub.join("moved.bin");
+    runtime.block_on(backend.rename(&path, &moved)).unwrap();
+    assert!(runtime.block_on(backend.is_dir(&sub)).unwrap());
+    assert!(!runtime.block_on(backend.exists(&path)));
+
+    let entries = runtime.block_on(backend.read_dir(&sub)).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name, "moved.bin");
+
+    runtime.block_on(backend.remove(&sub)).unwrap();
+    assert!(!sub.exists());
+    let missing = runtime
+        .block_on(backend.read_range(&moved, 0, 1))
+        .unwrap_err();
+    assert_eq!(missing.kind(), std::io::ErrorKind::NotFound);
+}
+
+/// Without the posix-rename extension, writing still replaces the file and
+/// keeps its permissions
+#[test]
+fn test_sftp_write_without_posix_rename() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("notes.txt");
+    std::fs::write(&path, "old").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let backend = SftpFsBackend::new(sftp_client(SftpServerOptions {
+        posix_rename: false,
+        ..Default::default()
+    }));
+    runtime.block_on(backend.write(&path, b"new")).unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+    assert!(!temp_dir.path().join("notes.tmp").exists());
+}
+
+/// A remote file is opened, edited and saved through the backend
+#[test]
+fn test_edit_and_save_remote_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("config.toml");
+    std::fs::write(&path, "name = \"remote\"\n").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+    let mut harness = remote_harness(temp_dir.path(), HarnessOptions::new());
+    harness.open_file(&path).unwrap();
+    harness.assert_screen_contains("name = \"remote\"");
+    assert!(harness.editor().active_state().buffer.is_remote());
+
+    harness.type_text("# ").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Saved");
+
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "# name = \"remote\"\n"
+    );
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+/// A file that doesn't exist on the host yet is created there on save
+#[test]
+fn test_create_remote_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("new.txt");
+
+    let mut harness = remote_harness(temp_dir.path(), HarnessOptions::new());
+    harness.open_file(&path).unwrap();
+    harness.type_text("hello").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+}
+
+/// The file explorer lists the remote project and creates files in it
+#[test]
+fn test_file_explorer_on_remote_project() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::create_dir(temp_dir.path().join("src")).unwrap();
+    std::fs::write(temp_dir.path().join("README.md"), "# Remote\n").unwrap();
+
+    let mut harness = remote_harness(temp_dir.path(), HarnessOptions::new());
+    harness.editor_mut().toggle_file_explorer();
+    harness.wait_for_file_explorer_item("README.md").unwrap();
+    harness.assert_screen_contains("src");
+
+    harness.editor_mut().file_explorer_new_directory();
+    harness.render().unwrap();
+    let created: Vec<_> = std::fs::read_dir(temp_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with("New Folder"))
+        .collect();
+    assert_eq!(created.len(), 1);
+}
+
+/// With network latency, opening and saving a remote file reads it once
+/// and writes it once
+#[test]
+fn test_remote_file_on_slow_network() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("slow.txt");
+    std::fs::write(&path, "line one\nline two\n").unwrap();
+
+    let mut harness = remote_harness(
+        temp_dir.path(),
+        HarnessOptions::new().with_slow_fs(SlowFsConfig::slow_network()),
+    );
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    harness.open_file(&path).unwrap();
+    harness.assert_screen_contains("line two");
+    harness.type_text("// ").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+
+    let metrics = runtime.block_on(harness.get_fs_metrics_snapshot()).unwrap();
+    assert_eq!(metrics.read_calls, 1);
+    assert_eq!(metrics.write_calls, 1);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "// line one\nline two\n"
+    );
+}
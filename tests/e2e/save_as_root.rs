@@ -0,0 +1,95 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// A stand-in for `sudo` that never has cached credentials, takes the
+/// password "hunter2" on stdin and runs its command as the current user
+const FAKE_SUDO: &str = r#"#!/bin/sh
+if [ "$1" = "-n" ]; then exit 1; fi
+read -r password
+if [ "$password" != "hunter2" ]; then echo "Sorry, try again." >&2; exit 1; fi
+shift 4
+exec "$@"
+"#;
+
+/// A harness whose "Save as Root" helper is the fake `sudo` in `dir`
+fn harness_with_fake_sudo(dir: &Path) -> EditorTestHarness {
+    let sudo = dir.join("sudo");
+    std::fs::write(&sudo, FAKE_SUDO).unwrap();
+    std::fs::set_permissions(&sudo, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut config = Config::default();
+    config.editor.privileged_save_command = sudo.display().to_string();
+    EditorTestHarness::with_config(100, 24, config).unwrap()
+}
+
+/// Run "Save as Root" and answer the password prompt with `password`
+fn save_as_root(harness: &mut EditorTestHarness, password: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.type_text("Save as Root").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.type_text(password).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Password: *******");
+    harness.assert_screen_not_contains(password);
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_prompt_closed().unwrap();
+    harness.render().unwrap();
+}
+
+/// Saving as root writes through the helper with the password from the
+/// prompt, and keeps the file's permissions
+#[test]
+fn test_save_as_root_keeps_permissions() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("hosts");
+    std::fs::write(&path, "127.0.0.1 localhost\n").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+    let mut harness = harness_with_fake_sudo(temp_dir.path());
+    harness.open_file(&path).unwrap();
+    harness.type_text("# ").unwrap();
+
+    save_as_root(&mut harness, "hunter2");
+    harness.wait_for_screen_contains("Saved").unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "# 127.0.0.1 localhost\n"
+    );
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+    assert!(!temp_dir.path().join("hosts.tmp").exists());
+}
+
+/// A wrong password leaves the file and the buffer's modified state alone
+#[test]
+fn test_save_as_root_wrong_password() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("hosts");
+    std::fs::write(&path, "127.0.0.1 localhost\n").unwrap();
+
+    let mut harness = harness_with_fake_sudo(temp_dir.path());
+    harness.open_file(&path).unwrap();
+    harness.type_text("# ").unwrap();
+
+    save_as_root(&mut harness, "hunter3");
+    harness
+        .wait_for_screen_contains("Save as root failed: Sorry")
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "127.0.0.1 localhost\n"
+    );
+    assert!(harness.editor().active_state().buffer.is_modified());
+}
@@ -393,3 +393,29 @@ fn test_large_file_editing_with_slow_fs() {
         "Last line should be present"
     );
 }
+
+/// Local files are read and saved through the filesystem backend, so a slow
+/// backend sees the buffer's reads and writes
+#[test]
+fn test_open_and_save_go_through_slow_fs() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("notes.txt");
+    std::fs::write(&path, "hello\n").unwrap();
+
+    let slow_config = SlowFsConfig::uniform(Duration::from_millis(5));
+    let mut harness = EditorTestHarness::with_slow_fs(80, 24, slow_config).unwrap();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let before = runtime.block_on(harness.get_fs_metrics_snapshot()).unwrap();
+    harness.open_file(&path).unwrap();
+    let opened = runtime.block_on(harness.get_fs_metrics_snapshot()).unwrap();
+    assert!(opened.read_calls > before.read_calls);
+
+    harness.type_text("> ").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    let saved = runtime.block_on(harness.get_fs_metrics_snapshot()).unwrap();
+    assert!(saved.write_calls > opened.write_calls);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "> hello\n");
+}
@@ -0,0 +1,174 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config_io::DirectoryContext;
+use std::fs;
+use tempfile::TempDir;
+
+const RUST_SNIPPETS: &str = r##"{
+    "Function": {
+        "prefix": "fn",
+        "body": ["fn ${1:name}(${2:args}) {", "\t$0", "}"]
+    },
+    "Let and print": {
+        "prefix": "lp",
+        "body": "let ${1:x} = 1;\nprintln!(\"{}\", $1);$0"
+    },
+    "Log": {
+        "prefix": "log",
+        "body": "${1|debug,info,warn|}!(\"$2\");"
+    },
+    "Header": {
+        "prefix": "hdr",
+        "body": "// ${TM_FILENAME_BASE} (${TM_FILENAME})",
+        "description": "File header"
+    }
+}"##;
+
+/// Open `main.rs` in an editor whose config directory has the Rust snippets above
+fn harness_with_snippets(temp_dir: &TempDir) -> EditorTestHarness {
+    let project = temp_dir.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let file_path = project.join("main.rs");
+    fs::write(&file_path, "").unwrap();
+
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("context"));
+    fs::create_dir_all(dir_context.snippets_dir()).unwrap();
+    fs::write(dir_context.snippets_dir().join("rust.json"), RUST_SNIPPETS).unwrap();
+
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        100,
+        24,
+        Default::default(),
+        project,
+        dir_context,
+    )
+    .unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+    harness
+}
+
+fn tab(harness: &mut EditorTestHarness) {
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+}
+
+/// Tab after a prefix expands the snippet, then Tab and Shift+Tab walk its tabstops
+#[test]
+fn test_tab_expands_prefix_and_walks_tabstops() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_snippets(&temp_dir);
+
+    harness.type_text("fn").unwrap();
+    tab(&mut harness);
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "fn name(args) {\n    \n}"
+    );
+    assert_eq!(harness.get_selected_text(), "name");
+
+    // Typing replaces the placeholder
+    harness.type_text("main").unwrap();
+    tab(&mut harness);
+    assert_eq!(harness.get_selected_text(), "args");
+
+    harness
+        .send_key(KeyCode::BackTab, KeyModifiers::SHIFT)
+        .unwrap();
+    assert_eq!(harness.get_selected_text(), "main");
+
+    // The final tabstop ends the session, so Tab indents again
+    tab(&mut harness);
+    tab(&mut harness);
+    assert_eq!(harness.cursor_position(), "fn main(args) {\n    ".len());
+    tab(&mut harness);
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "fn main(args) {\n        \n}"
+    );
+}
+
+/// Typing into a mirrored tabstop updates every copy of it
+#[test]
+fn test_mirrored_tabstops_update_together() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_snippets(&temp_dir);
+
+    harness.type_text("lp").unwrap();
+    tab(&mut harness);
+    assert_eq!(harness.cursor_count(), 2);
+
+    harness.type_text("count").unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "let count = 1;\nprintln!(\"{}\", count);"
+    );
+
+    tab(&mut harness);
+    assert_eq!(harness.cursor_count(), 1);
+    assert_eq!(
+        harness.cursor_position(),
+        harness.get_buffer_content().unwrap().len()
+    );
+}
+
+/// A choice tabstop opens a popup with its choices
+#[test]
+fn test_choice_tabstop_popup() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_snippets(&temp_dir);
+
+    harness.type_text("log").unwrap();
+    tab(&mut harness);
+    harness.render().unwrap();
+    harness.assert_screen_contains("warn");
+    assert_eq!(harness.get_buffer_content().unwrap(), "debug!(\"\");");
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "info!(\"\");");
+
+    tab(&mut harness);
+    harness.type_text("ready").unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "info!(\"ready\");");
+}
+
+/// Insert Snippet picks a snippet from the command palette, resolving its variables
+#[test]
+fn test_insert_snippet_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_snippets(&temp_dir);
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.type_text("insert snippet").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("File header");
+
+    harness.type_text("Header").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_prompt_closed().unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "// main (main.rs)");
+}
+
+/// Tab without a snippet prefix before the cursor still indents
+#[test]
+fn test_tab_without_prefix_indents() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_snippets(&temp_dir);
+
+    // "xfn" is not the "fn" prefix
+    harness.type_text("xfn").unwrap();
+    tab(&mut harness);
+    let content = harness.get_buffer_content().unwrap();
+    assert!(content.starts_with("xfn ") && content.trim_end() == "xfn");
+}
@@ -0,0 +1,164 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, StatusBarConfig, StatusBarSegment};
+use fresh::services::plugins::api::PluginCommand;
+use std::fs;
+use tempfile::TempDir;
+
+fn segment(name: &str, format: Option<&str>) -> StatusBarSegment {
+    StatusBarSegment {
+        format: format.map(str::to_string),
+        ..StatusBarSegment::new(name)
+    }
+}
+
+fn config_with(status_bar: StatusBarConfig) -> Config {
+    Config {
+        status_bar,
+        ..Config::default()
+    }
+}
+
+/// The configured segments replace the default ones, in their order
+#[test]
+fn test_configured_segments() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = temp_dir.path().join("repo");
+    fs::create_dir_all(repo.join(".git")).unwrap();
+    fs::write(
+        repo.join(".git").join("HEAD"),
+        "ref: refs/heads/feature-x\n",
+    )
+    .unwrap();
+    let file_path = repo.join("main.rs");
+    fs::write(&file_path, "fn main() {}\n").unwrap();
+
+    let config = config_with(StatusBarConfig {
+        left: vec![
+            segment("file", Some("{name}")),
+            segment("git_branch", None),
+            segment("diagnostics", Some("E:{errors}")),
+            segment("position", Some("{line}:{col}")),
+        ],
+        center: vec![],
+        right: vec![segment("language", None), segment("line_ending", None)],
+    });
+    let mut harness = EditorTestHarness::with_config(100, 24, config).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.editor_mut().process_async_messages();
+    harness.render().unwrap();
+
+    let status = harness.get_status_bar();
+    assert!(
+        status.starts_with("main.rs | feature-x | E:0 | 1:1 "),
+        "got: {}",
+        status
+    );
+    assert!(status.trim_end().ends_with("rust  LF"), "got: {}", status);
+    assert!(!status.contains("Palette"), "got: {}", status);
+}
+
+/// Plugins set their own segments, shown by the "plugins" segment unless
+/// the config places them
+#[test]
+fn test_plugin_segments() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let set_segment = |harness: &mut EditorTestHarness, text: &str| {
+        harness
+            .editor_mut()
+            .handle_plugin_command(PluginCommand::SetStatusSegment {
+                name: "tests".to_string(),
+                text: text.to_string(),
+            })
+            .unwrap();
+        harness.render().unwrap();
+    };
+
+    set_segment(&mut harness, "12 passed");
+    assert!(
+        harness.get_status_bar().contains(" | 12 passed"),
+        "got: {}",
+        harness.get_status_bar()
+    );
+
+    set_segment(&mut harness, "");
+    assert!(!harness.get_status_bar().contains("passed"));
+
+    let mut status_bar = StatusBarConfig::default();
+    status_bar
+        .right
+        .insert(0, segment("tests", Some("Tests: {text}")));
+    let mut harness = EditorTestHarness::with_config(100, 24, config_with(status_bar)).unwrap();
+    set_segment(&mut harness, "3 failed");
+    let status = harness.get_status_bar();
+    let position = status.find("Tests: 3 failed").expect(&status);
+    assert!(position > status.find("Ln 1").unwrap(), "got: {}", status);
+}
+
+/// The selection and macro recording segments show only while there is
+/// something to show
+#[test]
+fn test_selection_and_macro_segments() {
+    let config = config_with(StatusBarConfig {
+        left: vec![
+            segment("selection", None),
+            segment("macro", None),
+            segment("position", None),
+        ],
+        center: vec![],
+        right: vec![],
+    });
+    let mut harness = EditorTestHarness::with_config(100, 24, config).unwrap();
+    harness.type_text("one\ntwo\n").unwrap();
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    assert!(harness.get_status_bar().starts_with("Ln 1, Col 1 "));
+
+    for _ in 0..3 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    harness.render().unwrap();
+    assert!(
+        harness
+            .get_status_bar()
+            .starts_with("3 bytes selected | Ln 1, Col 4 "),
+        "got: {}",
+        harness.get_status_bar()
+    );
+
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+    harness.render().unwrap();
+    assert!(
+        harness
+            .get_status_bar()
+            .starts_with("2 lines (7 bytes) selected"),
+        "got: {}",
+        harness.get_status_bar()
+    );
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("record macro").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("q").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    assert!(
+        harness
+            .get_status_bar()
+            .starts_with("2 lines (7 bytes) selected | Recording macro q | Ln 2"),
+        "got: {}",
+        harness.get_status_bar()
+    );
+}
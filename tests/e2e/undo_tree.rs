@@ -0,0 +1,109 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use tempfile::TempDir;
+
+fn undo(harness: &mut EditorTestHarness, times: usize) {
+    for _ in 0..times {
+        harness
+            .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+            .unwrap();
+    }
+}
+
+fn press(harness: &mut EditorTestHarness, code: KeyCode, times: usize) {
+    for _ in 0..times {
+        harness.send_key(code, KeyModifiers::NONE).unwrap();
+    }
+}
+
+/// Run a command from the command palette, answering its prompt if `input` is given
+fn run_command(harness: &mut EditorTestHarness, command: &str, input: Option<&str>) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.type_text(command).unwrap();
+    press(harness, KeyCode::Enter, 1);
+    if let Some(input) = input {
+        harness.wait_for_prompt().unwrap();
+        harness.type_text(input).unwrap();
+        press(harness, KeyCode::Enter, 1);
+    }
+    harness.wait_for_prompt_closed().unwrap();
+    harness.render().unwrap();
+}
+
+fn open_file(harness: &mut EditorTestHarness, temp_dir: &TempDir) {
+    let file_path = temp_dir.path().join("colors.txt");
+    std::fs::write(&file_path, "").unwrap();
+    harness.open_file(&file_path).unwrap();
+}
+
+/// Edits undone before typing something else stay in the history, and the
+/// history panel can go back to them
+#[test]
+fn test_undo_history_restores_abandoned_branch() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(120, 30).unwrap();
+    open_file(&mut harness, &temp_dir);
+
+    harness.type_text("red").unwrap();
+    undo(&mut harness, 3);
+    harness.type_text("blue").unwrap();
+    let source = harness.editor().active_buffer();
+
+    run_command(&mut harness, "Undo History", None);
+    harness.assert_screen_contains("*Undo History*");
+    harness.assert_screen_contains("inserted \"r\"");
+    harness.assert_screen_contains("inserted \"b\"");
+
+    // The list starts at the current state; the "red" branch is listed
+    // before the "blue" one, ending four lines up
+    press(&mut harness, KeyCode::Up, 4);
+    harness.render().unwrap();
+    harness.assert_screen_contains("Selected");
+    harness.assert_screen_contains("red");
+
+    press(&mut harness, KeyCode::Enter, 1);
+    harness.assert_screen_not_contains("blue");
+
+    // Closing the panel goes back to the file, where undo continues along
+    // the restored branch
+    harness
+        .send_key(KeyCode::Char('q'), KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("*Undo History*");
+    assert_eq!(harness.editor().active_buffer(), source);
+    harness.assert_buffer_content("red");
+    undo(&mut harness, 1);
+    harness.assert_buffer_content("re");
+}
+
+/// "Go Earlier" and "Go Later" move through the history by steps or by time
+#[test]
+fn test_time_travel_commands() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    open_file(&mut harness, &temp_dir);
+    harness.type_text("abc").unwrap();
+
+    run_command(&mut harness, "Undo History: Go Earlier", Some("2"));
+    harness.assert_buffer_content("a");
+    assert!(harness.editor().active_state().buffer.is_modified());
+
+    run_command(&mut harness, "Undo History: Go Later", Some("1 step"));
+    harness.assert_buffer_content("ab");
+
+    // Every edit was made within the last day
+    run_command(&mut harness, "Undo History: Go Earlier", Some("1 day"));
+    harness.assert_buffer_content("");
+    assert!(!harness.editor().active_state().buffer.is_modified());
+
+    run_command(&mut harness, "Undo History: Go Earlier", Some("1"));
+    harness.assert_screen_contains("Already at the oldest state");
+
+    run_command(&mut harness, "Undo History: Go Later", Some("soon"));
+    harness.assert_screen_contains("Not a number of edits");
+    harness.assert_buffer_content("");
+}
@@ -0,0 +1,112 @@
+use crate::common::harness::{EditorTestHarness, HarnessOptions};
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::services::plugins::api::PluginCommand;
+use std::time::Duration;
+
+fn emacs_harness(config: Config) -> EditorTestHarness {
+    let config = Config {
+        active_keybinding_map: "emacs".into(),
+        ..config
+    };
+    EditorTestHarness::create(
+        100,
+        30,
+        HarnessOptions::new()
+            .with_config(config)
+            .with_preserved_keybinding_map(),
+    )
+    .unwrap()
+}
+
+/// After a chord prefix is held for the delay, the keys that can follow are listed
+#[test]
+fn test_which_key_popup_after_delay() {
+    let mut harness = emacs_harness(Config::default());
+    harness.type_text("hello").unwrap();
+
+    harness
+        .send_key(KeyCode::Char('x'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.advance_time(Duration::from_millis(500));
+    harness.editor_mut().process_async_messages();
+    harness.render().unwrap();
+    assert!(!harness.screen_to_string().contains("Split Vertical"));
+
+    harness.advance_time(Duration::from_millis(600));
+    harness.editor_mut().process_async_messages();
+    harness.render().unwrap();
+    let screen = harness.screen_to_string();
+    assert!(screen.contains("Ctrl+X"), "screen:\n{}", screen);
+    assert!(screen.contains("Split Vertical"), "screen:\n{}", screen);
+    assert!(screen.contains("Save File"), "screen:\n{}", screen);
+
+    // The next key closes the popup and completes the chord
+    harness
+        .send_key(KeyCode::Char('3'), KeyModifiers::NONE)
+        .unwrap();
+    let screen = harness.screen_to_string();
+    assert!(!screen.contains("Save File"), "screen:\n{}", screen);
+    assert_eq!(harness.get_buffer_content().unwrap(), "hello");
+}
+
+/// Quickly typed chords and a disabled setting never show the popup
+#[test]
+fn test_which_key_not_shown() {
+    let mut harness = emacs_harness(Config::default());
+    harness
+        .send_key(KeyCode::Char('x'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('o'), KeyModifiers::NONE)
+        .unwrap();
+    harness.advance_time(Duration::from_secs(2));
+    harness.editor_mut().process_async_messages();
+    harness.render().unwrap();
+    assert!(!harness.screen_to_string().contains("Split Vertical"));
+
+    let mut config = Config::default();
+    config.editor.which_key_enabled = false;
+    let mut harness = emacs_harness(config);
+    harness
+        .send_key(KeyCode::Char('x'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.advance_time(Duration::from_secs(2));
+    harness.editor_mut().process_async_messages();
+    harness.render().unwrap();
+    assert!(!harness.screen_to_string().contains("Split Vertical"));
+}
+
+/// Chords of the editor mode set by a plugin are listed instead of the keymap's
+#[test]
+fn test_which_key_editor_mode_chords() {
+    let mut config = Config::default();
+    config.editor.which_key_delay_ms = 0;
+    let mut harness = EditorTestHarness::with_config(100, 30, config).unwrap();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::DefineMode {
+            name: "vi-normal".to_string(),
+            parent: None,
+            bindings: vec![
+                ("g g".to_string(), "move_document_start".to_string()),
+                ("g u".to_string(), "vi_lowercase".to_string()),
+                ("g z z".to_string(), "scroll_center".to_string()),
+            ],
+            read_only: true,
+        })
+        .unwrap();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::SetEditorMode {
+            mode: Some("vi-normal".to_string()),
+        })
+        .unwrap();
+
+    harness
+        .send_key(KeyCode::Char('g'), KeyModifiers::NONE)
+        .unwrap();
+    let screen = harness.screen_to_string();
+    assert!(screen.contains("vi_lowercase"), "screen:\n{}", screen);
+    assert!(screen.contains("+more keys"), "screen:\n{}", screen);
+}
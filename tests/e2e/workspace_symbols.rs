@@ -0,0 +1,164 @@
+//! E2E tests for workspace symbol search (workspace/symbol)
+
+use crate::common::fake_lsp::FakeLspServer;
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::path::{Path, PathBuf};
+
+const LIB_SOURCE: &str = "// symbols\n\nfn helper_function() {}\n\nimpl Engine {\n    // engine\n    fn slow_path() {}\n}\n";
+
+/// Create a project whose language server reports symbols of `lib.rs`
+fn setup(temp_dir: &Path) -> std::io::Result<(EditorTestHarness, PathBuf, PathBuf)> {
+    let lib_file = temp_dir.join("lib.rs");
+    std::fs::write(&lib_file, LIB_SOURCE)?;
+    let main_file = temp_dir.join("main.rs");
+    std::fs::write(&main_file, "fn main() {}\n")?;
+    let log_file = temp_dir.join("workspace_symbols_log.txt");
+
+    let lib_uri = url::Url::from_file_path(&lib_file).unwrap().to_string();
+    let mut config = fresh::config::Config::default();
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: FakeLspServer::workspace_symbols_script_path()
+                .to_string_lossy()
+                .to_string(),
+            args: vec![lib_uri, log_file.to_string_lossy().to_string()],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+        },
+    );
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(120, 30, config, temp_dir.to_path_buf())?;
+    harness.open_file(&main_file)?;
+    harness.render()?;
+    harness.wait_until(|h| {
+        h.editor()
+            .running_lsp_servers()
+            .contains(&"rust".to_string())
+    })?;
+
+    Ok((harness, lib_file, log_file))
+}
+
+fn open_prompt(harness: &mut EditorTestHarness) -> std::io::Result<()> {
+    harness.send_key(
+        KeyCode::Char('o'),
+        KeyModifiers::CONTROL | KeyModifiers::ALT,
+    )?;
+    harness.render()
+}
+
+/// Results of the server are listed and choosing one opens its file at the symbol
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_workspace_symbol_jumps_to_other_file() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_workspace_symbols()?;
+    let temp_dir = tempfile::tempdir()?;
+    let (mut harness, _lib_file, _log_file) = setup(temp_dir.path())?;
+
+    open_prompt(&mut harness)?;
+    harness.assert_screen_contains("Symbol in workspace:");
+
+    harness.type_text("helper")?;
+    harness.wait_until(|h| h.screen_to_string().contains("helper_function"))?;
+    harness.assert_screen_contains("lib.rs:3");
+    // Results not matching the query are left out
+    harness.assert_screen_not_contains("slow_path");
+
+    harness.send_key(KeyCode::Enter, KeyModifiers::NONE)?;
+    harness.render()?;
+
+    harness.assert_buffer_content(LIB_SOURCE);
+    assert_eq!(
+        harness.cursor_position(),
+        LIB_SOURCE.find("helper_function").unwrap()
+    );
+    Ok(())
+}
+
+/// Symbols reported without a range are resolved before jumping to them
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_workspace_symbol_resolves_location() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_workspace_symbols()?;
+    let temp_dir = tempfile::tempdir()?;
+    let (mut harness, _lib_file, log_file) = setup(temp_dir.path())?;
+
+    open_prompt(&mut harness)?;
+    harness.type_text("Engine")?;
+    // Listed without a line number, as its range is unknown
+    harness.wait_until(|h| h.screen_to_string().contains("lib.rs"))?;
+    harness.send_key(KeyCode::Enter, KeyModifiers::NONE)?;
+
+    let target = LIB_SOURCE.find("Engine").unwrap();
+    harness.wait_until(|h| h.cursor_position() == target)?;
+    harness.assert_buffer_content(LIB_SOURCE);
+    let log = std::fs::read_to_string(&log_file)?;
+    assert!(log.contains("workspaceSymbol/resolve"), "got: {}", log);
+    Ok(())
+}
+
+/// A numeric query is a query, not the index of a listed symbol
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_workspace_symbol_numeric_query_without_match() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_workspace_symbols()?;
+    let temp_dir = tempfile::tempdir()?;
+    let (mut harness, _lib_file, log_file) = setup(temp_dir.path())?;
+
+    open_prompt(&mut harness)?;
+    harness.type_text("1")?;
+    harness.wait_until(|_| {
+        std::fs::read_to_string(&log_file)
+            .unwrap_or_default()
+            .contains("workspace/symbol 1\n")
+    })?;
+    harness.send_key(KeyCode::Enter, KeyModifiers::NONE)?;
+    harness.render()?;
+
+    harness.assert_buffer_content("fn main() {}\n");
+    Ok(())
+}
+
+/// A query superseded while the server is still busy gets cancelled
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_workspace_symbol_cancels_stale_query() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_workspace_symbols()?;
+    let temp_dir = tempfile::tempdir()?;
+    let (mut harness, _lib_file, log_file) = setup(temp_dir.path())?;
+    let log = || std::fs::read_to_string(&log_file).unwrap_or_default();
+
+    open_prompt(&mut harness)?;
+    harness.type_text("slow")?;
+    harness.wait_until(|_| log().contains("workspace/symbol slow\n"))?;
+
+    // Refine the query while the server is answering the previous one
+    harness.type_text("_p")?;
+    harness.wait_until(|_| log().contains("workspace/symbol slow_p\n"))?;
+    harness.wait_until(|h| h.screen_to_string().contains("slow_path"))?;
+
+    let log = log();
+    let lines: Vec<&str> = log.lines().collect();
+    let cancel = lines.iter().position(|l| *l == "$/cancelRequest");
+    let refined = lines.iter().position(|l| *l == "workspace/symbol slow_p");
+    assert!(
+        cancel.is_some() && cancel < refined,
+        "Expected the first query to be cancelled before the refined one: {:?}",
+        lines
+    );
+    Ok(())
+}
+
+/// Without a running server the prompt doesn't open
+#[test]
+fn test_workspace_symbol_without_server() {
+    let mut harness = EditorTestHarness::new(100, 30).unwrap();
+    open_prompt(&mut harness).unwrap();
+    harness.assert_screen_not_contains("Symbol in workspace:");
+    harness.assert_screen_contains("No LSP servers are currently running");
+}